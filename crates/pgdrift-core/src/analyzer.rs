@@ -1,10 +1,149 @@
-use crate::stats::FieldStats;
+use crate::dedup::dedup_samples;
+use crate::stats::{DEFAULT_EXAMPLES, FieldStats, FieldStatsState, margin_of_error};
+use crate::types::JsonType;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
+/// Path segment that stands in for the keys of a collapsed map
+pub const WILDCARD: &str = "*";
+
+/// Configuration for the analyzer
+#[derive(Debug, Clone)]
+pub struct AnalyzerConfig {
+    /// Collapse objects used as maps (e.g. `counts_by_country.US`) into
+    /// wildcard paths (`counts_by_country.*`)
+    pub collapse_maps: bool,
+    /// Minimum distinct sibling keys before an object is considered a map (default: 20)
+    pub min_map_keys: usize,
+    /// Nesting depth below which documents are not walked (default: 64)
+    pub max_depth: usize,
+    /// Maximum number of unique paths tracked; new paths beyond it are skipped (default: 10,000)
+    pub max_paths: usize,
+    /// Approximate memory for all field statistics; once exceeded, new paths
+    /// are skipped (default: 512 MiB)
+    pub memory_budget_bytes: usize,
+    /// Example values kept per path, chosen uniformly from all occurrences (default: 10)
+    pub max_examples: usize,
+}
+
+impl Default for AnalyzerConfig {
+    fn default() -> Self {
+        Self {
+            collapse_maps: true,
+            min_map_keys: 20,
+            max_depth: 64,
+            max_paths: 10_000,
+            memory_budget_bytes: 512 * 1024 * 1024,
+            max_examples: DEFAULT_EXAMPLES,
+        }
+    }
+}
+
+/// Below this many documents, parallel analysis isn't worth the thread startup
+const PARALLEL_MIN_SAMPLES: usize = 1000;
+
+/// Documents analyzed between memory budget checks
+const MEMORY_CHECK_INTERVAL: u64 = 100;
+
+/// Skipped paths kept as examples in the truncation report
+const MAX_SKIPPED_PATH_EXAMPLES: usize = 10;
+
+/// What the analyzer left out because a guardrail was hit
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Truncation {
+    /// Subtrees not walked because they were nested deeper than `max_depth`
+    pub depth_limited: u64,
+    /// Occurrences of paths that were not tracked because of `max_paths` or the memory budget
+    pub skipped_occurrences: u64,
+    /// A few of the paths that were not tracked
+    pub skipped_paths: Vec<String>,
+    pub max_paths_reached: bool,
+    pub memory_budget_exceeded: bool,
+}
+
+impl Truncation {
+    pub fn is_truncated(&self) -> bool {
+        self.depth_limited > 0 || self.skipped_occurrences > 0
+    }
+
+    /// One-line description of what was truncated
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if self.depth_limited > 0 {
+            parts.push(format!(
+                "{} subtree(s) beyond max depth not walked",
+                self.depth_limited
+            ));
+        }
+        if self.skipped_occurrences > 0 {
+            let reason = if self.memory_budget_exceeded {
+                "memory budget exceeded"
+            } else {
+                "path limit reached"
+            };
+            parts.push(format!(
+                "{}, {} occurrence(s) of new paths skipped (e.g. {})",
+                reason,
+                self.skipped_occurrences,
+                self.skipped_paths.join(", ")
+            ));
+        }
+        parts.join("; ")
+    }
+
+    fn merge(&mut self, other: &Truncation) {
+        self.depth_limited += other.depth_limited;
+        self.skipped_occurrences += other.skipped_occurrences;
+        for path in &other.skipped_paths {
+            if self.skipped_paths.len() >= MAX_SKIPPED_PATH_EXAMPLES {
+                break;
+            }
+            if !self.skipped_paths.contains(path) {
+                self.skipped_paths.push(path.clone());
+            }
+        }
+        self.max_paths_reached |= other.max_paths_reached;
+        self.memory_budget_exceeded |= other.memory_budget_exceeded;
+    }
+
+    fn skip_path(&mut self, path: &str, times: u64) {
+        self.skipped_occurrences += times;
+        if self.skipped_paths.len() < MAX_SKIPPED_PATH_EXAMPLES
+            && !self.skipped_paths.iter().any(|p| p == path)
+        {
+            self.skipped_paths.push(path.to_string());
+        }
+    }
+}
+
+/// Raw state of an unfinalized analyzer, for checkpointing a long analysis
+///
+/// Serialize it to disk periodically and restore it with
+/// `JsonAnalyzer::from_state` to continue after a crash. The config is not
+/// part of the state.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalyzerState {
+    total_samples: u64,
+    total_weight: f64,
+    truncation: Truncation,
+    stats: Vec<FieldStatsState>,
+}
+
+impl AnalyzerState {
+    /// Number of documents analyzed when the state was taken
+    pub fn total_samples(&self) -> u64 {
+        self.total_samples
+    }
+}
 
 pub struct JsonAnalyzer {
     stats: HashMap<String, FieldStats>,
     total_samples: u64,
+    /// Sum of document weights (equals `total_samples` when unweighted)
+    total_weight: f64,
+    config: AnalyzerConfig,
+    truncation: Truncation,
 }
 
 impl Default for JsonAnalyzer {
@@ -15,20 +154,220 @@ impl Default for JsonAnalyzer {
 
 impl JsonAnalyzer {
     pub fn new() -> Self {
+        Self::with_config(AnalyzerConfig::default())
+    }
+
+    pub fn with_config(config: AnalyzerConfig) -> Self {
         Self {
             stats: HashMap::new(),
             total_samples: 0,
+            total_weight: 0.0,
+            config,
+            truncation: Truncation::default(),
+        }
+    }
+
+    /// Continue an analysis from a snapshot taken with `state`
+    pub fn from_state(state: AnalyzerState, config: AnalyzerConfig) -> Self {
+        let stats = state
+            .stats
+            .into_iter()
+            .map(|state| FieldStats::from_state(state).with_max_examples(config.max_examples))
+            .map(|stats| (stats.path.clone(), stats))
+            .collect();
+
+        Self {
+            stats,
+            total_samples: state.total_samples,
+            total_weight: state.total_weight,
+            config,
+            truncation: state.truncation,
+        }
+    }
+
+    /// Snapshot the raw statistics gathered so far
+    pub fn state(&self) -> AnalyzerState {
+        let mut stats: Vec<FieldStatsState> = self.stats.values().map(FieldStats::state).collect();
+        stats.sort_by(|a, b| a.path().cmp(b.path()));
+
+        AnalyzerState {
+            total_samples: self.total_samples,
+            total_weight: self.total_weight,
+            truncation: self.truncation.clone(),
+            stats,
         }
     }
 
     /// Analyze a sing json document
     pub fn analyze(&mut self, value: &Value) {
-        self.total_samples += 1;
-        self.walk("", value, 0);
+        self.analyze_repeated(value, 1);
+    }
+
+    /// Analyze a document that occurs `times` times among the samples
+    ///
+    /// Same result as calling `analyze` `times` times, but the document is
+    /// only walked once.
+    pub fn analyze_repeated(&mut self, value: &Value, times: u64) {
+        self.analyze_occurrences(value, times, times as f64);
+    }
+
+    /// Analyze a document that stands for `weight` documents of the table
+    ///
+    /// For samples that over- or under-represent parts of a table (stratified
+    /// or recency-biased sampling), weighting each document by the inverse of
+    /// its sampling rate keeps densities unbiased. Type counts and value
+    /// statistics still count the document once. Documents with a weight that
+    /// isn't positive and finite are ignored.
+    pub fn analyze_weighted(&mut self, value: &Value, weight: f64) {
+        if weight > 0.0 && weight.is_finite() {
+            self.analyze_occurrences(value, 1, weight);
+        }
+    }
+
+    fn analyze_occurrences(&mut self, value: &Value, times: u64, weight: f64) {
+        if times == 0 {
+            return;
+        }
+        let before = self.total_samples;
+        self.total_samples += times;
+        self.total_weight += weight;
+        self.walk("", value, 0, times, weight);
+
+        if before / MEMORY_CHECK_INTERVAL != self.total_samples / MEMORY_CHECK_INTERVAL {
+            self.check_memory();
+        }
+    }
+
+    /// Count documents whose paths are added with `record_type_count`
+    pub fn add_documents(&mut self, count: u64) {
+        self.total_samples += count;
+        self.total_weight += count as f64;
+    }
+
+    /// Add occurrences of a path aggregated elsewhere, e.g. inside Postgres
+    ///
+    /// Only presence and types are known this way: cardinality, numeric,
+    /// format and example statistics stay empty for the path. Path limits
+    /// apply as for analyzed documents.
+    pub fn record_type_count(&mut self, path: &str, depth: usize, json_type: JsonType, count: u64) {
+        if let Some(stats) = self.stats.get_mut(path) {
+            stats.record_type(json_type, count);
+            return;
+        }
+
+        if self.stats.len() >= self.config.max_paths {
+            self.truncation.max_paths_reached = true;
+            self.truncation.skip_path(path, count);
+            return;
+        }
+
+        let mut stats =
+            FieldStats::new(path.to_string(), depth).with_max_examples(self.config.max_examples);
+        stats.record_type(json_type, count);
+        self.stats.insert(path.to_string(), stats);
+    }
+
+    /// Analyze many documents, sharding them across `workers` threads
+    ///
+    /// Identical documents are analyzed once with their repeat count. Each
+    /// worker fills its own analyzer (with an equal share of the memory
+    /// budget) and the results are merged in order, so field stats match a
+    /// sequential run apart from approximate percentiles and which examples
+    /// are picked.
+    pub fn analyze_parallel(&mut self, samples: &[Value], workers: usize) {
+        let unique = dedup_samples(samples);
+        if workers <= 1 || unique.len() < PARALLEL_MIN_SAMPLES {
+            for (sample, times) in unique {
+                self.analyze_repeated(sample, times);
+            }
+            return;
+        }
+
+        let worker_config = AnalyzerConfig {
+            memory_budget_bytes: self.config.memory_budget_bytes / workers,
+            ..self.config.clone()
+        };
+        let chunk_size = unique.len().div_ceil(workers);
+
+        let partials: Vec<JsonAnalyzer> = std::thread::scope(|scope| {
+            let handles: Vec<_> = unique
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    let config = worker_config.clone();
+                    scope.spawn(move || {
+                        let mut analyzer = JsonAnalyzer::with_config(config);
+                        for &(sample, times) in chunk {
+                            analyzer.analyze_repeated(sample, times);
+                        }
+                        analyzer
+                    })
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .map(|h| h.join().expect("analyzer worker panicked"))
+                .collect()
+        });
+
+        for partial in partials {
+            self.merge(partial);
+        }
+    }
+
+    /// Count every document analyzed so far as `factor` documents of the table
+    ///
+    /// Samples drawn at different rates, e.g. 5k of 1M older rows and 5k of
+    /// 20k newer ones, must be scaled by the rows each sample stands for
+    /// before they are merged, or the densities lean towards the more densely
+    /// sampled rows. Only densities change. Factors that aren't positive and
+    /// finite are ignored.
+    pub fn reweight(&mut self, factor: f64) {
+        if factor > 0.0 && factor.is_finite() {
+            self.total_weight *= factor;
+            for stats in self.stats.values_mut() {
+                stats.scale_weight(factor);
+            }
+        }
+    }
+
+    /// Fold another analyzer's raw statistics into this one
+    ///
+    /// Both analyzers must not be finalized yet. The result is the same as if
+    /// this analyzer had seen all documents of both, which lets batches,
+    /// partitions or separate machines be analyzed independently. This
+    /// analyzer's config (limits, map collapsing) applies to the merged result.
+    pub fn merge(&mut self, other: JsonAnalyzer) {
+        self.total_samples += other.total_samples;
+        self.total_weight += other.total_weight;
+        self.truncation.merge(&other.truncation);
+
+        for (path, stats) in other.stats {
+            if let Some(existing) = self.stats.get_mut(&path) {
+                existing.merge(&stats);
+            } else if self.stats.len() >= self.config.max_paths {
+                self.truncation.max_paths_reached = true;
+                self.truncation.skip_path(&path, 1);
+            } else {
+                self.stats.insert(path, stats);
+            }
+        }
     }
 
     /// Recursive walk
-    fn walk(&mut self, path: &str, value: &Value, depth: usize) {
+    fn walk(&mut self, path: &str, value: &Value, depth: usize, times: u64, weight: f64) {
+        if depth >= self.config.max_depth {
+            let has_children = match value {
+                Value::Object(map) => !map.is_empty(),
+                Value::Array(arr) => !arr.is_empty(),
+                _ => false,
+            };
+            if has_children {
+                self.truncation.depth_limited += times;
+            }
+            return;
+        }
+
         match value {
             Value::Object(map) => {
                 for (key, val) in map {
@@ -38,16 +377,16 @@ impl JsonAnalyzer {
                         format!("{}.{}", path, key)
                     };
 
-                    self.record_field(&field_path, val, depth + 1);
+                    self.record_field(&field_path, val, depth + 1, times, weight);
 
-                    self.walk(&field_path, val, depth + 1);
+                    self.walk(&field_path, val, depth + 1, times, weight);
                 }
             }
             Value::Array(arr) => {
                 let array_path = format!("{}[]", path);
 
                 for item in arr {
-                    self.walk(&array_path, item, depth + 1);
+                    self.walk(&array_path, item, depth + 1, times, weight);
                 }
             }
             _ => {
@@ -56,19 +395,243 @@ impl JsonAnalyzer {
         }
     }
 
-    fn record_field(&mut self, path: &str, value: &Value, depth: usize) {
+    fn record_field(&mut self, path: &str, value: &Value, depth: usize, times: u64, weight: f64) {
+        if let Some(stats) = self.stats.get_mut(path) {
+            stats.record_weighted(value, times, weight);
+            return;
+        }
+
+        if self.stats.len() >= self.config.max_paths {
+            self.truncation.max_paths_reached = true;
+            self.truncation.skip_path(path, times);
+            return;
+        }
+        if self.truncation.memory_budget_exceeded {
+            self.truncation.skip_path(path, times);
+            return;
+        }
+
+        let mut stats =
+            FieldStats::new(path.to_string(), depth).with_max_examples(self.config.max_examples);
+        stats.record_weighted(value, times, weight);
+        self.stats.insert(path.to_string(), stats);
+    }
+
+    /// Stop tracking new paths once the statistics outgrow the memory budget
+    fn check_memory(&mut self) {
+        if !self.truncation.memory_budget_exceeded
+            && self.approx_bytes() > self.config.memory_budget_bytes
+        {
+            self.truncation.memory_budget_exceeded = true;
+        }
+    }
+
+    /// Rough memory held by all field statistics
+    pub fn approx_bytes(&self) -> usize {
+        self.stats.values().map(FieldStats::approx_bytes).sum()
+    }
+
+    /// What was left out because a guardrail was hit
+    pub fn truncation(&self) -> &Truncation {
+        &self.truncation
+    }
+
+    /// Number of documents analyzed so far
+    pub fn total_samples(&self) -> u64 {
+        self.total_samples
+    }
+
+    /// Largest margin of error across the density estimates of all observed paths
+    ///
+    /// Used by adaptive sampling to decide when enough documents have been seen.
+    pub fn max_margin_of_error(&self, confidence: f64) -> f64 {
+        if self.total_samples == 0 {
+            return f64::INFINITY;
+        }
+
         self.stats
-            .entry(path.to_string())
-            .or_insert_with(|| FieldStats::new(path.to_string(), depth))
-            .record(value);
+            .values()
+            .map(|s| margin_of_error(s.occurrences, self.total_samples, confidence))
+            .fold(0.0, f64::max)
     }
 
     pub fn finalize(mut self) -> HashMap<String, FieldStats> {
-        for stats in self.stats.values_mut() {
-            stats.finalize(self.total_samples);
+        if self.config.collapse_maps {
+            self.collapse_maps();
+        }
+
+        // Paths under a wildcard are measured against the number of map
+        // entries rather than the number of documents
+        let wildcard_entries: HashMap<String, (u64, f64)> = self
+            .stats
+            .iter()
+            .filter(|(path, _)| path.as_str() == WILDCARD || path.ends_with(".*"))
+            .map(|(path, stats)| {
+                let entries = (stats.occurrences, stats.weighted_occurrences());
+                (path.clone(), entries)
+            })
+            .collect();
+
+        for (path, stats) in self.stats.iter_mut() {
+            let (total, total_weight) = innermost_wildcard(path, &wildcard_entries)
+                .unwrap_or((self.total_samples, self.total_weight));
+            stats.finalize_weighted(total, total_weight);
         }
         self.stats
     }
+
+    /// Replace the keys of map-like objects with a wildcard, shallowest maps first
+    fn collapse_maps(&mut self) {
+        let max_depth = self.stats.values().map(|s| s.depth).max().unwrap_or(0);
+
+        for depth in 1..=max_depth {
+            let mut siblings: HashMap<String, Vec<String>> = HashMap::new();
+            for (path, stats) in &self.stats {
+                let (parent, key) = split_parent(path);
+                if stats.depth == depth && key != WILDCARD {
+                    siblings
+                        .entry(parent.to_string())
+                        .or_default()
+                        .push(key.to_string());
+                }
+            }
+
+            for (parent, keys) in siblings {
+                if self.is_map(&parent, &keys) {
+                    self.collapse(&parent, &keys);
+                }
+            }
+        }
+    }
+
+    /// An object is a map when it has many distinct keys, each instance only
+    /// holds a few of them, and all values share one type
+    fn is_map(&self, parent: &str, keys: &[String]) -> bool {
+        if keys.len() < self.config.min_map_keys {
+            return false;
+        }
+
+        let children: Vec<&FieldStats> = keys
+            .iter()
+            .map(|key| &self.stats[&join_path(parent, key)])
+            .collect();
+
+        let value_types: HashSet<JsonType> = children
+            .iter()
+            .flat_map(|c| c.types.keys().copied())
+            .filter(|t| *t != JsonType::Null)
+            .collect();
+        if value_types.len() != 1 {
+            return false;
+        }
+
+        // Each instance holds at most one of each key, so the busiest key is a
+        // lower bound on instances when the parent itself isn't tracked (array items)
+        let parent_instances = if parent.is_empty() {
+            self.total_samples
+        } else {
+            self.stats
+                .get(parent)
+                .and_then(|p| p.types.get(&JsonType::Object).copied())
+                .unwrap_or(0)
+        };
+        let instances = children
+            .iter()
+            .map(|c| c.occurrences)
+            .max()
+            .unwrap_or(0)
+            .max(parent_instances);
+        let entries: u64 = children.iter().map(|c| c.occurrences).sum();
+
+        entries as f64 / instances as f64 <= keys.len() as f64 / 2.0
+    }
+
+    /// Merge `parent.<key>` and everything below it into `parent.*`
+    fn collapse(&mut self, parent: &str, keys: &[String]) {
+        let keys: HashSet<&str> = keys.iter().map(String::as_str).collect();
+        let prefix_len = if parent.is_empty() {
+            0
+        } else {
+            parent.len() + 1
+        };
+        let wildcard = join_path(parent, WILDCARD);
+
+        let moved: Vec<String> = self
+            .stats
+            .keys()
+            .filter(|path| {
+                path.len() > prefix_len
+                    && (parent.is_empty()
+                        || (path.starts_with(parent) && path[parent.len()..].starts_with('.')))
+                    && keys.contains(first_segment(&path[prefix_len..]))
+            })
+            .cloned()
+            .collect();
+
+        for path in moved {
+            let mut stats = self.stats.remove(&path).unwrap();
+            let rest = &path[prefix_len + first_segment(&path[prefix_len..]).len()..];
+            let new_path = format!("{}{}", wildcard, rest);
+
+            match self.stats.get_mut(&new_path) {
+                Some(existing) => existing.merge(&stats),
+                None => {
+                    stats.path = new_path.clone();
+                    self.stats.insert(new_path, stats);
+                }
+            }
+        }
+
+        if let Some(stats) = self.stats.get_mut(&wildcard) {
+            stats.collapsed_keys = Some(keys.len() as u64);
+        }
+    }
+}
+
+/// Whether a path passes through a collapsed map
+pub fn is_wildcard_path(path: &str) -> bool {
+    path == WILDCARD
+        || path.starts_with("*.")
+        || path.starts_with("*[")
+        || path.ends_with(".*")
+        || path.contains(".*.")
+        || path.contains(".*[")
+}
+
+fn join_path(parent: &str, key: &str) -> String {
+    if parent.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", parent, key)
+    }
+}
+
+/// Split `a.b.c` into (`a.b`, `c`); top-level keys have an empty parent
+fn split_parent(path: &str) -> (&str, &str) {
+    path.rsplit_once('.').unwrap_or(("", path))
+}
+
+/// Leading key of a relative path, up to the next `.` or `[]`
+fn first_segment(rest: &str) -> &str {
+    let end = rest.find(['.', '[']).unwrap_or(rest.len());
+    &rest[..end]
+}
+
+/// Entry count of the deepest wildcard path enclosing `path` (or `path` itself)
+fn innermost_wildcard(
+    path: &str,
+    wildcard_entries: &HashMap<String, (u64, f64)>,
+) -> Option<(u64, f64)> {
+    path.match_indices(WILDCARD)
+        .filter_map(|(i, _)| {
+            let next = path[i + 1..].chars().next();
+            if matches!(next, None | Some('.') | Some('[')) {
+                wildcard_entries.get(&path[..=i]).copied()
+            } else {
+                None
+            }
+        })
+        .last()
 }
 
 #[cfg(test)]
@@ -233,6 +796,36 @@ mod tests {
         assert_eq!(stats["level1.level2.level3.level4.value"].depth, 5);
     }
 
+    #[test]
+    fn test_max_margin_of_error_converges() {
+        let mut analyzer = JsonAnalyzer::new();
+        assert!(analyzer.max_margin_of_error(0.95).is_infinite());
+
+        for i in 0..100 {
+            let doc = if i % 2 == 0 {
+                json!({"id": i, "half": 1})
+            } else {
+                json!({"id": i})
+            };
+            analyzer.analyze(&doc);
+        }
+        let after_100 = analyzer.max_margin_of_error(0.95);
+
+        for i in 0..9900 {
+            let doc = if i % 2 == 0 {
+                json!({"id": i, "half": 1})
+            } else {
+                json!({"id": i})
+            };
+            analyzer.analyze(&doc);
+        }
+        let after_10000 = analyzer.max_margin_of_error(0.95);
+
+        assert_eq!(analyzer.total_samples(), 10000);
+        assert!(after_100 > 0.05);
+        assert!(after_10000 < 0.01);
+    }
+
     #[test]
     fn test_examples_collection() {
         let mut analyzer = JsonAnalyzer::new();
@@ -247,4 +840,457 @@ mod tests {
         // Should limit to 10 examples
         assert_eq!(value_stats.examples.len(), 10);
     }
+
+    #[test]
+    fn test_max_examples_config() {
+        let mut analyzer = JsonAnalyzer::with_config(AnalyzerConfig {
+            max_examples: 25,
+            ..Default::default()
+        });
+        for i in 0..100 {
+            analyzer.analyze(&json!({"value": i}));
+        }
+
+        let resumed = JsonAnalyzer::from_state(
+            analyzer.state(),
+            AnalyzerConfig {
+                max_examples: 5,
+                ..Default::default()
+            },
+        );
+        assert_eq!(analyzer.finalize()["value"].examples.len(), 25);
+        assert_eq!(resumed.finalize()["value"].examples.len(), 5);
+    }
+
+    fn country_codes() -> Vec<String> {
+        (b'A'..=b'Z').map(|c| format!("X{}", c as char)).collect()
+    }
+
+    #[test]
+    fn test_collapse_map_keys() {
+        let mut analyzer = JsonAnalyzer::new();
+        let codes = country_codes();
+
+        for i in 0..100 {
+            // Each document holds three of the 26 keys
+            let mut counts = serde_json::Map::new();
+            for j in 0..3 {
+                counts.insert(codes[(i + j * 7) % codes.len()].clone(), json!({"n": i}));
+            }
+            analyzer.analyze(&json!({"id": i, "counts_by_country": counts}));
+        }
+
+        let stats = analyzer.finalize();
+        let mut paths: Vec<&str> = stats.keys().map(String::as_str).collect();
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![
+                "counts_by_country",
+                "counts_by_country.*",
+                "counts_by_country.*.n",
+                "id"
+            ]
+        );
+
+        let wildcard = &stats["counts_by_country.*"];
+        assert_eq!(wildcard.occurrences, 300);
+        assert_eq!(wildcard.density, 1.0);
+        assert_eq!(wildcard.collapsed_keys, Some(26));
+        assert_eq!(stats["counts_by_country.*.n"].density, 1.0);
+        assert!(is_wildcard_path("counts_by_country.*.n"));
+        assert!(!is_wildcard_path("counts_by_country"));
+    }
+
+    #[test]
+    fn test_fixed_wide_object_is_not_collapsed() {
+        let mut analyzer = JsonAnalyzer::new();
+        let codes = country_codes();
+
+        for i in 0..50 {
+            // Every document holds all 26 keys - a wide record, not a map
+            let metrics: serde_json::Map<String, Value> =
+                codes.iter().map(|c| (c.clone(), json!(i))).collect();
+            analyzer.analyze(&json!({ "metrics": metrics }));
+        }
+
+        let stats = analyzer.finalize();
+        assert!(stats.contains_key("metrics.XA"));
+        assert!(!stats.contains_key("metrics.*"));
+    }
+
+    #[test]
+    fn test_mixed_value_types_are_not_collapsed() {
+        let mut analyzer = JsonAnalyzer::new();
+        for (i, code) in country_codes().iter().enumerate() {
+            let value = if i % 2 == 0 { json!(1) } else { json!("one") };
+            analyzer.analyze(&json!({ "attrs": { code.clone(): value } }));
+        }
+
+        assert!(!analyzer.finalize().contains_key("attrs.*"));
+    }
+
+    #[test]
+    fn test_collapse_disabled() {
+        let mut analyzer = JsonAnalyzer::with_config(AnalyzerConfig {
+            collapse_maps: false,
+            ..Default::default()
+        });
+        for code in country_codes() {
+            analyzer.analyze(&json!({ code: 1 }));
+        }
+
+        let stats = analyzer.finalize();
+        assert_eq!(stats.len(), 26);
+        assert_eq!(stats["XA"].density, 1.0 / 26.0);
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let mut analyzer = JsonAnalyzer::with_config(AnalyzerConfig {
+            max_depth: 2,
+            ..Default::default()
+        });
+        analyzer.analyze(&json!({"a": {"b": {"c": {"d": 1}}}, "x": 1}));
+
+        assert_eq!(analyzer.truncation().depth_limited, 1);
+        assert!(analyzer.truncation().is_truncated());
+
+        let stats = analyzer.finalize();
+        assert!(stats.contains_key("a.b"));
+        assert!(!stats.contains_key("a.b.c"));
+        assert!(stats.contains_key("x"));
+    }
+
+    #[test]
+    fn test_max_paths() {
+        let mut analyzer = JsonAnalyzer::with_config(AnalyzerConfig {
+            max_paths: 2,
+            collapse_maps: false,
+            ..Default::default()
+        });
+        analyzer.analyze(&json!({"a": 1, "b": 2}));
+        analyzer.analyze(&json!({"a": 1, "c": 3}));
+        analyzer.analyze(&json!({"c": 3, "d": 4}));
+
+        let truncation = analyzer.truncation().clone();
+        assert!(truncation.max_paths_reached);
+        assert_eq!(truncation.skipped_occurrences, 3);
+        assert_eq!(truncation.skipped_paths, vec!["c", "d"]);
+        assert!(truncation.summary().contains("path limit reached"));
+
+        let stats = analyzer.finalize();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats["a"].occurrences, 2);
+    }
+
+    #[test]
+    fn test_memory_budget() {
+        let mut analyzer = JsonAnalyzer::with_config(AnalyzerConfig {
+            memory_budget_bytes: 1,
+            ..Default::default()
+        });
+        for _ in 0..MEMORY_CHECK_INTERVAL {
+            analyzer.analyze(&json!({"a": 1}));
+        }
+        analyzer.analyze(&json!({"a": 1, "b": 2}));
+
+        let truncation = analyzer.truncation();
+        assert!(truncation.memory_budget_exceeded);
+        assert_eq!(truncation.skipped_paths, vec!["b"]);
+        assert!(truncation.summary().contains("memory budget exceeded"));
+        assert_eq!(analyzer.finalize()["a"].occurrences, 101);
+    }
+
+    #[test]
+    fn test_no_truncation_by_default() {
+        let mut analyzer = JsonAnalyzer::new();
+        analyzer.analyze(&json!({"a": {"b": [{"c": 1}]}}));
+        assert!(!analyzer.truncation().is_truncated());
+        assert_eq!(analyzer.truncation().summary(), "");
+    }
+
+    fn mixed_documents(n: usize) -> Vec<Value> {
+        (0..n)
+            .map(|i| {
+                if i % 4 == 0 {
+                    json!({"id": i, "status": "active", "tags": ["a", "b"], "legacy": true})
+                } else {
+                    json!({"id": i.to_string(), "status": "disabled", "tags": []})
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parallel_matches_sequential() {
+        let samples = mixed_documents(5000);
+
+        let mut sequential = JsonAnalyzer::new();
+        for sample in &samples {
+            sequential.analyze(sample);
+        }
+        let mut parallel = JsonAnalyzer::new();
+        parallel.analyze_parallel(&samples, 4);
+
+        assert_eq!(parallel.total_samples(), 5000);
+        let sequential = sequential.finalize();
+        let parallel = parallel.finalize();
+
+        assert_eq!(sequential.len(), parallel.len());
+        for (path, seq) in &sequential {
+            let par = &parallel[path];
+            assert_eq!(seq.occurrences, par.occurrences, "{}", path);
+            assert_eq!(seq.density, par.density, "{}", path);
+            assert_eq!(seq.types, par.types, "{}", path);
+            assert_eq!(seq.cardinality, par.cardinality, "{}", path);
+            assert_eq!(seq.examples.len(), par.examples.len(), "{}", path);
+            assert_eq!(seq.top_values, par.top_values, "{}", path);
+            assert_eq!(seq.array, par.array, "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_merge_analyzers() {
+        let mut january = JsonAnalyzer::new();
+        january.analyze(&json!({"id": 1, "plan": "free"}));
+        january.analyze(&json!({"id": 2, "plan": "pro"}));
+
+        let mut february = JsonAnalyzer::new();
+        february.analyze(&json!({"id": 3, "plan": "pro", "coupon": "SPRING"}));
+        february.analyze(&json!({"id": "4", "plan": null}));
+
+        january.merge(february);
+        assert_eq!(january.total_samples(), 4);
+
+        let stats = january.finalize();
+        assert_eq!(stats["id"].density, 1.0);
+        assert_eq!(stats["id"].types[&JsonType::Number], 3);
+        assert_eq!(stats["id"].types[&JsonType::String], 1);
+        assert_eq!(stats["coupon"].density, 0.25);
+        assert_eq!(stats["plan"].null_count, 1);
+        assert_eq!(stats["plan"].cardinality, 2);
+    }
+
+    #[test]
+    fn test_merge_respects_max_paths() {
+        let mut a = JsonAnalyzer::with_config(AnalyzerConfig {
+            max_paths: 1,
+            ..Default::default()
+        });
+        a.analyze(&json!({"a": 1}));
+
+        let mut b = JsonAnalyzer::new();
+        b.analyze(&json!({"b": 1}));
+
+        a.merge(b);
+        assert!(a.truncation().max_paths_reached);
+        assert_eq!(a.truncation().skipped_paths, vec!["b"]);
+    }
+
+    #[test]
+    fn test_parallel_small_input_runs_sequentially() {
+        let mut analyzer = JsonAnalyzer::new();
+        analyzer.analyze_parallel(&mixed_documents(10), 8);
+        assert_eq!(analyzer.total_samples(), 10);
+        assert_eq!(analyzer.finalize()["legacy"].occurrences, 3);
+    }
+
+    #[test]
+    fn test_duplicate_documents_match_sequential() {
+        let samples: Vec<Value> = (0..3000)
+            .map(|i| match i % 3 {
+                0 => json!({"plan": "free", "seats": 1, "tags": []}),
+                1 => json!({"plan": "pro", "seats": 5, "tags": ["beta"]}),
+                _ => json!({"plan": null, "seats": 1.5}),
+            })
+            .collect();
+
+        let mut sequential = JsonAnalyzer::new();
+        for sample in &samples {
+            sequential.analyze(sample);
+        }
+        let mut deduplicated = JsonAnalyzer::new();
+        deduplicated.analyze_parallel(&samples, 1);
+
+        assert_eq!(deduplicated.total_samples(), 3000);
+        let sequential = sequential.finalize();
+        let deduplicated = deduplicated.finalize();
+
+        assert_eq!(sequential.len(), deduplicated.len());
+        for (path, seq) in &sequential {
+            let dedup = &deduplicated[path];
+            assert_eq!(seq.occurrences, dedup.occurrences, "{}", path);
+            assert_eq!(seq.null_count, dedup.null_count, "{}", path);
+            assert_eq!(seq.types, dedup.types, "{}", path);
+            assert_eq!(seq.cardinality, dedup.cardinality, "{}", path);
+            assert_eq!(seq.top_values, dedup.top_values, "{}", path);
+            assert_eq!(seq.array, dedup.array, "{}", path);
+            assert_eq!(
+                seq.numeric.as_ref().map(|n| n.mean),
+                dedup.numeric.as_ref().map(|n| n.mean),
+                "{}",
+                path
+            );
+        }
+    }
+
+    #[test]
+    fn test_type_counts_match_analyzed_documents() {
+        let documents = [
+            json!({"id": 1, "tags": [{"name": "a"}, {"name": null}]}),
+            json!({"id": "2", "tags": []}),
+            json!({"id": 3}),
+        ];
+        let mut analyzed = JsonAnalyzer::new();
+        for document in &documents {
+            analyzed.analyze(document);
+        }
+
+        // What the database reports for the same documents
+        let mut counted = JsonAnalyzer::new();
+        counted.add_documents(3);
+        counted.record_type_count("id", 1, JsonType::Number, 2);
+        counted.record_type_count("id", 1, JsonType::String, 1);
+        counted.record_type_count("tags", 1, JsonType::Array, 2);
+        counted.record_type_count("tags[].name", 3, JsonType::String, 1);
+        counted.record_type_count("tags[].name", 3, JsonType::Null, 1);
+
+        let analyzed = analyzed.finalize();
+        let counted = counted.finalize();
+        assert_eq!(analyzed.len(), counted.len());
+        for (path, expected) in &analyzed {
+            let actual = &counted[path];
+            assert_eq!(expected.occurrences, actual.occurrences, "{}", path);
+            assert_eq!(expected.density, actual.density, "{}", path);
+            assert_eq!(expected.types, actual.types, "{}", path);
+            assert_eq!(expected.null_count, actual.null_count, "{}", path);
+            assert_eq!(expected.depth, actual.depth, "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_weighted_density() {
+        // Recent rows were oversampled 4x; each stands for a quarter of a row
+        let mut analyzer = JsonAnalyzer::new();
+        for _ in 0..80 {
+            analyzer.analyze_weighted(&json!({"id": 1, "coupon": "NEW"}), 0.25);
+        }
+        for _ in 0..20 {
+            analyzer.analyze_weighted(&json!({"id": 1}), 1.0);
+        }
+        analyzer.analyze_weighted(&json!({"id": 1, "coupon": "NEW"}), f64::NAN);
+
+        assert_eq!(analyzer.total_samples(), 100);
+        let stats = analyzer.finalize();
+        assert_eq!(stats["coupon"].occurrences, 80);
+        assert_eq!(stats["coupon"].density, 0.5);
+        assert_eq!(stats["id"].density, 1.0);
+        assert_eq!(stats["coupon"].types[&JsonType::String], 80);
+    }
+
+    #[test]
+    fn test_reweight_before_merge() {
+        // 10 of 1000 older rows, half with a coupon, and all 10 newer rows
+        let mut older = JsonAnalyzer::new();
+        for i in 0..10 {
+            let document = if i % 2 == 0 {
+                json!({"id": i, "coupon": "NEW"})
+            } else {
+                json!({"id": i})
+            };
+            older.analyze(&document);
+        }
+        older.reweight(100.0);
+        let mut newer = JsonAnalyzer::new();
+        for i in 0..10 {
+            newer.analyze(&json!({"id": i}));
+        }
+        newer.reweight(f64::INFINITY);
+        older.merge(newer);
+
+        assert_eq!(older.total_samples(), 20);
+        let stats = older.finalize();
+        assert_eq!(stats["coupon"].occurrences, 5);
+        assert_eq!(stats["coupon"].density, 500.0 / 1010.0);
+        assert_eq!(stats["id"].density, 1.0);
+    }
+
+    #[test]
+    fn test_unit_weights_match_unweighted() {
+        let mut weighted = JsonAnalyzer::new();
+        let mut unweighted = JsonAnalyzer::new();
+        for sample in mixed_documents(200) {
+            weighted.analyze_weighted(&sample, 1.0);
+            unweighted.analyze(&sample);
+        }
+
+        let weighted = weighted.finalize();
+        for (path, stats) in unweighted.finalize() {
+            assert_eq!(stats.density, weighted[&path].density, "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_resume_from_state() {
+        let samples = mixed_documents(400);
+
+        let mut uninterrupted = JsonAnalyzer::new();
+        for sample in &samples {
+            uninterrupted.analyze(sample);
+        }
+
+        let mut first = JsonAnalyzer::new();
+        for sample in &samples[..150] {
+            first.analyze(sample);
+        }
+        let saved = serde_json::to_string(&first.state()).unwrap();
+        let state: AnalyzerState = serde_json::from_str(&saved).unwrap();
+        assert_eq!(state.total_samples(), 150);
+
+        let mut resumed = JsonAnalyzer::from_state(state, AnalyzerConfig::default());
+        for sample in &samples[150..] {
+            resumed.analyze(sample);
+        }
+
+        assert_eq!(resumed.total_samples(), 400);
+        let expected = uninterrupted.finalize();
+        let actual = resumed.finalize();
+        assert_eq!(expected.len(), actual.len());
+        for (path, stats) in &expected {
+            let restored = &actual[path];
+            assert_eq!(stats.occurrences, restored.occurrences, "{}", path);
+            assert_eq!(stats.density, restored.density, "{}", path);
+            assert_eq!(stats.types, restored.types, "{}", path);
+            assert_eq!(stats.cardinality, restored.cardinality, "{}", path);
+            assert_eq!(stats.examples, restored.examples, "{}", path);
+            assert_eq!(stats.top_values, restored.top_values, "{}", path);
+            assert_eq!(stats.numeric, restored.numeric, "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_analyze_repeated_counts_truncation() {
+        let mut analyzer = JsonAnalyzer::with_config(AnalyzerConfig {
+            max_paths: 1,
+            ..Default::default()
+        });
+        analyzer.analyze_repeated(&json!({"a": 1, "b": 2}), 50);
+
+        assert_eq!(analyzer.total_samples(), 50);
+        assert_eq!(analyzer.truncation().skipped_occurrences, 50);
+        assert_eq!(analyzer.finalize()["a"].density, 1.0);
+    }
+
+    #[test]
+    fn test_collapse_top_level_map() {
+        let mut analyzer = JsonAnalyzer::new();
+        for code in country_codes() {
+            analyzer.analyze(&json!({ code: 1 }));
+        }
+
+        let stats = analyzer.finalize();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats["*"].occurrences, 26);
+    }
 }
@@ -0,0 +1,261 @@
+use crate::stats::FieldStats;
+use crate::types::JsonType;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// Configuration for comparing older and newer documents
+#[derive(Debug, Clone)]
+pub struct CohortConfig {
+    /// Minimum two-proportion z-score for a difference to count (default: 3.0)
+    pub min_z_score: f64,
+    /// Minimum absolute difference between the two proportions (default: 0.05)
+    pub min_difference: f64,
+}
+
+impl Default for CohortConfig {
+    fn default() -> Self {
+        Self {
+            min_z_score: 3.0,
+            min_difference: 0.05,
+        }
+    }
+}
+
+/// A path whose shape differs between older and newer documents
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum CohortShift {
+    /// The path is present in a different share of documents
+    Density {
+        path: String,
+        /// Percentage of older documents containing the path
+        old_percentage: f64,
+        /// Percentage of newer documents containing the path
+        new_percentage: f64,
+    },
+    /// One JSON type makes up a different share of the path's values
+    TypeShare {
+        path: String,
+        json_type: JsonType,
+        old_percentage: f64,
+        new_percentage: f64,
+    },
+}
+
+impl CohortShift {
+    pub fn path(&self) -> &str {
+        match self {
+            CohortShift::Density { path, .. } => path,
+            CohortShift::TypeShare { path, .. } => path,
+        }
+    }
+
+    /// Absolute change in percentage points
+    pub fn change(&self) -> f64 {
+        match self {
+            CohortShift::Density {
+                old_percentage,
+                new_percentage,
+                ..
+            }
+            | CohortShift::TypeShare {
+                old_percentage,
+                new_percentage,
+                ..
+            } => (new_percentage - old_percentage).abs(),
+        }
+    }
+
+    pub fn description(&self) -> String {
+        match self {
+            CohortShift::Density {
+                old_percentage,
+                new_percentage,
+                ..
+            } => {
+                if *old_percentage == 0.0 {
+                    format!("New field: in {:.1}% of new documents", new_percentage)
+                } else if *new_percentage == 0.0 {
+                    format!(
+                        "Field dropped: in {:.1}% of old documents, none of the new",
+                        old_percentage
+                    )
+                } else {
+                    format!(
+                        "Density changed from {:.1}% to {:.1}%",
+                        old_percentage, new_percentage
+                    )
+                }
+            }
+            CohortShift::TypeShare {
+                json_type,
+                old_percentage,
+                new_percentage,
+                ..
+            } => format!(
+                "Share of {} values changed from {:.1}% to {:.1}%",
+                json_type, old_percentage, new_percentage
+            ),
+        }
+    }
+}
+
+/// Find paths whose density or type mix differs significantly between cohorts
+///
+/// `old` and `new` are the finalized field stats of documents on either side
+/// of a split (e.g. rows created more or less than 30 days ago); a difference
+/// in what the newer rows look like is drift that is happening now. Each
+/// proportion is compared with a two-proportion z-test, and only differences
+/// that are both significant and large enough are reported, biggest first.
+pub fn compare_cohorts(
+    old: &HashMap<String, FieldStats>,
+    new: &HashMap<String, FieldStats>,
+    old_samples: u64,
+    new_samples: u64,
+    config: &CohortConfig,
+) -> Vec<CohortShift> {
+    let paths: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    let mut shifts = Vec::new();
+
+    for path in paths {
+        let old_stats = old.get(path);
+        let new_stats = new.get(path);
+
+        let old_density = old_stats.map_or(0.0, |s| s.density);
+        let new_density = new_stats.map_or(0.0, |s| s.density);
+        if is_significant(old_density, old_samples, new_density, new_samples, config) {
+            shifts.push(CohortShift::Density {
+                path: path.clone(),
+                old_percentage: old_density * 100.0,
+                new_percentage: new_density * 100.0,
+            });
+            continue;
+        }
+
+        // A type mix only means something where both cohorts have values
+        let (Some(old_stats), Some(new_stats)) = (old_stats, new_stats) else {
+            continue;
+        };
+        let old_total: u64 = old_stats.types.values().sum();
+        let new_total: u64 = new_stats.types.values().sum();
+        if let Some(shift) = type_shift(path, old_stats, old_total, new_stats, new_total, config) {
+            shifts.push(shift);
+        }
+    }
+
+    shifts.sort_by(|a, b| {
+        b.change()
+            .total_cmp(&a.change())
+            .then_with(|| a.path().cmp(b.path()))
+    });
+    shifts
+}
+
+/// The type whose share moved the most, if that move is significant
+fn type_shift(
+    path: &str,
+    old_stats: &FieldStats,
+    old_total: u64,
+    new_stats: &FieldStats,
+    new_total: u64,
+    config: &CohortConfig,
+) -> Option<CohortShift> {
+    let types: HashSet<&JsonType> = old_stats
+        .types
+        .keys()
+        .chain(new_stats.types.keys())
+        .collect();
+
+    types
+        .into_iter()
+        .map(|json_type| {
+            let share = |stats: &FieldStats, total: u64| {
+                stats.types.get(json_type).copied().unwrap_or(0) as f64 / total.max(1) as f64
+            };
+            (
+                *json_type,
+                share(old_stats, old_total),
+                share(new_stats, new_total),
+            )
+        })
+        .filter(|&(_, old, new)| is_significant(old, old_total, new, new_total, config))
+        .max_by(|a, b| {
+            (a.2 - a.1)
+                .abs()
+                .total_cmp(&(b.2 - b.1).abs())
+                .then_with(|| b.0.to_string().cmp(&a.0.to_string()))
+        })
+        .map(|(json_type, old, new)| CohortShift::TypeShare {
+            path: path.to_string(),
+            json_type,
+            old_percentage: old * 100.0,
+            new_percentage: new * 100.0,
+        })
+}
+
+/// Two-proportion z-test of `p1` out of `n1` against `p2` out of `n2`
+fn is_significant(p1: f64, n1: u64, p2: f64, n2: u64, config: &CohortConfig) -> bool {
+    if n1 == 0 || n2 == 0 || (p1 - p2).abs() < config.min_difference {
+        return false;
+    }
+
+    let (n1, n2) = (n1 as f64, n2 as f64);
+    let pooled = (p1 * n1 + p2 * n2) / (n1 + n2);
+    let standard_error = (pooled * (1.0 - pooled) * (1.0 / n1 + 1.0 / n2)).sqrt();
+    standard_error > 0.0 && (p1 - p2).abs() / standard_error >= config.min_z_score
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::JsonAnalyzer;
+    use serde_json::json;
+
+    fn analyze(documents: &[serde_json::Value]) -> HashMap<String, FieldStats> {
+        let mut analyzer = JsonAnalyzer::new();
+        for document in documents {
+            analyzer.analyze(document);
+        }
+        analyzer.finalize()
+    }
+
+    #[test]
+    fn test_compare_cohorts() {
+        let old: Vec<_> = (0..200)
+            .map(|i| json!({"id": i, "legacy": true, "note": "x", "amount": i}))
+            .collect();
+        let new: Vec<_> = (0..200)
+            .map(|i| {
+                let amount = if i % 2 == 0 { json!(i) } else { json!(i.to_string()) };
+                json!({"id": i, "source": "api", "note": if i % 10 == 0 { json!(null) } else { json!("x") }, "amount": amount})
+            })
+            .collect();
+
+        let shifts = compare_cohorts(
+            &analyze(&old),
+            &analyze(&new),
+            200,
+            200,
+            &CohortConfig::default(),
+        );
+        let described: Vec<_> = shifts
+            .iter()
+            .map(|s| format!("{}: {}", s.path(), s.description()))
+            .collect();
+        assert_eq!(
+            described,
+            vec![
+                "legacy: Field dropped: in 100.0% of old documents, none of the new",
+                "source: New field: in 100.0% of new documents",
+                "amount: Share of number values changed from 100.0% to 50.0%",
+                "note: Share of null values changed from 0.0% to 10.0%",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_small_cohorts_are_not_significant() {
+        let old = analyze(&[json!({"a": 1}), json!({"a": 1, "b": 2})]);
+        let new = analyze(&[json!({"a": 1, "b": 2}), json!({"a": 1, "b": 2})]);
+        assert!(compare_cohorts(&old, &new, 2, 2, &CohortConfig::default()).is_empty());
+    }
+}
@@ -0,0 +1,244 @@
+use crate::stats::FieldStats;
+use crate::types::JsonType;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+
+/// Configuration for comparing one column across two databases
+#[derive(Debug, Clone)]
+pub struct CompareConfig {
+    /// Minimum difference in density for a path to be reported (default: 0.05)
+    pub min_density_difference: f64,
+    /// Minimum difference in the share of any type for a path to be reported (default: 0.05)
+    pub min_type_difference: f64,
+}
+
+impl Default for CompareConfig {
+    fn default() -> Self {
+        Self {
+            min_density_difference: 0.05,
+            min_type_difference: 0.05,
+        }
+    }
+}
+
+/// Share of a path's values holding one JSON type
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TypeShare {
+    pub json_type: JsonType,
+    pub percentage: f64,
+}
+
+/// How a path differs between the left and the right column
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum PathDifference {
+    /// The path only appears on the left
+    OnlyLeft { path: String, density: f64 },
+    /// The path only appears on the right
+    OnlyRight { path: String, density: f64 },
+    /// The path is present in a different share of documents
+    Density {
+        path: String,
+        left_density: f64,
+        right_density: f64,
+    },
+    /// The path's values are split over types differently, most common type first
+    Types {
+        path: String,
+        left: Vec<TypeShare>,
+        right: Vec<TypeShare>,
+    },
+}
+
+impl PathDifference {
+    pub fn path(&self) -> &str {
+        match self {
+            PathDifference::OnlyLeft { path, .. } => path,
+            PathDifference::OnlyRight { path, .. } => path,
+            PathDifference::Density { path, .. } => path,
+            PathDifference::Types { path, .. } => path,
+        }
+    }
+
+    /// Describe the difference, naming the two sides `left` and `right`
+    pub fn description(&self, left: &str, right: &str) -> String {
+        match self {
+            PathDifference::OnlyLeft { density, .. } => {
+                format!("Only in {} ({:.1}% present)", left, density * 100.0)
+            }
+            PathDifference::OnlyRight { density, .. } => {
+                format!("Only in {} ({:.1}% present)", right, density * 100.0)
+            }
+            PathDifference::Density {
+                left_density,
+                right_density,
+                ..
+            } => format!(
+                "Density {:.1}% in {} vs {:.1}% in {}",
+                left_density * 100.0,
+                left,
+                right_density * 100.0,
+                right
+            ),
+            PathDifference::Types {
+                left: left_types,
+                right: right_types,
+                ..
+            } => format!(
+                "Types {} in {} vs {} in {}",
+                shares_label(left_types),
+                left,
+                shares_label(right_types),
+                right
+            ),
+        }
+    }
+}
+
+fn shares_label(shares: &[TypeShare]) -> String {
+    shares
+        .iter()
+        .map(|s| format!("{} {:.1}%", s.json_type, s.percentage))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Structural diff of one column's field stats from two databases
+///
+/// Reports paths present on one side only, and for shared paths any density
+/// or type distribution differing by more than the configured thresholds
+/// (a path can differ in both). Differences are ordered by path.
+pub fn compare_columns(
+    left: &HashMap<String, FieldStats>,
+    right: &HashMap<String, FieldStats>,
+    config: &CompareConfig,
+) -> Vec<PathDifference> {
+    let paths: BTreeSet<&String> = left.keys().chain(right.keys()).collect();
+    let mut differences = Vec::new();
+
+    for path in paths {
+        let (left_stats, right_stats) = match (left.get(path), right.get(path)) {
+            (Some(l), Some(r)) => (l, r),
+            (Some(l), None) => {
+                differences.push(PathDifference::OnlyLeft {
+                    path: path.clone(),
+                    density: l.density,
+                });
+                continue;
+            }
+            (None, Some(r)) => {
+                differences.push(PathDifference::OnlyRight {
+                    path: path.clone(),
+                    density: r.density,
+                });
+                continue;
+            }
+            (None, None) => continue,
+        };
+
+        if (left_stats.density - right_stats.density).abs() >= config.min_density_difference {
+            differences.push(PathDifference::Density {
+                path: path.clone(),
+                left_density: left_stats.density,
+                right_density: right_stats.density,
+            });
+        }
+
+        let left_shares = type_shares(left_stats);
+        let right_shares = type_shares(right_stats);
+        let share_of = |shares: &[TypeShare], json_type: JsonType| {
+            shares
+                .iter()
+                .find(|s| s.json_type == json_type)
+                .map_or(0.0, |s| s.percentage)
+        };
+        let types_differ = left_shares.iter().chain(&right_shares).any(|s| {
+            (share_of(&left_shares, s.json_type) - share_of(&right_shares, s.json_type)).abs()
+                >= config.min_type_difference * 100.0
+        });
+        if types_differ {
+            differences.push(PathDifference::Types {
+                path: path.clone(),
+                left: left_shares,
+                right: right_shares,
+            });
+        }
+    }
+
+    differences
+}
+
+/// Type distribution of a path, most common type first
+fn type_shares(stats: &FieldStats) -> Vec<TypeShare> {
+    let total: u64 = stats.types.values().sum();
+    let mut shares: Vec<TypeShare> = stats
+        .types
+        .iter()
+        .map(|(json_type, count)| TypeShare {
+            json_type: *json_type,
+            percentage: *count as f64 / total.max(1) as f64 * 100.0,
+        })
+        .collect();
+    shares.sort_by(|a, b| {
+        b.percentage
+            .total_cmp(&a.percentage)
+            .then_with(|| a.json_type.to_string().cmp(&b.json_type.to_string()))
+    });
+    shares
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::JsonAnalyzer;
+    use serde_json::json;
+
+    fn analyze(documents: &[serde_json::Value]) -> HashMap<String, FieldStats> {
+        let mut analyzer = JsonAnalyzer::new();
+        for document in documents {
+            analyzer.analyze(document);
+        }
+        analyzer.finalize()
+    }
+
+    #[test]
+    fn test_compare_columns() {
+        let staging: Vec<_> = (0..100)
+            .map(|i| json!({"id": i, "beta": true, "plan": "pro", "seats": i}))
+            .collect();
+        let prod: Vec<_> = (0..100)
+            .map(|i| match i % 4 {
+                0 => json!({"id": i, "seats": i.to_string()}),
+                _ => json!({"id": i, "plan": "pro", "seats": i}),
+            })
+            .collect();
+
+        let differences = compare_columns(
+            &analyze(&staging),
+            &analyze(&prod),
+            &CompareConfig::default(),
+        );
+        let described: Vec<_> = differences
+            .iter()
+            .map(|d| format!("{}: {}", d.path(), d.description("staging", "prod")))
+            .collect();
+        assert_eq!(
+            described,
+            vec![
+                "beta: Only in staging (100.0% present)",
+                "plan: Density 100.0% in staging vs 75.0% in prod",
+                "seats: Types number 100.0% in staging vs number 75.0%, string 25.0% in prod",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_small_differences_are_ignored() {
+        let left = analyze(&(0..100).map(|i| json!({"a": i})).collect::<Vec<_>>());
+        let right = analyze(
+            &(0..100)
+                .map(|i| if i == 0 { json!({}) } else { json!({"a": i}) })
+                .collect::<Vec<_>>(),
+        );
+        assert!(compare_columns(&left, &right, &CompareConfig::default()).is_empty());
+    }
+}
@@ -0,0 +1,295 @@
+use crate::analyzer::is_wildcard_path;
+use crate::drift::{DriftIssue, sort_issues};
+use crate::segment::lookup;
+use crate::stats::FieldStats;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Configuration for conditional field detection
+#[derive(Debug, Clone)]
+pub struct ConditionalConfig {
+    /// Minimum documents on each side of the condition (default: 10)
+    pub min_support: u64,
+    /// Most distinct values a path may hold to be used as a condition (default: 10)
+    pub max_values: usize,
+    /// Lowest density where the condition holds (default: 0.95)
+    pub min_density_when: f64,
+    /// Highest density where the condition doesn't hold (default: 0.05)
+    pub max_density_otherwise: f64,
+}
+
+impl Default for ConditionalConfig {
+    fn default() -> Self {
+        Self {
+            min_support: 10,
+            max_values: 10,
+            min_density_when: 0.95,
+            max_density_otherwise: 0.05,
+        }
+    }
+}
+
+/// Replace density issues of fields whose presence depends on another field's value
+///
+/// A field like `refund_reason` that only appears when `status` is
+/// `"refunded"` is sparse by design, not drift. For every ghost, sparse or
+/// missing key, the enum-like paths of the column are tried as conditions;
+/// if the field is (nearly) always present for some of their values and
+/// (nearly) never otherwise, the issue becomes a `ConditionalField`. Paths
+/// under arrays or collapsed maps are not considered.
+pub fn explain_conditional_fields(
+    issues: Vec<DriftIssue>,
+    samples: &[Value],
+    stats: &HashMap<String, FieldStats>,
+    config: &ConditionalConfig,
+) -> Vec<DriftIssue> {
+    let candidates: Vec<String> = issues
+        .iter()
+        .filter(|issue| is_density_issue(issue) && comparable(issue.path()))
+        .map(|issue| issue.path().to_string())
+        .collect();
+    let conditions = condition_paths(stats, config);
+    if candidates.is_empty() || conditions.is_empty() {
+        return issues;
+    }
+
+    // Documents per value of each condition path, and how many of them
+    // contain each candidate
+    let mut value_counts: Vec<Vec<u64>> = conditions
+        .iter()
+        .map(|(_, values)| vec![0; values.len()])
+        .collect();
+    let mut present_counts: Vec<Vec<Vec<u64>>> = vec![value_counts.clone(); candidates.len()];
+    let mut candidate_counts = vec![0u64; candidates.len()];
+    let mut matched = Vec::with_capacity(conditions.len());
+    for document in samples {
+        matched.clear();
+        matched.extend(conditions.iter().map(|(path, values)| {
+            let value = lookup(document, path)?;
+            values.iter().position(|v| v == value)
+        }));
+        for (d, index) in matched.iter().enumerate() {
+            if let Some(index) = index {
+                value_counts[d][*index] += 1;
+            }
+        }
+        for (c, path) in candidates.iter().enumerate() {
+            if lookup(document, path).is_none() {
+                continue;
+            }
+            candidate_counts[c] += 1;
+            for (d, index) in matched.iter().enumerate() {
+                if let Some(index) = index {
+                    present_counts[c][d][*index] += 1;
+                }
+            }
+        }
+    }
+
+    let total = samples.len() as u64;
+    let mut explained: HashMap<String, DriftIssue> = HashMap::new();
+    for (c, path) in candidates.iter().enumerate() {
+        let mut best: Option<(f64, DriftIssue)> = None;
+        for (d, (condition_path, values)) in conditions.iter().enumerate() {
+            if is_related(path, condition_path) {
+                continue;
+            }
+
+            // The values under which the field is mostly present
+            let selected: Vec<usize> = (0..values.len())
+                .filter(|&i| {
+                    let documents = value_counts[d][i];
+                    documents > 0 && present_counts[c][d][i] * 2 >= documents
+                })
+                .collect();
+            let documents_when: u64 = selected.iter().map(|&i| value_counts[d][i]).sum();
+            let present_when: u64 = selected.iter().map(|&i| present_counts[c][d][i]).sum();
+            let documents_otherwise = total - documents_when;
+            let present_otherwise = candidate_counts[c] - present_when;
+            if documents_when < config.min_support || documents_otherwise < config.min_support {
+                continue;
+            }
+
+            let density_when = present_when as f64 / documents_when as f64;
+            let density_otherwise = present_otherwise as f64 / documents_otherwise as f64;
+            if density_when < config.min_density_when
+                || density_otherwise > config.max_density_otherwise
+            {
+                continue;
+            }
+
+            let contrast = density_when - density_otherwise;
+            if best.as_ref().is_some_and(|(score, _)| *score >= contrast) {
+                continue;
+            }
+            best = Some((
+                contrast,
+                DriftIssue::ConditionalField {
+                    path: path.clone(),
+                    condition_path: condition_path.clone(),
+                    values: selected.iter().map(|&i| values[i].clone()).collect(),
+                    density_when,
+                    density_otherwise,
+                    documents_when,
+                },
+            ));
+        }
+        if let Some((_, issue)) = best {
+            explained.insert(path.clone(), issue);
+        }
+    }
+
+    let mut issues: Vec<DriftIssue> = issues
+        .into_iter()
+        .map(|issue| {
+            if is_density_issue(&issue)
+                && let Some(conditional) = explained.remove(issue.path())
+            {
+                conditional
+            } else {
+                issue
+            }
+        })
+        .collect();
+    sort_issues(&mut issues);
+    issues
+}
+
+fn is_density_issue(issue: &DriftIssue) -> bool {
+    matches!(
+        issue,
+        DriftIssue::GhostKey { .. }
+            | DriftIssue::SparseField { .. }
+            | DriftIssue::MissingKey { .. }
+    )
+}
+
+/// Enum-like paths and their values, each value seen in enough documents
+fn condition_paths(
+    stats: &HashMap<String, FieldStats>,
+    config: &ConditionalConfig,
+) -> Vec<(String, Vec<Value>)> {
+    let mut paths: Vec<(String, Vec<Value>)> = stats
+        .values()
+        .filter(|fs| comparable(&fs.path))
+        .filter_map(|fs| {
+            let values: Vec<Value> = fs
+                .top_values
+                .as_ref()?
+                .iter()
+                .filter(|vc| !vc.value.is_null() && vc.count >= config.min_support)
+                .map(|vc| vc.value.clone())
+                .collect();
+            (values.len() >= 2 && values.len() <= config.max_values)
+                .then(|| (fs.path.clone(), values))
+        })
+        .collect();
+    paths.sort_by(|a, b| a.0.cmp(&b.0));
+    paths
+}
+
+/// Whether a path can be looked up once per document
+fn comparable(path: &str) -> bool {
+    !path.contains("[]") && !is_wildcard_path(path)
+}
+
+/// Whether the paths are the same or one is nested inside the other
+fn is_related(a: &str, b: &str) -> bool {
+    let nested = |outer: &str, inner: &str| {
+        inner.len() > outer.len()
+            && inner.starts_with(outer)
+            && inner.as_bytes()[outer.len()] == b'.'
+    };
+    a == b || nested(a, b) || nested(b, a)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::JsonAnalyzer;
+    use crate::drift::{DriftConfig, detect_drift};
+    use serde_json::json;
+
+    fn explain(samples: &[Value]) -> Vec<DriftIssue> {
+        let mut analyzer = JsonAnalyzer::new();
+        for sample in samples {
+            analyzer.analyze(sample);
+        }
+        let stats = analyzer.finalize();
+        let issues = detect_drift(&stats, &DriftConfig::default());
+        explain_conditional_fields(issues, samples, &stats, &ConditionalConfig::default())
+    }
+
+    #[test]
+    fn test_conditional_field() {
+        let samples: Vec<Value> = (0..200)
+            .map(|i| match i % 10 {
+                0 | 1 => json!({"id": i, "status": "refunded", "refund_reason": "damaged"}),
+                2 => json!({"id": i, "status": "chargeback", "refund_reason": "fraud"}),
+                3 | 4 => json!({"id": i, "status": "paid", "coupon": "SPRING"}),
+                _ => json!({"id": i, "status": "paid"}),
+            })
+            .collect();
+
+        let issues = explain(&samples);
+        let refund: Vec<_> = issues
+            .iter()
+            .filter(|issue| issue.path() == "refund_reason")
+            .collect();
+        assert_eq!(refund.len(), 1);
+        match refund[0] {
+            DriftIssue::ConditionalField {
+                condition_path,
+                values,
+                density_when,
+                density_otherwise,
+                documents_when,
+                ..
+            } => {
+                assert_eq!(condition_path, "status");
+                assert_eq!(values, &vec![json!("refunded"), json!("chargeback")]);
+                assert_eq!(*density_when, 1.0);
+                assert_eq!(*density_otherwise, 0.0);
+                assert_eq!(*documents_when, 60);
+            }
+            other => panic!("expected a conditional field, got {:?}", other),
+        }
+        assert_eq!(
+            refund[0].description(),
+            "Conditional field: present in 100.0% of documents where status is \"refunded\" or \"chargeback\" (0.0% otherwise)"
+        );
+
+        // Present in only some of the "paid" documents: still sparse
+        assert!(issues.iter().any(
+            |issue| matches!(issue, DriftIssue::SparseField { path, .. } if path == "coupon")
+        ));
+    }
+
+    #[test]
+    fn test_unconditional_field_stays_sparse() {
+        let samples: Vec<Value> = (0..200)
+            .map(|i| {
+                let status = if i % 2 == 0 { "paid" } else { "pending" };
+                if i % 4 < 2 {
+                    json!({"status": status, "note": "x"})
+                } else {
+                    json!({"status": status})
+                }
+            })
+            .collect();
+
+        let issues = explain(&samples);
+        assert!(
+            issues.iter().any(
+                |issue| matches!(issue, DriftIssue::SparseField { path, .. } if path == "note")
+            )
+        );
+    }
+
+    #[test]
+    fn test_is_related() {
+        assert!(is_related("status", "status"));
+        assert!(is_related("order", "order.status"));
+        assert!(!is_related("order", "order_status"));
+    }
+}
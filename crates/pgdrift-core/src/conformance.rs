@@ -0,0 +1,213 @@
+use jsonschema::Validator;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+/// Checks documents one by one against a JSON Schema (draft 2020-12)
+pub struct ConformanceChecker {
+    validator: Validator,
+    documents: u64,
+    failing_documents: u64,
+    /// Documents failing each (path, keyword), with the first error message seen
+    violations: HashMap<(String, String), (u64, String)>,
+}
+
+/// Share of documents breaking one schema keyword at one path
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct KeywordViolation {
+    /// Path of the offending value (`items[].sku`), empty for the document itself
+    pub path: String,
+    /// Schema keyword that failed (`type`, `required`, `enum`, ...)
+    pub keyword: String,
+    pub documents: u64,
+    pub percentage: f64,
+    /// Message of the first such error, without the offending value
+    pub example: String,
+}
+
+/// How well the sampled documents conform to a JSON Schema
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ConformanceReport {
+    pub documents: u64,
+    /// Documents with at least one violation
+    pub failing_documents: u64,
+    /// Violations, most widespread first
+    pub violations: Vec<KeywordViolation>,
+}
+
+impl ConformanceReport {
+    /// Percentage of documents that validate
+    pub fn conformance_percentage(&self) -> f64 {
+        if self.documents == 0 {
+            return 100.0;
+        }
+        (self.documents - self.failing_documents) as f64 / self.documents as f64 * 100.0
+    }
+}
+
+impl ConformanceChecker {
+    /// Compile a schema, failing with the reason if it isn't a valid JSON Schema
+    pub fn new(schema: &Value) -> Result<Self, String> {
+        let validator = jsonschema::draft202012::new(schema).map_err(|e| e.to_string())?;
+        Ok(Self {
+            validator,
+            documents: 0,
+            failing_documents: 0,
+            violations: HashMap::new(),
+        })
+    }
+
+    pub fn check(&mut self, document: &Value) {
+        self.documents += 1;
+
+        // A keyword failing several times in one document (e.g. for every array
+        // element) counts that document once
+        let mut seen = HashSet::new();
+        for error in self.validator.iter_errors(document) {
+            let path = pointer_path(document, error.instance_path.as_str());
+            let keyword = error
+                .schema_path
+                .as_str()
+                .rsplit('/')
+                .next()
+                .unwrap_or_default()
+                .to_string();
+            let key = (path, keyword);
+            if seen.contains(&key) {
+                continue;
+            }
+            self.violations
+                .entry(key.clone())
+                .or_insert_with(|| (0, error.masked().to_string()))
+                .0 += 1;
+            seen.insert(key);
+        }
+
+        if !seen.is_empty() {
+            self.failing_documents += 1;
+        }
+    }
+
+    pub fn check_all(&mut self, documents: &[Value]) {
+        for document in documents {
+            self.check(document);
+        }
+    }
+
+    pub fn report(&self) -> ConformanceReport {
+        let mut violations: Vec<KeywordViolation> = self
+            .violations
+            .iter()
+            .map(|((path, keyword), (documents, example))| KeywordViolation {
+                path: path.clone(),
+                keyword: keyword.clone(),
+                documents: *documents,
+                percentage: *documents as f64 / self.documents.max(1) as f64 * 100.0,
+                example: example.clone(),
+            })
+            .collect();
+        violations.sort_by(|a, b| {
+            b.documents
+                .cmp(&a.documents)
+                .then_with(|| a.path.cmp(&b.path))
+                .then_with(|| a.keyword.cmp(&b.keyword))
+        });
+
+        ConformanceReport {
+            documents: self.documents,
+            failing_documents: self.failing_documents,
+            violations,
+        }
+    }
+}
+
+/// Path of a JSON pointer into `document`, written the way pgdrift reports paths
+///
+/// Array indexes become `[]`, so errors in different elements share a path.
+fn pointer_path(document: &Value, pointer: &str) -> String {
+    let mut path = String::new();
+    let mut current = Some(document);
+
+    for token in pointer.split('/').skip(1) {
+        let token = token.replace("~1", "/").replace("~0", "~");
+        match current {
+            Some(Value::Array(items)) => {
+                path.push_str("[]");
+                current = token.parse::<usize>().ok().and_then(|i| items.get(i));
+            }
+            other => {
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                current = other.and_then(|value| value.get(&token));
+                path.push_str(&token);
+            }
+        }
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_conformance() {
+        let schema = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "type": "object",
+            "required": ["id"],
+            "properties": {
+                "id": {"type": "integer"},
+                "items": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {"sku": {"type": "string"}}
+                    }
+                }
+            }
+        });
+        let mut checker = ConformanceChecker::new(&schema).unwrap();
+        checker.check_all(&[
+            json!({"id": 1, "items": [{"sku": "a"}]}),
+            json!({"id": "2", "items": [{"sku": 1}, {"sku": 2}]}),
+            json!({"items": [{"sku": "a"}, {"sku": 3}]}),
+            json!({"id": 4}),
+        ]);
+
+        let report = checker.report();
+        assert_eq!(report.documents, 4);
+        assert_eq!(report.failing_documents, 2);
+        assert_eq!(report.conformance_percentage(), 50.0);
+
+        let found: Vec<_> = report
+            .violations
+            .iter()
+            .map(|v| (v.path.as_str(), v.keyword.as_str(), v.documents))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("items[].sku", "type", 2),
+                ("", "required", 1),
+                ("id", "type", 1)
+            ]
+        );
+        // Offending values are left out of the messages
+        assert!(!report.violations[2].example.contains("\"2\""));
+    }
+
+    #[test]
+    fn test_invalid_schema() {
+        assert!(ConformanceChecker::new(&json!({"type": "no-such-type"})).is_err());
+    }
+
+    #[test]
+    fn test_pointer_path() {
+        let document = json!({"a": {"0": [{"b/c": 1}]}});
+        assert_eq!(pointer_path(&document, ""), "");
+        assert_eq!(pointer_path(&document, "/a/0/0/b~1c"), "a.0[].b/c");
+    }
+}
@@ -0,0 +1,425 @@
+use crate::analyzer::is_wildcard_path;
+use crate::redact::Redaction;
+use crate::segment::lookup;
+use crate::stats::FieldStats;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Configuration for co-occurrence analysis
+#[derive(Debug, Clone)]
+pub struct CooccurrenceConfig {
+    /// Minimum documents matching a path or value before it takes part (default: 10)
+    pub min_support: u64,
+    /// Optional paths compared pairwise, most frequent first (default: 100)
+    pub max_paths: usize,
+    /// `path == value` conditions considered as implication consequents (default: 100)
+    pub max_values: usize,
+    /// Maximum number of implication rules reported (default: 50)
+    pub max_implications: usize,
+}
+
+impl Default for CooccurrenceConfig {
+    fn default() -> Self {
+        Self {
+            min_support: 10,
+            max_paths: 100,
+            max_values: 100,
+            max_implications: 50,
+        }
+    }
+}
+
+/// Something a single document can satisfy
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Condition {
+    /// The path exists (even if null)
+    Present { path: String },
+    /// The path holds this scalar value
+    Equals { path: String, value: Value },
+}
+
+impl Condition {
+    pub fn path(&self) -> &str {
+        match self {
+            Condition::Present { path } | Condition::Equals { path, .. } => path,
+        }
+    }
+
+    fn matches(&self, document: &Value) -> bool {
+        match self {
+            Condition::Present { path } => lookup(document, path).is_some(),
+            Condition::Equals { path, value } => lookup(document, path) == Some(value),
+        }
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Condition::Present { path } => write!(f, "{} present", path),
+            Condition::Equals { path, value } => write!(f, "{} == {}", path, value),
+        }
+    }
+}
+
+/// Optional paths that are either always together or never together
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PathGroup {
+    pub paths: Vec<String>,
+    /// Documents containing any of the paths
+    pub occurrences: u64,
+}
+
+/// `antecedent present ⇒ consequent`, held by every sampled document
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Implication {
+    pub antecedent: String,
+    pub consequent: Condition,
+    /// Documents containing the antecedent
+    pub support: u64,
+}
+
+impl fmt::Display for Implication {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} present ⇒ {}", self.antecedent, self.consequent)
+    }
+}
+
+/// How the optional paths of a column relate to each other
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Cooccurrence {
+    /// Paths that always appear together - likely one hidden sub-schema
+    pub groups: Vec<PathGroup>,
+    /// Paths that never appear together - likely variants of one another
+    pub exclusive: Vec<PathGroup>,
+    pub implications: Vec<Implication>,
+}
+
+impl Cooccurrence {
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty() && self.exclusive.is_empty() && self.implications.is_empty()
+    }
+
+    /// Hide the sampled values used in implication rules
+    pub fn redact(&mut self, redaction: Redaction) {
+        for implication in &mut self.implications {
+            if let Condition::Equals { value, .. } = &mut implication.consequent {
+                *value = redaction.apply(value);
+            }
+        }
+    }
+}
+
+/// Find co-occurring, mutually exclusive and implied paths across documents
+///
+/// Candidates come from finalized field stats: paths that are optional within
+/// their parent, and the common values of enum-like paths. Presence is then
+/// counted per document, so only rules that hold in every sample are reported.
+/// Paths under arrays or collapsed maps are not considered.
+pub fn analyze_cooccurrence(
+    samples: &[Value],
+    stats: &HashMap<String, FieldStats>,
+    config: &CooccurrenceConfig,
+) -> Cooccurrence {
+    let total = samples.len() as u64;
+    let optional = optional_paths(stats, config);
+    let path_count = optional.len();
+    let atoms: Vec<Condition> = optional
+        .into_iter()
+        .map(|path| Condition::Present { path })
+        .chain(value_conditions(stats, config))
+        .collect();
+
+    // Documents matching each condition, and each pair of conditions
+    let n = atoms.len();
+    let mut counts = vec![0u64; n];
+    let mut pairs = vec![0u64; n * n];
+    let mut hits = Vec::with_capacity(n);
+    for document in samples {
+        hits.clear();
+        hits.extend((0..n).filter(|&i| atoms[i].matches(document)));
+        for (k, &i) in hits.iter().enumerate() {
+            counts[i] += 1;
+            for &j in &hits[k + 1..] {
+                pairs[i * n + j] += 1;
+                pairs[j * n + i] += 1;
+            }
+        }
+    }
+    let pair = |i: usize, j: usize| pairs[i * n + j];
+    let usable = |i: usize| counts[i] >= config.min_support && counts[i] < total;
+
+    // Paths seen in exactly the same documents, most frequent first
+    let mut by_count: Vec<usize> = (0..path_count).filter(|&i| usable(i)).collect();
+    by_count.sort_by(|&a, &b| counts[b].cmp(&counts[a]).then_with(|| a.cmp(&b)));
+
+    let mut representative: Vec<Option<usize>> = vec![None; n];
+    let mut members: Vec<Vec<usize>> = Vec::new();
+    for &i in &by_count {
+        if representative[i].is_some() {
+            continue;
+        }
+        let group: Vec<usize> = by_count
+            .iter()
+            .copied()
+            .filter(|&j| {
+                representative[j].is_none() && counts[j] == counts[i] && pair(i, j) == counts[i]
+            })
+            .chain([i])
+            .collect();
+        for &j in &group {
+            representative[j] = Some(i);
+        }
+        members.push(group);
+    }
+    let representatives: Vec<usize> = members.iter().map(|group| group[group.len() - 1]).collect();
+
+    let mut result = Cooccurrence::default();
+    for group in members.iter().filter(|group| group.len() > 1) {
+        let mut paths: Vec<String> = group.iter().map(|&i| atoms[i].path().to_string()).collect();
+        paths.sort();
+        result.groups.push(PathGroup {
+            paths,
+            occurrences: counts[group[0]],
+        });
+    }
+
+    // Greedily grow sets of paths that never share a document
+    let mut exclusive: Vec<Vec<usize>> = Vec::new();
+    for &r in &representatives {
+        match exclusive
+            .iter_mut()
+            .find(|set| set.iter().all(|&m| pair(r, m) == 0))
+        {
+            Some(set) => set.push(r),
+            None => exclusive.push(vec![r]),
+        }
+    }
+    for set in exclusive.iter().filter(|set| set.len() > 1) {
+        result.exclusive.push(PathGroup {
+            paths: set.iter().map(|&i| atoms[i].path().to_string()).collect(),
+            occurrences: set.iter().map(|&i| counts[i]).sum(),
+        });
+    }
+
+    for &a in &representatives {
+        let antecedent = atoms[a].path();
+        let implied: Vec<usize> = (0..n)
+            .filter(|&c| {
+                let is_representative = c >= path_count || representative[c] == Some(c);
+                usable(c)
+                    && is_representative
+                    && representative[c] != representative[a]
+                    && pair(a, c) == counts[a]
+                    && !is_related(antecedent, atoms[c].path())
+            })
+            .collect();
+
+        for &c in &implied {
+            // A known value already says the path is present
+            let redundant = c < path_count
+                && implied
+                    .iter()
+                    .any(|&v| v >= path_count && atoms[v].path() == atoms[c].path());
+            if !redundant {
+                result.implications.push(Implication {
+                    antecedent: antecedent.to_string(),
+                    consequent: atoms[c].clone(),
+                    support: counts[a],
+                });
+            }
+        }
+    }
+    result.implications.sort_by(|a, b| {
+        b.support
+            .cmp(&a.support)
+            .then_with(|| a.antecedent.cmp(&b.antecedent))
+            .then_with(|| a.consequent.path().cmp(b.consequent.path()))
+    });
+    result.implications.truncate(config.max_implications);
+
+    result
+}
+
+/// Paths missing from some documents that contain their parent
+fn optional_paths(stats: &HashMap<String, FieldStats>, config: &CooccurrenceConfig) -> Vec<String> {
+    let mut candidates: Vec<&FieldStats> = stats
+        .values()
+        .filter(|fs| comparable(&fs.path) && fs.occurrences >= config.min_support)
+        .filter(|fs| {
+            let parent_occurrences = match fs.path.rsplit_once('.') {
+                Some((parent, _)) => stats.get(parent).map_or(0, |p| p.occurrences),
+                None => fs.total_samples,
+            };
+            fs.occurrences < parent_occurrences
+        })
+        .collect();
+    candidates.sort_by(|a, b| {
+        b.occurrences
+            .cmp(&a.occurrences)
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    candidates
+        .into_iter()
+        .take(config.max_paths)
+        .map(|fs| fs.path.clone())
+        .collect()
+}
+
+/// Common values of enum-like paths, excluding values a path always holds
+fn value_conditions(
+    stats: &HashMap<String, FieldStats>,
+    config: &CooccurrenceConfig,
+) -> Vec<Condition> {
+    let mut candidates: Vec<(u64, Condition)> = stats
+        .values()
+        .filter(|fs| comparable(&fs.path))
+        .flat_map(|fs| {
+            fs.top_values
+                .iter()
+                .flatten()
+                .filter(|vc| vc.count >= config.min_support && vc.count < fs.occurrences)
+                .map(|vc| {
+                    let condition = Condition::Equals {
+                        path: fs.path.clone(),
+                        value: vc.value.clone(),
+                    };
+                    (vc.count, condition)
+                })
+        })
+        .collect();
+    candidates.sort_by(|a, b| {
+        b.0.cmp(&a.0)
+            .then_with(|| a.1.path().cmp(b.1.path()))
+            .then_with(|| a.1.to_string().cmp(&b.1.to_string()))
+    });
+    candidates
+        .into_iter()
+        .take(config.max_values)
+        .map(|(_, condition)| condition)
+        .collect()
+}
+
+/// Whether a path can be looked up once per document
+fn comparable(path: &str) -> bool {
+    !path.contains("[]") && !is_wildcard_path(path)
+}
+
+/// Whether one path is nested inside the other
+fn is_related(a: &str, b: &str) -> bool {
+    let nested = |outer: &str, inner: &str| {
+        inner.len() > outer.len()
+            && inner.starts_with(outer)
+            && inner.as_bytes()[outer.len()] == b'.'
+    };
+    nested(a, b) || nested(b, a)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::JsonAnalyzer;
+    use serde_json::json;
+
+    fn analyze(samples: &[Value]) -> Cooccurrence {
+        let mut analyzer = JsonAnalyzer::new();
+        for sample in samples {
+            analyzer.analyze(sample);
+        }
+        let stats = analyzer.finalize();
+        analyze_cooccurrence(samples, &stats, &CooccurrenceConfig::default())
+    }
+
+    fn payments() -> Vec<Value> {
+        let mut samples = Vec::new();
+        for i in 0..60 {
+            samples.push(
+                json!({"id": i, "status": "paid", "card_last4": "4242", "card_brand": "visa"}),
+            );
+        }
+        for i in 0..30 {
+            samples.push(json!({"id": i, "status": "paid", "iban": "DE89"}));
+        }
+        for i in 0..20 {
+            samples.push(json!({
+                "id": i,
+                "status": "refunded",
+                "iban": "DE89",
+                "refund": {"amount": 5, "reason": "damaged"}
+            }));
+        }
+        samples
+    }
+
+    #[test]
+    fn test_groups_fields_that_always_appear_together() {
+        let result = analyze(&payments());
+        assert_eq!(
+            result.groups,
+            vec![PathGroup {
+                paths: vec!["card_brand".to_string(), "card_last4".to_string()],
+                occurrences: 60,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_mutually_exclusive_fields() {
+        let result = analyze(&payments());
+        assert_eq!(result.exclusive.len(), 1);
+        let paths = &result.exclusive[0].paths;
+        assert_eq!(paths.len(), 2);
+        assert!(paths.contains(&"iban".to_string()));
+        assert!(paths.iter().any(|p| p.starts_with("card_")));
+        assert_eq!(result.exclusive[0].occurrences, 110);
+    }
+
+    #[test]
+    fn test_implications() {
+        let result = analyze(&payments());
+        let rules: Vec<String> = result.implications.iter().map(|i| i.to_string()).collect();
+
+        assert!(rules.contains(&"refund present ⇒ status == \"refunded\"".to_string()));
+        assert!(rules.contains(&"refund present ⇒ iban present".to_string()));
+        assert!(rules.iter().any(|r| r.ends_with("⇒ status == \"paid\"")));
+        // Nested fields are part of the parent, not a separate rule
+        assert!(!rules.iter().any(|r| r.contains("refund.amount")));
+        // Always-present fields carry no information
+        assert!(!rules.iter().any(|r| r.contains("id present")));
+    }
+
+    #[test]
+    fn test_below_min_support_is_ignored() {
+        let samples: Vec<Value> = (0..20)
+            .map(|i| match i {
+                0..5 => json!({"a": 1, "b": 2}),
+                _ => json!({"c": 3}),
+            })
+            .collect();
+        let result = analyze(&samples);
+        assert!(result.groups.is_empty());
+        assert!(result.implications.is_empty());
+    }
+
+    #[test]
+    fn test_redact() {
+        let mut result = analyze(&payments());
+        result.redact(Redaction::Mask);
+        assert!(result.implications.iter().any(|i| i.consequent
+            == Condition::Equals {
+                path: "status".to_string(),
+                value: json!("xxxxxxxx"),
+            }));
+    }
+
+    #[test]
+    fn test_is_related() {
+        assert!(is_related("refund", "refund.amount"));
+        assert!(is_related("a.b.c", "a"));
+        assert!(!is_related("refund", "refund_id"));
+        assert!(!is_related("refund", "refund"));
+    }
+}
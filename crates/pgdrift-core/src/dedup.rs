@@ -0,0 +1,62 @@
+use serde_json::Value;
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// Collapse identical documents into one entry with a repeat count
+///
+/// Many columns hold the same payload over and over (default settings, empty
+/// carts, canned events); analyzing each distinct document once with its
+/// multiplicity gives the same field stats for a fraction of the work.
+/// Documents keep the order in which they were first seen.
+pub fn dedup_samples(samples: &[Value]) -> Vec<(&Value, u64)> {
+    let mut unique: Vec<(&Value, u64)> = Vec::new();
+    // Hash of the canonical JSON text -> indices into `unique` sharing it
+    let mut buckets: HashMap<u64, Vec<usize>> = HashMap::new();
+
+    for sample in samples {
+        let bucket = buckets.entry(document_hash(sample)).or_default();
+        match bucket.iter().find(|&&i| unique[i].0 == sample) {
+            Some(&i) => unique[i].1 += 1,
+            None => {
+                bucket.push(unique.len());
+                unique.push((sample, 1));
+            }
+        }
+    }
+
+    unique
+}
+
+fn document_hash(document: &Value) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    document.to_string().hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_dedup_samples() {
+        let samples = vec![
+            json!({"plan": "free", "seats": 1}),
+            json!({"plan": "pro", "seats": 5}),
+            json!({"seats": 1, "plan": "free"}),
+            json!({"plan": "free", "seats": 1}),
+        ];
+
+        let unique = dedup_samples(&samples);
+        assert_eq!(unique.len(), 2);
+        assert_eq!(unique[0], (&samples[0], 3));
+        assert_eq!(unique[1], (&samples[1], 1));
+    }
+
+    #[test]
+    fn test_types_are_not_conflated() {
+        let samples = vec![json!({"id": 1}), json!({"id": "1"}), json!({"id": 1.0})];
+        assert_eq!(dedup_samples(&samples).len(), 3);
+    }
+}
@@ -0,0 +1,268 @@
+use crate::analyzer::is_wildcard_path;
+use crate::segment::lookup;
+use crate::stats::FieldStats;
+use crate::types::JsonType;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::{BTreeSet, HashSet};
+
+/// Configuration for denormalization detection
+#[derive(Debug, Clone)]
+pub struct DenormalizationConfig {
+    /// Minimum rows where both the path and the column hold a value (default: 10)
+    pub min_support: u64,
+    /// Share of matching values from which a path duplicates a differently
+    /// named column (default: 0.9)
+    pub min_match_rate: f64,
+    /// Distinct matching values needed to pair a path with a differently
+    /// named column, so flags and small enums don't match by chance (default: 5)
+    pub min_distinct: usize,
+}
+
+impl Default for DenormalizationConfig {
+    fn default() -> Self {
+        Self {
+            min_support: 10,
+            min_match_rate: 0.9,
+            min_distinct: 5,
+        }
+    }
+}
+
+/// A JSON path that probably duplicates a relational column of the same table
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Denormalization {
+    pub path: String,
+    pub column: String,
+    /// Whether the path is named like the column (`customer.email` and `email`)
+    pub same_name: bool,
+    /// Rows where both hold a non-null value
+    pub compared: u64,
+    /// Compared rows where the values differ
+    pub mismatches: u64,
+    pub mismatch_percentage: f64,
+}
+
+/// Find JSON paths duplicating other columns of the row they're stored in
+///
+/// `rows[i]` holds the other columns of the row `documents[i]` was read
+/// from. A scalar path is paired with a column when they share a name, or
+/// when their values agree in nearly every row. Values are compared as text,
+/// numbers by value. Paths under arrays or collapsed maps are not considered.
+pub fn find_denormalization(
+    documents: &[Value],
+    rows: &[Map<String, Value>],
+    stats: &[FieldStats],
+    config: &DenormalizationConfig,
+) -> Vec<Denormalization> {
+    let paths = scalar_paths(stats, config);
+    let columns: Vec<&String> = rows
+        .iter()
+        .flat_map(|row| row.keys())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    if paths.is_empty() || columns.is_empty() {
+        return Vec::new();
+    }
+
+    let n = columns.len();
+    let mut compared = vec![0u64; paths.len() * n];
+    let mut matches = vec![0u64; paths.len() * n];
+    // Distinct matching values, only counted up to `min_distinct`
+    let mut distinct: Vec<HashSet<String>> = vec![HashSet::new(); paths.len() * n];
+
+    for (document, row) in documents.iter().zip(rows) {
+        let column_values: Vec<Option<String>> = columns
+            .iter()
+            .map(|column| row.get(*column).and_then(scalar_text))
+            .collect();
+        for (p, path) in paths.iter().enumerate() {
+            let Some(value) = lookup(document, path).and_then(scalar_text) else {
+                continue;
+            };
+            for (c, column_value) in column_values.iter().enumerate() {
+                let Some(column_value) = column_value else {
+                    continue;
+                };
+                let i = p * n + c;
+                compared[i] += 1;
+                if values_equal(&value, column_value) {
+                    matches[i] += 1;
+                    if distinct[i].len() < config.min_distinct {
+                        distinct[i].insert(value.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    let mut found = Vec::new();
+    for (p, path) in paths.iter().enumerate() {
+        for (c, column) in columns.iter().enumerate() {
+            let i = p * n + c;
+            if compared[i] < config.min_support {
+                continue;
+            }
+            let same_name = same_name(path, column);
+            let match_rate = matches[i] as f64 / compared[i] as f64;
+            let same_values =
+                match_rate >= config.min_match_rate && distinct[i].len() >= config.min_distinct;
+            if !same_name && !same_values {
+                continue;
+            }
+
+            let mismatches = compared[i] - matches[i];
+            found.push(Denormalization {
+                path: path.clone(),
+                column: column.to_string(),
+                same_name,
+                compared: compared[i],
+                mismatches,
+                mismatch_percentage: mismatches as f64 / compared[i] as f64 * 100.0,
+            });
+        }
+    }
+    found.sort_by(|a, b| a.path.cmp(&b.path).then_with(|| a.column.cmp(&b.column)));
+    found
+}
+
+/// Paths holding scalar values in enough documents
+fn scalar_paths(stats: &[FieldStats], config: &DenormalizationConfig) -> Vec<String> {
+    let mut paths: Vec<String> = stats
+        .iter()
+        .filter(|fs| !fs.path.contains("[]") && !is_wildcard_path(&fs.path))
+        .filter(|fs| fs.occurrences - fs.null_count >= config.min_support)
+        .filter(|fs| {
+            fs.types.keys().any(|json_type| {
+                matches!(
+                    json_type,
+                    JsonType::String | JsonType::Number | JsonType::Boolean
+                )
+            })
+        })
+        .map(|fs| fs.path.clone())
+        .collect();
+    paths.sort();
+    paths
+}
+
+/// Text of a scalar value; null, objects and arrays have none
+fn scalar_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        Value::Null | Value::Array(_) | Value::Object(_) => None,
+    }
+}
+
+/// Equal text, or equal numbers written differently (`10.5` and `10.50`)
+fn values_equal(a: &str, b: &str) -> bool {
+    a == b
+        || matches!(
+            (a.parse::<f64>(), b.parse::<f64>()),
+            (Ok(x), Ok(y)) if x == y
+        )
+}
+
+/// Whether a path is named like a column, ignoring case, `_` and `-`
+///
+/// Either the last key or the whole path may match: `customer.email` is
+/// named like both `email` and `customer_email`.
+fn same_name(path: &str, column: &str) -> bool {
+    let normalize = |name: &str| -> String {
+        name.chars()
+            .filter(|c| !matches!(c, '_' | '-' | '.'))
+            .flat_map(char::to_lowercase)
+            .collect()
+    };
+    let column = normalize(column);
+    let key = path.rsplit('.').next().unwrap_or(path);
+    normalize(key) == column || normalize(path) == column
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::JsonAnalyzer;
+    use serde_json::json;
+
+    fn find(samples: &[(Value, Value)]) -> Vec<Denormalization> {
+        let mut analyzer = JsonAnalyzer::new();
+        let mut documents = Vec::new();
+        let mut rows = Vec::new();
+        for (document, row) in samples {
+            analyzer.analyze(document);
+            documents.push(document.clone());
+            rows.push(row.as_object().unwrap().clone());
+        }
+        let stats: Vec<FieldStats> = analyzer.finalize().into_values().collect();
+        find_denormalization(&documents, &rows, &stats, &DenormalizationConfig::default())
+    }
+
+    #[test]
+    fn test_find_denormalization() {
+        let samples: Vec<(Value, Value)> = (0..100)
+            .map(|i| {
+                let email = format!("user{}@example.com", i);
+                // Every tenth JSON copy is stale
+                let copied = if i % 10 == 0 {
+                    "old@example.com".to_string()
+                } else {
+                    email.clone()
+                };
+                (
+                    json!({
+                        "contact": {"email": copied},
+                        "account": i + 1000,
+                        "amount": "12.50",
+                        "verified": i % 2 == 0,
+                    }),
+                    json!({
+                        "id": i,
+                        "email": email,
+                        "account_id": i + 1000,
+                        "total": 12.5,
+                        "active": i % 2 == 0,
+                    }),
+                )
+            })
+            .collect();
+
+        let found: Vec<_> = find(&samples)
+            .iter()
+            .map(|d| {
+                (
+                    d.path.clone(),
+                    d.column.clone(),
+                    d.same_name,
+                    d.compared,
+                    d.mismatches,
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("account".into(), "account_id".into(), false, 100, 0),
+                ("contact.email".into(), "email".into(), true, 100, 10),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_same_name() {
+        assert!(same_name("customer.email", "email"));
+        assert!(same_name("customer.email", "customer_email"));
+        assert!(same_name("userId", "user_id"));
+        assert!(!same_name("customer.email", "email_verified"));
+    }
+
+    #[test]
+    fn test_values_equal() {
+        assert!(values_equal("10.5", "10.50"));
+        assert!(values_equal("abc", "abc"));
+        assert!(!values_equal("1", "true"));
+    }
+}
@@ -1,13 +1,21 @@
+use crate::analyzer::is_wildcard_path;
+use crate::format::StringFormat;
+use crate::redact::Redaction;
+use crate::segment::lookup;
 use crate::stats::FieldStats;
 use crate::types::JsonType;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::HashMap;
 
 /// Severity level for drift issues
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum Severity {
+    #[serde(alias = "info")]
     Info,
+    #[serde(alias = "warning")]
     Warning,
+    #[serde(alias = "critical")]
     Critical, // Add more if we need to
 }
 
@@ -36,6 +44,8 @@ pub enum DriftIssue {
         density: f64,
         occurunces: u64,
         total_samples: u64,
+        /// Margin of error of `density` at the configured confidence level
+        margin: f64,
     },
     /// Optional field with moderate presence (10-80%)
     SparseField {
@@ -43,6 +53,8 @@ pub enum DriftIssue {
         density: f64,
         occurrences: u64,
         total_samples: u64,
+        /// Margin of error of `density` at the configured confidence level
+        margin: f64,
     },
     /// Expected high density key with unexpected gaps (80-95%)
     MissingKey {
@@ -50,14 +62,170 @@ pub enum DriftIssue {
         density: f64,
         expected_occurrences: u64,
         actual_occurrences: u64,
+        /// Margin of error of `density` at the configured confidence level
+        margin: f64,
     },
     /// Schema changes detected - versions or naming inconsistency
     SchemaEvolution {
         path: String,
         pattern: EvolutionPattern,
     },
+    /// String field mostly in one format (uuid, date, ...) with other formats mixed in
+    MixedFormat {
+        path: String,
+        dominant: StringFormat,
+        formats: HashMap<StringFormat, u64>,
+        minority_percentage: f64,
+    },
+    /// Field holds the same value in every sample - a leftover flag or a CHECK constraint candidate
+    ConstantField {
+        path: String,
+        value: Value,
+        occurrences: u64,
+    },
+    /// String field whose values are nearly all numeric literals (`"42"`, `"3.14"`)
+    NumericString {
+        path: String,
+        numeric_count: u64,
+        string_count: u64,
+        numeric_percentage: f64,
+    },
+    /// Boolean field written with several encodings (`true`, `"yes"`, `1`, ...)
+    BooleanEncoding {
+        path: String,
+        dominant: BoolEncoding,
+        encodings: HashMap<BoolEncoding, u64>,
+        minority_percentage: f64,
+    },
+    /// Dates and timestamps written in several formats (ISO 8601, epoch, `MM/DD/YYYY`)
+    DateFormatDrift {
+        path: String,
+        formats: HashMap<DateFormat, u64>,
+        minority_percentage: f64,
+        suggested: DateFormat,
+    },
+    /// Field saying "nothing" both with `""` and with null or an absent key
+    EmptyString {
+        path: String,
+        empty_count: u64,
+        null_count: u64,
+        missing_count: u64,
+        total_samples: u64,
+    },
+    /// Field written as explicit null by some producers and omitted by others
+    NullVsMissing {
+        path: String,
+        null_count: u64,
+        missing_count: u64,
+        minority_percentage: f64,
+    },
+    /// Sibling keys differing only by case or separators (`userId` vs `user_id`)
+    KeyNamingConflict {
+        path: String,
+        /// Every spelling, most common first
+        variants: Vec<KeyVariant>,
+    },
+    /// Two similarly named sibling keys that split the documents between them
+    ///
+    /// Detectors only see densities, so they report candidates; see
+    /// `confirm_renames`.
+    ProbableRename {
+        path: String,
+        old_path: String,
+        new_path: String,
+        old_density: f64,
+        new_density: f64,
+        /// Whether the documents were checked and the keys (nearly) never
+        /// share one
+        confirmed: bool,
+    },
+    /// Arrays of a fixed length with a different type at each position - a
+    /// tuple that would be clearer as an object
+    TupleArray {
+        path: String,
+        /// Type counts of the elements at each position
+        positions: Vec<HashMap<JsonType, u64>>,
+        arrays: u64,
+    },
+    /// Subtree nested deeper than the configured limit, reported at its root
+    ExcessiveDepth {
+        path: String,
+        /// Depth of the root itself
+        depth: usize,
+        /// Depth of the deepest path below it
+        deepest: usize,
+        limit: usize,
+    },
+    /// Large strings or embedded blobs (base64, serialized documents)
+    OversizedPayload {
+        path: String,
+        avg_bytes: f64,
+        max_bytes: u64,
+        blob_count: u64,
+        string_count: u64,
+        /// Average bytes this path adds to each document
+        bytes_per_document: f64,
+    },
+    /// Key that is awkward or impossible to use in SQL path expressions
+    ProblematicKey {
+        path: String,
+        key: String,
+        problems: Vec<KeyProblem>,
+        occurrences: u64,
+    },
+    /// Numbers split into two bands ~100x or ~1000x apart (cents vs dollars, ms vs s)
+    UnitDrift {
+        path: String,
+        low_median: f64,
+        high_median: f64,
+        /// Share of positive values in the upper band, as a percentage
+        high_percentage: f64,
+        /// 100 or 1000
+        factor: u32,
+    },
+    /// Sparse field whose presence depends on the value of another field
+    ConditionalField {
+        path: String,
+        /// Path whose value decides whether the field is present
+        condition_path: String,
+        /// Values of `condition_path` under which the field is present
+        values: Vec<Value>,
+        density_when: f64,
+        density_otherwise: f64,
+        /// Documents where `condition_path` holds one of `values`
+        documents_when: u64,
+    },
+    /// Another issue whose severity was overridden by the user
+    Reclassified {
+        issue: Box<DriftIssue>,
+        severity: Severity,
+    },
 }
 
+/// Names of the issue kinds, as returned by `DriftIssue::kind`
+pub const ISSUE_KINDS: &[&str] = &[
+    "TypeInconsistency",
+    "GhostKey",
+    "SparseField",
+    "MissingKey",
+    "SchemaEvolution",
+    "MixedFormat",
+    "ConstantField",
+    "NumericString",
+    "BooleanEncoding",
+    "DateFormatDrift",
+    "EmptyString",
+    "NullVsMissing",
+    "KeyNamingConflict",
+    "ProbableRename",
+    "TupleArray",
+    "ExcessiveDepth",
+    "OversizedPayload",
+    "ProblematicKey",
+    "UnitDrift",
+    "ConditionalField",
+];
+
 /// Type distribution
 #[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct TypeDistribution {
@@ -66,11 +234,174 @@ pub struct TypeDistribution {
     pub percentage: f64,
 }
 
+/// Way a boolean value is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BoolEncoding {
+    /// JSON `true` / `false`
+    Boolean,
+    /// `"true"` / `"false"`, any case
+    String,
+    /// `"yes"` / `"no"` (or `"y"` / `"n"`), any case
+    YesNo,
+    /// `1` / `0`
+    Integer,
+    /// `"1"` / `"0"`
+    DigitString,
+}
+
+impl BoolEncoding {
+    /// Encoding of a value that reads as a boolean, if it does
+    pub fn classify(value: &Value) -> Option<Self> {
+        match value {
+            Value::Bool(_) => Some(BoolEncoding::Boolean),
+            Value::Number(n) if n.as_f64() == Some(0.0) || n.as_f64() == Some(1.0) => {
+                Some(BoolEncoding::Integer)
+            }
+            Value::String(s) => match s.to_ascii_lowercase().as_str() {
+                "true" | "false" => Some(BoolEncoding::String),
+                "yes" | "no" | "y" | "n" => Some(BoolEncoding::YesNo),
+                "1" | "0" => Some(BoolEncoding::DigitString),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for BoolEncoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BoolEncoding::Boolean => write!(f, "boolean"),
+            BoolEncoding::String => write!(f, "\"true\"/\"false\""),
+            BoolEncoding::YesNo => write!(f, "yes/no"),
+            BoolEncoding::Integer => write!(f, "1/0"),
+            BoolEncoding::DigitString => write!(f, "\"1\"/\"0\""),
+        }
+    }
+}
+
+/// Way a date or timestamp is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DateFormat {
+    /// ISO 8601 date, e.g. `"2024-01-15"`
+    IsoDate,
+    /// ISO 8601 timestamp, e.g. `"2024-01-15T10:30:00Z"`
+    IsoDateTime,
+    /// Slash-separated date, e.g. `"01/15/2024"`
+    SlashDate,
+    /// Unix time as a number, in seconds or milliseconds
+    Epoch,
+}
+
+impl std::fmt::Display for DateFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DateFormat::IsoDate => write!(f, "iso-date"),
+            DateFormat::IsoDateTime => write!(f, "iso-datetime"),
+            DateFormat::SlashDate => write!(f, "slash-date"),
+            DateFormat::Epoch => write!(f, "epoch"),
+        }
+    }
+}
+
+/// Numbers in this range read as Unix time: seconds from late 2001, or
+/// milliseconds up to the year 2286
+const EPOCH_RANGE: std::ops::Range<f64> = 1e9..1e13;
+
+/// Versions named in a version marker's description, the rest are counted
+const MAX_LISTED_VERSIONS: usize = 5;
+
+/// What makes a key hard to use in `#>` / `#>>` paths
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyProblem {
+    /// The key is `""`
+    Empty,
+    /// Spaces before or after the name, usually a producer bug
+    SurroundingWhitespace,
+    /// Whitespace inside the name
+    Whitespace,
+    /// Control characters such as tabs or newlines
+    ControlCharacter,
+    /// Characters that must be quoted in a `'{a,b}'` path: `,` `{` `}` `"` `'` `\`
+    NeedsQuoting,
+}
+
+impl KeyProblem {
+    /// Everything wrong with a key, in a stable order
+    pub fn find(key: &str) -> Vec<KeyProblem> {
+        let mut problems = Vec::new();
+        if key.is_empty() {
+            problems.push(KeyProblem::Empty);
+        }
+        if key.trim() != key {
+            problems.push(KeyProblem::SurroundingWhitespace);
+        }
+        if key
+            .trim()
+            .contains(|c: char| c.is_whitespace() && !c.is_control())
+        {
+            problems.push(KeyProblem::Whitespace);
+        }
+        if key.contains(char::is_control) {
+            problems.push(KeyProblem::ControlCharacter);
+        }
+        if key.contains([',', '{', '}', '"', '\'', '\\']) {
+            problems.push(KeyProblem::NeedsQuoting);
+        }
+        problems
+    }
+}
+
+impl std::fmt::Display for KeyProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeyProblem::Empty => write!(f, "empty key"),
+            KeyProblem::SurroundingWhitespace => write!(f, "leading/trailing whitespace"),
+            KeyProblem::Whitespace => write!(f, "whitespace"),
+            KeyProblem::ControlCharacter => write!(f, "control characters"),
+            KeyProblem::NeedsQuoting => write!(f, "characters needing quotes"),
+        }
+    }
+}
+
+/// One spelling of a key in a naming conflict
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct KeyVariant {
+    pub path: String,
+    pub density: f64,
+}
+
+/// Documents carrying one value of a version marker
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VersionShare {
+    pub value: Value,
+    pub documents: u64,
+    /// Percentage of non-null marker values
+    pub percentage: f64,
+}
+
+/// A field whose presence differs between versions
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VersionDifference {
+    pub path: String,
+    /// Density within each version, in the order of the marker's `versions`
+    pub densities: Vec<f64>,
+}
+
 /// Evolution pattern for existing schema
 #[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum EvolutionPattern {
     /// Version markers
-    VersionMarker { marker_path: String },
+    VersionMarker {
+        marker_path: String,
+        /// Most common first; empty when the marker's values weren't tracked
+        versions: Vec<VersionShare>,
+        /// Filled in from the documents by `crate::version::compare_versions`
+        differences: Vec<VersionDifference>,
+    },
     /// Deprecated or legacy naming patters
     DeprecatedNaming { old_path: String, new_path: String },
     /// Mutually exclusive field
@@ -105,6 +436,125 @@ impl DriftIssue {
             DriftIssue::GhostKey { .. } => Severity::Info,
             DriftIssue::SparseField { .. } => Severity::Info,
             DriftIssue::SchemaEvolution { .. } => Severity::Warning,
+            DriftIssue::MixedFormat {
+                minority_percentage,
+                ..
+            } => {
+                if *minority_percentage >= 10.0 {
+                    Severity::Warning
+                } else {
+                    Severity::Info
+                }
+            }
+            DriftIssue::ConstantField { .. } => Severity::Info,
+            DriftIssue::NumericString { .. } => Severity::Warning,
+            DriftIssue::EmptyString { .. } => Severity::Warning,
+            DriftIssue::KeyNamingConflict { .. } => Severity::Warning,
+            DriftIssue::ProbableRename { confirmed, .. } => {
+                if *confirmed {
+                    Severity::Warning
+                } else {
+                    Severity::Info
+                }
+            }
+            DriftIssue::TupleArray { .. } => Severity::Info,
+            DriftIssue::ExcessiveDepth { .. } => Severity::Warning,
+            DriftIssue::OversizedPayload { .. } => Severity::Warning,
+            DriftIssue::ProblematicKey { .. } => Severity::Warning,
+            DriftIssue::UnitDrift { .. } => Severity::Warning,
+            DriftIssue::ConditionalField { .. } => Severity::Info,
+            DriftIssue::NullVsMissing {
+                minority_percentage,
+                ..
+            } => {
+                if *minority_percentage >= 10.0 {
+                    Severity::Warning
+                } else {
+                    Severity::Info
+                }
+            }
+            DriftIssue::DateFormatDrift {
+                minority_percentage,
+                ..
+            } => {
+                if *minority_percentage >= 10.0 {
+                    Severity::Warning
+                } else {
+                    Severity::Info
+                }
+            }
+            DriftIssue::BooleanEncoding {
+                minority_percentage,
+                ..
+            } => {
+                if *minority_percentage >= 10.0 {
+                    Severity::Warning
+                } else {
+                    Severity::Info
+                }
+            }
+            DriftIssue::Reclassified { severity, .. } => *severity,
+        }
+    }
+
+    /// Name of the kind of issue, e.g. `SparseField` (see `ISSUE_KINDS`)
+    pub fn kind(&self) -> &'static str {
+        match self {
+            DriftIssue::TypeInconsistency { .. } => "TypeInconsistency",
+            DriftIssue::GhostKey { .. } => "GhostKey",
+            DriftIssue::SparseField { .. } => "SparseField",
+            DriftIssue::MissingKey { .. } => "MissingKey",
+            DriftIssue::SchemaEvolution { .. } => "SchemaEvolution",
+            DriftIssue::MixedFormat { .. } => "MixedFormat",
+            DriftIssue::ConstantField { .. } => "ConstantField",
+            DriftIssue::NumericString { .. } => "NumericString",
+            DriftIssue::BooleanEncoding { .. } => "BooleanEncoding",
+            DriftIssue::DateFormatDrift { .. } => "DateFormatDrift",
+            DriftIssue::EmptyString { .. } => "EmptyString",
+            DriftIssue::NullVsMissing { .. } => "NullVsMissing",
+            DriftIssue::KeyNamingConflict { .. } => "KeyNamingConflict",
+            DriftIssue::ProbableRename { .. } => "ProbableRename",
+            DriftIssue::TupleArray { .. } => "TupleArray",
+            DriftIssue::ExcessiveDepth { .. } => "ExcessiveDepth",
+            DriftIssue::OversizedPayload { .. } => "OversizedPayload",
+            DriftIssue::ProblematicKey { .. } => "ProblematicKey",
+            DriftIssue::UnitDrift { .. } => "UnitDrift",
+            DriftIssue::ConditionalField { .. } => "ConditionalField",
+            DriftIssue::Reclassified { issue, .. } => issue.kind(),
+        }
+    }
+
+    /// Report this issue at `severity` instead of its computed severity
+    pub fn reclassify(self, severity: Severity) -> DriftIssue {
+        let issue = match self {
+            DriftIssue::Reclassified { issue, .. } => issue,
+            issue => Box::new(issue),
+        };
+        if issue.severity() == severity {
+            return *issue;
+        }
+        DriftIssue::Reclassified { issue, severity }
+    }
+
+    /// Hide any sampled value carried by the issue
+    pub fn redact(&mut self, redaction: Redaction) {
+        match self {
+            DriftIssue::ConstantField { value, .. } => *value = redaction.apply(value),
+            DriftIssue::ConditionalField { values, .. } => {
+                for value in values {
+                    *value = redaction.apply(value);
+                }
+            }
+            DriftIssue::SchemaEvolution {
+                pattern: EvolutionPattern::VersionMarker { versions, .. },
+                ..
+            } => {
+                for version in versions {
+                    version.value = redaction.apply(&version.value);
+                }
+            }
+            DriftIssue::Reclassified { issue, .. } => issue.redact(redaction),
+            _ => {}
         }
     }
 
@@ -116,6 +566,22 @@ impl DriftIssue {
             DriftIssue::SparseField { path, .. } => path,
             DriftIssue::MissingKey { path, .. } => path,
             DriftIssue::SchemaEvolution { path, .. } => path,
+            DriftIssue::MixedFormat { path, .. } => path,
+            DriftIssue::ConstantField { path, .. } => path,
+            DriftIssue::NumericString { path, .. } => path,
+            DriftIssue::BooleanEncoding { path, .. } => path,
+            DriftIssue::DateFormatDrift { path, .. } => path,
+            DriftIssue::EmptyString { path, .. } => path,
+            DriftIssue::NullVsMissing { path, .. } => path,
+            DriftIssue::KeyNamingConflict { path, .. } => path,
+            DriftIssue::ProbableRename { path, .. } => path,
+            DriftIssue::TupleArray { path, .. } => path,
+            DriftIssue::ExcessiveDepth { path, .. } => path,
+            DriftIssue::OversizedPayload { path, .. } => path,
+            DriftIssue::ProblematicKey { path, .. } => path,
+            DriftIssue::UnitDrift { path, .. } => path,
+            DriftIssue::ConditionalField { path, .. } => path,
+            DriftIssue::Reclassified { issue, .. } => issue.path(),
         }
     }
 
@@ -128,7 +594,11 @@ impl DriftIssue {
                 ..
             } => {
                 let mut type_list: Vec<_> = types.values().collect();
-                type_list.sort_by(|a, b| b.percentage.partial_cmp(&a.percentage).unwrap());
+                type_list.sort_by(|a, b| {
+                    b.percentage
+                        .total_cmp(&a.percentage)
+                        .then_with(|| a.json_type.to_string().cmp(&b.json_type.to_string()))
+                });
                 let type_sts: Vec<String> = type_list
                     .iter()
                     .map(|td| format!("{}:{:.1}", td.json_type, td.percentage))
@@ -143,11 +613,13 @@ impl DriftIssue {
                 density,
                 occurunces,
                 total_samples,
+                margin,
                 ..
             } => {
                 format!(
-                    "Ghost key: {:.2}% present ({}/{} samples)",
+                    "Ghost key: {:.2}% ±{:.2}% present ({}/{} samples)",
                     density * 100.0,
+                    margin * 100.0,
                     occurunces,
                     total_samples
                 )
@@ -156,11 +628,13 @@ impl DriftIssue {
                 density,
                 occurrences,
                 total_samples,
+                margin,
                 ..
             } => {
                 format!(
-                    "Sparse field: {:.2}% present ({}/{} samples)",
+                    "Sparse field: {:.2}% ±{:.2}% present ({}/{} samples)",
                     density * 100.0,
+                    margin * 100.0,
                     occurrences,
                     total_samples
                 )
@@ -169,19 +643,47 @@ impl DriftIssue {
                 density,
                 actual_occurrences,
                 expected_occurrences,
+                margin,
                 ..
             } => {
                 let missing_count = expected_occurrences - actual_occurrences;
                 let missing_percentage = (1.0 - density) * 100.0;
                 format!(
-                    "Missing key: {:.2}% missing ({}/{} samples missing field)",
-                    missing_percentage, missing_count, expected_occurrences
+                    "Missing key: {:.2}% ±{:.2}% missing ({}/{} samples missing field)",
+                    missing_percentage,
+                    margin * 100.0,
+                    missing_count,
+                    expected_occurrences
                 )
             }
 
             DriftIssue::SchemaEvolution { pattern, .. } => match pattern {
-                EvolutionPattern::VersionMarker { marker_path } => {
-                    format!("Schema evolution: version marker '{}'", marker_path)
+                EvolutionPattern::VersionMarker {
+                    marker_path,
+                    versions,
+                    differences,
+                } => {
+                    let mut description =
+                        format!("Schema evolution: version marker '{}'", marker_path);
+                    if !versions.is_empty() {
+                        let mut shares: Vec<String> = versions
+                            .iter()
+                            .take(MAX_LISTED_VERSIONS)
+                            .map(|v| format!("{}: {:.1}%", v.value, v.percentage))
+                            .collect();
+                        if versions.len() > MAX_LISTED_VERSIONS {
+                            shares.push(format!("{} more", versions.len() - MAX_LISTED_VERSIONS));
+                        }
+                        description.push_str(&format!(" ({})", shares.join(", ")));
+                    }
+                    match differences.len() {
+                        0 => {}
+                        1 => description.push_str(", 1 field differs between versions"),
+                        n => {
+                            description.push_str(&format!(", {} fields differ between versions", n))
+                        }
+                    }
+                    description
                 }
                 EvolutionPattern::DeprecatedNaming { old_path, new_path } => {
                     format!(
@@ -196,6 +698,227 @@ impl DriftIssue {
                     )
                 }
             },
+            DriftIssue::MixedFormat {
+                dominant,
+                formats,
+                minority_percentage,
+                ..
+            } => {
+                let total: u64 = formats.values().sum();
+                let mut format_list: Vec<_> = formats.iter().collect();
+                format_list.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+                let format_strs: Vec<String> = format_list
+                    .iter()
+                    .map(|(f, count)| format!("{}:{:.1}", f, **count as f64 / total as f64 * 100.0))
+                    .collect();
+                format!(
+                    "Mixed formats (mostly {}, {:.1}% other: {})",
+                    dominant,
+                    minority_percentage,
+                    format_strs.join(", ")
+                )
+            }
+            DriftIssue::ConstantField {
+                value, occurrences, ..
+            } => {
+                format!("Constant field: always {} ({} samples)", value, occurrences)
+            }
+            DriftIssue::NumericString {
+                numeric_count,
+                string_count,
+                numeric_percentage,
+                ..
+            } => {
+                format!(
+                    "Numbers stored as strings: {:.1}% numeric ({}/{} strings) - store JSON numbers, or cast with ::numeric when querying",
+                    numeric_percentage, numeric_count, string_count
+                )
+            }
+            DriftIssue::BooleanEncoding {
+                dominant,
+                encodings,
+                minority_percentage,
+                ..
+            } => {
+                let total: u64 = encodings.values().sum();
+                let mut encoding_list: Vec<_> = encodings.iter().collect();
+                encoding_list.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+                let encoding_strs: Vec<String> = encoding_list
+                    .iter()
+                    .map(|(e, count)| format!("{}:{:.1}", e, **count as f64 / total as f64 * 100.0))
+                    .collect();
+                format!(
+                    "Mixed boolean encodings (mostly {}, {:.1}% other: {})",
+                    dominant,
+                    minority_percentage,
+                    encoding_strs.join(", ")
+                )
+            }
+            DriftIssue::DateFormatDrift {
+                formats, suggested, ..
+            } => {
+                let total: u64 = formats.values().sum();
+                let mut format_list: Vec<_> = formats.iter().collect();
+                format_list.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+                let format_strs: Vec<String> = format_list
+                    .iter()
+                    .map(|(f, count)| format!("{}:{:.1}", f, **count as f64 / total as f64 * 100.0))
+                    .collect();
+                format!(
+                    "Mixed date formats ({}) - normalize to {}",
+                    format_strs.join(", "),
+                    suggested
+                )
+            }
+            DriftIssue::EmptyString {
+                empty_count,
+                null_count,
+                missing_count,
+                total_samples,
+                ..
+            } => {
+                format!(
+                    "Empty strings alongside null/missing: {} empty, {} null, {} missing ({} samples)",
+                    empty_count, null_count, missing_count, total_samples
+                )
+            }
+            DriftIssue::NullVsMissing {
+                null_count,
+                missing_count,
+                ..
+            } => {
+                format!(
+                    "Null vs missing: explicit null in {} samples, key absent in {}",
+                    null_count, missing_count
+                )
+            }
+            DriftIssue::KeyNamingConflict { variants, .. } => {
+                let variant_strs: Vec<String> = variants
+                    .iter()
+                    .map(|v| format!("{} ({:.1}%)", v.path, v.density * 100.0))
+                    .collect();
+                format!("Key naming conflict: {}", variant_strs.join(" vs "))
+            }
+            DriftIssue::ProbableRename {
+                old_path,
+                new_path,
+                old_density,
+                new_density,
+                confirmed,
+                ..
+            } => {
+                format!(
+                    "Probable rename: '{}' ({:.1}%) → '{}' ({:.1}%), keep '{}'{}",
+                    old_path,
+                    old_density * 100.0,
+                    new_path,
+                    new_density * 100.0,
+                    new_path,
+                    if *confirmed {
+                        ""
+                    } else {
+                        " (unconfirmed, the documents weren't checked for both keys)"
+                    }
+                )
+            }
+            DriftIssue::TupleArray {
+                positions, arrays, ..
+            } => {
+                let position_strs: Vec<String> = positions
+                    .iter()
+                    .enumerate()
+                    .map(|(i, types)| {
+                        let total: u64 = types.values().sum();
+                        let (json_type, count) = types
+                            .iter()
+                            .map(|(t, c)| (t.to_string(), *c))
+                            // Ties go to the first type by name
+                            .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0)))
+                            .unwrap_or_default();
+                        format!(
+                            "[{}] {}:{:.1}",
+                            i,
+                            json_type,
+                            count as f64 / total.max(1) as f64 * 100.0
+                        )
+                    })
+                    .collect();
+                format!(
+                    "Tuple encoded as array ({} arrays: {}) - use an object with named keys",
+                    arrays,
+                    position_strs.join(", ")
+                )
+            }
+            DriftIssue::ExcessiveDepth { deepest, limit, .. } => {
+                format!(
+                    "Excessive nesting: paths below reach depth {} (limit {})",
+                    deepest, limit
+                )
+            }
+            DriftIssue::OversizedPayload {
+                avg_bytes,
+                max_bytes,
+                blob_count,
+                string_count,
+                bytes_per_document,
+                ..
+            } => {
+                let blobs = if *blob_count > 0 {
+                    format!(", {}/{} look like blobs", blob_count, string_count)
+                } else {
+                    String::new()
+                };
+                format!(
+                    "Oversized payload: avg {}, max {}{}; ~{} per million rows - move it out of JSONB",
+                    human_bytes(*avg_bytes),
+                    human_bytes(*max_bytes as f64),
+                    blobs,
+                    human_bytes(bytes_per_document * 1_000_000.0)
+                )
+            }
+            DriftIssue::ProblematicKey {
+                key,
+                problems,
+                occurrences,
+                ..
+            } => {
+                let problem_strs: Vec<String> = problems.iter().map(|p| p.to_string()).collect();
+                format!(
+                    "Problematic key {:?}: {} ({} samples)",
+                    key,
+                    problem_strs.join(", "),
+                    occurrences
+                )
+            }
+            DriftIssue::UnitDrift {
+                low_median,
+                high_median,
+                high_percentage,
+                factor,
+                ..
+            } => {
+                format!(
+                    "Probable unit drift: values cluster around {} and {} (~{}x, {:.1}% in the upper band)",
+                    low_median, high_median, factor, high_percentage
+                )
+            }
+            DriftIssue::ConditionalField {
+                condition_path,
+                values,
+                density_when,
+                density_otherwise,
+                ..
+            } => {
+                let value_strs: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+                format!(
+                    "Conditional field: present in {:.1}% of documents where {} is {} ({:.1}% otherwise)",
+                    density_when * 100.0,
+                    condition_path,
+                    value_strs.join(" or "),
+                    density_otherwise * 100.0
+                )
+            }
+            DriftIssue::Reclassified { issue, .. } => issue.description(),
         }
     }
 }
@@ -221,6 +944,31 @@ pub struct DriftConfig {
     pub missing_key_threshold: f64,
     /// Whether to detect schema evolution patterns
     pub detect_schema_evolution: bool,
+    /// Which schema evolution patterns are looked for, and how
+    pub schema_evolution: SchemaEvolutionConfig,
+    /// Minimum percentage of strings outside the dominant format to report mixed formats (default: 2.0%)
+    pub format_inconsistency_threshold: f64,
+    /// Minimum occurrences before a single-valued field is reported as constant (default: 10)
+    pub constant_field_min_occurrences: u64,
+    /// Minimum percentage of a path's strings that are numeric to report numbers stored as strings (default: 95.0%)
+    pub numeric_string_threshold: f64,
+    /// Minimum percentage of occurrences that are `""` to report empty strings next to null/missing (default: 5.0%)
+    pub empty_string_threshold: f64,
+    /// Minimum percentage of the rarer of null and absent to report null vs missing (default: 5.0%)
+    pub null_missing_threshold: f64,
+    /// Minimum share of documents two similarly named keys must cover together to report a rename (default: 0.90 = 90%)
+    pub rename_coverage_threshold: f64,
+    /// Maximum share of documents holding both keys of a rename (default: 0.01 = 1%)
+    pub rename_max_overlap: f64,
+    /// Minimum arrays of one fixed length before position-wise types are reported as a tuple (default: 10)
+    pub tuple_min_arrays: u64,
+    /// Deepest nesting allowed before paths are reported as excessively deep (default: 8)
+    pub max_nesting_depth: usize,
+    /// Average string size in bytes from which a path is reported as an oversized payload (default: 2048)
+    pub oversized_string_bytes: u64,
+    /// Confidence level of the density intervals that ghost key, sparse field and
+    /// missing key detection threshold on (default: 0.95); 0 uses the point estimate
+    pub density_confidence: f64,
 }
 
 impl Default for DriftConfig {
@@ -231,39 +979,478 @@ impl Default for DriftConfig {
             sparse_field_threshold: 0.80,
             missing_key_threshold: 0.95,
             detect_schema_evolution: true,
+            schema_evolution: SchemaEvolutionConfig::default(),
+            format_inconsistency_threshold: 2.0,
+            constant_field_min_occurrences: 10,
+            numeric_string_threshold: 95.0,
+            empty_string_threshold: 5.0,
+            null_missing_threshold: 5.0,
+            rename_coverage_threshold: 0.90,
+            rename_max_overlap: 0.01,
+            tuple_min_arrays: 10,
+            max_nesting_depth: 8,
+            oversized_string_bytes: 2048,
+            density_confidence: 0.95,
         }
     }
 }
 
-/// Analyze field statistics and detect drift
-pub fn detect_drift(stats: &HashMap<String, FieldStats>, config: &DriftConfig) -> Vec<DriftIssue> {
-    let mut issues = Vec::new();
-    for field_stats in stats.values() {
-        if let Some(issue) = detect_type_inconsistency(field_stats, config) {
-            issues.push(issue);
-        }
-        if let Some(issue) = detect_ghost_key(field_stats, config) {
-            issues.push(issue);
-        }
-        if let Some(issue) = detect_sparse_field(field_stats, config) {
-            issues.push(issue);
-        }
-        if let Some(issue) = detect_missing_key(field_stats, config) {
-            issues.push(issue);
+/// Names of the schema evolution patterns, as used to disable them
+pub const EVOLUTION_PATTERNS: &[&str] =
+    &["version_marker", "deprecated_naming", "mutually_exclusive"];
+
+/// Heuristics behind schema evolution detection
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaEvolutionConfig {
+    /// Report keys marking a schema version (default: true)
+    pub version_markers: bool,
+    /// Key names taken as version markers, ignoring ASCII case
+    /// (default: `version`, `schema_version`, `v`, `api_version`)
+    pub version_keys: Vec<String>,
+    /// Report old copies of renamed fields (default: true)
+    pub deprecated_naming: bool,
+    /// Key prefixes marking the old copy (default: `old_`, `legacy_`, `deprecated_`)
+    pub deprecated_prefixes: Vec<String>,
+    /// Key suffixes marking the old copy (default: `_old`, `_legacy`, `_deprecated`)
+    pub deprecated_suffixes: Vec<String>,
+    /// Report variants of one field that never appear together (default: true)
+    pub mutually_exclusive: bool,
+    /// Key suffixes marking a variant, `#` standing for any number
+    /// (default: `_v#`, `_#`, `_old`, `_new`)
+    pub variant_suffixes: Vec<String>,
+    /// Lowest density every field of a pattern needs (default: 0.01)
+    pub min_density: f64,
+}
+
+impl Default for SchemaEvolutionConfig {
+    fn default() -> Self {
+        let strings = |values: &[&str]| values.iter().map(|v| v.to_string()).collect();
+        Self {
+            version_markers: true,
+            version_keys: strings(&["version", "schema_version", "v", "api_version"]),
+            deprecated_naming: true,
+            deprecated_prefixes: strings(&["old_", "legacy_", "deprecated_"]),
+            deprecated_suffixes: strings(&["_old", "_legacy", "_deprecated"]),
+            mutually_exclusive: true,
+            variant_suffixes: strings(&["_v#", "_#", "_old", "_new"]),
+            min_density: 0.01,
         }
     }
+}
 
-    if config.detect_schema_evolution {
-        issues.extend(detect_schema_evolution(stats));
+impl SchemaEvolutionConfig {
+    /// Stop looking for one of the `EVOLUTION_PATTERNS`; unknown names are ignored
+    pub fn disable(&mut self, pattern: &str) {
+        match pattern {
+            "version_marker" => self.version_markers = false,
+            "deprecated_naming" => self.deprecated_naming = false,
+            "mutually_exclusive" => self.mutually_exclusive = false,
+            _ => {}
+        }
     }
+}
 
-    issues.sort_by(|a, b| {
-        b.severity()
-            .cmp(&a.severity())
-            .then_with(|| a.path().cmp(b.path()))
-    });
-
-    issues
+/// Analyze field statistics and detect drift with the built-in detectors
+///
+/// Probable renames are unconfirmed candidates: statistics can't tell
+/// whether two keys share documents. Pass the issues through
+/// `confirm_renames` with the analyzed documents to check them.
+pub fn detect_drift(stats: &HashMap<String, FieldStats>, config: &DriftConfig) -> Vec<DriftIssue> {
+    DriftDetectors::builtin().detect(stats, config)
+}
+
+/// A check that turns field statistics into drift issues
+///
+/// Most checks look at one path at a time and only implement `detect_field`;
+/// checks comparing paths (like schema evolution) override `detect`.
+pub trait DriftDetector: Send + Sync {
+    /// Name used to enable or disable the detector, e.g. `sparse_field`
+    fn name(&self) -> &str;
+
+    /// Issues found across all paths
+    fn detect(&self, stats: &HashMap<String, FieldStats>, config: &DriftConfig) -> Vec<DriftIssue> {
+        stats
+            .values()
+            .filter_map(|field_stats| self.detect_field(field_stats, config))
+            .collect()
+    }
+
+    /// Issue found at a single path, if any
+    fn detect_field(&self, _stats: &FieldStats, _config: &DriftConfig) -> Option<DriftIssue> {
+        None
+    }
+}
+
+/// Names of the built-in detectors, in the order they run
+pub const BUILTIN_DETECTORS: &[&str] = &[
+    "type_inconsistency",
+    "ghost_key",
+    "sparse_field",
+    "missing_key",
+    "mixed_format",
+    "constant_field",
+    "numeric_string",
+    "boolean_encoding",
+    "date_format",
+    "empty_string",
+    "null_vs_missing",
+    "key_naming",
+    "rename",
+    "tuple_array",
+    "excessive_depth",
+    "oversized_payload",
+    "problematic_key",
+    "unit_drift",
+    "schema_evolution",
+];
+
+/// The set of detectors a drift analysis runs
+pub struct DriftDetectors {
+    detectors: Vec<Box<dyn DriftDetector>>,
+}
+
+impl Default for DriftDetectors {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl DriftDetectors {
+    /// No detectors at all - register your own
+    pub fn empty() -> Self {
+        Self {
+            detectors: Vec::new(),
+        }
+    }
+
+    /// All built-in detectors (see `BUILTIN_DETECTORS`)
+    pub fn builtin() -> Self {
+        let mut detectors = Self::empty();
+        detectors.register(TypeInconsistencyDetector);
+        detectors.register(GhostKeyDetector);
+        detectors.register(SparseFieldDetector);
+        detectors.register(MissingKeyDetector);
+        detectors.register(MixedFormatDetector);
+        detectors.register(ConstantFieldDetector);
+        detectors.register(NumericStringDetector);
+        detectors.register(BooleanEncodingDetector);
+        detectors.register(DateFormatDetector);
+        detectors.register(EmptyStringDetector);
+        detectors.register(NullVsMissingDetector);
+        detectors.register(KeyNamingDetector);
+        detectors.register(RenameDetector);
+        detectors.register(TupleArrayDetector);
+        detectors.register(ExcessiveDepthDetector);
+        detectors.register(OversizedPayloadDetector);
+        detectors.register(ProblematicKeyDetector);
+        detectors.register(UnitDriftDetector);
+        detectors.register(SchemaEvolutionDetector);
+        detectors
+    }
+
+    /// Add a detector; it runs after the ones already registered
+    pub fn register(&mut self, detector: impl DriftDetector + 'static) {
+        self.detectors.push(Box::new(detector));
+    }
+
+    /// Remove the detector with this name, returning whether there was one
+    pub fn disable(&mut self, name: &str) -> bool {
+        let before = self.detectors.len();
+        self.detectors.retain(|d| d.name() != name);
+        self.detectors.len() < before
+    }
+
+    /// Names of the registered detectors
+    pub fn names(&self) -> Vec<&str> {
+        self.detectors.iter().map(|d| d.name()).collect()
+    }
+
+    /// Run every detector, most severe issues first
+    ///
+    /// As with `detect_drift`, probable renames still need `confirm_renames`.
+    pub fn detect(
+        &self,
+        stats: &HashMap<String, FieldStats>,
+        config: &DriftConfig,
+    ) -> Vec<DriftIssue> {
+        let mut issues: Vec<DriftIssue> = self
+            .detectors
+            .iter()
+            .flat_map(|detector| detector.detect(stats, config))
+            .collect();
+        sort_issues(&mut issues);
+        issues
+    }
+}
+
+/// Fields with several JSON types, see `DriftIssue::TypeInconsistency`
+pub struct TypeInconsistencyDetector;
+
+impl DriftDetector for TypeInconsistencyDetector {
+    fn name(&self) -> &str {
+        "type_inconsistency"
+    }
+
+    fn detect_field(&self, stats: &FieldStats, config: &DriftConfig) -> Option<DriftIssue> {
+        detect_type_inconsistency(stats, config)
+    }
+}
+
+/// Fields in very few documents, see `DriftIssue::GhostKey`
+pub struct GhostKeyDetector;
+
+impl DriftDetector for GhostKeyDetector {
+    fn name(&self) -> &str {
+        "ghost_key"
+    }
+
+    fn detect_field(&self, stats: &FieldStats, config: &DriftConfig) -> Option<DriftIssue> {
+        detect_ghost_key(stats, config)
+    }
+}
+
+/// Optional fields, see `DriftIssue::SparseField`
+pub struct SparseFieldDetector;
+
+impl DriftDetector for SparseFieldDetector {
+    fn name(&self) -> &str {
+        "sparse_field"
+    }
+
+    fn detect_field(&self, stats: &FieldStats, config: &DriftConfig) -> Option<DriftIssue> {
+        detect_sparse_field(stats, config)
+    }
+}
+
+/// Nearly-required fields with gaps, see `DriftIssue::MissingKey`
+pub struct MissingKeyDetector;
+
+impl DriftDetector for MissingKeyDetector {
+    fn name(&self) -> &str {
+        "missing_key"
+    }
+
+    fn detect_field(&self, stats: &FieldStats, config: &DriftConfig) -> Option<DriftIssue> {
+        detect_missing_key(stats, config)
+    }
+}
+
+/// Strings mixing formats, see `DriftIssue::MixedFormat`
+pub struct MixedFormatDetector;
+
+impl DriftDetector for MixedFormatDetector {
+    fn name(&self) -> &str {
+        "mixed_format"
+    }
+
+    fn detect_field(&self, stats: &FieldStats, config: &DriftConfig) -> Option<DriftIssue> {
+        detect_mixed_format(stats, config)
+    }
+}
+
+/// Fields that never vary, see `DriftIssue::ConstantField`
+pub struct ConstantFieldDetector;
+
+impl DriftDetector for ConstantFieldDetector {
+    fn name(&self) -> &str {
+        "constant_field"
+    }
+
+    fn detect_field(&self, stats: &FieldStats, config: &DriftConfig) -> Option<DriftIssue> {
+        detect_constant_field(stats, config)
+    }
+}
+
+/// Numbers kept in strings, see `DriftIssue::NumericString`
+pub struct NumericStringDetector;
+
+impl DriftDetector for NumericStringDetector {
+    fn name(&self) -> &str {
+        "numeric_string"
+    }
+
+    fn detect_field(&self, stats: &FieldStats, config: &DriftConfig) -> Option<DriftIssue> {
+        detect_numeric_string(stats, config)
+    }
+}
+
+/// Booleans written several ways, see `DriftIssue::BooleanEncoding`
+pub struct BooleanEncodingDetector;
+
+impl DriftDetector for BooleanEncodingDetector {
+    fn name(&self) -> &str {
+        "boolean_encoding"
+    }
+
+    fn detect_field(&self, stats: &FieldStats, _config: &DriftConfig) -> Option<DriftIssue> {
+        detect_boolean_encoding(stats)
+    }
+}
+
+/// Dates written several ways, see `DriftIssue::DateFormatDrift`
+pub struct DateFormatDetector;
+
+impl DriftDetector for DateFormatDetector {
+    fn name(&self) -> &str {
+        "date_format"
+    }
+
+    fn detect_field(&self, stats: &FieldStats, _config: &DriftConfig) -> Option<DriftIssue> {
+        detect_date_format(stats)
+    }
+}
+
+/// Several ways to say nothing, see `DriftIssue::EmptyString`
+pub struct EmptyStringDetector;
+
+impl DriftDetector for EmptyStringDetector {
+    fn name(&self) -> &str {
+        "empty_string"
+    }
+
+    fn detect_field(&self, stats: &FieldStats, config: &DriftConfig) -> Option<DriftIssue> {
+        detect_empty_string(stats, config)
+    }
+}
+
+/// Producers disagreeing on null vs omit, see `DriftIssue::NullVsMissing`
+pub struct NullVsMissingDetector;
+
+impl DriftDetector for NullVsMissingDetector {
+    fn name(&self) -> &str {
+        "null_vs_missing"
+    }
+
+    fn detect_field(&self, stats: &FieldStats, config: &DriftConfig) -> Option<DriftIssue> {
+        detect_null_vs_missing(stats, config)
+    }
+}
+
+/// Keys spelled several ways, see `DriftIssue::KeyNamingConflict`
+pub struct KeyNamingDetector;
+
+impl DriftDetector for KeyNamingDetector {
+    fn name(&self) -> &str {
+        "key_naming"
+    }
+
+    fn detect(
+        &self,
+        stats: &HashMap<String, FieldStats>,
+        _config: &DriftConfig,
+    ) -> Vec<DriftIssue> {
+        detect_key_naming(stats)
+    }
+}
+
+/// Renamed keys, see `DriftIssue::ProbableRename`
+///
+/// Reports unconfirmed candidates, to be checked with `confirm_renames`.
+pub struct RenameDetector;
+
+impl DriftDetector for RenameDetector {
+    fn name(&self) -> &str {
+        "rename"
+    }
+
+    fn detect(&self, stats: &HashMap<String, FieldStats>, config: &DriftConfig) -> Vec<DriftIssue> {
+        detect_renames(stats, config)
+    }
+}
+
+/// Tuples stored as arrays, see `DriftIssue::TupleArray`
+pub struct TupleArrayDetector;
+
+impl DriftDetector for TupleArrayDetector {
+    fn name(&self) -> &str {
+        "tuple_array"
+    }
+
+    fn detect_field(&self, stats: &FieldStats, config: &DriftConfig) -> Option<DriftIssue> {
+        detect_tuple_array(stats, config)
+    }
+}
+
+/// Deeply nested subtrees, see `DriftIssue::ExcessiveDepth`
+pub struct ExcessiveDepthDetector;
+
+impl DriftDetector for ExcessiveDepthDetector {
+    fn name(&self) -> &str {
+        "excessive_depth"
+    }
+
+    fn detect(&self, stats: &HashMap<String, FieldStats>, config: &DriftConfig) -> Vec<DriftIssue> {
+        detect_excessive_depth(stats, config)
+    }
+}
+
+/// Large strings and blobs, see `DriftIssue::OversizedPayload`
+pub struct OversizedPayloadDetector;
+
+impl DriftDetector for OversizedPayloadDetector {
+    fn name(&self) -> &str {
+        "oversized_payload"
+    }
+
+    fn detect_field(&self, stats: &FieldStats, config: &DriftConfig) -> Option<DriftIssue> {
+        detect_oversized_payload(stats, config)
+    }
+}
+
+/// Keys that break path expressions, see `DriftIssue::ProblematicKey`
+pub struct ProblematicKeyDetector;
+
+impl DriftDetector for ProblematicKeyDetector {
+    fn name(&self) -> &str {
+        "problematic_key"
+    }
+
+    fn detect_field(&self, stats: &FieldStats, _config: &DriftConfig) -> Option<DriftIssue> {
+        detect_problematic_key(stats)
+    }
+}
+
+/// Numbers in mixed units, see `DriftIssue::UnitDrift`
+pub struct UnitDriftDetector;
+
+impl DriftDetector for UnitDriftDetector {
+    fn name(&self) -> &str {
+        "unit_drift"
+    }
+
+    fn detect_field(&self, stats: &FieldStats, _config: &DriftConfig) -> Option<DriftIssue> {
+        detect_unit_drift(stats)
+    }
+}
+
+/// Version markers and renamed fields, see `DriftIssue::SchemaEvolution`
+pub struct SchemaEvolutionDetector;
+
+impl DriftDetector for SchemaEvolutionDetector {
+    fn name(&self) -> &str {
+        "schema_evolution"
+    }
+
+    fn detect(&self, stats: &HashMap<String, FieldStats>, config: &DriftConfig) -> Vec<DriftIssue> {
+        if config.detect_schema_evolution {
+            detect_schema_evolution(stats, &config.schema_evolution)
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+/// Order issues most severe first, then by path, then by description
+///
+/// Detectors walk a `HashMap`, so without the last tie-breaker issues on the
+/// same path could come out in a different order on every run.
+pub fn sort_issues(issues: &mut [DriftIssue]) {
+    issues.sort_by(|a, b| {
+        b.severity()
+            .cmp(&a.severity())
+            .then_with(|| a.path().cmp(b.path()))
+            .then_with(|| a.description().cmp(&b.description()))
+    });
 }
 
 /// Detect type inconsistency: field appears as multiple types
@@ -310,14 +1497,20 @@ fn detect_type_inconsistency(stats: &FieldStats, config: &DriftConfig) -> Option
     }
 }
 
+// The density detectors only fire when the whole confidence interval of the
+// density lies in their band, so a sampled 94% that could well be 96% in the
+// table isn't reported as a missing key. Densities in between are left alone.
+
 /// Detect ghost keys: fields with very low density
 fn detect_ghost_key(stats: &FieldStats, config: &DriftConfig) -> Option<DriftIssue> {
-    if stats.density <= config.ghost_key_threshold && stats.density > 0.0 {
+    let bounds = stats.density_bounds(config.density_confidence);
+    if bounds.upper <= config.ghost_key_threshold && stats.density > 0.0 {
         Some(DriftIssue::GhostKey {
             path: stats.path.clone(),
             density: stats.density,
             occurunces: stats.occurrences,
             total_samples: stats.total_samples,
+            margin: bounds.margin(stats.density),
         })
     } else {
         None
@@ -327,114 +1520,742 @@ fn detect_ghost_key(stats: &FieldStats, config: &DriftConfig) -> Option<DriftIss
 /// Detect sparse fields: optional fields with moderate presence (10-80%)
 fn detect_sparse_field(stats: &FieldStats, config: &DriftConfig) -> Option<DriftIssue> {
     // Fields between ghost threshold and sparse threshold
-    if stats.density > config.ghost_key_threshold && stats.density <= config.sparse_field_threshold
-    {
+    let bounds = stats.density_bounds(config.density_confidence);
+    if bounds.lower > config.ghost_key_threshold && bounds.upper <= config.sparse_field_threshold {
         Some(DriftIssue::SparseField {
             path: stats.path.clone(),
             density: stats.density,
             occurrences: stats.occurrences,
             total_samples: stats.total_samples,
+            margin: bounds.margin(stats.density),
+        })
+    } else {
+        None
+    }
+}
+
+/// Detect missing keys: expected fields (high density) with gaps (80-95%)
+fn detect_missing_key(stats: &FieldStats, config: &DriftConfig) -> Option<DriftIssue> {
+    // Only check fields that should be present (density between sparse and missing thresholds)
+    let bounds = stats.density_bounds(config.density_confidence);
+    if bounds.lower > config.sparse_field_threshold && bounds.upper < config.missing_key_threshold {
+        let expected_occurrences = stats.total_samples;
+        Some(DriftIssue::MissingKey {
+            path: stats.path.clone(),
+            density: stats.density,
+            expected_occurrences,
+            actual_occurrences: stats.occurrences,
+            margin: bounds.margin(stats.density),
+        })
+    } else {
+        None
+    }
+}
+
+/// Detect mixed formats: strings that are mostly one recognizable format
+/// (uuid, date, email, ...) but not always
+fn detect_mixed_format(stats: &FieldStats, config: &DriftConfig) -> Option<DriftIssue> {
+    let format = stats.format.as_ref()?;
+
+    // Free text has no format to drift from
+    if format.format == StringFormat::Text || stats.formats.len() < 2 {
+        return None;
+    }
+
+    let minority_percentage = 100.0 - format.consistency;
+    if minority_percentage >= config.format_inconsistency_threshold {
+        Some(DriftIssue::MixedFormat {
+            path: stats.path.clone(),
+            dominant: format.format,
+            formats: stats.formats.clone(),
+            minority_percentage,
+        })
+    } else {
+        None
+    }
+}
+
+/// Detect constant fields: the same scalar value in every occurrence
+fn detect_constant_field(stats: &FieldStats, config: &DriftConfig) -> Option<DriftIssue> {
+    let value = stats.constant_value.as_ref()?;
+    if stats.occurrences < config.constant_field_min_occurrences {
+        return None;
+    }
+
+    Some(DriftIssue::ConstantField {
+        path: stats.path.clone(),
+        value: value.clone(),
+        occurrences: stats.occurrences,
+    })
+}
+
+/// Detect numbers stored as strings: string values that are nearly all numeric
+///
+/// Type inconsistency misses these when every value is a string. Paths with
+/// leading zeros in their examples (zip codes, phone numbers) are identifiers
+/// rather than numbers and are left alone.
+fn detect_numeric_string(stats: &FieldStats, config: &DriftConfig) -> Option<DriftIssue> {
+    let string_count: u64 = stats.formats.values().sum();
+    let numeric_count = stats
+        .formats
+        .get(&StringFormat::Numeric)
+        .copied()
+        .unwrap_or(0);
+    if numeric_count == 0 {
+        return None;
+    }
+
+    let has_leading_zero = stats.examples.iter().any(|example| {
+        example.as_str().is_some_and(|s| {
+            let digits = s.trim_start_matches(['-', '+']).as_bytes();
+            digits.len() > 1 && digits[0] == b'0' && digits[1].is_ascii_digit()
+        })
+    });
+    if has_leading_zero {
+        return None;
+    }
+
+    let numeric_percentage = numeric_count as f64 / string_count as f64 * 100.0;
+    if numeric_percentage >= config.numeric_string_threshold {
+        Some(DriftIssue::NumericString {
+            path: stats.path.clone(),
+            numeric_count,
+            string_count,
+            numeric_percentage,
+        })
+    } else {
+        None
+    }
+}
+
+/// Detect boolean encoding drift: every value reads as a boolean, but in
+/// more than one encoding
+///
+/// Works from the top values, so only enum-like paths are checked - which
+/// every boolean is.
+fn detect_boolean_encoding(stats: &FieldStats) -> Option<DriftIssue> {
+    let top_values = stats.top_values.as_ref()?;
+
+    let mut encodings: HashMap<BoolEncoding, u64> = HashMap::new();
+    for top in top_values {
+        *encodings
+            .entry(BoolEncoding::classify(&top.value)?)
+            .or_insert(0) += top.count;
+    }
+    if encodings.len() < 2 {
+        return None;
+    }
+
+    // Top values are capped, so make sure none were left out
+    let total: u64 = encodings.values().sum();
+    if total + stats.null_count < stats.occurrences {
+        return None;
+    }
+
+    let (dominant, dominant_count) = encodings
+        .iter()
+        .max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0)))
+        .map(|(encoding, count)| (*encoding, *count))?;
+    Some(DriftIssue::BooleanEncoding {
+        path: stats.path.clone(),
+        dominant,
+        encodings,
+        minority_percentage: (total - dominant_count) as f64 / total as f64 * 100.0,
+    })
+}
+
+/// Detect date format drift: a path mixing ISO 8601 strings, slash dates
+/// and epoch numbers
+///
+/// Numbers only count as epochs when the path also holds date strings and
+/// every number falls in `EPOCH_RANGE`.
+fn detect_date_format(stats: &FieldStats) -> Option<DriftIssue> {
+    let mut formats: HashMap<DateFormat, u64> = HashMap::new();
+    for (format, count) in &stats.formats {
+        let date_format = match format {
+            StringFormat::Date => DateFormat::IsoDate,
+            StringFormat::DateTime => DateFormat::IsoDateTime,
+            StringFormat::SlashDate => DateFormat::SlashDate,
+            _ => continue,
+        };
+        formats.insert(date_format, *count);
+    }
+    if formats.is_empty() {
+        return None;
+    }
+
+    let epochs = stats
+        .numeric
+        .as_ref()
+        .filter(|n| EPOCH_RANGE.contains(&n.min) && EPOCH_RANGE.contains(&n.max));
+    if let Some(numeric) = epochs {
+        formats.insert(DateFormat::Epoch, numeric.count);
+    }
+    if formats.len() < 2 {
+        return None;
+    }
+
+    let total: u64 = formats.values().sum();
+    let dominant_count = formats.values().max().copied().unwrap_or(0);
+    // Keep the time of day if any representation carries one
+    let suggested = if formats.contains_key(&DateFormat::IsoDateTime)
+        || formats.contains_key(&DateFormat::Epoch)
+    {
+        DateFormat::IsoDateTime
+    } else {
+        DateFormat::IsoDate
+    };
+
+    Some(DriftIssue::DateFormatDrift {
+        path: stats.path.clone(),
+        formats,
+        minority_percentage: (total - dominant_count) as f64 / total as f64 * 100.0,
+        suggested,
+    })
+}
+
+/// Detect empty strings used next to null or an absent key: three ways to
+/// say "no value" that a filter like `IS NOT NULL` treats differently
+fn detect_empty_string(stats: &FieldStats, config: &DriftConfig) -> Option<DriftIssue> {
+    let empty_count = stats
+        .formats
+        .get(&StringFormat::Empty)
+        .copied()
+        .unwrap_or(0);
+    let missing_count = stats.total_samples.saturating_sub(stats.occurrences);
+    if empty_count == 0 || stats.null_count + missing_count == 0 {
+        return None;
+    }
+
+    let empty_percentage = empty_count as f64 / stats.occurrences as f64 * 100.0;
+    if empty_percentage >= config.empty_string_threshold {
+        Some(DriftIssue::EmptyString {
+            path: stats.path.clone(),
+            empty_count,
+            null_count: stats.null_count,
+            missing_count,
+            total_samples: stats.total_samples,
         })
     } else {
         None
     }
 }
 
-/// Detect missing keys: expected fields (high density) with gaps (80-95%)
-fn detect_missing_key(stats: &FieldStats, config: &DriftConfig) -> Option<DriftIssue> {
-    // Only check fields that should be present (density between sparse and missing thresholds)
-    if stats.density > config.sparse_field_threshold && stats.density < config.missing_key_threshold
-    {
-        let expected_occurrences = stats.total_samples;
-        Some(DriftIssue::MissingKey {
-            path: stats.path.clone(),
-            density: stats.density,
-            expected_occurrences,
-            actual_occurrences: stats.occurrences,
+/// Detect null vs missing: a path that is explicit null in some documents
+/// and absent from others
+///
+/// Sparsity alone doesn't say whether producers agree on how to write "no
+/// value"; this compares the two. The rarer one must reach the threshold so
+/// a single stray null doesn't count.
+fn detect_null_vs_missing(stats: &FieldStats, config: &DriftConfig) -> Option<DriftIssue> {
+    let missing_count = stats.total_samples.saturating_sub(stats.occurrences);
+    if stats.null_count == 0 || missing_count == 0 {
+        return None;
+    }
+
+    let minority = stats.null_count.min(missing_count);
+    let minority_percentage = minority as f64 / (stats.null_count + missing_count) as f64 * 100.0;
+    if minority_percentage >= config.null_missing_threshold {
+        Some(DriftIssue::NullVsMissing {
+            path: stats.path.clone(),
+            null_count: stats.null_count,
+            missing_count,
+            minority_percentage,
+        })
+    } else {
+        None
+    }
+}
+
+/// Detect key naming conflicts: sibling keys that only differ by case or
+/// by `_` / `-` separators, likely written by different producers
+fn detect_key_naming(stats: &HashMap<String, FieldStats>) -> Vec<DriftIssue> {
+    // (parent path, normalized key) -> paths spelling that key
+    let mut families: HashMap<(&str, String), Vec<&FieldStats>> = HashMap::new();
+    for (path, field_stats) in stats {
+        let (parent, key) = path.rsplit_once('.').unwrap_or(("", path));
+        if key.contains(['*', '[']) {
+            continue;
+        }
+        let normalized: String = key
+            .chars()
+            .filter(|c| !matches!(c, '_' | '-'))
+            .map(|c| c.to_ascii_lowercase())
+            .collect();
+        families
+            .entry((parent, normalized))
+            .or_default()
+            .push(field_stats);
+    }
+
+    families
+        .into_values()
+        .filter(|spellings| spellings.len() >= 2)
+        .map(|mut spellings| {
+            spellings.sort_by(|a, b| {
+                b.density
+                    .partial_cmp(&a.density)
+                    .unwrap()
+                    .then_with(|| a.path.cmp(&b.path))
+            });
+            DriftIssue::KeyNamingConflict {
+                path: spellings[0].path.clone(),
+                variants: spellings
+                    .iter()
+                    .map(|s| KeyVariant {
+                        path: s.path.clone(),
+                        density: s.density,
+                    })
+                    .collect(),
+            }
+        })
+        .collect()
+}
+
+/// Detect probable renames: sibling keys with similar names whose documents
+/// don't overlap and together cover nearly every document
+///
+/// Only counts are seen here: the two keys can only be disjoint if their
+/// occurrences add up to no more than the samples, but they may still share
+/// documents, so the candidates are checked by `confirm_renames`. The denser
+/// key is taken as the current name.
+fn detect_renames(stats: &HashMap<String, FieldStats>, config: &DriftConfig) -> Vec<DriftIssue> {
+    let mut siblings: HashMap<&str, Vec<&FieldStats>> = HashMap::new();
+    for (path, field_stats) in stats {
+        let (parent, key) = path.rsplit_once('.').unwrap_or(("", path));
+        if !key.contains(['*', '[']) && field_stats.occurrences < field_stats.total_samples {
+            siblings.entry(parent).or_default().push(field_stats);
+        }
+    }
+
+    let mut issues = Vec::new();
+    for fields in siblings.values() {
+        for (i, a) in fields.iter().enumerate() {
+            for b in &fields[i + 1..] {
+                let total = a.total_samples;
+                let together = a.occurrences + b.occurrences;
+                if together > total
+                    || (together as f64) < total as f64 * config.rename_coverage_threshold
+                {
+                    continue;
+                }
+                if !similar_keys(last_key(&a.path), last_key(&b.path)) {
+                    continue;
+                }
+
+                let (old, new) = if (a.density, &b.path) < (b.density, &a.path) {
+                    (a, b)
+                } else {
+                    (b, a)
+                };
+                issues.push(DriftIssue::ProbableRename {
+                    path: old.path.clone(),
+                    old_path: old.path.clone(),
+                    new_path: new.path.clone(),
+                    old_density: old.density,
+                    new_density: new.density,
+                    confirmed: false,
+                });
+            }
+        }
+    }
+    issues
+}
+
+/// Confirm the probable renames whose keys (nearly) never share a document
+///
+/// Counts alone let through keys that overlap, e.g. `email` in 45 documents
+/// all of which also hold `email_address`. Each rename is checked against
+/// the analyzed documents and dropped if both keys are present in more than
+/// `rename_max_overlap` of them. Without the documents (server-side or
+/// streamed analysis) renames can't be checked and are kept unconfirmed, as
+/// are renames under arrays or collapsed maps. Confirmed renames are
+/// Warnings and unconfirmed ones Info, so the issues are sorted again.
+pub fn confirm_renames(
+    issues: Vec<DriftIssue>,
+    samples: &[Value],
+    config: &DriftConfig,
+) -> Vec<DriftIssue> {
+    let comparable = |path: &str| !path.contains("[]") && !is_wildcard_path(path);
+    let mut issues: Vec<DriftIssue> = issues
+        .into_iter()
+        .filter_map(|mut issue| {
+            if let DriftIssue::ProbableRename {
+                old_path,
+                new_path,
+                confirmed,
+                ..
+            } = &mut issue
+                && !samples.is_empty()
+                && comparable(old_path)
+                && comparable(new_path)
+            {
+                let both = samples
+                    .iter()
+                    .filter(|document| {
+                        lookup(document, old_path).is_some() && lookup(document, new_path).is_some()
+                    })
+                    .count();
+                if both as f64 > samples.len() as f64 * config.rename_max_overlap {
+                    return None;
+                }
+                *confirmed = true;
+            }
+            Some(issue)
+        })
+        .collect();
+    sort_issues(&mut issues);
+    issues
+}
+
+fn last_key(path: &str) -> &str {
+    path.rsplit_once('.').map_or(path, |(_, key)| key)
+}
+
+/// Whether two keys look like spellings of one name: shared words
+/// (`email` / `email_address`), a shared prefix (`zip` / `zipcode`) or a
+/// small edit distance (`adress` / `address`)
+///
+/// Keys differing only by case or separators are naming conflicts instead.
+fn similar_keys(a: &str, b: &str) -> bool {
+    let (words_a, words_b) = (key_words(a), key_words(b));
+    let (joined_a, joined_b) = (words_a.concat(), words_b.concat());
+    if joined_a == joined_b {
+        return false;
+    }
+
+    let shared = words_a.iter().filter(|w| words_b.contains(w)).count();
+    let union = words_a.len() + words_b.len() - shared;
+    if shared * 2 >= union {
+        return true;
+    }
+
+    let shorter = joined_a.len().min(joined_b.len());
+    if shorter >= 3 && (joined_a.starts_with(&joined_b) || joined_b.starts_with(&joined_a)) {
+        return true;
+    }
+
+    let longer = joined_a.len().max(joined_b.len());
+    longer >= 5 && edit_distance(&joined_a, &joined_b) as f64 / longer as f64 <= 0.25
+}
+
+/// Lowercase words of a key split on `_`, `-` and camelCase boundaries
+fn key_words(key: &str) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut previous_lower = false;
+    for c in key.chars() {
+        let boundary = c == '_' || c == '-' || (c.is_uppercase() && previous_lower);
+        if boundary && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+        if c != '_' && c != '-' {
+            current.extend(c.to_lowercase());
+        }
+        previous_lower = c.is_lowercase() || c.is_ascii_digit();
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+/// Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Share of a position's elements that must have its dominant type for the
+/// position to count as typed
+const TUPLE_POSITION_CONSISTENCY: f64 = 0.95;
+
+/// Detect tuple arrays: every array has the same length, each position has
+/// a consistent type, and not all positions share it (`["name", 42, true]`)
+fn detect_tuple_array(stats: &FieldStats, config: &DriftConfig) -> Option<DriftIssue> {
+    let array = stats.array.as_ref()?;
+    let positions = stats.position_types();
+    if array.min_length != array.max_length
+        || array.min_length < 2
+        || positions.len() != array.max_length as usize
+        || array.count < config.tuple_min_arrays
+    {
+        return None;
+    }
+
+    let mut dominant_types = Vec::new();
+    for types in positions {
+        let total: u64 = types.values().sum();
+        let (json_type, count) = types.iter().max_by_key(|(_, count)| **count)?;
+        if (*count as f64) < total as f64 * TUPLE_POSITION_CONSISTENCY {
+            return None;
+        }
+        dominant_types.push(*json_type);
+    }
+    if dominant_types.iter().all(|t| *t == dominant_types[0]) {
+        return None;
+    }
+
+    Some(DriftIssue::TupleArray {
+        path: stats.path.clone(),
+        positions: positions.to_vec(),
+        arrays: array.count,
+    })
+}
+
+/// Detect excessive depth: one issue per subtree nested beyond the limit,
+/// at its deepest ancestor still within it, rather than one per deep path
+///
+/// Deep nesting is rarely on purpose - double-encoded payloads, wrappers
+/// applied recursively - and every level makes the data harder to query.
+fn detect_excessive_depth(
+    stats: &HashMap<String, FieldStats>,
+    config: &DriftConfig,
+) -> Vec<DriftIssue> {
+    let limit = config.max_nesting_depth;
+
+    // Subtree root -> (its depth, deepest path below it)
+    let mut subtrees: HashMap<&str, (usize, usize)> = HashMap::new();
+    for deep in stats.values().filter(|s| s.depth > limit) {
+        // Nearest recorded ancestor within the limit, walking up one `.` or `[` at a time
+        let root = deep
+            .path
+            .rmatch_indices(['.', '['])
+            .filter_map(|(i, _)| stats.get(&deep.path[..i]))
+            .find(|s| s.depth <= limit)
+            .unwrap_or(deep);
+        let entry = subtrees.entry(&root.path).or_insert((root.depth, 0));
+        entry.1 = entry.1.max(deep.depth);
+    }
+
+    subtrees
+        .into_iter()
+        .map(|(path, (depth, deepest))| DriftIssue::ExcessiveDepth {
+            path: path.to_string(),
+            depth,
+            deepest,
+            limit,
+        })
+        .collect()
+}
+
+/// Detect oversized payloads: strings that are large on average, or mostly
+/// base64 / serialized documents
+///
+/// Big strings get TOASTed and compressed, make every read of the document
+/// slower, and are better kept in a separate column or object storage.
+fn detect_oversized_payload(stats: &FieldStats, config: &DriftConfig) -> Option<DriftIssue> {
+    let strings = stats.strings.as_ref()?;
+    let mostly_blobs = strings.blob_count * 2 > strings.count;
+    if strings.avg_bytes < config.oversized_string_bytes as f64 && !mostly_blobs {
+        return None;
+    }
+
+    Some(DriftIssue::OversizedPayload {
+        path: stats.path.clone(),
+        avg_bytes: strings.avg_bytes,
+        max_bytes: strings.max_bytes,
+        blob_count: strings.blob_count,
+        string_count: strings.count,
+        bytes_per_document: strings.total_bytes as f64 / stats.total_samples.max(1) as f64,
+    })
+}
+
+/// Byte count with a binary unit, e.g. `12.4 KiB`
+fn human_bytes(bytes: f64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{:.0} {}", value, UNITS[unit])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// Detect problematic keys: whitespace, control characters or characters
+/// that need quoting in `#>>` paths - almost always a producer bug
+///
+/// Only the last key of each path is checked, so a bad key is reported once
+/// rather than again for every path below it.
+fn detect_problematic_key(stats: &FieldStats) -> Option<DriftIssue> {
+    let key = last_key(&stats.path).trim_end_matches("[]");
+    if key == crate::analyzer::WILDCARD {
+        return None;
+    }
+
+    let problems = KeyProblem::find(key);
+    if problems.is_empty() {
+        return None;
+    }
+    Some(DriftIssue::ProblematicKey {
+        path: stats.path.clone(),
+        key: key.to_string(),
+        problems,
+        occurrences: stats.occurrences,
+    })
+}
+
+/// Detect unit drift: numbers in two magnitude bands whose medians are
+/// about 100x (cents vs dollars) or 1000x (ms vs s, g vs kg) apart
+fn detect_unit_drift(stats: &FieldStats) -> Option<DriftIssue> {
+    let bands = stats.numeric.as_ref()?.bands.as_ref()?;
+    let ratio = bands.ratio();
+    let factor = [100, 1000]
+        .into_iter()
+        .find(|factor| (0.5..=2.0).contains(&(ratio / *factor as f64)))?;
+
+    Some(DriftIssue::UnitDrift {
+        path: stats.path.clone(),
+        low_median: bands.low_median,
+        high_median: bands.high_median,
+        high_percentage: bands.high_share * 100.0,
+        factor,
+    })
+}
+
+/// The values of a version marker, from its tracked top values
+fn version_shares(stats: &FieldStats) -> Vec<VersionShare> {
+    let Some(top_values) = &stats.top_values else {
+        return Vec::new();
+    };
+    top_values
+        .iter()
+        .filter(|vc| !vc.value.is_null())
+        .map(|vc| VersionShare {
+            value: vc.value.clone(),
+            documents: vc.count,
+            percentage: vc.percentage,
         })
-    } else {
-        None
-    }
+        .collect()
 }
 
 /// Detect schema evolution patterns
-fn detect_schema_evolution(stats: &HashMap<String, FieldStats>) -> Vec<DriftIssue> {
-    // TODO: probably need to rework this. Too many assumptions, maybe not even relevent
+///
+/// Patterns are matched on the last key of each path, so `meta.old_theme`
+/// is the old copy of `meta.theme`. Fields rarer than `min_density` are left
+/// out, as are old copies and variants whose counterpart was never seen.
+fn detect_schema_evolution(
+    stats: &HashMap<String, FieldStats>,
+    config: &SchemaEvolutionConfig,
+) -> Vec<DriftIssue> {
     let mut issues = Vec::new();
-
-    // Check for version markers
-    let version_markers = ["version", "schema_version", "v", "api_version"];
-    for path in stats.keys() {
-        let path_segments: Vec<&str> = path.split('.').collect();
-        for marker in &version_markers {
-            // Check if any path segment exactly matches the version marker
-            if path_segments
-                .iter()
-                .any(|seg| seg.to_lowercase() == *marker)
-            {
+    let dense = |path: &str| {
+        stats
+            .get(path)
+            .is_some_and(|fs| fs.density >= config.min_density)
+    };
+    let mut paths: Vec<&String> = stats.keys().filter(|path| dense(path)).collect();
+    paths.sort();
+
+    if config.version_markers {
+        for path in &paths {
+            if path.split('.').any(|segment| {
+                config
+                    .version_keys
+                    .iter()
+                    .any(|key| segment.eq_ignore_ascii_case(key))
+            }) {
                 issues.push(DriftIssue::SchemaEvolution {
-                    path: path.clone(),
+                    path: path.to_string(),
                     pattern: EvolutionPattern::VersionMarker {
-                        marker_path: path.clone(),
+                        marker_path: path.to_string(),
+                        versions: stats.get(*path).map(version_shares).unwrap_or_default(),
+                        differences: Vec::new(),
                     },
                 });
-                break;
-            }
-        }
-    }
-
-    // Check for deprecated/legacy naming
-    let deprecated_prefixes = ["old_", "legacy_", "deprecated_"];
-    for path in stats.keys() {
-        for prefix in &deprecated_prefixes {
-            if path.to_lowercase().starts_with(prefix) {
-                // Try to find the new field (without prefix)
-                let potential_new = path.replacen(prefix, "", 1);
-                if stats.contains_key(&potential_new) {
-                    issues.push(DriftIssue::SchemaEvolution {
-                        path: path.clone(),
-                        pattern: EvolutionPattern::DeprecatedNaming {
-                            old_path: path.clone(),
-                            new_path: potential_new,
-                        },
-                    });
-                }
-                break;
             }
         }
     }
 
-    // Check for mutually exclusive fields (same base path, different variants)
-    // This is a more complex pattern - simplified version here
-    // Making a lot of assuptions at this point
-    let mut path_families: HashMap<String, Vec<String>> = HashMap::new();
-    for path in stats.keys() {
-        // Group by base path (e.g., "user.address" for "user.address_v1" and "user.address_v2")
-        if let Some(base) = path.rsplit_once('_').map(|(base, _)| base) {
-            path_families
-                .entry(base.to_string())
-                .or_default()
-                .push(path.clone());
+    // Old and new copies already reported, so they aren't reported as variants too
+    let mut renamed: Vec<(String, String)> = Vec::new();
+    if config.deprecated_naming {
+        for path in &paths {
+            let (parent, key) = split_key(path);
+            let current = config
+                .deprecated_prefixes
+                .iter()
+                .find_map(|prefix| strip_prefix_ignore_case(key, prefix))
+                .or_else(|| {
+                    config
+                        .deprecated_suffixes
+                        .iter()
+                        .find_map(|suffix| strip_suffix_ignore_case(key, suffix))
+                });
+            let Some(current) = current else {
+                continue;
+            };
+            let new_path = format!("{}{}", parent, current);
+            if dense(&new_path) {
+                issues.push(DriftIssue::SchemaEvolution {
+                    path: path.to_string(),
+                    pattern: EvolutionPattern::DeprecatedNaming {
+                        old_path: path.to_string(),
+                        new_path: new_path.clone(),
+                    },
+                });
+                renamed.push((path.to_string(), new_path));
+            }
         }
     }
 
-    for (base, paths) in path_families {
-        if paths.len() >= 2 {
-            // Check if they're mutually exclusive (sum of densities ~= max individual density)
-            let densities: Vec<f64> = paths
+    if config.mutually_exclusive {
+        // Variants grouped by the path they're a variant of (`address` for
+        // `address_v1` and `address_v2`), which is a member itself if present
+        let mut families: HashMap<String, Vec<String>> = HashMap::new();
+        for path in &paths {
+            let (parent, key) = split_key(path);
+            if let Some(base) = config
+                .variant_suffixes
                 .iter()
-                .filter_map(|p| stats.get(p).map(|s| s.density))
-                .collect();
-            if densities.len() >= 2 {
-                let sum: f64 = densities.iter().sum();
-                let max = densities.iter().copied().fold(0.0f64, f64::max);
-                // If sum is close to max, they're likely mutually exclusive
-                if (sum - max).abs() < 0.1 {
-                    issues.push(DriftIssue::SchemaEvolution {
-                        path: base,
-                        pattern: EvolutionPattern::MutuallyExclusive { paths },
-                    });
-                }
+                .find_map(|suffix| strip_variant_suffix(key, suffix))
+            {
+                families
+                    .entry(format!("{}{}", parent, base))
+                    .or_default()
+                    .push(path.to_string());
+            }
+        }
+
+        let mut families: Vec<(String, Vec<String>)> = families.into_iter().collect();
+        families.sort();
+        for (base, mut family) in families {
+            if dense(&base) {
+                family.insert(0, base.clone());
+            }
+            if family.len() < 2
+                || (family.len() == 2
+                    && renamed
+                        .iter()
+                        .any(|(old, new)| family.contains(old) && family.contains(new)))
+            {
+                continue;
+            }
+
+            // Mutually exclusive if they add up to about the most common one
+            let densities: Vec<f64> = family.iter().map(|p| stats[p].density).collect();
+            let sum: f64 = densities.iter().sum();
+            let max = densities.iter().copied().fold(0.0f64, f64::max);
+            if (sum - max).abs() < 0.1 {
+                issues.push(DriftIssue::SchemaEvolution {
+                    path: base,
+                    pattern: EvolutionPattern::MutuallyExclusive { paths: family },
+                });
             }
         }
     }
@@ -442,6 +2263,46 @@ fn detect_schema_evolution(stats: &HashMap<String, FieldStats>) -> Vec<DriftIssu
     issues
 }
 
+/// Split `a.b.key` into `a.b.` and `key`
+fn split_key(path: &str) -> (&str, &str) {
+    match path.rfind('.') {
+        Some(i) => path.split_at(i + 1),
+        None => ("", path),
+    }
+}
+
+fn strip_prefix_ignore_case<'a>(key: &'a str, prefix: &str) -> Option<&'a str> {
+    let rest = key.get(prefix.len()..)?;
+    (key[..prefix.len()].eq_ignore_ascii_case(prefix) && !rest.is_empty()).then_some(rest)
+}
+
+fn strip_suffix_ignore_case<'a>(key: &'a str, suffix: &str) -> Option<&'a str> {
+    let split = key.len().checked_sub(suffix.len())?;
+    let rest = key.get(..split)?;
+    (key[split..].eq_ignore_ascii_case(suffix) && !rest.is_empty()).then_some(rest)
+}
+
+/// Strip a variant suffix such as `_v#` (`address_v2` → `address`), `#`
+/// matching one or more digits
+fn strip_variant_suffix<'a>(key: &'a str, suffix: &str) -> Option<&'a str> {
+    let Some((before, after)) = suffix.split_once('#') else {
+        return strip_suffix_ignore_case(key, suffix);
+    };
+    let rest = if after.is_empty() {
+        key
+    } else {
+        strip_suffix_ignore_case(key, after)?
+    };
+    let digits = rest.trim_end_matches(|c: char| c.is_ascii_digit());
+    if digits.len() == rest.len() {
+        return None;
+    }
+    if before.is_empty() {
+        return (!digits.is_empty()).then_some(digits);
+    }
+    strip_suffix_ignore_case(digits, before)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -462,6 +2323,86 @@ mod tests {
         stats
     }
 
+    fn create_string_stats(path: &str, values: &[(&str, usize)]) -> FieldStats {
+        let mut stats = FieldStats::new(path.to_string(), 1);
+        let mut total = 0;
+        for (value, count) in values {
+            for _ in 0..*count {
+                stats.record(&serde_json::json!(value));
+            }
+            total += *count as u64;
+        }
+        stats.finalize(total);
+        stats
+    }
+
+    #[test]
+    fn test_mixed_format_detection() {
+        let config = DriftConfig::default();
+        let stats = create_string_stats("created", &[("2024-01-15", 85), ("01/15/2024", 15)]);
+
+        let issue = detect_mixed_format(&stats, &config).unwrap();
+        assert_eq!(issue.severity(), Severity::Warning);
+        if let DriftIssue::MixedFormat {
+            dominant,
+            minority_percentage,
+            ..
+        } = &issue
+        {
+            assert_eq!(*dominant, StringFormat::Date);
+            assert_eq!(*minority_percentage, 15.0);
+        }
+        assert!(issue.description().contains("mostly date"));
+        assert!(issue.description().contains("slash-date:15.0"));
+    }
+
+    #[test]
+    fn test_mixed_format_ignores_consistent_and_free_text() {
+        let config = DriftConfig::default();
+
+        let consistent = create_string_stats("email", &[("a@example.com", 100)]);
+        assert!(detect_mixed_format(&consistent, &config).is_none());
+
+        let text = create_string_stats("note", &[("hello there", 90), ("a@example.com", 10)]);
+        assert!(detect_mixed_format(&text, &config).is_none());
+
+        let below = create_string_stats("id", &[("42", 99), ("", 1)]);
+        assert!(detect_mixed_format(&below, &config).is_none());
+    }
+
+    #[test]
+    fn test_constant_field_detection() {
+        let config = DriftConfig::default();
+
+        let constant = create_string_stats("plan", &[("legacy", 25)]);
+        let issue = detect_constant_field(&constant, &config).unwrap();
+        assert_eq!(issue.severity(), Severity::Info);
+        assert_eq!(
+            issue.description(),
+            "Constant field: always \"legacy\" (25 samples)"
+        );
+
+        let too_few = create_string_stats("plan", &[("legacy", 3)]);
+        assert!(detect_constant_field(&too_few, &config).is_none());
+
+        let varied = create_string_stats("plan", &[("legacy", 20), ("pro", 5)]);
+        assert!(detect_constant_field(&varied, &config).is_none());
+    }
+
+    #[test]
+    fn test_redact_constant_field() {
+        let mut issue = DriftIssue::ConstantField {
+            path: "owner".to_string(),
+            value: serde_json::json!("alice@example.com"),
+            occurrences: 10,
+        };
+        issue.redact(Redaction::Drop);
+        assert_eq!(
+            issue.description(),
+            "Constant field: always \"[redacted]\" (10 samples)"
+        );
+    }
+
     #[test]
     fn test_severity_ordering() {
         assert!(Severity::Critical > Severity::Warning);
@@ -596,6 +2537,35 @@ mod tests {
         assert_eq!(issue.unwrap().severity(), Severity::Warning);
     }
 
+    #[test]
+    fn test_missing_key_within_margin_of_error() {
+        let config = DriftConfig::default();
+
+        // 94% of 200 samples could well be 95%+ in the table
+        let stats = create_field_stats("user.phone", 188, 200, vec![(JsonType::String, 188)]);
+        assert!(detect_missing_key(&stats, &config).is_none());
+
+        // The same share of 20000 samples can't
+        let stats = create_field_stats("user.phone", 18800, 20000, vec![(JsonType::String, 18800)]);
+        let issue = detect_missing_key(&stats, &config).unwrap();
+        let DriftIssue::MissingKey { margin, .. } = issue else {
+            panic!("expected a missing key");
+        };
+        assert!(margin > 0.002 && margin < 0.005);
+        assert_eq!(
+            issue.description(),
+            "Missing key: 6.00% ±0.34% missing (1200/20000 samples missing field)"
+        );
+
+        // Point estimates when the confidence level is 0
+        let point = DriftConfig {
+            density_confidence: 0.0,
+            ..Default::default()
+        };
+        let stats = create_field_stats("user.phone", 188, 200, vec![(JsonType::String, 188)]);
+        assert!(detect_missing_key(&stats, &point).is_some());
+    }
+
     #[test]
     fn test_missing_key_above_threshold() {
         let config = DriftConfig::default();
@@ -615,7 +2585,7 @@ mod tests {
             create_field_stats("schema_version", 1000, 1000, vec![(JsonType::Number, 1000)]),
         );
 
-        let issues = detect_schema_evolution(&stats);
+        let issues = detect_schema_evolution(&stats, &SchemaEvolutionConfig::default());
         assert_eq!(issues.len(), 1);
         assert!(matches!(
             issues[0],
@@ -638,7 +2608,7 @@ mod tests {
             create_field_stats("address", 900, 1000, vec![(JsonType::String, 900)]),
         );
 
-        let issues = detect_schema_evolution(&stats);
+        let issues = detect_schema_evolution(&stats, &SchemaEvolutionConfig::default());
         assert!(!issues.is_empty());
 
         let deprecated = issues.iter().find(|i| {
@@ -653,6 +2623,97 @@ mod tests {
         assert!(deprecated.is_some());
     }
 
+    fn evolution(
+        fields: &[(&str, u64)],
+        config: &SchemaEvolutionConfig,
+    ) -> Vec<(String, EvolutionPattern)> {
+        let stats: HashMap<String, FieldStats> = fields
+            .iter()
+            .map(|(path, occurrences)| {
+                let fs = create_field_stats(
+                    path,
+                    *occurrences,
+                    1000,
+                    vec![(JsonType::String, *occurrences)],
+                );
+                (path.to_string(), fs)
+            })
+            .collect();
+        let mut issues: Vec<_> = detect_schema_evolution(&stats, config)
+            .into_iter()
+            .map(|issue| match issue {
+                DriftIssue::SchemaEvolution { path, pattern } => (path, pattern),
+                other => panic!("unexpected issue {:?}", other),
+            })
+            .collect();
+        issues.sort_by(|a, b| a.0.cmp(&b.0));
+        issues
+    }
+
+    #[test]
+    fn test_schema_evolution_patterns() {
+        let fields = [
+            ("meta.theme_old", 30),
+            ("meta.theme", 950),
+            ("address_v1", 50),
+            ("address_v2", 900),
+            ("line_1", 1000),
+            ("line_2", 1000),
+            ("customer_id", 500),
+            ("customer_name", 480),
+            ("nickname_old", 5),
+            ("nickname", 600),
+        ];
+        let found = evolution(&fields, &SchemaEvolutionConfig::default());
+        assert_eq!(
+            found,
+            vec![
+                (
+                    "address".to_string(),
+                    EvolutionPattern::MutuallyExclusive {
+                        paths: vec!["address_v1".to_string(), "address_v2".to_string()],
+                    }
+                ),
+                (
+                    "meta.theme_old".to_string(),
+                    EvolutionPattern::DeprecatedNaming {
+                        old_path: "meta.theme_old".to_string(),
+                        new_path: "meta.theme".to_string(),
+                    }
+                ),
+            ]
+        );
+
+        let mut config = SchemaEvolutionConfig {
+            deprecated_suffixes: vec![],
+            variant_suffixes: vec!["_v#".to_string(), "_old".to_string()],
+            min_density: 0.0,
+            ..Default::default()
+        };
+        config.disable("mutually_exclusive");
+        let found = evolution(&fields, &config);
+        assert!(found.is_empty());
+
+        config.mutually_exclusive = true;
+        let paths: Vec<String> = evolution(&fields, &config)
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect();
+        assert_eq!(paths, vec!["address", "meta.theme", "nickname"]);
+    }
+
+    #[test]
+    fn test_strip_variant_suffix() {
+        assert_eq!(strip_variant_suffix("address_v2", "_v#"), Some("address"));
+        assert_eq!(strip_variant_suffix("address_V10", "_v#"), Some("address"));
+        assert_eq!(strip_variant_suffix("address_v", "_v#"), None);
+        assert_eq!(strip_variant_suffix("line_2", "_#"), Some("line"));
+        assert_eq!(strip_variant_suffix("v2", "_v#"), None);
+        assert_eq!(strip_variant_suffix("email_new", "_new"), Some("email"));
+        assert_eq!(strip_variant_suffix("v2_beta", "v#_beta"), None);
+        assert_eq!(strip_variant_suffix("api_v2_beta", "_v#_beta"), Some("api"));
+    }
+
     #[test]
     fn test_detect_drift_comprehensive() {
         let mut stats = HashMap::new();
@@ -739,6 +2800,18 @@ mod tests {
             sparse_field_threshold: 0.70,
             missing_key_threshold: 0.99,
             detect_schema_evolution: false,
+            schema_evolution: SchemaEvolutionConfig::default(),
+            format_inconsistency_threshold: 2.0,
+            constant_field_min_occurrences: 10,
+            numeric_string_threshold: 95.0,
+            empty_string_threshold: 5.0,
+            null_missing_threshold: 5.0,
+            rename_coverage_threshold: 0.90,
+            rename_max_overlap: 0.01,
+            tuple_min_arrays: 10,
+            max_nesting_depth: 8,
+            oversized_string_bytes: 2048,
+            density_confidence: 0.95,
         };
 
         // 8% minority - should NOT trigger with 10% threshold
@@ -752,4 +2825,512 @@ mod tests {
         let issue = detect_type_inconsistency(&stats, &config);
         assert!(issue.is_none());
     }
+
+    #[test]
+    fn test_reclassify() {
+        let issue = DriftIssue::SparseField {
+            path: "flags.beta".to_string(),
+            density: 0.4,
+            occurrences: 40,
+            total_samples: 100,
+            margin: 0.1,
+        };
+
+        let critical = issue.clone().reclassify(Severity::Critical);
+        assert_eq!(critical.severity(), Severity::Critical);
+        assert_eq!(critical.kind(), "SparseField");
+        assert_eq!(critical.path(), "flags.beta");
+        assert_eq!(critical.description(), issue.description());
+
+        // Reclassifying again replaces the override rather than nesting it
+        let warning = critical.reclassify(Severity::Warning);
+        assert!(matches!(
+            &warning,
+            DriftIssue::Reclassified { issue: inner, .. } if **inner == issue
+        ));
+        // Back at the computed severity, the plain issue is restored
+        assert_eq!(warning.reclassify(Severity::Info), issue);
+    }
+
+    /// Flags every path named `debug`
+    struct DebugFieldDetector;
+
+    impl DriftDetector for DebugFieldDetector {
+        fn name(&self) -> &str {
+            "debug_field"
+        }
+
+        fn detect_field(&self, stats: &FieldStats, _config: &DriftConfig) -> Option<DriftIssue> {
+            (stats.path == "debug").then(|| DriftIssue::GhostKey {
+                path: stats.path.clone(),
+                density: stats.density,
+                occurunces: stats.occurrences,
+                total_samples: stats.total_samples,
+                margin: 0.0,
+            })
+        }
+    }
+
+    #[test]
+    fn test_detector_registry() {
+        let mut stats = HashMap::new();
+        stats.insert(
+            "debug".to_string(),
+            create_field_stats("debug", 1000, 1000, vec![(JsonType::Boolean, 1000)]),
+        );
+        stats.insert(
+            "note".to_string(),
+            create_field_stats("note", 500, 1000, vec![(JsonType::String, 500)]),
+        );
+
+        let mut detectors = DriftDetectors::builtin();
+        assert_eq!(detectors.names(), BUILTIN_DETECTORS);
+        assert_eq!(
+            detectors.detect(&stats, &DriftConfig::default()),
+            detect_drift(&stats, &DriftConfig::default())
+        );
+
+        assert!(detectors.disable("sparse_field"));
+        assert!(!detectors.disable("sparse_field"));
+        detectors.register(DebugFieldDetector);
+
+        let issues = detectors.detect(&stats, &DriftConfig::default());
+        let paths: Vec<&str> = issues.iter().map(|i| i.path()).collect();
+        assert_eq!(paths, vec!["debug"]);
+    }
+
+    #[test]
+    fn test_numeric_string() {
+        let config = DriftConfig::default();
+
+        let stats = create_string_stats(
+            "order.total",
+            &[("42", 60), ("3.14", 20), ("-7", 19), ("n/a", 1)],
+        );
+        match detect_numeric_string(&stats, &config) {
+            Some(DriftIssue::NumericString {
+                numeric_count,
+                string_count,
+                numeric_percentage,
+                ..
+            }) => {
+                assert_eq!(numeric_count, 99);
+                assert_eq!(string_count, 100);
+                assert!((numeric_percentage - 99.0).abs() < 0.01);
+            }
+            other => panic!("Expected NumericString, got {:?}", other),
+        }
+
+        // Mostly text with a few numbers is not a numeric column
+        let stats = create_string_stats("note", &[("hello", 50), ("42", 50)]);
+        assert!(detect_numeric_string(&stats, &config).is_none());
+
+        // Leading zeros mean identifiers, not numbers
+        let stats = create_string_stats("address.zip", &[("02134", 50), ("01002", 50)]);
+        assert!(detect_numeric_string(&stats, &config).is_none());
+    }
+
+    #[test]
+    fn test_boolean_encoding() {
+        let mut stats = FieldStats::new("user.active".to_string(), 1);
+        stats.record_repeated(&serde_json::json!(true), 60);
+        stats.record_repeated(&serde_json::json!(false), 25);
+        stats.record_repeated(&serde_json::json!("yes"), 10);
+        stats.record_repeated(&serde_json::json!(0), 5);
+        stats.finalize(100);
+
+        match detect_boolean_encoding(&stats) {
+            Some(DriftIssue::BooleanEncoding {
+                dominant,
+                ref encodings,
+                minority_percentage,
+                ..
+            }) => {
+                assert_eq!(dominant, BoolEncoding::Boolean);
+                assert_eq!(encodings[&BoolEncoding::Boolean], 85);
+                assert_eq!(encodings[&BoolEncoding::YesNo], 10);
+                assert_eq!(encodings[&BoolEncoding::Integer], 5);
+                assert!((minority_percentage - 15.0).abs() < 0.01);
+            }
+            other => panic!("Expected BooleanEncoding, got {:?}", other),
+        }
+
+        // A single encoding is just a boolean
+        let mut stats = FieldStats::new("flag".to_string(), 1);
+        stats.record_repeated(&serde_json::json!(true), 50);
+        stats.record_repeated(&serde_json::json!(false), 50);
+        stats.finalize(100);
+        assert!(detect_boolean_encoding(&stats).is_none());
+
+        // 0/1/2 is a number, not a boolean
+        let mut stats = FieldStats::new("retries".to_string(), 1);
+        stats.record_repeated(&serde_json::json!(0), 50);
+        stats.record_repeated(&serde_json::json!(2), 5);
+        stats.record_repeated(&serde_json::json!("1"), 5);
+        stats.finalize(60);
+        assert!(detect_boolean_encoding(&stats).is_none());
+    }
+
+    #[test]
+    fn test_date_format_drift() {
+        let mut stats = FieldStats::new("created_at".to_string(), 1);
+        stats.record_repeated(&serde_json::json!("2024-01-15T10:30:00Z"), 60);
+        stats.record_repeated(&serde_json::json!(1705314600), 25);
+        stats.record_repeated(&serde_json::json!("01/15/2024"), 15);
+        stats.finalize(100);
+
+        match detect_date_format(&stats) {
+            Some(DriftIssue::DateFormatDrift {
+                ref formats,
+                minority_percentage,
+                suggested,
+                ..
+            }) => {
+                assert_eq!(formats[&DateFormat::IsoDateTime], 60);
+                assert_eq!(formats[&DateFormat::Epoch], 25);
+                assert_eq!(formats[&DateFormat::SlashDate], 15);
+                assert!((minority_percentage - 40.0).abs() < 0.01);
+                assert_eq!(suggested, DateFormat::IsoDateTime);
+            }
+            other => panic!("Expected DateFormatDrift, got {:?}", other),
+        }
+
+        // Small numbers next to dates aren't timestamps
+        let mut stats = FieldStats::new("due".to_string(), 1);
+        stats.record_repeated(&serde_json::json!("2024-01-15"), 90);
+        stats.record_repeated(&serde_json::json!(7), 10);
+        stats.finalize(100);
+        assert!(detect_date_format(&stats).is_none());
+
+        let stats = create_string_stats("due", &[("2024-01-15", 90), ("01/15/2024", 10)]);
+        let issue = detect_date_format(&stats).expect("expected date format drift");
+        assert!(issue.description().contains("normalize to iso-date"));
+    }
+
+    #[test]
+    fn test_empty_string() {
+        let config = DriftConfig::default();
+
+        let mut stats = FieldStats::new("user.nickname".to_string(), 1);
+        stats.record_repeated(&serde_json::json!("ace"), 60);
+        stats.record_repeated(&serde_json::json!(""), 20);
+        stats.record_repeated(&serde_json::Value::Null, 10);
+        stats.finalize(100);
+
+        let issue = detect_empty_string(&stats, &config).expect("expected empty string issue");
+        assert_eq!(
+            issue.description(),
+            "Empty strings alongside null/missing: 20 empty, 10 null, 10 missing (100 samples)"
+        );
+
+        // Empty strings as the only way to say nothing are consistent
+        let stats = create_string_stats("user.nickname", &[("ace", 80), ("", 20)]);
+        assert!(detect_empty_string(&stats, &config).is_none());
+
+        // A stray empty string is below the threshold
+        let mut stats = FieldStats::new("user.nickname".to_string(), 1);
+        stats.record_repeated(&serde_json::json!("ace"), 99);
+        stats.record_repeated(&serde_json::json!(""), 1);
+        stats.finalize(200);
+        assert!(detect_empty_string(&stats, &config).is_none());
+    }
+
+    #[test]
+    fn test_null_vs_missing() {
+        let config = DriftConfig::default();
+
+        let mut stats = FieldStats::new("user.deleted_at".to_string(), 1);
+        stats.record_repeated(&serde_json::Value::Null, 30);
+        stats.record_repeated(&serde_json::json!("2024-01-15"), 50);
+        stats.finalize(100);
+
+        match detect_null_vs_missing(&stats, &config) {
+            Some(issue @ DriftIssue::NullVsMissing { .. }) => {
+                assert_eq!(issue.severity(), Severity::Warning);
+                assert_eq!(
+                    issue.description(),
+                    "Null vs missing: explicit null in 30 samples, key absent in 20"
+                );
+            }
+            other => panic!("Expected NullVsMissing, got {:?}", other),
+        }
+
+        // Always omitted, never null: sparse but consistent
+        let stats = create_field_stats("user.deleted_at", 50, 100, vec![(JsonType::String, 50)]);
+        assert!(detect_null_vs_missing(&stats, &config).is_none());
+
+        // One stray null among many omissions
+        let mut stats = FieldStats::new("user.deleted_at".to_string(), 1);
+        stats.record_repeated(&serde_json::Value::Null, 1);
+        stats.record_repeated(&serde_json::json!("2024-01-15"), 49);
+        stats.finalize(100);
+        assert!(detect_null_vs_missing(&stats, &config).is_none());
+    }
+
+    #[test]
+    fn test_key_naming_conflict() {
+        let mut stats = HashMap::new();
+        for (path, occurrences) in [
+            ("user.userId", 80),
+            ("user.user_id", 20),
+            ("user.Status", 5),
+            ("user.status", 95),
+            ("order.user_id", 100),
+            ("items[]", 100),
+        ] {
+            stats.insert(
+                path.to_string(),
+                create_field_stats(
+                    path,
+                    occurrences,
+                    100,
+                    vec![(JsonType::String, occurrences)],
+                ),
+            );
+        }
+
+        let mut issues = detect_key_naming(&stats);
+        sort_issues(&mut issues);
+        let descriptions: Vec<String> = issues.iter().map(|i| i.description()).collect();
+        assert_eq!(
+            descriptions,
+            vec![
+                "Key naming conflict: user.status (95.0%) vs user.Status (5.0%)",
+                "Key naming conflict: user.userId (80.0%) vs user.user_id (20.0%)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sort_issues_is_total() {
+        let rename = |new_path: &str| DriftIssue::ProbableRename {
+            path: "user.adress".to_string(),
+            old_path: "user.adress".to_string(),
+            new_path: new_path.to_string(),
+            old_density: 0.1,
+            new_density: 0.9,
+            confirmed: true,
+        };
+        let mut forward = vec![rename("user.address"), rename("user.addresses")];
+        let mut backward = vec![rename("user.addresses"), rename("user.address")];
+        sort_issues(&mut forward);
+        sort_issues(&mut backward);
+        assert_eq!(forward, backward);
+    }
+
+    #[test]
+    fn test_similar_keys() {
+        assert_eq!(
+            key_words("userEmail_address"),
+            vec!["user", "email", "address"]
+        );
+        assert_eq!(edit_distance("adress", "address"), 1);
+
+        assert!(similar_keys("email", "email_address"));
+        assert!(similar_keys("zip", "zipcode"));
+        assert!(similar_keys("adress", "address"));
+        assert!(!similar_keys("name", "game"));
+        assert!(!similar_keys("userId", "user_id"));
+    }
+
+    #[test]
+    fn test_probable_rename() {
+        let mut stats = HashMap::new();
+        for (path, occurrences) in [
+            ("user.email", 30),
+            ("user.email_address", 68),
+            ("user.phone", 40),
+            ("user.phone_number", 70),
+            ("user.name", 50),
+            ("user.game", 48),
+        ] {
+            stats.insert(
+                path.to_string(),
+                create_field_stats(
+                    path,
+                    occurrences,
+                    100,
+                    vec![(JsonType::String, occurrences)],
+                ),
+            );
+        }
+
+        let issues = detect_renames(&stats, &DriftConfig::default());
+        assert_eq!(issues.len(), 1, "{:?}", issues);
+        assert_eq!(
+            issues[0].description(),
+            "Probable rename: 'user.email' (30.0%) → 'user.email_address' (68.0%), keep 'user.email_address' (unconfirmed, the documents weren't checked for both keys)"
+        );
+        assert_eq!(issues[0].severity(), Severity::Info);
+    }
+
+    #[test]
+    fn test_rename_needs_disjoint_documents() {
+        let config = DriftConfig::default();
+        let analyze = |documents: &[Value]| {
+            let mut analyzer = crate::analyzer::JsonAnalyzer::new();
+            for document in documents {
+                analyzer.analyze(document);
+            }
+            analyzer.finalize()
+        };
+
+        // 45 + 50 occurrences fit in 100 documents, but every document with
+        // `email` has `email_address` too
+        let overlapping: Vec<Value> = (0..100)
+            .map(|i| match i {
+                0..45 => serde_json::json!({"user": {"email": "a", "email_address": "a"}}),
+                45..50 => serde_json::json!({"user": {"email_address": "a"}}),
+                _ => serde_json::json!({"user": {}}),
+            })
+            .collect();
+        let stats = analyze(&overlapping);
+        let candidates = detect_renames(&stats, &config);
+        assert_eq!(candidates.len(), 1, "{:?}", candidates);
+        assert!(confirm_renames(candidates, &overlapping, &config).is_empty());
+
+        let disjoint: Vec<Value> = (0..100)
+            .map(|i| match i {
+                0..30 => serde_json::json!({"user": {"email": "a"}}),
+                _ => serde_json::json!({"user": {"email_address": "a"}}),
+            })
+            .collect();
+        let candidates = detect_renames(&analyze(&disjoint), &config);
+        let confirmed = confirm_renames(candidates.clone(), &disjoint, &config);
+        assert_eq!(confirmed.len(), 1);
+        assert_eq!(confirmed[0].severity(), Severity::Warning);
+        assert_eq!(
+            confirmed[0].description(),
+            "Probable rename: 'user.email' (30.0%) → 'user.email_address' (70.0%), keep 'user.email_address'"
+        );
+        // Nothing to confirm them with, so they're kept as candidates
+        let unconfirmed = confirm_renames(candidates.clone(), &[], &config);
+        assert_eq!(unconfirmed, candidates);
+        assert_eq!(unconfirmed[0].severity(), Severity::Info);
+    }
+
+    #[test]
+    fn test_tuple_array() {
+        let config = DriftConfig::default();
+
+        let mut stats = FieldStats::new("owner".to_string(), 1);
+        stats.record_repeated(&serde_json::json!(["alice", 42, true]), 20);
+        stats.finalize(20);
+        let issue = detect_tuple_array(&stats, &config).expect("expected a tuple array");
+        assert_eq!(
+            issue.description(),
+            "Tuple encoded as array (20 arrays: [0] string:100.0, [1] number:100.0, [2] boolean:100.0) - use an object with named keys"
+        );
+
+        // Same type everywhere: a plain list
+        let mut stats = FieldStats::new("point".to_string(), 1);
+        stats.record_repeated(&serde_json::json!([1.5, 2.5]), 20);
+        stats.finalize(20);
+        assert!(detect_tuple_array(&stats, &config).is_none());
+
+        // Varying lengths: a list, even with mixed types
+        let mut stats = FieldStats::new("tags".to_string(), 1);
+        stats.record_repeated(&serde_json::json!(["a", 1]), 10);
+        stats.record_repeated(&serde_json::json!(["a", 1, 2]), 10);
+        stats.finalize(20);
+        assert!(detect_tuple_array(&stats, &config).is_none());
+    }
+
+    #[test]
+    fn test_excessive_depth() {
+        let mut analyzer = crate::analyzer::JsonAnalyzer::new();
+        analyzer.analyze(&serde_json::json!({
+            "id": 1,
+            "a": {"b": {"c": {"d": [{"e": 1, "f": {"g": true}}]}}}
+        }));
+        let stats = analyzer.finalize();
+
+        let config = DriftConfig {
+            max_nesting_depth: 4,
+            ..Default::default()
+        };
+        let issues = detect_excessive_depth(&stats, &config);
+        assert_eq!(issues.len(), 1, "{:?}", issues);
+        assert_eq!(issues[0].path(), "a.b.c.d");
+        assert_eq!(
+            issues[0].description(),
+            "Excessive nesting: paths below reach depth 7 (limit 4)"
+        );
+
+        assert!(detect_excessive_depth(&stats, &DriftConfig::default()).is_empty());
+    }
+
+    #[test]
+    fn test_oversized_payload() {
+        let config = DriftConfig::default();
+
+        let mut stats = FieldStats::new("attachment".to_string(), 1);
+        stats.record_repeated(&serde_json::json!("x".repeat(4096)), 10);
+        stats.finalize(20);
+        let issue = detect_oversized_payload(&stats, &config).expect("expected oversized payload");
+        assert_eq!(
+            issue.description(),
+            "Oversized payload: avg 4.0 KiB, max 4.0 KiB; ~1.9 GiB per million rows - move it out of JSONB"
+        );
+
+        // Small base64 thumbnails are still blobs
+        let mut stats = FieldStats::new("thumbnail".to_string(), 1);
+        stats.record_repeated(
+            &serde_json::json!("iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNkYPhfDwAChwGA60e6kgAAAABJRU5ErkJggg=="),
+            10,
+        );
+        stats.finalize(10);
+        let issue = detect_oversized_payload(&stats, &config).expect("expected oversized payload");
+        assert!(issue.description().contains("10/10 look like blobs"));
+
+        let stats = create_string_stats("name", &[("alice", 10)]);
+        assert!(detect_oversized_payload(&stats, &config).is_none());
+    }
+
+    #[test]
+    fn test_problematic_key() {
+        assert_eq!(KeyProblem::find("user_id"), vec![]);
+        assert_eq!(
+            KeyProblem::find(" first name"),
+            vec![KeyProblem::SurroundingWhitespace, KeyProblem::Whitespace]
+        );
+        assert_eq!(KeyProblem::find("a\tb"), vec![KeyProblem::ControlCharacter]);
+        assert_eq!(KeyProblem::find("x,y"), vec![KeyProblem::NeedsQuoting]);
+        assert_eq!(KeyProblem::find(""), vec![KeyProblem::Empty]);
+
+        let stats = create_field_stats("user.status ", 40, 100, vec![(JsonType::String, 40)]);
+        let issue = detect_problematic_key(&stats).expect("expected a problematic key");
+        assert_eq!(
+            issue.description(),
+            "Problematic key \"status \": leading/trailing whitespace (40 samples)"
+        );
+
+        let stats = create_field_stats("user.status", 40, 100, vec![(JsonType::String, 40)]);
+        assert!(detect_problematic_key(&stats).is_none());
+    }
+
+    #[test]
+    fn test_unit_drift() {
+        let mut stats = FieldStats::new("duration".to_string(), 1);
+        for i in 0..80 {
+            stats.record(&serde_json::json!(2.0 + (i % 10) as f64 * 0.5));
+        }
+        for i in 0..20 {
+            stats.record(&serde_json::json!(2000 + (i % 10) * 500));
+        }
+        stats.finalize(100);
+
+        let issue = detect_unit_drift(&stats).expect("expected unit drift");
+        assert_eq!(
+            issue.description(),
+            "Probable unit drift: values cluster around 4.5 and 4500 (~1000x, 20.0% in the upper band)"
+        );
+
+        // Bands 20x apart are two populations, not two units
+        let mut stats = FieldStats::new("quantity".to_string(), 1);
+        stats.record_repeated(&serde_json::json!(5), 50);
+        stats.record_repeated(&serde_json::json!(100), 50);
+        stats.finalize(100);
+        assert!(detect_unit_drift(&stats).is_none());
+    }
 }
@@ -0,0 +1,253 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Inferred format of a string value
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StringFormat {
+    Uuid,
+    Email,
+    Url,
+    /// ISO 8601 date, e.g. `2024-01-15`
+    Date,
+    /// ISO 8601 timestamp, e.g. `2024-01-15T10:30:00Z`
+    DateTime,
+    /// Slash-separated date, e.g. `01/15/2024` or `2024/01/15`
+    SlashDate,
+    /// Number stored as a string, e.g. `"19.99"`
+    Numeric,
+    /// `"true"` or `"false"` stored as a string
+    Boolean,
+    Empty,
+    /// Anything else
+    Text,
+}
+
+impl StringFormat {
+    /// Classify a string value
+    pub fn classify(s: &str) -> Self {
+        if s.is_empty() {
+            StringFormat::Empty
+        } else if is_uuid(s) {
+            StringFormat::Uuid
+        } else if is_date(s) {
+            StringFormat::Date
+        } else if is_datetime(s) {
+            StringFormat::DateTime
+        } else if is_slash_date(s) {
+            StringFormat::SlashDate
+        } else if is_numeric(s) {
+            StringFormat::Numeric
+        } else if s.eq_ignore_ascii_case("true") || s.eq_ignore_ascii_case("false") {
+            StringFormat::Boolean
+        } else if is_url(s) {
+            StringFormat::Url
+        } else if is_email(s) {
+            StringFormat::Email
+        } else {
+            StringFormat::Text
+        }
+    }
+}
+
+impl fmt::Display for StringFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StringFormat::Uuid => write!(f, "uuid"),
+            StringFormat::Email => write!(f, "email"),
+            StringFormat::Url => write!(f, "url"),
+            StringFormat::Date => write!(f, "date"),
+            StringFormat::DateTime => write!(f, "datetime"),
+            StringFormat::SlashDate => write!(f, "slash-date"),
+            StringFormat::Numeric => write!(f, "numeric"),
+            StringFormat::Boolean => write!(f, "boolean"),
+            StringFormat::Empty => write!(f, "empty"),
+            StringFormat::Text => write!(f, "text"),
+        }
+    }
+}
+
+/// Strings shorter than this are never considered blobs
+pub const MIN_BLOB_BYTES: usize = 64;
+
+/// Whether a string looks like an embedded binary or serialized payload:
+/// base64 (standard or URL-safe) or a JSON/XML document in a string
+pub fn looks_like_blob(s: &str) -> bool {
+    if s.len() < MIN_BLOB_BYTES {
+        return false;
+    }
+
+    let serialized = (s.starts_with('{') && s.ends_with('}'))
+        || (s.starts_with('[') && s.ends_with(']'))
+        || (s.starts_with('<') && s.ends_with('>'));
+    if serialized {
+        return true;
+    }
+
+    let body = s.trim_end_matches('=');
+    s.len() - body.len() <= 2
+        && body
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'/' | b'-' | b'_'))
+        && body.bytes().any(|b| b.is_ascii_digit())
+        && body.bytes().any(|b| b.is_ascii_uppercase())
+        && body.bytes().any(|b| b.is_ascii_lowercase())
+}
+
+fn all_digits(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// 8-4-4-4-12 hex digits
+fn is_uuid(s: &str) -> bool {
+    let groups: Vec<&str> = s.split('-').collect();
+    groups.len() == 5
+        && groups
+            .iter()
+            .zip([8, 4, 4, 4, 12])
+            .all(|(g, len)| g.len() == len && g.bytes().all(|b| b.is_ascii_hexdigit()))
+}
+
+/// YYYY-MM-DD with a plausible month and day
+fn is_date(s: &str) -> bool {
+    let b = s.as_bytes();
+    if b.len() != 10 || b[4] != b'-' || b[7] != b'-' {
+        return false;
+    }
+    let (year, month, day) = (&s[0..4], &s[5..7], &s[8..10]);
+    all_digits(year) && valid_month_day(month, day)
+}
+
+/// A date followed by `T` or a space and at least HH:MM
+fn is_datetime(s: &str) -> bool {
+    let b = s.as_bytes();
+    if b.len() < 16 || !(b[10] == b'T' || b[10] == b' ') || !is_date(&s[..10]) {
+        return false;
+    }
+    b[11..13].iter().all(u8::is_ascii_digit)
+        && b[13] == b':'
+        && b[14..16].iter().all(u8::is_ascii_digit)
+}
+
+/// NN/NN/NNNN or NNNN/NN/NN
+fn is_slash_date(s: &str) -> bool {
+    let parts: Vec<&str> = s.split('/').collect();
+    if parts.len() != 3 || !parts.iter().all(|p| all_digits(p)) {
+        return false;
+    }
+    let lens: Vec<usize> = parts.iter().map(|p| p.len()).collect();
+    matches!(lens.as_slice(), [1..=2, 1..=2, 4] | [4, 1..=2, 1..=2])
+}
+
+fn valid_month_day(month: &str, day: &str) -> bool {
+    let month: u32 = month.parse().unwrap_or(0);
+    let day: u32 = day.parse().unwrap_or(0);
+    (1..=12).contains(&month) && (1..=31).contains(&day)
+}
+
+/// Plain decimal numbers; rejects `inf`/`NaN` and surrounding whitespace
+fn is_numeric(s: &str) -> bool {
+    s.bytes().any(|b| b.is_ascii_digit())
+        && s.bytes()
+            .all(|b| b.is_ascii_digit() || matches!(b, b'-' | b'+' | b'.' | b'e' | b'E'))
+        && s.parse::<f64>().is_ok()
+}
+
+fn is_url(s: &str) -> bool {
+    match s.split_once("://") {
+        Some((scheme, rest)) => {
+            !scheme.is_empty()
+                && scheme
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'-' | b'.'))
+                && !rest.is_empty()
+                && !s.contains(char::is_whitespace)
+        }
+        None => false,
+    }
+}
+
+fn is_email(s: &str) -> bool {
+    match s.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && domain.contains('.')
+                && !domain.starts_with('.')
+                && !domain.ends_with('.')
+                && !domain.contains('@')
+                && !s.contains(char::is_whitespace)
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        let cases = [
+            ("", StringFormat::Empty),
+            ("550e8400-e29b-41d4-a716-446655440000", StringFormat::Uuid),
+            ("alice@example.com", StringFormat::Email),
+            ("https://example.com/a?b=c", StringFormat::Url),
+            ("2024-01-15", StringFormat::Date),
+            ("2024-01-15T10:30:00Z", StringFormat::DateTime),
+            ("2024-01-15 10:30:00.123+02:00", StringFormat::DateTime),
+            ("01/15/2024", StringFormat::SlashDate),
+            ("2024/1/15", StringFormat::SlashDate),
+            ("19.99", StringFormat::Numeric),
+            ("-42", StringFormat::Numeric),
+            ("TRUE", StringFormat::Boolean),
+            ("hello world", StringFormat::Text),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(StringFormat::classify(input), expected, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_near_misses_are_text() {
+        for input in [
+            "550e8400-e29b-41d4-a716-44665544000",
+            "2024-13-01",
+            "2024-01-15Tnoon",
+            "12/2024",
+            "inf",
+            "NaN",
+            " 42",
+            "@example.com",
+            "alice@localhost",
+            "not a url://x",
+        ] {
+            assert_eq!(
+                StringFormat::classify(input),
+                StringFormat::Text,
+                "{:?}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_looks_like_blob() {
+        let base64 = "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNkYPhfDwAChwGA60e6kgAAAABJRU5ErkJggg==";
+        assert!(looks_like_blob(base64));
+        assert!(looks_like_blob(&format!(
+            "{{\"items\": [{}]}}",
+            "1, ".repeat(30) + "1"
+        )));
+        assert!(!looks_like_blob(
+            &"a long sentence of plain prose. ".repeat(4)
+        ));
+        assert!(!looks_like_blob("aGVsbG8="));
+        assert!(!looks_like_blob(&"abcdefgh".repeat(10)));
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(StringFormat::SlashDate.to_string(), "slash-date");
+        assert_eq!(StringFormat::DateTime.to_string(), "datetime");
+    }
+}
@@ -0,0 +1,221 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Number of index bits - 2^12 = 4096 registers, ~1.6% standard error
+const PRECISION: u32 = 12;
+const REGISTERS: usize = 1 << PRECISION;
+
+/// HyperLogLog sketch for approximate distinct-value counting
+///
+/// Uses a fixed 4 KiB of memory per field regardless of how many distinct
+/// values are seen, and sketches can be merged across samples.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(into = "SparseRegisters", try_from = "SparseRegisters")]
+pub struct HyperLogLog {
+    registers: Vec<u8>,
+}
+
+/// Serialized form of a sketch: only the non-empty registers, as (index, rank)
+///
+/// Most paths see few distinct values, so this is far smaller than all 4096.
+#[derive(Serialize, Deserialize)]
+struct SparseRegisters(Vec<(u16, u8)>);
+
+impl From<HyperLogLog> for SparseRegisters {
+    fn from(hll: HyperLogLog) -> Self {
+        SparseRegisters(
+            hll.registers
+                .iter()
+                .enumerate()
+                .filter(|(_, rank)| **rank > 0)
+                .map(|(i, rank)| (i as u16, *rank))
+                .collect(),
+        )
+    }
+}
+
+impl TryFrom<SparseRegisters> for HyperLogLog {
+    type Error = String;
+
+    fn try_from(sparse: SparseRegisters) -> Result<Self, Self::Error> {
+        let mut hll = HyperLogLog::new();
+        for (i, rank) in sparse.0 {
+            let register = hll
+                .registers
+                .get_mut(i as usize)
+                .ok_or_else(|| format!("register index {} out of range", i))?;
+            *register = rank;
+        }
+        Ok(hll)
+    }
+}
+
+impl Default for HyperLogLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HyperLogLog {
+    /// Memory used by one sketch's registers
+    pub const BYTES: usize = REGISTERS;
+
+    pub fn new() -> Self {
+        Self {
+            registers: vec![0; REGISTERS],
+        }
+    }
+
+    /// Add a JSON value to the sketch
+    ///
+    /// Values are hashed by their canonical JSON text, so `1` and `"1"` are
+    /// distinct.
+    pub fn insert(&mut self, value: &Value) {
+        self.insert_hash(stable_hash(value.to_string().as_bytes()));
+    }
+
+    fn insert_hash(&mut self, hash: u64) {
+        let index = (hash >> (64 - PRECISION)) as usize;
+        // Leading zeros of the remaining bits, plus one; the sentinel bit caps it
+        let rest = (hash << PRECISION) | (1 << (PRECISION - 1));
+        let rank = rest.leading_zeros() as u8 + 1;
+
+        if rank > self.registers[index] {
+            self.registers[index] = rank;
+        }
+    }
+
+    /// Combine another sketch into this one
+    pub fn merge(&mut self, other: &HyperLogLog) {
+        for (mine, theirs) in self.registers.iter_mut().zip(&other.registers) {
+            *mine = (*mine).max(*theirs);
+        }
+    }
+
+    /// Estimated number of distinct values inserted
+    pub fn estimate(&self) -> u64 {
+        let m = REGISTERS as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+
+        let sum: f64 = self.registers.iter().map(|&r| 2f64.powi(-(r as i32))).sum();
+        let raw = alpha * m * m / sum;
+
+        // Small-range correction: linear counting while registers are still empty
+        let empty = self.registers.iter().filter(|&&r| r == 0).count();
+        if raw <= 2.5 * m && empty > 0 {
+            (m * (m / empty as f64).ln()).round() as u64
+        } else {
+            raw.round() as u64
+        }
+    }
+}
+
+/// Hash that is the same on every platform and release
+///
+/// Sketches are saved in checkpoints and watermark files and merged with
+/// later ones, so unlike `DefaultHasher` the hash must never change. Each
+/// 8-byte word is folded in with the splitmix64 finalizer.
+fn stable_hash(bytes: &[u8]) -> u64 {
+    let mut hash = bytes.len() as u64;
+    for chunk in bytes.chunks(8) {
+        let mut word = [0; 8];
+        word[..chunk.len()].copy_from_slice(chunk);
+        hash = mix(hash.wrapping_add(0x9E37_79B9_7F4A_7C15) ^ u64::from_le_bytes(word));
+    }
+    mix(hash)
+}
+
+/// splitmix64 finalizer
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn assert_close(estimate: u64, actual: u64) {
+        let error = (estimate as f64 - actual as f64).abs() / actual as f64;
+        assert!(
+            error < 0.05,
+            "estimate {} too far from {} ({:.1}% error)",
+            estimate,
+            actual,
+            error * 100.0
+        );
+    }
+
+    #[test]
+    fn test_empty_sketch() {
+        assert_eq!(HyperLogLog::new().estimate(), 0);
+    }
+
+    #[test]
+    fn test_duplicates_are_counted_once() {
+        let mut hll = HyperLogLog::new();
+        for _ in 0..1000 {
+            hll.insert(&json!("active"));
+            hll.insert(&json!("disabled"));
+        }
+        assert_eq!(hll.estimate(), 2);
+    }
+
+    #[test]
+    fn test_estimate_large_cardinality() {
+        let mut hll = HyperLogLog::new();
+        for i in 0..50_000 {
+            hll.insert(&json!(format!("user-{}", i)));
+        }
+        assert_close(hll.estimate(), 50_000);
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut a = HyperLogLog::new();
+        let mut b = HyperLogLog::new();
+        for i in 0..5000 {
+            a.insert(&json!(i));
+            b.insert(&json!(i + 2500));
+        }
+
+        a.merge(&b);
+        assert_close(a.estimate(), 7500);
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let mut hll = HyperLogLog::new();
+        for i in 0..100 {
+            hll.insert(&json!(i));
+        }
+
+        let serialized = serde_json::to_string(&hll).unwrap();
+        let restored: HyperLogLog = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(restored, hll);
+        assert!(serde_json::from_str::<HyperLogLog>("[[5000, 1]]").is_err());
+    }
+
+    #[test]
+    fn test_types_are_distinct() {
+        let mut hll = HyperLogLog::new();
+        hll.insert(&json!(1));
+        hll.insert(&json!("1"));
+        assert_eq!(hll.estimate(), 2);
+    }
+
+    #[test]
+    fn test_registers_are_stable() {
+        let mut hll = HyperLogLog::new();
+        for value in [json!("pro"), json!(42), json!({"plan": "free"})] {
+            hll.insert(&value);
+        }
+        // Saved sketches are merged with new ones, so these must never change
+        assert_eq!(
+            serde_json::to_string(&hll).unwrap(),
+            "[[341,1],[1722,1],[3570,1]]"
+        );
+    }
+}
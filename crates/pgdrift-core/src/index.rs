@@ -1,6 +1,14 @@
-use crate::stats::FieldStats;
+use crate::analyzer::is_wildcard_path;
+use crate::format::StringFormat;
+use crate::promotion::column_name;
+use crate::remediation::path_literal;
+use crate::sql::{quote_ident, quote_literal};
+use crate::stats::{FieldStats, ValueCount};
 use crate::types::JsonType;
+use crate::workload::{ColumnAccess, JsonOperator, QueryUsage};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
 
 /// Type of index to recommend
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -11,6 +19,19 @@ pub enum IndexType {
     Partial,
     /// B-tree index on extcted scalar values
     BTreeExtracted,
+    /// Partial B-tree on the rare values of a skewed field, e.g. a queue's
+    /// pending rows among completed ones
+    PartialBTree,
+    /// Hash index on extracted values that are only compared for equality
+    HashExtracted,
+    /// Trigram GIN index on extracted text searched with `LIKE`/`ILIKE`
+    TrigramExtracted,
+    /// BRIN index on extracted timestamps that follow the physical row order
+    BrinExtracted,
+    /// Stored generated column holding the extracted value, with a plain index
+    GeneratedColumn,
+    /// B-tree led by plain columns of the table, then the extracted value
+    CompositeBTree,
 }
 
 impl IndexType {
@@ -19,6 +40,12 @@ impl IndexType {
             IndexType::Gin => "GIN",
             IndexType::Partial => "Partial GIN",
             IndexType::BTreeExtracted => "B-tree (extracted)",
+            IndexType::PartialBTree => "Partial B-tree (values)",
+            IndexType::HashExtracted => "Hash (extracted)",
+            IndexType::TrigramExtracted => "Trigram GIN (extracted)",
+            IndexType::BrinExtracted => "BRIN (extracted)",
+            IndexType::GeneratedColumn => "Generated column",
+            IndexType::CompositeBTree => "Composite B-tree",
         }
     }
 }
@@ -42,6 +69,15 @@ impl IndexPriority {
             IndexPriority::Low => "Low",
         }
     }
+
+    /// Sort key, most urgent first
+    pub fn rank(&self) -> u8 {
+        match self {
+            IndexPriority::High => 0,
+            IndexPriority::Medium => 1,
+            IndexPriority::Low => 2,
+        }
+    }
 }
 
 /// Index recommendation for sepecific fields
@@ -53,6 +89,92 @@ pub struct IndexRecommendation {
     pub reason: String,
     pub sql: String,
     pub estimated_benefit: String,
+    /// Approximate on-disk size, set by `estimate_index_sizes`
+    pub estimated_size_bytes: Option<i64>,
+    /// How the planner treats a hypothetical copy of the index, when checked
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub planner_check: Option<PlannerCheck>,
+    /// Recorded queries the index would serve, when the workload was read
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workload: Option<QueryUsage>,
+}
+
+/// The planner's use of a recommended index, checked with a hypothetical
+/// copy of it (hypopg), or with one built in a transaction that's rolled back
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct PlannerCheck {
+    /// Query the index is meant to serve, from `probe_query`
+    pub query: String,
+    /// Whether the planner chose the index for the query
+    pub used: bool,
+    /// Estimated cost of the query without the index
+    pub cost_without: f64,
+    /// Estimated cost of the query with the index
+    pub cost_with: f64,
+    /// Execution time of the query without the index in milliseconds, when
+    /// it was run with EXPLAIN ANALYZE
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_without_ms: Option<f64>,
+    /// Execution time of the query with the index in milliseconds, when it
+    /// was run with EXPLAIN ANALYZE
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_with_ms: Option<f64>,
+}
+
+impl PlannerCheck {
+    /// Share of the query's estimated cost the index saves, when the planner
+    /// used it
+    pub fn cost_reduction(&self) -> Option<f64> {
+        if !self.used || self.cost_without <= 0.0 {
+            return None;
+        }
+        Some((1.0 - self.cost_with / self.cost_without).max(0.0))
+    }
+
+    /// The measured benefit of the index, to replace the estimate a
+    /// recommendation starts with
+    pub fn benefit(&self) -> String {
+        let timing = match (self.time_without_ms, self.time_with_ms) {
+            (Some(without), Some(with)) => {
+                format!(", {:.2} ms -> {:.2} ms when run", without, with)
+            }
+            _ => String::new(),
+        };
+        match self.cost_reduction() {
+            Some(reduction) => format!(
+                "Cuts the estimated cost of the probe query by {:.1}% ({:.1} -> {:.1}{})",
+                reduction * 100.0,
+                self.cost_without,
+                self.cost_with,
+                timing
+            ),
+            None => format!(
+                "None measured: the planner didn't use the index for the probe query (cost {:.1}{})",
+                self.cost_without, timing
+            ),
+        }
+    }
+}
+
+/// Operator class of recommended GIN indexes
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum GinOperatorClass {
+    /// `jsonb_ops`: key existence (`?`, `?|`, `?&`) and containment (`@>`)
+    #[default]
+    JsonbOps,
+    /// `jsonb_path_ops`: containment and jsonpath matches (`@>`, `@?`, `@@`) only,
+    /// in a smaller index that's faster to search
+    JsonbPathOps,
+}
+
+impl GinOperatorClass {
+    /// Indexed expression of a GIN index on `column`
+    fn operand(&self, column: &str) -> String {
+        match self {
+            GinOperatorClass::JsonbOps => quote_ident(column),
+            GinOperatorClass::JsonbPathOps => format!("{} jsonb_path_ops", quote_ident(column)),
+        }
+    }
 }
 
 /// Configuration for index recommendations
@@ -64,6 +186,67 @@ pub struct IndexConfig {
     pub medium_density_threshold: f64,
     /// Minimum occurences for index recommendation (default: 100)
     pub min_occurences: u64,
+    /// Operator class of GIN indexes; `JsonbPathOps` when the column is only
+    /// queried by containment (default: `JsonbOps`)
+    pub gin_operator_class: GinOperatorClass,
+    /// Calls of the recorded queries an index serves from which it is high
+    /// priority (default: 100)
+    pub min_query_calls: u64,
+    /// Share of distinct sampled values from which a path only compared for
+    /// equality gets a hash index instead of a B-tree (default: 0.9)
+    pub hash_distinct_ratio: f64,
+    /// Whether the pg_trgm extension is installed, without which no trigram
+    /// index is recommended (default: false)
+    pub trigram: bool,
+    /// Average length in bytes from which a string path searched with
+    /// `LIKE`/`ILIKE` gets a trigram index (default: 16)
+    pub min_trigram_bytes: f64,
+    /// Paths known to be searched with `LIKE`/`ILIKE`, besides the ones the
+    /// recorded workload shows
+    pub text_search_paths: Vec<String>,
+    /// Correlation with the physical row order (either way) from which a
+    /// timestamp path gets a BRIN index instead of a B-tree (default: 0.9)
+    pub min_brin_correlation: f64,
+    /// How the table is laid out on disk, as far as the caller measured it
+    pub layout: PhysicalLayout,
+    /// Turn extracted B-tree and hash recommendations the recorded queries
+    /// run at least `min_query_calls` times into generated columns with a
+    /// plain index, for ORMs that can't use expression indexes (default: false)
+    pub generated_columns: bool,
+    /// Rows below which every recommendation is low priority (default: 10,000)
+    pub min_table_rows: i64,
+    /// Rows from which selective recommendations are high priority (default: 1,000,000)
+    pub large_table_rows: i64,
+    /// Share of rows a value may match before an index on it is low priority,
+    /// as the planner would rather scan the table (default: 0.1)
+    pub max_selectivity: f64,
+    /// Share of rows a value may match for an index on a large table to be
+    /// high priority (default: 0.01)
+    pub high_selectivity: f64,
+    /// Share of a path's values its most common value must have for the path
+    /// to count as skewed (default: 0.8)
+    pub min_skew: f64,
+    /// Share of a skewed path's values each of its rare values may have to be
+    /// indexed by a partial B-tree on them (default: 0.1)
+    pub max_hot_value_share: f64,
+    /// Plain columns of the table queries filter on along with the JSON
+    /// paths, e.g. `tenant_id`: extracted B-trees are led by them
+    pub with_columns: Vec<String>,
+    /// Schema of the table, to qualify it in the recommended statements;
+    /// left to the search path when `None`
+    pub schema: Option<String>,
+}
+
+/// How the rows of a table are laid out on disk
+#[derive(Debug, Clone, Default)]
+pub struct PhysicalLayout {
+    /// Rows of the table, 0 when unknown
+    pub rows: i64,
+    /// Heap pages of the table, 0 when unknown
+    pub pages: i64,
+    /// Correlation of `brin_candidates` paths with the physical row order,
+    /// from `order_correlation`
+    pub correlations: HashMap<String, f64>,
 }
 
 impl Default for IndexConfig {
@@ -72,6 +255,34 @@ impl Default for IndexConfig {
             high_density_threshold: 0.8,
             medium_density_threshold: 0.2,
             min_occurences: 100,
+            gin_operator_class: GinOperatorClass::JsonbOps,
+            min_query_calls: 100,
+            hash_distinct_ratio: 0.9,
+            trigram: false,
+            min_trigram_bytes: 16.0,
+            text_search_paths: Vec::new(),
+            min_brin_correlation: 0.9,
+            layout: PhysicalLayout::default(),
+            generated_columns: false,
+            min_table_rows: 10_000,
+            large_table_rows: 1_000_000,
+            max_selectivity: 0.1,
+            high_selectivity: 0.01,
+            min_skew: 0.8,
+            max_hot_value_share: 0.1,
+            with_columns: Vec::new(),
+            schema: None,
+        }
+    }
+}
+
+impl IndexConfig {
+    /// `table` as the recommended statements name it: quoted, and qualified
+    /// with `schema` when it's set
+    fn relation(&self, table: &str) -> String {
+        match &self.schema {
+            Some(schema) => format!("{}.{}", quote_ident(schema), quote_ident(table)),
+            None => quote_ident(table),
         }
     }
 }
@@ -85,9 +296,16 @@ pub fn recommend_index(
 ) -> Vec<IndexRecommendation> {
     let mut recommendations = Vec::new();
 
+    // Collapsed map keys can't be expressed as a single indexable path
+    let field_stats: Vec<&FieldStats> = field_stats
+        .iter()
+        .filter(|s| !is_wildcard_path(&s.path))
+        .collect();
+
     // Collect all high-density fields first to create a single consolidated GIN index
     let high_density_fields: Vec<&FieldStats> = field_stats
         .iter()
+        .copied()
         .filter(|s| {
             s.occurrences >= config.min_occurences
                 && s.density >= config.high_density_threshold
@@ -116,6 +334,7 @@ pub fn recommend_index(
             primary_field,
             &high_density_fields,
             IndexPriority::Medium,
+            config,
         ));
     }
 
@@ -130,6 +349,11 @@ pub fn recommend_index(
             continue;
         }
 
+        // Skewed fields are queried for their rare values, whatever their density
+        let partial_btree = create_partial_btree_recommendation(table, column, stats, config);
+        let skewed = partial_btree.is_some();
+        recommendations.extend(partial_btree);
+
         // Skip high-density fields (already handled above)
         if stats.density >= config.high_density_threshold {
             continue;
@@ -141,10 +365,12 @@ pub fn recommend_index(
                 column,
                 stats,
                 IndexPriority::Medium,
+                config,
             ));
         } else if stats.density > config.medium_density_threshold
             && stats.density < config.high_density_threshold
             && is_scalar_type(dominant_type)
+            && !skewed
         {
             recommendations.push(create_btree_extracted_recommendation(
                 table,
@@ -152,22 +378,1468 @@ pub fn recommend_index(
                 stats,
                 dominant_type.unwrap(),
                 IndexPriority::Medium,
+                config,
             ));
         }
     }
 
-    recommendations.sort_by(|a, b| {
-        let priority_order = |p: &IndexPriority| match p {
-            IndexPriority::High => 0,
-            IndexPriority::Medium => 1,
-            IndexPriority::Low => 2,
+    recommendations.sort_by_key(|r| r.priority.rank());
+
+    recommendations
+}
+
+/// An index that already exists on the table, as reported by pg_index
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ExistingIndex {
+    pub name: String,
+    /// Access method, e.g. `gin` or `btree`
+    pub method: String,
+    /// Key columns and expressions, in index order
+    pub keys: Vec<String>,
+    /// Operator class of each key, e.g. `jsonb_path_ops`
+    pub operator_classes: Vec<String>,
+    /// `WHERE` clause of a partial index
+    pub predicate: Option<String>,
+    /// Whether the index enforces uniqueness (including primary keys)
+    pub unique: bool,
+    /// Full `CREATE INDEX` statement
+    pub definition: String,
+}
+
+/// A recommendation left out because an existing index already serves it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoveredRecommendation {
+    pub field_path: String,
+    pub index_type: IndexType,
+    /// Name of the existing index
+    pub covered_by: String,
+}
+
+/// How an existing index overlaps another one
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum IndexOverlapKind {
+    /// Same keys, operator classes and predicate
+    Duplicate,
+    /// B-tree whose keys are the leading keys of another B-tree
+    Prefix,
+    /// Serves a subset of the queries of another index
+    Overlapping,
+}
+
+impl IndexOverlapKind {
+    pub fn to_name(&self) -> &str {
+        match self {
+            IndexOverlapKind::Duplicate => "Duplicate",
+            IndexOverlapKind::Prefix => "Prefix",
+            IndexOverlapKind::Overlapping => "Overlapping",
+        }
+    }
+}
+
+/// An existing index that another existing index makes redundant
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexOverlap {
+    /// Name of the redundant index
+    pub index: String,
+    /// Name of the index that serves its queries
+    pub overlaps: String,
+    pub kind: IndexOverlapKind,
+    pub reason: String,
+    pub definition: String,
+}
+
+/// How much an existing index is used, from pg_stat_user_indexes
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IndexUsage {
+    pub name: String,
+    /// Whether the index enforces uniqueness (including primary keys)
+    pub unique: bool,
+    /// Scans since the statistics were last reset
+    pub scans: i64,
+    /// Size on disk in bytes
+    pub size_bytes: i64,
+}
+
+/// An existing index worth dropping, because nothing scans it or another
+/// index serves its queries
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DropRecommendation {
+    pub index: String,
+    /// Scans since the statistics were last reset
+    pub scans: i64,
+    /// Space the drop frees, in bytes
+    pub size_bytes: i64,
+    pub reason: String,
+    /// `DROP INDEX CONCURRENTLY` statement
+    pub sql: String,
+}
+
+/// Index recommendations checked against the indexes the table already has
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IndexPlan {
+    /// Recommendations no existing index serves
+    pub recommendations: Vec<IndexRecommendation>,
+    /// Recommendations an existing index already serves
+    pub covered: Vec<CoveredRecommendation>,
+    /// Existing indexes made redundant by another existing index
+    pub overlaps: Vec<IndexOverlap>,
+}
+
+/// Recommend indexes like `recommend_index`, prioritized by the size of the
+/// table, how selective each index is and the recorded `accesses` to the
+/// column when there are any, leaving out the ones the table already has and
+/// flagging redundant existing indexes
+///
+/// A recommended GIN index is covered by a GIN index on the bare column with
+/// an operator class that serves the same operators (`jsonb_ops` serves
+/// everything `jsonb_path_ops` does). An extracted B-tree is covered by a
+/// B-tree whose leading key extracts the same path to the same type, however
+/// it is spelled (`data->'a'->>'b'` or `data #>> '{a,b}'`), and an extracted
+/// hash index by such a B-tree or hash index. An extracted trigram index is
+/// covered by a `gin_trgm_ops` or `gist_trgm_ops` index on the same
+/// expression, an extracted BRIN index by a B-tree or BRIN index on it, and a
+/// generated column by a B-tree on the expression or on a column of its name.
+/// A partial B-tree on rare values is covered by a B-tree on the expression,
+/// and a composite B-tree by one with the same leading columns before it.
+/// Partial indexes only count when their predicate matches the recommended one.
+pub fn plan_indexes(
+    table: &str,
+    column: &str,
+    field_stats: &[FieldStats],
+    accesses: &[ColumnAccess],
+    existing: &[ExistingIndex],
+    config: &IndexConfig,
+) -> IndexPlan {
+    let mut plan = IndexPlan {
+        overlaps: find_overlapping_indexes(existing),
+        ..Default::default()
+    };
+
+    let mut recommendations = recommend_index(table, column, field_stats, config);
+    recommendations.extend(recommend_trigram_indexes(
+        table,
+        column,
+        field_stats,
+        accesses,
+        config,
+    ));
+    recommend_brin_indexes(table, column, field_stats, &mut recommendations, config);
+    assign_priorities(&mut recommendations, field_stats, config);
+    if accesses.is_empty() {
+        recommendations.sort_by_key(|r| r.priority.rank());
+    } else {
+        recommendations = prioritize_by_workload(
+            table,
+            column,
+            recommendations,
+            field_stats,
+            accesses,
+            config,
+        );
+        if config.generated_columns {
+            for recommendation in &mut recommendations {
+                use_generated_column(table, column, recommendation, field_stats, config);
+            }
+        }
+    }
+    if !config.with_columns.is_empty() {
+        for recommendation in &mut recommendations {
+            lead_with_columns(table, column, recommendation, field_stats, config);
+        }
+    }
+    for recommendation in recommendations {
+        let value_type = field_stats
+            .iter()
+            .find(|s| s.path == recommendation.field_path)
+            .and_then(get_dominant_type)
+            .map(|json_type| match json_type {
+                JsonType::Number => "numeric",
+                JsonType::Boolean => "boolean",
+                _ => "text",
+            });
+        match existing
+            .iter()
+            .find(|index| covers(index, &recommendation, column, value_type, config))
+        {
+            Some(index) => plan.covered.push(CoveredRecommendation {
+                field_path: recommendation.field_path,
+                index_type: recommendation.index_type,
+                covered_by: index.name.clone(),
+            }),
+            None => plan.recommendations.push(recommendation),
+        }
+    }
+
+    plan
+}
+
+/// Trigram indexes for long string paths searched with `LIKE`/`ILIKE`, either
+/// listed in `text_search_paths` or seen in the recorded accesses, when
+/// pg_trgm is installed
+fn recommend_trigram_indexes(
+    table: &str,
+    column: &str,
+    field_stats: &[FieldStats],
+    accesses: &[ColumnAccess],
+    config: &IndexConfig,
+) -> Vec<IndexRecommendation> {
+    if !config.trigram {
+        return Vec::new();
+    }
+
+    let mut paths: Vec<&str> = config
+        .text_search_paths
+        .iter()
+        .map(String::as_str)
+        .collect();
+    for access in accesses {
+        if let Some(path) = &access.path
+            && access.operator == JsonOperator::Pattern
+            && !paths.contains(&path.as_str())
+        {
+            paths.push(path);
+        }
+    }
+
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            field_stats
+                .iter()
+                .find(|s| s.path == path && !is_wildcard_path(&s.path))
+        })
+        .filter(|stats| {
+            get_dominant_type(stats) == Some(JsonType::String)
+                && stats
+                    .strings
+                    .as_ref()
+                    .is_some_and(|s| s.avg_bytes >= config.min_trigram_bytes)
+        })
+        .map(|stats| create_trigram_extracted_recommendation(table, column, stats, config))
+        .collect()
+}
+
+/// Timestamp paths a BRIN index might suit: strings nearly always in an ISO
+/// 8601 date or timestamp format, outside arrays. How each follows the
+/// physical row order is for the caller to measure into `PhysicalLayout`.
+pub fn brin_candidates(field_stats: &[FieldStats], config: &IndexConfig) -> Vec<String> {
+    field_stats
+        .iter()
+        .filter(|s| {
+            !s.path.is_empty()
+                && !s.path.contains("[]")
+                && !is_wildcard_path(&s.path)
+                && s.occurrences >= config.min_occurences
+                && get_dominant_type(s) == Some(JsonType::String)
+                && s.format.as_ref().is_some_and(|f| {
+                    matches!(f.format, StringFormat::Date | StringFormat::DateTime)
+                        && f.consistency >= 95.0
+                })
+        })
+        .map(|s| s.path.clone())
+        .collect()
+}
+
+/// Rank (Spearman) correlation between the block number of rows and their
+/// value at a path, from -1 to 1
+///
+/// ISO 8601 strings sort in time order, so they are ranked as they are.
+/// `None` with fewer than 3 rows or when either side never changes.
+pub fn order_correlation(rows: &[(i64, String)]) -> Option<f64> {
+    if rows.len() < 3 {
+        return None;
+    }
+    let blocks = ranks(&rows.iter().map(|(block, _)| block).collect::<Vec<_>>());
+    let values = ranks(&rows.iter().map(|(_, value)| value).collect::<Vec<_>>());
+
+    let n = rows.len() as f64;
+    let mean = (n + 1.0) / 2.0;
+    let (mut covariance, mut block_variance, mut value_variance) = (0.0, 0.0, 0.0);
+    for (b, v) in blocks.iter().zip(&values) {
+        covariance += (b - mean) * (v - mean);
+        block_variance += (b - mean) * (b - mean);
+        value_variance += (v - mean) * (v - mean);
+    }
+    if block_variance == 0.0 || value_variance == 0.0 {
+        return None;
+    }
+    Some(covariance / (block_variance * value_variance).sqrt())
+}
+
+/// 1-based ranks of `values`, ties sharing the mean of their ranks
+fn ranks<T: Ord>(values: &[T]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| values[a].cmp(&values[b]));
+    let mut ranks = vec![0.0; values.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start + 1;
+        while end < order.len() && values[order[end]] == values[order[start]] {
+            end += 1;
+        }
+        let rank = (start + end + 1) as f64 / 2.0;
+        for &i in &order[start..end] {
+            ranks[i] = rank;
+        }
+        start = end;
+    }
+    ranks
+}
+
+/// Replace B-trees with BRIN indexes on timestamp paths that follow the
+/// physical row order closely enough, adding them where no B-tree was
+/// recommended
+fn recommend_brin_indexes(
+    table: &str,
+    column: &str,
+    field_stats: &[FieldStats],
+    recommendations: &mut Vec<IndexRecommendation>,
+    config: &IndexConfig,
+) {
+    for path in brin_candidates(field_stats, config) {
+        let Some(&correlation) = config.layout.correlations.get(&path) else {
+            continue;
         };
-        priority_order(&a.priority).cmp(&priority_order(&b.priority))
-    });
+        if correlation.abs() < config.min_brin_correlation {
+            continue;
+        }
+        let Some(stats) = field_stats.iter().find(|s| s.path == path) else {
+            continue;
+        };
+        recommendations
+            .retain(|r| !(r.index_type == IndexType::BTreeExtracted && r.field_path == path));
+        recommendations.push(create_brin_extracted_recommendation(
+            table,
+            column,
+            stats,
+            correlation,
+            config,
+        ));
+    }
+}
+
+/// Set the priority of each recommendation from the size of the table and the
+/// share of rows a lookup through the index would match, noting why in its
+/// reason
+///
+/// On a table under `min_table_rows` a sequential scan is about as fast, and
+/// an index whose values each match more than `max_selectivity` of the rows
+/// is one the planner would skip: both are low priority. Over
+/// `large_table_rows`, indexes matching at most `high_selectivity` per value
+/// are high priority, as are BRIN and trigram indexes, which serve ranges and
+/// patterns rather than single values. The rest stay medium. A value's share
+/// is its path's density over its distinct values, the most selective of the
+/// high-density paths for the GIN index on the whole column.
+fn assign_priorities(
+    recommendations: &mut [IndexRecommendation],
+    field_stats: &[FieldStats],
+    config: &IndexConfig,
+) {
+    let rows = config.layout.rows;
+    for recommendation in recommendations.iter_mut() {
+        let selectivity = match recommendation.index_type {
+            IndexType::BrinExtracted | IndexType::TrigramExtracted => None,
+            IndexType::PartialBTree => field_stats
+                .iter()
+                .find(|s| s.path == recommendation.field_path)
+                .and_then(|s| {
+                    let hottest = hot_values(s, config).first()?.percentage / 100.0;
+                    Some(non_null_share(s) * hottest)
+                }),
+            IndexType::Gin => field_stats
+                .iter()
+                .filter(|s| {
+                    s.density >= config.high_density_threshold
+                        && is_scalar_type(get_dominant_type(s))
+                })
+                .filter_map(|s| lookup_selectivity(s, rows))
+                .min_by(f64::total_cmp),
+            _ => field_stats
+                .iter()
+                .find(|s| s.path == recommendation.field_path)
+                .and_then(|s| lookup_selectivity(s, rows)),
+        };
+        let selective = match recommendation.index_type {
+            IndexType::BrinExtracted | IndexType::TrigramExtracted => true,
+            _ => selectivity.is_some_and(|s| s <= config.high_selectivity),
+        };
+
+        let (priority, note) = if rows > 0 && rows < config.min_table_rows {
+            (
+                IndexPriority::Low,
+                format!(
+                    "Small table ({} rows): a sequential scan is about as fast.",
+                    rows
+                ),
+            )
+        } else if let Some(share) = selectivity.filter(|s| *s > config.max_selectivity) {
+            (
+                IndexPriority::Low,
+                format!(
+                    "Each value matches ~{:.1}% of rows, too many for the planner to prefer the index.",
+                    share * 100.0
+                ),
+            )
+        } else if rows >= config.large_table_rows && selective {
+            let note = match selectivity {
+                Some(share) => format!(
+                    "Large table ({} rows), each value matching ~{:.3}% of them.",
+                    rows,
+                    share * 100.0
+                ),
+                None => format!("Large table ({} rows).", rows),
+            };
+            (IndexPriority::High, note)
+        } else {
+            continue;
+        };
+        recommendation.priority = priority;
+        recommendation.reason = format!("{} {}", recommendation.reason, note);
+    }
+}
 
+/// Share of the table's rows one value of the field matches, `None` when no
+/// value was seen
+fn lookup_selectivity(stats: &FieldStats, rows: i64) -> Option<f64> {
+    let values = stats.occurrences.saturating_sub(stats.null_count) as f64;
+    if values == 0.0 || stats.cardinality == 0 {
+        return None;
+    }
+    let share = non_null_share(stats);
+    // Values seen about once each in the sample are likely unique in the table
+    let distinct = if rows > 0 && stats.cardinality as f64 >= 0.9 * values {
+        rows as f64 * share
+    } else {
+        stats.cardinality as f64
+    };
+    Some(share / distinct.max(1.0))
+}
+
+/// Raise or lower recommendations by how the recorded workload queries the column
+///
+/// Each recommendation is matched with the accesses its index serves: any
+/// `@>` (and `?` for `jsonb_ops`) for the GIN index on the whole column, `@>`
+/// or `?` on its own path for a partial GIN index, and extraction of its path
+/// for a B-tree or BRIN index (`=` or `IN` only for a hash index, `LIKE`
+/// for a trigram index). Matches with at least `min_query_calls` calls make it high
+/// priority; when nothing could match, not even an access with parameter
+/// keys, it drops to low priority. Extracted scalar paths the density
+/// heuristics left out get a B-tree of their own. A B-tree on a nearly unique
+/// path the workload only compares with `=` or `IN` becomes a hash index. The
+/// busiest come first.
+fn prioritize_by_workload(
+    table: &str,
+    column: &str,
+    recommendations: Vec<IndexRecommendation>,
+    field_stats: &[FieldStats],
+    accesses: &[ColumnAccess],
+    config: &IndexConfig,
+) -> Vec<IndexRecommendation> {
+    let mut recommendations: Vec<IndexRecommendation> = recommendations
+        .into_iter()
+        .map(|mut recommendation| {
+            if recommendation.index_type == IndexType::BTreeExtracted
+                && let Some(hash) = field_stats
+                    .iter()
+                    .find(|s| s.path == recommendation.field_path)
+                    .and_then(|stats| {
+                        create_hash_extracted_recommendation(
+                            table,
+                            column,
+                            stats,
+                            accesses,
+                            recommendation.priority.clone(),
+                            config,
+                        )
+                    })
+            {
+                recommendation = hash;
+            }
+
+            let mut usage = QueryUsage::default();
+            for access in accesses
+                .iter()
+                .filter(|a| serves(&recommendation, a, config.gin_operator_class))
+            {
+                usage.add(&access.usage);
+            }
+
+            if usage.calls > 0 {
+                if usage.calls >= config.min_query_calls {
+                    recommendation.priority = IndexPriority::High;
+                }
+                recommendation.reason = format!(
+                    "{} Recorded queries: {} statement(s), {} calls, {:.1} ms mean.",
+                    recommendation.reason, usage.statements, usage.calls, usage.mean_exec_time_ms
+                );
+                recommendation.workload = Some(usage);
+            } else if !accesses
+                .iter()
+                .any(|a| might_serve(&recommendation, a, config.gin_operator_class))
+            {
+                recommendation.priority = IndexPriority::Low;
+                recommendation.reason =
+                    format!("{} No recorded query uses it.", recommendation.reason);
+            }
+            recommendation
+        })
+        .collect();
+
+    for access in accesses {
+        let Some(path) = &access.path else {
+            continue;
+        };
+        let extracted = |r: &IndexRecommendation| {
+            matches!(
+                r.index_type,
+                IndexType::BTreeExtracted
+                    | IndexType::PartialBTree
+                    | IndexType::CompositeBTree
+                    | IndexType::HashExtracted
+                    | IndexType::BrinExtracted
+            ) && r.field_path == *path
+        };
+        if !matches!(
+            access.operator,
+            JsonOperator::Extract | JsonOperator::Equality
+        ) || recommendations.iter().any(extracted)
+        {
+            continue;
+        }
+        let Some(stats) = field_stats
+            .iter()
+            .find(|s| s.path == *path && !is_wildcard_path(&s.path))
+        else {
+            continue;
+        };
+        let dominant_type = get_dominant_type(stats);
+        if !is_scalar_type(dominant_type) {
+            continue;
+        }
+
+        // Extractions of the path with and without `=` are separate accesses
+        let mut usage = QueryUsage::default();
+        for other in accesses.iter().filter(|a| {
+            a.path == access.path
+                && matches!(a.operator, JsonOperator::Extract | JsonOperator::Equality)
+        }) {
+            usage.add(&other.usage);
+        }
+        let priority = if usage.calls >= config.min_query_calls {
+            IndexPriority::High
+        } else {
+            IndexPriority::Medium
+        };
+        let mut recommendation = match create_hash_extracted_recommendation(
+            table,
+            column,
+            stats,
+            accesses,
+            priority.clone(),
+            config,
+        ) {
+            Some(mut recommendation) => {
+                recommendation.reason = format!(
+                    "{} Recorded queries: {} statement(s), {} calls, {:.1} ms mean.",
+                    recommendation.reason, usage.statements, usage.calls, usage.mean_exec_time_ms
+                );
+                recommendation
+            }
+            None => {
+                let mut recommendation = create_btree_extracted_recommendation(
+                    table,
+                    column,
+                    stats,
+                    dominant_type.unwrap(),
+                    priority,
+                    config,
+                );
+                recommendation.reason = format!(
+                    "Extracted by {} recorded statement(s) ({} calls, {:.1} ms mean). \
+                     B-tree index on the extracted value serves equality and range filters.",
+                    usage.statements, usage.calls, usage.mean_exec_time_ms
+                );
+                recommendation
+            }
+        };
+        recommendation.workload = Some(usage);
+        recommendations.push(recommendation);
+    }
+
+    recommendations.sort_by(|a, b| {
+        let total_time =
+            |r: &IndexRecommendation| r.workload.as_ref().map_or(0.0, QueryUsage::total_time_ms);
+        a.priority
+            .rank()
+            .cmp(&b.priority.rank())
+            .then_with(|| total_time(b).total_cmp(&total_time(a)))
+    });
     recommendations
 }
 
+/// Turn a busy extracted B-tree or hash recommendation into a stored generated
+/// column with a B-tree on it
+fn use_generated_column(
+    table: &str,
+    column: &str,
+    recommendation: &mut IndexRecommendation,
+    field_stats: &[FieldStats],
+    config: &IndexConfig,
+) {
+    let busy = recommendation
+        .workload
+        .as_ref()
+        .is_some_and(|usage| usage.calls >= config.min_query_calls);
+    if !busy
+        || !matches!(
+            recommendation.index_type,
+            IndexType::BTreeExtracted | IndexType::HashExtracted
+        )
+    {
+        return;
+    }
+    let Some(json_type) = field_stats
+        .iter()
+        .find(|s| s.path == recommendation.field_path)
+        .and_then(get_dominant_type)
+    else {
+        return;
+    };
+
+    let new_column = column_name(&recommendation.field_path);
+    let relation = config.relation(table);
+    let (extraction_expr, pg_type) =
+        extraction_expression(column, &recommendation.field_path, json_type);
+    recommendation.index_type = IndexType::GeneratedColumn;
+    recommendation.sql = format!(
+        "-- Generated {} column for {}; adding it rewrites the table under an exclusive lock\n\
+        ALTER TABLE {} ADD COLUMN {} {} GENERATED ALWAYS AS {} STORED;\n\
+        CREATE INDEX {} ON {} ({});",
+        pg_type,
+        recommendation.field_path,
+        relation,
+        quote_ident(&new_column),
+        pg_type,
+        extraction_expr,
+        generate_index_name(table, column, &recommendation.field_path, "gen"),
+        relation,
+        quote_ident(&new_column)
+    );
+    recommendation.reason = format!(
+        "{} Busy enough for a column of its own: ORMs map and filter on {} like any \
+         other column, while an expression index only serves queries spelling out the \
+         same expression. Queries have to be changed to use the column.",
+        recommendation.reason, new_column
+    );
+    recommendation.estimated_benefit = format!(
+        "Equality and range filters on {} through a plain B-tree, with planner statistics of its own.",
+        new_column
+    );
+}
+
+/// Turn an extracted B-tree recommendation, partial or not, into a composite
+/// B-tree led by `with_columns`
+///
+/// The plain columns come first, in the order given: queries usually compare
+/// them for equality (`tenant_id = $1`), which narrows the index to one
+/// range for the extracted value to be searched in. The partial predicate is
+/// kept.
+fn lead_with_columns(
+    table: &str,
+    column: &str,
+    recommendation: &mut IndexRecommendation,
+    field_stats: &[FieldStats],
+    config: &IndexConfig,
+) {
+    if !matches!(
+        recommendation.index_type,
+        IndexType::BTreeExtracted | IndexType::PartialBTree
+    ) {
+        return;
+    }
+    let Some(json_type) = field_stats
+        .iter()
+        .find(|s| s.path == recommendation.field_path)
+        .and_then(get_dominant_type)
+    else {
+        return;
+    };
+    let Some(predicate) = recommended_predicate(recommendation).map(str::to_string) else {
+        return;
+    };
+
+    let leading = config.with_columns.join(", ");
+    let (extraction_expr, pg_type) =
+        extraction_expression(column, &recommendation.field_path, json_type);
+    recommendation.index_type = IndexType::CompositeBTree;
+    recommendation.sql = format!(
+        "-- Composite B-tree on {} and the extracted {} value\n\
+        CREATE INDEX {} ON {} ({}, {}) WHERE {};",
+        leading,
+        pg_type,
+        generate_index_name(table, column, &recommendation.field_path, "comp"),
+        config.relation(table),
+        config
+            .with_columns
+            .iter()
+            .map(|with| quote_ident(with))
+            .collect::<Vec<_>>()
+            .join(", "),
+        extraction_expr,
+        predicate
+    );
+    recommendation.reason = format!(
+        "{} Led by {}, which queries filter on as well.",
+        recommendation.reason, leading
+    );
+    recommendation.estimated_benefit = format!(
+        "Filters on {} and {} together in one index scan.",
+        leading, recommendation.field_path
+    );
+}
+
+/// Whether the recommended index serves an access
+fn serves(
+    recommendation: &IndexRecommendation,
+    access: &ColumnAccess,
+    opclass: GinOperatorClass,
+) -> bool {
+    let on_path = access.path.as_deref() == Some(recommendation.field_path.as_str());
+    match recommendation.index_type {
+        IndexType::Gin => match access.operator {
+            JsonOperator::Contains => true,
+            JsonOperator::Exists => opclass == GinOperatorClass::JsonbOps,
+            JsonOperator::Extract | JsonOperator::Equality | JsonOperator::Pattern => false,
+        },
+        IndexType::Partial => {
+            matches!(
+                access.operator,
+                JsonOperator::Contains | JsonOperator::Exists
+            ) && on_path
+        }
+        IndexType::BTreeExtracted
+        | IndexType::PartialBTree
+        | IndexType::CompositeBTree
+        | IndexType::BrinExtracted
+        | IndexType::GeneratedColumn => {
+            matches!(
+                access.operator,
+                JsonOperator::Extract | JsonOperator::Equality
+            ) && on_path
+        }
+        IndexType::HashExtracted => access.operator == JsonOperator::Equality && on_path,
+        IndexType::TrigramExtracted => access.operator == JsonOperator::Pattern && on_path,
+    }
+}
+
+/// Whether the recorded queries only compare `path` for equality: some may
+/// compare it with `=` or `IN`, and no other extraction may be of it
+fn only_compared_for_equality(path: &str, accesses: &[ColumnAccess]) -> bool {
+    let of_path: Vec<&ColumnAccess> = accesses
+        .iter()
+        .filter(|a| a.path.is_none() || a.path.as_deref() == Some(path))
+        .collect();
+    of_path.iter().any(|a| a.operator == JsonOperator::Equality)
+        && !of_path
+            .iter()
+            .any(|a| matches!(a.operator, JsonOperator::Extract | JsonOperator::Pattern))
+}
+
+/// Whether nearly every non-null value of the field is distinct
+fn is_nearly_unique(stats: &FieldStats, config: &IndexConfig) -> bool {
+    let values = stats.occurrences.saturating_sub(stats.null_count);
+    values > 0 && stats.cardinality as f64 >= config.hash_distinct_ratio * values as f64
+}
+
+/// Whether the recommended index might serve an access whose keys are parameters
+fn might_serve(
+    recommendation: &IndexRecommendation,
+    access: &ColumnAccess,
+    opclass: GinOperatorClass,
+) -> bool {
+    access.path.is_none()
+        && serves(
+            recommendation,
+            &ColumnAccess {
+                path: Some(recommendation.field_path.clone()),
+                ..access.clone()
+            },
+            opclass,
+        )
+}
+
+/// Whether an existing index serves the queries of a recommendation
+fn covers(
+    index: &ExistingIndex,
+    recommendation: &IndexRecommendation,
+    column: &str,
+    value_type: Option<&str>,
+    config: &IndexConfig,
+) -> bool {
+    let Some(key) = index.keys.first().map(|key| normalize_expression(key)) else {
+        return false;
+    };
+    let normalized_column = normalize_expression(column);
+    let predicate = index.predicate.as_deref().map(normalize_expression);
+
+    match recommendation.index_type {
+        IndexType::Gin | IndexType::Partial => {
+            let serves = match index.operator_classes.first().map(String::as_str) {
+                Some("jsonb_ops") => true,
+                Some("jsonb_path_ops") => {
+                    config.gin_operator_class == GinOperatorClass::JsonbPathOps
+                }
+                _ => false,
+            };
+            let same_rows = match &predicate {
+                None => true,
+                Some(predicate) => {
+                    recommendation.index_type == IndexType::Partial
+                        && *predicate
+                            == normalize_expression(&json_path_to_sql_conditions(
+                                column,
+                                &recommendation.field_path,
+                            ))
+                }
+            };
+            index.method == "gin"
+                && index.keys.len() == 1
+                && key == normalized_column
+                && serves
+                && same_rows
+        }
+        IndexType::BTreeExtracted
+        | IndexType::PartialBTree
+        | IndexType::CompositeBTree
+        | IndexType::HashExtracted
+        | IndexType::TrigramExtracted
+        | IndexType::BrinExtracted
+        | IndexType::GeneratedColumn => {
+            if recommendation.index_type == IndexType::GeneratedColumn
+                && index.method == "btree"
+                && key == normalize_expression(&column_name(&recommendation.field_path))
+            {
+                return true;
+            }
+            // The leading columns must match before the extracted value
+            let key = if recommendation.index_type == IndexType::CompositeBTree {
+                let leading = config.with_columns.len();
+                if index.keys.len() <= leading
+                    || !index
+                        .keys
+                        .iter()
+                        .zip(&config.with_columns)
+                        .all(|(key, with)| normalize_expression(key) == normalize_expression(with))
+                {
+                    return false;
+                }
+                normalize_expression(&index.keys[leading])
+            } else {
+                key
+            };
+            let method = match recommendation.index_type {
+                IndexType::HashExtracted => index.method == "btree" || index.method == "hash",
+                IndexType::BrinExtracted => index.method == "btree" || index.method == "brin",
+                IndexType::TrigramExtracted => matches!(
+                    index.operator_classes.first().map(String::as_str),
+                    Some("gin_trgm_ops" | "gist_trgm_ops")
+                ),
+                _ => index.method == "btree",
+            };
+            let path: Vec<String> = recommendation
+                .field_path
+                .replace("[]", "")
+                .split('.')
+                .map(str::to_string)
+                .collect();
+            let same_rows = match &predicate {
+                None => true,
+                Some(predicate) => {
+                    predicate.strip_suffix("isnotnull") == Some(key.as_str())
+                        || (recommendation.index_type == IndexType::PartialBTree
+                            && recommended_predicate(recommendation)
+                                .is_some_and(|p| normalize_expression(p) == *predicate))
+                }
+            };
+            method
+                && extracted_path(&key, &normalized_column)
+                    .is_some_and(|(keys, cast)| keys == path && Some(cast.as_str()) == value_type)
+                && same_rows
+        }
+    }
+}
+
+/// Find existing indexes whose queries another existing index already serves
+///
+/// Unique indexes are never reported, since they enforce a constraint. Of two
+/// identical indexes, the one listed later is reported.
+pub fn find_overlapping_indexes(existing: &[ExistingIndex]) -> Vec<IndexOverlap> {
+    let normalized: Vec<(Vec<String>, Option<String>)> = existing
+        .iter()
+        .map(|index| {
+            (
+                index.keys.iter().map(|k| normalize_expression(k)).collect(),
+                index.predicate.as_deref().map(normalize_expression),
+            )
+        })
+        .collect();
+
+    let mut overlaps = Vec::new();
+    for (i, index) in existing.iter().enumerate() {
+        if index.unique {
+            continue;
+        }
+        let (keys, predicate) = &normalized[i];
+        let overlap = existing.iter().enumerate().find_map(|(j, other)| {
+            if i == j || index.method != other.method {
+                return None;
+            }
+            let (other_keys, other_predicate) = &normalized[j];
+            let same_predicate = predicate == other_predicate;
+
+            if keys == other_keys
+                && index.operator_classes == other.operator_classes
+                && same_predicate
+                && (other.unique || j < i)
+            {
+                return Some((
+                    other,
+                    IndexOverlapKind::Duplicate,
+                    format!("Same definition as {}", other.name),
+                ));
+            }
+            if index.method == "btree"
+                && keys.len() < other_keys.len()
+                && other_keys.starts_with(keys)
+                && other.operator_classes.starts_with(&index.operator_classes)
+                && same_predicate
+            {
+                return Some((
+                    other,
+                    IndexOverlapKind::Prefix,
+                    format!(
+                        "Keys are the leading keys of {}, which serves the same queries",
+                        other.name
+                    ),
+                ));
+            }
+            if keys == other_keys
+                && index.operator_classes == other.operator_classes
+                && predicate.is_some()
+                && other_predicate.is_none()
+            {
+                return Some((
+                    other,
+                    IndexOverlapKind::Overlapping,
+                    format!(
+                        "Partial index on the same keys as {}, which already indexes every row",
+                        other.name
+                    ),
+                ));
+            }
+            if index.method == "gin"
+                && keys.len() == 1
+                && keys == other_keys
+                && index.operator_classes.first().map(String::as_str) == Some("jsonb_path_ops")
+                && other.operator_classes.first().map(String::as_str) == Some("jsonb_ops")
+                && (same_predicate || other_predicate.is_none())
+            {
+                return Some((
+                    other,
+                    IndexOverlapKind::Overlapping,
+                    format!(
+                        "{} (jsonb_ops) also serves containment queries; \
+                         keep this jsonb_path_ops index only if they need its smaller size",
+                        other.name
+                    ),
+                ));
+            }
+            None
+        });
+
+        if let Some((other, kind, reason)) = overlap {
+            overlaps.push(IndexOverlap {
+                index: index.name.clone(),
+                overlaps: other.name.clone(),
+                kind,
+                reason,
+                definition: index.definition.clone(),
+            });
+        }
+    }
+
+    overlaps
+}
+
+/// Suggest dropping the indexes in `usage` that another index makes redundant
+/// (from `find_overlapping_indexes`) or that were never scanned, largest first
+///
+/// Unique indexes are kept, since they enforce a constraint. A redundant
+/// index may still be scanned: its queries move to the index it overlaps.
+pub fn recommend_drops(
+    schema: &str,
+    overlaps: &[IndexOverlap],
+    usage: &[IndexUsage],
+) -> Vec<DropRecommendation> {
+    let mut drops: Vec<DropRecommendation> = usage
+        .iter()
+        .filter(|index| !index.unique)
+        .filter_map(|index| {
+            let reason = match overlaps.iter().find(|o| o.index == index.name) {
+                Some(overlap) => format!(
+                    "{} of {}: {}",
+                    overlap.kind.to_name(),
+                    overlap.overlaps,
+                    overlap.reason
+                ),
+                None if index.scans == 0 => "Never scanned.".to_string(),
+                None => return None,
+            };
+            Some(DropRecommendation {
+                index: index.name.clone(),
+                scans: index.scans,
+                size_bytes: index.size_bytes,
+                reason,
+                sql: format!(
+                    "DROP INDEX CONCURRENTLY IF EXISTS {}.{};",
+                    quote_ident(schema),
+                    quote_ident(&index.name)
+                ),
+            })
+        })
+        .collect();
+    drops.sort_by_key(|d| std::cmp::Reverse(d.size_bytes));
+    drops
+}
+
+/// Canonical form of an index key or predicate for comparisons: lowercase and
+/// without whitespace, parentheses, identifier quotes or casts of literals
+/// (`pg_get_indexdef` prints `'email'::text` where the DDL said `'email'`)
+fn normalize_expression(expr: &str) -> String {
+    let mut normalized = String::new();
+    let mut chars = expr.chars().peekable();
+    let mut after_literal = false;
+    while let Some(c) = chars.next() {
+        if c == '\'' {
+            normalized.push(c);
+            while let Some(c) = chars.next() {
+                normalized.push(c);
+                if c == '\'' {
+                    if chars.peek() == Some(&'\'') {
+                        normalized.push('\'');
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+            }
+            after_literal = true;
+            continue;
+        }
+        if after_literal && c == ':' && chars.peek() == Some(&':') {
+            chars.next();
+            while chars
+                .peek()
+                .is_some_and(|c| c.is_alphanumeric() || matches!(c, '_' | '[' | ']'))
+            {
+                chars.next();
+            }
+            continue;
+        }
+        after_literal = false;
+        if c.is_whitespace() || matches!(c, '(' | ')' | '"') {
+            continue;
+        }
+        normalized.extend(c.to_lowercase());
+    }
+    normalized
+}
+
+/// Path and result type of a normalized expression extracting a value from
+/// `column`, e.g. `(["a", "b"], "numeric")` for `((data #>> '{a,b}'))::numeric`
+fn extracted_path(expr: &str, column: &str) -> Option<(Vec<String>, String)> {
+    let mut rest = expr.strip_prefix(column)?;
+    let mut keys = Vec::new();
+    let mut as_text = false;
+    loop {
+        let (op_text, op_path, after) = if let Some(after) = rest.strip_prefix("#>>") {
+            (true, true, after)
+        } else if let Some(after) = rest.strip_prefix("#>") {
+            (false, true, after)
+        } else if let Some(after) = rest.strip_prefix("->>") {
+            (true, false, after)
+        } else if let Some(after) = rest.strip_prefix("->") {
+            (false, false, after)
+        } else {
+            break;
+        };
+        let (literal, after) = split_literal(after)?;
+        if op_path {
+            let literal = literal.strip_prefix('{')?.strip_suffix('}')?;
+            keys.extend(literal.split(',').map(str::to_string));
+        } else {
+            keys.push(literal);
+        }
+        as_text = op_text;
+        rest = after;
+    }
+
+    if keys.is_empty() {
+        return None;
+    }
+    let value_type = match rest.strip_prefix("::") {
+        Some(cast) => cast.to_string(),
+        None if rest.is_empty() && as_text => "text".to_string(),
+        None if rest.is_empty() => "jsonb".to_string(),
+        None => return None,
+    };
+    Some((keys, value_type))
+}
+
+/// Split a leading SQL string literal off `s`, unescaping doubled quotes
+fn split_literal(s: &str) -> Option<(String, &str)> {
+    let body = s.strip_prefix('\'')?;
+    let mut literal = String::new();
+    let mut chars = body.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if c == '\'' {
+            if chars.peek().is_some_and(|(_, c)| *c == '\'') {
+                literal.push('\'');
+                chars.next();
+            } else {
+                return Some((literal, &body[i + 1..]));
+            }
+        } else {
+            literal.push(c);
+        }
+    }
+    None
+}
+
+/// Bytes of a Postgres page
+const PAGE_BYTES: f64 = 8192.0;
+/// Bytes of a page left for tuples, after the page header and index special space
+const PAGE_USABLE_BYTES: f64 = 8152.0;
+/// Default fill factor of B-tree leaf pages and GIN entry pages
+const FILL_FACTOR: f64 = 0.9;
+/// Default fill factor of hash index buckets
+const HASH_FILL_FACTOR: f64 = 0.75;
+/// Distinct trigrams assumed in a column of free text
+const TRIGRAM_ENTRIES: f64 = 50_000.0;
+/// Heap pages summarized by each BRIN range (default `pages_per_range`)
+const BRIN_PAGES_PER_RANGE: f64 = 128.0;
+/// Average bytes per (entry, row) pair in the varbyte-compressed GIN posting lists
+const GIN_POSTING_BYTES: f64 = 2.0;
+/// Longest key or value `jsonb_ops` stores as is; longer ones are hashed
+const GIN_MAX_ENTRY_BYTES: f64 = 125.0;
+
+/// Estimate the on-disk size of each recommended index on a table of `row_count` rows
+///
+/// The figures are rough, meant to weigh a recommendation against its storage:
+/// - a B-tree has one tuple per row with a non-null value: 8 bytes of header
+///   and the key, 8-byte aligned, plus a 4-byte line pointer
+/// - a GIN index has one entry per distinct key and value (`jsonb_ops`) or
+///   per distinct path-to-value hash (`jsonb_path_ops`), plus about 2 bytes
+///   per (entry, row) pair in its compressed posting lists. Values that are
+///   nearly unique in the sample are taken as unique across the table.
+/// - a generated column's index is sized as a B-tree; the column itself
+///   isn't counted
+/// - a partial B-tree on rare values is sized as a B-tree over the rows
+///   holding them
+/// - a hash index has one tuple per row with a non-null value, holding its
+///   4-byte hash code whatever the value's size
+/// - a trigram index has about one posting per character of each value, and
+///   a few tens of thousands of distinct trigrams
+/// - a BRIN index has one min/max summary per 128 heap pages, plus 6 bytes
+///   of range map. Without `layout.pages`, every row is taken to fill a page.
+///   Its benefit notes how much smaller it is than a B-tree.
+///
+/// Pages are filled to the defaults (90%, 75% for hash buckets), plus a
+/// metapage and, for hash indexes, a bitmap page.
+pub fn estimate_index_sizes(
+    recommendations: &mut [IndexRecommendation],
+    field_stats: &[FieldStats],
+    row_count: i64,
+    config: &IndexConfig,
+) {
+    let rows = row_count.max(0) as f64;
+    for recommendation in recommendations.iter_mut() {
+        let stats = field_stats
+            .iter()
+            .find(|s| s.path == recommendation.field_path);
+        let bytes = match (&recommendation.index_type, stats) {
+            (IndexType::Gin, _) => gin_bytes(field_stats, rows, config.gin_operator_class),
+            (IndexType::Partial, Some(stats)) => {
+                gin_bytes(field_stats, rows * stats.density, config.gin_operator_class)
+            }
+            (IndexType::BTreeExtracted | IndexType::GeneratedColumn, Some(stats)) => {
+                btree_bytes(stats, rows)
+            }
+            (IndexType::PartialBTree, Some(stats)) => {
+                let hot: f64 = hot_values(stats, config)
+                    .iter()
+                    .map(|v| v.percentage / 100.0)
+                    .sum();
+                btree_bytes(stats, rows * hot)
+            }
+            (IndexType::CompositeBTree, Some(stats)) => {
+                // Led on from a partial B-tree on rare values, only their rows are indexed
+                let share = if recommended_predicate(recommendation)
+                    .is_some_and(|p| p.ends_with("IS NOT NULL"))
+                {
+                    1.0
+                } else {
+                    hot_values(stats, config)
+                        .iter()
+                        .map(|v| v.percentage / 100.0)
+                        .sum()
+                };
+                composite_btree_bytes(stats, rows * share, config.with_columns.len())
+            }
+            (IndexType::HashExtracted, Some(stats)) => hash_bytes(stats, rows),
+            (IndexType::TrigramExtracted, Some(stats)) => trigram_bytes(stats, rows),
+            (IndexType::BrinExtracted, Some(stats)) => {
+                let pages = if config.layout.pages > 0 {
+                    config.layout.pages as f64
+                } else {
+                    rows
+                };
+                let bytes = brin_bytes(stats, pages);
+                recommendation.estimated_benefit = format!(
+                    "{} About {:.0}x smaller than a B-tree on the same value.",
+                    recommendation.estimated_benefit,
+                    btree_bytes(stats, rows) / bytes
+                );
+                bytes
+            }
+            _ => continue,
+        };
+        recommendation.estimated_size_bytes = Some(bytes as i64);
+    }
+}
+
+/// Pages needed for `bytes` of tuples, plus the metapage, in bytes
+fn index_pages_bytes(bytes: f64) -> f64 {
+    ((bytes / (PAGE_USABLE_BYTES * FILL_FACTOR)).ceil() + 1.0) * PAGE_BYTES
+}
+
+/// Bytes of an index tuple with a key of `key_bytes`, including its line pointer
+fn index_tuple_bytes(key_bytes: f64) -> f64 {
+    ((8.0 + key_bytes) / 8.0).ceil() * 8.0 + 4.0
+}
+
+fn btree_bytes(stats: &FieldStats, rows: f64) -> f64 {
+    composite_btree_bytes(stats, rows, 0)
+}
+
+/// Bytes of the extracted value as a B-tree key
+fn btree_key_bytes(stats: &FieldStats) -> f64 {
+    match get_dominant_type(stats) {
+        Some(JsonType::Number) => 8.0,
+        Some(JsonType::Boolean) => 1.0,
+        _ => {
+            let avg = stats.strings.as_ref().map_or(0.0, |s| s.avg_bytes);
+            // Short values get a 1-byte varlena header, longer ones 4 bytes
+            avg + if avg < 127.0 { 1.0 } else { 4.0 }
+        }
+    }
+}
+
+/// A B-tree with `leading` plain columns before the extracted value, each
+/// taken as an 8-byte integer or timestamp
+fn composite_btree_bytes(stats: &FieldStats, rows: f64, leading: usize) -> f64 {
+    let key_bytes = btree_key_bytes(stats) + 8.0 * leading as f64;
+    index_pages_bytes(rows * non_null_share(stats) * index_tuple_bytes(key_bytes))
+}
+
+fn hash_bytes(stats: &FieldStats, rows: f64) -> f64 {
+    let bytes = rows * non_null_share(stats) * index_tuple_bytes(4.0);
+    ((bytes / (PAGE_USABLE_BYTES * HASH_FILL_FACTOR)).ceil() + 2.0) * PAGE_BYTES
+}
+
+fn trigram_bytes(stats: &FieldStats, rows: f64) -> f64 {
+    let avg = stats.strings.as_ref().map_or(0.0, |s| s.avg_bytes);
+    // Every word is padded with blanks, giving a value of n characters about n + 2 trigrams
+    let trigrams_per_row = non_null_share(stats) * (avg + 2.0);
+    index_pages_bytes(
+        rows * trigrams_per_row * GIN_POSTING_BYTES + TRIGRAM_ENTRIES * index_tuple_bytes(4.0),
+    )
+}
+
+fn brin_bytes(stats: &FieldStats, pages: f64) -> f64 {
+    let avg = stats.strings.as_ref().map_or(0.0, |s| s.avg_bytes);
+    let ranges = (pages / BRIN_PAGES_PER_RANGE).ceil();
+    index_pages_bytes(ranges * (index_tuple_bytes(2.0 * (avg + 1.0)) + 6.0))
+}
+
+/// Share of sampled rows with a non-null value at the field's path
+fn non_null_share(stats: &FieldStats) -> f64 {
+    stats.occurrences.saturating_sub(stats.null_count) as f64 / stats.total_samples.max(1) as f64
+}
+
+fn gin_bytes(field_stats: &[FieldStats], rows: f64, opclass: GinOperatorClass) -> f64 {
+    let mut items_per_row = 0.0;
+    let mut entry_bytes = 0.0;
+    let mut key_names = std::collections::HashSet::new();
+
+    for stats in field_stats.iter().filter(|s| !s.path.is_empty()) {
+        let samples = stats.total_samples.max(1) as f64;
+        let scalars = stats
+            .types
+            .iter()
+            .filter(|(t, _)| !matches!(t, JsonType::Object | JsonType::Array))
+            .map(|(_, count)| *count)
+            .sum::<u64>() as f64;
+        items_per_row += scalars / samples;
+
+        // Values seen about once each in the sample are likely unique in the table
+        let values = scalars - stats.types.get(&JsonType::Null).copied().unwrap_or(0) as f64;
+        let distinct = if values > 0.0 && stats.cardinality as f64 >= 0.9 * values {
+            rows * values / samples
+        } else {
+            stats.cardinality as f64
+        };
+        let value_bytes = match opclass {
+            GinOperatorClass::JsonbPathOps => 4.0,
+            GinOperatorClass::JsonbOps => {
+                let text = match get_dominant_type(stats) {
+                    Some(JsonType::String) => stats.strings.as_ref().map_or(0.0, |s| s.avg_bytes),
+                    _ => 8.0,
+                };
+                1.0 + text.min(GIN_MAX_ENTRY_BYTES)
+            }
+        };
+        entry_bytes += distinct * index_tuple_bytes(value_bytes);
+
+        // jsonb_ops also indexes every key on its own
+        if opclass == GinOperatorClass::JsonbOps && !stats.path.ends_with("[]") {
+            items_per_row += stats.occurrences as f64 / samples;
+            let key = stats.path.rsplit('.').next().unwrap_or(&stats.path);
+            if key_names.insert(key) {
+                let keys = stats.collapsed_keys.unwrap_or(1) as f64;
+                entry_bytes += keys * index_tuple_bytes(1.0 + key.len() as f64);
+            }
+        }
+    }
+
+    index_pages_bytes(rows * items_per_row * GIN_POSTING_BYTES + entry_bytes)
+}
+
+/// A query a recommended index is meant to serve, to check with the planner
+///
+/// It filters on a value seen in the samples: containment for GIN indexes,
+/// together with the predicate of a partial one, equality on the extracted
+/// value for B-tree, hash and BRIN indexes (the rarest counted value for a
+/// partial B-tree on rare values), and a substring of it for trigram
+/// ones. `None` when the path has no usable example.
+pub fn probe_query(
+    table: &str,
+    column: &str,
+    recommendation: &IndexRecommendation,
+    field_stats: &[FieldStats],
+) -> Option<String> {
+    let stats = field_stats
+        .iter()
+        .find(|s| s.path == recommendation.field_path)?;
+    let json_type = get_dominant_type(stats)?;
+    if !is_scalar_type(Some(json_type)) {
+        return None;
+    }
+    let example = stats
+        .examples
+        .iter()
+        .find(|value| JsonType::from_value(value) == json_type)?;
+    // Run with the table's schema first on the search path
+    let relation = quote_ident(table);
+
+    Some(match recommendation.index_type {
+        // Hypothetical indexes can't be built on a column that doesn't exist yet
+        IndexType::GeneratedColumn => return None,
+        // Values of the plain columns aren't sampled
+        IndexType::CompositeBTree => return None,
+        IndexType::Gin => format!(
+            "SELECT * FROM {} WHERE {} @> {}",
+            relation,
+            quote_ident(column),
+            containment_literal(&stats.path, example)
+        ),
+        IndexType::Partial => format!(
+            "SELECT * FROM {} WHERE {} AND {} @> {}",
+            relation,
+            json_path_to_sql_conditions(column, &stats.path),
+            quote_ident(column),
+            containment_literal(&stats.path, example)
+        ),
+        IndexType::TrigramExtracted => {
+            let Value::String(text) = example else {
+                return None;
+            };
+            let needle: String = text
+                .chars()
+                .skip(1)
+                .take(6)
+                .filter(|c| !matches!(c, '%' | '_' | '\\'))
+                .collect();
+            if needle.chars().count() < 3 {
+                return None;
+            }
+            let (expr, _) = extraction_expression(column, &stats.path, json_type);
+            format!(
+                "SELECT * FROM {} WHERE {} ILIKE {}",
+                relation,
+                expr,
+                quote_literal(&format!("%{}%", needle))
+            )
+        }
+        IndexType::PartialBTree => {
+            let (expr, _) = extraction_expression(column, &stats.path, json_type);
+            // The rarest value is in the predicate whatever the thresholds
+            let rarest = &stats.top_values.as_ref()?.last()?.value;
+            format!(
+                "SELECT * FROM {} WHERE {} = {}",
+                relation,
+                expr,
+                sql_literal(rarest)
+            )
+        }
+        IndexType::BTreeExtracted | IndexType::HashExtracted | IndexType::BrinExtracted => {
+            let (expr, _) = extraction_expression(column, &stats.path, json_type);
+            format!(
+                "SELECT * FROM {} WHERE {} = {}",
+                relation,
+                expr,
+                sql_literal(example)
+            )
+        }
+    })
+}
+
+/// SQL literal of a scalar JSON value, as compared with its extraction
+fn sql_literal(value: &Value) -> String {
+    match value {
+        Value::String(s) => quote_literal(s),
+        other => other.to_string(),
+    }
+}
+
+/// SQL literal of the smallest document holding `value` at `path`
+fn containment_literal(path: &str, value: &Value) -> String {
+    let mut document = value.clone();
+    for segment in path.rsplit('.') {
+        let mut key = segment;
+        while let Some(element) = key.strip_suffix("[]") {
+            document = Value::Array(vec![document]);
+            key = element;
+        }
+        if !key.is_empty() {
+            let mut object = serde_json::Map::new();
+            object.insert(key.to_string(), document);
+            document = Value::Object(object);
+        }
+    }
+    quote_literal(&document.to_string())
+}
+
 fn get_dominant_type(stats: &FieldStats) -> Option<JsonType> {
     stats
         .types
@@ -189,7 +1861,9 @@ fn create_consolidated_gin_recommendation(
     primary_stats: &FieldStats,
     all_high_density: &[&FieldStats],
     priority: IndexPriority,
+    config: &IndexConfig,
 ) -> IndexRecommendation {
+    let opclass = config.gin_operator_class;
     let index_name = generate_index_name(table, column, "gin", "gin");
 
     // Create a list of all high-density fields with their densities
@@ -202,25 +1876,41 @@ fn create_consolidated_gin_recommendation(
     let sql = format!(
         "-- GIN index for high-density fields: {}\n\
         CREATE INDEX {} ON {} USING GIN ({});",
-        field_list, index_name, table, column
+        field_list,
+        index_name,
+        config.relation(table),
+        opclass.operand(column)
     );
 
+    let operators = match opclass {
+        GinOperatorClass::JsonbOps => "@>, ?, ?&, ?|",
+        GinOperatorClass::JsonbPathOps => "@>, @?, @@",
+    };
     let reason = if all_high_density.len() == 1 {
         format!(
             "High density ({:.1}%) - present in {}/{} samples. \
-             GIN index enables fast JSONB queries (@>, ?, ?&, ?|)",
+             GIN index enables fast JSONB queries ({})",
             primary_stats.density * 100.0,
             primary_stats.occurrences,
-            primary_stats.total_samples
+            primary_stats.total_samples,
+            operators
         )
     } else {
         format!(
             "{} high-density fields ({}). \
-             Single GIN index supports fast JSONB queries (@>, ?, ?&, ?|) for all fields.",
+             Single GIN index supports fast JSONB queries ({}) for all fields.",
             all_high_density.len(),
-            field_list
+            field_list,
+            operators
         )
     };
+    let estimated_benefit = match opclass {
+        GinOperatorClass::JsonbOps => "Improved query performance for existence checks and containment queries across all high-density fields.".to_string(),
+        GinOperatorClass::JsonbPathOps => format!(
+            "Improved containment query performance across all high-density fields. {}",
+            PATH_OPS_TRADEOFF
+        ),
+    };
 
     IndexRecommendation {
         field_path: primary_stats.path.clone(),
@@ -228,27 +1918,42 @@ fn create_consolidated_gin_recommendation(
         priority,
         reason,
         sql,
-        estimated_benefit: "Improved query performance for existence checks and containment queries across all high-density fields.".to_string(),
+        estimated_benefit,
+        estimated_size_bytes: None,
+        planner_check: None,
+        workload: None,
     }
 }
 
+/// What `jsonb_path_ops` gives up for its smaller size
+const PATH_OPS_TRADEOFF: &str = "jsonb_path_ops stores one hash per path to each value, so the index is usually several times smaller and faster to search than jsonb_ops, but it can't answer key-existence queries (?, ?|, ?&).";
+
 fn create_partial_gin_recommendation(
     table: &str,
     column: &str,
     stats: &FieldStats,
     priority: IndexPriority,
+    config: &IndexConfig,
 ) -> IndexRecommendation {
+    let opclass = config.gin_operator_class;
     let index_name = generate_index_name(table, column, &stats.path, "partial_gin");
-    let path_condition = json_path_to_sql_conditions(&stats.path);
+    let path_condition = json_path_to_sql_conditions(column, &stats.path);
     let sql = format!(
         "-- Partial GIN index for sparse field: {:.1}% of rows contain this field\n\
         CREATE INDEX {} ON {} USING GIN ({}) WHERE {};",
         stats.density * 100.0,
         index_name,
-        table,
-        column,
+        config.relation(table),
+        opclass.operand(column),
         path_condition
     );
+    let mut estimated_benefit = format!(
+        "Smaller index (~{:.1}% of full GIN), faster updates, same query performance for matching rows",
+        stats.density * 100.0
+    );
+    if opclass == GinOperatorClass::JsonbPathOps {
+        estimated_benefit = format!("{}. {}", estimated_benefit, PATH_OPS_TRADEOFF);
+    }
 
     IndexRecommendation {
         field_path: stats.path.clone(),
@@ -262,10 +1967,10 @@ fn create_partial_gin_recommendation(
             stats.total_samples
         ),
         sql,
-        estimated_benefit: format!(
-            "Smaller index (~{:.1}% of full GIN), faster updates, same query performance for matching rows",
-            stats.density * 100.0
-        ),
+        estimated_benefit,
+        estimated_size_bytes: None,
+        planner_check: None,
+        workload: None,
     }
 }
 
@@ -275,31 +1980,10 @@ fn create_btree_extracted_recommendation(
     stats: &FieldStats,
     json_type: JsonType,
     priority: IndexPriority,
+    config: &IndexConfig,
 ) -> IndexRecommendation {
     let index_name = generate_index_name(table, column, &stats.path, "btree_ext");
-    let (extraction_expr, pg_type) = match json_type {
-        JsonType::String => (
-            format!("({} #>> '{{{}}}')", column, escape_json_path(&stats.path)),
-            "TEXT",
-        ),
-        JsonType::Number => (
-            format!(
-                "(({} #>> '{{{}}}')::NUMERIC)",
-                column,
-                escape_json_path(&stats.path)
-            ),
-            "NUMERIC",
-        ),
-        JsonType::Boolean => (
-            format!(
-                "(({} #>> '{{{}}}')::BOOLEAN)",
-                column,
-                escape_json_path(&stats.path)
-            ),
-            "BOOLEAN",
-        ),
-        _ => unreachable!(),
-    };
+    let (extraction_expr, pg_type) = extraction_expression(column, &stats.path, json_type);
 
     let sql = format!(
         "-- B-tree index on extracted {} value: {:.1}% density\n\
@@ -307,7 +1991,7 @@ fn create_btree_extracted_recommendation(
         pg_type,
         stats.density * 100.0,
         index_name,
-        table,
+        config.relation(table),
         extraction_expr,
         extraction_expr
     );
@@ -327,19 +2011,284 @@ fn create_btree_extracted_recommendation(
         sql,
         estimated_benefit:
             "Improved query performance for lookups and range queries on scalar values.".to_string(),
+        estimated_size_bytes: None,
+        planner_check: None,
+        workload: None,
     }
 }
 
+/// Rare values of a skewed scalar field, most common first: those matching at
+/// most `max_hot_value_share` of its values when the most common value
+/// matches at least `min_skew`. Empty when the field isn't skewed or its
+/// values weren't counted.
+fn hot_values<'a>(stats: &'a FieldStats, config: &IndexConfig) -> Vec<&'a ValueCount> {
+    let Some(top) = stats.top_values.as_deref() else {
+        return Vec::new();
+    };
+    match top.first() {
+        Some(most_common) if most_common.percentage >= config.min_skew * 100.0 => top[1..]
+            .iter()
+            .filter(|v| v.percentage <= config.max_hot_value_share * 100.0)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Partial B-tree on the extracted value of a skewed field, restricted to its
+/// rare values, or `None` when the field isn't skewed
+///
+/// Queue-style queries look for the few pending rows among millions of
+/// completed ones: an index on just those rows stays tiny, and the planner
+/// uses it for any query whose filter implies its predicate.
+fn create_partial_btree_recommendation(
+    table: &str,
+    column: &str,
+    stats: &FieldStats,
+    config: &IndexConfig,
+) -> Option<IndexRecommendation> {
+    let json_type = get_dominant_type(stats)?;
+    if !is_scalar_type(Some(json_type))
+        || stats.path.contains("[]")
+        || stats.occurrences < config.min_occurences
+    {
+        return None;
+    }
+    let hot = hot_values(stats, config);
+    let most_common = stats.top_values.as_ref()?.first()?;
+    if hot.is_empty() {
+        return None;
+    }
+
+    let index_name = generate_index_name(table, column, &stats.path, "hot");
+    let (extraction_expr, pg_type) = extraction_expression(column, &stats.path, json_type);
+    let literals: Vec<String> = hot.iter().map(|v| sql_literal(&v.value)).collect();
+    let predicate = match literals.as_slice() {
+        [literal] => format!("{} = {}", extraction_expr, literal),
+        _ => format!("{} IN ({})", extraction_expr, literals.join(", ")),
+    };
+    let hot_share: f64 = hot.iter().map(|v| v.percentage).sum();
+
+    let sql = format!(
+        "-- Partial B-tree on the rare {} values: {:.1}% of values\n\
+        CREATE INDEX {} ON {} ({}) WHERE {};",
+        pg_type,
+        hot_share,
+        index_name,
+        config.relation(table),
+        extraction_expr,
+        predicate
+    );
+
+    Some(IndexRecommendation {
+        field_path: stats.path.clone(),
+        index_type: IndexType::PartialBTree,
+        priority: IndexPriority::Medium,
+        reason: format!(
+            "Skewed {} field: {} in {:.1}% of values, {} in {:.1}%. \
+             Partial B-tree indexes only the rare values, which queue-style queries look for.",
+            json_type,
+            most_common.value,
+            most_common.percentage,
+            hot.iter()
+                .map(|v| v.value.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+            hot_share
+        ),
+        sql,
+        estimated_benefit: format!(
+            "Fast lookups of {} rows in a fraction of the size of a full B-tree.",
+            literals.join(", ")
+        ),
+        estimated_size_bytes: None,
+        planner_check: None,
+        workload: None,
+    })
+}
+
+/// WHERE clause of a recommended partial index
+fn recommended_predicate(recommendation: &IndexRecommendation) -> Option<&str> {
+    let (_, predicate) = recommendation.sql.rsplit_once(" WHERE ")?;
+    Some(predicate.trim_end().trim_end_matches(';'))
+}
+
+/// Hash index on the extracted value of a nearly unique path the recorded
+/// queries only compare for equality, or `None` when a B-tree fits better
+fn create_hash_extracted_recommendation(
+    table: &str,
+    column: &str,
+    stats: &FieldStats,
+    accesses: &[ColumnAccess],
+    priority: IndexPriority,
+    config: &IndexConfig,
+) -> Option<IndexRecommendation> {
+    let json_type = get_dominant_type(stats)?;
+    if !matches!(json_type, JsonType::String | JsonType::Number)
+        || stats.occurrences < config.min_occurences
+        || !is_nearly_unique(stats, config)
+        || !only_compared_for_equality(&stats.path, accesses)
+    {
+        return None;
+    }
+
+    let index_name = generate_index_name(table, column, &stats.path, "hash_ext");
+    let (extraction_expr, pg_type) = extraction_expression(column, &stats.path, json_type);
+    let values = stats.occurrences.saturating_sub(stats.null_count);
+    let distinct = (stats.cardinality as f64 / values as f64).min(1.0) * 100.0;
+
+    let sql = format!(
+        "-- Hash index on extracted {} value: {:.1}% distinct, equality lookups only\n\
+        CREATE INDEX {} ON {} USING HASH ({}) WHERE {} IS NOT NULL;",
+        pg_type,
+        distinct,
+        index_name,
+        config.relation(table),
+        extraction_expr,
+        extraction_expr
+    );
+
+    Some(IndexRecommendation {
+        field_path: stats.path.clone(),
+        index_type: IndexType::HashExtracted,
+        priority,
+        reason: format!(
+            "Nearly unique {} field ({:.1}% of {} sampled values distinct), only compared \
+             with = or IN by recorded queries. Hash index stores a 4-byte hash code per row \
+             instead of the value, so it stays small however long the values are and finds \
+             a match in about one page read. Unlike a B-tree it can't serve range filters, \
+             sorting or unique constraints, and it is only crash-safe from PostgreSQL 10.",
+            json_type, distinct, values
+        ),
+        sql,
+        estimated_benefit: "Smaller and faster equality lookups than a B-tree on the same value."
+            .to_string(),
+        estimated_size_bytes: None,
+        planner_check: None,
+        workload: None,
+    })
+}
+
+fn create_brin_extracted_recommendation(
+    table: &str,
+    column: &str,
+    stats: &FieldStats,
+    correlation: f64,
+    config: &IndexConfig,
+) -> IndexRecommendation {
+    let index_name = generate_index_name(table, column, &stats.path, "brin_ext");
+    let (extraction_expr, _) = extraction_expression(column, &stats.path, JsonType::String);
+    let format = stats
+        .format
+        .as_ref()
+        .map_or(StringFormat::DateTime, |f| f.format);
+
+    let sql = format!(
+        "-- BRIN index on extracted {} value: correlation {:.2} with the row order\n\
+        CREATE INDEX {} ON {} USING BRIN ({});",
+        format,
+        correlation,
+        index_name,
+        config.relation(table),
+        extraction_expr
+    );
+
+    IndexRecommendation {
+        field_path: stats.path.clone(),
+        index_type: IndexType::BrinExtracted,
+        priority: IndexPriority::Medium,
+        reason: format!(
+            "ISO 8601 {} field ({:.1}% density) whose values follow the physical row order \
+             (correlation {:.2}), as when rows are appended in time order. BRIN index keeps \
+             the min and max of each 128-page block range instead of an entry per row, and \
+             serves range filters on the extracted text (>=, BETWEEN on ISO strings) by \
+             reading only the ranges that can match. It can't serve sorting or fast \
+             single-row lookups, and updates that move rows around weaken it.",
+            format,
+            stats.density * 100.0,
+            correlation
+        ),
+        sql,
+        estimated_benefit:
+            "Range scans over recent or historical slices of the table at a fraction of a B-tree's size and write cost.".to_string(),
+        estimated_size_bytes: None,
+        planner_check: None,
+        workload: None,
+    }
+}
+
+fn create_trigram_extracted_recommendation(
+    table: &str,
+    column: &str,
+    stats: &FieldStats,
+    config: &IndexConfig,
+) -> IndexRecommendation {
+    let index_name = generate_index_name(table, column, &stats.path, "trgm_ext");
+    let (extraction_expr, _) = extraction_expression(column, &stats.path, JsonType::String);
+    let avg_bytes = stats.strings.as_ref().map_or(0.0, |s| s.avg_bytes);
+
+    let sql = format!(
+        "-- Trigram GIN index on extracted text (needs the pg_trgm extension): {:.1}% density\n\
+        CREATE INDEX {} ON {} USING GIN ({} gin_trgm_ops) WHERE {} IS NOT NULL;",
+        stats.density * 100.0,
+        index_name,
+        config.relation(table),
+        extraction_expr,
+        extraction_expr
+    );
+
+    IndexRecommendation {
+        field_path: stats.path.clone(),
+        index_type: IndexType::TrigramExtracted,
+        priority: IndexPriority::Medium,
+        reason: format!(
+            "Text field averaging {:.0} bytes ({:.1}% density), searched with LIKE/ILIKE. \
+             Trigram index serves LIKE and ILIKE with leading wildcards and regular \
+             expressions (~, ~*), which a B-tree can't; a pattern needs 3 characters \
+             in a row without wildcards to use it.",
+            avg_bytes,
+            stats.density * 100.0
+        ),
+        sql,
+        estimated_benefit: "Substring and case-insensitive searches without a sequential scan, at the cost of an index often larger than the text and slower writes.".to_string(),
+        estimated_size_bytes: None,
+        planner_check: None,
+        workload: None,
+    }
+}
+
+/// Expression extracting a scalar at `path` as its SQL type, and that type
+fn extraction_expression(column: &str, path: &str, json_type: JsonType) -> (String, &'static str) {
+    let text = format!(
+        "({} #>> {})",
+        quote_ident(column),
+        path_literal(&path.replace("[]", ""))
+    );
+    match json_type {
+        JsonType::String => (text, "TEXT"),
+        JsonType::Number => (format!("({}::NUMERIC)", text), "NUMERIC"),
+        JsonType::Boolean => (format!("({}::BOOLEAN)", text), "BOOLEAN"),
+        _ => unreachable!(),
+    }
+}
+
+/// Name of a recommended index, a valid identifier without quoting
 fn generate_index_name(table: &str, column: &str, path: &str, index_type: &str) -> String {
-    let clean_path = path
-        .replace("[]", "_arr")
-        .replace(".", "_")
-        .chars()
-        .filter(|c| c.is_alphanumeric() || *c == '_')
-        .collect::<String>();
+    let clean = |name: &str| {
+        name.replace("[]", "_arr")
+            .replace(".", "_")
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+            .collect::<String>()
+    };
 
     let max_len = 63;
-    let prefix = format!("idx_{}_{}_{}_{}", table, column, clean_path, index_type);
+    let prefix = format!(
+        "idx_{}_{}_{}_{}",
+        clean(table),
+        clean(column),
+        clean(path),
+        index_type
+    );
 
     if prefix.len() <= max_len {
         prefix
@@ -355,31 +2304,44 @@ fn calculate_simple_hash(s: &str) -> u32 {
         .fold(0u32, |hash, b| hash.wrapping_mul(31).wrapping_add(b as u32))
 }
 
-fn json_path_to_sql_conditions(path: &str) -> String {
-    let parts: Vec<&str> = path.split('.').collect();
-    if parts.len() == 1 {
-        let clean_part = parts[0].replace("[]", "");
-        format!("metadata ? '{}'", clean_part)
-    } else {
-        let parent_path = parts[..parts.len() - 1]
-            .iter()
-            .map(|p| p.replace("[]", ""))
-            .collect::<Vec<_>>()
-            .join(",");
-        let last = parts.last().unwrap().replace("[]", "");
-        format!("metadata #> '{{{}}}' ? '{}'", parent_path, last)
+/// Predicate of the documents holding `path`, for partial indexes
+///
+/// A top-level key is checked with `?`, which GIN indexes serve. A nested
+/// path goes through a strict jsonpath, so every key on the way has to be an
+/// object key and `[]` steps have to be arrays: `?` on the extracted parent
+/// would also match a string equal to the key, or an array holding it.
+fn json_path_to_sql_conditions(column: &str, path: &str) -> String {
+    let mut path = path;
+    while let Some(parent) = path.strip_suffix("[]") {
+        path = parent;
+    }
+
+    if !path.contains('.') && !path.contains("[]") {
+        return format!("{} ? {}", quote_ident(column), quote_literal(path));
     }
-}
 
-fn escape_json_path(path: &str) -> String {
-    path.replace("[]", "") // Remove array notation
-        .replace('\'', "''") // Escape single quotes for SQL
-        .replace('.', ",") // Convert dots to commas for PostgreSQL {a,b,c} syntax
+    let mut jsonpath = String::from("strict $");
+    for segment in path.split('.') {
+        let mut key = segment;
+        let mut elements = 0;
+        while let Some(parent) = key.strip_suffix("[]") {
+            key = parent;
+            elements += 1;
+        }
+        if !key.is_empty() {
+            let key = key.replace('\\', "\\\\").replace('"', "\\\"");
+            jsonpath.push_str(&format!(".\"{}\"", key));
+        }
+        jsonpath.push_str(&"[*]".repeat(elements));
+    }
+    format!("{} @? {}", quote_ident(column), quote_literal(&jsonpath))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::stats::StringStats;
+    use serde_json::json;
 
     fn create_test_stats(path: &str, density: f64, occurrences: u64, total: u64) -> FieldStats {
         let mut stats = FieldStats::new(path.to_string(), 1);
@@ -405,6 +2367,31 @@ mod tests {
         assert!(recommendations[0].sql.contains("95.0%"));
     }
 
+    #[test]
+    fn test_jsonb_path_ops_gin() {
+        let mut dense = create_test_stats("user.email", 0.95, 9500, 10000);
+        dense.types.insert(JsonType::String, 9500);
+        let mut sparse = create_test_stats("billing.legacy_plan", 0.05, 500, 10000);
+        sparse.types.insert(JsonType::String, 500);
+
+        let config = IndexConfig {
+            gin_operator_class: GinOperatorClass::JsonbPathOps,
+            ..Default::default()
+        };
+        let recommendations = recommend_index("users", "metadata", &[dense, sparse], &config);
+
+        assert_eq!(recommendations.len(), 2);
+        for recommendation in &recommendations {
+            assert!(
+                recommendation
+                    .sql
+                    .contains(r#"USING GIN ("metadata" jsonb_path_ops)"#)
+            );
+            assert!(recommendation.estimated_benefit.contains("key-existence"));
+        }
+        assert!(recommendations[0].reason.contains("(@>, @?, @@)"));
+    }
+
     #[test]
     fn test_sparse_field_recommends_partial_gin() {
         let mut stats = create_test_stats("billing.legacy_plan", 0.05, 100, 2000);
@@ -486,6 +2473,17 @@ mod tests {
         assert_eq!(recommendations.len(), 0);
     }
 
+    #[test]
+    fn test_skips_wildcard_paths() {
+        let mut stats = create_test_stats("counts_by_country.*", 0.5, 500, 1000);
+        stats.types.insert(JsonType::Number, 500);
+
+        let config = IndexConfig::default();
+        let recommendations = recommend_index("users", "metadata", &[stats], &config);
+
+        assert_eq!(recommendations.len(), 0);
+    }
+
     #[test]
     fn test_skips_low_occurrence_fields() {
         let mut stats = create_test_stats("rare_field", 0.9, 50, 55);
@@ -526,6 +2524,23 @@ mod tests {
             high_density_threshold: 0.6,
             medium_density_threshold: 0.2,
             min_occurences: 100,
+            gin_operator_class: GinOperatorClass::JsonbOps,
+            min_query_calls: 100,
+            hash_distinct_ratio: 0.9,
+            trigram: false,
+            min_trigram_bytes: 16.0,
+            text_search_paths: Vec::new(),
+            min_brin_correlation: 0.9,
+            layout: PhysicalLayout::default(),
+            generated_columns: false,
+            min_table_rows: 10_000,
+            large_table_rows: 1_000_000,
+            max_selectivity: 0.1,
+            high_selectivity: 0.01,
+            min_skew: 0.8,
+            max_hot_value_share: 0.1,
+            with_columns: Vec::new(),
+            schema: None,
         };
 
         let recommendations = recommend_index("users", "metadata", &[stats], &config);
@@ -543,6 +2558,23 @@ mod tests {
             high_density_threshold: 0.8,
             medium_density_threshold: 0.1,
             min_occurences: 100,
+            gin_operator_class: GinOperatorClass::JsonbOps,
+            min_query_calls: 100,
+            hash_distinct_ratio: 0.9,
+            trigram: false,
+            min_trigram_bytes: 16.0,
+            text_search_paths: Vec::new(),
+            min_brin_correlation: 0.9,
+            layout: PhysicalLayout::default(),
+            generated_columns: false,
+            min_table_rows: 10_000,
+            large_table_rows: 1_000_000,
+            max_selectivity: 0.1,
+            high_selectivity: 0.01,
+            min_skew: 0.8,
+            max_hot_value_share: 0.1,
+            with_columns: Vec::new(),
+            schema: None,
         };
 
         let recommendations = recommend_index("users", "metadata", &[stats], &config);
@@ -583,32 +2615,63 @@ mod tests {
     }
 
     #[test]
-    fn test_json_path_escaping() {
-        // Dots are converted to commas for PostgreSQL path syntax
-        assert_eq!(escape_json_path("user.email"), "user,email");
-        assert_eq!(escape_json_path("tags[]"), "tags");
-        // Single quotes are escaped, dots become commas
-        assert_eq!(escape_json_path("user's.name"), "user''s,name");
-        // Array notation removed, dots become commas
-        assert_eq!(escape_json_path("items[].price"), "items,price");
+    fn test_extraction_expression() {
+        assert_eq!(
+            extraction_expression("metadata", "user.email", JsonType::String),
+            ("(\"metadata\" #>> '{user,email}')".to_string(), "TEXT")
+        );
+        // Array notation removed
+        assert_eq!(
+            extraction_expression("data", "items[].price", JsonType::Number),
+            (
+                "((\"data\" #>> '{items,price}')::NUMERIC)".to_string(),
+                "NUMERIC"
+            )
+        );
+        // Keys that aren't plain words are quoted, single quotes escaped
+        assert_eq!(
+            extraction_expression("Doc", "user's.first name", JsonType::String),
+            (
+                "(\"Doc\" #>> '{\"user''s\",\"first name\"}')".to_string(),
+                "TEXT"
+            )
+        );
     }
 
     #[test]
     fn test_json_path_to_sql_conditions_simple() {
-        let condition = json_path_to_sql_conditions("email");
-        assert_eq!(condition, "metadata ? 'email'");
+        let condition = json_path_to_sql_conditions("metadata", "email");
+        assert_eq!(condition, "\"metadata\" ? 'email'");
     }
 
     #[test]
     fn test_json_path_to_sql_conditions_nested() {
-        let condition = json_path_to_sql_conditions("user.profile.email");
-        assert_eq!(condition, "metadata #> '{user,profile}' ? 'email'");
+        let condition = json_path_to_sql_conditions("metadata", "user.profile.email");
+        assert_eq!(
+            condition,
+            "\"metadata\" @? 'strict $.\"user\".\"profile\".\"email\"'"
+        );
     }
 
     #[test]
     fn test_json_path_to_sql_conditions_with_array() {
-        let condition = json_path_to_sql_conditions("tags[]");
-        assert_eq!(condition, "metadata ? 'tags'");
+        let condition = json_path_to_sql_conditions("metadata", "tags[]");
+        assert_eq!(condition, "\"metadata\" ? 'tags'");
+
+        let condition = json_path_to_sql_conditions("payload", "items[].sku");
+        assert_eq!(condition, "\"payload\" @? 'strict $.\"items\"[*].\"sku\"'");
+    }
+
+    #[test]
+    fn test_json_path_to_sql_conditions_quoting() {
+        assert_eq!(
+            json_path_to_sql_conditions("Order Data", "owner's"),
+            "\"Order Data\" ? 'owner''s'"
+        );
+        assert_eq!(
+            json_path_to_sql_conditions("data", "a\"b.c's"),
+            "\"data\" @? 'strict $.\"a\\\"b\".\"c''s\"'"
+        );
     }
 
     #[test]
@@ -798,6 +2861,23 @@ mod tests {
         assert!(sql.contains("metadata"));
     }
 
+    #[test]
+    fn test_schema_qualified_sql() {
+        let mut stats = create_test_stats("rare.field", 0.05, 100, 2000);
+        stats.types.insert(JsonType::String, 100);
+
+        let config = IndexConfig {
+            schema: Some("Audit".to_string()),
+            ..Default::default()
+        };
+        let recommendations = recommend_index("Events", "Payload", &[stats], &config);
+
+        assert_eq!(
+            recommendations[0].sql.lines().last().unwrap(),
+            r#"CREATE INDEX idx_Events_Payload_rare_field_partial_gin ON "Audit"."Events" USING GIN ("Payload") WHERE "Payload" @? 'strict $."rare"."field"';"#
+        );
+    }
+
     #[test]
     fn test_btree_has_extraction_and_where() {
         let mut stats = create_test_stats("score", 0.5, 500, 1000);
@@ -812,4 +2892,984 @@ mod tests {
         assert!(sql.contains("WHERE"));
         assert!(sql.contains("IS NOT NULL"));
     }
+
+    fn existing(name: &str, method: &str, keys: &[&str], opclasses: &[&str]) -> ExistingIndex {
+        ExistingIndex {
+            name: name.to_string(),
+            method: method.to_string(),
+            keys: keys.iter().map(|k| k.to_string()).collect(),
+            operator_classes: opclasses.iter().map(|o| o.to_string()).collect(),
+            predicate: None,
+            unique: false,
+            definition: format!("CREATE INDEX {} ON users USING {} (...)", name, method),
+        }
+    }
+
+    #[test]
+    fn test_plan_indexes_skips_covered() {
+        let mut dense = create_test_stats("user.email", 0.95, 9500, 10000);
+        dense.types.insert(JsonType::String, 9500);
+        let mut sparse = create_test_stats("billing.plan", 0.05, 500, 10000);
+        sparse.types.insert(JsonType::String, 500);
+        let mut score = create_test_stats("user.score", 0.5, 5000, 10000);
+        score.types.insert(JsonType::Number, 5000);
+        let mut city = create_test_stats("address.city", 0.5, 5000, 10000);
+        city.types.insert(JsonType::String, 5000);
+        let stats = [dense, sparse, score, city];
+
+        // Spelled the way pg_get_indexdef prints them
+        let mut score_index = existing(
+            "idx_score",
+            "btree",
+            &["(((metadata #>> '{user,score}'::text[]))::numeric)"],
+            &["numeric_ops"],
+        );
+        score_index.predicate =
+            Some("(((metadata #>> '{user,score}'::text[]))::numeric IS NOT NULL)".to_string());
+        let indexes = [
+            existing("idx_meta", "gin", &["metadata"], &["jsonb_ops"]),
+            score_index,
+            // Extracts the city as jsonb, which doesn't serve text comparisons
+            existing(
+                "idx_city",
+                "btree",
+                &["((metadata -> 'address'::text) -> 'city'::text)"],
+                &["jsonb_ops"],
+            ),
+        ];
+
+        let plan = plan_indexes(
+            "users",
+            "metadata",
+            &stats,
+            &[],
+            &indexes,
+            &IndexConfig::default(),
+        );
+        let covered: Vec<_> = plan
+            .covered
+            .iter()
+            .map(|c| (c.field_path.as_str(), c.covered_by.as_str()))
+            .collect();
+        assert_eq!(
+            covered,
+            vec![
+                ("user.email", "idx_meta"),
+                ("billing.plan", "idx_meta"),
+                ("user.score", "idx_score"),
+            ]
+        );
+        assert_eq!(plan.recommendations.len(), 1);
+        assert_eq!(plan.recommendations[0].field_path, "address.city");
+        assert!(plan.overlaps.is_empty());
+    }
+
+    #[test]
+    fn test_jsonb_path_ops_index_only_covers_containment() {
+        let mut stats = create_test_stats("user.email", 0.95, 9500, 10000);
+        stats.types.insert(JsonType::String, 9500);
+        let indexes = [existing(
+            "idx_meta",
+            "gin",
+            &["metadata"],
+            &["jsonb_path_ops"],
+        )];
+
+        let plan = plan_indexes(
+            "users",
+            "metadata",
+            std::slice::from_ref(&stats),
+            &[],
+            &indexes,
+            &IndexConfig::default(),
+        );
+        assert_eq!(plan.recommendations.len(), 1);
+
+        let config = IndexConfig {
+            gin_operator_class: GinOperatorClass::JsonbPathOps,
+            ..Default::default()
+        };
+        let plan = plan_indexes("users", "metadata", &[stats], &[], &indexes, &config);
+        assert!(plan.recommendations.is_empty());
+        assert_eq!(plan.covered[0].covered_by, "idx_meta");
+    }
+
+    #[test]
+    fn test_find_overlapping_indexes() {
+        let mut pkey = existing("users_pkey", "btree", &["id"], &["int4_ops"]);
+        pkey.unique = true;
+        let mut partial = existing("idx_meta_active", "gin", &["metadata"], &["jsonb_ops"]);
+        partial.predicate = Some("(active = true)".to_string());
+        let indexes = [
+            pkey,
+            existing("idx_id", "btree", &["id"], &["int4_ops"]),
+            existing(
+                "idx_email",
+                "btree",
+                &["(metadata ->> 'email'::text)"],
+                &["text_ops"],
+            ),
+            existing(
+                "idx_email_name",
+                "btree",
+                &["((metadata ->> 'email'))", "name"],
+                &["text_ops", "text_ops"],
+            ),
+            existing("idx_meta", "gin", &["metadata"], &["jsonb_ops"]),
+            existing("idx_meta_paths", "gin", &["metadata"], &["jsonb_path_ops"]),
+            existing("idx_meta_copy", "gin", &["\"metadata\""], &["jsonb_ops"]),
+            partial,
+        ];
+
+        let overlaps: Vec<_> = find_overlapping_indexes(&indexes)
+            .into_iter()
+            .map(|o| (o.index, o.overlaps, o.kind))
+            .collect();
+        let expected = [
+            ("idx_id", "users_pkey", IndexOverlapKind::Duplicate),
+            ("idx_email", "idx_email_name", IndexOverlapKind::Prefix),
+            ("idx_meta_paths", "idx_meta", IndexOverlapKind::Overlapping),
+            ("idx_meta_copy", "idx_meta", IndexOverlapKind::Duplicate),
+            ("idx_meta_active", "idx_meta", IndexOverlapKind::Overlapping),
+        ];
+        assert_eq!(
+            overlaps,
+            expected
+                .iter()
+                .map(|(a, b, kind)| (a.to_string(), b.to_string(), kind.clone()))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_recommend_drops() {
+        let overlaps = [IndexOverlap {
+            index: "idx_meta_copy".to_string(),
+            overlaps: "idx_meta".to_string(),
+            kind: IndexOverlapKind::Duplicate,
+            reason: "Same keys as idx_meta".to_string(),
+            definition: String::new(),
+        }];
+        let usage = |name: &str, scans: i64, size_bytes: i64| IndexUsage {
+            name: name.to_string(),
+            unique: false,
+            scans,
+            size_bytes,
+        };
+        let usage = [
+            usage("idx_meta", 120, 4_000_000),
+            usage("idx_meta_copy", 15, 4_000_000),
+            usage("idx_email", 0, 9_000_000),
+            IndexUsage {
+                unique: true,
+                ..usage("users_email_key", 0, 2_000_000)
+            },
+        ];
+
+        let drops = recommend_drops("public", &overlaps, &usage);
+        assert_eq!(drops.len(), 2);
+        assert_eq!(drops[0].index, "idx_email");
+        assert_eq!(drops[0].reason, "Never scanned.");
+        assert_eq!(
+            drops[0].sql,
+            "DROP INDEX CONCURRENTLY IF EXISTS \"public\".\"idx_email\";"
+        );
+        // Scanned, but its queries would move to the index it duplicates
+        assert_eq!(drops[1].index, "idx_meta_copy");
+        assert_eq!(drops[1].scans, 15);
+        assert!(drops[1].reason.starts_with("Duplicate of idx_meta"));
+    }
+
+    #[test]
+    fn test_extracted_path() {
+        let column = normalize_expression("metadata");
+        let extract = |expr: &str| extracted_path(&normalize_expression(expr), &column);
+
+        let email = Some((
+            vec!["user".to_string(), "email".to_string()],
+            "text".to_string(),
+        ));
+        assert_eq!(extract("metadata #>> '{user,email}'"), email);
+        assert_eq!(
+            extract("((metadata -> 'user'::text) ->> 'email'::text)"),
+            email
+        );
+        assert_eq!(
+            extract("((metadata ->> 'it''s'))::numeric"),
+            Some((vec!["it's".to_string()], "numeric".to_string()))
+        );
+        assert_eq!(
+            extract("(metadata -> 'tags')"),
+            Some((vec!["tags".to_string()], "jsonb".to_string()))
+        );
+        assert_eq!(extract("lower((metadata ->> 'email'))"), None);
+        assert_eq!(extract("metadata"), None);
+    }
+
+    #[test]
+    fn test_estimate_index_sizes() {
+        let mut email = create_test_stats("user.email", 0.95, 9500, 10000);
+        email.types.insert(JsonType::String, 9500);
+        email.cardinality = 9500;
+        email.strings = Some(StringStats {
+            count: 9500,
+            total_bytes: 9500 * 20,
+            avg_bytes: 20.0,
+            max_bytes: 40,
+            blob_count: 0,
+        });
+        let mut score = create_test_stats("score", 0.5, 5000, 10000);
+        score.types.insert(JsonType::Number, 5000);
+        score.cardinality = 100;
+        let stats = [email, score];
+
+        let config = IndexConfig::default();
+        let mut recommendations = recommend_index("users", "metadata", &stats, &config);
+        estimate_index_sizes(&mut recommendations, &stats, 1_000_000, &config);
+
+        let size = |index_type: IndexType| {
+            recommendations
+                .iter()
+                .find(|r| r.index_type == index_type)
+                .and_then(|r| r.estimated_size_bytes)
+                .unwrap()
+        };
+        // 500k tuples of 20 bytes (header, numeric key, line pointer) in 90% full pages
+        let btree = size(IndexType::BTreeExtracted);
+        assert!((10_000_000..12_000_000).contains(&btree), "{}", btree);
+        // The unique emails dominate the GIN index
+        let gin = size(IndexType::Gin);
+        assert!(gin > btree * 3, "{} vs {}", gin, btree);
+
+        let config = IndexConfig {
+            gin_operator_class: GinOperatorClass::JsonbPathOps,
+            ..Default::default()
+        };
+        let mut path_ops = recommend_index("users", "metadata", &stats, &config);
+        estimate_index_sizes(&mut path_ops, &stats, 1_000_000, &config);
+        assert!(path_ops[0].estimated_size_bytes.unwrap() < gin);
+    }
+
+    #[test]
+    fn test_planner_check_benefit() {
+        let mut check = PlannerCheck {
+            query: "SELECT 1".to_string(),
+            used: true,
+            cost_without: 2041.0,
+            cost_with: 12.3,
+            ..Default::default()
+        };
+        assert_eq!(
+            check.benefit(),
+            "Cuts the estimated cost of the probe query by 99.4% (2041.0 -> 12.3)"
+        );
+
+        check.time_without_ms = Some(85.2);
+        check.time_with_ms = Some(0.412);
+        assert_eq!(
+            check.benefit(),
+            "Cuts the estimated cost of the probe query by 99.4% (2041.0 -> 12.3, 85.20 ms -> 0.41 ms when run)"
+        );
+
+        check.used = false;
+        assert_eq!(check.cost_reduction(), None);
+        assert!(check.benefit().starts_with("None measured"));
+    }
+
+    #[test]
+    fn test_probe_query() {
+        let mut email = create_test_stats("user.email", 0.95, 9500, 10000);
+        email.types.insert(JsonType::String, 9500);
+        email.examples = vec![json!(42), json!("o'brien@example.com")];
+        let mut tags = create_test_stats("prefs.tags[]", 0.1, 1000, 10000);
+        tags.types.insert(JsonType::String, 1000);
+        tags.examples = vec![json!("beta")];
+        let mut score = create_test_stats("score", 0.5, 5000, 10000);
+        score.types.insert(JsonType::Number, 5000);
+        score.examples = vec![json!(7.5)];
+        let stats = [email, tags, score];
+
+        let recommendations = recommend_index("users", "metadata", &stats, &IndexConfig::default());
+        let queries: Vec<_> = recommendations
+            .iter()
+            .map(|r| probe_query("users", "metadata", r, &stats).unwrap())
+            .collect();
+        assert_eq!(
+            queries,
+            vec![
+                r#"SELECT * FROM "users" WHERE "metadata" @> '{"user":{"email":"o''brien@example.com"}}'"#,
+                r#"SELECT * FROM "users" WHERE "metadata" @? 'strict $."prefs"."tags"' AND "metadata" @> '{"prefs":{"tags":["beta"]}}'"#,
+                r#"SELECT * FROM "users" WHERE (("metadata" #>> '{score}')::NUMERIC) = 7.5"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_prioritize_by_workload() {
+        let mut email = create_test_stats("user.email", 0.95, 9500, 10000);
+        email.types.insert(JsonType::String, 9500);
+        let mut plan = create_test_stats("billing.plan", 0.05, 500, 10000);
+        plan.types.insert(JsonType::String, 500);
+        let mut score = create_test_stats("score", 0.5, 5000, 10000);
+        score.types.insert(JsonType::Number, 5000);
+        let stats = [email, plan, score];
+
+        let access = |path: Option<&str>, operator, calls| ColumnAccess {
+            path: path.map(str::to_string),
+            operator,
+            usage: QueryUsage {
+                statements: 1,
+                calls,
+                mean_exec_time_ms: 2.0,
+            },
+        };
+        let accesses = [
+            access(Some("user.email"), JsonOperator::Contains, 5000),
+            access(Some("user.email"), JsonOperator::Extract, 20000),
+            access(Some("score"), JsonOperator::Extract, 10),
+        ];
+
+        let plan = plan_indexes(
+            "users",
+            "metadata",
+            &stats,
+            &accesses,
+            &[],
+            &IndexConfig::default(),
+        );
+        let found: Vec<_> = plan
+            .recommendations
+            .iter()
+            .map(|r| {
+                (
+                    r.field_path.as_str(),
+                    r.index_type.clone(),
+                    r.priority.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                // Not recommended by density, but queried the most
+                ("user.email", IndexType::BTreeExtracted, IndexPriority::High),
+                ("user.email", IndexType::Gin, IndexPriority::High),
+                ("score", IndexType::BTreeExtracted, IndexPriority::Medium),
+                ("billing.plan", IndexType::Partial, IndexPriority::Low),
+            ]
+        );
+        assert_eq!(
+            plan.recommendations[1].workload.as_ref().unwrap().calls,
+            5000
+        );
+        assert!(
+            plan.recommendations[3]
+                .reason
+                .ends_with("No recorded query uses it.")
+        );
+    }
+
+    #[test]
+    fn test_hash_index_for_equality_lookups() {
+        let mut email = create_test_stats("user.email", 0.5, 5000, 10000);
+        email.types.insert(JsonType::String, 5000);
+        email.cardinality = 4900;
+        let mut age = create_test_stats("user.age", 0.5, 5000, 10000);
+        age.types.insert(JsonType::Number, 5000);
+        age.cardinality = 4900;
+        let mut plan = create_test_stats("plan", 0.5, 5000, 10000);
+        plan.types.insert(JsonType::String, 5000);
+        plan.cardinality = 3;
+        let stats = [email, age, plan];
+
+        let access = |path: &str, operator| ColumnAccess {
+            path: Some(path.to_string()),
+            operator,
+            usage: QueryUsage {
+                statements: 1,
+                calls: 500,
+                mean_exec_time_ms: 1.0,
+            },
+        };
+        let accesses = [
+            access("user.email", JsonOperator::Equality),
+            access("user.age", JsonOperator::Equality),
+            access("user.age", JsonOperator::Extract),
+            access("plan", JsonOperator::Equality),
+        ];
+        let config = IndexConfig::default();
+        let mut plan = plan_indexes("users", "metadata", &stats, &accesses, &[], &config);
+        let index_type = |path: &str| {
+            plan.recommendations
+                .iter()
+                .find(|r| r.field_path == path)
+                .map(|r| r.index_type.clone())
+                .unwrap()
+        };
+        assert_eq!(index_type("user.email"), IndexType::HashExtracted);
+        // Also range-filtered or sorted
+        assert_eq!(index_type("user.age"), IndexType::BTreeExtracted);
+        // Too few distinct values
+        assert_eq!(index_type("plan"), IndexType::BTreeExtracted);
+
+        let hash = plan
+            .recommendations
+            .iter()
+            .find(|r| r.index_type == IndexType::HashExtracted)
+            .unwrap();
+        assert!(
+            hash.sql
+                .contains(r#"USING HASH (("metadata" #>> '{user,email}'))"#)
+        );
+        assert!(hash.reason.contains("range filters"));
+        assert_eq!(hash.priority, IndexPriority::High);
+
+        // Parameter keys compared otherwise could be the same path
+        let mut unknown = accesses.to_vec();
+        unknown.push(ColumnAccess {
+            path: None,
+            ..access("", JsonOperator::Extract)
+        });
+        let with_unknown = plan_indexes("users", "metadata", &stats, &unknown, &[], &config);
+        assert!(
+            with_unknown
+                .recommendations
+                .iter()
+                .all(|r| r.index_type != IndexType::HashExtracted)
+        );
+
+        // An existing B-tree on the value serves the same lookups
+        let existing = ExistingIndex {
+            name: "idx_email".to_string(),
+            method: "btree".to_string(),
+            keys: vec!["(metadata -> 'user'::text) ->> 'email'::text".to_string()],
+            operator_classes: vec!["text_ops".to_string()],
+            predicate: None,
+            unique: false,
+            definition: String::new(),
+        };
+        let covered = plan_indexes("users", "metadata", &stats, &accesses, &[existing], &config);
+        assert!(
+            covered
+                .covered
+                .iter()
+                .any(|c| c.index_type == IndexType::HashExtracted && c.covered_by == "idx_email")
+        );
+
+        // 4-byte hash codes, however long the values
+        estimate_index_sizes(&mut plan.recommendations, &stats, 1_000_000, &config);
+        let size = plan
+            .recommendations
+            .iter()
+            .find(|r| r.index_type == IndexType::HashExtracted)
+            .and_then(|r| r.estimated_size_bytes)
+            .unwrap();
+        assert!((13_000_000..15_000_000).contains(&size), "{}", size);
+    }
+
+    #[test]
+    fn test_trigram_index_for_text_search() {
+        let string_stats = |avg_bytes: f64| StringStats {
+            count: 5000,
+            total_bytes: (5000.0 * avg_bytes) as u64,
+            avg_bytes,
+            max_bytes: 400,
+            blob_count: 0,
+        };
+        let mut bio = create_test_stats("profile.bio", 0.5, 5000, 10000);
+        bio.types.insert(JsonType::String, 5000);
+        bio.strings = Some(string_stats(120.0));
+        bio.examples = vec![json!("Likes 100% o'clock")];
+        let mut code = create_test_stats("code", 0.5, 5000, 10000);
+        code.types.insert(JsonType::String, 5000);
+        code.strings = Some(string_stats(6.0));
+        let stats = [bio, code];
+
+        let accesses = [ColumnAccess {
+            path: Some("profile.bio".to_string()),
+            operator: JsonOperator::Pattern,
+            usage: QueryUsage {
+                statements: 1,
+                calls: 800,
+                mean_exec_time_ms: 40.0,
+            },
+        }];
+        let trigrams = |plan: &IndexPlan| {
+            plan.recommendations
+                .iter()
+                .filter(|r| r.index_type == IndexType::TrigramExtracted)
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+
+        // Only with pg_trgm installed
+        let config = IndexConfig::default();
+        let plan = plan_indexes("users", "metadata", &stats, &accesses, &[], &config);
+        assert!(trigrams(&plan).is_empty());
+
+        let config = IndexConfig {
+            trigram: true,
+            // Too short to be worth it
+            text_search_paths: vec!["code".to_string()],
+            ..Default::default()
+        };
+        let mut plan = plan_indexes("users", "metadata", &stats, &accesses, &[], &config);
+        let found = trigrams(&plan);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].field_path, "profile.bio");
+        assert_eq!(found[0].priority, IndexPriority::High);
+        assert!(
+            found[0]
+                .sql
+                .contains(r#"USING GIN (("metadata" #>> '{profile,bio}') gin_trgm_ops)"#)
+        );
+        assert_eq!(
+            probe_query("users", "metadata", &found[0], &stats).unwrap(),
+            r#"SELECT * FROM "users" WHERE ("metadata" #>> '{profile,bio}') ILIKE '%ikes 1%'"#
+        );
+
+        estimate_index_sizes(&mut plan.recommendations, &stats, 1_000_000, &config);
+        let size = trigrams(&plan)[0].estimated_size_bytes.unwrap();
+        // 500k values of ~122 trigrams at 2 bytes each
+        assert!((120_000_000..150_000_000).contains(&size), "{}", size);
+
+        let existing = ExistingIndex {
+            name: "idx_bio_trgm".to_string(),
+            method: "gist".to_string(),
+            keys: vec!["(metadata -> 'profile'::text) ->> 'bio'::text".to_string()],
+            operator_classes: vec!["gist_trgm_ops".to_string()],
+            predicate: None,
+            unique: false,
+            definition: String::new(),
+        };
+        let covered = plan_indexes("users", "metadata", &stats, &accesses, &[existing], &config);
+        assert!(trigrams(&covered).is_empty());
+        assert_eq!(covered.covered[0].covered_by, "idx_bio_trgm");
+    }
+
+    #[test]
+    fn test_partial_btree_for_skewed_values() {
+        let mut status = FieldStats::new("status".to_string(), 1);
+        for (value, count) in [("completed", 9500), ("pending", 400), ("failed", 100)] {
+            for _ in 0..count {
+                status.record(&json!(value));
+            }
+        }
+        status.finalize(10000);
+        let mut kind = FieldStats::new("kind".to_string(), 1);
+        for (value, count) in [("order", 6000), ("refund", 4000)] {
+            for _ in 0..count {
+                kind.record(&json!(value));
+            }
+        }
+        kind.finalize(10000);
+        let stats = [status, kind];
+
+        let mut config = IndexConfig::default();
+        config.layout.rows = 1_000_000;
+        let mut plan = plan_indexes("jobs", "data", &stats, &[], &[], &config);
+        let partial: Vec<&IndexRecommendation> = plan
+            .recommendations
+            .iter()
+            .filter(|r| r.index_type == IndexType::PartialBTree)
+            .collect();
+        // An evenly split field isn't skewed
+        assert_eq!(partial.len(), 1);
+        assert_eq!(partial[0].field_path, "status");
+        assert!(partial[0].sql.ends_with(
+            r#"ON "jobs" (("data" #>> '{status}')) WHERE ("data" #>> '{status}') IN ('pending', 'failed');"#
+        ));
+        // Each pending value matches 4% of rows: selective enough, not high
+        assert_eq!(partial[0].priority, IndexPriority::Medium);
+        assert_eq!(
+            probe_query("jobs", "data", partial[0], &stats).unwrap(),
+            r#"SELECT * FROM "jobs" WHERE ("data" #>> '{status}') = 'failed'"#
+        );
+
+        estimate_index_sizes(&mut plan.recommendations, &stats, 1_000_000, &config);
+        let size = plan
+            .recommendations
+            .iter()
+            .find(|r| r.index_type == IndexType::PartialBTree)
+            .and_then(|r| r.estimated_size_bytes)
+            .unwrap();
+        // 50k rows of ~24-byte tuples, far from the 1M of a full B-tree
+        assert!((1_000_000..2_000_000).contains(&size), "{}", size);
+
+        // Only the rarest value when the threshold is lower
+        let config = IndexConfig {
+            max_hot_value_share: 0.02,
+            ..Default::default()
+        };
+        let recommendation = recommend_index("jobs", "data", &stats, &config)
+            .into_iter()
+            .find(|r| r.index_type == IndexType::PartialBTree)
+            .unwrap();
+        assert!(
+            recommendation
+                .sql
+                .ends_with(r#"WHERE ("data" #>> '{status}') = 'failed';"#)
+        );
+
+        let existing = ExistingIndex {
+            name: "idx_jobs_failed".to_string(),
+            method: "btree".to_string(),
+            keys: vec!["(data #>> '{status}'::text[])".to_string()],
+            operator_classes: vec!["text_ops".to_string()],
+            predicate: Some("((data #>> '{status}'::text[]) = 'failed'::text)".to_string()),
+            unique: false,
+            definition: String::new(),
+        };
+        assert!(covers(
+            &existing,
+            &recommendation,
+            "data",
+            Some("text"),
+            &config
+        ));
+        let existing = ExistingIndex {
+            predicate: Some("((data #>> '{status}'::text[]) = 'pending'::text)".to_string()),
+            ..existing
+        };
+        assert!(!covers(
+            &existing,
+            &recommendation,
+            "data",
+            Some("text"),
+            &config
+        ));
+    }
+
+    #[test]
+    fn test_composite_btree_with_columns() {
+        let mut email = create_test_stats("email", 0.5, 5000, 10000);
+        email.types.insert(JsonType::String, 5000);
+        email.cardinality = 5000;
+        let mut status = FieldStats::new("status".to_string(), 1);
+        for (value, count) in [("completed", 9500), ("pending", 500)] {
+            for _ in 0..count {
+                status.record(&json!(value));
+            }
+        }
+        status.finalize(10000);
+        let stats = [email, status];
+
+        let config = IndexConfig {
+            with_columns: vec!["tenant_id".to_string()],
+            ..Default::default()
+        };
+        let mut plan = plan_indexes("users", "metadata", &stats, &[], &[], &config);
+        let composite: Vec<&IndexRecommendation> = plan
+            .recommendations
+            .iter()
+            .filter(|r| r.index_type == IndexType::CompositeBTree)
+            .collect();
+        assert_eq!(composite.len(), 2);
+        assert!(plan.recommendations.iter().all(|r| !matches!(
+            r.index_type,
+            IndexType::BTreeExtracted | IndexType::PartialBTree
+        )));
+        let email = composite.iter().find(|r| r.field_path == "email").unwrap();
+        assert!(email.sql.ends_with(
+            r#"ON "users" ("tenant_id", ("metadata" #>> '{email}')) WHERE ("metadata" #>> '{email}') IS NOT NULL;"#
+        ));
+        let status = composite.iter().find(|r| r.field_path == "status").unwrap();
+        assert!(status.sql.ends_with(
+            r#"ON "users" ("tenant_id", ("metadata" #>> '{status}')) WHERE ("metadata" #>> '{status}') = 'pending';"#
+        ));
+        assert!(probe_query("users", "metadata", status, &stats).is_none());
+
+        estimate_index_sizes(&mut plan.recommendations, &stats, 1_000_000, &config);
+        let size = |path: &str| {
+            plan.recommendations
+                .iter()
+                .find(|r| r.field_path == path && r.index_type == IndexType::CompositeBTree)
+                .and_then(|r| r.estimated_size_bytes)
+                .unwrap()
+        };
+        // Only the 5% pending rows are in the status index
+        assert!(size("status") * 5 < size("email"));
+
+        let email = plan
+            .recommendations
+            .iter()
+            .find(|r| r.field_path == "email" && r.index_type == IndexType::CompositeBTree)
+            .unwrap();
+        let existing = ExistingIndex {
+            name: "idx_users_tenant_email".to_string(),
+            method: "btree".to_string(),
+            keys: vec![
+                "tenant_id".to_string(),
+                "(metadata ->> 'email'::text)".to_string(),
+            ],
+            operator_classes: vec!["int8_ops".to_string(), "text_ops".to_string()],
+            predicate: None,
+            unique: false,
+            definition: String::new(),
+        };
+        assert!(covers(&existing, email, "metadata", Some("text"), &config));
+        // Led by the extracted value instead, it doesn't serve tenant lookups
+        let existing = ExistingIndex {
+            keys: vec![
+                "(metadata ->> 'email'::text)".to_string(),
+                "tenant_id".to_string(),
+            ],
+            ..existing
+        };
+        assert!(!covers(&existing, email, "metadata", Some("text"), &config));
+    }
+
+    #[test]
+    fn test_order_correlation() {
+        let rows = |values: &[(i64, &str)]| {
+            values
+                .iter()
+                .map(|(block, value)| (*block, value.to_string()))
+                .collect::<Vec<_>>()
+        };
+        let appended = rows(&[
+            (0, "2024-01-01T00:00:00Z"),
+            (0, "2024-01-01T00:05:00Z"),
+            (1, "2024-01-02T10:00:00Z"),
+            (2, "2024-01-03T08:00:00Z"),
+            (3, "2024-01-05T12:00:00Z"),
+        ]);
+        assert!((order_correlation(&appended).unwrap() - 0.974).abs() < 0.001);
+
+        let reversed: Vec<_> = appended
+            .iter()
+            .map(|(block, value)| (-block, value.clone()))
+            .collect();
+        assert!(order_correlation(&reversed).unwrap() < -0.9);
+
+        let shuffled = rows(&[
+            (0, "2024-01-03"),
+            (1, "2024-01-01"),
+            (2, "2024-01-05"),
+            (3, "2024-01-02"),
+        ]);
+        assert!(order_correlation(&shuffled).unwrap().abs() < 0.5);
+
+        assert_eq!(order_correlation(&appended[..2]), None);
+        assert_eq!(
+            order_correlation(&rows(&[(0, "a"), (1, "a"), (2, "a")])),
+            None
+        );
+    }
+
+    #[test]
+    fn test_brin_for_ordered_timestamps() {
+        use crate::stats::FormatConsistency;
+
+        let timestamp = |path: &str| {
+            let mut stats = create_test_stats(path, 0.5, 5000, 10000);
+            stats.types.insert(JsonType::String, 5000);
+            stats.cardinality = 5000;
+            stats.format = Some(FormatConsistency {
+                format: StringFormat::DateTime,
+                consistency: 100.0,
+            });
+            stats.strings = Some(StringStats {
+                count: 5000,
+                total_bytes: 5000 * 20,
+                avg_bytes: 20.0,
+                max_bytes: 20,
+                blob_count: 0,
+            });
+            stats
+        };
+        let mut note = create_test_stats("note", 0.5, 5000, 10000);
+        note.types.insert(JsonType::String, 5000);
+        let stats = [timestamp("created_at"), timestamp("updated_at"), note];
+
+        let mut config = IndexConfig::default();
+        assert_eq!(
+            brin_candidates(&stats, &config),
+            vec!["created_at", "updated_at"]
+        );
+        config.layout = PhysicalLayout {
+            pages: 20_000,
+            correlations: HashMap::from([
+                ("created_at".to_string(), 0.99),
+                ("updated_at".to_string(), 0.3),
+            ]),
+            ..Default::default()
+        };
+
+        let mut plan = plan_indexes("events", "data", &stats, &[], &[], &config);
+        let index_type = |plan: &IndexPlan, path: &str| {
+            plan.recommendations
+                .iter()
+                .find(|r| r.field_path == path)
+                .map(|r| r.index_type.clone())
+        };
+        assert_eq!(
+            index_type(&plan, "created_at"),
+            Some(IndexType::BrinExtracted)
+        );
+        assert_eq!(
+            index_type(&plan, "updated_at"),
+            Some(IndexType::BTreeExtracted)
+        );
+        let brin = plan
+            .recommendations
+            .iter()
+            .find(|r| r.index_type == IndexType::BrinExtracted)
+            .unwrap();
+        assert!(
+            brin.sql
+                .contains(r#"USING BRIN (("data" #>> '{created_at}'));"#)
+        );
+        assert!(brin.reason.contains("correlation 0.99"));
+
+        // 157 ranges of 66 bytes in two pages and the metapage
+        estimate_index_sizes(&mut plan.recommendations, &stats, 1_000_000, &config);
+        let brin = plan
+            .recommendations
+            .iter()
+            .find(|r| r.index_type == IndexType::BrinExtracted)
+            .unwrap();
+        assert_eq!(brin.estimated_size_bytes, Some(3 * 8192));
+        assert!(brin.estimated_benefit.contains("x smaller than a B-tree"));
+
+        // An existing B-tree on the expression already serves range filters
+        let existing = ExistingIndex {
+            name: "idx_created".to_string(),
+            method: "btree".to_string(),
+            keys: vec!["(data ->> 'created_at'::text)".to_string()],
+            operator_classes: vec!["text_ops".to_string()],
+            predicate: None,
+            unique: false,
+            definition: String::new(),
+        };
+        let covered = plan_indexes("events", "data", &stats, &[], &[existing], &config);
+        assert_eq!(index_type(&covered, "created_at"), None);
+        assert_eq!(covered.covered[0].index_type, IndexType::BrinExtracted);
+    }
+
+    #[test]
+    fn test_generated_column() {
+        let mut email = create_test_stats("user.emailAddress", 0.5, 5000, 10000);
+        email.types.insert(JsonType::String, 5000);
+        let mut score = create_test_stats("score", 0.5, 5000, 10000);
+        score.types.insert(JsonType::Number, 5000);
+        let stats = [email, score];
+
+        let access = |path: &str, calls| ColumnAccess {
+            path: Some(path.to_string()),
+            operator: JsonOperator::Extract,
+            usage: QueryUsage {
+                statements: 1,
+                calls,
+                mean_exec_time_ms: 1.0,
+            },
+        };
+        let accesses = [access("user.emailAddress", 50_000), access("score", 10)];
+        let config = IndexConfig {
+            generated_columns: true,
+            ..Default::default()
+        };
+
+        let plan = plan_indexes("users", "metadata", &stats, &accesses, &[], &config);
+        let generated = &plan.recommendations[0];
+        assert_eq!(generated.index_type, IndexType::GeneratedColumn);
+        assert_eq!(generated.field_path, "user.emailAddress");
+        assert!(generated.sql.contains(
+            "ALTER TABLE \"users\" ADD COLUMN \"user_email_address\" TEXT \
+             GENERATED ALWAYS AS (\"metadata\" #>> '{user,emailAddress}') STORED;\n\
+             CREATE INDEX idx_users_metadata_user_emailAddress_gen ON \"users\" (\"user_email_address\");"
+        ));
+        assert_eq!(probe_query("users", "metadata", generated, &stats), None);
+        // Too quiet for a column of its own
+        assert_eq!(
+            plan.recommendations[1].index_type,
+            IndexType::BTreeExtracted
+        );
+
+        let existing = ExistingIndex {
+            name: "users_email_idx".to_string(),
+            method: "btree".to_string(),
+            keys: vec!["user_email_address".to_string()],
+            operator_classes: vec!["text_ops".to_string()],
+            predicate: None,
+            unique: false,
+            definition: String::new(),
+        };
+        let covered = plan_indexes("users", "metadata", &stats, &accesses, &[existing], &config);
+        assert_eq!(covered.covered[0].index_type, IndexType::GeneratedColumn);
+        assert_eq!(covered.covered[0].covered_by, "users_email_idx");
+    }
+
+    #[test]
+    fn test_assign_priorities() {
+        let scalar = |path: &str, density: f64, cardinality: u64| {
+            let occurrences = (density * 10000.0) as u64;
+            let mut stats = create_test_stats(path, density, occurrences, 10000);
+            stats.types.insert(JsonType::String, occurrences);
+            stats.cardinality = cardinality;
+            stats
+        };
+        let stats = [
+            // Nearly unique, so about one row per value
+            scalar("order_id", 0.5, 4900),
+            // 5 values over half of the rows, ~10% each
+            scalar("status", 0.5, 5),
+            // 200 values, ~0.25% of the rows each
+            scalar("region", 0.5, 200),
+            scalar("coupon", 0.1, 400),
+        ];
+        let plan = |rows: i64| {
+            let config = IndexConfig {
+                layout: PhysicalLayout {
+                    rows,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            plan_indexes("orders", "data", &stats, &[], &[], &config)
+                .recommendations
+                .into_iter()
+                .map(|r| (r.field_path, r.priority))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            plan(5_000_000),
+            vec![
+                ("order_id".to_string(), IndexPriority::High),
+                ("region".to_string(), IndexPriority::High),
+                ("coupon".to_string(), IndexPriority::High),
+                ("status".to_string(), IndexPriority::Medium),
+            ]
+        );
+        assert_eq!(
+            plan(100_000),
+            vec![
+                ("order_id".to_string(), IndexPriority::Medium),
+                ("status".to_string(), IndexPriority::Medium),
+                ("region".to_string(), IndexPriority::Medium),
+                ("coupon".to_string(), IndexPriority::Medium),
+            ]
+        );
+        assert!(
+            plan(2_000)
+                .iter()
+                .all(|(_, priority)| *priority == IndexPriority::Low)
+        );
+
+        let unselective = [scalar("status", 0.5, 2)];
+        let recommendations = plan_indexes(
+            "orders",
+            "data",
+            &unselective,
+            &[],
+            &[],
+            &IndexConfig::default(),
+        )
+        .recommendations;
+        assert_eq!(recommendations[0].priority, IndexPriority::Low);
+        assert!(recommendations[0].reason.ends_with(
+            "Each value matches ~25.0% of rows, too many for the planner to prefer the index."
+        ));
+    }
 }
@@ -1,5 +1,26 @@
 pub mod analyzer;
+pub mod cohort;
+pub mod compare;
+pub mod conditional;
+pub mod conformance;
+pub mod cooccurrence;
+pub mod dedup;
+pub mod denormalization;
 pub mod drift;
+pub mod format;
+pub mod hll;
 pub mod index;
+pub mod promotion;
+pub mod redact;
+pub mod remediation;
+pub mod report;
+pub mod score;
+pub mod segment;
+pub mod sql;
 pub mod stats;
+pub mod tree;
 pub mod types;
+pub mod usage;
+pub mod validate;
+pub mod version;
+pub mod workload;
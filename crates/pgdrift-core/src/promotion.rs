@@ -0,0 +1,393 @@
+use crate::analyzer::is_wildcard_path;
+use crate::format::StringFormat;
+use crate::remediation::path_literal;
+use crate::sql::quote_ident;
+use crate::stats::FieldStats;
+use crate::types::JsonType;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Configuration for column promotion recommendations
+#[derive(Debug, Clone)]
+pub struct PromotionConfig {
+    /// Lowest bound of the density interval a path must reach (default: 0.95)
+    pub min_density: f64,
+    /// Minimum non-null values seen for the path (default: 100)
+    pub min_occurrences: u64,
+    /// Distinct values from which the promoted column gets an index (default: 10)
+    pub min_index_cardinality: u64,
+    /// Rows updated (and committed) per backfill batch (default: 1000)
+    pub batch_size: usize,
+}
+
+impl Default for PromotionConfig {
+    fn default() -> Self {
+        Self {
+            min_density: 0.95,
+            min_occurrences: 100,
+            min_index_cardinality: 10,
+            batch_size: 1000,
+        }
+    }
+}
+
+/// A JSON path worth moving into a column of its own
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Promotion {
+    pub path: String,
+    /// Name suggested for the new column, e.g. `customer_email`
+    pub column_name: String,
+    pub sql_type: String,
+    pub density: f64,
+    /// A stored generated column (kept in sync by Postgres) rather than a
+    /// plain column the application has to write
+    pub generated: bool,
+    /// Whether the DDL creates an index on the new column
+    pub indexed: bool,
+    /// Approximate bytes the key and its value take in each document
+    pub json_bytes_per_row: f64,
+    /// Approximate bytes the value takes as a column
+    pub column_bytes_per_row: f64,
+    pub sql: String,
+    pub benefit: String,
+}
+
+/// Recommend promoting stable scalar paths out of the JSON column
+///
+/// Candidates are present in nearly every document (judged on the lower
+/// bound of their density interval) and always hold the same scalar type;
+/// strings get `uuid`, `date` or `timestamptz` when every value has that
+/// format. Types whose cast is immutable become stored generated columns,
+/// the others plain columns with a batched backfill. Paths inside arrays or
+/// collapsed maps can't be promoted and are skipped.
+pub fn recommend_promotions(
+    schema: &str,
+    table: &str,
+    column: &str,
+    field_stats: &[FieldStats],
+    config: &PromotionConfig,
+) -> Vec<Promotion> {
+    let mut promotions: Vec<Promotion> = field_stats
+        .iter()
+        .filter(|fs| !fs.path.is_empty() && !fs.path.contains("[]") && !is_wildcard_path(&fs.path))
+        .filter(|fs| {
+            fs.density_interval.lower >= config.min_density
+                && fs.occurrences - fs.null_count >= config.min_occurrences
+                && fs.cardinality > 1
+        })
+        .filter_map(|fs| {
+            let sql_type = column_type(fs)?;
+            Some(promotion(schema, table, column, fs, sql_type, config))
+        })
+        .collect();
+    promotions.sort_by(|a, b| a.path.cmp(&b.path));
+    promotions
+}
+
+/// Postgres type for the values of a path, if they all share one scalar type
+fn column_type(fs: &FieldStats) -> Option<SqlType> {
+    let mut types = fs.types.keys().filter(|t| **t != JsonType::Null);
+    let json_type = *types.next()?;
+    if types.next().is_some() {
+        return None;
+    }
+
+    match json_type {
+        JsonType::Boolean => Some(SqlType::Boolean),
+        JsonType::Number => Some(if is_integral(fs) {
+            SqlType::Bigint
+        } else {
+            SqlType::Numeric
+        }),
+        JsonType::String => {
+            let format = fs.format.as_ref().filter(|f| f.consistency >= 100.0);
+            Some(match format.map(|f| f.format) {
+                Some(StringFormat::Uuid) => SqlType::Uuid,
+                Some(StringFormat::Date) => SqlType::Date,
+                Some(StringFormat::DateTime) => SqlType::Timestamptz,
+                _ => SqlType::Text,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Whether every number seen looks like an integer that fits a bigint
+fn is_integral(fs: &FieldStats) -> bool {
+    let Some(numeric) = &fs.numeric else {
+        return false;
+    };
+    let whole = |x: f64| x.fract() == 0.0 && x.abs() < 9.0e18;
+    whole(numeric.min)
+        && whole(numeric.max)
+        && fs
+            .examples
+            .iter()
+            .all(|v| matches!(v, Value::Number(n) if n.is_i64() || n.is_u64()))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SqlType {
+    Text,
+    Bigint,
+    Numeric,
+    Boolean,
+    Uuid,
+    Date,
+    Timestamptz,
+}
+
+impl SqlType {
+    fn name(self) -> &'static str {
+        match self {
+            SqlType::Text => "text",
+            SqlType::Bigint => "bigint",
+            SqlType::Numeric => "numeric",
+            SqlType::Boolean => "boolean",
+            SqlType::Uuid => "uuid",
+            SqlType::Date => "date",
+            SqlType::Timestamptz => "timestamptz",
+        }
+    }
+
+    /// Casts from text to date and time types depend on settings like
+    /// `DateStyle` and `TimeZone`, so generated columns can't use them
+    fn is_immutable(self) -> bool {
+        !matches!(self, SqlType::Date | SqlType::Timestamptz)
+    }
+
+    /// Approximate on-disk size of a value of this type
+    fn bytes(self, fs: &FieldStats) -> f64 {
+        match self {
+            SqlType::Boolean => 1.0,
+            SqlType::Date => 4.0,
+            SqlType::Bigint | SqlType::Numeric | SqlType::Timestamptz => 8.0,
+            SqlType::Uuid => 16.0,
+            SqlType::Text => 1.0 + avg_string_bytes(fs),
+        }
+    }
+}
+
+fn avg_string_bytes(fs: &FieldStats) -> f64 {
+    fs.strings.as_ref().map_or(0.0, |s| s.avg_bytes)
+}
+
+/// Approximate bytes a key and its value take in a jsonb document
+///
+/// Every key and value has a 4-byte entry header; booleans live entirely in
+/// theirs and numbers are stored as numerics of about 8 bytes.
+fn json_bytes(fs: &FieldStats) -> f64 {
+    let key = fs.path.rsplit('.').next().unwrap_or(&fs.path);
+    let value = match fs.types.keys().find(|t| **t != JsonType::Null) {
+        Some(JsonType::String) => avg_string_bytes(fs),
+        Some(JsonType::Number) => 8.0,
+        _ => 0.0,
+    };
+    8.0 + key.len() as f64 + value
+}
+
+fn promotion(
+    schema: &str,
+    table: &str,
+    column: &str,
+    fs: &FieldStats,
+    sql_type: SqlType,
+    config: &PromotionConfig,
+) -> Promotion {
+    let column_name = column_name(&fs.path);
+    let table_quoted = format!("{}.{}", quote_ident(schema), quote_ident(table));
+    let new_column = quote_ident(&column_name);
+    let text = format!("{} #>> {}", quote_ident(column), path_literal(&fs.path));
+    let value = match sql_type {
+        SqlType::Text => text.clone(),
+        _ => format!("({})::{}", text, sql_type.name()),
+    };
+    let generated = sql_type.is_immutable();
+    let indexed = sql_type != SqlType::Boolean && fs.cardinality >= config.min_index_cardinality;
+
+    let mut sql = format!("-- {} → {} {}\n", fs.path, column_name, sql_type.name());
+    if generated {
+        sql.push_str(&format!(
+            "-- Adding a stored generated column rewrites the table under an exclusive lock\n\
+             ALTER TABLE {table} ADD COLUMN {new_column} {sql_type}\n    \
+             GENERATED ALWAYS AS ({value}) STORED;",
+            table = table_quoted,
+            sql_type = sql_type.name(),
+        ));
+    } else {
+        sql.push_str(&format!(
+            "-- The application has to write the column from now on\n\
+             ALTER TABLE {table} ADD COLUMN {new_column} {sql_type};\n\
+             DO $$\n\
+             DECLARE\n    updated integer;\n\
+             BEGIN\n    LOOP\n        \
+             UPDATE {table}\n        \
+             SET {new_column} = {value}\n        \
+             WHERE ctid IN (\n            \
+             SELECT ctid FROM {table}\n            \
+             WHERE {new_column} IS NULL AND {text} IS NOT NULL\n            \
+             LIMIT {batch_size}\n        \
+             );\n        \
+             GET DIAGNOSTICS updated = ROW_COUNT;\n        \
+             EXIT WHEN updated = 0;\n        \
+             COMMIT;\n    \
+             END LOOP;\n\
+             END $$;",
+            table = table_quoted,
+            sql_type = sql_type.name(),
+            batch_size = config.batch_size,
+        ));
+    }
+    if indexed {
+        sql.push_str(&format!(
+            "\nCREATE INDEX CONCURRENTLY {} ON {} ({});",
+            quote_ident(&format!("{}_{}_idx", table, column_name)),
+            table_quoted,
+            new_column
+        ));
+    }
+
+    let mut benefit = format!(
+        "Typed {} column with planner statistics: filters on ~{} distinct values are estimated from real data instead of a fixed guess",
+        sql_type.name(),
+        fs.cardinality
+    );
+    if indexed {
+        benefit.push_str(", and served by a B-tree index");
+    }
+
+    Promotion {
+        path: fs.path.clone(),
+        column_name,
+        sql_type: sql_type.name().to_string(),
+        density: fs.density,
+        generated,
+        indexed,
+        json_bytes_per_row: json_bytes(fs) * fs.density,
+        column_bytes_per_row: sql_type.bytes(fs) * fs.density,
+        sql,
+        benefit,
+    }
+}
+
+/// Column name for a path: keys joined with `_`, camelCase turned into snake_case
+pub fn column_name(path: &str) -> String {
+    let mut name = String::new();
+    let mut previous_lower = false;
+    for c in path.chars() {
+        if c.is_ascii_uppercase() {
+            if previous_lower {
+                name.push('_');
+            }
+            name.push(c.to_ascii_lowercase());
+            previous_lower = false;
+        } else if c.is_ascii_alphanumeric() {
+            name.push(c);
+            previous_lower = true;
+        } else {
+            if !name.is_empty() && !name.ends_with('_') {
+                name.push('_');
+            }
+            previous_lower = false;
+        }
+    }
+    name.trim_end_matches('_').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::JsonAnalyzer;
+    use serde_json::json;
+
+    fn recommend(documents: &[Value]) -> Vec<Promotion> {
+        let mut analyzer = JsonAnalyzer::new();
+        for document in documents {
+            analyzer.analyze(document);
+        }
+        let stats: Vec<FieldStats> = analyzer.finalize().into_values().collect();
+        recommend_promotions(
+            "public",
+            "orders",
+            "data",
+            &stats,
+            &PromotionConfig::default(),
+        )
+    }
+
+    #[test]
+    fn test_recommend_promotions() {
+        let documents: Vec<Value> = (0..500)
+            .map(|i| {
+                json!({
+                    "customer": {"email": format!("user{}@example.com", i % 50)},
+                    "createdAt": format!("2024-01-{:02}T10:00:00Z", i % 28 + 1),
+                    "total": i as f64 + 0.5,
+                    "quantity": i % 7,
+                    "gift": i % 2 == 0,
+                    "currency": "EUR",
+                    "mixed": if i % 2 == 0 { json!(1) } else { json!("1") },
+                    "note": if i % 4 == 0 { json!(format!("note {}", i % 3)) } else { Value::Null },
+                    "items": [{"sku": "a"}],
+                })
+            })
+            .collect();
+
+        let found: Vec<_> = recommend(&documents)
+            .iter()
+            .map(|p| {
+                format!(
+                    "{} {} {} generated={} indexed={}",
+                    p.path, p.column_name, p.sql_type, p.generated, p.indexed
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                "createdAt created_at timestamptz generated=false indexed=true",
+                "customer.email customer_email text generated=true indexed=true",
+                "gift gift boolean generated=true indexed=false",
+                "note note text generated=true indexed=false",
+                "quantity quantity bigint generated=true indexed=false",
+                "total total numeric generated=true indexed=true",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_promotion_sql() {
+        let documents: Vec<Value> = (0..200)
+            .map(|i| json!({"customer": {"id": i}, "shipped_on": format!("2024-02-{:02}", i % 28 + 1)}))
+            .collect();
+        let promotions = recommend(&documents);
+
+        let generated = &promotions[0];
+        assert_eq!(generated.path, "customer.id");
+        assert!(generated.sql.contains(
+            "ALTER TABLE \"public\".\"orders\" ADD COLUMN \"customer_id\" bigint\n    GENERATED ALWAYS AS ((\"data\" #>> '{customer,id}')::bigint) STORED;"
+        ));
+        assert!(generated.sql.contains(
+            "CREATE INDEX CONCURRENTLY \"orders_customer_id_idx\" ON \"public\".\"orders\" (\"customer_id\");"
+        ));
+
+        let backfilled = &promotions[1];
+        assert_eq!(backfilled.sql_type, "date");
+        assert!(backfilled.sql.contains("ADD COLUMN \"shipped_on\" date;"));
+        assert!(
+            backfilled
+                .sql
+                .contains("SET \"shipped_on\" = (\"data\" #>> '{shipped_on}')::date")
+        );
+        assert!(backfilled.sql.contains("LIMIT 1000"));
+        assert!(backfilled.json_bytes_per_row > backfilled.column_bytes_per_row);
+    }
+
+    #[test]
+    fn test_column_name() {
+        assert_eq!(column_name("customer.email"), "customer_email");
+        assert_eq!(column_name("createdAt"), "created_at");
+        assert_eq!(column_name("HTTPStatus"), "httpstatus");
+        assert_eq!(column_name("billing-address.zip"), "billing_address_zip");
+    }
+}
@@ -0,0 +1,198 @@
+use hmac::{Hmac, Mac};
+use serde_json::{Number, Value};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::str::FromStr;
+
+/// Placeholder for values removed by `Redaction::Drop`
+pub const REDACTED: &str = "[redacted]";
+
+/// How sampled values are hidden before they are reported
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Redaction {
+    /// Keep the shape: letters become `x`, digits become `9`, punctuation stays
+    /// (`alice@example.com` -> `xxxxx@xxxxxxx.xxx`)
+    Mask,
+    /// Replace strings with an HMAC-SHA256 under a secret key, so equal
+    /// values stay comparable across runs with the same key; numbers are
+    /// masked. Without the key, guesses can't be hashed and looked up.
+    Hash(RedactionKey),
+    /// Remove example values entirely
+    Drop,
+}
+
+impl Redaction {
+    /// Redact a single value
+    pub fn apply(&self, value: &Value) -> Value {
+        match self {
+            Redaction::Drop => Value::String(REDACTED.to_string()),
+            Redaction::Mask | Redaction::Hash(_) => self.redact(value),
+        }
+    }
+
+    fn redact(&self, value: &Value) -> Value {
+        match value {
+            Value::Null | Value::Bool(_) => value.clone(),
+            Value::Number(n) => mask_number(n),
+            Value::String(s) => match self {
+                Redaction::Hash(key) => Value::String(key.hash_str(s)),
+                _ => Value::String(mask_str(s)),
+            },
+            Value::Array(items) => Value::Array(items.iter().map(|v| self.redact(v)).collect()),
+            // Keys are schema, not data, so they are kept
+            Value::Object(map) => Value::Object(
+                map.iter()
+                    .map(|(k, v)| (k.clone(), self.redact(v)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Only `mask` and `drop` parse; `hash` needs a key, see `RedactionKey`
+impl FromStr for Redaction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mask" => Ok(Redaction::Mask),
+            "hash" => Err("hash redaction needs a secret key".to_string()),
+            "drop" => Ok(Redaction::Drop),
+            other => Err(format!(
+                "unknown redaction '{}' (expected mask, hash or drop)",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Redaction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Redaction::Mask => write!(f, "mask"),
+            Redaction::Hash(_) => write!(f, "hash"),
+            Redaction::Drop => write!(f, "drop"),
+        }
+    }
+}
+
+/// Secret key of `Redaction::Hash`
+///
+/// Derived from a secret of any length. Hashes only match across runs
+/// using the same secret.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct RedactionKey([u8; 32]);
+
+impl RedactionKey {
+    /// Derive the key from a secret, e.g. a passphrase
+    pub fn new(secret: &[u8]) -> Self {
+        Self(Sha256::digest(secret).into())
+    }
+
+    /// First 64 bits of the HMAC-SHA256 of a string, as hex
+    fn hash_str(&self, s: &str) -> String {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.0).expect("HMAC accepts keys of any length");
+        mac.update(s.as_bytes());
+        let digest = mac.finalize().into_bytes();
+        let hex: String = digest[..8].iter().map(|b| format!("{:02x}", b)).collect();
+        format!("hash:{}", hex)
+    }
+}
+
+/// Keeps the key out of logs and error messages
+impl fmt::Debug for RedactionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("RedactionKey(..)")
+    }
+}
+
+fn mask_str(s: &str) -> String {
+    s.chars()
+        .map(|c| {
+            if c.is_ascii_digit() {
+                '9'
+            } else if c.is_uppercase() {
+                'X'
+            } else if c.is_alphabetic() {
+                'x'
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// Mask the digits of a number but keep its sign, decimal point and exponent,
+/// so the order of magnitude survives
+fn mask_number(n: &Number) -> Value {
+    let text = n.to_string();
+    let (mantissa, exponent) = match text.find(['e', 'E']) {
+        Some(i) => text.split_at(i),
+        None => (text.as_str(), ""),
+    };
+    let masked = format!("{}{}", mask_str(mantissa), exponent);
+    serde_json::from_str(&masked).unwrap_or(Value::Number(0.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_mask() {
+        let value = json!({
+            "email": "Alice@example.com",
+            "amount": -1234.5,
+            "big": 1.5e20,
+            "active": true,
+            "tags": ["vip", null]
+        });
+
+        assert_eq!(
+            Redaction::Mask.apply(&value),
+            json!({
+                "email": "Xxxxx@xxxxxxx.xxx",
+                "amount": -9999.9,
+                "big": 9.9e20,
+                "active": true,
+                "tags": ["xxx", null]
+            })
+        );
+    }
+
+    #[test]
+    fn test_hash_is_stable() {
+        let hash = Redaction::Hash(RedactionKey::new(b"secret"));
+        let a = hash.apply(&json!("alice@example.com"));
+        let b = hash.apply(&json!("alice@example.com"));
+        let c = hash.apply(&json!("bob@example.com"));
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(hash.apply(&json!(42)), json!(99));
+        // Pinned so reports stay comparable across releases and platforms
+        assert_eq!(a, json!("hash:944d3afd14d6f3ff"));
+
+        let other = Redaction::Hash(RedactionKey::new(b"another secret"));
+        assert_ne!(other.apply(&json!("alice@example.com")), a);
+    }
+
+    #[test]
+    fn test_drop() {
+        assert_eq!(Redaction::Drop.apply(&json!({"a": 1})), json!(REDACTED));
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("mask".parse::<Redaction>(), Ok(Redaction::Mask));
+        assert_eq!("drop".parse::<Redaction>(), Ok(Redaction::Drop));
+        assert!("hash".parse::<Redaction>().is_err());
+        assert!("scramble".parse::<Redaction>().is_err());
+        assert_eq!(
+            Redaction::Hash(RedactionKey::new(b"secret")).to_string(),
+            "hash"
+        );
+    }
+}
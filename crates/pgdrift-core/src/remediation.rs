@@ -0,0 +1,318 @@
+use crate::drift::{BoolEncoding, DriftIssue};
+use crate::sql::{quote_ident, quote_literal};
+use crate::types::JsonType;
+use serde::Serialize;
+
+/// Configuration for remediation SQL
+#[derive(Debug, Clone)]
+pub struct RemediationConfig {
+    /// Rows updated (and committed) per batch (default: 1000)
+    pub batch_size: usize,
+}
+
+impl Default for RemediationConfig {
+    fn default() -> Self {
+        Self { batch_size: 1000 }
+    }
+}
+
+/// SQL rewriting the minority representations of a field to the dominant one
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Remediation {
+    pub path: String,
+    /// What the statement changes, e.g. `string → number`
+    pub description: String,
+    pub sql: String,
+}
+
+/// Remediation SQL for the issues it can fix automatically
+///
+/// Covers type inconsistencies with a lossless conversion (numeric strings
+/// to numbers, numbers and booleans to strings) and boolean encoding drift.
+/// Paths inside arrays or collapsed maps can't be targeted by `jsonb_set`
+/// and are skipped. Each statement updates rows in batches, guarded so it
+/// only touches values it can convert and can safely be run again.
+pub fn remediate(
+    schema: &str,
+    table: &str,
+    column: &str,
+    issues: &[DriftIssue],
+    config: &RemediationConfig,
+) -> Vec<Remediation> {
+    let target = Target {
+        table: format!("{}.{}", quote_ident(schema), quote_ident(table)),
+        column: quote_ident(column),
+        batch_size: config.batch_size,
+    };
+
+    let mut remediations = Vec::new();
+    for issue in issues {
+        let issue = match issue {
+            DriftIssue::Reclassified { issue, .. } => issue.as_ref(),
+            issue => issue,
+        };
+        if !is_addressable(issue.path()) {
+            continue;
+        }
+        match issue {
+            DriftIssue::TypeInconsistency { path, types, .. } => {
+                let Some(dominant) = types
+                    .values()
+                    .filter(|t| t.json_type != JsonType::Null)
+                    .max_by_key(|t| t.count)
+                else {
+                    continue;
+                };
+                for minority in types.values() {
+                    if let Some(fix) =
+                        type_conversion(&target, path, minority.json_type, dominant.json_type)
+                    {
+                        remediations.push(fix);
+                    }
+                }
+            }
+            DriftIssue::BooleanEncoding { path, dominant, .. } => {
+                remediations.push(boolean_normalization(&target, path, *dominant));
+            }
+            _ => {}
+        }
+    }
+
+    remediations.sort_by(|a, b| {
+        a.path
+            .cmp(&b.path)
+            .then_with(|| a.description.cmp(&b.description))
+    });
+    remediations
+}
+
+/// Migration file running the given remediations in order
+pub fn migration_file(remediations: &[Remediation]) -> String {
+    let mut contents = String::from(
+        "-- Generated by pgdrift: normalizes drifted values to their dominant representation.\n\
+         -- Every batch is committed on its own, so run this outside a transaction block\n\
+         -- (PostgreSQL 11+). Review before running and take a backup first.\n",
+    );
+    for remediation in remediations {
+        contents.push('\n');
+        contents.push_str(&remediation.sql);
+        contents.push('\n');
+    }
+    contents
+}
+
+/// Table and column the statements update, already quoted
+struct Target {
+    table: String,
+    column: String,
+    batch_size: usize,
+}
+
+impl Target {
+    /// Batched `UPDATE` setting `path` to `value` wherever `guard` holds
+    fn update(&self, comment: &str, path: &str, value: &str, guard: &str) -> String {
+        format!(
+            "-- {comment}\n\
+             DO $$\n\
+             DECLARE\n    updated integer;\n\
+             BEGIN\n    LOOP\n        \
+             UPDATE {table}\n        \
+             SET {column} = jsonb_set({column}, {path}, {value})\n        \
+             WHERE ctid IN (\n            \
+             SELECT ctid FROM {table}\n            \
+             WHERE {guard}\n            \
+             LIMIT {batch_size}\n        \
+             )\n        \
+             AND {guard};\n        \
+             GET DIAGNOSTICS updated = ROW_COUNT;\n        \
+             EXIT WHEN updated = 0;\n        \
+             COMMIT;\n    \
+             END LOOP;\n\
+             END $$;",
+            table = self.table,
+            column = self.column,
+            batch_size = self.batch_size,
+        )
+    }
+
+    /// `column #> path`, the value at `path` as jsonb
+    fn value(&self, path: &str) -> String {
+        format!("{} #> {}", self.column, path)
+    }
+
+    /// `column #>> path`, the value at `path` as text
+    fn text(&self, path: &str) -> String {
+        format!("{} #>> {}", self.column, path)
+    }
+}
+
+fn type_conversion(
+    target: &Target,
+    path: &str,
+    from: JsonType,
+    to: JsonType,
+) -> Option<Remediation> {
+    let literal = path_literal(path);
+    let typeof_guard = format!("jsonb_typeof({}) = '{}'", target.value(&literal), from);
+    let (value, guard) = match (from, to) {
+        (JsonType::String, JsonType::Number) => (
+            format!("to_jsonb(({})::numeric)", target.text(&literal)),
+            format!(
+                "{} AND {} ~ '^-?[0-9]+(\\.[0-9]+)?([eE][-+]?[0-9]+)?$'",
+                typeof_guard,
+                target.text(&literal)
+            ),
+        ),
+        (JsonType::Number | JsonType::Boolean, JsonType::String) => {
+            (format!("to_jsonb({})", target.text(&literal)), typeof_guard)
+        }
+        _ => return None,
+    };
+
+    let description = format!("{} → {}", from, to);
+    let comment = format!("{}: {}", path, description);
+    Some(Remediation {
+        path: path.to_string(),
+        sql: target.update(&comment, &literal, &value, &guard),
+        description,
+    })
+}
+
+fn boolean_normalization(target: &Target, path: &str, dominant: BoolEncoding) -> Remediation {
+    let literal = path_literal(path);
+    let (yes, no) = match dominant {
+        BoolEncoding::Boolean => ("true", "false"),
+        BoolEncoding::String => ("\"true\"", "\"false\""),
+        BoolEncoding::YesNo => ("\"yes\"", "\"no\""),
+        BoolEncoding::Integer => ("1", "0"),
+        BoolEncoding::DigitString => ("\"1\"", "\"0\""),
+    };
+    let text = format!("lower({})", target.text(&literal));
+    let value = format!(
+        "CASE WHEN {} IN ('true', 'yes', 'y', '1') THEN '{}'::jsonb ELSE '{}'::jsonb END",
+        text, yes, no
+    );
+    let guard = format!(
+        "jsonb_typeof({value}) IN ('boolean', 'number', 'string') \
+         AND {text} IN ('true', 'false', 'yes', 'no', 'y', 'n', '1', '0') \
+         AND {value} NOT IN ('{yes}'::jsonb, '{no}'::jsonb)",
+        value = target.value(&literal),
+    );
+
+    let description = format!("booleans → {}", dominant);
+    let comment = format!("{}: {}", path, description);
+    Remediation {
+        path: path.to_string(),
+        sql: target.update(&comment, &literal, &value, &guard),
+        description,
+    }
+}
+
+/// Whether `jsonb_set` can target the path: no array elements or collapsed map keys
+fn is_addressable(path: &str) -> bool {
+    !path.is_empty() && !path.contains("[]") && !path.split('.').any(|key| key == "*")
+}
+
+/// Path as a quoted Postgres text array, e.g. `'{customer,id}'`
+pub fn path_literal(path: &str) -> String {
+    let keys: Vec<String> = path
+        .split('.')
+        .map(|key| {
+            if !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                key.to_string()
+            } else {
+                format!("\"{}\"", key.replace('\\', "\\\\").replace('"', "\\\""))
+            }
+        })
+        .collect();
+    quote_literal(&format!("{{{}}}", keys.join(",")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drift::{Severity, TypeDistribution};
+    use std::collections::HashMap;
+
+    fn type_inconsistency(path: &str, types: &[(JsonType, u64)]) -> DriftIssue {
+        DriftIssue::TypeInconsistency {
+            path: path.to_string(),
+            types: types
+                .iter()
+                .map(|(json_type, count)| {
+                    (
+                        *json_type,
+                        TypeDistribution {
+                            json_type: *json_type,
+                            count: *count,
+                            percentage: 0.0,
+                        },
+                    )
+                })
+                .collect(),
+            minority_percentage: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_remediate() {
+        let issues = vec![
+            type_inconsistency(
+                "order.total",
+                &[(JsonType::Number, 90), (JsonType::String, 10)],
+            ),
+            type_inconsistency(
+                "tags[].id",
+                &[(JsonType::Number, 90), (JsonType::String, 10)],
+            ),
+            type_inconsistency("meta", &[(JsonType::Object, 90), (JsonType::Array, 10)]),
+            DriftIssue::BooleanEncoding {
+                path: "active".to_string(),
+                dominant: BoolEncoding::Boolean,
+                encodings: HashMap::new(),
+                minority_percentage: 10.0,
+            }
+            .reclassify(Severity::Critical),
+        ];
+
+        let remediations = remediate(
+            "public",
+            "orders",
+            "data",
+            &issues,
+            &RemediationConfig::default(),
+        );
+        let described: Vec<_> = remediations
+            .iter()
+            .map(|r| format!("{}: {}", r.path, r.description))
+            .collect();
+        assert_eq!(
+            described,
+            vec!["active: booleans → boolean", "order.total: string → number"]
+        );
+
+        let sql = &remediations[1].sql;
+        assert!(sql.contains(
+            "SET \"data\" = jsonb_set(\"data\", '{order,total}', to_jsonb((\"data\" #>> '{order,total}')::numeric))"
+        ));
+        assert!(sql.contains("jsonb_typeof(\"data\" #> '{order,total}') = 'string'"));
+        assert!(sql.contains("LIMIT 1000"));
+        assert!(
+            remediations[0]
+                .sql
+                .contains("THEN 'true'::jsonb ELSE 'false'::jsonb")
+        );
+
+        let file = migration_file(&remediations);
+        assert_eq!(file.matches("END $$;").count(), 2);
+    }
+
+    #[test]
+    fn test_path_literal() {
+        assert_eq!(path_literal("a.b_c"), "'{a,b_c}'");
+        assert_eq!(path_literal("it's.a,b"), "'{\"it''s\",\"a,b\"}'");
+        assert!(!is_addressable("items[].sku"));
+        assert!(!is_addressable("prefs.*.enabled"));
+        assert!(is_addressable("prefs.enabled"));
+    }
+}
@@ -0,0 +1,590 @@
+//! Report of `analyze`, for a whole column or per segment or group
+
+use super::ExpiringSuppression;
+use super::IssueCounts;
+use super::field::FieldStats;
+use super::issue::DriftIssue;
+use crate::{
+    analyzer, conformance, cooccurrence, denormalization, drift, remediation, stats, usage,
+};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Report of `analyze` for one column (or one segment or group of it)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AnalysisReport {
+    pub table: String,
+    pub column: String,
+    pub samples_analyzed: u64,
+    pub field_stats: Vec<FieldStats>,
+    pub drift_issues: Vec<DriftIssue>,
+    pub summary: AnalysisSummary,
+    /// Only present when the analysis hit a limit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub truncation: Option<Truncation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cooccurrence: Option<Cooccurrence>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conformance: Option<ConformanceReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remediations: Option<Vec<Remediation>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub denormalization: Option<Vec<Denormalization>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unused_fields: Option<Vec<UnusedField>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub expiring_suppressions: Vec<ExpiringSuppression>,
+    /// Example values of the paths with issues, only present when asked for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issue_examples: Option<BTreeMap<String, Vec<Value>>>,
+}
+
+/// Up to `limit` example values of each path with an issue, by path
+///
+/// Paths without examples are left out, e.g. after `Redaction::Drop` or when
+/// the issue is about a path that wasn't tracked.
+pub fn issue_examples<'a>(
+    field_stats: &'a [stats::FieldStats],
+    drift_issues: &[drift::DriftIssue],
+    limit: usize,
+) -> BTreeMap<&'a str, &'a [Value]> {
+    field_stats
+        .iter()
+        .filter(|fs| !fs.examples.is_empty())
+        .filter(|fs| drift_issues.iter().any(|issue| issue.path() == fs.path))
+        .map(|fs| {
+            (
+                fs.path.as_str(),
+                &fs.examples[..limit.min(fs.examples.len())],
+            )
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AnalysisSummary {
+    /// Drift health from 0 to 100, see `crate::score::health_score`
+    pub health_score: u8,
+    pub total_paths: usize,
+    pub max_depth: usize,
+    pub critical_issues: usize,
+    pub warning_issues: usize,
+    pub info_issues: usize,
+    /// Issues left out because their path is suppressed
+    pub suppressed_issues: usize,
+}
+
+impl AnalysisSummary {
+    pub fn new(
+        field_stats: &[stats::FieldStats],
+        drift_issues: &[drift::DriftIssue],
+        health_score: u8,
+        suppressed_issues: usize,
+    ) -> Self {
+        let counts = IssueCounts::from_issues(drift_issues);
+        Self {
+            health_score,
+            total_paths: field_stats.len(),
+            max_depth: field_stats.iter().map(|fs| fs.depth).max().unwrap_or(0),
+            critical_issues: counts.critical,
+            warning_issues: counts.warning,
+            info_issues: counts.info,
+            suppressed_issues,
+        }
+    }
+}
+
+/// What the analyzer left out because a guardrail was hit
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Truncation {
+    pub depth_limited: u64,
+    pub skipped_occurrences: u64,
+    /// A few of the paths that were not tracked
+    pub skipped_paths: Vec<String>,
+    pub max_paths_reached: bool,
+    pub memory_budget_exceeded: bool,
+}
+
+impl From<&analyzer::Truncation> for Truncation {
+    fn from(truncation: &analyzer::Truncation) -> Self {
+        Self {
+            depth_limited: truncation.depth_limited,
+            skipped_occurrences: truncation.skipped_occurrences,
+            skipped_paths: truncation.skipped_paths.clone(),
+            max_paths_reached: truncation.max_paths_reached,
+            memory_budget_exceeded: truncation.memory_budget_exceeded,
+        }
+    }
+}
+
+/// How the optional paths of a column relate to each other
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Cooccurrence {
+    /// Paths that always appear together
+    pub groups: Vec<PathGroup>,
+    /// Paths that never appear together
+    pub exclusive: Vec<PathGroup>,
+    pub implications: Vec<Implication>,
+}
+
+impl From<&cooccurrence::Cooccurrence> for Cooccurrence {
+    fn from(cooccurrence: &cooccurrence::Cooccurrence) -> Self {
+        let groups = |groups: &[cooccurrence::PathGroup]| {
+            groups
+                .iter()
+                .map(|g| PathGroup {
+                    paths: g.paths.clone(),
+                    occurrences: g.occurrences,
+                })
+                .collect()
+        };
+        Self {
+            groups: groups(&cooccurrence.groups),
+            exclusive: groups(&cooccurrence.exclusive),
+            implications: cooccurrence
+                .implications
+                .iter()
+                .map(|i| Implication {
+                    antecedent: i.antecedent.clone(),
+                    consequent: match &i.consequent {
+                        cooccurrence::Condition::Present { path } => {
+                            Condition::Present { path: path.clone() }
+                        }
+                        cooccurrence::Condition::Equals { path, value } => Condition::Equals {
+                            path: path.clone(),
+                            value: value.clone(),
+                        },
+                    },
+                    support: i.support,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PathGroup {
+    pub paths: Vec<String>,
+    /// Documents containing any of the paths
+    pub occurrences: u64,
+}
+
+/// `antecedent present ⇒ consequent`, held by every sampled document
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Implication {
+    pub antecedent: String,
+    pub consequent: Condition,
+    /// Documents containing the antecedent
+    pub support: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Condition {
+    Present { path: String },
+    Equals { path: String, value: Value },
+}
+
+/// How well the sampled documents conform to a JSON Schema
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConformanceReport {
+    pub documents: u64,
+    pub failing_documents: u64,
+    pub violations: Vec<KeywordViolation>,
+}
+
+impl From<&conformance::ConformanceReport> for ConformanceReport {
+    fn from(report: &conformance::ConformanceReport) -> Self {
+        Self {
+            documents: report.documents,
+            failing_documents: report.failing_documents,
+            violations: report
+                .violations
+                .iter()
+                .map(|v| KeywordViolation {
+                    path: v.path.clone(),
+                    keyword: v.keyword.clone(),
+                    documents: v.documents,
+                    percentage: v.percentage,
+                    example: v.example.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct KeywordViolation {
+    pub path: String,
+    pub keyword: String,
+    pub documents: u64,
+    pub percentage: f64,
+    pub example: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Remediation {
+    pub path: String,
+    pub description: String,
+    pub sql: String,
+}
+
+impl From<&remediation::Remediation> for Remediation {
+    fn from(remediation: &remediation::Remediation) -> Self {
+        Self {
+            path: remediation.path.clone(),
+            description: remediation.description.clone(),
+            sql: remediation.sql.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Denormalization {
+    pub path: String,
+    pub column: String,
+    pub same_name: bool,
+    pub compared: u64,
+    pub mismatches: u64,
+    pub mismatch_percentage: f64,
+}
+
+impl From<&denormalization::Denormalization> for Denormalization {
+    fn from(d: &denormalization::Denormalization) -> Self {
+        Self {
+            path: d.path.clone(),
+            column: d.column.clone(),
+            same_name: d.same_name,
+            compared: d.compared,
+            mismatches: d.mismatches,
+            mismatch_percentage: d.mismatch_percentage,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UnusedField {
+    pub path: String,
+    pub density: f64,
+    pub bytes_per_document: f64,
+    /// Large enough to be worth deprecating
+    pub candidate: bool,
+}
+
+impl From<&usage::UnusedField> for UnusedField {
+    fn from(field: &usage::UnusedField) -> Self {
+        Self {
+            path: field.path.clone(),
+            density: field.density,
+            bytes_per_document: field.bytes_per_document,
+            candidate: field.candidate,
+        }
+    }
+}
+
+/// Report of `analyze --segment-by`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SegmentsReport {
+    pub segment_by: String,
+    pub segments: Vec<SegmentReport>,
+}
+
+/// Analysis of the documents sharing one value of the segment field
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SegmentReport {
+    pub segment: String,
+    #[serde(flatten)]
+    pub analysis: AnalysisReport,
+}
+
+/// Report of `analyze --group-by`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GroupsReport {
+    pub group_by: String,
+    pub groups: Vec<GroupReport>,
+}
+
+/// Analysis of the rows sharing one value of the group column
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GroupReport {
+    pub group: String,
+    #[serde(flatten)]
+    pub analysis: AnalysisReport,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::Versioned;
+    use serde_json::json;
+
+    fn issues() -> Vec<drift::DriftIssue> {
+        vec![drift::DriftIssue::SparseField {
+            path: "email".to_string(),
+            density: 0.5,
+            occurrences: 2,
+            total_samples: 4,
+            margin: 0.1,
+        }]
+    }
+
+    fn field_stats() -> Vec<stats::FieldStats> {
+        let mut email = stats::FieldStats::new("email".to_string(), 1);
+        for value in ["a@example.com", "b@example.com", "c@example.com"] {
+            email.record(&json!(value));
+        }
+        let mut id = stats::FieldStats::new("id".to_string(), 1);
+        id.record(&json!(1));
+        vec![email, id]
+    }
+
+    /// Report without any of the optional sections
+    fn report(table: &str) -> AnalysisReport {
+        AnalysisReport {
+            table: table.to_string(),
+            column: "metadata".to_string(),
+            samples_analyzed: 4,
+            field_stats: vec![],
+            drift_issues: issues().iter().map(DriftIssue::from).collect(),
+            summary: AnalysisSummary::new(&[], &issues(), 90, 0),
+            truncation: None,
+            cooccurrence: None,
+            conformance: None,
+            remediations: None,
+            denormalization: None,
+            unused_fields: None,
+            expiring_suppressions: vec![],
+            issue_examples: None,
+        }
+    }
+
+    fn report_json(table: &str) -> Value {
+        json!({
+            "table": table,
+            "column": "metadata",
+            "samples_analyzed": 4,
+            "field_stats": [],
+            "drift_issues": [{
+                "SparseField": {
+                    "path": "email",
+                    "density": 0.5,
+                    "occurrences": 2,
+                    "total_samples": 4,
+                    "margin": 0.1
+                }
+            }],
+            "summary": {
+                "health_score": 90,
+                "total_paths": 0,
+                "max_depth": 0,
+                "critical_issues": 0,
+                "warning_issues": 0,
+                "info_issues": 1,
+                "suppressed_issues": 0
+            }
+        })
+    }
+
+    // Pins the fields of version 1: changing them breaks the tools reading the reports
+    #[test]
+    fn test_analysis_report_fields() {
+        let mut expected = report_json("users");
+        expected["schema_version"] = json!(1);
+        assert_eq!(
+            serde_json::to_value(Versioned::new(report("users"))).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_analysis_report_sections() {
+        let truncation = analyzer::Truncation {
+            depth_limited: 1,
+            skipped_occurrences: 3,
+            skipped_paths: vec!["a.b.c".to_string()],
+            max_paths_reached: false,
+            memory_budget_exceeded: true,
+        };
+        let cooccurrence = cooccurrence::Cooccurrence {
+            groups: vec![cooccurrence::PathGroup {
+                paths: vec!["card.last4".to_string(), "card.brand".to_string()],
+                occurrences: 40,
+            }],
+            exclusive: vec![],
+            implications: vec![
+                cooccurrence::Implication {
+                    antecedent: "card".to_string(),
+                    consequent: cooccurrence::Condition::Equals {
+                        path: "method".to_string(),
+                        value: json!("card"),
+                    },
+                    support: 40,
+                },
+                cooccurrence::Implication {
+                    antecedent: "card".to_string(),
+                    consequent: cooccurrence::Condition::Present {
+                        path: "billing".to_string(),
+                    },
+                    support: 40,
+                },
+            ],
+        };
+        let conformance = conformance::ConformanceReport {
+            documents: 4,
+            failing_documents: 1,
+            violations: vec![conformance::KeywordViolation {
+                path: "age".to_string(),
+                keyword: "type".to_string(),
+                documents: 1,
+                percentage: 25.0,
+                example: "is not of type \"integer\"".to_string(),
+            }],
+        };
+        let remediation = remediation::Remediation {
+            path: "age".to_string(),
+            description: "string → number".to_string(),
+            sql: "UPDATE users SET metadata = ...".to_string(),
+        };
+        let denormalization = denormalization::Denormalization {
+            path: "customer.email".to_string(),
+            column: "email".to_string(),
+            same_name: true,
+            compared: 4,
+            mismatches: 1,
+            mismatch_percentage: 25.0,
+        };
+        let unused = usage::UnusedField {
+            path: "legacy".to_string(),
+            density: 0.9,
+            bytes_per_document: 120.0,
+            candidate: true,
+        };
+        let field_stats = field_stats();
+        let examples = issue_examples(&field_stats, &issues(), 2)
+            .into_iter()
+            .map(|(path, examples)| (path.to_string(), examples.to_vec()))
+            .collect();
+        let report = AnalysisReport {
+            truncation: Some((&truncation).into()),
+            cooccurrence: Some((&cooccurrence).into()),
+            conformance: Some((&conformance).into()),
+            remediations: Some(vec![(&remediation).into()]),
+            denormalization: Some(vec![(&denormalization).into()]),
+            unused_fields: Some(vec![(&unused).into()]),
+            expiring_suppressions: vec![ExpiringSuppression {
+                path: "legacy.*".to_string(),
+                until: Some("2026-11-01".to_string()),
+                reason: None,
+            }],
+            issue_examples: Some(examples),
+            ..report("users")
+        };
+        let value = serde_json::to_value(&report).unwrap();
+
+        let mut expected = report_json("users");
+        let sections = json!({
+            "truncation": {
+                "depth_limited": 1,
+                "skipped_occurrences": 3,
+                "skipped_paths": ["a.b.c"],
+                "max_paths_reached": false,
+                "memory_budget_exceeded": true
+            },
+            "cooccurrence": {
+                "groups": [{"paths": ["card.last4", "card.brand"], "occurrences": 40}],
+                "exclusive": [],
+                "implications": [
+                    {
+                        "antecedent": "card",
+                        "consequent": {"kind": "equals", "path": "method", "value": "card"},
+                        "support": 40
+                    },
+                    {
+                        "antecedent": "card",
+                        "consequent": {"kind": "present", "path": "billing"},
+                        "support": 40
+                    }
+                ]
+            },
+            "conformance": {
+                "documents": 4,
+                "failing_documents": 1,
+                "violations": [{
+                    "path": "age",
+                    "keyword": "type",
+                    "documents": 1,
+                    "percentage": 25.0,
+                    "example": "is not of type \"integer\""
+                }]
+            },
+            "remediations": [{
+                "path": "age",
+                "description": "string → number",
+                "sql": "UPDATE users SET metadata = ..."
+            }],
+            "denormalization": [{
+                "path": "customer.email",
+                "column": "email",
+                "same_name": true,
+                "compared": 4,
+                "mismatches": 1,
+                "mismatch_percentage": 25.0
+            }],
+            "unused_fields": [{
+                "path": "legacy",
+                "density": 0.9,
+                "bytes_per_document": 120.0,
+                "candidate": true
+            }],
+            "expiring_suppressions": [{"path": "legacy.*", "until": "2026-11-01"}],
+            "issue_examples": {"email": ["a@example.com", "b@example.com"]}
+        });
+        for (key, section) in sections.as_object().unwrap() {
+            expected[key] = section.clone();
+        }
+        assert_eq!(value, expected);
+    }
+
+    #[test]
+    fn test_segments_and_groups_report_fields() {
+        let segments = SegmentsReport {
+            segment_by: "type".to_string(),
+            segments: vec![SegmentReport {
+                segment: "click".to_string(),
+                analysis: report("events"),
+            }],
+        };
+        let mut segment = report_json("events");
+        segment["segment"] = json!("click");
+        assert_eq!(
+            serde_json::to_value(Versioned::new(segments)).unwrap(),
+            json!({"schema_version": 1, "segment_by": "type", "segments": [segment]})
+        );
+
+        let groups = GroupsReport {
+            group_by: "tenant_id".to_string(),
+            groups: vec![GroupReport {
+                group: "42".to_string(),
+                analysis: report("events"),
+            }],
+        };
+        let mut group = report_json("events");
+        group["group"] = json!("42");
+        assert_eq!(
+            serde_json::to_value(Versioned::new(groups)).unwrap(),
+            json!({"schema_version": 1, "group_by": "tenant_id", "groups": [group]})
+        );
+    }
+
+    #[test]
+    fn test_issue_examples() {
+        let field_stats = field_stats();
+        let examples = issue_examples(&field_stats, &issues(), 2);
+        assert_eq!(examples.len(), 1);
+        assert_eq!(examples["email"].len(), 2);
+
+        // More than there are
+        assert_eq!(issue_examples(&field_stats, &issues(), 5)["email"].len(), 3);
+    }
+}
@@ -0,0 +1,121 @@
+//! Report of `analyze --cohort-split`
+
+use super::field::JsonType;
+use crate::cohort;
+use serde::Serialize;
+
+/// Rows older and newer than the split point, compared
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CohortReport {
+    pub table: String,
+    pub column: String,
+    /// Timestamp column the rows were split on
+    pub cohort_column: String,
+    /// How long before now the split lies, as a Postgres interval
+    pub split: String,
+    pub old_samples: u64,
+    pub new_samples: u64,
+    pub shifts: Vec<CohortShift>,
+}
+
+/// A path whose shape differs between older and newer documents
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum CohortShift {
+    Density {
+        path: String,
+        old_percentage: f64,
+        new_percentage: f64,
+    },
+    TypeShare {
+        path: String,
+        json_type: JsonType,
+        old_percentage: f64,
+        new_percentage: f64,
+    },
+}
+
+impl From<&cohort::CohortShift> for CohortShift {
+    fn from(shift: &cohort::CohortShift) -> Self {
+        match shift.clone() {
+            cohort::CohortShift::Density {
+                path,
+                old_percentage,
+                new_percentage,
+            } => Self::Density {
+                path,
+                old_percentage,
+                new_percentage,
+            },
+            cohort::CohortShift::TypeShare {
+                path,
+                json_type,
+                old_percentage,
+                new_percentage,
+            } => Self::TypeShare {
+                path,
+                json_type: json_type.into(),
+                old_percentage,
+                new_percentage,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::Versioned;
+    use crate::types;
+    use serde_json::json;
+
+    // Pins the fields of version 1
+    #[test]
+    fn test_cohort_report_fields() {
+        let shifts = [
+            cohort::CohortShift::Density {
+                path: "discount".to_string(),
+                old_percentage: 10.0,
+                new_percentage: 45.0,
+            },
+            cohort::CohortShift::TypeShare {
+                path: "amount".to_string(),
+                json_type: types::JsonType::String,
+                old_percentage: 0.0,
+                new_percentage: 20.0,
+            },
+        ];
+        let report = CohortReport {
+            table: "orders".to_string(),
+            column: "data".to_string(),
+            cohort_column: "created_at".to_string(),
+            split: "30 days".to_string(),
+            old_samples: 500,
+            new_samples: 400,
+            shifts: shifts.iter().map(CohortShift::from).collect(),
+        };
+
+        assert_eq!(
+            serde_json::to_value(Versioned::new(report)).unwrap(),
+            json!({
+                "schema_version": 1,
+                "table": "orders",
+                "column": "data",
+                "cohort_column": "created_at",
+                "split": "30 days",
+                "old_samples": 500,
+                "new_samples": 400,
+                "shifts": [
+                    {"Density": {"path": "discount", "old_percentage": 10.0, "new_percentage": 45.0}},
+                    {
+                        "TypeShare": {
+                            "path": "amount",
+                            "json_type": "String",
+                            "old_percentage": 0.0,
+                            "new_percentage": 20.0
+                        }
+                    }
+                ]
+            })
+        );
+    }
+}
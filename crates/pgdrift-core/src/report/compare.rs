@@ -0,0 +1,170 @@
+//! Report of `compare`
+
+use super::field::JsonType;
+use crate::compare;
+use serde::Serialize;
+
+/// The same column analyzed in two databases
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CompareReport {
+    pub table: String,
+    pub column: String,
+    pub left: CompareSide,
+    pub right: CompareSide,
+    pub differences: Vec<PathDifference>,
+}
+
+/// One of the two databases compared
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CompareSide {
+    pub label: String,
+    pub samples_analyzed: u64,
+}
+
+/// How a path differs between the left and the right column
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum PathDifference {
+    OnlyLeft {
+        path: String,
+        density: f64,
+    },
+    OnlyRight {
+        path: String,
+        density: f64,
+    },
+    Density {
+        path: String,
+        left_density: f64,
+        right_density: f64,
+    },
+    /// Most common type first on each side
+    Types {
+        path: String,
+        left: Vec<TypeShare>,
+        right: Vec<TypeShare>,
+    },
+}
+
+impl From<&compare::PathDifference> for PathDifference {
+    fn from(difference: &compare::PathDifference) -> Self {
+        let shares = |shares: Vec<compare::TypeShare>| {
+            shares
+                .into_iter()
+                .map(|s| TypeShare {
+                    json_type: s.json_type.into(),
+                    percentage: s.percentage,
+                })
+                .collect()
+        };
+        match difference.clone() {
+            compare::PathDifference::OnlyLeft { path, density } => Self::OnlyLeft { path, density },
+            compare::PathDifference::OnlyRight { path, density } => {
+                Self::OnlyRight { path, density }
+            }
+            compare::PathDifference::Density {
+                path,
+                left_density,
+                right_density,
+            } => Self::Density {
+                path,
+                left_density,
+                right_density,
+            },
+            compare::PathDifference::Types { path, left, right } => Self::Types {
+                path,
+                left: shares(left),
+                right: shares(right),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TypeShare {
+    pub json_type: JsonType,
+    pub percentage: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::Versioned;
+    use crate::types;
+    use serde_json::json;
+
+    // Pins the fields of version 1
+    #[test]
+    fn test_compare_report_fields() {
+        let differences = [
+            compare::PathDifference::OnlyLeft {
+                path: "legacy_id".to_string(),
+                density: 0.5,
+            },
+            compare::PathDifference::OnlyRight {
+                path: "tenant".to_string(),
+                density: 1.0,
+            },
+            compare::PathDifference::Density {
+                path: "email".to_string(),
+                left_density: 0.99,
+                right_density: 0.6,
+            },
+            compare::PathDifference::Types {
+                path: "age".to_string(),
+                left: vec![compare::TypeShare {
+                    json_type: types::JsonType::Number,
+                    percentage: 100.0,
+                }],
+                right: vec![
+                    compare::TypeShare {
+                        json_type: types::JsonType::Number,
+                        percentage: 70.0,
+                    },
+                    compare::TypeShare {
+                        json_type: types::JsonType::String,
+                        percentage: 30.0,
+                    },
+                ],
+            },
+        ];
+        let report = CompareReport {
+            table: "users".to_string(),
+            column: "metadata".to_string(),
+            left: CompareSide {
+                label: "staging".to_string(),
+                samples_analyzed: 100,
+            },
+            right: CompareSide {
+                label: "production".to_string(),
+                samples_analyzed: 200,
+            },
+            differences: differences.iter().map(PathDifference::from).collect(),
+        };
+
+        assert_eq!(
+            serde_json::to_value(Versioned::new(report)).unwrap(),
+            json!({
+                "schema_version": 1,
+                "table": "users",
+                "column": "metadata",
+                "left": {"label": "staging", "samples_analyzed": 100},
+                "right": {"label": "production", "samples_analyzed": 200},
+                "differences": [
+                    {"OnlyLeft": {"path": "legacy_id", "density": 0.5}},
+                    {"OnlyRight": {"path": "tenant", "density": 1.0}},
+                    {"Density": {"path": "email", "left_density": 0.99, "right_density": 0.6}},
+                    {
+                        "Types": {
+                            "path": "age",
+                            "left": [{"json_type": "Number", "percentage": 100.0}],
+                            "right": [
+                                {"json_type": "Number", "percentage": 70.0},
+                                {"json_type": "String", "percentage": 30.0}
+                            ]
+                        }
+                    }
+                ]
+            })
+        );
+    }
+}
@@ -0,0 +1,103 @@
+//! Report of `diff`
+
+use crate::drift::Severity;
+use serde::Serialize;
+
+/// A new run of a column compared against its baseline
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DiffReport {
+    pub schema: String,
+    pub table: String,
+    pub column: String,
+    pub baseline_samples: u64,
+    pub samples_analyzed: u64,
+    pub baseline_health_score: u8,
+    pub health_score: u8,
+    /// Issues the baseline didn't have
+    pub new_issues: Vec<BaselineIssue>,
+    /// Issues whose severity went up
+    pub worsened_issues: Vec<WorsenedIssue>,
+    /// Baseline issues no longer reported
+    pub resolved_issues: Vec<BaselineIssue>,
+    /// Whether the run has new or worsened issues
+    pub regression: bool,
+}
+
+/// A drift issue, reduced to what it is matched against the baseline by
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BaselineIssue {
+    /// Kind of issue (`SparseField`, `TypeInconsistency`, ...)
+    pub kind: String,
+    pub path: String,
+    pub severity: Severity,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WorsenedIssue {
+    pub issue: BaselineIssue,
+    pub previous_severity: Severity,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::Versioned;
+    use serde_json::json;
+
+    // Pins the fields of version 1
+    #[test]
+    fn test_diff_report_fields() {
+        let issue = |kind: &str, severity| BaselineIssue {
+            kind: kind.to_string(),
+            path: "email".to_string(),
+            severity,
+            description: "email: present in 60.0% of documents".to_string(),
+        };
+        let report = DiffReport {
+            schema: "public".to_string(),
+            table: "users".to_string(),
+            column: "metadata".to_string(),
+            baseline_samples: 100,
+            samples_analyzed: 120,
+            baseline_health_score: 90,
+            health_score: 75,
+            new_issues: vec![],
+            worsened_issues: vec![WorsenedIssue {
+                issue: issue("MissingKey", Severity::Critical),
+                previous_severity: Severity::Warning,
+            }],
+            resolved_issues: vec![issue("SparseField", Severity::Info)],
+            regression: true,
+        };
+
+        let issue = |kind: &str, severity: &str| {
+            json!({
+                "kind": kind,
+                "path": "email",
+                "severity": severity,
+                "description": "email: present in 60.0% of documents"
+            })
+        };
+        assert_eq!(
+            serde_json::to_value(Versioned::new(report)).unwrap(),
+            json!({
+                "schema_version": 1,
+                "schema": "public",
+                "table": "users",
+                "column": "metadata",
+                "baseline_samples": 100,
+                "samples_analyzed": 120,
+                "baseline_health_score": 90,
+                "health_score": 75,
+                "new_issues": [],
+                "worsened_issues": [{
+                    "issue": issue("MissingKey", "Critical"),
+                    "previous_severity": "Warning"
+                }],
+                "resolved_issues": [issue("SparseField", "Info")],
+                "regression": true
+            })
+        );
+    }
+}
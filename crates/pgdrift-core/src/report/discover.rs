@@ -0,0 +1,161 @@
+//! Report of `discover`
+
+use serde::Serialize;
+
+/// JSON columns found in the database
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DiscoverReport {
+    pub columns: Vec<JsonbColumn>,
+    pub count: usize,
+    /// Columns no index covers
+    pub unindexed: usize,
+    /// Columns whose table statistics are stale
+    pub stale_stats: usize,
+}
+
+/// A JSON column and the table it lives in
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JsonbColumn {
+    pub schema: String,
+    pub table: String,
+    pub column: String,
+    pub data_type: ColumnType,
+    pub relation_kind: RelationKind,
+    /// For partitioned tables, estimated rows are summed over all partitions
+    pub estimated_rows: Option<i64>,
+    /// Number of leaf partitions rolled up into a partitioned table
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub partitions: Option<i64>,
+    /// Parent table (`schema.table`) when the column belongs to a partition
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub partition_of: Option<String>,
+    pub dead_tuples: Option<i64>,
+    /// Last manual ANALYZE (UTC, ISO 8601)
+    pub last_analyze: Option<String>,
+    /// Last ANALYZE run by autovacuum (UTC, ISO 8601)
+    pub last_autoanalyze: Option<String>,
+    pub stats_age_secs: Option<i64>,
+    /// Indexes that already cover the column
+    pub indexes: Vec<ColumnIndex>,
+    /// Only present with `--storage`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub storage: Option<ColumnStorage>,
+}
+
+/// Postgres type of a JSON column
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColumnType {
+    Jsonb,
+    Json,
+    Hstore,
+    JsonbArray,
+    JsonArray,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RelationKind {
+    Table,
+    View,
+    MaterializedView,
+}
+
+/// An existing index that covers a JSON column
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ColumnIndex {
+    pub name: String,
+    pub method: String,
+    /// Whether the column is used inside an expression rather than indexed directly
+    pub expression: bool,
+    pub definition: String,
+}
+
+/// Storage footprint of a JSON column, estimated from a sample of documents
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ColumnStorage {
+    pub avg_bytes: f64,
+    pub estimated_total_bytes: Option<i64>,
+    pub toast_bytes: i64,
+    pub toasted_fraction: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::Versioned;
+    use serde_json::json;
+
+    // Pins the fields of version 1
+    #[test]
+    fn test_discover_report_fields() {
+        let column = JsonbColumn {
+            schema: "public".to_string(),
+            table: "events".to_string(),
+            column: "payload".to_string(),
+            data_type: ColumnType::Jsonb,
+            relation_kind: RelationKind::Table,
+            estimated_rows: Some(5000),
+            partitions: Some(4),
+            partition_of: None,
+            dead_tuples: Some(12),
+            last_analyze: None,
+            last_autoanalyze: Some("2024-01-15T10:30:00Z".to_string()),
+            stats_age_secs: Some(3600),
+            indexes: vec![ColumnIndex {
+                name: "events_payload_idx".to_string(),
+                method: "gin".to_string(),
+                expression: false,
+                definition: "CREATE INDEX events_payload_idx ON public.events USING gin (payload)"
+                    .to_string(),
+            }],
+            storage: Some(ColumnStorage {
+                avg_bytes: 512.0,
+                estimated_total_bytes: Some(2560000),
+                toast_bytes: 8192,
+                toasted_fraction: 0.25,
+            }),
+        };
+        let report = DiscoverReport {
+            columns: vec![column],
+            count: 1,
+            unindexed: 0,
+            stale_stats: 0,
+        };
+
+        assert_eq!(
+            serde_json::to_value(Versioned::new(report)).unwrap(),
+            json!({
+                "schema_version": 1,
+                "columns": [{
+                    "schema": "public",
+                    "table": "events",
+                    "column": "payload",
+                    "data_type": "jsonb",
+                    "relation_kind": "table",
+                    "estimated_rows": 5000,
+                    "partitions": 4,
+                    "dead_tuples": 12,
+                    "last_analyze": null,
+                    "last_autoanalyze": "2024-01-15T10:30:00Z",
+                    "stats_age_secs": 3600,
+                    "indexes": [{
+                        "name": "events_payload_idx",
+                        "method": "gin",
+                        "expression": false,
+                        "definition": "CREATE INDEX events_payload_idx ON public.events USING gin (payload)"
+                    }],
+                    "storage": {
+                        "avg_bytes": 512.0,
+                        "estimated_total_bytes": 2560000,
+                        "toast_bytes": 8192,
+                        "toasted_fraction": 0.25
+                    }
+                }],
+                "count": 1,
+                "unindexed": 0,
+                "stale_stats": 0
+            })
+        );
+    }
+}
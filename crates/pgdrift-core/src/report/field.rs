@@ -0,0 +1,369 @@
+//! Statistics of one path, as reported in `field_stats`
+
+use crate::{format, stats, types};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+/// JSON type of a value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum JsonType {
+    Null,
+    Boolean,
+    Number,
+    String,
+    Array,
+    Object,
+}
+
+impl From<types::JsonType> for JsonType {
+    fn from(json_type: types::JsonType) -> Self {
+        match json_type {
+            types::JsonType::Null => Self::Null,
+            types::JsonType::Boolean => Self::Boolean,
+            types::JsonType::Number => Self::Number,
+            types::JsonType::String => Self::String,
+            types::JsonType::Array => Self::Array,
+            types::JsonType::Object => Self::Object,
+        }
+    }
+}
+
+/// Inferred format of a string value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StringFormat {
+    Uuid,
+    Email,
+    Url,
+    Date,
+    DateTime,
+    SlashDate,
+    Numeric,
+    Boolean,
+    Empty,
+    Text,
+}
+
+impl From<format::StringFormat> for StringFormat {
+    fn from(format: format::StringFormat) -> Self {
+        match format {
+            format::StringFormat::Uuid => Self::Uuid,
+            format::StringFormat::Email => Self::Email,
+            format::StringFormat::Url => Self::Url,
+            format::StringFormat::Date => Self::Date,
+            format::StringFormat::DateTime => Self::DateTime,
+            format::StringFormat::SlashDate => Self::SlashDate,
+            format::StringFormat::Numeric => Self::Numeric,
+            format::StringFormat::Boolean => Self::Boolean,
+            format::StringFormat::Empty => Self::Empty,
+            format::StringFormat::Text => Self::Text,
+        }
+    }
+}
+
+/// Counts keyed by the report form of their key, in key order
+pub(super) fn counts<K, R>(counts: &HashMap<K, u64>) -> BTreeMap<R, u64>
+where
+    K: Copy + Into<R>,
+    R: Ord,
+{
+    counts
+        .iter()
+        .map(|(&key, &count)| (key.into(), count))
+        .collect()
+}
+
+/// Shape and values of one path
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldStats {
+    pub path: String,
+    pub occurrences: u64,
+    pub total_samples: u64,
+    pub density: f64,
+    pub density_interval: DensityInterval,
+    pub null_count: u64,
+    pub types: BTreeMap<JsonType, u64>,
+    pub examples: Vec<Value>,
+    pub depth: usize,
+    /// Approximate number of distinct non-null scalar values
+    pub cardinality: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub numeric: Option<NumericStats>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub formats: BTreeMap<StringFormat, u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<FormatConsistency>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strings: Option<StringStats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub array: Option<ArrayStats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_values: Option<Vec<ValueCount>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub constant_value: Option<Value>,
+    /// Number of distinct map keys folded into this wildcard (`*`) path
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collapsed_keys: Option<u64>,
+}
+
+impl From<&stats::FieldStats> for FieldStats {
+    fn from(fs: &stats::FieldStats) -> Self {
+        Self {
+            path: fs.path.clone(),
+            occurrences: fs.occurrences,
+            total_samples: fs.total_samples,
+            density: fs.density,
+            density_interval: DensityInterval {
+                lower: fs.density_interval.lower,
+                upper: fs.density_interval.upper,
+            },
+            null_count: fs.null_count,
+            types: counts(&fs.types),
+            examples: fs.examples.clone(),
+            depth: fs.depth,
+            cardinality: fs.cardinality,
+            numeric: fs.numeric.as_ref().map(NumericStats::from),
+            formats: counts(&fs.formats),
+            format: fs.format.as_ref().map(|f| FormatConsistency {
+                format: f.format.into(),
+                consistency: f.consistency,
+            }),
+            strings: fs.strings.as_ref().map(|s| StringStats {
+                count: s.count,
+                total_bytes: s.total_bytes,
+                avg_bytes: s.avg_bytes,
+                max_bytes: s.max_bytes,
+                blob_count: s.blob_count,
+            }),
+            array: fs.array.as_ref().map(|a| ArrayStats {
+                count: a.count,
+                min_length: a.min_length,
+                max_length: a.max_length,
+                avg_length: a.avg_length,
+                p99_length: a.p99_length,
+                empty_ratio: a.empty_ratio,
+            }),
+            top_values: fs
+                .top_values
+                .as_ref()
+                .map(|values| values.iter().map(ValueCount::from).collect()),
+            constant_value: fs.constant_value.clone(),
+            collapsed_keys: fs.collapsed_keys,
+        }
+    }
+}
+
+/// Range the true density of a path lies in, at some confidence level
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct DensityInterval {
+    pub lower: f64,
+    pub upper: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NumericStats {
+    pub count: u64,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bands: Option<MagnitudeBands>,
+}
+
+impl From<&stats::NumericStats> for NumericStats {
+    fn from(numeric: &stats::NumericStats) -> Self {
+        Self {
+            count: numeric.count,
+            min: numeric.min,
+            max: numeric.max,
+            mean: numeric.mean,
+            p50: numeric.p50,
+            p90: numeric.p90,
+            p99: numeric.p99,
+            bands: numeric.bands.as_ref().map(|b| MagnitudeBands {
+                low_median: b.low_median,
+                high_median: b.high_median,
+                high_share: b.high_share,
+            }),
+        }
+    }
+}
+
+/// Positive values falling into two separate orders of magnitude
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MagnitudeBands {
+    pub low_median: f64,
+    pub high_median: f64,
+    /// Share of positive values in the upper band (0.0 - 1.0)
+    pub high_share: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FormatConsistency {
+    pub format: StringFormat,
+    /// Percentage of string values with this format
+    pub consistency: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StringStats {
+    pub count: u64,
+    pub total_bytes: u64,
+    pub avg_bytes: f64,
+    pub max_bytes: u64,
+    pub blob_count: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ArrayStats {
+    pub count: u64,
+    pub min_length: u64,
+    pub max_length: u64,
+    pub avg_length: f64,
+    pub p99_length: u64,
+    /// Share of arrays with no elements (0.0 - 1.0)
+    pub empty_ratio: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ValueCount {
+    pub value: Value,
+    pub count: u64,
+    /// Percentage of non-null occurrences with this value
+    pub percentage: f64,
+}
+
+impl From<&stats::ValueCount> for ValueCount {
+    fn from(value: &stats::ValueCount) -> Self {
+        Self {
+            value: value.value.clone(),
+            count: value.count,
+            percentage: value.percentage,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    // Pins the statistics of a path in version 1 of the reports
+    #[test]
+    fn test_field_stats_fields() {
+        let mut price = stats::FieldStats::new("price".to_string(), 1);
+        for value in [json!(12.5), json!(1250), json!("19.99"), json!(null)] {
+            price.record(&value);
+        }
+        price.finalize(5);
+        let mut tags = stats::FieldStats::new("tags".to_string(), 1);
+        for value in [json!([]), json!(["new", "sale"])] {
+            tags.record(&value);
+        }
+        tags.finalize(5);
+
+        let fields = [FieldStats::from(&price), FieldStats::from(&tags)];
+        let expected = json!([
+            {
+                "path": "price",
+                "occurrences": 4,
+                "total_samples": 5,
+                "density": 0.8,
+                "density_interval": {
+                    "lower": 0.37553462948323696,
+                    "upper": 0.9637758914091483
+                },
+                "null_count": 1,
+                "types": {
+                    "Null": 1,
+                    "Number": 2,
+                    "String": 1
+                },
+                "examples": [
+                    12.5,
+                    1250,
+                    "19.99",
+                    null
+                ],
+                "depth": 1,
+                "cardinality": 3,
+                "numeric": {
+                    "count": 2,
+                    "min": 12.5,
+                    "max": 1250.0,
+                    "mean": 631.25,
+                    "p50": 12.5,
+                    "p90": 1250.0,
+                    "p99": 1250.0
+                },
+                "formats": {
+                    "numeric": 1
+                },
+                "format": {
+                    "format": "numeric",
+                    "consistency": 100.0
+                },
+                "strings": {
+                    "count": 1,
+                    "total_bytes": 5,
+                    "avg_bytes": 5.0,
+                    "max_bytes": 5,
+                    "blob_count": 0
+                },
+                "top_values": [
+                    {
+                        "value": "19.99",
+                        "count": 1,
+                        "percentage": 33.33333333333333
+                    },
+                    {
+                        "value": 12.5,
+                        "count": 1,
+                        "percentage": 33.33333333333333
+                    },
+                    {
+                        "value": 1250,
+                        "count": 1,
+                        "percentage": 33.33333333333333
+                    }
+                ]
+            },
+            {
+                "path": "tags",
+                "occurrences": 2,
+                "total_samples": 5,
+                "density": 0.4,
+                "density_interval": {
+                    "lower": 0.11762077412107164,
+                    "upper": 0.7692757189147998
+                },
+                "null_count": 0,
+                "types": {
+                    "Array": 2
+                },
+                "examples": [
+                    [],
+                    [
+                        "new",
+                        "sale"
+                    ]
+                ],
+                "depth": 1,
+                "cardinality": 0,
+                "array": {
+                    "count": 2,
+                    "min_length": 0,
+                    "max_length": 2,
+                    "avg_length": 1.0,
+                    "p99_length": 2,
+                    "empty_ratio": 0.5
+                }
+            }
+        ]);
+        assert_eq!(serde_json::to_value(fields).unwrap(), expected);
+    }
+}
@@ -0,0 +1,618 @@
+//! Reports of `index` and `index-all`
+
+use crate::{index, workload};
+use serde::Serialize;
+
+/// Index recommendations for one column
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IndexReport {
+    pub schema: String,
+    pub table: String,
+    pub column: String,
+    /// Rows in the table, which the size estimates are based on
+    pub row_count: i64,
+    /// Recommendations no existing index serves
+    pub recommendations: Vec<IndexRecommendation>,
+    /// Recommendations an existing index already serves
+    pub covered: Vec<CoveredRecommendation>,
+    /// Existing indexes made redundant by another existing index
+    pub redundant_indexes: Vec<IndexOverlap>,
+    /// Existing indexes on the column that are unused or redundant
+    pub drop_recommendations: Vec<DropRecommendation>,
+    /// When the statistics the scan counts start from were last reset
+    pub stats_reset: Option<String>,
+    /// How recorded statements query the column, busiest first
+    pub query_patterns: Vec<ColumnAccess>,
+    pub summary: IndexSummary,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IndexSummary {
+    pub total_recommendations: usize,
+    /// Estimated size of all recommended indexes together
+    pub estimated_size_bytes: i64,
+    pub checked_by_planner: usize,
+    pub used_by_planner: usize,
+    pub covered: usize,
+    pub redundant_indexes: usize,
+    pub drop_recommendations: usize,
+    pub high_priority: usize,
+    pub medium_priority: usize,
+    pub low_priority: usize,
+}
+
+impl IndexSummary {
+    /// Totals of `recommendations`, with the counts of the other sections
+    pub fn new(
+        recommendations: &[IndexRecommendation],
+        covered: usize,
+        redundant_indexes: usize,
+        drop_recommendations: usize,
+    ) -> Self {
+        let checks: Vec<&PlannerCheck> = recommendations
+            .iter()
+            .filter_map(|r| r.planner_check.as_ref())
+            .collect();
+        let priority = |priority: IndexPriority| {
+            recommendations
+                .iter()
+                .filter(|r| r.priority == priority)
+                .count()
+        };
+        Self {
+            total_recommendations: recommendations.len(),
+            estimated_size_bytes: recommendations
+                .iter()
+                .filter_map(|r| r.estimated_size_bytes)
+                .sum(),
+            checked_by_planner: checks.len(),
+            used_by_planner: checks.iter().filter(|c| c.used).count(),
+            covered,
+            redundant_indexes,
+            drop_recommendations,
+            high_priority: priority(IndexPriority::High),
+            medium_priority: priority(IndexPriority::Medium),
+            low_priority: priority(IndexPriority::Low),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IndexRecommendation {
+    pub field_path: String,
+    pub index_type: IndexType,
+    pub priority: IndexPriority,
+    pub reason: String,
+    pub sql: String,
+    pub estimated_benefit: String,
+    /// Approximate on-disk size, `None` if it couldn't be estimated
+    pub estimated_size_bytes: Option<i64>,
+    /// Only present when the planner was asked
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub planner_check: Option<PlannerCheck>,
+    /// Only present when the workload was read
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workload: Option<QueryUsage>,
+}
+
+impl From<&index::IndexRecommendation> for IndexRecommendation {
+    fn from(rec: &index::IndexRecommendation) -> Self {
+        Self {
+            field_path: rec.field_path.clone(),
+            index_type: (&rec.index_type).into(),
+            priority: match rec.priority {
+                index::IndexPriority::High => IndexPriority::High,
+                index::IndexPriority::Medium => IndexPriority::Medium,
+                index::IndexPriority::Low => IndexPriority::Low,
+            },
+            reason: rec.reason.clone(),
+            sql: rec.sql.clone(),
+            estimated_benefit: rec.estimated_benefit.clone(),
+            estimated_size_bytes: rec.estimated_size_bytes,
+            planner_check: rec.planner_check.as_ref().map(|c| PlannerCheck {
+                query: c.query.clone(),
+                used: c.used,
+                cost_without: c.cost_without,
+                cost_with: c.cost_with,
+                time_without_ms: c.time_without_ms,
+                time_with_ms: c.time_with_ms,
+            }),
+            workload: rec.workload.as_ref().map(QueryUsage::from),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum IndexType {
+    Gin,
+    Partial,
+    BTreeExtracted,
+    PartialBTree,
+    HashExtracted,
+    TrigramExtracted,
+    BrinExtracted,
+    GeneratedColumn,
+    CompositeBTree,
+}
+
+impl From<&index::IndexType> for IndexType {
+    fn from(index_type: &index::IndexType) -> Self {
+        match index_type {
+            index::IndexType::Gin => Self::Gin,
+            index::IndexType::Partial => Self::Partial,
+            index::IndexType::BTreeExtracted => Self::BTreeExtracted,
+            index::IndexType::PartialBTree => Self::PartialBTree,
+            index::IndexType::HashExtracted => Self::HashExtracted,
+            index::IndexType::TrigramExtracted => Self::TrigramExtracted,
+            index::IndexType::BrinExtracted => Self::BrinExtracted,
+            index::IndexType::GeneratedColumn => Self::GeneratedColumn,
+            index::IndexType::CompositeBTree => Self::CompositeBTree,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum IndexPriority {
+    High,
+    Medium,
+    Low,
+}
+
+/// The planner's use of a recommended index, checked with a hypothetical copy of it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PlannerCheck {
+    pub query: String,
+    pub used: bool,
+    pub cost_without: f64,
+    pub cost_with: f64,
+    /// Only present when the query was run with EXPLAIN ANALYZE
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_without_ms: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_with_ms: Option<f64>,
+}
+
+/// Statements, calls and time spent in queries of one kind
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QueryUsage {
+    pub statements: usize,
+    pub calls: u64,
+    /// Mean execution time in milliseconds, weighted by calls
+    pub mean_exec_time_ms: f64,
+}
+
+impl From<&workload::QueryUsage> for QueryUsage {
+    fn from(usage: &workload::QueryUsage) -> Self {
+        Self {
+            statements: usage.statements,
+            calls: usage.calls,
+            mean_exec_time_ms: usage.mean_exec_time_ms,
+        }
+    }
+}
+
+/// A recommendation left out because an existing index already serves it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CoveredRecommendation {
+    pub field_path: String,
+    pub index_type: IndexType,
+    /// Name of the existing index
+    pub covered_by: String,
+}
+
+impl From<&index::CoveredRecommendation> for CoveredRecommendation {
+    fn from(covered: &index::CoveredRecommendation) -> Self {
+        Self {
+            field_path: covered.field_path.clone(),
+            index_type: (&covered.index_type).into(),
+            covered_by: covered.covered_by.clone(),
+        }
+    }
+}
+
+/// An existing index that another existing index makes redundant
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IndexOverlap {
+    pub index: String,
+    pub overlaps: String,
+    pub kind: IndexOverlapKind,
+    pub reason: String,
+    pub definition: String,
+}
+
+impl From<&index::IndexOverlap> for IndexOverlap {
+    fn from(overlap: &index::IndexOverlap) -> Self {
+        Self {
+            index: overlap.index.clone(),
+            overlaps: overlap.overlaps.clone(),
+            kind: match overlap.kind {
+                index::IndexOverlapKind::Duplicate => IndexOverlapKind::Duplicate,
+                index::IndexOverlapKind::Prefix => IndexOverlapKind::Prefix,
+                index::IndexOverlapKind::Overlapping => IndexOverlapKind::Overlapping,
+            },
+            reason: overlap.reason.clone(),
+            definition: overlap.definition.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum IndexOverlapKind {
+    Duplicate,
+    Prefix,
+    Overlapping,
+}
+
+/// An existing index worth dropping
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DropRecommendation {
+    pub index: String,
+    /// Scans since the statistics were last reset
+    pub scans: i64,
+    pub size_bytes: i64,
+    pub reason: String,
+    pub sql: String,
+}
+
+impl From<&index::DropRecommendation> for DropRecommendation {
+    fn from(drop: &index::DropRecommendation) -> Self {
+        Self {
+            index: drop.index.clone(),
+            scans: drop.scans,
+            size_bytes: drop.size_bytes,
+            reason: drop.reason.clone(),
+            sql: drop.sql.clone(),
+        }
+    }
+}
+
+/// How often the recorded statements apply an operator to a path of the column
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ColumnAccess {
+    /// `None` when the keys are query parameters
+    pub path: Option<String>,
+    pub operator: JsonOperator,
+    #[serde(flatten)]
+    pub usage: QueryUsage,
+}
+
+impl From<&workload::ColumnAccess> for ColumnAccess {
+    fn from(access: &workload::ColumnAccess) -> Self {
+        Self {
+            path: access.path.clone(),
+            operator: match access.operator {
+                workload::JsonOperator::Extract => JsonOperator::Extract,
+                workload::JsonOperator::Equality => JsonOperator::Equality,
+                workload::JsonOperator::Pattern => JsonOperator::Pattern,
+                workload::JsonOperator::Contains => JsonOperator::Contains,
+                workload::JsonOperator::Exists => JsonOperator::Exists,
+            },
+            usage: (&access.usage).into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum JsonOperator {
+    Extract,
+    Equality,
+    Pattern,
+    Contains,
+    Exists,
+}
+
+/// Index recommendations across all JSON columns of a database
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IndexAllReport {
+    pub total_columns: usize,
+    pub summary: IndexAllSummary,
+    /// Most urgent first
+    pub recommendations: Vec<ColumnRecommendation>,
+    pub drop_recommendations: Vec<ColumnDrop>,
+    pub columns: Vec<IndexedColumn>,
+    pub failed_columns: Vec<ColumnFailure>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IndexAllSummary {
+    pub total_recommendations: usize,
+    pub estimated_size_bytes: i64,
+    pub high_priority: usize,
+    pub medium_priority: usize,
+    pub low_priority: usize,
+    pub drop_recommendations: usize,
+    pub failed_columns: usize,
+}
+
+impl IndexAllSummary {
+    /// Totals of the columns' summaries
+    pub fn new(summaries: &[IndexSummary], failed_columns: usize) -> Self {
+        let sum = |count: fn(&IndexSummary) -> usize| summaries.iter().map(count).sum();
+        Self {
+            total_recommendations: sum(|s| s.total_recommendations),
+            estimated_size_bytes: summaries.iter().map(|s| s.estimated_size_bytes).sum(),
+            high_priority: sum(|s| s.high_priority),
+            medium_priority: sum(|s| s.medium_priority),
+            low_priority: sum(|s| s.low_priority),
+            drop_recommendations: sum(|s| s.drop_recommendations),
+            failed_columns,
+        }
+    }
+}
+
+/// A recommendation and the column it is for
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ColumnRecommendation {
+    pub schema: String,
+    pub table: String,
+    pub column: String,
+    pub recommendation: IndexRecommendation,
+}
+
+/// A drop recommendation and the column the index is on
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ColumnDrop {
+    pub schema: String,
+    pub table: String,
+    pub column: String,
+    pub drop: DropRecommendation,
+}
+
+/// Counts of one column's recommendations
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IndexedColumn {
+    pub schema: String,
+    pub table: String,
+    pub column: String,
+    pub row_count: i64,
+    pub recommendations: usize,
+    pub covered: usize,
+    pub redundant_indexes: usize,
+}
+
+/// A column `index-all` couldn't recommend indexes for
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ColumnFailure {
+    pub schema: String,
+    pub table: String,
+    pub column: String,
+    pub error: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::Versioned;
+    use serde_json::{Value, json};
+
+    fn recommendation() -> index::IndexRecommendation {
+        index::IndexRecommendation {
+            field_path: "email".to_string(),
+            index_type: index::IndexType::BTreeExtracted,
+            priority: index::IndexPriority::High,
+            reason: "present in every document".to_string(),
+            sql: "CREATE INDEX idx_users_email ON users ((metadata->>'email'))".to_string(),
+            estimated_benefit: "Faster lookups by email".to_string(),
+            estimated_size_bytes: Some(65536),
+            planner_check: Some(index::PlannerCheck {
+                query: "SELECT 1 FROM users WHERE metadata->>'email' = 'x'".to_string(),
+                used: true,
+                cost_without: 120.0,
+                cost_with: 8.5,
+                time_without_ms: None,
+                time_with_ms: None,
+            }),
+            workload: Some(workload::QueryUsage {
+                statements: 2,
+                calls: 40,
+                mean_exec_time_ms: 1.5,
+            }),
+        }
+    }
+
+    fn drop() -> index::DropRecommendation {
+        index::DropRecommendation {
+            index: "idx_users_old".to_string(),
+            scans: 0,
+            size_bytes: 8192,
+            reason: "never scanned".to_string(),
+            sql: "DROP INDEX CONCURRENTLY idx_users_old;".to_string(),
+        }
+    }
+
+    fn index_report() -> IndexReport {
+        let recommendations = vec![IndexRecommendation::from(&recommendation())];
+        let summary = IndexSummary::new(&recommendations, 1, 1, 1);
+        IndexReport {
+            schema: "public".to_string(),
+            table: "users".to_string(),
+            column: "metadata".to_string(),
+            row_count: 1000,
+            recommendations,
+            covered: vec![CoveredRecommendation::from(&index::CoveredRecommendation {
+                field_path: "tags".to_string(),
+                index_type: index::IndexType::Gin,
+                covered_by: "idx_users_metadata".to_string(),
+            })],
+            redundant_indexes: vec![IndexOverlap::from(&index::IndexOverlap {
+                index: "idx_a".to_string(),
+                overlaps: "idx_ab".to_string(),
+                kind: index::IndexOverlapKind::Prefix,
+                reason: "leading keys of idx_ab".to_string(),
+                definition: "CREATE INDEX idx_a ON users (a)".to_string(),
+            })],
+            drop_recommendations: vec![DropRecommendation::from(&drop())],
+            stats_reset: None,
+            query_patterns: vec![ColumnAccess::from(&workload::ColumnAccess {
+                path: Some("email".to_string()),
+                operator: workload::JsonOperator::Equality,
+                usage: workload::QueryUsage {
+                    statements: 2,
+                    calls: 40,
+                    mean_exec_time_ms: 1.5,
+                },
+            })],
+            summary,
+        }
+    }
+
+    fn recommendation_json() -> Value {
+        json!({
+            "field_path": "email",
+            "index_type": "BTreeExtracted",
+            "priority": "High",
+            "reason": "present in every document",
+            "sql": "CREATE INDEX idx_users_email ON users ((metadata->>'email'))",
+            "estimated_benefit": "Faster lookups by email",
+            "estimated_size_bytes": 65536,
+            "planner_check": {
+                "query": "SELECT 1 FROM users WHERE metadata->>'email' = 'x'",
+                "used": true,
+                "cost_without": 120.0,
+                "cost_with": 8.5
+            },
+            "workload": {"statements": 2, "calls": 40, "mean_exec_time_ms": 1.5}
+        })
+    }
+
+    fn drop_json() -> Value {
+        json!({
+            "index": "idx_users_old",
+            "scans": 0,
+            "size_bytes": 8192,
+            "reason": "never scanned",
+            "sql": "DROP INDEX CONCURRENTLY idx_users_old;"
+        })
+    }
+
+    // Pins the fields of version 1
+    #[test]
+    fn test_index_report_fields() {
+        let report = index_report();
+
+        assert_eq!(
+            serde_json::to_value(Versioned::new(report)).unwrap(),
+            json!({
+                "schema_version": 1,
+                "schema": "public",
+                "table": "users",
+                "column": "metadata",
+                "row_count": 1000,
+                "recommendations": [recommendation_json()],
+                "covered": [{
+                    "field_path": "tags",
+                    "index_type": "Gin",
+                    "covered_by": "idx_users_metadata"
+                }],
+                "redundant_indexes": [{
+                    "index": "idx_a",
+                    "overlaps": "idx_ab",
+                    "kind": "Prefix",
+                    "reason": "leading keys of idx_ab",
+                    "definition": "CREATE INDEX idx_a ON users (a)"
+                }],
+                "drop_recommendations": [drop_json()],
+                "stats_reset": null,
+                "query_patterns": [{
+                    "path": "email",
+                    "operator": "Equality",
+                    "statements": 2,
+                    "calls": 40,
+                    "mean_exec_time_ms": 1.5
+                }],
+                "summary": {
+                    "total_recommendations": 1,
+                    "estimated_size_bytes": 65536,
+                    "checked_by_planner": 1,
+                    "used_by_planner": 1,
+                    "covered": 1,
+                    "redundant_indexes": 1,
+                    "drop_recommendations": 1,
+                    "high_priority": 1,
+                    "medium_priority": 0,
+                    "low_priority": 0
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_index_all_report_fields() {
+        let column = index_report();
+        let report = IndexAllReport {
+            total_columns: 2,
+            summary: IndexAllSummary::new(std::slice::from_ref(&column.summary), 1),
+            recommendations: vec![ColumnRecommendation {
+                schema: "public".to_string(),
+                table: "users".to_string(),
+                column: "metadata".to_string(),
+                recommendation: column.recommendations[0].clone(),
+            }],
+            drop_recommendations: vec![ColumnDrop {
+                schema: "public".to_string(),
+                table: "users".to_string(),
+                column: "metadata".to_string(),
+                drop: column.drop_recommendations[0].clone(),
+            }],
+            columns: vec![IndexedColumn {
+                schema: "public".to_string(),
+                table: "users".to_string(),
+                column: "metadata".to_string(),
+                row_count: 1000,
+                recommendations: 1,
+                covered: 1,
+                redundant_indexes: 1,
+            }],
+            failed_columns: vec![ColumnFailure {
+                schema: "public".to_string(),
+                table: "events".to_string(),
+                column: "payload".to_string(),
+                error: "permission denied for table events".to_string(),
+            }],
+        };
+
+        assert_eq!(
+            serde_json::to_value(Versioned::new(report)).unwrap(),
+            json!({
+                "schema_version": 1,
+                "total_columns": 2,
+                "summary": {
+                    "total_recommendations": 1,
+                    "estimated_size_bytes": 65536,
+                    "high_priority": 1,
+                    "medium_priority": 0,
+                    "low_priority": 0,
+                    "drop_recommendations": 1,
+                    "failed_columns": 1
+                },
+                "recommendations": [{
+                    "schema": "public",
+                    "table": "users",
+                    "column": "metadata",
+                    "recommendation": recommendation_json()
+                }],
+                "drop_recommendations": [{
+                    "schema": "public",
+                    "table": "users",
+                    "column": "metadata",
+                    "drop": drop_json()
+                }],
+                "columns": [{
+                    "schema": "public",
+                    "table": "users",
+                    "column": "metadata",
+                    "row_count": 1000,
+                    "recommendations": 1,
+                    "covered": 1,
+                    "redundant_indexes": 1
+                }],
+                "failed_columns": [{
+                    "schema": "public",
+                    "table": "events",
+                    "column": "payload",
+                    "error": "permission denied for table events"
+                }]
+            })
+        );
+    }
+}
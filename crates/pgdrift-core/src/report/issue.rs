@@ -0,0 +1,995 @@
+//! Drift issues, as reported in `drift_issues`
+
+use super::field::{JsonType, StringFormat, counts};
+use crate::drift::{self, Severity};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// A drift issue, tagged with its kind (`{"SparseField": {...}}`)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum DriftIssue {
+    TypeInconsistency {
+        path: String,
+        types: BTreeMap<JsonType, TypeDistribution>,
+        minority_percentage: f64,
+    },
+    GhostKey {
+        path: String,
+        density: f64,
+        /// Misspelled in version 1 of the reports
+        #[serde(rename = "occurunces")]
+        occurrences: u64,
+        total_samples: u64,
+        margin: f64,
+    },
+    SparseField {
+        path: String,
+        density: f64,
+        occurrences: u64,
+        total_samples: u64,
+        margin: f64,
+    },
+    MissingKey {
+        path: String,
+        density: f64,
+        expected_occurrences: u64,
+        actual_occurrences: u64,
+        margin: f64,
+    },
+    SchemaEvolution {
+        path: String,
+        pattern: EvolutionPattern,
+    },
+    MixedFormat {
+        path: String,
+        dominant: StringFormat,
+        formats: BTreeMap<StringFormat, u64>,
+        minority_percentage: f64,
+    },
+    ConstantField {
+        path: String,
+        value: Value,
+        occurrences: u64,
+    },
+    NumericString {
+        path: String,
+        numeric_count: u64,
+        string_count: u64,
+        numeric_percentage: f64,
+    },
+    BooleanEncoding {
+        path: String,
+        dominant: BoolEncoding,
+        encodings: BTreeMap<BoolEncoding, u64>,
+        minority_percentage: f64,
+    },
+    DateFormatDrift {
+        path: String,
+        formats: BTreeMap<DateFormat, u64>,
+        minority_percentage: f64,
+        suggested: DateFormat,
+    },
+    EmptyString {
+        path: String,
+        empty_count: u64,
+        null_count: u64,
+        missing_count: u64,
+        total_samples: u64,
+    },
+    NullVsMissing {
+        path: String,
+        null_count: u64,
+        missing_count: u64,
+        minority_percentage: f64,
+    },
+    KeyNamingConflict {
+        path: String,
+        variants: Vec<KeyVariant>,
+    },
+    ProbableRename {
+        path: String,
+        old_path: String,
+        new_path: String,
+        old_density: f64,
+        new_density: f64,
+        confirmed: bool,
+    },
+    TupleArray {
+        path: String,
+        positions: Vec<BTreeMap<JsonType, u64>>,
+        arrays: u64,
+    },
+    ExcessiveDepth {
+        path: String,
+        depth: usize,
+        deepest: usize,
+        limit: usize,
+    },
+    OversizedPayload {
+        path: String,
+        avg_bytes: f64,
+        max_bytes: u64,
+        blob_count: u64,
+        string_count: u64,
+        bytes_per_document: f64,
+    },
+    ProblematicKey {
+        path: String,
+        key: String,
+        problems: Vec<KeyProblem>,
+        occurrences: u64,
+    },
+    UnitDrift {
+        path: String,
+        low_median: f64,
+        high_median: f64,
+        high_percentage: f64,
+        factor: u32,
+    },
+    ConditionalField {
+        path: String,
+        condition_path: String,
+        values: Vec<Value>,
+        density_when: f64,
+        density_otherwise: f64,
+        documents_when: u64,
+    },
+    /// Another issue whose severity was overridden by the user
+    Reclassified {
+        issue: Box<DriftIssue>,
+        severity: Severity,
+    },
+}
+
+impl From<&drift::DriftIssue> for DriftIssue {
+    fn from(issue: &drift::DriftIssue) -> Self {
+        use drift::DriftIssue as Issue;
+        match issue.clone() {
+            Issue::TypeInconsistency {
+                path,
+                types,
+                minority_percentage,
+            } => Self::TypeInconsistency {
+                path,
+                types: types
+                    .into_iter()
+                    .map(|(json_type, d)| {
+                        let distribution = TypeDistribution {
+                            json_type: d.json_type.into(),
+                            count: d.count,
+                            percentage: d.percentage,
+                        };
+                        (json_type.into(), distribution)
+                    })
+                    .collect(),
+                minority_percentage,
+            },
+            Issue::GhostKey {
+                path,
+                density,
+                occurunces,
+                total_samples,
+                margin,
+            } => Self::GhostKey {
+                path,
+                density,
+                occurrences: occurunces,
+                total_samples,
+                margin,
+            },
+            Issue::SparseField {
+                path,
+                density,
+                occurrences,
+                total_samples,
+                margin,
+            } => Self::SparseField {
+                path,
+                density,
+                occurrences,
+                total_samples,
+                margin,
+            },
+            Issue::MissingKey {
+                path,
+                density,
+                expected_occurrences,
+                actual_occurrences,
+                margin,
+            } => Self::MissingKey {
+                path,
+                density,
+                expected_occurrences,
+                actual_occurrences,
+                margin,
+            },
+            Issue::SchemaEvolution { path, pattern } => Self::SchemaEvolution {
+                path,
+                pattern: pattern.into(),
+            },
+            Issue::MixedFormat {
+                path,
+                dominant,
+                formats,
+                minority_percentage,
+            } => Self::MixedFormat {
+                path,
+                dominant: dominant.into(),
+                formats: counts(&formats),
+                minority_percentage,
+            },
+            Issue::ConstantField {
+                path,
+                value,
+                occurrences,
+            } => Self::ConstantField {
+                path,
+                value,
+                occurrences,
+            },
+            Issue::NumericString {
+                path,
+                numeric_count,
+                string_count,
+                numeric_percentage,
+            } => Self::NumericString {
+                path,
+                numeric_count,
+                string_count,
+                numeric_percentage,
+            },
+            Issue::BooleanEncoding {
+                path,
+                dominant,
+                encodings,
+                minority_percentage,
+            } => Self::BooleanEncoding {
+                path,
+                dominant: dominant.into(),
+                encodings: counts(&encodings),
+                minority_percentage,
+            },
+            Issue::DateFormatDrift {
+                path,
+                formats,
+                minority_percentage,
+                suggested,
+            } => Self::DateFormatDrift {
+                path,
+                formats: counts(&formats),
+                minority_percentage,
+                suggested: suggested.into(),
+            },
+            Issue::EmptyString {
+                path,
+                empty_count,
+                null_count,
+                missing_count,
+                total_samples,
+            } => Self::EmptyString {
+                path,
+                empty_count,
+                null_count,
+                missing_count,
+                total_samples,
+            },
+            Issue::NullVsMissing {
+                path,
+                null_count,
+                missing_count,
+                minority_percentage,
+            } => Self::NullVsMissing {
+                path,
+                null_count,
+                missing_count,
+                minority_percentage,
+            },
+            Issue::KeyNamingConflict { path, variants } => Self::KeyNamingConflict {
+                path,
+                variants: variants
+                    .into_iter()
+                    .map(|v| KeyVariant {
+                        path: v.path,
+                        density: v.density,
+                    })
+                    .collect(),
+            },
+            Issue::ProbableRename {
+                path,
+                old_path,
+                new_path,
+                old_density,
+                new_density,
+                confirmed,
+            } => Self::ProbableRename {
+                path,
+                old_path,
+                new_path,
+                old_density,
+                new_density,
+                confirmed,
+            },
+            Issue::TupleArray {
+                path,
+                positions,
+                arrays,
+            } => Self::TupleArray {
+                path,
+                positions: positions.iter().map(counts).collect(),
+                arrays,
+            },
+            Issue::ExcessiveDepth {
+                path,
+                depth,
+                deepest,
+                limit,
+            } => Self::ExcessiveDepth {
+                path,
+                depth,
+                deepest,
+                limit,
+            },
+            Issue::OversizedPayload {
+                path,
+                avg_bytes,
+                max_bytes,
+                blob_count,
+                string_count,
+                bytes_per_document,
+            } => Self::OversizedPayload {
+                path,
+                avg_bytes,
+                max_bytes,
+                blob_count,
+                string_count,
+                bytes_per_document,
+            },
+            Issue::ProblematicKey {
+                path,
+                key,
+                problems,
+                occurrences,
+            } => Self::ProblematicKey {
+                path,
+                key,
+                problems: problems.into_iter().map(KeyProblem::from).collect(),
+                occurrences,
+            },
+            Issue::UnitDrift {
+                path,
+                low_median,
+                high_median,
+                high_percentage,
+                factor,
+            } => Self::UnitDrift {
+                path,
+                low_median,
+                high_median,
+                high_percentage,
+                factor,
+            },
+            Issue::ConditionalField {
+                path,
+                condition_path,
+                values,
+                density_when,
+                density_otherwise,
+                documents_when,
+            } => Self::ConditionalField {
+                path,
+                condition_path,
+                values,
+                density_when,
+                density_otherwise,
+                documents_when,
+            },
+            Issue::Reclassified { issue, severity } => Self::Reclassified {
+                issue: Box::new(Self::from(issue.as_ref())),
+                severity,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TypeDistribution {
+    pub json_type: JsonType,
+    pub count: u64,
+    pub percentage: f64,
+}
+
+/// Way a boolean value is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BoolEncoding {
+    Boolean,
+    String,
+    YesNo,
+    Integer,
+    DigitString,
+}
+
+impl From<drift::BoolEncoding> for BoolEncoding {
+    fn from(encoding: drift::BoolEncoding) -> Self {
+        match encoding {
+            drift::BoolEncoding::Boolean => Self::Boolean,
+            drift::BoolEncoding::String => Self::String,
+            drift::BoolEncoding::YesNo => Self::YesNo,
+            drift::BoolEncoding::Integer => Self::Integer,
+            drift::BoolEncoding::DigitString => Self::DigitString,
+        }
+    }
+}
+
+/// Way a date or timestamp is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DateFormat {
+    IsoDate,
+    IsoDateTime,
+    SlashDate,
+    Epoch,
+}
+
+impl From<drift::DateFormat> for DateFormat {
+    fn from(format: drift::DateFormat) -> Self {
+        match format {
+            drift::DateFormat::IsoDate => Self::IsoDate,
+            drift::DateFormat::IsoDateTime => Self::IsoDateTime,
+            drift::DateFormat::SlashDate => Self::SlashDate,
+            drift::DateFormat::Epoch => Self::Epoch,
+        }
+    }
+}
+
+/// What makes a key hard to use in `#>` / `#>>` paths
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyProblem {
+    Empty,
+    SurroundingWhitespace,
+    Whitespace,
+    ControlCharacter,
+    NeedsQuoting,
+}
+
+impl From<drift::KeyProblem> for KeyProblem {
+    fn from(problem: drift::KeyProblem) -> Self {
+        match problem {
+            drift::KeyProblem::Empty => Self::Empty,
+            drift::KeyProblem::SurroundingWhitespace => Self::SurroundingWhitespace,
+            drift::KeyProblem::Whitespace => Self::Whitespace,
+            drift::KeyProblem::ControlCharacter => Self::ControlCharacter,
+            drift::KeyProblem::NeedsQuoting => Self::NeedsQuoting,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct KeyVariant {
+    pub path: String,
+    pub density: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum EvolutionPattern {
+    VersionMarker {
+        marker_path: String,
+        versions: Vec<VersionShare>,
+        differences: Vec<VersionDifference>,
+    },
+    DeprecatedNaming {
+        old_path: String,
+        new_path: String,
+    },
+    MutuallyExclusive {
+        paths: Vec<String>,
+    },
+}
+
+impl From<drift::EvolutionPattern> for EvolutionPattern {
+    fn from(pattern: drift::EvolutionPattern) -> Self {
+        match pattern {
+            drift::EvolutionPattern::VersionMarker {
+                marker_path,
+                versions,
+                differences,
+            } => Self::VersionMarker {
+                marker_path,
+                versions: versions
+                    .into_iter()
+                    .map(|v| VersionShare {
+                        value: v.value,
+                        documents: v.documents,
+                        percentage: v.percentage,
+                    })
+                    .collect(),
+                differences: differences
+                    .into_iter()
+                    .map(|d| VersionDifference {
+                        path: d.path,
+                        densities: d.densities,
+                    })
+                    .collect(),
+            },
+            drift::EvolutionPattern::DeprecatedNaming { old_path, new_path } => {
+                Self::DeprecatedNaming { old_path, new_path }
+            }
+            drift::EvolutionPattern::MutuallyExclusive { paths } => {
+                Self::MutuallyExclusive { paths }
+            }
+        }
+    }
+}
+
+/// Documents carrying one value of a version marker
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VersionShare {
+    pub value: Value,
+    pub documents: u64,
+    pub percentage: f64,
+}
+
+/// A field whose presence differs between versions
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VersionDifference {
+    pub path: String,
+    /// Density within each version, in the order of the marker's `versions`
+    pub densities: Vec<f64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drift::{DriftIssue as Issue, TypeDistribution as Distribution};
+    use crate::format::StringFormat as Format;
+    use crate::types::JsonType as Type;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    fn all_issues() -> Vec<Issue> {
+        let issues = vec![
+            Issue::TypeInconsistency {
+                path: "age".to_string(),
+                types: HashMap::from([
+                    (
+                        Type::Number,
+                        Distribution {
+                            json_type: Type::Number,
+                            count: 90,
+                            percentage: 90.0,
+                        },
+                    ),
+                    (
+                        Type::String,
+                        Distribution {
+                            json_type: Type::String,
+                            count: 10,
+                            percentage: 10.0,
+                        },
+                    ),
+                ]),
+                minority_percentage: 10.0,
+            },
+            Issue::GhostKey {
+                path: "debug".to_string(),
+                density: 0.02,
+                occurunces: 2,
+                total_samples: 100,
+                margin: 0.01,
+            },
+            Issue::SparseField {
+                path: "nickname".to_string(),
+                density: 0.4,
+                occurrences: 40,
+                total_samples: 100,
+                margin: 0.05,
+            },
+            Issue::MissingKey {
+                path: "email".to_string(),
+                density: 0.85,
+                expected_occurrences: 100,
+                actual_occurrences: 85,
+                margin: 0.03,
+            },
+            Issue::SchemaEvolution {
+                path: "version".to_string(),
+                pattern: drift::EvolutionPattern::VersionMarker {
+                    marker_path: "version".to_string(),
+                    versions: vec![drift::VersionShare {
+                        value: json!(2),
+                        documents: 60,
+                        percentage: 60.0,
+                    }],
+                    differences: vec![drift::VersionDifference {
+                        path: "address".to_string(),
+                        densities: vec![1.0, 0.0],
+                    }],
+                },
+            },
+            Issue::SchemaEvolution {
+                path: "user_name".to_string(),
+                pattern: drift::EvolutionPattern::DeprecatedNaming {
+                    old_path: "user_name".to_string(),
+                    new_path: "username".to_string(),
+                },
+            },
+            Issue::SchemaEvolution {
+                path: "phone".to_string(),
+                pattern: drift::EvolutionPattern::MutuallyExclusive {
+                    paths: vec!["phone".to_string(), "mobile".to_string()],
+                },
+            },
+            Issue::MixedFormat {
+                path: "id".to_string(),
+                dominant: Format::Uuid,
+                formats: HashMap::from([(Format::Uuid, 95), (Format::Text, 5)]),
+                minority_percentage: 5.0,
+            },
+            Issue::ConstantField {
+                path: "source".to_string(),
+                value: json!("web"),
+                occurrences: 100,
+            },
+            Issue::NumericString {
+                path: "price".to_string(),
+                numeric_count: 98,
+                string_count: 100,
+                numeric_percentage: 98.0,
+            },
+            Issue::BooleanEncoding {
+                path: "active".to_string(),
+                dominant: drift::BoolEncoding::Boolean,
+                encodings: HashMap::from([
+                    (drift::BoolEncoding::Boolean, 90),
+                    (drift::BoolEncoding::YesNo, 10),
+                ]),
+                minority_percentage: 10.0,
+            },
+            Issue::DateFormatDrift {
+                path: "created".to_string(),
+                formats: HashMap::from([
+                    (drift::DateFormat::IsoDate, 80),
+                    (drift::DateFormat::SlashDate, 20),
+                ]),
+                minority_percentage: 20.0,
+                suggested: drift::DateFormat::IsoDate,
+            },
+            Issue::EmptyString {
+                path: "name".to_string(),
+                empty_count: 12,
+                null_count: 3,
+                missing_count: 0,
+                total_samples: 100,
+            },
+            Issue::NullVsMissing {
+                path: "middle_name".to_string(),
+                null_count: 30,
+                missing_count: 20,
+                minority_percentage: 40.0,
+            },
+            Issue::KeyNamingConflict {
+                path: "userId".to_string(),
+                variants: vec![drift::KeyVariant {
+                    path: "userId".to_string(),
+                    density: 0.7,
+                }],
+            },
+            Issue::ProbableRename {
+                path: "zip".to_string(),
+                old_path: "zip".to_string(),
+                new_path: "zip_code".to_string(),
+                old_density: 0.3,
+                new_density: 0.7,
+                confirmed: true,
+            },
+            Issue::TupleArray {
+                path: "point".to_string(),
+                positions: vec![HashMap::from([(Type::Number, 10)])],
+                arrays: 10,
+            },
+            Issue::ExcessiveDepth {
+                path: "a".to_string(),
+                depth: 1,
+                deepest: 12,
+                limit: 8,
+            },
+            Issue::OversizedPayload {
+                path: "avatar".to_string(),
+                avg_bytes: 2048.0,
+                max_bytes: 4096,
+                blob_count: 10,
+                string_count: 10,
+                bytes_per_document: 2048.0,
+            },
+            Issue::ProblematicKey {
+                path: "a b".to_string(),
+                key: "a b".to_string(),
+                problems: vec![drift::KeyProblem::Whitespace],
+                occurrences: 5,
+            },
+            Issue::UnitDrift {
+                path: "amount".to_string(),
+                low_median: 12.5,
+                high_median: 1250.0,
+                high_percentage: 30.0,
+                factor: 100,
+            },
+            Issue::ConditionalField {
+                path: "card".to_string(),
+                condition_path: "method".to_string(),
+                values: vec![json!("card")],
+                density_when: 1.0,
+                density_otherwise: 0.0,
+                documents_when: 40,
+            },
+        ];
+        let reclassified = issues[2].clone().reclassify(Severity::Warning);
+        issues.into_iter().chain([reclassified]).collect()
+    }
+
+    // Pins the shape of every kind of issue in version 1 of the reports
+    #[test]
+    fn test_issue_fields() {
+        let issues: Vec<DriftIssue> = all_issues().iter().map(DriftIssue::from).collect();
+        let expected = json!([
+            {
+                "TypeInconsistency": {
+                    "path": "age",
+                    "types": {
+                        "Number": {
+                            "json_type": "Number",
+                            "count": 90,
+                            "percentage": 90.0
+                        },
+                        "String": {
+                            "json_type": "String",
+                            "count": 10,
+                            "percentage": 10.0
+                        }
+                    },
+                    "minority_percentage": 10.0
+                }
+            },
+            {
+                "GhostKey": {
+                    "path": "debug",
+                    "density": 0.02,
+                    "occurunces": 2,
+                    "total_samples": 100,
+                    "margin": 0.01
+                }
+            },
+            {
+                "SparseField": {
+                    "path": "nickname",
+                    "density": 0.4,
+                    "occurrences": 40,
+                    "total_samples": 100,
+                    "margin": 0.05
+                }
+            },
+            {
+                "MissingKey": {
+                    "path": "email",
+                    "density": 0.85,
+                    "expected_occurrences": 100,
+                    "actual_occurrences": 85,
+                    "margin": 0.03
+                }
+            },
+            {
+                "SchemaEvolution": {
+                    "path": "version",
+                    "pattern": {
+                        "VersionMarker": {
+                            "marker_path": "version",
+                            "versions": [
+                                {
+                                    "value": 2,
+                                    "documents": 60,
+                                    "percentage": 60.0
+                                }
+                            ],
+                            "differences": [
+                                {
+                                    "path": "address",
+                                    "densities": [
+                                        1.0,
+                                        0.0
+                                    ]
+                                }
+                            ]
+                        }
+                    }
+                }
+            },
+            {
+                "SchemaEvolution": {
+                    "path": "user_name",
+                    "pattern": {
+                        "DeprecatedNaming": {
+                            "old_path": "user_name",
+                            "new_path": "username"
+                        }
+                    }
+                }
+            },
+            {
+                "SchemaEvolution": {
+                    "path": "phone",
+                    "pattern": {
+                        "MutuallyExclusive": {
+                            "paths": [
+                                "phone",
+                                "mobile"
+                            ]
+                        }
+                    }
+                }
+            },
+            {
+                "MixedFormat": {
+                    "path": "id",
+                    "dominant": "uuid",
+                    "formats": {
+                        "uuid": 95,
+                        "text": 5
+                    },
+                    "minority_percentage": 5.0
+                }
+            },
+            {
+                "ConstantField": {
+                    "path": "source",
+                    "value": "web",
+                    "occurrences": 100
+                }
+            },
+            {
+                "NumericString": {
+                    "path": "price",
+                    "numeric_count": 98,
+                    "string_count": 100,
+                    "numeric_percentage": 98.0
+                }
+            },
+            {
+                "BooleanEncoding": {
+                    "path": "active",
+                    "dominant": "boolean",
+                    "encodings": {
+                        "boolean": 90,
+                        "yes_no": 10
+                    },
+                    "minority_percentage": 10.0
+                }
+            },
+            {
+                "DateFormatDrift": {
+                    "path": "created",
+                    "formats": {
+                        "iso_date": 80,
+                        "slash_date": 20
+                    },
+                    "minority_percentage": 20.0,
+                    "suggested": "iso_date"
+                }
+            },
+            {
+                "EmptyString": {
+                    "path": "name",
+                    "empty_count": 12,
+                    "null_count": 3,
+                    "missing_count": 0,
+                    "total_samples": 100
+                }
+            },
+            {
+                "NullVsMissing": {
+                    "path": "middle_name",
+                    "null_count": 30,
+                    "missing_count": 20,
+                    "minority_percentage": 40.0
+                }
+            },
+            {
+                "KeyNamingConflict": {
+                    "path": "userId",
+                    "variants": [
+                        {
+                            "path": "userId",
+                            "density": 0.7
+                        }
+                    ]
+                }
+            },
+            {
+                "ProbableRename": {
+                    "path": "zip",
+                    "old_path": "zip",
+                    "new_path": "zip_code",
+                    "old_density": 0.3,
+                    "new_density": 0.7,
+                    "confirmed": true
+                }
+            },
+            {
+                "TupleArray": {
+                    "path": "point",
+                    "positions": [
+                        {
+                            "Number": 10
+                        }
+                    ],
+                    "arrays": 10
+                }
+            },
+            {
+                "ExcessiveDepth": {
+                    "path": "a",
+                    "depth": 1,
+                    "deepest": 12,
+                    "limit": 8
+                }
+            },
+            {
+                "OversizedPayload": {
+                    "path": "avatar",
+                    "avg_bytes": 2048.0,
+                    "max_bytes": 4096,
+                    "blob_count": 10,
+                    "string_count": 10,
+                    "bytes_per_document": 2048.0
+                }
+            },
+            {
+                "ProblematicKey": {
+                    "path": "a b",
+                    "key": "a b",
+                    "problems": [
+                        "whitespace"
+                    ],
+                    "occurrences": 5
+                }
+            },
+            {
+                "UnitDrift": {
+                    "path": "amount",
+                    "low_median": 12.5,
+                    "high_median": 1250.0,
+                    "high_percentage": 30.0,
+                    "factor": 100
+                }
+            },
+            {
+                "ConditionalField": {
+                    "path": "card",
+                    "condition_path": "method",
+                    "values": [
+                        "card"
+                    ],
+                    "density_when": 1.0,
+                    "density_otherwise": 0.0,
+                    "documents_when": 40
+                }
+            },
+            {
+                "Reclassified": {
+                    "issue": {
+                        "SparseField": {
+                            "path": "nickname",
+                            "density": 0.4,
+                            "occurrences": 40,
+                            "total_samples": 100,
+                            "margin": 0.05
+                        }
+                    },
+                    "severity": "Warning"
+                }
+            }
+        ]);
+        assert_eq!(serde_json::to_value(&issues).unwrap(), expected);
+    }
+}
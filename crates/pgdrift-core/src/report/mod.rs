@@ -0,0 +1,212 @@
+//! Machine-readable reports
+//!
+//! Every JSON report of the CLI is serialized from the types of these
+//! modules, one per command, and wrapped in `Versioned`; so are the run
+//! summaries of `--summary-file`. The types are owned copies of the analysis
+//! results, converted with `From`, so reworking the analysis doesn't change
+//! the reports by accident. Within a schema version, fields are only ever
+//! added: a field is removed, renamed or changes meaning only together with a
+//! bump of `SCHEMA_VERSION`, so tools reading the reports can rely on the
+//! fields they know.
+
+pub mod analysis;
+pub mod cohort;
+pub mod compare;
+pub mod diff;
+pub mod discover;
+pub mod field;
+pub mod index;
+pub mod issue;
+pub mod promote;
+pub mod scan_all;
+pub mod validate;
+
+use crate::drift::{DriftIssue, Severity};
+use serde::Serialize;
+
+/// Version of the JSON report structures
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// A report with the `schema_version` it was written with as its first field
+#[derive(Debug, Clone, Serialize)]
+pub struct Versioned<T> {
+    pub schema_version: u32,
+    #[serde(flatten)]
+    pub report: T,
+}
+
+impl<T> Versioned<T> {
+    pub fn new(report: T) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            report,
+        }
+    }
+}
+
+/// Number of issues of each severity
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct IssueCounts {
+    pub critical: usize,
+    pub warning: usize,
+    pub info: usize,
+}
+
+impl IssueCounts {
+    pub fn from_issues<'a>(issues: impl IntoIterator<Item = &'a DriftIssue>) -> Self {
+        let mut counts = Self::default();
+        for issue in issues {
+            match issue.severity() {
+                Severity::Critical => counts.critical += 1,
+                Severity::Warning => counts.warning += 1,
+                Severity::Info => counts.info += 1,
+            }
+        }
+        counts
+    }
+
+    pub fn total(&self) -> usize {
+        self.critical + self.warning + self.info
+    }
+
+    /// Issues at `severity` or above
+    pub fn at_least(&self, severity: Severity) -> usize {
+        match severity {
+            Severity::Critical => self.critical,
+            Severity::Warning => self.critical + self.warning,
+            Severity::Info => self.total(),
+        }
+    }
+}
+
+/// Limits a run fails on, as given with `--fail-below-score` and `--fail-on`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct Thresholds {
+    pub fail_below_score: Option<u8>,
+    pub fail_on: Option<Severity>,
+}
+
+/// Outcome of a run in a few fields, for build systems to gate and badge on
+/// without parsing the full report
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RunSummary {
+    /// Columns analyzed
+    pub columns: usize,
+    pub samples_analyzed: u64,
+    /// Lowest health score of the columns, `None` if none could be scored
+    pub health_score: Option<u8>,
+    pub issues: IssueCounts,
+    pub suppressed_issues: usize,
+    pub thresholds: Thresholds,
+    /// Whether the run stayed within the thresholds, i.e. exits successfully
+    pub passed: bool,
+}
+
+impl RunSummary {
+    pub fn new<'a>(
+        columns: usize,
+        samples_analyzed: u64,
+        scores: impl IntoIterator<Item = u8>,
+        issues: impl IntoIterator<Item = &'a DriftIssue>,
+        suppressed_issues: usize,
+        thresholds: Thresholds,
+    ) -> Self {
+        let health_score = scores.into_iter().min();
+        let issues = IssueCounts::from_issues(issues);
+        let passed = thresholds
+            .fail_below_score
+            .is_none_or(|threshold| health_score.is_none_or(|score| score >= threshold))
+            && thresholds
+                .fail_on
+                .is_none_or(|severity| issues.at_least(severity) == 0);
+        Self {
+            columns,
+            samples_analyzed,
+            health_score,
+            issues,
+            suppressed_issues,
+            thresholds,
+            passed,
+        }
+    }
+}
+
+/// A configured suppression that runs out soon
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExpiringSuppression {
+    /// Glob pattern of the suppressed paths
+    pub path: String,
+    /// Last day the suppression applies, as `YYYY-MM-DD`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn issues() -> Vec<DriftIssue> {
+        vec![
+            DriftIssue::MissingKey {
+                path: "email".to_string(),
+                density: 0.85,
+                expected_occurrences: 100,
+                actual_occurrences: 85,
+                margin: 0.0,
+            },
+            DriftIssue::EmptyString {
+                path: "name".to_string(),
+                empty_count: 12,
+                null_count: 0,
+                missing_count: 0,
+                total_samples: 100,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_run_summary() {
+        let issues = issues();
+        let summary = |thresholds| RunSummary::new(2, 200, [70, 90], &issues, 1, thresholds);
+
+        let passing = summary(Thresholds::default());
+        assert_eq!(passing.health_score, Some(70));
+        assert_eq!(passing.issues.total(), 2);
+        assert!(passing.passed);
+
+        let critical = Thresholds {
+            fail_on: Some(Severity::Critical),
+            ..Default::default()
+        };
+        assert!(!summary(critical).passed);
+        let below = Thresholds {
+            fail_below_score: Some(80),
+            ..Default::default()
+        };
+        assert!(!summary(below).passed);
+        let met = Thresholds {
+            fail_below_score: Some(70),
+            ..Default::default()
+        };
+        assert!(summary(met).passed);
+
+        let value = serde_json::to_value(summary(critical)).unwrap();
+        assert_eq!(
+            value["thresholds"],
+            json!({"fail_below_score": null, "fail_on": "Critical"})
+        );
+        assert_eq!(value["passed"], false);
+    }
+
+    #[test]
+    fn test_versioned() {
+        let report = Versioned::new(json!({"table": "users"}));
+        assert_eq!(
+            serde_json::to_string(&report).unwrap(),
+            r#"{"schema_version":1,"table":"users"}"#
+        );
+    }
+}
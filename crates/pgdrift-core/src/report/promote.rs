@@ -0,0 +1,103 @@
+//! Report of `promote`
+
+use crate::promotion;
+use serde::Serialize;
+
+/// JSON paths of a column worth moving into columns of their own
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PromoteReport {
+    pub table: String,
+    pub column: String,
+    pub samples_analyzed: u64,
+    pub row_count: i64,
+    pub promotions: Vec<Promotion>,
+    /// Bytes saved across the table once the promoted keys are removed
+    pub estimated_bytes_saved: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Promotion {
+    pub path: String,
+    pub column_name: String,
+    pub sql_type: String,
+    pub density: f64,
+    pub generated: bool,
+    pub indexed: bool,
+    pub json_bytes_per_row: f64,
+    pub column_bytes_per_row: f64,
+    pub sql: String,
+    pub benefit: String,
+}
+
+impl From<&promotion::Promotion> for Promotion {
+    fn from(p: &promotion::Promotion) -> Self {
+        Self {
+            path: p.path.clone(),
+            column_name: p.column_name.clone(),
+            sql_type: p.sql_type.clone(),
+            density: p.density,
+            generated: p.generated,
+            indexed: p.indexed,
+            json_bytes_per_row: p.json_bytes_per_row,
+            column_bytes_per_row: p.column_bytes_per_row,
+            sql: p.sql.clone(),
+            benefit: p.benefit.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::Versioned;
+    use serde_json::json;
+
+    // Pins the fields of version 1
+    #[test]
+    fn test_promote_report_fields() {
+        let promotion = promotion::Promotion {
+            path: "customer.email".to_string(),
+            column_name: "customer_email".to_string(),
+            sql_type: "text".to_string(),
+            density: 0.98,
+            generated: true,
+            indexed: false,
+            json_bytes_per_row: 32.0,
+            column_bytes_per_row: 20.0,
+            sql: "ALTER TABLE orders ADD COLUMN customer_email text".to_string(),
+            benefit: "queried in 12 statements".to_string(),
+        };
+        let report = PromoteReport {
+            table: "orders".to_string(),
+            column: "data".to_string(),
+            samples_analyzed: 100,
+            row_count: 1000,
+            promotions: vec![Promotion::from(&promotion)],
+            estimated_bytes_saved: 32000,
+        };
+
+        assert_eq!(
+            serde_json::to_value(Versioned::new(report)).unwrap(),
+            json!({
+                "schema_version": 1,
+                "table": "orders",
+                "column": "data",
+                "samples_analyzed": 100,
+                "row_count": 1000,
+                "promotions": [{
+                    "path": "customer.email",
+                    "column_name": "customer_email",
+                    "sql_type": "text",
+                    "density": 0.98,
+                    "generated": true,
+                    "indexed": false,
+                    "json_bytes_per_row": 32.0,
+                    "column_bytes_per_row": 20.0,
+                    "sql": "ALTER TABLE orders ADD COLUMN customer_email text",
+                    "benefit": "queried in 12 statements"
+                }],
+                "estimated_bytes_saved": 32000
+            })
+        );
+    }
+}
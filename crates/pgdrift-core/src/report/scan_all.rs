@@ -0,0 +1,183 @@
+//! Report of `scan-all`
+
+use super::issue::DriftIssue;
+use super::{ExpiringSuppression, IssueCounts};
+use serde::Serialize;
+
+/// Report of `scan-all`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ScanAllReport {
+    pub total_columns: usize,
+    pub total_samples: u64,
+    pub summary: ScanAllSummary,
+    pub columns: Vec<ColumnReport>,
+    pub expiring_suppressions: Vec<ExpiringSuppression>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ScanAllSummary {
+    pub total_issues: usize,
+    pub critical: usize,
+    pub warning: usize,
+    pub info: usize,
+    pub suppressed: usize,
+}
+
+/// One column of a `scan-all` report
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ColumnReport {
+    pub schema: String,
+    pub table: String,
+    pub column: String,
+    pub samples_analyzed: u64,
+    /// `None` if the column couldn't be analyzed
+    pub health_score: Option<u8>,
+    pub drift_issues: Vec<DriftIssue>,
+    pub issue_counts: ColumnIssueCounts,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ColumnIssueCounts {
+    #[serde(flatten)]
+    pub counts: IssueCounts,
+    pub suppressed: usize,
+}
+
+impl ScanAllReport {
+    /// Report of the scanned columns, totalling their samples and issues
+    pub fn new(
+        total_columns: usize,
+        columns: Vec<ColumnReport>,
+        expiring_suppressions: Vec<ExpiringSuppression>,
+    ) -> Self {
+        let mut counts = IssueCounts::default();
+        for column in &columns {
+            counts.critical += column.issue_counts.counts.critical;
+            counts.warning += column.issue_counts.counts.warning;
+            counts.info += column.issue_counts.counts.info;
+        }
+        Self {
+            total_columns,
+            total_samples: columns.iter().map(|c| c.samples_analyzed).sum(),
+            summary: ScanAllSummary {
+                total_issues: counts.total(),
+                critical: counts.critical,
+                warning: counts.warning,
+                info: counts.info,
+                suppressed: columns.iter().map(|c| c.issue_counts.suppressed).sum(),
+            },
+            columns,
+            expiring_suppressions,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drift;
+    use crate::report::Versioned;
+    use serde_json::json;
+
+    // Pins the fields of version 1: changing them breaks the tools reading the reports
+    #[test]
+    fn test_scan_all_report_fields() {
+        let issues = [
+            drift::DriftIssue::MissingKey {
+                path: "email".to_string(),
+                density: 0.85,
+                expected_occurrences: 100,
+                actual_occurrences: 85,
+                margin: 0.0,
+            },
+            drift::DriftIssue::EmptyString {
+                path: "name".to_string(),
+                empty_count: 12,
+                null_count: 0,
+                missing_count: 0,
+                total_samples: 100,
+            },
+        ];
+        let column = ColumnReport {
+            schema: "public".to_string(),
+            table: "users".to_string(),
+            column: "metadata".to_string(),
+            samples_analyzed: 100,
+            health_score: Some(70),
+            drift_issues: issues.iter().map(DriftIssue::from).collect(),
+            issue_counts: ColumnIssueCounts {
+                counts: IssueCounts::from_issues(&issues),
+                suppressed: 1,
+            },
+        };
+        let failed = ColumnReport {
+            schema: "public".to_string(),
+            table: "events".to_string(),
+            column: "payload".to_string(),
+            samples_analyzed: 0,
+            health_score: None,
+            drift_issues: vec![],
+            issue_counts: ColumnIssueCounts {
+                counts: IssueCounts::default(),
+                suppressed: 0,
+            },
+        };
+        let report = ScanAllReport::new(2, vec![column, failed], vec![]);
+
+        assert_eq!(
+            serde_json::to_value(Versioned::new(report)).unwrap(),
+            json!({
+                "schema_version": 1,
+                "total_columns": 2,
+                "total_samples": 100,
+                "summary": {
+                    "total_issues": 2,
+                    "critical": 1,
+                    "warning": 1,
+                    "info": 0,
+                    "suppressed": 1
+                },
+                "columns": [
+                    {
+                        "schema": "public",
+                        "table": "users",
+                        "column": "metadata",
+                        "samples_analyzed": 100,
+                        "health_score": 70,
+                        "drift_issues": [
+                            {
+                                "MissingKey": {
+                                    "path": "email",
+                                    "density": 0.85,
+                                    "expected_occurrences": 100,
+                                    "actual_occurrences": 85,
+                                    "margin": 0.0
+                                }
+                            },
+                            {
+                                "EmptyString": {
+                                    "path": "name",
+                                    "empty_count": 12,
+                                    "null_count": 0,
+                                    "missing_count": 0,
+                                    "total_samples": 100
+                                }
+                            }
+                        ],
+                        "issue_counts": {"critical": 1, "warning": 1, "info": 0, "suppressed": 1}
+                    },
+                    {
+                        "schema": "public",
+                        "table": "events",
+                        "column": "payload",
+                        "samples_analyzed": 0,
+                        "health_score": null,
+                        "drift_issues": [],
+                        "issue_counts": {"critical": 0, "warning": 0, "info": 0, "suppressed": 0}
+                    }
+                ],
+                "expiring_suppressions": []
+            })
+        );
+    }
+}
@@ -0,0 +1,93 @@
+//! Report of `validate`
+
+use crate::drift::Severity;
+use crate::validate;
+use serde::Serialize;
+
+/// A sampled column checked against a schema spec
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ValidateReport {
+    pub table: String,
+    pub column: String,
+    pub samples_analyzed: u64,
+    pub violations: Vec<Violation>,
+}
+
+/// A rule of the spec the column breaks
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Violation {
+    pub path: String,
+    pub rule: Rule,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl From<&validate::Violation> for Violation {
+    fn from(violation: &validate::Violation) -> Self {
+        Self {
+            path: violation.path.clone(),
+            rule: violation.rule.into(),
+            severity: violation.severity,
+            message: violation.message.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Rule {
+    Required,
+    Types,
+    Values,
+    Nulls,
+}
+
+impl From<validate::Rule> for Rule {
+    fn from(rule: validate::Rule) -> Self {
+        match rule {
+            validate::Rule::Required => Self::Required,
+            validate::Rule::Types => Self::Types,
+            validate::Rule::Values => Self::Values,
+            validate::Rule::Nulls => Self::Nulls,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::Versioned;
+    use serde_json::json;
+
+    // Pins the fields of version 1
+    #[test]
+    fn test_validate_report_fields() {
+        let violation = validate::Violation {
+            path: "email".to_string(),
+            rule: validate::Rule::Required,
+            severity: Severity::Critical,
+            message: "present in 85.0% of documents, required".to_string(),
+        };
+        let report = ValidateReport {
+            table: "users".to_string(),
+            column: "metadata".to_string(),
+            samples_analyzed: 100,
+            violations: vec![Violation::from(&violation)],
+        };
+
+        assert_eq!(
+            serde_json::to_value(Versioned::new(report)).unwrap(),
+            json!({
+                "schema_version": 1,
+                "table": "users",
+                "column": "metadata",
+                "samples_analyzed": 100,
+                "violations": [{
+                    "path": "email",
+                    "rule": "Required",
+                    "severity": "Critical",
+                    "message": "present in 85.0% of documents, required"
+                }]
+            })
+        );
+    }
+}
@@ -0,0 +1,116 @@
+use crate::drift::{DriftIssue, Severity};
+use crate::stats::FieldStats;
+use crate::types::JsonType;
+use std::collections::HashMap;
+
+/// Share of the score taken by reported issues
+const ISSUE_WEIGHT: f64 = 0.6;
+
+/// Share of the score taken by how consistently paths are present
+const DENSITY_WEIGHT: f64 = 0.2;
+
+/// Share of the score taken by how consistently paths hold one type
+const TYPE_WEIGHT: f64 = 0.2;
+
+/// Points one issue takes off the issue component
+fn penalty(severity: Severity) -> f64 {
+    match severity {
+        Severity::Critical => 25.0,
+        Severity::Warning => 8.0,
+        Severity::Info => 2.0,
+    }
+}
+
+/// Drift health of a column, from 0 (badly drifted) to 100 (clean)
+///
+/// Three components of 0-100 are weighted together:
+/// - issues (60%): 100 minus 25 per critical, 8 per warning and 2 per info issue
+/// - density (20%): how decisively each path is either present or absent; a
+///   path in every document scores 100, one in half of them 0
+/// - types (20%): the share of each path's non-null values holding its most
+///   common type
+///
+/// `issues` should be the reported issues, after suppression and any severity
+/// overrides, so the score matches what the report shows.
+pub fn health_score(stats: &HashMap<String, FieldStats>, issues: &[DriftIssue]) -> u8 {
+    let issue_score = (100.0 - issues.iter().map(|i| penalty(i.severity())).sum::<f64>()).max(0.0);
+
+    let density_score = average(
+        stats
+            .values()
+            .map(|fs| (2.0 * fs.density - 1.0).abs() * 100.0),
+    );
+
+    let type_score = average(stats.values().filter_map(|fs| {
+        let counts = fs
+            .types
+            .iter()
+            .filter(|(json_type, _)| **json_type != JsonType::Null)
+            .map(|(_, count)| *count);
+        let total: u64 = counts.clone().sum();
+        let dominant = counts.max()?;
+        Some(dominant as f64 / total as f64 * 100.0)
+    }));
+
+    let score =
+        issue_score * ISSUE_WEIGHT + density_score * DENSITY_WEIGHT + type_score * TYPE_WEIGHT;
+    score.round().clamp(0.0, 100.0) as u8
+}
+
+/// Mean of the values, 100 when there are none
+fn average(values: impl Iterator<Item = f64>) -> f64 {
+    let (sum, count) = values.fold((0.0, 0), |(sum, count), v| (sum + v, count + 1));
+    if count == 0 {
+        100.0
+    } else {
+        sum / count as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::JsonAnalyzer;
+    use crate::drift::{DriftConfig, detect_drift};
+    use serde_json::json;
+
+    fn score(documents: &[serde_json::Value]) -> u8 {
+        let mut analyzer = JsonAnalyzer::new();
+        for document in documents {
+            analyzer.analyze(document);
+        }
+        let stats = analyzer.finalize();
+        let issues = detect_drift(&stats, &DriftConfig::default());
+        health_score(&stats, &issues)
+    }
+
+    #[test]
+    fn test_health_score() {
+        assert_eq!(health_score(&HashMap::new(), &[]), 100);
+
+        let clean: Vec<_> = (0..100)
+            .map(|i| json!({"id": i, "name": format!("user {}", i)}))
+            .collect();
+        assert_eq!(score(&clean), 100);
+
+        let drifted: Vec<_> = (0..100)
+            .map(|i| match i % 2 {
+                0 => json!({"id": i, "name": format!("user {}", i)}),
+                _ => json!({"id": i.to_string(), "userName": format!("user {}", i)}),
+            })
+            .collect();
+        let drifted = score(&drifted);
+        assert!(drifted < 80, "score {}", drifted);
+    }
+
+    #[test]
+    fn test_issue_penalties_bottom_out() {
+        let issue = DriftIssue::TypeInconsistency {
+            path: "id".to_string(),
+            types: HashMap::new(),
+            minority_percentage: 50.0,
+        };
+        let issues = vec![issue; 10];
+        assert_eq!(health_score(&HashMap::new(), &issues), 40);
+    }
+}
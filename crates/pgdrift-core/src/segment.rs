@@ -0,0 +1,133 @@
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Maximum number of segments reported; smaller ones are grouped into `OTHER_SEGMENT`
+pub const MAX_SEGMENTS: usize = 20;
+
+/// Segment for documents without the discriminator field or group value
+pub const MISSING_SEGMENT: &str = "(missing)";
+
+/// Segment collecting the smallest segments once there are more than `MAX_SEGMENTS`
+pub const OTHER_SEGMENT: &str = "(other)";
+
+/// Documents sharing one value of the discriminator field
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment {
+    pub value: String,
+    pub samples: Vec<Value>,
+}
+
+/// Look up a dotted path (`payload.type`) in a document
+pub fn lookup<'a>(document: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.')
+        .try_fold(document, |value, key| value.as_object()?.get(key))
+}
+
+/// Partition documents by the value at `path`, largest segment first
+///
+/// Polymorphic columns (e.g. events keyed by `type`) look like a mess of
+/// sparse fields when analyzed as one; each segment can be analyzed on its own.
+pub fn segment_samples(samples: Vec<Value>, path: &str) -> Vec<Segment> {
+    group_samples(samples.into_iter().map(|sample| {
+        let value = lookup(&sample, path).cloned();
+        (value, sample)
+    }))
+}
+
+/// Partition documents by a value accompanying each of them, largest segment first
+///
+/// Used to split documents by another column of their row, such as a
+/// tenant id. Documents without a value go into `MISSING_SEGMENT`.
+pub fn group_samples(samples: impl IntoIterator<Item = (Option<Value>, Value)>) -> Vec<Segment> {
+    let mut groups: HashMap<String, Vec<Value>> = HashMap::new();
+    for (value, sample) in samples {
+        let label = match value {
+            None => MISSING_SEGMENT.to_string(),
+            Some(Value::String(s)) => s,
+            Some(other) => other.to_string(),
+        };
+        groups.entry(label).or_default().push(sample);
+    }
+
+    let mut segments: Vec<Segment> = groups
+        .into_iter()
+        .map(|(value, samples)| Segment { value, samples })
+        .collect();
+    segments.sort_by(|a, b| {
+        b.samples
+            .len()
+            .cmp(&a.samples.len())
+            .then_with(|| a.value.cmp(&b.value))
+    });
+
+    if segments.len() > MAX_SEGMENTS {
+        let rest = segments.split_off(MAX_SEGMENTS - 1);
+        segments.push(Segment {
+            value: OTHER_SEGMENT.to_string(),
+            samples: rest.into_iter().flat_map(|s| s.samples).collect(),
+        });
+    }
+
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_lookup() {
+        let doc = json!({"meta": {"type": "order"}, "n": 1});
+        assert_eq!(lookup(&doc, "meta.type"), Some(&json!("order")));
+        assert_eq!(lookup(&doc, "n"), Some(&json!(1)));
+        assert_eq!(lookup(&doc, "n.x"), None);
+        assert_eq!(lookup(&doc, "missing"), None);
+    }
+
+    #[test]
+    fn test_segment_samples() {
+        let samples = vec![
+            json!({"type": "order", "total": 10}),
+            json!({"type": "refund", "reason": "damaged"}),
+            json!({"type": "order", "total": 12}),
+            json!({"type": 3}),
+            json!({"other": true}),
+        ];
+
+        let segments = segment_samples(samples, "type");
+        let labels: Vec<&str> = segments.iter().map(|s| s.value.as_str()).collect();
+        assert_eq!(labels, vec!["order", "(missing)", "3", "refund"]);
+        assert_eq!(segments[0].samples.len(), 2);
+    }
+
+    #[test]
+    fn test_group_samples() {
+        let samples = vec![
+            (Some(json!("acme")), json!({"a": 1})),
+            (Some(json!(42)), json!({"b": 1})),
+            (Some(json!("acme")), json!({"a": 2})),
+            (None, json!({"c": 1})),
+        ];
+
+        let segments = group_samples(samples);
+        let labels: Vec<&str> = segments.iter().map(|s| s.value.as_str()).collect();
+        assert_eq!(labels, vec!["acme", "(missing)", "42"]);
+        assert_eq!(segments[0].samples, vec![json!({"a": 1}), json!({"a": 2})]);
+    }
+
+    #[test]
+    fn test_small_segments_grouped_into_other() {
+        let samples: Vec<Value> = (0..MAX_SEGMENTS + 5)
+            .map(|i| json!({ "kind": format!("k{:02}", i) }))
+            .chain(std::iter::repeat_n(json!({"kind": "common"}), 3))
+            .collect();
+
+        let segments = segment_samples(samples, "kind");
+        assert_eq!(segments.len(), MAX_SEGMENTS);
+        assert_eq!(segments[0].value, "common");
+        let other = segments.last().unwrap();
+        assert_eq!(other.value, OTHER_SEGMENT);
+        assert_eq!(other.samples.len(), 7);
+    }
+}
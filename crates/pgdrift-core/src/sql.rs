@@ -0,0 +1,42 @@
+//! Quoting of identifiers and literals in generated SQL
+//!
+//! Every query and script pgdrift builds quotes names and values through
+//! these, whether it runs them itself or prints them for review.
+
+/// Quote a Postgres identifier (schema, table, column or index name)
+pub fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Quote a string literal; the result is untyped, so Postgres coerces it to
+/// whatever type it is compared with
+pub fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quote_ident() {
+        assert_eq!(quote_ident("simple"), "\"simple\"");
+        assert_eq!(quote_ident("with\"quote"), "\"with\"\"quote\"");
+        assert_eq!(quote_ident("schema.table"), "\"schema.table\"");
+    }
+
+    #[test]
+    fn test_quote_ident_sql_injection() {
+        // Ensure SQL injection attempts are properly escaped
+        assert_eq!(
+            quote_ident("table\"; DROP TABLE users; --"),
+            "\"table\"\"; DROP TABLE users; --\""
+        );
+    }
+
+    #[test]
+    fn test_quote_literal() {
+        assert_eq!(quote_literal("it's"), "'it''s'");
+        assert_eq!(quote_literal("5s"), "'5s'");
+    }
+}
@@ -1,7 +1,12 @@
+use crate::format::{StringFormat, looks_like_blob};
+use crate::hll::HyperLogLog;
+use crate::redact::Redaction;
 use crate::types::JsonType;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 
 #[derive(Debug, Clone, Serialize)]
 pub struct FieldStats {
@@ -9,10 +14,484 @@ pub struct FieldStats {
     pub occurrences: u64,
     pub total_samples: u64,
     pub density: f64,
+    /// Confidence interval of `density` at `DENSITY_CONFIDENCE`
+    pub density_interval: DensityInterval,
     pub null_count: u64,
     pub types: HashMap<JsonType, u64>,
     pub examples: Vec<Value>,
     pub depth: usize,
+    /// Approximate number of distinct non-null scalar values
+    pub cardinality: u64,
+    #[serde(skip)]
+    cardinality_sketch: HyperLogLog,
+    /// Distribution of numeric values, if any were seen
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub numeric: Option<NumericStats>,
+    #[serde(skip)]
+    numeric_values: NumericAccumulator,
+    /// Count of string values per inferred format
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub formats: HashMap<StringFormat, u64>,
+    /// Most common string format and how consistently it is used
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<FormatConsistency>,
+    /// Size of the string values, if any were seen
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strings: Option<StringStats>,
+    #[serde(skip)]
+    string_sizes: StringSizes,
+    /// Element-count distribution, if the path held arrays
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub array: Option<ArrayStats>,
+    #[serde(skip)]
+    array_lengths: NumericAccumulator,
+    /// Element types by position, for arrays up to `TUPLE_MAX_POSITIONS` long
+    #[serde(skip)]
+    position_types: Vec<HashMap<JsonType, u64>>,
+    /// Most common scalar values, if the path has few distinct values
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_values: Option<Vec<ValueCount>>,
+    #[serde(skip)]
+    value_counts: ValueCounter,
+    /// The single value this path always held, if it never varied
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub constant_value: Option<Value>,
+    /// Number of distinct map keys folded into this wildcard (`*`) path
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collapsed_keys: Option<u64>,
+    #[serde(skip)]
+    example_bytes: usize,
+    /// Reservoir keys of `examples`, index for index; the smallest keys are kept
+    #[serde(skip)]
+    example_keys: Vec<f64>,
+    #[serde(skip)]
+    max_examples: usize,
+    /// splitmix64 state drawing the reservoir keys
+    #[serde(skip)]
+    example_rng: u64,
+    /// Sum of the sample weights of all occurrences (equals `occurrences` when unweighted)
+    #[serde(skip)]
+    weight: f64,
+}
+
+/// Arrays longer than this aren't tracked position by position - they are
+/// lists, not tuples
+pub const TUPLE_MAX_POSITIONS: usize = 8;
+
+/// Number of most common values reported per path
+pub const TOP_VALUES: usize = 10;
+
+/// Confidence level of the density interval attached to each path
+pub const DENSITY_CONFIDENCE: f64 = 0.95;
+
+/// Number of example values kept per path unless configured otherwise
+pub const DEFAULT_EXAMPLES: usize = 10;
+
+/// Paths with more distinct scalar values than this are not enum-like, so
+/// their values are not counted
+pub const TOP_VALUES_MAX_DISTINCT: usize = 50;
+
+/// A scalar value and how often it was seen
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ValueCount {
+    pub value: Value,
+    pub count: u64,
+    /// Percentage of non-null occurrences with this value
+    pub percentage: f64,
+}
+
+/// Exact counts of scalar values, abandoned once the path stops looking like an enum
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ValueCounter {
+    counts: HashMap<String, (Value, u64)>,
+    overflowed: bool,
+}
+
+impl ValueCounter {
+    fn record(&mut self, value: &Value, times: u64) {
+        if self.overflowed {
+            return;
+        }
+
+        // Keyed by canonical JSON so 1 and "1" stay distinct
+        let key = value.to_string();
+        if let Some((_, count)) = self.counts.get_mut(&key) {
+            *count += times;
+        } else if self.counts.len() < TOP_VALUES_MAX_DISTINCT {
+            self.counts.insert(key, (value.clone(), times));
+        } else {
+            self.overflowed = true;
+            self.counts.clear();
+        }
+    }
+
+    fn merge(&mut self, other: &ValueCounter) {
+        if other.overflowed {
+            self.overflowed = true;
+        }
+        if self.overflowed {
+            self.counts.clear();
+            return;
+        }
+
+        for (key, (value, count)) in &other.counts {
+            self.counts
+                .entry(key.clone())
+                .or_insert((value.clone(), 0))
+                .1 += count;
+        }
+        if self.counts.len() > TOP_VALUES_MAX_DISTINCT {
+            self.overflowed = true;
+            self.counts.clear();
+        }
+    }
+
+    fn top(&self) -> Option<Vec<ValueCount>> {
+        self.ranked(TOP_VALUES)
+    }
+
+    /// The `limit` most frequent values, `None` once there were too many to count
+    fn ranked(&self, limit: usize) -> Option<Vec<ValueCount>> {
+        if self.overflowed || self.counts.is_empty() {
+            return None;
+        }
+
+        let total: u64 = self.counts.values().map(|(_, c)| c).sum();
+        let mut entries: Vec<(&String, &(Value, u64))> = self.counts.iter().collect();
+        entries.sort_by(|a, b| b.1.1.cmp(&a.1.1).then_with(|| a.0.cmp(b.0)));
+
+        Some(
+            entries
+                .into_iter()
+                .take(limit)
+                .map(|(_, (value, count))| ValueCount {
+                    value: value.clone(),
+                    count: *count,
+                    percentage: *count as f64 / total as f64 * 100.0,
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Byte sizes of the string values at a path
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StringStats {
+    pub count: u64,
+    pub total_bytes: u64,
+    pub avg_bytes: f64,
+    pub max_bytes: u64,
+    /// Strings that look like base64 or serialized documents
+    pub blob_count: u64,
+}
+
+/// Running totals behind `StringStats`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct StringSizes {
+    count: u64,
+    total_bytes: u64,
+    max_bytes: u64,
+    blob_count: u64,
+}
+
+impl StringSizes {
+    fn record(&mut self, s: &str, times: u64) {
+        self.count += times;
+        self.total_bytes += s.len() as u64 * times;
+        self.max_bytes = self.max_bytes.max(s.len() as u64);
+        if looks_like_blob(s) {
+            self.blob_count += times;
+        }
+    }
+
+    fn merge(&mut self, other: &StringSizes) {
+        self.count += other.count;
+        self.total_bytes += other.total_bytes;
+        self.max_bytes = self.max_bytes.max(other.max_bytes);
+        self.blob_count += other.blob_count;
+    }
+
+    fn summarize(&self) -> Option<StringStats> {
+        (self.count > 0).then(|| StringStats {
+            count: self.count,
+            total_bytes: self.total_bytes,
+            avg_bytes: self.total_bytes as f64 / self.count as f64,
+            max_bytes: self.max_bytes,
+            blob_count: self.blob_count,
+        })
+    }
+}
+
+/// Element-count distribution for the arrays seen at a path
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ArrayStats {
+    pub count: u64,
+    pub min_length: u64,
+    pub max_length: u64,
+    pub avg_length: f64,
+    pub p99_length: u64,
+    /// Share of arrays with no elements (0.0 - 1.0)
+    pub empty_ratio: f64,
+}
+
+impl ArrayStats {
+    /// Every array holds exactly one element - a candidate for flattening
+    pub fn is_singleton(&self) -> bool {
+        self.min_length == 1 && self.max_length == 1
+    }
+}
+
+/// Dominant string format of a path
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FormatConsistency {
+    pub format: StringFormat,
+    /// Percentage of string values with this format
+    pub consistency: f64,
+}
+
+/// Range the true density of a path lies in, at some confidence level
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct DensityInterval {
+    pub lower: f64,
+    pub upper: f64,
+}
+
+impl DensityInterval {
+    /// Wilson score interval of a proportion `p` measured on `n` samples
+    ///
+    /// Unlike the normal approximation it stays inside [0, 1] and doesn't
+    /// collapse to a point at 0% or 100%.
+    pub fn wilson(p: f64, n: u64, confidence: f64) -> Self {
+        if n == 0 {
+            return Self {
+                lower: 0.0,
+                upper: 1.0,
+            };
+        }
+
+        let n = n as f64;
+        let z = z_score(confidence);
+        let z2 = z * z;
+        let denominator = 1.0 + z2 / n;
+        let center = (p + z2 / (2.0 * n)) / denominator;
+        let half_width = z * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt() / denominator;
+        Self {
+            lower: (center - half_width).max(0.0),
+            upper: (center + half_width).min(1.0),
+        }
+    }
+
+    /// Largest distance from `estimate` to either bound
+    pub fn margin(&self, estimate: f64) -> f64 {
+        (estimate - self.lower).max(self.upper - estimate)
+    }
+}
+
+/// Distribution summary for the numeric values seen at a path
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NumericStats {
+    pub count: u64,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    /// Percentiles are approximate once more than `NUMERIC_SAMPLE_CAPACITY` values are seen
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+    /// Two clusters of positive values far apart in magnitude, if the values split that way
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bands: Option<MagnitudeBands>,
+}
+
+/// Positive values falling into two separate orders of magnitude
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MagnitudeBands {
+    pub low_median: f64,
+    pub high_median: f64,
+    /// Share of positive values in the upper band (0.0 - 1.0)
+    pub high_share: f64,
+}
+
+impl MagnitudeBands {
+    /// How many times larger the upper band is than the lower one
+    pub fn ratio(&self) -> f64 {
+        self.high_median / self.low_median
+    }
+
+    /// Split sorted values at the widest gap on a log scale
+    ///
+    /// Only a gap of at least `MIN_BAND_GAP` with `MIN_BAND_SHARE` of the
+    /// positive values on each side counts as two bands.
+    fn find(sorted: &[f64]) -> Option<Self> {
+        let positive: Vec<f64> = sorted.iter().copied().filter(|n| *n > 0.0).collect();
+        if positive.len() < MIN_BAND_VALUES {
+            return None;
+        }
+
+        let (split, gap) = positive
+            .windows(2)
+            .enumerate()
+            .map(|(i, pair)| (i + 1, pair[1] / pair[0]))
+            .max_by(|a, b| a.1.total_cmp(&b.1))?;
+        let min_side = (positive.len() as f64 * MIN_BAND_SHARE).ceil() as usize;
+        if gap < MIN_BAND_GAP || split < min_side || positive.len() - split < min_side {
+            return None;
+        }
+
+        let (low, high) = positive.split_at(split);
+        Some(MagnitudeBands {
+            low_median: low[low.len() / 2],
+            high_median: high[high.len() / 2],
+            high_share: high.len() as f64 / positive.len() as f64,
+        })
+    }
+}
+
+/// Fewest positive sampled values to look for magnitude bands in
+const MIN_BAND_VALUES: usize = 20;
+
+/// Smallest share of values in each magnitude band
+const MIN_BAND_SHARE: f64 = 0.05;
+
+/// Smallest factor between the top of the low band and the bottom of the high band
+const MIN_BAND_GAP: f64 = 3.0;
+
+/// Upper bound on the numeric values kept per path for percentile estimates
+const NUMERIC_SAMPLE_CAPACITY: usize = 2048;
+
+/// Running min/max/mean plus a bounded, evenly-spaced sample for percentiles
+///
+/// When the sample fills up, every other value is dropped and the stride
+/// doubles, so memory stays fixed while the sample still spans the stream.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct NumericAccumulator {
+    count: u64,
+    min: f64,
+    max: f64,
+    sum: f64,
+    sample: Vec<f64>,
+    stride: u64,
+    /// Exact zero count - empty arrays when tracking lengths
+    zeros: u64,
+}
+
+impl NumericAccumulator {
+    /// Record `n` as if it had been seen `times` times in a row
+    fn record(&mut self, n: f64, times: u64) {
+        if times == 0 {
+            return;
+        }
+        if self.count == 0 {
+            self.min = n;
+            self.max = n;
+            self.stride = 1;
+        } else {
+            self.min = self.min.min(n);
+            self.max = self.max.max(n);
+        }
+
+        // Sample every position in the run that falls on the current stride
+        let end = self.count + times;
+        let mut next = self.count.next_multiple_of(self.stride);
+        while next < end {
+            self.sample.push(n);
+            self.thin();
+            next = (next + 1).next_multiple_of(self.stride);
+        }
+
+        if n == 0.0 {
+            self.zeros += times;
+        }
+
+        self.count = end;
+        self.sum += n * times as f64;
+    }
+
+    /// Drop every other sampled value until the sample is under capacity
+    fn thin(&mut self) {
+        while self.sample.len() >= NUMERIC_SAMPLE_CAPACITY {
+            let mut i = 0;
+            self.sample.retain(|_| {
+                i += 1;
+                i % 2 == 1
+            });
+            self.stride *= 2;
+        }
+    }
+
+    fn merge(&mut self, other: &NumericAccumulator) {
+        if other.count == 0 {
+            return;
+        }
+        if self.count == 0 {
+            *self = other.clone();
+            return;
+        }
+
+        self.count += other.count;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.sum += other.sum;
+        self.zeros += other.zeros;
+        self.sample.extend_from_slice(&other.sample);
+        self.stride = self.stride.max(other.stride);
+        self.thin();
+    }
+
+    fn summarize(&self) -> Option<NumericStats> {
+        if self.count == 0 {
+            return None;
+        }
+
+        let mut sorted = self.sample.clone();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let percentile = |p: f64| {
+            let rank = (p * sorted.len() as f64).ceil() as usize;
+            sorted[rank.clamp(1, sorted.len()) - 1]
+        };
+
+        Some(NumericStats {
+            count: self.count,
+            min: self.min,
+            max: self.max,
+            mean: self.sum / self.count as f64,
+            p50: percentile(0.50),
+            p90: percentile(0.90),
+            p99: percentile(0.99),
+            bands: MagnitudeBands::find(&sorted),
+        })
+    }
+}
+
+/// Raw, unfinalized observations of a path, for checkpointing an analysis
+///
+/// Derived values (density, cardinality, percentiles, ...) are not stored;
+/// `finalize` recomputes them after `FieldStats::from_state`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldStatsState {
+    path: String,
+    depth: usize,
+    occurrences: u64,
+    null_count: u64,
+    weight: f64,
+    types: HashMap<JsonType, u64>,
+    examples: Vec<Value>,
+    example_keys: Vec<f64>,
+    example_rng: u64,
+    cardinality_sketch: HyperLogLog,
+    numeric_values: NumericAccumulator,
+    formats: HashMap<StringFormat, u64>,
+    string_sizes: StringSizes,
+    array_lengths: NumericAccumulator,
+    position_types: Vec<HashMap<JsonType, u64>>,
+    value_counts: ValueCounter,
+    collapsed_keys: Option<u64>,
+}
+
+impl FieldStatsState {
+    pub fn path(&self) -> &str {
+        &self.path
+    }
 }
 
 impl FieldStats {
@@ -22,28 +501,314 @@ impl FieldStats {
             occurrences: 0,
             total_samples: 0,
             density: 0.0,
+            density_interval: DensityInterval::default(),
             null_count: 0,
             types: HashMap::new(),
             examples: Vec::new(),
             depth,
+            cardinality: 0,
+            cardinality_sketch: HyperLogLog::new(),
+            numeric: None,
+            numeric_values: NumericAccumulator::default(),
+            formats: HashMap::new(),
+            format: None,
+            strings: None,
+            string_sizes: StringSizes::default(),
+            array: None,
+            array_lengths: NumericAccumulator::default(),
+            position_types: Vec::new(),
+            top_values: None,
+            value_counts: ValueCounter::default(),
+            constant_value: None,
+            collapsed_keys: None,
+            example_bytes: 0,
+            example_keys: Vec::new(),
+            max_examples: DEFAULT_EXAMPLES,
+            example_rng: 0,
+            weight: 0.0,
         }
     }
 
+    /// Keep up to `max` example values instead of `DEFAULT_EXAMPLES`
+    pub fn with_max_examples(mut self, max: usize) -> Self {
+        self.max_examples = max;
+        // Stats restored from a checkpoint may hold more than the new limit
+        while self.examples.len() > max {
+            self.remove_example(self.largest_example_key().0);
+        }
+        self
+    }
+
     /// Record and occurence of this field with its value
     pub fn record(&mut self, value: &Value) {
-        self.occurrences += 1;
+        self.record_repeated(value, 1);
+    }
+
+    /// Record `times` occurrences of the same value at once
+    ///
+    /// Equivalent to calling `record` `times` times, without the repeated work.
+    pub fn record_repeated(&mut self, value: &Value, times: u64) {
+        self.record_weighted(value, times, times as f64);
+    }
+
+    /// Record `times` occurrences whose sample weights add up to `weight`
+    ///
+    /// The weight only affects density (see `finalize_weighted`); type counts,
+    /// top values and other value statistics count each occurrence once.
+    pub fn record_weighted(&mut self, value: &Value, times: u64, weight: f64) {
+        if times == 0 {
+            return;
+        }
+        self.occurrences += times;
+        self.weight += weight;
 
         let json_type = JsonType::from_value(value);
-        *self.types.entry(json_type).or_insert(0) += 1;
+        *self.types.entry(json_type).or_insert(0) += times;
+
+        match value {
+            Value::Null => self.null_count += times,
+            Value::Number(n) => {
+                self.cardinality_sketch.insert(value);
+                self.value_counts.record(value, times);
+                if let Some(n) = n.as_f64() {
+                    self.numeric_values.record(n, times);
+                }
+            }
+            Value::String(s) => {
+                self.cardinality_sketch.insert(value);
+                self.value_counts.record(value, times);
+                *self.formats.entry(StringFormat::classify(s)).or_insert(0) += times;
+                self.string_sizes.record(s, times);
+            }
+            Value::Bool(_) => {
+                self.cardinality_sketch.insert(value);
+                self.value_counts.record(value, times);
+            }
+            Value::Array(items) => {
+                self.array_lengths.record(items.len() as f64, times);
+                if items.len() <= TUPLE_MAX_POSITIONS {
+                    if self.position_types.len() < items.len() {
+                        self.position_types.resize_with(items.len(), HashMap::new);
+                    }
+                    for (types, item) in self.position_types.iter_mut().zip(items) {
+                        *types.entry(JsonType::from_value(item)).or_insert(0) += times;
+                    }
+                }
+            }
+            // Objects are described by their child paths instead
+            Value::Object(_) => {}
+        }
+
+        if self.occurrences == times {
+            self.seed_examples(value);
+        }
+        self.sample_examples(value, times);
+    }
+
+    /// Seed the reservoir keys from the path and the first value seen
+    ///
+    /// Including the value keeps batches analyzed in parallel from drawing
+    /// the same key sequence, which would favor the same offsets in each.
+    fn seed_examples(&mut self, value: &Value) {
+        let mut hasher = DefaultHasher::new();
+        self.path.hash(&mut hasher);
+        value.to_string().hash(&mut hasher);
+        self.example_rng = hasher.finish();
+    }
+
+    /// Offer a run of `times` occurrences of `value` as examples
+    ///
+    /// Examples are a reservoir sample over all occurrences: each occurrence
+    /// gets a uniform random key and the `max_examples` smallest keys are
+    /// kept, so reservoirs from different batches merge by key. Only the
+    /// smallest keys of a run can win, so they are drawn directly as the
+    /// ascending order statistics of `times` uniform keys.
+    fn sample_examples(&mut self, value: &Value, times: u64) {
+        let mut key = 0.0;
+        for i in 0..times.min(self.max_examples as u64) {
+            let u = self.next_uniform();
+            key = 1.0 - (1.0 - key) * u.powf(1.0 / (times - i) as f64);
+            if !self.offer_example(key, value) {
+                break;
+            }
+        }
+    }
+
+    /// Keep `value` if `key` is among the smallest seen; false if it was rejected
+    fn offer_example(&mut self, key: f64, value: &Value) -> bool {
+        if self.examples.len() >= self.max_examples {
+            if self.examples.is_empty() {
+                return false;
+            }
+            let (slot, largest) = self.largest_example_key();
+            if key >= largest {
+                return false;
+            }
+            self.remove_example(slot);
+        }
+
+        self.example_bytes += value_size(value);
+        self.example_keys.push(key);
+        self.examples.push(value.clone());
+        true
+    }
+
+    fn largest_example_key(&self) -> (usize, f64) {
+        self.example_keys
+            .iter()
+            .copied()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap_or((0, 0.0))
+    }
+
+    fn remove_example(&mut self, slot: usize) {
+        self.example_keys.swap_remove(slot);
+        let removed = self.examples.swap_remove(slot);
+        self.example_bytes = self.example_bytes.saturating_sub(value_size(&removed));
+    }
+
+    /// Next pseudo-random number in (0, 1] from the splitmix64 stream
+    fn next_uniform(&mut self) -> f64 {
+        self.example_rng = self.example_rng.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.example_rng;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        ((z >> 11) + 1) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Record occurrences known only by type, e.g. counted by the database
+    ///
+    /// Value statistics (cardinality, numbers, formats, examples) are left
+    /// untouched, so they stay empty if nothing else is recorded.
+    pub fn record_type(&mut self, json_type: JsonType, times: u64) {
+        self.occurrences += times;
+        self.weight += times as f64;
+        *self.types.entry(json_type).or_insert(0) += times;
+        if json_type == JsonType::Null {
+            self.null_count += times;
+        }
+    }
+
+    /// Hide sampled values (examples, top values, constant value) before reporting
+    ///
+    /// `Redaction::Drop` removes them; the other modes rewrite them in place.
+    pub fn redact(&mut self, redaction: Redaction) {
+        if redaction == Redaction::Drop {
+            self.examples.clear();
+            self.example_keys.clear();
+            self.top_values = None;
+            self.constant_value = None;
+            return;
+        }
+
+        for example in &mut self.examples {
+            *example = redaction.apply(example);
+        }
+        for top in self.top_values.iter_mut().flatten() {
+            top.value = redaction.apply(&top.value);
+        }
+        if let Some(value) = &mut self.constant_value {
+            *value = redaction.apply(value);
+        }
+    }
+
+    /// Rough number of heap and inline bytes held by this path's statistics
+    pub fn approx_bytes(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self.path.len()
+            + HyperLogLog::BYTES
+            + (self.numeric_values.sample.capacity() + self.array_lengths.sample.capacity())
+                * std::mem::size_of::<f64>()
+            + self
+                .value_counts
+                .counts
+                .iter()
+                .map(|(key, (value, _))| key.len() + value_size(value))
+                .sum::<usize>()
+            + self.example_bytes
+    }
 
-        if matches!(value, Value::Null) {
-            self.null_count += 1;
+    /// Snapshot the raw observations so the analysis can be resumed later
+    pub fn state(&self) -> FieldStatsState {
+        FieldStatsState {
+            path: self.path.clone(),
+            depth: self.depth,
+            occurrences: self.occurrences,
+            null_count: self.null_count,
+            weight: self.weight,
+            types: self.types.clone(),
+            examples: self.examples.clone(),
+            example_keys: self.example_keys.clone(),
+            example_rng: self.example_rng,
+            cardinality_sketch: self.cardinality_sketch.clone(),
+            numeric_values: self.numeric_values.clone(),
+            formats: self.formats.clone(),
+            string_sizes: self.string_sizes.clone(),
+            array_lengths: self.array_lengths.clone(),
+            position_types: self.position_types.clone(),
+            value_counts: self.value_counts.clone(),
+            collapsed_keys: self.collapsed_keys,
         }
+    }
+
+    /// Rebuild unfinalized stats from a snapshot taken with `state`
+    pub fn from_state(state: FieldStatsState) -> Self {
+        let mut stats = FieldStats::new(state.path, state.depth);
+        stats.occurrences = state.occurrences;
+        stats.null_count = state.null_count;
+        stats.weight = state.weight;
+        stats.types = state.types;
+        stats.example_bytes = state.examples.iter().map(value_size).sum();
+        stats.examples = state.examples;
+        stats.example_keys = state.example_keys;
+        stats.example_rng = state.example_rng;
+        stats.cardinality_sketch = state.cardinality_sketch;
+        stats.numeric_values = state.numeric_values;
+        stats.formats = state.formats;
+        stats.string_sizes = state.string_sizes;
+        stats.array_lengths = state.array_lengths;
+        stats.position_types = state.position_types;
+        stats.value_counts = state.value_counts;
+        stats.collapsed_keys = state.collapsed_keys;
+        stats
+    }
 
-        // store examples  - max 10
-        if self.examples.len() < 10 {
-            self.examples.push(value.clone());
+    /// Fold the raw observations of another path into this one
+    ///
+    /// Use this to combine stats for the same path computed from different
+    /// batches, partitions or machines. Derived values (density, cardinality,
+    /// ...) are recomputed by the next `finalize`, which must be given the
+    /// combined sample count.
+    pub fn merge(&mut self, other: &FieldStats) {
+        self.occurrences += other.occurrences;
+        self.weight += other.weight;
+        self.null_count += other.null_count;
+        for (json_type, count) in &other.types {
+            *self.types.entry(*json_type).or_insert(0) += count;
+        }
+        for (key, example) in other.example_keys.iter().zip(&other.examples) {
+            self.offer_example(*key, example);
+        }
+        self.cardinality_sketch.merge(&other.cardinality_sketch);
+        self.numeric_values.merge(&other.numeric_values);
+        for (format, count) in &other.formats {
+            *self.formats.entry(*format).or_insert(0) += count;
         }
+        self.string_sizes.merge(&other.string_sizes);
+        self.array_lengths.merge(&other.array_lengths);
+        if self.position_types.len() < other.position_types.len() {
+            self.position_types
+                .resize_with(other.position_types.len(), HashMap::new);
+        }
+        for (types, other_types) in self.position_types.iter_mut().zip(&other.position_types) {
+            for (json_type, count) in other_types {
+                *types.entry(*json_type).or_insert(0) += count;
+            }
+        }
+        self.value_counts.merge(&other.value_counts);
+        self.collapsed_keys = self.collapsed_keys.max(other.collapsed_keys);
     }
 
     pub fn finalize(&mut self, total_samples: u64) {
@@ -52,5 +817,601 @@ impl FieldStats {
         if self.total_samples > 0 {
             self.density = self.occurrences as f64 / self.total_samples as f64;
         }
+        self.density_interval = self.density_bounds(DENSITY_CONFIDENCE);
+
+        // Never report more distinct values than non-null occurrences
+        let non_null = self.occurrences - self.null_count;
+        self.cardinality = self.cardinality_sketch.estimate().min(non_null);
+        self.numeric = self.numeric_values.summarize();
+        self.format = self.dominant_format();
+        self.strings = self.string_sizes.summarize();
+        self.array = self.array_lengths.summarize().map(|lengths| ArrayStats {
+            count: lengths.count,
+            min_length: lengths.min as u64,
+            max_length: lengths.max as u64,
+            avg_length: lengths.mean,
+            p99_length: lengths.p99 as u64,
+            empty_ratio: self.array_lengths.zeros as f64 / lengths.count as f64,
+        });
+        self.top_values = self.value_counts.top();
+
+        // Constant only if every occurrence was the same scalar - no nulls or containers
+        self.constant_value = match self.top_values.as_deref() {
+            Some([only]) if only.count == self.occurrences => Some(only.value.clone()),
+            _ => None,
+        };
+    }
+
+    /// Finalize for weighted samples: density is the weighted share of documents
+    ///
+    /// `total_weight` is the sum of the weights of all documents; with every
+    /// weight 1 this is the same as `finalize`.
+    pub fn finalize_weighted(&mut self, total_samples: u64, total_weight: f64) {
+        self.finalize(total_samples);
+        if total_weight > 0.0 {
+            self.density = self.weight / total_weight;
+            self.density_interval = self.density_bounds(DENSITY_CONFIDENCE);
+        }
+    }
+
+    /// Confidence interval of the density at `confidence`
+    pub fn density_bounds(&self, confidence: f64) -> DensityInterval {
+        DensityInterval::wilson(self.density, self.total_samples, confidence)
+    }
+
+    /// Sum of the sample weights of all occurrences
+    pub fn weighted_occurrences(&self) -> f64 {
+        self.weight
+    }
+
+    /// Multiply the sample weights of all occurrences recorded so far
+    pub(crate) fn scale_weight(&mut self, factor: f64) {
+        self.weight *= factor;
+    }
+
+    fn dominant_format(&self) -> Option<FormatConsistency> {
+        let total: u64 = self.formats.values().sum();
+        // Ties go to the lower-ordered format so results are stable
+        let (format, count) = self
+            .formats
+            .iter()
+            .max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0)))?;
+
+        Some(FormatConsistency {
+            format: *format,
+            consistency: *count as f64 / total as f64 * 100.0,
+        })
+    }
+
+    /// Every distinct scalar value, most frequent first
+    ///
+    /// Unlike `top_values` the list isn't cut at `TOP_VALUES`; it is `None`
+    /// once the path held more than `TOP_VALUES_MAX_DISTINCT` distinct values.
+    pub fn value_counts(&self) -> Option<Vec<ValueCount>> {
+        self.value_counts.ranked(usize::MAX)
+    }
+
+    /// Element type counts by position, for arrays of at most `TUPLE_MAX_POSITIONS`
+    pub fn position_types(&self) -> &[HashMap<JsonType, u64>] {
+        &self.position_types
+    }
+
+    /// Share of non-null occurrences that are distinct (1.0 = every value unique)
+    pub fn cardinality_ratio(&self) -> f64 {
+        let non_null = self.occurrences - self.null_count;
+        if non_null == 0 {
+            return 0.0;
+        }
+        self.cardinality as f64 / non_null as f64
+    }
+}
+
+/// Approximate in-memory size of a JSON value
+fn value_size(value: &Value) -> usize {
+    const NODE: usize = std::mem::size_of::<Value>();
+    match value {
+        Value::Null | Value::Bool(_) | Value::Number(_) => NODE,
+        Value::String(s) => NODE + s.len(),
+        Value::Array(items) => NODE + items.iter().map(value_size).sum::<usize>(),
+        Value::Object(map) => {
+            NODE + map
+                .iter()
+                .map(|(k, v)| k.len() + NODE + value_size(v))
+                .sum::<usize>()
+        }
+    }
+}
+
+/// Two-sided z-score for a confidence level (e.g. 0.95 -> ~1.96)
+///
+/// Uses Acklam's rational approximation of the inverse normal CDF, which is
+/// accurate to ~1e-9 - far more than density estimates need.
+pub fn z_score(confidence: f64) -> f64 {
+    let p = 1.0 - (1.0 - confidence.clamp(0.0, 0.999_999)) / 2.0;
+
+    const A: [f64; 6] = [
+        -3.969_683_028_665_376e1,
+        2.209_460_984_245_205e2,
+        -2.759_285_104_469_687e2,
+        1.383_577_518_672_69e2,
+        -3.066_479_806_614_716e1,
+        2.506_628_277_459_239,
+    ];
+    const B: [f64; 5] = [
+        -5.447_609_879_822_406e1,
+        1.615_858_368_580_409e2,
+        -1.556_989_798_598_866e2,
+        6.680_131_188_771_972e1,
+        -1.328_068_155_288_572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784_894_002_430_293e-3,
+        -3.223_964_580_411_365e-1,
+        -2.400_758_277_161_838,
+        -2.549_732_539_343_734,
+        4.374_664_141_464_968,
+        2.938_163_982_698_783,
+    ];
+    const D: [f64; 4] = [
+        7.784_695_709_041_462e-3,
+        3.224_671_290_700_398e-1,
+        2.445_134_137_142_996,
+        3.754_408_661_907_416,
+    ];
+    const P_HIGH: f64 = 1.0 - 0.024_25;
+
+    if p <= P_HIGH {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    } else {
+        let q = (-2.0 * (1.0 - p).ln()).sqrt();
+        -(((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    }
+}
+
+/// Margin of error of a proportion estimate (`successes` out of `n`)
+///
+/// Uses the Agresti-Coull adjustment so that densities of exactly 0% or 100%
+/// still carry uncertainty on small samples instead of collapsing to zero.
+pub fn margin_of_error(successes: u64, n: u64, confidence: f64) -> f64 {
+    if n == 0 {
+        return f64::INFINITY;
+    }
+
+    let z = z_score(confidence);
+    let z2 = z * z;
+    let n_adj = n as f64 + z2;
+    let p_adj = ((successes.min(n) as f64) + z2 / 2.0) / n_adj;
+
+    z * (p_adj * (1.0 - p_adj) / n_adj).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_field_cardinality() {
+        let mut stats = FieldStats::new("status".to_string(), 1);
+        for i in 0..300 {
+            let status = ["active", "disabled", "pending"][i % 3];
+            stats.record(&json!(status));
+        }
+        stats.record(&json!(null));
+        stats.finalize(301);
+
+        assert_eq!(stats.cardinality, 3);
+        assert!(stats.cardinality_ratio() < 0.02);
+    }
+
+    #[test]
+    fn test_unique_field_cardinality() {
+        let mut stats = FieldStats::new("id".to_string(), 1);
+        for i in 0..1000 {
+            stats.record(&json!(i));
+        }
+        stats.finalize(1000);
+
+        assert!(stats.cardinality_ratio() > 0.95);
+        assert!(stats.cardinality <= 1000);
+    }
+
+    #[test]
+    fn test_numeric_stats() {
+        let mut stats = FieldStats::new("amount".to_string(), 1);
+        for i in 1..=100 {
+            stats.record(&json!(i));
+        }
+        stats.record(&json!("n/a"));
+        stats.finalize(101);
+
+        let numeric = stats.numeric.unwrap();
+        assert_eq!(numeric.count, 100);
+        assert_eq!(numeric.min, 1.0);
+        assert_eq!(numeric.max, 100.0);
+        assert_eq!(numeric.mean, 50.5);
+        assert_eq!(numeric.p50, 50.0);
+        assert_eq!(numeric.p90, 90.0);
+        assert_eq!(numeric.p99, 99.0);
+    }
+
+    #[test]
+    fn test_numeric_percentiles_beyond_capacity() {
+        let mut stats = FieldStats::new("latency".to_string(), 1);
+        for i in 0..100_000 {
+            stats.record(&json!(i as f64 / 10.0));
+        }
+        stats.finalize(100_000);
+
+        let numeric = stats.numeric.unwrap();
+        assert_eq!(numeric.min, 0.0);
+        assert_eq!(numeric.max, 9999.9);
+        assert!(
+            (numeric.p50 - 5000.0).abs() < 100.0,
+            "p50 = {}",
+            numeric.p50
+        );
+        assert!(
+            (numeric.p99 - 9900.0).abs() < 100.0,
+            "p99 = {}",
+            numeric.p99
+        );
+    }
+
+    #[test]
+    fn test_no_numeric_stats_for_strings() {
+        let mut stats = FieldStats::new("name".to_string(), 1);
+        stats.record(&json!("alice"));
+        stats.finalize(1);
+
+        assert!(stats.numeric.is_none());
+    }
+
+    #[test]
+    fn test_magnitude_bands() {
+        let mut stats = FieldStats::new("amount".to_string(), 1);
+        for i in 0..70 {
+            stats.record(&json!(10 + i % 40));
+        }
+        for i in 0..30 {
+            stats.record(&json!(1000 + (i % 40) * 100));
+        }
+        stats.finalize(100);
+
+        let bands = stats.numeric.unwrap().bands.unwrap();
+        assert_eq!(bands.low_median, 27.0);
+        assert_eq!(bands.high_median, 2500.0);
+        assert_eq!(bands.high_share, 0.3);
+
+        // One continuous spread isn't banded
+        let mut stats = FieldStats::new("amount".to_string(), 1);
+        for i in 1..=100 {
+            stats.record(&json!(i * 37));
+        }
+        stats.finalize(100);
+        assert!(stats.numeric.unwrap().bands.is_none());
+    }
+
+    #[test]
+    fn test_string_formats() {
+        let mut stats = FieldStats::new("user_id".to_string(), 1);
+        for _ in 0..9 {
+            stats.record(&json!("550e8400-e29b-41d4-a716-446655440000"));
+        }
+        stats.record(&json!(""));
+        stats.record(&json!(42));
+        stats.finalize(11);
+
+        assert_eq!(stats.formats[&StringFormat::Uuid], 9);
+        assert_eq!(stats.formats[&StringFormat::Empty], 1);
+        let format = stats.format.unwrap();
+        assert_eq!(format.format, StringFormat::Uuid);
+        assert_eq!(format.consistency, 90.0);
+
+        let strings = stats.strings.unwrap();
+        assert_eq!(strings.count, 10);
+        assert_eq!(strings.total_bytes, 9 * 36);
+        assert_eq!(strings.max_bytes, 36);
+        assert_eq!(strings.blob_count, 0);
+    }
+
+    #[test]
+    fn test_array_stats() {
+        let mut stats = FieldStats::new("tags".to_string(), 1);
+        stats.record(&json!([]));
+        stats.record(&json!(["a"]));
+        stats.record(&json!(["a", "b", "c"]));
+        stats.record(&json!(["a", "b"]));
+        stats.record(&json!("legacy"));
+        stats.finalize(5);
+
+        let positions = stats.position_types();
+        assert_eq!(positions.len(), 3);
+        assert_eq!(positions[0][&JsonType::String], 3);
+        assert_eq!(positions[2][&JsonType::String], 1);
+
+        let array = stats.array.unwrap();
+        assert_eq!(array.count, 4);
+        assert_eq!(array.min_length, 0);
+        assert_eq!(array.max_length, 3);
+        assert_eq!(array.avg_length, 1.5);
+        assert_eq!(array.p99_length, 3);
+        assert_eq!(array.empty_ratio, 0.25);
+        assert!(!array.is_singleton());
+        assert!(stats.numeric.is_none());
+    }
+
+    #[test]
+    fn test_singleton_arrays() {
+        let mut stats = FieldStats::new("addresses".to_string(), 1);
+        for _ in 0..10 {
+            stats.record(&json!([{"city": "Paris"}]));
+        }
+        stats.finalize(10);
+
+        assert!(stats.array.unwrap().is_singleton());
+    }
+
+    #[test]
+    fn test_top_values() {
+        let mut stats = FieldStats::new("status".to_string(), 1);
+        for (value, count) in [("active", 80), ("ACTIVE", 12), ("disabled", 8)] {
+            for _ in 0..count {
+                stats.record(&json!(value));
+            }
+        }
+        stats.record(&json!(null));
+        stats.finalize(101);
+
+        let top = stats.top_values.unwrap();
+        assert_eq!(top.len(), 3);
+        assert_eq!(top[0].value, json!("active"));
+        assert_eq!(top[0].count, 80);
+        assert_eq!(top[0].percentage, 80.0);
+        assert_eq!(top[1].value, json!("ACTIVE"));
+        assert_eq!(top[2].value, json!("disabled"));
+    }
+
+    #[test]
+    fn test_top_values_truncated() {
+        let mut stats = FieldStats::new("code".to_string(), 1);
+        for i in 0..20 {
+            stats.record(&json!(i));
+        }
+        stats.finalize(20);
+
+        assert_eq!(stats.top_values.unwrap().len(), TOP_VALUES);
+    }
+
+    #[test]
+    fn test_no_top_values_for_high_cardinality() {
+        let mut stats = FieldStats::new("id".to_string(), 1);
+        for i in 0..=TOP_VALUES_MAX_DISTINCT {
+            stats.record(&json!(i));
+        }
+        stats.record(&json!(0));
+        stats.finalize(TOP_VALUES_MAX_DISTINCT as u64 + 2);
+
+        assert!(stats.top_values.is_none());
+    }
+
+    #[test]
+    fn test_constant_value() {
+        let mut stats = FieldStats::new("feature_enabled".to_string(), 1);
+        for _ in 0..20 {
+            stats.record(&json!(true));
+        }
+        stats.finalize(20);
+        assert_eq!(stats.constant_value, Some(json!(true)));
+
+        stats.record(&json!(null));
+        stats.finalize(21);
+        assert_eq!(stats.constant_value, None);
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut us = FieldStats::new("counts.US".to_string(), 2);
+        for i in 0..10 {
+            us.record(&json!(i));
+        }
+        let mut de = FieldStats::new("counts.DE".to_string(), 2);
+        de.record(&json!(100));
+        de.record(&json!(null));
+
+        us.merge(&de);
+        us.finalize(20);
+
+        assert_eq!(us.occurrences, 12);
+        assert_eq!(us.null_count, 1);
+        assert_eq!(us.types[&JsonType::Number], 11);
+        assert_eq!(us.cardinality, 11);
+        let numeric = us.numeric.unwrap();
+        assert_eq!(numeric.count, 11);
+        assert_eq!(numeric.max, 100.0);
+        assert_eq!(us.top_values.unwrap().len(), TOP_VALUES);
+    }
+
+    #[test]
+    fn test_examples_sampled_across_stream() {
+        let mut stats = FieldStats::new("n".to_string(), 1);
+        for i in 0..10_000 {
+            stats.record(&json!(i));
+        }
+        stats.record_repeated(&json!(-1), 10_000);
+
+        assert_eq!(stats.examples.len(), DEFAULT_EXAMPLES);
+        // Not just the first values seen
+        assert!(stats.examples.iter().any(|v| v.as_i64().unwrap() >= 1000));
+        assert!(stats.examples.contains(&json!(-1)));
+    }
+
+    #[test]
+    fn test_max_examples() {
+        let mut stats = FieldStats::new("n".to_string(), 1).with_max_examples(3);
+        for i in 0..100 {
+            stats.record(&json!(i));
+        }
+        assert_eq!(stats.examples.len(), 3);
+
+        let mut other = FieldStats::new("n".to_string(), 1);
+        other.record_repeated(&json!("x"), 50);
+        stats.merge(&other);
+        assert_eq!(stats.examples.len(), 3);
+
+        let fewer = stats.with_max_examples(1);
+        assert_eq!(fewer.examples.len(), 1);
+    }
+
+    #[test]
+    fn test_approx_bytes_counts_examples() {
+        let mut small = FieldStats::new("doc".to_string(), 1);
+        small.record(&json!("x"));
+
+        let mut large = FieldStats::new("doc".to_string(), 1);
+        large.record(&json!("x".repeat(100_000)));
+
+        assert!(small.approx_bytes() >= HyperLogLog::BYTES);
+        assert!(large.approx_bytes() > small.approx_bytes() + 100_000);
+    }
+
+    #[test]
+    fn test_redact() {
+        let mut stats = FieldStats::new("email".to_string(), 1);
+        for _ in 0..10 {
+            stats.record(&json!("alice@example.com"));
+        }
+        stats.finalize(10);
+
+        let mut masked = stats.clone();
+        masked.redact(Redaction::Mask);
+        assert_eq!(masked.examples[0], json!("xxxxx@xxxxxxx.xxx"));
+        assert_eq!(
+            masked.top_values.unwrap()[0].value,
+            json!("xxxxx@xxxxxxx.xxx")
+        );
+        assert_eq!(masked.constant_value, Some(json!("xxxxx@xxxxxxx.xxx")));
+
+        stats.redact(Redaction::Drop);
+        assert!(stats.examples.is_empty());
+        assert!(stats.top_values.is_none());
+        assert!(stats.constant_value.is_none());
+    }
+
+    #[test]
+    fn test_z_score_common_levels() {
+        assert!((z_score(0.90) - 1.6449).abs() < 1e-3);
+        assert!((z_score(0.95) - 1.9600).abs() < 1e-3);
+        assert!((z_score(0.99) - 2.5758).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_state_round_trip() {
+        let mut stats = FieldStats::new("amount".to_string(), 2);
+        for i in 0..5000 {
+            stats.record(&json!(i % 40));
+        }
+        stats.record(&json!("n/a"));
+        stats.record(&json!([1, 2]));
+        stats.record(&json!(null));
+
+        let serialized = serde_json::to_string(&stats.state()).unwrap();
+        let mut restored = FieldStats::from_state(serde_json::from_str(&serialized).unwrap());
+        stats.finalize(6000);
+        restored.finalize(6000);
+
+        assert_eq!(restored.occurrences, stats.occurrences);
+        assert_eq!(restored.density, stats.density);
+        assert_eq!(restored.types, stats.types);
+        assert_eq!(restored.examples, stats.examples);
+        assert_eq!(restored.cardinality, stats.cardinality);
+        assert_eq!(restored.numeric, stats.numeric);
+        assert_eq!(restored.formats, stats.formats);
+        assert_eq!(restored.strings, stats.strings);
+        assert_eq!(restored.array, stats.array);
+        assert_eq!(restored.position_types(), stats.position_types());
+        assert_eq!(restored.top_values, stats.top_values);
+    }
+
+    #[test]
+    fn test_record_repeated_matches_record() {
+        let values = [
+            (json!(3), 5000),
+            (json!("pro"), 7),
+            (json!([1, 2]), 3),
+            (json!(null), 2),
+            (json!(0), 1),
+        ];
+
+        let mut single = FieldStats::new("x".to_string(), 1);
+        let mut repeated = FieldStats::new("x".to_string(), 1);
+        for (value, times) in &values {
+            for _ in 0..*times {
+                single.record(value);
+            }
+            repeated.record_repeated(value, *times);
+        }
+        single.finalize(6000);
+        repeated.finalize(6000);
+
+        assert_eq!(single.occurrences, repeated.occurrences);
+        assert_eq!(single.null_count, repeated.null_count);
+        assert_eq!(single.types, repeated.types);
+        // Both are random samples of the same occurrences, not the same draw
+        assert_eq!(single.examples.len(), repeated.examples.len());
+        assert_eq!(single.numeric, repeated.numeric);
+        assert_eq!(single.array, repeated.array);
+        assert_eq!(single.top_values, repeated.top_values);
+        assert_eq!(single.cardinality, repeated.cardinality);
+        assert_eq!(single.numeric_values.sample, repeated.numeric_values.sample);
+    }
+
+    #[test]
+    fn test_margin_of_error_shrinks_with_samples() {
+        let small = margin_of_error(50, 100, 0.95);
+        let large = margin_of_error(5000, 10000, 0.95);
+
+        assert!(small > large);
+        // Worst case p=0.5 at n=10000 is just under 1%
+        assert!((large - 0.0098).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_margin_of_error_nonzero_at_full_density() {
+        // 10/10 present is not proof of 100% density
+        assert!(margin_of_error(10, 10, 0.95) > 0.1);
+    }
+
+    #[test]
+    fn test_density_interval() {
+        let interval = DensityInterval::wilson(0.95, 100, 0.95);
+        assert!((interval.lower - 0.888).abs() < 1e-3);
+        assert!((interval.upper - 0.978).abs() < 1e-3);
+        assert!((interval.margin(0.95) - 0.062).abs() < 1e-3);
+
+        // Narrower with more samples, and never a single point at 100%
+        let large = DensityInterval::wilson(0.95, 50_000, 0.95);
+        assert!(large.upper - large.lower < 0.005);
+        let full = DensityInterval::wilson(1.0, 20, 0.95);
+        assert_eq!(full.upper, 1.0);
+        assert!(full.lower < 0.85);
+
+        // A zero confidence level gives the point estimate
+        let point = DensityInterval::wilson(0.3, 10, 0.0);
+        assert!((point.lower - 0.3).abs() < 1e-9 && (point.upper - 0.3).abs() < 1e-9);
+
+        let mut stats = FieldStats::new("a".to_string(), 1);
+        stats.occurrences = 950;
+        stats.finalize(1000);
+        assert!(stats.density_interval.lower > 0.93 && stats.density_interval.upper < 0.97);
+    }
+
+    #[test]
+    fn test_margin_of_error_without_samples() {
+        assert!(margin_of_error(0, 0, 0.95).is_infinite());
     }
 }
@@ -0,0 +1,128 @@
+//! Field hierarchy of a column's documents
+//!
+//! Field stats are flat, one per path (`user.address.city`, `tags[]`). The
+//! tree nests every path under its parent, with array elements as `[]`
+//! children of the array.
+
+use crate::stats::FieldStats;
+use std::collections::BTreeMap;
+
+/// A key of the field hierarchy and the keys nested under it
+#[derive(Debug, Clone)]
+pub struct FieldNode<'a> {
+    /// Key of the node, `[]` for the elements of an array
+    pub key: String,
+    /// Full path of the node, as in `FieldStats::path`
+    pub path: String,
+    /// Stats of the path, `None` if only paths below it were tracked
+    pub stats: Option<&'a FieldStats>,
+    /// Nested keys, alphabetical
+    pub children: Vec<FieldNode<'a>>,
+}
+
+#[derive(Default)]
+struct Branch<'a> {
+    path: String,
+    stats: Option<&'a FieldStats>,
+    children: BTreeMap<String, Branch<'a>>,
+}
+
+impl<'a> Branch<'a> {
+    fn into_node(self, key: String) -> FieldNode<'a> {
+        FieldNode {
+            key,
+            path: self.path,
+            stats: self.stats,
+            children: self
+                .children
+                .into_iter()
+                .map(|(key, branch)| branch.into_node(key))
+                .collect(),
+        }
+    }
+}
+
+/// Arrange field stats into their hierarchy, returning the top-level keys
+pub fn field_tree(field_stats: &[FieldStats]) -> Vec<FieldNode<'_>> {
+    let mut root = Branch::default();
+    for fs in field_stats {
+        let mut branch = &mut root;
+        for (key, path) in path_segments(&fs.path) {
+            branch = branch.children.entry(key).or_insert_with(|| Branch {
+                path,
+                ..Default::default()
+            });
+        }
+        branch.stats = Some(fs);
+    }
+    root.into_node(String::new()).children
+}
+
+/// Keys of a path with the path up to each of them:
+/// `a.b[].c` gives `a`, `b`, `[]` and `c`
+fn path_segments(path: &str) -> Vec<(String, String)> {
+    let mut segments = Vec::new();
+    let mut prefix = String::new();
+    for part in path.split('.') {
+        let key = part.trim_end_matches("[]");
+        if !prefix.is_empty() {
+            prefix.push('.');
+        }
+        prefix.push_str(key);
+        segments.push((key.to_string(), prefix.clone()));
+        for _ in 0..(part.len() - key.len()) / 2 {
+            prefix.push_str("[]");
+            segments.push(("[]".to_string(), prefix.clone()));
+        }
+    }
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(path: &str) -> FieldStats {
+        FieldStats::new(path.to_string(), path.split('.').count())
+    }
+
+    #[test]
+    fn test_path_segments() {
+        let keys = |path| {
+            path_segments(path)
+                .into_iter()
+                .map(|(key, path)| format!("{}={}", key, path))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(keys("id"), ["id=id"]);
+        assert_eq!(keys("a.b[].c"), ["a=a", "b=a.b", "[]=a.b[]", "c=a.b[].c"]);
+        assert_eq!(keys("grid[][]"), ["grid=grid", "[]=grid[]", "[]=grid[][]"]);
+    }
+
+    #[test]
+    fn test_field_tree() {
+        let field_stats = vec![
+            stats("user.name"),
+            stats("tags"),
+            stats("tags[]"),
+            stats("user.address.city"),
+            stats("user"),
+        ];
+        let tree = field_tree(&field_stats);
+
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree[0].key, "tags");
+        assert_eq!(tree[0].children[0].key, "[]");
+        assert_eq!(tree[0].children[0].path, "tags[]");
+
+        let user = &tree[1];
+        assert_eq!(user.stats.map(|s| s.path.as_str()), Some("user"));
+        assert_eq!(user.children.len(), 2);
+        let address = &user.children[0];
+        assert_eq!(address.path, "user.address");
+        // Only its child was tracked
+        assert!(address.stats.is_none());
+        assert_eq!(address.children[0].path, "user.address.city");
+        assert_eq!(user.children[1].key, "name");
+    }
+}
@@ -1,15 +1,21 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::fmt;
 
 /// Represet the different json types we can encouter
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum JsonType {
+    #[serde(alias = "null")]
     Null,
+    #[serde(alias = "boolean")]
     Boolean,
+    #[serde(alias = "number")]
     Number,
+    #[serde(alias = "string")]
     String,
+    #[serde(alias = "array")]
     Array,
+    #[serde(alias = "object")]
     Object,
 }
 
@@ -25,6 +31,19 @@ impl JsonType {
             Value::Object(_) => JsonType::Object,
         }
     }
+
+    /// Parse the name returned by Postgres' `jsonb_typeof`
+    pub fn from_jsonb_typeof(name: &str) -> Option<Self> {
+        match name {
+            "null" => Some(JsonType::Null),
+            "boolean" => Some(JsonType::Boolean),
+            "number" => Some(JsonType::Number),
+            "string" => Some(JsonType::String),
+            "array" => Some(JsonType::Array),
+            "object" => Some(JsonType::Object),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for JsonType {
@@ -58,6 +77,24 @@ mod tests {
         assert_eq!(JsonType::from_value(&json!({})), JsonType::Object);
     }
 
+    #[test]
+    fn test_json_type_from_jsonb_typeof() {
+        for json_type in [
+            JsonType::Null,
+            JsonType::Boolean,
+            JsonType::Number,
+            JsonType::String,
+            JsonType::Array,
+            JsonType::Object,
+        ] {
+            assert_eq!(
+                JsonType::from_jsonb_typeof(&json_type.to_string()),
+                Some(json_type)
+            );
+        }
+        assert_eq!(JsonType::from_jsonb_typeof("integer"), None);
+    }
+
     #[test]
     fn test_json_type_display() {
         assert_eq!(JsonType::Null.to_string(), "null");
@@ -0,0 +1,228 @@
+use crate::stats::FieldStats;
+use crate::types::JsonType;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// Configuration for unused field detection
+#[derive(Debug, Clone)]
+pub struct UsageConfig {
+    /// Average bytes per document from which an unused path is a deprecation
+    /// candidate (default: 32)
+    pub min_candidate_bytes: f64,
+}
+
+impl Default for UsageConfig {
+    fn default() -> Self {
+        Self {
+            min_candidate_bytes: 32.0,
+        }
+    }
+}
+
+/// A path no known query refers to
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UnusedField {
+    pub path: String,
+    pub density: f64,
+    /// Approximate bytes the path and everything below it take per document
+    pub bytes_per_document: f64,
+    /// Large enough to be worth deprecating
+    pub candidate: bool,
+}
+
+/// Estimate which paths of a column no query refers to
+///
+/// `queries` is SQL the database runs (see `pgdrift_db::discovery::query_texts`);
+/// only texts naming both the table and the column are considered. A key
+/// counts as referenced when it appears in one of them as a whole word, which
+/// covers `->'key'`, `#>'{a,key}'`, `@> '{"key": ...}'` and jsonpath
+/// `$.a.key`. A path is used when its own key is referenced, or when an
+/// ancestor is referenced without any of that ancestor's children, since the
+/// whole object is then read. Only the topmost unused path of a subtree is
+/// reported, largest first.
+pub fn find_unused_fields(
+    table: &str,
+    column: &str,
+    field_stats: &[FieldStats],
+    queries: &[String],
+    config: &UsageConfig,
+) -> Vec<UnusedField> {
+    let relevant: Vec<&str> = queries
+        .iter()
+        .map(String::as_str)
+        .filter(|query| {
+            let lower = query.to_lowercase();
+            mentions(&lower, &table.to_lowercase()) && mentions(&lower, &column.to_lowercase())
+        })
+        .collect();
+
+    let mut keys: HashSet<&str> = HashSet::new();
+    for fs in field_stats {
+        keys.extend(path_keys(&fs.path));
+    }
+    let referenced: HashSet<&str> = keys
+        .into_iter()
+        .filter(|key| *key != "*" && relevant.iter().any(|query| mentions(query, key)))
+        .collect();
+
+    let by_path: HashMap<&str, &FieldStats> = field_stats
+        .iter()
+        .map(|fs| (fs.path.as_str(), fs))
+        .collect();
+    // Paths with at least one referenced child
+    let mut opened: HashSet<&str> = HashSet::new();
+    for fs in field_stats {
+        if let Some((parent, key)) = split_path(&fs.path)
+            && referenced.contains(key)
+        {
+            opened.insert(parent);
+        }
+    }
+
+    let used = |path: &str| -> bool {
+        let mut current = path;
+        let mut own = true;
+        loop {
+            let key = path_keys(current).last().unwrap_or_default();
+            if referenced.contains(key) && (own || !opened.contains(current)) {
+                return true;
+            }
+            own = false;
+            match split_path(current) {
+                Some((parent, _)) => current = parent,
+                None => return false,
+            }
+        }
+    };
+
+    let mut unused: Vec<UnusedField> = field_stats
+        .iter()
+        .filter(|fs| !fs.path.is_empty() && !used(&fs.path))
+        .filter(|fs| match split_path(&fs.path) {
+            // Children of an unused path are covered by it
+            Some((parent, _)) => !by_path.contains_key(parent) || used(parent),
+            None => true,
+        })
+        .map(|fs| {
+            let bytes_per_document = subtree_bytes(&fs.path, field_stats);
+            UnusedField {
+                path: fs.path.clone(),
+                density: fs.density,
+                bytes_per_document,
+                candidate: bytes_per_document >= config.min_candidate_bytes,
+            }
+        })
+        .collect();
+    unused.sort_by(|a, b| {
+        b.bytes_per_document
+            .total_cmp(&a.bytes_per_document)
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    unused
+}
+
+/// Keys along a path, without array markers (`items[].sku` → `items`, `sku`)
+fn path_keys(path: &str) -> impl Iterator<Item = &str> {
+    path.split('.')
+        .map(|key| key.trim_end_matches("[]"))
+        .filter(|key| !key.is_empty())
+}
+
+/// Split `a.b[].c` into (`a.b[]`, `c`); top-level paths have no parent
+fn split_path(path: &str) -> Option<(&str, &str)> {
+    path.rsplit_once('.')
+        .map(|(parent, key)| (parent, key.trim_end_matches("[]")))
+}
+
+/// Approximate bytes per document of a path and its descendants
+///
+/// Each occurrence costs the key, 8 bytes of jsonb entry headers and the
+/// value: the average string size, or 8 bytes for a number.
+fn subtree_bytes(path: &str, field_stats: &[FieldStats]) -> f64 {
+    field_stats
+        .iter()
+        .filter(|fs| {
+            fs.path == path
+                || fs
+                    .path
+                    .strip_prefix(path)
+                    .is_some_and(|rest| rest.starts_with('.') || rest.starts_with("[]"))
+        })
+        .map(|fs| {
+            let key = path_keys(&fs.path).last().unwrap_or_default();
+            let strings = fs.strings.as_ref().map_or(0.0, |s| s.total_bytes as f64);
+            let numbers = fs.types.get(&JsonType::Number).copied().unwrap_or(0) as f64 * 8.0;
+            let total = (8 + key.len()) as f64 * fs.occurrences as f64 + strings + numbers;
+            total / fs.total_samples.max(1) as f64
+        })
+        .sum()
+}
+
+/// Whether `word` appears in `text` with no identifier character on either side
+fn mentions(text: &str, word: &str) -> bool {
+    if word.is_empty() {
+        return false;
+    }
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    text.match_indices(word).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + word.len()..].chars().next();
+        !before.is_some_and(is_ident) && !after.is_some_and(is_ident)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::JsonAnalyzer;
+    use serde_json::json;
+
+    #[test]
+    fn test_find_unused_fields() {
+        let mut analyzer = JsonAnalyzer::new();
+        for i in 0..100 {
+            analyzer.analyze(&json!({
+                "email": format!("user{}@example.com", i),
+                "customer": {"id": i, "phone": "+1 555 0100"},
+                "prefs": {"theme": "dark", "lang": "en"},
+                "legacy": {"notes": "x".repeat(200), "flags": [1, 2]},
+                "tag": "a",
+            }));
+        }
+        let stats: Vec<FieldStats> = analyzer.finalize().into_values().collect();
+        let queries = vec![
+            "SELECT id FROM users WHERE metadata->>$1 = $2 AND metadata->'customer'->>'id' = $3"
+                .to_string(),
+            "CREATE INDEX users_email ON public.users USING btree (((metadata ->> 'email'::text)))"
+                .to_string(),
+            "SELECT metadata->'prefs' FROM users".to_string(),
+            // Another table
+            "SELECT data->>'tag' FROM orders".to_string(),
+        ];
+
+        let unused = find_unused_fields(
+            "users",
+            "metadata",
+            &stats,
+            &queries,
+            &UsageConfig::default(),
+        );
+        let found: Vec<_> = unused
+            .iter()
+            .map(|u| (u.path.as_str(), u.candidate))
+            .collect();
+        assert_eq!(
+            found,
+            vec![("legacy", true), ("customer.phone", false), ("tag", false)]
+        );
+        assert!(unused[0].bytes_per_document > 200.0);
+    }
+
+    #[test]
+    fn test_mentions() {
+        assert!(mentions("data->>'email'", "email"));
+        assert!(mentions("jsonb_path_query(data, '$.a.email')", "email"));
+        assert!(!mentions("data->>'email_verified'", "email"));
+        assert!(!mentions("users_archive", "users"));
+    }
+}
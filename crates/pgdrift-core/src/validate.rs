@@ -0,0 +1,276 @@
+use crate::drift::Severity;
+use crate::stats::{FieldStats, TOP_VALUES_MAX_DISTINCT};
+use crate::types::JsonType;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+/// Expected shape of a column, usually committed next to the code
+///
+/// ```toml
+/// [[paths]]
+/// path = "status"
+/// required = true
+/// types = ["string"]
+/// values = ["pending", "paid", "refunded"]
+///
+/// [[paths]]
+/// path = "customer.email"
+/// types = ["string"]
+/// max_null_percentage = 5.0
+/// severity = "warning"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SchemaSpec {
+    pub paths: Vec<PathSpec>,
+}
+
+/// Rules for one path, written the way pgdrift reports paths (`items[].sku`)
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PathSpec {
+    pub path: String,
+    /// Every document must contain the path
+    pub required: bool,
+    /// Types its non-null values may have; any type if empty
+    pub types: Vec<JsonType>,
+    /// Values it may hold (an enum); any value if empty
+    pub values: Vec<Value>,
+    /// Most documents, as a percentage, in which it may be null
+    pub max_null_percentage: Option<f64>,
+    /// Severity of every violation of this path, instead of the rule's default
+    pub severity: Option<Severity>,
+}
+
+/// Which rule of a path spec was broken
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Rule {
+    Required,
+    Types,
+    Values,
+    Nulls,
+}
+
+impl Rule {
+    /// Severity used unless the path spec sets one
+    pub fn default_severity(self) -> Severity {
+        match self {
+            Rule::Required | Rule::Types => Severity::Critical,
+            Rule::Values | Rule::Nulls => Severity::Warning,
+        }
+    }
+}
+
+/// A sampled column breaking a rule of the spec
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Violation {
+    pub path: String,
+    pub rule: Rule,
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// Check sampled field stats against a spec
+///
+/// Returns violations in spec order, most severe first within a path.
+pub fn validate(stats: &HashMap<String, FieldStats>, spec: &SchemaSpec) -> Vec<Violation> {
+    let mut violations = Vec::new();
+
+    for path_spec in &spec.paths {
+        let mut found = Vec::new();
+        let field = stats.get(&path_spec.path);
+
+        if path_spec.required {
+            match field {
+                None => found.push((Rule::Required, "Required path never present".to_string())),
+                Some(fs) if fs.density < 1.0 => found.push((
+                    Rule::Required,
+                    format!(
+                        "Required path missing from {:.1}% of documents",
+                        (1.0 - fs.density) * 100.0
+                    ),
+                )),
+                Some(_) => {}
+            }
+        }
+
+        if let Some(fs) = field {
+            found.extend(check_types(fs, &path_spec.types));
+            found.extend(check_values(fs, &path_spec.values));
+            found.extend(check_nulls(fs, path_spec.max_null_percentage));
+        }
+
+        let mut path_violations: Vec<Violation> = found
+            .into_iter()
+            .map(|(rule, message)| Violation {
+                path: path_spec.path.clone(),
+                rule,
+                severity: path_spec.severity.unwrap_or(rule.default_severity()),
+                message,
+            })
+            .collect();
+        path_violations.sort_by_key(|v| Reverse(v.severity));
+        violations.extend(path_violations);
+    }
+
+    violations
+}
+
+fn check_types(fs: &FieldStats, expected: &[JsonType]) -> Option<(Rule, String)> {
+    if expected.is_empty() {
+        return None;
+    }
+
+    let mut unexpected: Vec<(JsonType, u64)> = fs
+        .types
+        .iter()
+        .filter(|(json_type, _)| **json_type != JsonType::Null && !expected.contains(json_type))
+        .map(|(json_type, count)| (*json_type, *count))
+        .collect();
+    if unexpected.is_empty() {
+        return None;
+    }
+    unexpected.sort_by_key(|(_, count)| Reverse(*count));
+
+    let total: u64 = fs.types.values().sum();
+    let found: Vec<String> = unexpected
+        .iter()
+        .map(|(json_type, count)| {
+            format!(
+                "{} ({:.1}%)",
+                json_type,
+                *count as f64 / total.max(1) as f64 * 100.0
+            )
+        })
+        .collect();
+    Some((
+        Rule::Types,
+        format!("Unexpected types: {}", found.join(", ")),
+    ))
+}
+
+fn check_values(fs: &FieldStats, allowed: &[Value]) -> Option<(Rule, String)> {
+    if allowed.is_empty() {
+        return None;
+    }
+
+    // Values are only counted for paths with few distinct values
+    let Some(counts) = fs.value_counts() else {
+        return Some((
+            Rule::Values,
+            format!(
+                "More than {} distinct values, expected one of {}",
+                TOP_VALUES_MAX_DISTINCT,
+                allowed.len()
+            ),
+        ));
+    };
+
+    let unexpected: Vec<String> = counts
+        .iter()
+        .filter(|vc| !vc.value.is_null() && !allowed.contains(&vc.value))
+        .map(|vc| format!("{} ({})", vc.value, vc.count))
+        .collect();
+    if unexpected.is_empty() {
+        return None;
+    }
+    Some((
+        Rule::Values,
+        format!("Unexpected values: {}", unexpected.join(", ")),
+    ))
+}
+
+fn check_nulls(fs: &FieldStats, max_percentage: Option<f64>) -> Option<(Rule, String)> {
+    let max_percentage = max_percentage?;
+    let percentage = fs.null_count as f64 / fs.total_samples.max(1) as f64 * 100.0;
+    (percentage > max_percentage).then(|| {
+        (
+            Rule::Nulls,
+            format!(
+                "Null in {:.1}% of documents, at most {:.1}% allowed",
+                percentage, max_percentage
+            ),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::JsonAnalyzer;
+    use serde_json::json;
+
+    fn analyze(documents: &[Value]) -> HashMap<String, FieldStats> {
+        let mut analyzer = JsonAnalyzer::new();
+        for document in documents {
+            analyzer.analyze(document);
+        }
+        analyzer.finalize()
+    }
+
+    #[test]
+    fn test_validate() {
+        let documents: Vec<_> = (0..100)
+            .map(|i| match i % 10 {
+                0 => json!({"status": "lost", "email": null}),
+                1 => json!({"id": i.to_string(), "status": "paid", "email": null}),
+                _ => json!({"id": i, "status": "paid", "email": "a@example.com"}),
+            })
+            .collect();
+
+        let spec = SchemaSpec {
+            paths: vec![
+                PathSpec {
+                    path: "id".to_string(),
+                    required: true,
+                    types: vec![JsonType::Number],
+                    ..Default::default()
+                },
+                PathSpec {
+                    path: "status".to_string(),
+                    required: true,
+                    values: vec![json!("pending"), json!("paid")],
+                    ..Default::default()
+                },
+                PathSpec {
+                    path: "email".to_string(),
+                    max_null_percentage: Some(10.0),
+                    severity: Some(Severity::Info),
+                    ..Default::default()
+                },
+                PathSpec {
+                    path: "shipped_at".to_string(),
+                    types: vec![JsonType::String],
+                    ..Default::default()
+                },
+            ],
+        };
+
+        let described: Vec<_> = validate(&analyze(&documents), &spec)
+            .iter()
+            .map(|v| format!("{} {:?} {:?}: {}", v.path, v.rule, v.severity, v.message))
+            .collect();
+        assert_eq!(
+            described,
+            vec![
+                "id Required Critical: Required path missing from 10.0% of documents",
+                "id Types Critical: Unexpected types: string (11.1%)",
+                "status Values Warning: Unexpected values: \"lost\" (10)",
+                "email Nulls Info: Null in 20.0% of documents, at most 10.0% allowed",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_spec() {
+        let spec: SchemaSpec = serde_json::from_value(json!({
+            "paths": [{"path": "status", "types": ["string"], "severity": "critical"}]
+        }))
+        .unwrap();
+        assert_eq!(spec.paths[0].types, vec![JsonType::String]);
+        assert_eq!(spec.paths[0].severity, Some(Severity::Critical));
+        assert!(!spec.paths[0].required);
+    }
+}
@@ -0,0 +1,260 @@
+use crate::analyzer::is_wildcard_path;
+use crate::drift::{DriftIssue, EvolutionPattern, VersionDifference, VersionShare};
+use crate::segment::lookup;
+use crate::stats::FieldStats;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Configuration for comparing the versions of a version marker
+#[derive(Debug, Clone)]
+pub struct VersionConfig {
+    /// Minimum documents a version needs to be compared (default: 10)
+    pub min_documents: u64,
+    /// Smallest difference in density between two versions from which a
+    /// field differs between them (default: 0.5)
+    pub min_density_gap: f64,
+}
+
+impl Default for VersionConfig {
+    fn default() -> Self {
+        Self {
+            min_documents: 10,
+            min_density_gap: 0.5,
+        }
+    }
+}
+
+/// Find the fields that differ between the versions of each version marker
+///
+/// The documents are split by the marker's value, and the density of every
+/// other field is measured within each version. A field whose density is
+/// far apart in two versions with enough documents (`name` in v1, `first_name`
+/// and `last_name` in v2) is added to the marker's `differences`, largest gap
+/// first. Markers under arrays or collapsed maps are left as they are.
+pub fn compare_versions(
+    issues: Vec<DriftIssue>,
+    samples: &[Value],
+    stats: &HashMap<String, FieldStats>,
+    config: &VersionConfig,
+) -> Vec<DriftIssue> {
+    issues
+        .into_iter()
+        .map(|issue| match issue {
+            DriftIssue::SchemaEvolution {
+                path,
+                pattern:
+                    EvolutionPattern::VersionMarker {
+                        marker_path,
+                        versions,
+                        differences,
+                    },
+            } => {
+                let differences = if versions.len() >= 2 && comparable(&marker_path) {
+                    version_differences(&marker_path, &versions, samples, stats, config)
+                } else {
+                    differences
+                };
+                DriftIssue::SchemaEvolution {
+                    path,
+                    pattern: EvolutionPattern::VersionMarker {
+                        marker_path,
+                        versions,
+                        differences,
+                    },
+                }
+            }
+            issue => issue,
+        })
+        .collect()
+}
+
+fn version_differences(
+    marker_path: &str,
+    versions: &[VersionShare],
+    samples: &[Value],
+    stats: &HashMap<String, FieldStats>,
+    config: &VersionConfig,
+) -> Vec<VersionDifference> {
+    let mut paths: Vec<&str> = stats
+        .keys()
+        .map(String::as_str)
+        .filter(|path| comparable(path) && !is_related(path, marker_path))
+        .collect();
+    paths.sort();
+
+    let mut documents = vec![0u64; versions.len()];
+    let mut present = vec![vec![0u64; versions.len()]; paths.len()];
+    for document in samples {
+        let Some(value) = lookup(document, marker_path) else {
+            continue;
+        };
+        let Some(v) = versions.iter().position(|version| version.value == *value) else {
+            continue;
+        };
+        documents[v] += 1;
+        for (p, path) in paths.iter().enumerate() {
+            if lookup(document, path).is_some() {
+                present[p][v] += 1;
+            }
+        }
+    }
+
+    let compared: Vec<usize> = (0..versions.len())
+        .filter(|&v| documents[v] >= config.min_documents)
+        .collect();
+    if compared.len() < 2 {
+        return Vec::new();
+    }
+
+    let mut differences: Vec<(f64, VersionDifference)> = paths
+        .iter()
+        .zip(&present)
+        .filter_map(|(path, present)| {
+            let densities: Vec<f64> = present
+                .iter()
+                .zip(&documents)
+                .map(|(&present, &documents)| {
+                    if documents == 0 {
+                        0.0
+                    } else {
+                        present as f64 / documents as f64
+                    }
+                })
+                .collect();
+            let (min, max) = compared
+                .iter()
+                .map(|&v| densities[v])
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), density| {
+                    (min.min(density), max.max(density))
+                });
+            (max - min >= config.min_density_gap).then(|| {
+                (
+                    max - min,
+                    VersionDifference {
+                        path: path.to_string(),
+                        densities,
+                    },
+                )
+            })
+        })
+        .collect();
+    differences.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.path.cmp(&b.1.path)));
+    differences
+        .into_iter()
+        .map(|(_, difference)| difference)
+        .collect()
+}
+
+/// Whether a path can be looked up once per document
+fn comparable(path: &str) -> bool {
+    !path.contains("[]") && !is_wildcard_path(path)
+}
+
+/// Whether the paths are the same or one is nested inside the other
+fn is_related(a: &str, b: &str) -> bool {
+    let nested = |outer: &str, inner: &str| {
+        inner.len() > outer.len()
+            && inner.starts_with(outer)
+            && inner.as_bytes()[outer.len()] == b'.'
+    };
+    a == b || nested(a, b) || nested(b, a)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::JsonAnalyzer;
+    use crate::drift::{DriftConfig, detect_drift};
+    use serde_json::json;
+
+    fn marker(samples: &[Value]) -> DriftIssue {
+        let mut analyzer = JsonAnalyzer::new();
+        for sample in samples {
+            analyzer.analyze(sample);
+        }
+        let stats = analyzer.finalize();
+        let issues = detect_drift(&stats, &DriftConfig::default());
+        compare_versions(issues, samples, &stats, &VersionConfig::default())
+            .into_iter()
+            .find(|issue| {
+                matches!(
+                    issue,
+                    DriftIssue::SchemaEvolution {
+                        pattern: EvolutionPattern::VersionMarker { .. },
+                        ..
+                    }
+                )
+            })
+            .expect("version marker")
+    }
+
+    #[test]
+    fn test_compare_versions() {
+        let samples: Vec<Value> = (0..200)
+            .map(|i| match i % 5 {
+                0..=2 => {
+                    json!({"id": i, "meta": {"version": 2}, "first_name": "Ada", "last_name": "L"})
+                }
+                _ => json!({"id": i, "meta": {"version": 1}, "name": "Ada L"}),
+            })
+            .collect();
+
+        let issue = marker(&samples);
+        let DriftIssue::SchemaEvolution {
+            pattern:
+                EvolutionPattern::VersionMarker {
+                    marker_path,
+                    versions,
+                    differences,
+                },
+            ..
+        } = &issue
+        else {
+            unreachable!()
+        };
+        assert_eq!(marker_path, "meta.version");
+        let shares: Vec<_> = versions
+            .iter()
+            .map(|v| (v.value.clone(), v.documents))
+            .collect();
+        assert_eq!(shares, vec![(json!(2), 120), (json!(1), 80)]);
+
+        let found: Vec<_> = differences
+            .iter()
+            .map(|d| (d.path.as_str(), d.densities.clone()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("first_name", vec![1.0, 0.0]),
+                ("last_name", vec![1.0, 0.0]),
+                ("name", vec![0.0, 1.0]),
+            ]
+        );
+        assert_eq!(
+            issue.description(),
+            "Schema evolution: version marker 'meta.version' (2: 60.0%, 1: 40.0%), 3 fields differ between versions"
+        );
+    }
+
+    #[test]
+    fn test_single_version_has_no_differences() {
+        let samples: Vec<Value> = (0..50)
+            .map(|i| {
+                if i % 2 == 0 {
+                    json!({"version": "v1", "note": "x"})
+                } else {
+                    json!({"version": "v1"})
+                }
+            })
+            .collect();
+
+        match marker(&samples) {
+            DriftIssue::SchemaEvolution {
+                pattern: EvolutionPattern::VersionMarker { differences, .. },
+                ..
+            } => assert!(differences.is_empty()),
+            other => panic!("expected a version marker, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,541 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+/// A statement the database runs, with its execution statistics
+#[derive(Debug, Clone, PartialEq)]
+pub struct Statement {
+    pub query: String,
+    pub calls: u64,
+    /// Mean execution time in milliseconds
+    pub mean_exec_time_ms: f64,
+}
+
+/// JSON operators a statement applies to the column
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum JsonOperator {
+    /// `->`, `->>`, `#>` and `#>>`, served by B-trees on the extracted value
+    Extract,
+    /// An extracted value compared with `=` or `IN`, which hash indexes on it
+    /// serve as well
+    Equality,
+    /// An extracted value matched with `LIKE`, `ILIKE` or a regular
+    /// expression, served by trigram (`pg_trgm`) GIN indexes on it
+    Pattern,
+    /// `@>`, served by GIN indexes
+    Contains,
+    /// `?`, `?|` and `?&`, served by `jsonb_ops` GIN indexes
+    Exists,
+}
+
+impl JsonOperator {
+    pub fn to_name(&self) -> &str {
+        match self {
+            JsonOperator::Extract => "->>",
+            JsonOperator::Equality => "->> =",
+            JsonOperator::Pattern => "->> LIKE",
+            JsonOperator::Contains => "@>",
+            JsonOperator::Exists => "?",
+        }
+    }
+}
+
+/// How often the recorded statements apply an operator to a path of the column
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ColumnAccess {
+    /// `None` when the keys are query parameters, e.g. `data ->> $1`
+    pub path: Option<String>,
+    pub operator: JsonOperator,
+    #[serde(flatten)]
+    pub usage: QueryUsage,
+}
+
+/// Statements, calls and time spent in queries of one kind
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct QueryUsage {
+    pub statements: usize,
+    pub calls: u64,
+    /// Mean execution time in milliseconds, weighted by calls
+    pub mean_exec_time_ms: f64,
+}
+
+impl QueryUsage {
+    /// Total execution time in milliseconds
+    pub fn total_time_ms(&self) -> f64 {
+        self.calls as f64 * self.mean_exec_time_ms
+    }
+
+    /// Add the statements and calls of `other`
+    pub fn add(&mut self, other: &QueryUsage) {
+        let total = self.total_time_ms() + other.total_time_ms();
+        self.statements += other.statements;
+        self.calls += other.calls;
+        self.mean_exec_time_ms = if self.calls == 0 {
+            0.0
+        } else {
+            total / self.calls as f64
+        };
+    }
+}
+
+/// Find how the recorded statements query `column`, busiest first
+///
+/// Only statements naming `table` are read. After every mention of the
+/// column, chains of `->`/`->>`/`#>`/`#>>` give the extracted path, and a
+/// final `@>` or `?`/`?|`/`?&` its containment or existence check, with the
+/// paths of a JSON literal for `@>`. Extractions compared with `=` or `IN`,
+/// and those matched with `LIKE`/`ILIKE` or a regular expression, are told
+/// apart from the rest, which may be range filters or sort keys. pg_stat_statements records constants as
+/// parameters (`data ->> $1`), so most accesses it reports only know their
+/// operator; literal keys survive in statements that build them otherwise.
+pub fn find_column_accesses(
+    table: &str,
+    column: &str,
+    statements: &[Statement],
+) -> Vec<ColumnAccess> {
+    let mut accesses: HashMap<(Option<String>, JsonOperator), QueryUsage> = HashMap::new();
+    for statement in statements {
+        if !mentions(&statement.query.to_lowercase(), &table.to_lowercase()) {
+            continue;
+        }
+        let found: HashSet<(Option<String>, JsonOperator)> =
+            statement_accesses(&statement.query, column)
+                .into_iter()
+                .collect();
+        for access in found {
+            accesses.entry(access).or_default().add(&QueryUsage {
+                statements: 1,
+                calls: statement.calls,
+                mean_exec_time_ms: statement.mean_exec_time_ms,
+            });
+        }
+    }
+
+    let mut accesses: Vec<ColumnAccess> = accesses
+        .into_iter()
+        .map(|((path, operator), usage)| ColumnAccess {
+            path,
+            operator,
+            usage,
+        })
+        .collect();
+    accesses.sort_by(|a, b| {
+        b.usage
+            .total_time_ms()
+            .total_cmp(&a.usage.total_time_ms())
+            .then_with(|| a.path.cmp(&b.path))
+            .then_with(|| a.operator.to_name().cmp(b.operator.to_name()))
+    });
+    accesses
+}
+
+/// Operand of a JSON operator
+enum Operand {
+    Literal(String),
+    Number,
+    /// A parameter, column or expression
+    Other,
+}
+
+/// The paths and operators a query applies to `column`
+fn statement_accesses(query: &str, column: &str) -> Vec<(Option<String>, JsonOperator)> {
+    let chars: Vec<char> = query.chars().collect();
+    let mut accesses = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '\'' {
+            i = skip_literal(&chars, i).1;
+        } else if c == '"' {
+            let end = chars[i + 1..]
+                .iter()
+                .position(|&c| c == '"')
+                .map_or(chars.len(), |p| i + 1 + p);
+            let ident: String = chars[i + 1..end].iter().collect();
+            i = (end + 1).min(chars.len());
+            if ident == column {
+                accesses.extend(operator_chain(&chars, i));
+            }
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && is_ident_char(chars[i]) {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().collect();
+            if word.to_lowercase() == column {
+                accesses.extend(operator_chain(&chars, i));
+            }
+        } else if c == '$' || c.is_ascii_digit() {
+            // Parameters and numbers, so `$1` isn't read as an identifier
+            i += 1;
+            while i < chars.len() && is_ident_char(chars[i]) {
+                i += 1;
+            }
+        } else {
+            i += 1;
+        }
+    }
+    accesses
+}
+
+/// Follow the JSON operators applied to a column mention ending at `i`
+fn operator_chain(chars: &[char], mut i: usize) -> Vec<(Option<String>, JsonOperator)> {
+    // `None` once a key is not a literal
+    let mut keys: Option<Vec<String>> = Some(Vec::new());
+    let mut extracted = false;
+    loop {
+        i = skip(chars, i, |c| c.is_whitespace() || c == ')');
+        let Some((operator, len)) = ["#>>", "#>", "->>", "->", "@>", "?|", "?&", "?"]
+            .iter()
+            .find(|op| starts_with(chars, i, op))
+            .map(|op| (*op, op.len()))
+        else {
+            break;
+        };
+        let (operand, end) = operand(chars, i + len);
+        i = end;
+
+        match operator {
+            "#>>" | "#>" => {
+                match (&operand, keys.as_mut()) {
+                    (Operand::Literal(path), Some(keys)) => {
+                        for key in path
+                            .trim_start_matches('{')
+                            .trim_end_matches('}')
+                            .split(',')
+                        {
+                            let key = key.trim().trim_matches('"');
+                            match keys.last_mut() {
+                                Some(last) if key.chars().all(|c| c.is_ascii_digit()) => {
+                                    last.push_str("[]")
+                                }
+                                _ => keys.push(key.to_string()),
+                            }
+                        }
+                    }
+                    _ => keys = None,
+                }
+                extracted = true;
+            }
+            "->>" | "->" => {
+                match (&operand, keys.as_mut()) {
+                    (Operand::Literal(key), Some(keys)) => keys.push(key.clone()),
+                    (Operand::Number, Some(keys)) if !keys.is_empty() => {
+                        keys.last_mut().unwrap().push_str("[]")
+                    }
+                    _ => keys = None,
+                }
+                extracted = true;
+            }
+            "@>" => {
+                let paths = match (&operand, &keys) {
+                    (Operand::Literal(document), Some(keys)) => {
+                        match serde_json::from_str::<Value>(document) {
+                            Ok(document) => {
+                                let mut paths = Vec::new();
+                                leaf_paths(&document, keys.join("."), &mut paths);
+                                paths
+                            }
+                            Err(_) => Vec::new(),
+                        }
+                    }
+                    _ => Vec::new(),
+                };
+                if paths.is_empty() {
+                    return vec![(None, JsonOperator::Contains)];
+                }
+                return paths
+                    .into_iter()
+                    .map(|path| (Some(path), JsonOperator::Contains))
+                    .collect();
+            }
+            _ => {
+                // `?|` and `?&` take an array of keys, `?` a single one
+                let tested = match operand {
+                    Operand::Literal(key) if operator == "?" => vec![key],
+                    Operand::Literal(array) => array
+                        .trim_start_matches('{')
+                        .trim_end_matches('}')
+                        .split(',')
+                        .map(|key| key.trim().trim_matches('"').to_string())
+                        .collect(),
+                    _ if operator != "?" && starts_with_ignore_case(chars, i, "array[") => {
+                        array_literals(chars, i)
+                    }
+                    _ => Vec::new(),
+                };
+                return match keys {
+                    Some(keys) if !tested.is_empty() => tested
+                        .into_iter()
+                        .map(|key| {
+                            let mut path = keys.clone();
+                            path.push(key);
+                            (Some(path.join(".")), JsonOperator::Exists)
+                        })
+                        .collect(),
+                    _ => vec![(None, JsonOperator::Exists)],
+                };
+            }
+        }
+    }
+
+    if !extracted {
+        return Vec::new();
+    }
+    vec![(
+        keys.filter(|keys| !keys.is_empty())
+            .map(|keys| keys.join(".")),
+        comparison(chars, i),
+    )]
+}
+
+/// How the extracted value ending at `i` is compared: `Equality` for `=` and
+/// `IN`, `Pattern` for `LIKE`, `ILIKE` and regular expressions, otherwise `Extract`
+fn comparison(chars: &[char], mut i: usize) -> JsonOperator {
+    loop {
+        i = skip(chars, i, |c| c.is_whitespace() || c == ')');
+        if !starts_with(chars, i, "::") {
+            break;
+        }
+        i = skip(chars, i + 2, |c| is_ident_char(c) || c == '[' || c == ']');
+    }
+    let keyword = |word: &str| {
+        starts_with_ignore_case(chars, i, word)
+            && !chars.get(i + word.len()).is_some_and(|&c| is_ident_char(c))
+    };
+    if (starts_with(chars, i, "=") && !starts_with(chars, i, "=>")) || keyword("in") {
+        JsonOperator::Equality
+    } else if starts_with(chars, i, "~") || keyword("like") || keyword("ilike") {
+        // `~~` and `~~*` are how LIKE and ILIKE are written in pg_get_expr output
+        JsonOperator::Pattern
+    } else {
+        JsonOperator::Extract
+    }
+}
+
+/// Parse the operand starting at `i`, returning it and where it ends
+fn operand(chars: &[char], i: usize) -> (Operand, usize) {
+    let i = skip(chars, i, |c| c.is_whitespace() || c == '(');
+    match chars.get(i) {
+        Some('\'') => {
+            let (literal, end) = skip_literal(chars, i);
+            // Casts pg_stat_statements and pg_get_expr add, e.g. `'a'::text`
+            let end = if starts_with(chars, end, "::") {
+                skip(chars, end + 2, |c| is_ident_char(c) || c == '[' || c == ']')
+            } else {
+                end
+            };
+            (Operand::Literal(literal), end)
+        }
+        Some(c) if c.is_ascii_digit() => (Operand::Number, skip(chars, i, |c| c.is_ascii_digit())),
+        Some('$') => (Operand::Other, skip(chars, i + 1, |c| c.is_ascii_digit())),
+        _ => (Operand::Other, i),
+    }
+}
+
+/// The string literals of an `ARRAY['a', 'b']` starting at `i`
+fn array_literals(chars: &[char], mut i: usize) -> Vec<String> {
+    let mut literals = Vec::new();
+    i += "array[".len();
+    while i < chars.len() && chars[i] != ']' {
+        if chars[i] == '\'' {
+            let (literal, end) = skip_literal(chars, i);
+            literals.push(literal);
+            i = end;
+        } else {
+            i += 1;
+        }
+    }
+    literals
+}
+
+/// Read the string literal starting at `i`, returning its content and where it ends
+fn skip_literal(chars: &[char], mut i: usize) -> (String, usize) {
+    let mut literal = String::new();
+    i += 1;
+    while i < chars.len() {
+        if chars[i] == '\'' {
+            if chars.get(i + 1) == Some(&'\'') {
+                literal.push('\'');
+                i += 2;
+                continue;
+            }
+            return (literal, i + 1);
+        }
+        literal.push(chars[i]);
+        i += 1;
+    }
+    (literal, i)
+}
+
+/// Paths of the scalars (and empty containers) in a containment document
+fn leaf_paths(value: &Value, prefix: String, paths: &mut Vec<String>) {
+    let join = |key: &str| {
+        if prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", prefix, key)
+        }
+    };
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, value) in map {
+                leaf_paths(value, join(key), paths);
+            }
+        }
+        Value::Array(items) if !items.is_empty() => {
+            for item in items {
+                leaf_paths(item, format!("{}[]", prefix), paths);
+            }
+        }
+        _ if !prefix.is_empty() => paths.push(prefix),
+        _ => {}
+    }
+}
+
+fn skip(chars: &[char], mut i: usize, f: impl Fn(char) -> bool) -> usize {
+    while i < chars.len() && f(chars[i]) {
+        i += 1;
+    }
+    i
+}
+
+fn starts_with(chars: &[char], i: usize, s: &str) -> bool {
+    s.chars()
+        .enumerate()
+        .all(|(j, c)| chars.get(i + j) == Some(&c))
+}
+
+fn starts_with_ignore_case(chars: &[char], i: usize, s: &str) -> bool {
+    s.chars()
+        .enumerate()
+        .all(|(j, c)| chars.get(i + j).is_some_and(|d| d.eq_ignore_ascii_case(&c)))
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/// Whether `word` appears in `text` with no identifier character on either side
+fn mentions(text: &str, word: &str) -> bool {
+    if word.is_empty() {
+        return false;
+    }
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    text.match_indices(word).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + word.len()..].chars().next();
+        !before.is_some_and(is_ident) && !after.is_some_and(is_ident)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn statement(query: &str, calls: u64, mean: f64) -> Statement {
+        Statement {
+            query: query.to_string(),
+            calls,
+            mean_exec_time_ms: mean,
+        }
+    }
+
+    fn accesses(query: &str) -> Vec<(Option<String>, JsonOperator)> {
+        statement_accesses(query, "metadata")
+    }
+
+    #[test]
+    fn test_statement_accesses() {
+        let path = |p: &str, op| (Some(p.to_string()), op);
+        assert_eq!(
+            accesses("SELECT id FROM users WHERE metadata->>'email' = $1"),
+            vec![path("email", JsonOperator::Equality)]
+        );
+        assert_eq!(
+            accesses(
+                "SELECT * FROM users u WHERE (u.metadata -> 'user'::text) ->> 'id'::text = $1"
+            ),
+            vec![path("user.id", JsonOperator::Equality)]
+        );
+        assert_eq!(
+            accesses(
+                "SELECT * FROM users WHERE (metadata->>'age')::int >= $1 OR metadata->>'sku' IN ($2, $3)"
+            ),
+            vec![
+                path("age", JsonOperator::Extract),
+                path("sku", JsonOperator::Equality),
+            ]
+        );
+        assert_eq!(
+            accesses(
+                "SELECT * FROM users WHERE metadata->>'bio' ILIKE $1 OR (metadata #>> '{user,name}'::text[]) ~~* $2 OR metadata->>'inline' = $3"
+            ),
+            vec![
+                path("bio", JsonOperator::Pattern),
+                path("user.name", JsonOperator::Pattern),
+                path("inline", JsonOperator::Equality),
+            ]
+        );
+        assert_eq!(
+            accesses(
+                r#"SELECT * FROM users WHERE "metadata" #>> '{items,0}' = $1 AND metadata->'items'->0->>'sku' = $2"#
+            ),
+            vec![
+                path("items[]", JsonOperator::Equality),
+                path("items[].sku", JsonOperator::Equality),
+            ]
+        );
+        assert_eq!(
+            accesses(
+                r#"SELECT * FROM users WHERE metadata @> '{"user": {"plan": "pro"}, "tags": ["a"]}'"#
+            ),
+            vec![
+                path("tags[]", JsonOperator::Contains),
+                path("user.plan", JsonOperator::Contains),
+            ]
+        );
+        assert_eq!(
+            accesses(
+                "SELECT * FROM users WHERE metadata -> 'billing' ? 'vat' OR metadata ?| array['a', 'b']"
+            ),
+            vec![
+                path("billing.vat", JsonOperator::Exists),
+                path("a", JsonOperator::Exists),
+                path("b", JsonOperator::Exists),
+            ]
+        );
+        // Normalized by pg_stat_statements
+        assert_eq!(
+            accesses("SELECT * FROM users WHERE metadata @> $1 AND metadata ->> $2 = $3"),
+            vec![
+                (None, JsonOperator::Contains),
+                (None, JsonOperator::Equality)
+            ]
+        );
+        assert!(accesses("SELECT metadata, 'metadata->>x' FROM users").is_empty());
+    }
+
+    #[test]
+    fn test_find_column_accesses() {
+        let statements = [
+            statement("SELECT * FROM users WHERE metadata @> $1", 1000, 2.0),
+            statement(
+                "SELECT * FROM users WHERE metadata @> $1 LIMIT $2",
+                3000,
+                4.0,
+            ),
+            statement("SELECT metadata->>'email' FROM users", 10, 1.0),
+            statement("SELECT * FROM orders WHERE metadata @> $1", 99999, 9.0),
+        ];
+
+        let found = find_column_accesses("users", "metadata", &statements);
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].path, None);
+        assert_eq!(found[0].operator, JsonOperator::Contains);
+        assert_eq!(found[0].usage.statements, 2);
+        assert_eq!(found[0].usage.calls, 4000);
+        assert!((found[0].usage.mean_exec_time_ms - 3.5).abs() < 1e-9);
+        assert_eq!(found[1].path.as_deref(), Some("email"));
+    }
+}
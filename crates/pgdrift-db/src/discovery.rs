@@ -1,5 +1,128 @@
+use pgdrift_core::report::discover as report;
+use pgdrift_core::sql::quote_ident;
 use serde::Serialize;
 use sqlx::PgPool;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Postgres type of a discovered JSON column
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColumnType {
+    Jsonb,
+    /// Legacy text-based `json` - cast to jsonb when sampling
+    Json,
+    /// `hstore` key/value map - converted to a flat jsonb object
+    Hstore,
+    /// `jsonb[]` - each element is analyzed as its own document
+    JsonbArray,
+    /// `json[]` - each element is analyzed as its own document
+    JsonArray,
+}
+
+impl ColumnType {
+    /// Name of the type as reported by `format_type`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Jsonb => "jsonb",
+            Self::Json => "json",
+            Self::Hstore => "hstore",
+            Self::JsonbArray => "jsonb[]",
+            Self::JsonArray => "json[]",
+        }
+    }
+
+    /// Whether each row holds an array of documents rather than one document
+    pub fn is_array(&self) -> bool {
+        matches!(self, Self::JsonbArray | Self::JsonArray)
+    }
+
+    /// Look up a column type by its `format_type` name
+    pub fn from_sql(data_type: &str) -> Option<Self> {
+        match data_type {
+            "jsonb" => Some(Self::Jsonb),
+            "json" => Some(Self::Json),
+            "hstore" => Some(Self::Hstore),
+            "jsonb[]" => Some(Self::JsonbArray),
+            "json[]" => Some(Self::JsonArray),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ColumnType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Kind of relation a discovered column belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RelationKind {
+    Table,
+    View,
+    MaterializedView,
+}
+
+impl RelationKind {
+    /// Map a `pg_class.relkind` code to a relation kind
+    fn from_relkind(relkind: &str) -> Option<Self> {
+        match relkind {
+            "r" | "p" => Some(Self::Table),
+            "v" => Some(Self::View),
+            "m" => Some(Self::MaterializedView),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for RelationKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Table => "table",
+            Self::View => "view",
+            Self::MaterializedView => "matview",
+        })
+    }
+}
+
+/// An existing index that covers a JSON column
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ColumnIndex {
+    pub name: String,
+    /// Access method, e.g. `gin` or `btree`
+    pub method: String,
+    /// Whether the column is used inside an expression (e.g. `(data->>'email')`)
+    /// rather than indexed directly
+    pub expression: bool,
+    /// Full `CREATE INDEX` statement as reported by `pg_get_indexdef`
+    pub definition: String,
+}
+
+/// An index on a table, with its keys split out for comparison
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TableIndex {
+    pub name: String,
+    /// Access method, e.g. `gin` or `btree`
+    pub method: String,
+    /// Key columns and expressions, in index order (`INCLUDE` columns left out)
+    pub keys: Vec<String>,
+    /// Operator class of each key
+    pub operator_classes: Vec<String>,
+    /// `WHERE` clause of a partial index
+    pub predicate: Option<String>,
+    /// Whether the index enforces uniqueness (including primary keys)
+    pub unique: bool,
+    /// Full `CREATE INDEX` statement as reported by `pg_get_indexdef`
+    pub definition: String,
+    /// Table columns the index keys, expressions or predicate refer to
+    pub columns: Vec<String>,
+    /// Scans of the index since the statistics were last reset (`idx_scan`)
+    pub scans: i64,
+    /// Size of the index on disk
+    pub size_bytes: i64,
+}
 
 /// Represents a JSOBN column in discovered in the DB
 #[derive(Debug, Clone, Serialize)]
@@ -7,52 +130,748 @@ pub struct JsonbColumn {
     pub schema: String,
     pub table: String,
     pub column: String,
+    pub data_type: ColumnType,
+    /// Whether the column lives in a table, view or materialized view
+    pub relation_kind: RelationKind,
+    /// For partitioned tables, estimated rows are summed over all partitions
     pub estimated_rows: Option<i64>,
+    /// Number of leaf partitions rolled up into a partitioned table
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub partitions: Option<i64>,
+    /// Parent table (`schema.table`) when the column belongs to a partition
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub partition_of: Option<String>,
+    /// Dead tuples reported by pg_stat_user_tables
+    pub dead_tuples: Option<i64>,
+    /// Last manual ANALYZE (UTC, ISO 8601)
+    pub last_analyze: Option<String>,
+    /// Last ANALYZE run by autovacuum (UTC, ISO 8601)
+    pub last_autoanalyze: Option<String>,
+    /// Seconds since the table statistics were last refreshed by either
+    pub stats_age_secs: Option<i64>,
+    /// Indexes that already cover the column
+    pub indexes: Vec<ColumnIndex>,
+    /// Storage footprint, only collected when `DiscoveryOptions::storage` is set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub storage: Option<ColumnStorage>,
+}
+
+/// Storage footprint of a JSON column, estimated from a sample of documents
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ColumnStorage {
+    /// Average stored (possibly compressed) document size in bytes
+    pub avg_bytes: f64,
+    /// `avg_bytes` times the estimated row count
+    pub estimated_total_bytes: Option<i64>,
+    /// Size of the table's TOAST relation in bytes (shared by all its columns)
+    pub toast_bytes: i64,
+    /// Share of sampled documents over the ~2kB TOAST threshold
+    pub toasted_fraction: f64,
 }
 
+/// Statistics older than a week are considered stale
+const STALE_STATS_AGE_SECS: i64 = 7 * 24 * 60 * 60;
+
+/// More than 20% dead tuples skews live row estimates
+const STALE_DEAD_TUPLE_RATIO: f64 = 0.2;
+
+/// Documents sampled per column to estimate storage size
+const STORAGE_SAMPLE_ROWS: i64 = 1000;
+
+/// Stored values larger than this are candidates for TOAST (TOAST_TUPLE_THRESHOLD)
+const TOAST_THRESHOLD_BYTES: i32 = 2032;
+
 impl JsonbColumn {
     /// Get the fully qualified column name
     pub fn full_name(&self) -> String {
         format!("{}.{}.{}", self.schema, self.table, self.column)
     }
+
+    /// Whether the planner statistics are too old or too bloated to trust
+    ///
+    /// Stale statistics make `estimated_rows` (and so the TABLESAMPLE
+    /// percentage) unreliable. Relations without statistics, such as plain
+    /// views, are never reported as stale.
+    pub fn has_stale_stats(&self) -> bool {
+        let never_analyzed = self.relation_kind != RelationKind::View
+            && self.stats_age_secs.is_none()
+            && self.estimated_rows.is_some();
+        let too_old = self
+            .stats_age_secs
+            .is_some_and(|age| age > STALE_STATS_AGE_SECS);
+        let too_many_dead = match (self.dead_tuples, self.estimated_rows) {
+            (Some(dead), Some(live)) if live + dead > 0 => {
+                dead as f64 / (live + dead) as f64 > STALE_DEAD_TUPLE_RATIO
+            }
+            _ => false,
+        };
+
+        never_analyzed || too_old || too_many_dead
+    }
+
+    /// Short summary of the covering indexes, e.g. `idx_meta (gin), idx_email (btree expr)`
+    pub fn index_summary(&self) -> String {
+        if self.indexes.is_empty() {
+            return "none".to_string();
+        }
+
+        self.indexes
+            .iter()
+            .map(|idx| {
+                if idx.expression {
+                    format!("{} ({} expr)", idx.name, idx.method)
+                } else {
+                    format!("{} ({})", idx.name, idx.method)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+impl From<&JsonbColumn> for report::JsonbColumn {
+    fn from(column: &JsonbColumn) -> Self {
+        Self {
+            schema: column.schema.clone(),
+            table: column.table.clone(),
+            column: column.column.clone(),
+            data_type: match column.data_type {
+                ColumnType::Jsonb => report::ColumnType::Jsonb,
+                ColumnType::Json => report::ColumnType::Json,
+                ColumnType::Hstore => report::ColumnType::Hstore,
+                ColumnType::JsonbArray => report::ColumnType::JsonbArray,
+                ColumnType::JsonArray => report::ColumnType::JsonArray,
+            },
+            relation_kind: match column.relation_kind {
+                RelationKind::Table => report::RelationKind::Table,
+                RelationKind::View => report::RelationKind::View,
+                RelationKind::MaterializedView => report::RelationKind::MaterializedView,
+            },
+            estimated_rows: column.estimated_rows,
+            partitions: column.partitions,
+            partition_of: column.partition_of.clone(),
+            dead_tuples: column.dead_tuples,
+            last_analyze: column.last_analyze.clone(),
+            last_autoanalyze: column.last_autoanalyze.clone(),
+            stats_age_secs: column.stats_age_secs,
+            indexes: column
+                .indexes
+                .iter()
+                .map(|idx| report::ColumnIndex {
+                    name: idx.name.clone(),
+                    method: idx.method.clone(),
+                    expression: idx.expression,
+                    definition: idx.definition.clone(),
+                })
+                .collect(),
+            storage: column.storage.as_ref().map(|s| report::ColumnStorage {
+                avg_bytes: s.avg_bytes,
+                estimated_total_bytes: s.estimated_total_bytes,
+                toast_bytes: s.toast_bytes,
+                toasted_fraction: s.toasted_fraction,
+            }),
+        }
+    }
+}
+
+/// Options controlling which columns discovery returns
+#[derive(Debug, Clone, Default)]
+pub struct DiscoveryOptions {
+    /// Also discover legacy `json` columns
+    pub include_json: bool,
+    /// Also discover `hstore` columns
+    pub include_hstore: bool,
+    /// Also discover `jsonb[]` and `json[]` array columns
+    pub include_arrays: bool,
+    /// Only keep columns matching at least one of these glob patterns
+    pub include: Vec<String>,
+    /// Drop columns matching any of these glob patterns
+    pub exclude: Vec<String>,
+    /// Sample each column to estimate its storage and TOAST footprint
+    pub storage: bool,
+    /// Also discover columns of views and materialized views
+    pub include_views: bool,
+    /// List every partition separately instead of only the partitioned parent
+    pub expand_partitions: bool,
+    /// Skip columns whose table has fewer estimated rows than this
+    ///
+    /// Columns without an estimate (e.g. plain views) are kept.
+    pub min_rows: Option<i64>,
+}
+
+impl DiscoveryOptions {
+    fn data_types(&self) -> Vec<&'static str> {
+        let mut types = vec![ColumnType::Jsonb];
+        if self.include_json {
+            types.push(ColumnType::Json);
+        }
+        if self.include_hstore {
+            types.push(ColumnType::Hstore);
+        }
+        if self.include_arrays {
+            types.push(ColumnType::JsonbArray);
+            if self.include_json {
+                types.push(ColumnType::JsonArray);
+            }
+        }
+        types.iter().map(ColumnType::as_str).collect()
+    }
+
+    fn relkinds(&self) -> Vec<&'static str> {
+        let mut kinds = vec!["r", "p"];
+        if self.include_views {
+            kinds.extend(["v", "m"]);
+        }
+        kinds
+    }
+
+    /// Whether a column passes the include/exclude patterns
+    ///
+    /// Patterns are matched against `schema`, `schema.table` and
+    /// `schema.table.column`, so `public`, `public.users` and
+    /// `*.audit_*` all do what you'd expect. `*` matches any run of
+    /// characters (including dots) and `?` a single character.
+    pub fn matches(&self, column: &JsonbColumn) -> bool {
+        let names = [
+            column.schema.clone(),
+            format!("{}.{}", column.schema, column.table),
+            column.full_name(),
+        ];
+        let matches_any = |patterns: &[String]| {
+            patterns
+                .iter()
+                .any(|p| names.iter().any(|name| glob_match(p, name)))
+        };
+
+        (self.include.is_empty() || matches_any(&self.include)) && !matches_any(&self.exclude)
+    }
+
+    /// Whether a column's table is large enough to pass `min_rows`
+    pub fn has_min_rows(&self, column: &JsonbColumn) -> bool {
+        match (self.min_rows, column.estimated_rows) {
+            (Some(min), Some(rows)) => rows >= min,
+            _ => true,
+        }
+    }
+}
+
+/// Match `text` against a glob pattern supporting `*` and `?`
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` and the text index it was tried at
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, star_t)) = backtrack {
+            p = star + 1;
+            t = star_t + 1;
+            backtrack = Some((star, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Row returned by the discovery query
+#[derive(sqlx::FromRow)]
+struct DiscoveredRow {
+    schema: String,
+    table: String,
+    column: String,
+    data_type: String,
+    relkind: String,
+    estimated_rows: Option<i64>,
+    partitions: Option<i64>,
+    partition_of: Option<String>,
+    dead_tuples: Option<i64>,
+    last_analyze: Option<String>,
+    last_autoanalyze: Option<String>,
+    stats_age_secs: Option<i64>,
 }
 
 /// Discover all JSONB columns in the DB
 ///
-/// Queries information_schema to find all columns with the type 'Jsonb',
-/// excluding system schemas (pg_catalog, information_schema).
+/// Queries the catalog to find all table columns with the type 'Jsonb',
+/// excluding system schemas (pg_catalog, information_schema). Partitions
+/// are rolled up into their partitioned parent.
 /// Also, fetch estimated row counts from pg_stat_user_tables
 pub async fn discover_jsonb_columns(pool: &PgPool) -> Result<Vec<JsonbColumn>, sqlx::Error> {
-    let columns = sqlx::query_as::<_, (String, String, String, Option<i64>)>(
+    discover_columns(pool, &DiscoveryOptions::default()).await
+}
+
+/// Discover JSON columns in the DB using the given options
+pub async fn discover_columns(
+    pool: &PgPool,
+    options: &DiscoveryOptions,
+) -> Result<Vec<JsonbColumn>, sqlx::Error> {
+    let columns = sqlx::query_as::<_, DiscoveredRow>(
         r#"
           SELECT
-              c.table_schema,
-              c.table_name,
-              c.column_name,
-              s.n_live_tup as estimated_rows
-          FROM information_schema.columns c
-          LEFT JOIN pg_stat_user_tables s
-              ON s.schemaname = c.table_schema
-              AND s.relname = c.table_name
-          WHERE c.data_type = 'jsonb'
-              AND c.table_schema NOT IN ('pg_catalog', 'information_schema')
-          ORDER BY c.table_schema, c.table_name, c.column_name
+              n.nspname AS schema,
+              c.relname AS "table",
+              a.attname AS "column",
+              format_type(a.atttypid, NULL) AS data_type,
+              c.relkind::text AS relkind,
+              CASE
+                  WHEN c.relkind = 'm' AND st.live_tup IS NULL AND c.reltuples >= 0
+                      THEN c.reltuples::bigint
+                  ELSE st.live_tup
+              END AS estimated_rows,
+              CASE
+                  WHEN c.relkind = 'p' THEN (
+                      SELECT COUNT(*) FROM pg_partition_tree(c.oid) pt WHERE pt.isleaf
+                  )
+              END AS partitions,
+              (
+                  SELECT pn.nspname || '.' || pc.relname
+                  FROM pg_inherits i
+                  JOIN pg_class pc ON pc.oid = i.inhparent
+                  JOIN pg_namespace pn ON pn.oid = pc.relnamespace
+                  WHERE i.inhrelid = c.oid AND c.relispartition
+              ) AS partition_of,
+              st.dead_tup AS dead_tuples,
+              to_char(st.last_analyze AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"')
+                  AS last_analyze,
+              to_char(st.last_autoanalyze AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"')
+                  AS last_autoanalyze,
+              EXTRACT(EPOCH FROM now() - st.analyzed_at)::bigint AS stats_age_secs
+          FROM pg_attribute a
+          JOIN pg_class c ON c.oid = a.attrelid
+          JOIN pg_namespace n ON n.oid = c.relnamespace
+          -- Table statistics; partitioned tables aggregate over their leaf
+          -- partitions and report the oldest analyze time
+          LEFT JOIN LATERAL (
+              SELECT
+                  SUM(ps.n_live_tup)::bigint AS live_tup,
+                  SUM(ps.n_dead_tup)::bigint AS dead_tup,
+                  MIN(ps.last_analyze) AS last_analyze,
+                  MIN(ps.last_autoanalyze) AS last_autoanalyze,
+                  MIN(GREATEST(ps.last_analyze, ps.last_autoanalyze)) AS analyzed_at
+              FROM pg_stat_user_tables ps
+              WHERE ps.relid = c.oid
+                  OR (c.relkind = 'p' AND ps.relid IN (
+                      SELECT pt.relid FROM pg_partition_tree(c.oid) pt WHERE pt.isleaf
+                  ))
+          ) st ON true
+          WHERE format_type(a.atttypid, NULL) = ANY($1)
+              AND c.relkind::text = ANY($2)
+              AND (NOT c.relispartition OR $3)
+              AND a.attnum > 0
+              AND NOT a.attisdropped
+              AND n.nspname NOT IN ('pg_catalog', 'information_schema')
+              AND has_column_privilege(c.oid, a.attnum, 'SELECT, INSERT, UPDATE, REFERENCES')
+          ORDER BY n.nspname, c.relname, a.attname
           "#,
     )
+    .bind(options.data_types())
+    .bind(options.relkinds())
+    .bind(options.expand_partitions)
     .fetch_all(pool)
     .await?
     .into_iter()
-    .map(|(schema, table, column, estimated_rows)| JsonbColumn {
-        schema,
-        table,
-        column,
-        estimated_rows,
+    .filter_map(|row| {
+        Some(JsonbColumn {
+            schema: row.schema,
+            table: row.table,
+            column: row.column,
+            data_type: ColumnType::from_sql(&row.data_type)?,
+            relation_kind: RelationKind::from_relkind(&row.relkind)?,
+            estimated_rows: row.estimated_rows,
+            partitions: row.partitions,
+            partition_of: row.partition_of,
+            dead_tuples: row.dead_tuples,
+            last_analyze: row.last_analyze,
+            last_autoanalyze: row.last_autoanalyze,
+            stats_age_secs: row.stats_age_secs,
+            indexes: Vec::new(),
+            storage: None,
+        })
     })
-    .collect();
+    .filter(|column| options.matches(column) && options.has_min_rows(column))
+    .collect::<Vec<_>>();
+
+    let mut indexes = column_indexes(pool).await?;
+    let mut columns: Vec<JsonbColumn> = columns
+        .into_iter()
+        .map(|mut col| {
+            let key = (col.schema.clone(), col.table.clone(), col.column.clone());
+            col.indexes = indexes.remove(&key).unwrap_or_default();
+            col
+        })
+        .collect();
+
+    if options.storage {
+        for col in &mut columns {
+            col.storage = Some(column_storage(pool, col).await?);
+        }
+    }
 
     Ok(columns)
 }
 
+/// Estimate the storage footprint of a column
+///
+/// Averages `pg_column_size` over the first `STORAGE_SAMPLE_ROWS` non-NULL
+/// documents rather than scanning the whole table, and reads the TOAST
+/// relation size from pg_class.
+pub async fn column_storage(
+    pool: &PgPool,
+    column: &JsonbColumn,
+) -> Result<ColumnStorage, sqlx::Error> {
+    let column_quoted = quote_ident(&column.column);
+    let (avg_bytes, toasted_fraction): (Option<f64>, Option<f64>) = sqlx::query_as(&format!(
+        "SELECT
+                AVG(size)::float8,
+                AVG(CASE WHEN size > {} THEN 1.0 ELSE 0.0 END)::float8
+             FROM (
+                SELECT pg_column_size({}) AS size
+                FROM {}.{}
+                WHERE {} IS NOT NULL
+                LIMIT {}
+             ) s",
+        TOAST_THRESHOLD_BYTES,
+        column_quoted,
+        quote_ident(&column.schema),
+        quote_ident(&column.table),
+        column_quoted,
+        STORAGE_SAMPLE_ROWS
+    ))
+    .fetch_one(pool)
+    .await?;
+
+    // Partitioned tables have no TOAST of their own, so sum over the partitions
+    let toast_bytes: i64 = sqlx::query_scalar(
+        r#"
+          SELECT COALESCE(SUM(pg_relation_size(c.reltoastrelid)), 0)::bigint
+          FROM pg_class c
+          JOIN pg_namespace n ON n.oid = c.relnamespace
+          WHERE (n.nspname = $1 AND c.relname = $2)
+              OR c.oid IN (
+                  SELECT relid FROM pg_partition_tree(format('%I.%I', $1, $2)::regclass)
+              )
+          "#,
+    )
+    .bind(&column.schema)
+    .bind(&column.table)
+    .fetch_one(pool)
+    .await?;
+
+    let avg_bytes = avg_bytes.unwrap_or(0.0);
+    Ok(ColumnStorage {
+        avg_bytes,
+        estimated_total_bytes: column
+            .estimated_rows
+            .map(|rows| (avg_bytes * rows as f64) as i64),
+        toast_bytes,
+        toasted_fraction: toasted_fraction.unwrap_or(0.0),
+    })
+}
+
+/// Find all indexes in user schemas, keyed by the (schema, table, column) they cover
+///
+/// A column is covered if it is an index key or is referenced by an index
+/// expression (expression dependencies are recorded in pg_depend).
+async fn column_indexes(
+    pool: &PgPool,
+) -> Result<HashMap<(String, String, String), Vec<ColumnIndex>>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, (String, String, String, String, String, bool, String)>(
+        r#"
+          SELECT
+              n.nspname,
+              t.relname,
+              a.attname,
+              i.relname,
+              am.amname,
+              NOT (a.attnum = ANY(ix.indkey)) AS expression,
+              pg_get_indexdef(i.oid)
+          FROM pg_index ix
+          JOIN pg_class i ON i.oid = ix.indexrelid
+          JOIN pg_class t ON t.oid = ix.indrelid
+          JOIN pg_namespace n ON n.oid = t.relnamespace
+          JOIN pg_am am ON am.oid = i.relam
+          JOIN pg_attribute a ON a.attrelid = t.oid AND a.attnum > 0 AND NOT a.attisdropped
+          WHERE n.nspname NOT IN ('pg_catalog', 'information_schema')
+              AND (
+                  a.attnum = ANY(ix.indkey)
+                  OR EXISTS (
+                      SELECT 1 FROM pg_depend d
+                      WHERE d.classid = 'pg_class'::regclass
+                          AND d.objid = i.oid
+                          AND d.refclassid = 'pg_class'::regclass
+                          AND d.refobjid = t.oid
+                          AND d.refobjsubid = a.attnum
+                  )
+              )
+          ORDER BY n.nspname, t.relname, a.attname, i.relname
+          "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut indexes: HashMap<_, Vec<ColumnIndex>> = HashMap::new();
+    for (schema, table, column, name, method, expression, definition) in rows {
+        indexes
+            .entry((schema, table, column))
+            .or_default()
+            .push(ColumnIndex {
+                name,
+                method,
+                expression,
+                definition,
+            });
+    }
+
+    Ok(indexes)
+}
+
+/// List the valid indexes of a table
+///
+/// Indexes still being built or left invalid by a failed `CREATE INDEX
+/// CONCURRENTLY` are skipped, since the planner can't use them.
+pub async fn table_indexes(
+    pool: &PgPool,
+    schema: &str,
+    table: &str,
+) -> Result<Vec<TableIndex>, sqlx::Error> {
+    let rows = sqlx::query_as::<
+        _,
+        (
+            String,
+            String,
+            Vec<String>,
+            Vec<String>,
+            Option<String>,
+            bool,
+            String,
+            Vec<String>,
+            i64,
+            i64,
+        ),
+    >(
+        r#"
+          SELECT
+              i.relname,
+              am.amname,
+              ARRAY(
+                  SELECT pg_get_indexdef(ix.indexrelid, k, true)
+                  FROM generate_series(1, ix.indnkeyatts) k
+                  ORDER BY k
+              ),
+              ARRAY(
+                  SELECT opc.opcname::text
+                  FROM generate_series(1, ix.indnkeyatts) k
+                  JOIN pg_opclass opc ON opc.oid = ix.indclass[k - 1]
+                  ORDER BY k
+              ),
+              pg_get_expr(ix.indpred, ix.indrelid, true),
+              ix.indisunique,
+              pg_get_indexdef(ix.indexrelid),
+              ARRAY(
+                  SELECT a.attname::text
+                  FROM pg_attribute a
+                  WHERE a.attrelid = t.oid AND a.attnum > 0 AND NOT a.attisdropped
+                      AND (
+                          a.attnum = ANY(ix.indkey)
+                          OR EXISTS (
+                              SELECT 1 FROM pg_depend d
+                              WHERE d.classid = 'pg_class'::regclass
+                                  AND d.objid = i.oid
+                                  AND d.refclassid = 'pg_class'::regclass
+                                  AND d.refobjid = t.oid
+                                  AND d.refobjsubid = a.attnum
+                          )
+                      )
+                  ORDER BY a.attnum
+              ),
+              COALESCE(s.idx_scan, 0),
+              pg_relation_size(ix.indexrelid)
+          FROM pg_index ix
+          JOIN pg_class i ON i.oid = ix.indexrelid
+          JOIN pg_class t ON t.oid = ix.indrelid
+          JOIN pg_namespace n ON n.oid = t.relnamespace
+          JOIN pg_am am ON am.oid = i.relam
+          LEFT JOIN pg_stat_user_indexes s ON s.indexrelid = ix.indexrelid
+          WHERE n.nspname = $1 AND t.relname = $2 AND ix.indisvalid
+          ORDER BY i.relname
+          "#,
+    )
+    .bind(schema)
+    .bind(table)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(
+            |(
+                name,
+                method,
+                keys,
+                operator_classes,
+                predicate,
+                unique,
+                definition,
+                columns,
+                scans,
+                size_bytes,
+            )| TableIndex {
+                name,
+                method,
+                keys,
+                operator_classes,
+                predicate,
+                unique,
+                definition,
+                columns,
+                scans,
+                size_bytes,
+            },
+        )
+        .collect())
+}
+
+/// Look up the JSON type of a single column
+///
+/// Returns `None` if the column does not exist or is not json/jsonb.
+pub async fn column_type(
+    pool: &PgPool,
+    schema: &str,
+    table: &str,
+    column: &str,
+) -> Result<Option<ColumnType>, sqlx::Error> {
+    let data_type = column_data_type(pool, schema, table, column).await?;
+    Ok(data_type.as_deref().and_then(ColumnType::from_sql))
+}
+
+/// Look up the SQL type of a single column, e.g. `bigint`
+///
+/// Returns `None` if the column does not exist.
+pub async fn column_data_type(
+    pool: &PgPool,
+    schema: &str,
+    table: &str,
+    column: &str,
+) -> Result<Option<String>, sqlx::Error> {
+    // pg_attribute rather than information_schema so matview columns are found
+    sqlx::query_scalar(
+        r#"
+          SELECT format_type(a.atttypid, NULL)
+          FROM pg_attribute a
+          JOIN pg_class c ON c.oid = a.attrelid
+          JOIN pg_namespace n ON n.oid = c.relnamespace
+          WHERE n.nspname = $1 AND c.relname = $2 AND a.attname = $3
+              AND a.attnum > 0 AND NOT a.attisdropped
+          "#,
+    )
+    .bind(schema)
+    .bind(table)
+    .bind(column)
+    .fetch_optional(pool)
+    .await
+}
+
+/// When the statistics of the current database were last reset, e.g.
+/// `2025-01-31 09:12`, or `None` if they never were
+///
+/// Index scan counts (`idx_scan`) only cover the time since.
+pub async fn stats_reset(pool: &PgPool) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT to_char(stats_reset, 'YYYY-MM-DD HH24:MI') \
+         FROM pg_stat_database WHERE datname = current_database()",
+    )
+    .fetch_optional(pool)
+    .await
+    .map(Option::flatten)
+}
+
+/// Whether an extension is installed in the current database
+pub async fn has_extension(pool: &PgPool, name: &str) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM pg_extension WHERE extname = $1)")
+        .bind(name)
+        .fetch_one(pool)
+        .await
+}
+
+/// SQL the database is known to run: statements recorded by
+/// `pg_stat_statements` for the current database, plus the definitions of
+/// user views, materialized views, indexes and functions
+///
+/// `pg_stat_statements` must be installed. Statements of other users are
+/// left out unless the role may read them (`pg_read_all_stats`).
+pub async fn query_texts(pool: &PgPool) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+          SELECT s.query
+          FROM pg_stat_statements s
+          JOIN pg_database d ON d.oid = s.dbid
+          WHERE d.datname = current_database() AND s.query <> '<insufficient privilege>'
+          UNION ALL
+          SELECT definition FROM pg_views
+          WHERE schemaname NOT IN ('pg_catalog', 'information_schema')
+          UNION ALL
+          SELECT definition FROM pg_matviews
+          UNION ALL
+          SELECT indexdef FROM pg_indexes
+          WHERE schemaname NOT IN ('pg_catalog', 'information_schema')
+          UNION ALL
+          SELECT p.prosrc
+          FROM pg_proc p
+          JOIN pg_namespace n ON n.oid = p.pronamespace
+          WHERE n.nspname NOT IN ('pg_catalog', 'information_schema')
+          "#,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// A statement recorded by pg_stat_statements
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StatementStats {
+    pub query: String,
+    pub calls: i64,
+    /// Mean execution time in milliseconds
+    pub mean_exec_time: f64,
+}
+
+/// Statements pg_stat_statements recorded for the current database, with
+/// how often they ran and how long they took
+///
+/// `pg_stat_statements` must be installed, in version 1.8 (Postgres 13) or
+/// later. Statements of other users are left out unless the role may read
+/// them (`pg_read_all_stats`).
+pub async fn statement_stats(pool: &PgPool) -> Result<Vec<StatementStats>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, (String, i64, f64)>(
+        r#"
+          SELECT s.query, s.calls, s.mean_exec_time
+          FROM pg_stat_statements s
+          JOIN pg_database d ON d.oid = s.dbid
+          WHERE d.datname = current_database() AND s.query <> '<insufficient privilege>'
+          "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(query, calls, mean_exec_time)| StatementStats {
+            query,
+            calls,
+            mean_exec_time,
+        })
+        .collect())
+}
+
 /// Get exact row count for a specific table
 ///
 /// Executes COUNT(*) query on the specified table.
@@ -61,8 +880,8 @@ pub async fn discover_jsonb_columns(pool: &PgPool) -> Result<Vec<JsonbColumn>, s
 pub async fn get_row_count(pool: &PgPool, schema: &str, table: &str) -> Result<i64, sqlx::Error> {
     let count: i64 = sqlx::query_scalar(&format!(
         "SELECT COUNT(*) FROM {}.{}",
-        quote_identifier(schema),
-        quote_identifier(table)
+        quote_ident(schema),
+        quote_ident(table)
     ))
     .fetch_one(pool)
     .await?;
@@ -70,7 +889,235 @@ pub async fn get_row_count(pool: &PgPool, schema: &str, table: &str) -> Result<i
     Ok(count)
 }
 
-/// Quote a postgresql identifier (schema/table/column name) to prevent sql injection
-fn quote_identifier(identifier: &str) -> String {
-    format!("\"{}\"", identifier.replace("\"", "\"\""))
+/// Heap pages of a table, from its size on disk
+pub async fn table_pages(pool: &PgPool, schema: &str, table: &str) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT (pg_relation_size(format('%I.%I', $1, $2)::regclass) \
+                 / current_setting('block_size')::bigint)::bigint",
+    )
+    .bind(schema)
+    .bind(table)
+    .fetch_one(pool)
+    .await
+}
+
+/// Block number and text at `path` of up to `limit` rows spread over the
+/// table, to measure how the path follows the physical row order
+///
+/// Reads whole random pages (`TABLESAMPLE SYSTEM`), sized from `row_count`,
+/// so it stays cheap on large tables.
+pub async fn physical_order_sample(
+    pool: &PgPool,
+    schema: &str,
+    table: &str,
+    column: &str,
+    path: &[String],
+    row_count: i64,
+    limit: i64,
+) -> Result<Vec<(i64, String)>, sqlx::Error> {
+    // Oversample, since pages without the path or past the limit are dropped
+    let percent = if row_count > 0 {
+        (limit as f64 * 4.0 / row_count as f64 * 100.0).clamp(0.01, 100.0)
+    } else {
+        100.0
+    };
+    let column = quote_ident(column);
+    sqlx::query_as(&format!(
+        "SELECT ((ctid::text::point)[0])::bigint, {} #>> $1
+         FROM {}.{} TABLESAMPLE SYSTEM ($2::float4)
+         WHERE {} #>> $1 IS NOT NULL
+         LIMIT $3",
+        column,
+        quote_ident(schema),
+        quote_ident(table),
+        column
+    ))
+    .bind(path)
+    .bind(percent)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_discovery_data_types() {
+        assert_eq!(DiscoveryOptions::default().data_types(), vec!["jsonb"]);
+
+        let options = DiscoveryOptions {
+            include_json: true,
+            ..Default::default()
+        };
+        assert_eq!(options.data_types(), vec!["jsonb", "json"]);
+
+        let options = DiscoveryOptions {
+            include_json: true,
+            include_hstore: true,
+            include_arrays: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            options.data_types(),
+            vec!["jsonb", "json", "hstore", "jsonb[]", "json[]"]
+        );
+    }
+
+    fn column(schema: &str, table: &str, column: &str) -> JsonbColumn {
+        JsonbColumn {
+            schema: schema.to_string(),
+            table: table.to_string(),
+            column: column.to_string(),
+            data_type: ColumnType::Jsonb,
+            relation_kind: RelationKind::Table,
+            estimated_rows: None,
+            partitions: None,
+            partition_of: None,
+            dead_tuples: None,
+            last_analyze: None,
+            last_autoanalyze: None,
+            stats_age_secs: None,
+            indexes: Vec::new(),
+            storage: None,
+        }
+    }
+
+    #[test]
+    fn test_index_summary() {
+        let mut col = column("public", "users", "metadata");
+        assert_eq!(col.index_summary(), "none");
+
+        col.indexes = vec![
+            ColumnIndex {
+                name: "idx_meta".to_string(),
+                method: "gin".to_string(),
+                expression: false,
+                definition: String::new(),
+            },
+            ColumnIndex {
+                name: "idx_email".to_string(),
+                method: "btree".to_string(),
+                expression: true,
+                definition: String::new(),
+            },
+        ];
+        assert_eq!(
+            col.index_summary(),
+            "idx_meta (gin), idx_email (btree expr)"
+        );
+    }
+
+    #[test]
+    fn test_min_rows_filter() {
+        let options = DiscoveryOptions {
+            min_rows: Some(1000),
+            ..Default::default()
+        };
+
+        let mut col = column("public", "lookup", "data");
+        col.estimated_rows = Some(12);
+        assert!(!options.has_min_rows(&col));
+
+        col.estimated_rows = Some(1000);
+        assert!(options.has_min_rows(&col));
+
+        // No estimate available - keep the column
+        col.estimated_rows = None;
+        assert!(options.has_min_rows(&col));
+        assert!(DiscoveryOptions::default().has_min_rows(&col));
+    }
+
+    #[test]
+    fn test_stale_stats() {
+        let mut col = column("public", "users", "metadata");
+        col.estimated_rows = Some(1000);
+        col.dead_tuples = Some(10);
+        col.stats_age_secs = Some(3600);
+        assert!(!col.has_stale_stats());
+
+        col.stats_age_secs = Some(30 * 24 * 60 * 60);
+        assert!(col.has_stale_stats());
+
+        col.stats_age_secs = Some(3600);
+        col.dead_tuples = Some(500);
+        assert!(col.has_stale_stats());
+
+        col.dead_tuples = Some(0);
+        col.stats_age_secs = None;
+        assert!(col.has_stale_stats(), "never analyzed");
+
+        col.relation_kind = RelationKind::View;
+        col.estimated_rows = None;
+        assert!(!col.has_stale_stats());
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("public.*", "public.users"));
+        assert!(glob_match("*.audit_*", "billing.audit_log"));
+        assert!(glob_match("user?", "users"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("a*b*c", "aXXbYYc"));
+        assert!(!glob_match("public.*", "billing.users"));
+        assert!(!glob_match("user?", "user"));
+        assert!(!glob_match("a*b*c", "aXXbYY"));
+    }
+
+    #[test]
+    fn test_include_exclude_filters() {
+        let options = DiscoveryOptions {
+            include: vec!["public.*".to_string()],
+            exclude: vec!["*.audit_*".to_string()],
+            ..Default::default()
+        };
+
+        assert!(options.matches(&column("public", "users", "metadata")));
+        assert!(!options.matches(&column("public", "audit_log", "payload")));
+        assert!(!options.matches(&column("billing", "invoices", "data")));
+    }
+
+    #[test]
+    fn test_filters_match_schema_and_table_names() {
+        let options = DiscoveryOptions {
+            include: vec!["billing".to_string(), "public.users".to_string()],
+            ..Default::default()
+        };
+
+        assert!(options.matches(&column("billing", "invoices", "data")));
+        assert!(options.matches(&column("public", "users", "metadata")));
+        assert!(!options.matches(&column("public", "events", "payload")));
+        assert!(DiscoveryOptions::default().matches(&column("any", "table", "col")));
+    }
+
+    #[test]
+    fn test_discovery_relkinds() {
+        assert_eq!(DiscoveryOptions::default().relkinds(), vec!["r", "p"]);
+
+        let options = DiscoveryOptions {
+            include_views: true,
+            ..Default::default()
+        };
+        assert_eq!(options.relkinds(), vec!["r", "p", "v", "m"]);
+        assert_eq!(
+            RelationKind::from_relkind("m"),
+            Some(RelationKind::MaterializedView)
+        );
+        assert_eq!(RelationKind::from_relkind("i"), None);
+    }
+
+    #[test]
+    fn test_column_type_from_sql() {
+        assert_eq!(ColumnType::from_sql("jsonb"), Some(ColumnType::Jsonb));
+        assert_eq!(ColumnType::from_sql("json"), Some(ColumnType::Json));
+        assert_eq!(
+            ColumnType::from_sql("jsonb[]"),
+            Some(ColumnType::JsonbArray)
+        );
+        assert_eq!(ColumnType::from_sql("hstore"), Some(ColumnType::Hstore));
+        assert_eq!(ColumnType::from_sql("text"), None);
+        assert!(ColumnType::JsonArray.is_array());
+        assert!(!ColumnType::Hstore.is_array());
+    }
 }
@@ -0,0 +1,108 @@
+use pgdrift_core::sql::quote_ident;
+use serde_json::Value;
+use sqlx::PgPool;
+
+/// A query run with EXPLAIN ANALYZE before and after building an index
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeasuredPlan {
+    /// Whether the plan with the index scans it
+    pub uses_index: bool,
+    /// Total cost of the plan without the index
+    pub cost_without: f64,
+    /// Total cost of the plan with the index
+    pub cost_with: f64,
+    /// Execution time without the index, in milliseconds
+    pub time_without_ms: f64,
+    /// Execution time with the index, in milliseconds
+    pub time_with_ms: f64,
+}
+
+/// EXPLAIN ANALYZE `query` before and after building the index `name` with
+/// `index_sql`, a plain `CREATE INDEX` statement
+///
+/// Both statements may use unqualified names: `schema` comes first on the
+/// search path. Everything runs in one transaction that is rolled back, so
+/// the index is never committed, but it is really built: writes to the table
+/// wait until the transaction ends, and `lock_timeout` (e.g. "5s") bounds how
+/// long the build waits for its lock. Not for production databases.
+///
+/// `name` is read the way the recommended statements spell it: unquoted,
+/// so it's folded to lower case.
+pub async fn explain_analyze_index(
+    pool: &PgPool,
+    schema: &str,
+    name: &str,
+    index_sql: &str,
+    query: &str,
+    lock_timeout: &str,
+) -> Result<MeasuredPlan, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("SELECT set_config('search_path', $1, true), set_config('lock_timeout', $2, true)")
+        .bind(format!("{}, public", quote_ident(schema)))
+        .bind(lock_timeout)
+        .execute(&mut *tx)
+        .await?;
+
+    let explain = format!("EXPLAIN (ANALYZE, FORMAT JSON) {}", query);
+    let before: Value = sqlx::query_scalar(&explain).fetch_one(&mut *tx).await?;
+    sqlx::raw_sql(index_sql).execute(&mut *tx).await?;
+    let after: Value = sqlx::query_scalar(&explain).fetch_one(&mut *tx).await?;
+    tx.rollback().await?;
+
+    Ok(MeasuredPlan {
+        uses_index: scans_index(&after[0]["Plan"], &name.to_lowercase()),
+        cost_without: total_cost(&before),
+        cost_with: total_cost(&after),
+        time_without_ms: execution_time(&before),
+        time_with_ms: execution_time(&after),
+    })
+}
+
+pub(crate) fn total_cost(explain: &Value) -> f64 {
+    explain[0]["Plan"]["Total Cost"].as_f64().unwrap_or(0.0)
+}
+
+fn execution_time(explain: &Value) -> f64 {
+    explain[0]["Execution Time"].as_f64().unwrap_or(0.0)
+}
+
+/// Whether a plan node or any of its children scans the named index
+pub(crate) fn scans_index(plan: &Value, index_name: &str) -> bool {
+    plan["Index Name"].as_str() == Some(index_name)
+        || plan["Plans"]
+            .as_array()
+            .is_some_and(|plans| plans.iter().any(|p| scans_index(p, index_name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_scans_index() {
+        let explain = json!([{
+            "Plan": {
+                "Node Type": "Bitmap Heap Scan",
+                "Total Cost": 12.5,
+                "Plans": [{
+                    "Node Type": "Bitmap Index Scan",
+                    "Index Name": "<13542>btree_users_metadata"
+                }]
+            },
+            "Execution Time": 0.412
+        }]);
+
+        assert!(scans_index(
+            &explain[0]["Plan"],
+            "<13542>btree_users_metadata"
+        ));
+        assert!(!scans_index(
+            &explain[0]["Plan"],
+            "<13543>gin_users_metadata"
+        ));
+        assert_eq!(total_cost(&explain), 12.5);
+        assert_eq!(execution_time(&explain), 0.412);
+    }
+}
@@ -0,0 +1,59 @@
+use crate::explain::{scans_index, total_cost};
+use pgdrift_core::sql::quote_ident;
+use serde_json::Value;
+use sqlx::PgPool;
+
+/// Planner estimates for a query with and without a hypothetical index
+#[derive(Debug, Clone, PartialEq)]
+pub struct HypotheticalPlan {
+    /// Whether the plan with the hypothetical index scans it
+    pub uses_index: bool,
+    /// Total cost of the plan without the index
+    pub cost_without: f64,
+    /// Total cost of the plan with the index
+    pub cost_with: f64,
+}
+
+/// EXPLAIN `query` before and after creating `index_sql` as a hypothetical
+/// index with the hypopg extension, which must be installed
+///
+/// Both statements may use unqualified names: `schema` comes first on the
+/// search path. Hypothetical indexes only exist in the backend that created
+/// them, so everything runs in one transaction, which removes the index and
+/// is rolled back at the end.
+pub async fn explain_hypothetical(
+    pool: &PgPool,
+    schema: &str,
+    index_sql: &str,
+    query: &str,
+) -> Result<HypotheticalPlan, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("SELECT set_config('search_path', $1, true)")
+        .bind(format!("{}, public", quote_ident(schema)))
+        .execute(&mut *tx)
+        .await?;
+
+    let explain = format!("EXPLAIN (FORMAT JSON) {}", query);
+    let before: Value = sqlx::query_scalar(&explain).fetch_one(&mut *tx).await?;
+
+    let index_name: String = sqlx::query_scalar("SELECT indexname FROM hypopg_create_index($1)")
+        .bind(index_sql)
+        .fetch_one(&mut *tx)
+        .await?;
+    let after = sqlx::query_scalar::<_, Value>(&explain)
+        .fetch_one(&mut *tx)
+        .await;
+    // Drop the hypothetical index even if EXPLAIN failed
+    sqlx::query("SELECT hypopg_reset()")
+        .execute(&mut *tx)
+        .await?;
+    let after = after?;
+    tx.rollback().await?;
+
+    Ok(HypotheticalPlan {
+        uses_index: scans_index(&after[0]["Plan"], &index_name),
+        cost_without: total_cost(&before),
+        cost_with: total_cost(&after),
+    })
+}
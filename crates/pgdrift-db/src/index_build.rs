@@ -0,0 +1,120 @@
+use pgdrift_core::sql::quote_ident;
+use sqlx::{Connection, PgPool};
+use std::time::Duration;
+
+/// How often `pg_stat_progress_create_index` is read while an index builds
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Progress of an index build, from `pg_stat_progress_create_index`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BuildProgress {
+    /// Current phase, e.g. "building index: scanning table"
+    pub phase: String,
+    /// Blocks processed in the current phase
+    pub blocks_done: i64,
+    /// Blocks to process in the current phase, 0 when unknown
+    pub blocks_total: i64,
+}
+
+/// Run a `CREATE INDEX CONCURRENTLY` statement for the index `name`,
+/// reporting progress to `on_progress` while it builds
+///
+/// The statement runs on its own connection, with `schema` first on the
+/// search path and `lock_timeout` (e.g. "5s") bounding how long it waits
+/// for the locks it needs, so a busy table fails the build rather than
+/// stalling it. A failed concurrent build leaves an invalid index behind,
+/// which is dropped again before the error is returned.
+///
+/// Returns the size of the new index in bytes.
+pub async fn build_index_concurrently(
+    pool: &PgPool,
+    schema: &str,
+    name: &str,
+    statement: &str,
+    lock_timeout: &str,
+    mut on_progress: impl FnMut(&BuildProgress),
+) -> Result<i64, sqlx::Error> {
+    // Session settings must not leak back into the pool
+    let mut conn = pool.acquire().await?.detach();
+
+    sqlx::query(
+        "SELECT set_config('search_path', $1, false), set_config('lock_timeout', $2, false)",
+    )
+    .bind(format!("{}, public", quote_ident(schema)))
+    .bind(lock_timeout)
+    .execute(&mut conn)
+    .await?;
+    let pid: i32 = sqlx::query_scalar("SELECT pg_backend_pid()")
+        .fetch_one(&mut conn)
+        .await?;
+
+    let result = {
+        let build = sqlx::raw_sql(statement).execute(&mut conn);
+        tokio::pin!(build);
+        let mut interval = tokio::time::interval(PROGRESS_INTERVAL);
+        loop {
+            tokio::select! {
+                result = &mut build => break result,
+                _ = interval.tick() => {
+                    if let Ok(Some(progress)) = build_progress(pool, pid).await {
+                        on_progress(&progress);
+                    }
+                }
+            }
+        }
+    };
+    // A failed build may have broken the connection; the build's error is the one to report
+    let _ = conn.close().await;
+
+    let qualified = format!("{}.{}", quote_ident(schema), name);
+    if let Err(e) = result {
+        if is_invalid_index(pool, &qualified).await.unwrap_or(false) {
+            sqlx::raw_sql(&format!("DROP INDEX CONCURRENTLY IF EXISTS {}", qualified))
+                .execute(pool)
+                .await?;
+        }
+        return Err(e);
+    }
+
+    sqlx::query_scalar("SELECT pg_relation_size($1::regclass)")
+        .bind(qualified)
+        .fetch_one(pool)
+        .await
+}
+
+/// Whether an index already exists in `schema`
+///
+/// `name` is read the way the recommended statements spell it: unquoted,
+/// so it's folded to lower case.
+pub async fn index_exists(pool: &PgPool, schema: &str, name: &str) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar("SELECT to_regclass($1) IS NOT NULL")
+        .bind(format!("{}.{}", quote_ident(schema), name))
+        .fetch_one(pool)
+        .await
+}
+
+async fn build_progress(pool: &PgPool, pid: i32) -> Result<Option<BuildProgress>, sqlx::Error> {
+    let row: Option<(String, i64, i64)> = sqlx::query_as(
+        "SELECT phase, blocks_done, blocks_total \
+         FROM pg_stat_progress_create_index WHERE pid = $1",
+    )
+    .bind(pid)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|(phase, blocks_done, blocks_total)| BuildProgress {
+        phase,
+        blocks_done,
+        blocks_total,
+    }))
+}
+
+async fn is_invalid_index(pool: &PgPool, qualified: &str) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM pg_index \
+         WHERE indexrelid = to_regclass($1) AND NOT indisvalid)",
+    )
+    .bind(qualified)
+    .fetch_one(pool)
+    .await
+}
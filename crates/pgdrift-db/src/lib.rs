@@ -1,9 +1,18 @@
 pub mod connection;
 pub mod discovery;
+pub mod explain;
 pub mod fixtures;
+pub mod hypopg;
+pub mod index_build;
 pub mod sampler;
 pub mod test_utils; // Test utilities - available for integration tests
 
 pub use connection::ConnectionPool;
-pub use discovery::{JsonbColumn, discover_jsonb_columns};
-pub use sampler::{Sampler, SamplingStrategy};
+pub use discovery::{
+    ColumnIndex, ColumnStorage, ColumnType, DiscoveryOptions, JsonbColumn, RelationKind,
+    TableIndex, discover_columns, discover_jsonb_columns,
+};
+pub use sampler::{
+    Cohort, Cursor, PathStatistics, PathTypeCount, SampleFilter, Sampler, SamplingStrategy,
+    Watermark,
+};
@@ -1,6 +1,8 @@
+use crate::discovery::ColumnType;
 use futures::TryStreamExt;
 use indicatif::{ProgressBar, ProgressStyle};
-use serde_json::Value;
+use pgdrift_core::sql::{quote_ident, quote_literal};
+use serde_json::{Map, Value};
 use sqlx::PgPool;
 
 /// Sampling strategy selection based on table size
@@ -21,6 +23,10 @@ pub enum SamplingStrategy {
     /// TABLESAMPLE for larger tables (> 10M rows)
     /// Postgresql's built in sampling  - fast and no table locks
     TableSample { percentage: f32, limit: usize },
+
+    /// Most recent rows by a monotonic column (PK or timestamp)
+    /// ORDER BY column DESC LIMIT N - answers "is new data drifting?"
+    Tail { limit: usize, order_by: String },
 }
 
 impl SamplingStrategy {
@@ -59,6 +65,10 @@ impl SamplingStrategy {
                     }
                 }
             }
+            _ if is_plain_view(pool, schema, table).await? => {
+                // TABLESAMPLE is not supported on views
+                Self::Random { limit: sample_size }
+            }
             _ => {
                 // for very large tables
                 // Cap percentage at 100.0 (PostgreSQL limit) and minimum 0.1
@@ -71,6 +81,25 @@ impl SamplingStrategy {
         })
     }
 
+    /// Tail sampling of the newest rows
+    ///
+    /// Orders by `order_by` when given, otherwise by the table's numeric
+    /// primary key. Fails with `RowNotFound` if neither is available.
+    pub async fn tail(
+        pool: &PgPool,
+        schema: &str,
+        table: &str,
+        order_by: Option<&str>,
+        limit: usize,
+    ) -> Result<Self, sqlx::Error> {
+        let order_by = match order_by {
+            Some(column) => column.to_string(),
+            None => find_primary_key(pool, schema, table).await?,
+        };
+
+        Ok(Self::Tail { limit, order_by })
+    }
+
     /// Get the max number of samples that this strat should return
     pub fn max_samples(&self) -> usize {
         match self {
@@ -78,67 +107,390 @@ impl SamplingStrategy {
             Self::Random { limit } => *limit,
             Self::ReservoirPK { sample_size, .. } => *sample_size,
             Self::TableSample { limit, .. } => *limit,
+            Self::Tail { limit, .. } => *limit,
+        }
+    }
+
+    fn build_query(
+        &self,
+        schema: &str,
+        table: &str,
+        column: &str,
+        filter: &SampleFilter,
+        column_type: ColumnType,
+        repeatable: bool,
+    ) -> String {
+        self.build_select(
+            schema,
+            table,
+            column,
+            filter,
+            column_type,
+            None,
+            None,
+            repeatable,
+        )
+    }
+
+    /// Query returning the documents in random order, so that sampling can
+    /// stop after any number of them and still have a random sample
+    ///
+    /// Full scans and TABLESAMPLE return rows in physical order, the PK
+    /// reservoir in PK order and tail sampling newest first, so their rows
+    /// are shuffled. Repeatable runs order by a hash of the documents.
+    fn build_shuffled_query(
+        &self,
+        schema: &str,
+        table: &str,
+        column: &str,
+        filter: &SampleFilter,
+        column_type: ColumnType,
+        repeatable: bool,
+    ) -> String {
+        let query = self.build_query(schema, table, column, filter, column_type, repeatable);
+        if let Self::Random { .. } = self {
+            return query;
         }
+        let order = if repeatable {
+            "md5(s.document::text)"
+        } else {
+            "random()"
+        };
+        format!(
+            "SELECT s.document FROM ({}) AS s(document) ORDER BY {}",
+            query, order
+        )
+    }
+
+    /// Query returning `[document, key as text]` pairs of a scan read in the
+    /// order of the cursor's key, after the row it points to
+    fn build_cursor_query(
+        &self,
+        schema: &str,
+        table: &str,
+        column: &str,
+        filter: &SampleFilter,
+        column_type: ColumnType,
+        cursor: &Cursor,
+    ) -> String {
+        let key = format!("to_jsonb(t.{}::text)", quote_ident(&cursor.column));
+        self.build_select(
+            schema,
+            table,
+            column,
+            filter,
+            column_type,
+            Some(&key),
+            Some(cursor),
+            false,
+        )
+    }
+
+    /// Query returning `[document, other columns]` pairs instead of documents
+    ///
+    /// `columns` picks the other columns; all of them when `None`.
+    #[allow(clippy::too_many_arguments)]
+    fn build_row_query(
+        &self,
+        schema: &str,
+        table: &str,
+        column: &str,
+        filter: &SampleFilter,
+        column_type: ColumnType,
+        columns: Option<&[String]>,
+        repeatable: bool,
+    ) -> String {
+        let row = match columns {
+            None => format!("to_jsonb(t.*) - {}", quote_literal(column)),
+            Some(columns) => {
+                let pairs: Vec<String> = columns
+                    .iter()
+                    .map(|c| format!("{}, t.{}", quote_literal(c), quote_ident(c)))
+                    .collect();
+                format!("jsonb_build_object({})", pairs.join(", "))
+            }
+        };
+        self.build_select(
+            schema,
+            table,
+            column,
+            filter,
+            column_type,
+            Some(&row),
+            None,
+            repeatable,
+        )
     }
 
-    fn build_query(&self, schema: &str, table: &str, column: &str) -> String {
-        let schema_quoted = quote_identifier(schema);
-        let table_quoted = quote_identifier(table);
-        let column_quoted = quote_identifier(column);
+    /// `cursor` only applies to full scans
+    #[allow(clippy::too_many_arguments)]
+    fn build_select(
+        &self,
+        schema: &str,
+        table: &str,
+        column: &str,
+        filter: &SampleFilter,
+        column_type: ColumnType,
+        row: Option<&str>,
+        cursor: Option<&Cursor>,
+        repeatable: bool,
+    ) -> String {
+        let schema_quoted = quote_ident(schema);
+        let table_quoted = quote_ident(table);
+        // Non-jsonb columns are converted so containment filters and decoding
+        // behave exactly as for jsonb
+        let column_quoted = jsonb_expr(&quote_ident(column), column_type);
+        let predicates = filter.to_sql(&column_quoted, column_type.is_array());
+        // Columns of the row as a jsonb object, the table being aliased `t` in every query
+        let select = |document: &str| match row {
+            Some(row) => format!("jsonb_build_array({}, {})", document, row),
+            None => document.to_string(),
+        };
 
         match self {
             Self::Full => {
                 // Full table scan - deterministic, no randomization
-                format!(
-                    "SELECT {} FROM {}.{} WHERE {} IS NOT NULL",
-                    column_quoted, schema_quoted, table_quoted, column_quoted
-                )
+                let mut query = format!(
+                    "SELECT {} FROM {}.{} t WHERE {} IS NOT NULL{}",
+                    select(&column_quoted),
+                    schema_quoted,
+                    table_quoted,
+                    column_quoted,
+                    predicates
+                );
+                // Resumable scans go in key order, skipping the rows read before
+                if let Some(cursor) = cursor {
+                    let key = format!("t.{}", quote_ident(&cursor.column));
+                    if let Some(after) = &cursor.after {
+                        query.push_str(&format!(" AND {} > {}", key, quote_literal(after)));
+                    }
+                    query.push_str(&format!(" ORDER BY {}", key));
+                }
+                query
             }
             Self::Random { limit } => {
+                // Ordering by a hash of the documents picks the same ones every
+                // time, at the cost of hashing each of them
+                let order = if repeatable {
+                    format!("md5(({})::text)", column_quoted)
+                } else {
+                    "random()".to_string()
+                };
                 format!(
-                    "SELECT {} FROM {}.{} WHERE {} IS NOT NULL ORDER BY random() LIMIT {}",
-                    column_quoted, schema_quoted, table_quoted, column_quoted, limit
+                    "SELECT {} FROM {}.{} t WHERE {} IS NOT NULL{} ORDER BY {} LIMIT {}",
+                    select(&column_quoted),
+                    schema_quoted,
+                    table_quoted,
+                    column_quoted,
+                    predicates,
+                    order,
+                    limit
                 )
             }
             Self::ReservoirPK { sample_size, pk } => {
-                let pk_quoted = quote_identifier(pk);
+                let pk_quoted = quote_ident(pk);
+                let column_quoted = jsonb_expr(&format!("t.{}", quote_ident(column)), column_type);
                 // True reservoir sampling: generate random IDs and fetch via index
                 // This is MUCH faster than ORDER BY random() because it uses the PK index
+                // Repeatable runs spread the IDs with the golden ratio instead
+                let fraction = if repeatable {
+                    "((s.i * 0.6180339887498949) % 1)"
+                } else {
+                    "random()"
+                };
                 format!(
                     "WITH random_ids AS (
-                        SELECT floor(random() * (SELECT MAX({}) FROM {}.{}))::bigint AS rand_id
-                        FROM generate_series(1, {} * 2)
+                        SELECT floor({} * (SELECT MAX({}) FROM {}.{}))::bigint AS rand_id
+                        FROM generate_series(1, {} * 2) AS s(i)
                     )
-                    SELECT t.{}
+                    SELECT {}
                     FROM {}.{} t
                     INNER JOIN random_ids r ON t.{} = r.rand_id
-                    WHERE t.{} IS NOT NULL
+                    WHERE {} IS NOT NULL{}
                     LIMIT {}",
+                    fraction,
                     pk_quoted,
                     schema_quoted,
-                    table_quoted,  // MAX(pk)
-                    sample_size,   // Generate 2x samples to account for PK gaps
-                    column_quoted, // SELECT column
+                    table_quoted,           // MAX(pk)
+                    sample_size,            // Generate 2x samples to account for PK gaps
+                    select(&column_quoted), // SELECT column
                     schema_quoted,
                     table_quoted,  // FROM table
                     pk_quoted,     // JOIN ON pk
                     column_quoted, // WHERE column IS NOT NULL
+                    predicates,    // Extra row filters
                     sample_size    // LIMIT
                 )
             }
             Self::TableSample { percentage, limit } => {
                 format!(
-                    "SELECT {} FROM {}.{} TABLESAMPLE BERNOULLI({}) WHERE {} IS NOT NULL LIMIT {}",
-                    column_quoted, schema_quoted, table_quoted, percentage, column_quoted, limit
+                    "SELECT {} FROM {}.{} t TABLESAMPLE BERNOULLI({}){} WHERE {} IS NOT NULL{} LIMIT {}",
+                    select(&column_quoted),
+                    schema_quoted,
+                    table_quoted,
+                    percentage,
+                    if repeatable { " REPEATABLE (0)" } else { "" },
+                    column_quoted,
+                    predicates,
+                    limit
+                )
+            }
+            Self::Tail { limit, order_by } => {
+                format!(
+                    "SELECT {} FROM {}.{} t WHERE {} IS NOT NULL{} ORDER BY {} DESC LIMIT {}",
+                    select(&column_quoted),
+                    schema_quoted,
+                    table_quoted,
+                    column_quoted,
+                    predicates,
+                    quote_ident(order_by),
+                    limit
                 )
             }
         }
     }
 }
 
+/// Position of a full scan read in primary key order, for resuming it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cursor {
+    /// Numeric primary key the rows are ordered by
+    pub column: String,
+    /// Key of the last row read, as text - `None` to start at the first row
+    pub after: Option<String>,
+}
+
+/// Only sample rows past a watermark, e.g. rows inserted since the last run
+#[derive(Debug, Clone, PartialEq)]
+pub struct Watermark {
+    /// Monotonic column to compare against (usually the PK or a timestamp)
+    pub column: String,
+    /// Last value seen by the previous run - only rows above it are sampled
+    pub value: String,
+}
+
+/// Only sample rows on one side of a point in time, e.g. created in the last 30 days
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cohort {
+    /// Timestamp column the rows are split on
+    pub column: String,
+    /// How long before now the split lies, as a Postgres interval (`30 days`)
+    pub interval: String,
+    /// Sample rows at or after the split rather than before it
+    pub newer: bool,
+}
+
+/// Row restrictions applied on top of any sampling strategy
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SampleFilter {
+    /// Restrict sampling to rows above a watermark
+    pub since: Option<Watermark>,
+    /// Restrict sampling to rows at or below a watermark, e.g. the high-water
+    /// mark read when the run started
+    pub until: Option<Watermark>,
+    /// Only sample documents containing this JSON (`column @> value`)
+    pub contains: Option<Value>,
+    /// Only sample rows older or newer than a split point
+    pub cohort: Option<Cohort>,
+}
+
+impl SampleFilter {
+    /// Whether any restriction is set
+    pub fn is_empty(&self) -> bool {
+        self.since.is_none()
+            && self.until.is_none()
+            && self.contains.is_none()
+            && self.cohort.is_none()
+    }
+
+    /// Render the filter as extra `AND ...` predicates for a WHERE clause
+    ///
+    /// For array columns a row matches if any of its elements contains the
+    /// filter document.
+    fn to_sql(&self, column_quoted: &str, is_array: bool) -> String {
+        let mut sql = String::new();
+
+        if let Some(contains) = &self.contains {
+            let contains = if is_array {
+                Value::Array(vec![contains.clone()])
+            } else {
+                contains.clone()
+            };
+            sql.push_str(&format!(
+                " AND {} @> {}::jsonb",
+                column_quoted,
+                quote_literal(&contains.to_string())
+            ));
+        }
+
+        if let Some(watermark) = &self.since {
+            // The literal is left untyped so postgres coerces it to the column type
+            sql.push_str(&format!(
+                " AND {} > {}",
+                quote_ident(&watermark.column),
+                quote_literal(&watermark.value)
+            ));
+        }
+
+        if let Some(watermark) = &self.until {
+            sql.push_str(&format!(
+                " AND {} <= {}",
+                quote_ident(&watermark.column),
+                quote_literal(&watermark.value)
+            ));
+        }
+
+        if let Some(cohort) = &self.cohort {
+            sql.push_str(&format!(
+                " AND {} {} now() - {}::interval",
+                quote_ident(&cohort.column),
+                if cohort.newer { ">=" } else { "<" },
+                quote_literal(&cohort.interval)
+            ));
+        }
+
+        sql
+    }
+}
+
+/// Per-path type counts computed inside Postgres
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PathStatistics {
+    /// Number of sampled documents
+    pub total_documents: u64,
+    pub paths: Vec<PathTypeCount>,
+}
+
+/// How often a path held a value of one JSON type
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathTypeCount {
+    /// Dotted path, with `[]` for array elements (`items[].sku`)
+    pub path: String,
+    /// Nesting depth, counting array levels like the analyzer does
+    pub depth: usize,
+    /// `jsonb_typeof` name: object, array, string, number, boolean or null
+    pub json_type: String,
+    pub count: u64,
+}
+
+/// (path, depth, type, count, total documents); path columns are NULL when
+/// no document has any keys
+type PathStatisticsRow = (
+    Option<String>,
+    Option<i32>,
+    Option<String>,
+    Option<i64>,
+    i64,
+);
+
 pub struct Sampler {
     strategy: SamplingStrategy,
+    filter: SampleFilter,
+    column_type: ColumnType,
     show_progress: bool,
+    repeatable: bool,
+    shuffled: bool,
+    cursor: Option<Cursor>,
 }
 
 impl Sampler {
@@ -154,7 +506,12 @@ impl Sampler {
             SamplingStrategy::auto_select(pool, schema, table, estimated_rows, sample_size).await?;
         Ok(Self {
             strategy,
+            filter: SampleFilter::default(),
+            column_type: ColumnType::Jsonb,
             show_progress: true,
+            repeatable: false,
+            shuffled: false,
+            cursor: None,
         })
     }
 
@@ -162,16 +519,95 @@ impl Sampler {
     pub fn with_strategy(strategy: SamplingStrategy) -> Self {
         Self {
             strategy,
+            filter: SampleFilter::default(),
+            column_type: ColumnType::Jsonb,
             show_progress: true,
+            repeatable: false,
+            shuffled: false,
+            cursor: None,
         }
     }
 
+    /// The strategy picked for the table
+    pub fn strategy(&self) -> &SamplingStrategy {
+        &self.strategy
+    }
+
+    /// Restrict sampling to rows matching the filter
+    ///
+    /// Filters usually select a small slice of the table, so the PK reservoir
+    /// and TABLESAMPLE strategies (which pick rows from the whole table before
+    /// filtering) are swapped for random sampling of the slice.
+    pub fn with_filter(mut self, filter: SampleFilter) -> Self {
+        if !filter.is_empty() {
+            self.strategy = match self.strategy {
+                SamplingStrategy::ReservoirPK { sample_size, .. } => {
+                    SamplingStrategy::Random { limit: sample_size }
+                }
+                SamplingStrategy::TableSample { limit, .. } => SamplingStrategy::Random { limit },
+                other => other,
+            };
+        }
+        self.filter = filter;
+        self
+    }
+
+    /// Set the type of the sampled column (defaults to jsonb)
+    ///
+    /// json and hstore columns are converted to jsonb in the query; array
+    /// columns are flattened so each element is returned as its own sample.
+    pub fn column_type(mut self, column_type: ColumnType) -> Self {
+        self.column_type = column_type;
+        self
+    }
+
     /// Enable or disable prog bar
     pub fn show_progress(mut self, enabled: bool) -> Self {
         self.show_progress = enabled;
         self
     }
 
+    /// Sample the same rows on every run as long as the table doesn't change
+    ///
+    /// Random sampling orders by a hash of each document instead of
+    /// `random()`, which costs a hash per row; PK reservoir sampling picks
+    /// evenly spread IDs; TABLESAMPLE uses a fixed seed. Full scans and tail
+    /// sampling are repeatable anyway.
+    pub fn repeatable(mut self, enabled: bool) -> Self {
+        self.repeatable = enabled;
+        self
+    }
+
+    /// Have `sample_batches` hand out the rows in random order
+    ///
+    /// Needed whenever sampling may stop early, e.g. once an estimate is
+    /// precise enough: otherwise the first rows of a full scan are just the
+    /// oldest ones. Costs a sort of the sampled rows. Ignored by scans set up
+    /// with `resume_after`, which go in key order.
+    pub fn shuffled(mut self, enabled: bool) -> Self {
+        self.shuffled = enabled;
+        self
+    }
+
+    /// Read a full scan in primary key order, starting after the row `after`
+    ///
+    /// `sample_batches` then hands the key of the last row of each batch to
+    /// its callback, and a later scan given that key skips the rows read
+    /// before, so an interrupted scan can be resumed. Only full scans have a
+    /// stable order to resume; fails with `RowNotFound` if the table has no
+    /// numeric primary key.
+    pub async fn resume_after(
+        mut self,
+        pool: &PgPool,
+        schema: &str,
+        table: &str,
+        after: Option<String>,
+    ) -> Result<Self, sqlx::Error> {
+        let column = find_primary_key(pool, schema, table).await?;
+        self.cursor = Some(Cursor { column, after });
+        Ok(self)
+    }
+
     //// Execute the sampling strat and return jsonb valuies
     ///
     /// # Production safety
@@ -186,22 +622,15 @@ impl Sampler {
         table: &str,
         column: &str,
     ) -> Result<Vec<Value>, sqlx::Error> {
-        let query = self.strategy.build_query(schema, table, column);
-        let max_samples = self.strategy.max_samples();
-
-        // Create progress bar if enabled
-        let progress = if self.show_progress {
-            let pb = ProgressBar::new(max_samples as u64);
-            pb.set_style(
-                ProgressStyle::default_bar()
-                    .template("[{elapsed_precise}] {bar:40.cyan/blue} {pos}/{len} samples")
-                    .expect("Invalid progress bar template")
-                    .progress_chars("█▓▒░"),
-            );
-            Some(pb)
-        } else {
-            None
-        };
+        let query = self.strategy.build_query(
+            schema,
+            table,
+            column,
+            &self.filter,
+            self.column_type,
+            self.repeatable,
+        );
+        let progress = self.progress_bar();
 
         // Execute query and collect results
         let mut samples = Vec::new();
@@ -209,7 +638,7 @@ impl Sampler {
 
         // Use sqlx's streaming to handle large result sets
         while let Some(value) = rows.try_next().await? {
-            samples.push(value);
+            self.expand(value, &mut samples);
 
             if let Some(ref pb) = progress {
                 pb.set_position(samples.len() as u64);
@@ -222,9 +651,322 @@ impl Sampler {
 
         Ok(samples)
     }
+
+    /// Sample documents together with other columns of their row
+    ///
+    /// Each document comes with an object holding `columns`, or all of the
+    /// row's remaining columns when `None` (as `to_jsonb` renders them).
+    /// Elements of array columns all share their row's columns.
+    pub async fn sample_rows(
+        &self,
+        pool: &PgPool,
+        schema: &str,
+        table: &str,
+        column: &str,
+        columns: Option<&[String]>,
+    ) -> Result<Vec<(Value, Map<String, Value>)>, sqlx::Error> {
+        let query = self.strategy.build_row_query(
+            schema,
+            table,
+            column,
+            &self.filter,
+            self.column_type,
+            columns,
+            self.repeatable,
+        );
+        let progress = self.progress_bar();
+
+        let mut rows = Vec::new();
+        let mut documents = Vec::new();
+        let mut fetched = sqlx::query_scalar::<_, Value>(&query).fetch(pool);
+        while let Some(value) = fetched.try_next().await? {
+            let Value::Array(mut pair) = value else {
+                continue;
+            };
+            let (Some(Value::Object(row)), Some(document)) = (pair.pop(), pair.pop()) else {
+                continue;
+            };
+            documents.clear();
+            self.expand(document, &mut documents);
+            rows.extend(documents.drain(..).map(|document| (document, row.clone())));
+
+            if let Some(ref pb) = progress {
+                pb.set_position(rows.len() as u64);
+            }
+        }
+
+        if let Some(pb) = progress {
+            pb.finish_with_message(format!("Collected {} samples", rows.len()));
+        }
+
+        Ok(rows)
+    }
+
+    /// Stream samples in batches, stopping as soon as `on_batch` returns false
+    ///
+    /// The strategy's row limit acts as an upper bound. Rows are read from a
+    /// single query, so batches never repeat documents. Along with each batch
+    /// `on_batch` gets the key of its last row when the scan was set up with
+    /// `resume_after`. Returns the number of documents handed to `on_batch`.
+    pub async fn sample_batches<F>(
+        &self,
+        pool: &PgPool,
+        schema: &str,
+        table: &str,
+        column: &str,
+        batch_size: usize,
+        mut on_batch: F,
+    ) -> Result<usize, sqlx::Error>
+    where
+        F: FnMut(&[Value], Option<&str>) -> bool,
+    {
+        let query = match (&self.strategy, &self.cursor) {
+            (SamplingStrategy::Full, Some(cursor)) => self.strategy.build_cursor_query(
+                schema,
+                table,
+                column,
+                &self.filter,
+                self.column_type,
+                cursor,
+            ),
+            _ if self.shuffled => self.strategy.build_shuffled_query(
+                schema,
+                table,
+                column,
+                &self.filter,
+                self.column_type,
+                self.repeatable,
+            ),
+            _ => self.strategy.build_query(
+                schema,
+                table,
+                column,
+                &self.filter,
+                self.column_type,
+                self.repeatable,
+            ),
+        };
+        let keyed = self.cursor.is_some() && self.strategy == SamplingStrategy::Full;
+        let progress = self.progress_bar();
+        let batch_size = batch_size.max(1);
+
+        let mut batch = Vec::with_capacity(batch_size);
+        let mut total = 0;
+        let mut last_key = None;
+        let mut rows = sqlx::query_scalar::<_, Value>(&query).fetch(pool);
+
+        while let Some(value) = rows.try_next().await? {
+            let document = match value {
+                Value::Array(mut pair) if keyed => {
+                    let (Some(Value::String(key)), Some(document)) = (pair.pop(), pair.pop())
+                    else {
+                        continue;
+                    };
+                    last_key = Some(key);
+                    document
+                }
+                value => value,
+            };
+            let before = batch.len();
+            self.expand(document, &mut batch);
+            total += batch.len() - before;
+
+            if let Some(ref pb) = progress {
+                pb.set_position(total as u64);
+            }
+
+            if batch.len() >= batch_size {
+                let more = on_batch(&batch, last_key.as_deref());
+                batch.clear();
+                if !more {
+                    break;
+                }
+            }
+        }
+
+        if !batch.is_empty() {
+            on_batch(&batch, last_key.as_deref());
+        }
+
+        if let Some(pb) = progress {
+            pb.finish_with_message(format!("Collected {} samples", total));
+        }
+
+        Ok(total)
+    }
+
+    /// Count the documents the filter matches, whatever the strategy
+    ///
+    /// Divided by the number of documents sampled, this gives the rows each
+    /// sampled document stands for. Array columns count their non-null
+    /// elements, as sampling does.
+    pub async fn count_documents(
+        &self,
+        pool: &PgPool,
+        schema: &str,
+        table: &str,
+        column: &str,
+    ) -> Result<u64, sqlx::Error> {
+        let count: i64 = sqlx::query_scalar(&self.count_query(schema, table, column))
+            .fetch_one(pool)
+            .await?;
+        Ok(count as u64)
+    }
+
+    fn count_query(&self, schema: &str, table: &str, column: &str) -> String {
+        let query = SamplingStrategy::Full.build_query(
+            schema,
+            table,
+            column,
+            &self.filter,
+            self.column_type,
+            false,
+        );
+        if self.column_type.is_array() {
+            format!(
+                "SELECT count(*) FROM ({}) AS s(document) \
+                 CROSS JOIN LATERAL jsonb_array_elements(s.document) AS e(element) \
+                 WHERE e.element <> 'null'::jsonb",
+                query
+            )
+        } else {
+            format!("SELECT count(*) FROM ({}) AS s(document)", query)
+        }
+    }
+
+    /// Count paths and their types inside Postgres instead of fetching documents
+    ///
+    /// The sampled documents are walked with a recursive `jsonb_each` /
+    /// `jsonb_array_elements` query and only the aggregates are returned, so
+    /// no JSON is shipped to the client. Subtrees nested `max_depth` levels
+    /// deep are not walked.
+    pub async fn path_statistics(
+        &self,
+        pool: &PgPool,
+        schema: &str,
+        table: &str,
+        column: &str,
+        max_depth: usize,
+    ) -> Result<PathStatistics, sqlx::Error> {
+        let query = self.path_statistics_query(schema, table, column, max_depth);
+        let rows: Vec<PathStatisticsRow> = sqlx::query_as(&query).fetch_all(pool).await?;
+
+        // Every row carries the document count; documents without any keys
+        // yield a single row with no path
+        let total_documents = rows.first().map_or(0, |row| row.4 as u64);
+        let paths = rows
+            .into_iter()
+            .filter_map(|(path, depth, json_type, count, _)| {
+                Some(PathTypeCount {
+                    path: path?,
+                    depth: depth? as usize,
+                    json_type: json_type?,
+                    count: count? as u64,
+                })
+            })
+            .collect();
+
+        Ok(PathStatistics {
+            total_documents,
+            paths,
+        })
+    }
+
+    /// Sampled documents, one per row, with array columns flattened
+    fn documents_query(&self, schema: &str, table: &str, column: &str) -> String {
+        let sample = self.strategy.build_query(
+            schema,
+            table,
+            column,
+            &self.filter,
+            self.column_type,
+            self.repeatable,
+        );
+        if self.column_type.is_array() {
+            format!(
+                "SELECT e.doc FROM ({}) s(arr), jsonb_array_elements(s.arr) e(doc) \
+                 WHERE e.doc <> 'null'::jsonb",
+                sample
+            )
+        } else {
+            format!("SELECT s.doc FROM ({}) s(doc)", sample)
+        }
+    }
+
+    /// Aggregate query returning `PathStatisticsRow`s
+    ///
+    /// Array elements are walked into (as `path[]`) but, as in the analyzer,
+    /// not counted as paths of their own.
+    fn path_statistics_query(
+        &self,
+        schema: &str,
+        table: &str,
+        column: &str,
+        max_depth: usize,
+    ) -> String {
+        format!(
+            r#"
+            WITH RECURSIVE docs AS MATERIALIZED ({}),
+            walk(path, value, depth, is_element) AS (
+                SELECT e.key, e.value, 1, false
+                FROM docs d,
+                     jsonb_each(CASE WHEN jsonb_typeof(d.doc) = 'object' THEN d.doc ELSE '{{}}'::jsonb END) e
+              UNION ALL
+                SELECT c.path, c.value, w.depth + 1, c.is_element
+                FROM walk w
+                CROSS JOIN LATERAL (
+                    SELECT w.path || '.' || e.key, e.value, false
+                    FROM jsonb_each(CASE WHEN jsonb_typeof(w.value) = 'object' THEN w.value ELSE '{{}}'::jsonb END) e
+                  UNION ALL
+                    SELECT w.path || '[]', e.value, true
+                    FROM jsonb_array_elements(CASE WHEN jsonb_typeof(w.value) = 'array' THEN w.value ELSE '[]'::jsonb END) e
+                ) c(path, value, is_element)
+                WHERE w.depth < {}
+            )
+            SELECT a.path, a.depth, a.json_type, a.count, t.total
+            FROM (SELECT count(*) FROM docs) t(total)
+            LEFT JOIN (
+                SELECT path, max(depth), jsonb_typeof(value), count(*)
+                FROM walk
+                WHERE NOT is_element
+                GROUP BY path, jsonb_typeof(value)
+            ) a(path, depth, json_type, count) ON true
+            ORDER BY a.path
+            "#,
+            self.documents_query(schema, table, column),
+            max_depth
+        )
+    }
+
+    /// Push a fetched value, flattening array columns into their elements
+    fn expand(&self, value: Value, samples: &mut Vec<Value>) {
+        match value {
+            Value::Array(elements) if self.column_type.is_array() => {
+                samples.extend(elements.into_iter().filter(|e| !e.is_null()));
+            }
+            value => samples.push(value),
+        }
+    }
+
+    /// Create the progress bar for a sampling run, if enabled
+    fn progress_bar(&self) -> Option<ProgressBar> {
+        if !self.show_progress {
+            return None;
+        }
+
+        let pb = ProgressBar::new(self.strategy.max_samples() as u64);
+        pb.set_style(
+            ProgressStyle::default_bar()
+                .template("[{elapsed_precise}] {bar:40.cyan/blue} {pos}/{len} samples")
+                .expect("Invalid progress bar template")
+                .progress_chars("█▓▒░"),
+        );
+        Some(pb)
+    }
+
     /// Get information about the sampling strategy
     pub fn strategy_info(&self) -> String {
-        match &self.strategy {
+        let info = match &self.strategy {
             SamplingStrategy::Full => "Full table scan (all non-NULL rows)".to_string(),
             SamplingStrategy::Random { limit } => {
                 format!("Random sampling (up to {} rows)", limit)
@@ -238,10 +980,59 @@ impl Sampler {
             SamplingStrategy::TableSample { percentage, limit } => {
                 format!("TABLESAMPLE {:.2}% (up to {} rows)", percentage, limit)
             }
+            SamplingStrategy::Tail { limit, order_by } => {
+                format!("Tail sampling: newest {} rows by '{}'", limit, order_by)
+            }
+        };
+
+        let mut info = info;
+        if let Some(watermark) = &self.filter.since {
+            info.push_str(&format!(
+                ", rows with {} > {}",
+                watermark.column, watermark.value
+            ));
+        }
+        if let Some(watermark) = &self.filter.until {
+            info.push_str(&format!(
+                ", rows with {} <= {}",
+                watermark.column, watermark.value
+            ));
+        }
+        if let Some(contains) = &self.filter.contains {
+            info.push_str(&format!(", documents containing {}", contains));
         }
+        if let Some(cohort) = &self.filter.cohort {
+            info.push_str(&format!(
+                ", rows with {} {} {} ago",
+                cohort.column,
+                if cohort.newer { "since" } else { "before" },
+                cohort.interval
+            ));
+        }
+        info
     }
 }
 
+/// Get the current high-water mark of a column, as text
+///
+/// Used after an incremental run to record where the next run should start.
+/// Returns `None` when the table is empty.
+pub async fn max_column_value(
+    pool: &PgPool,
+    schema: &str,
+    table: &str,
+    column: &str,
+) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar(&format!(
+        "SELECT MAX({})::text FROM {}.{}",
+        quote_ident(column),
+        quote_ident(schema),
+        quote_ident(table)
+    ))
+    .fetch_one(pool)
+    .await
+}
+
 async fn find_primary_key(pool: &PgPool, schema: &str, table: &str) -> Result<String, sqlx::Error> {
     let pk: Option<String> = sqlx::query_scalar(
         r#"
@@ -263,8 +1054,32 @@ async fn find_primary_key(pool: &PgPool, schema: &str, table: &str) -> Result<St
     pk.ok_or_else(|| sqlx::Error::RowNotFound)
 }
 
-fn quote_identifier(identifier: &str) -> String {
-    format!("\"{}\"", identifier.replace("\"", "\"\""))
+/// Expression reading a column of the given type as jsonb
+fn jsonb_expr(column: &str, column_type: ColumnType) -> String {
+    match column_type {
+        ColumnType::Jsonb => column.to_string(),
+        ColumnType::Json => format!("{}::jsonb", column),
+        ColumnType::Hstore => format!("hstore_to_jsonb({})", column),
+        ColumnType::JsonbArray | ColumnType::JsonArray => format!("to_jsonb({})", column),
+    }
+}
+
+/// Whether the relation is a plain (non-materialized) view
+async fn is_plain_view(pool: &PgPool, schema: &str, table: &str) -> Result<bool, sqlx::Error> {
+    let relkind: Option<String> = sqlx::query_scalar(
+        r#"
+          SELECT c.relkind::text
+          FROM pg_class c
+          JOIN pg_namespace n ON n.oid = c.relnamespace
+          WHERE n.nspname = $1 AND c.relname = $2
+          "#,
+    )
+    .bind(schema)
+    .bind(table)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(relkind.as_deref() == Some("v"))
 }
 
 #[cfg(test)]
@@ -292,7 +1107,14 @@ mod tests {
     #[test]
     fn test_build_query_random() {
         let strategy = SamplingStrategy::Random { limit: 1000 };
-        let query = strategy.build_query("public", "users", "metadata");
+        let query = strategy.build_query(
+            "public",
+            "users",
+            "metadata",
+            &SampleFilter::default(),
+            ColumnType::Jsonb,
+            false,
+        );
 
         assert!(query.contains("ORDER BY random()"));
         assert!(query.contains("LIMIT 1000"));
@@ -308,7 +1130,14 @@ mod tests {
             sample_size: 5000,
             pk: "id".to_string(),
         };
-        let query = strategy.build_query("public", "users", "metadata");
+        let query = strategy.build_query(
+            "public",
+            "users",
+            "metadata",
+            &SampleFilter::default(),
+            ColumnType::Jsonb,
+            false,
+        );
 
         assert!(query.contains("WITH random_ids"));
         assert!(query.contains("generate_series"));
@@ -323,7 +1152,14 @@ mod tests {
             percentage: 0.5,
             limit: 10000,
         };
-        let query = strategy.build_query("public", "users", "metadata");
+        let query = strategy.build_query(
+            "public",
+            "users",
+            "metadata",
+            &SampleFilter::default(),
+            ColumnType::Jsonb,
+            false,
+        );
 
         assert!(query.contains("TABLESAMPLE BERNOULLI(0.5)"));
         assert!(query.contains("LIMIT 10000"));
@@ -331,18 +1167,458 @@ mod tests {
     }
 
     #[test]
-    fn test_quote_identifier() {
-        assert_eq!(quote_identifier("simple"), "\"simple\"");
-        assert_eq!(quote_identifier("with\"quote"), "\"with\"\"quote\"");
-        assert_eq!(quote_identifier("schema.table"), "\"schema.table\"");
+    fn test_build_query_repeatable() {
+        let query = |strategy: SamplingStrategy| {
+            strategy.build_query(
+                "public",
+                "users",
+                "metadata",
+                &SampleFilter::default(),
+                ColumnType::Jsonb,
+                true,
+            )
+        };
+
+        let random = query(SamplingStrategy::Random { limit: 1000 });
+        assert!(random.contains("ORDER BY md5((\"metadata\")::text) LIMIT 1000"));
+        let reservoir = query(SamplingStrategy::ReservoirPK {
+            sample_size: 1000,
+            pk: "id".to_string(),
+        });
+        assert!(reservoir.contains("floor(((s.i * 0.6180339887498949) % 1) * (SELECT MAX"));
+        let tablesample = query(SamplingStrategy::TableSample {
+            percentage: 0.5,
+            limit: 10000,
+        });
+        assert!(tablesample.contains("TABLESAMPLE BERNOULLI(0.5) REPEATABLE (0) WHERE"));
+        for query in [random, reservoir, tablesample] {
+            assert!(!query.contains("random()"), "{}", query);
+        }
+    }
+
+    #[test]
+    fn test_build_query_tail() {
+        let strategy = SamplingStrategy::Tail {
+            limit: 500,
+            order_by: "created_at".to_string(),
+        };
+        let query = strategy.build_query(
+            "public",
+            "events",
+            "payload",
+            &SampleFilter::default(),
+            ColumnType::Jsonb,
+            false,
+        );
+
+        assert!(query.contains("ORDER BY \"created_at\" DESC"));
+        assert!(query.contains("LIMIT 500"));
+        assert!(query.contains("IS NOT NULL"));
+        assert!(!query.contains("random()"));
+    }
+
+    #[test]
+    fn test_build_shuffled_query() {
+        let strategies = [
+            SamplingStrategy::Full,
+            SamplingStrategy::Random { limit: 1000 },
+            SamplingStrategy::ReservoirPK {
+                sample_size: 1000,
+                pk: "id".to_string(),
+            },
+            SamplingStrategy::TableSample {
+                percentage: 0.5,
+                limit: 1000,
+            },
+            SamplingStrategy::Tail {
+                limit: 1000,
+                order_by: "id".to_string(),
+            },
+        ];
+        for strategy in &strategies {
+            let query = |repeatable| {
+                strategy.build_shuffled_query(
+                    "public",
+                    "events",
+                    "payload",
+                    &SampleFilter::default(),
+                    ColumnType::Jsonb,
+                    repeatable,
+                )
+            };
+            let random = query(false);
+            assert!(random.contains("ORDER BY random()"), "{}", random);
+            let repeatable = query(true);
+            assert!(repeatable.contains("ORDER BY md5("), "{}", repeatable);
+        }
+
+        // The limit still applies to the sampled rows, before shuffling
+        let full = SamplingStrategy::Full.build_shuffled_query(
+            "public",
+            "events",
+            "payload",
+            &SampleFilter::default(),
+            ColumnType::Jsonb,
+            false,
+        );
+        assert!(full.ends_with("IS NOT NULL) AS s(document) ORDER BY random()"));
     }
 
     #[test]
-    fn test_quote_identifier_sql_injection() {
-        // Ensure SQL injection attempts are properly escaped
+    fn test_build_cursor_query() {
+        let query = |after: Option<&str>| {
+            SamplingStrategy::Full.build_cursor_query(
+                "public",
+                "events",
+                "payload",
+                &SampleFilter::default(),
+                ColumnType::Jsonb,
+                &Cursor {
+                    column: "id".to_string(),
+                    after: after.map(String::from),
+                },
+            )
+        };
+
+        let first = query(None);
+        assert!(
+            first.starts_with("SELECT jsonb_build_array(\"payload\", to_jsonb(t.\"id\"::text))")
+        );
+        assert!(first.ends_with("IS NOT NULL ORDER BY t.\"id\""));
+        let resumed = query(Some("41"));
+        assert!(resumed.ends_with("IS NOT NULL AND t.\"id\" > '41' ORDER BY t.\"id\""));
+    }
+
+    #[test]
+    fn test_build_row_query() {
+        let strategy = SamplingStrategy::Random { limit: 1000 };
+        let query = strategy.build_row_query(
+            "public",
+            "users",
+            "metadata",
+            &SampleFilter::default(),
+            ColumnType::Json,
+            None,
+            false,
+        );
+        assert!(query.starts_with(
+            "SELECT jsonb_build_array(\"metadata\"::jsonb, to_jsonb(t.*) - 'metadata') FROM \"public\".\"users\" t"
+        ));
+
+        let strategy = SamplingStrategy::ReservoirPK {
+            sample_size: 500,
+            pk: "id".to_string(),
+        };
+        let query = strategy.build_row_query(
+            "public",
+            "users",
+            "metadata",
+            &SampleFilter::default(),
+            ColumnType::Jsonb,
+            None,
+            false,
+        );
+        assert!(
+            query.contains("SELECT jsonb_build_array(t.\"metadata\", to_jsonb(t.*) - 'metadata')")
+        );
+
+        let query = SamplingStrategy::Random { limit: 1000 }.build_row_query(
+            "public",
+            "users",
+            "metadata",
+            &SampleFilter::default(),
+            ColumnType::Jsonb,
+            Some(&["tenant_id".to_string()]),
+            false,
+        );
+        assert!(query.starts_with(
+            "SELECT jsonb_build_array(\"metadata\", jsonb_build_object('tenant_id', t.\"tenant_id\")) FROM"
+        ));
+    }
+
+    #[test]
+    fn test_path_statistics_query() {
+        let sampler = Sampler::with_strategy(SamplingStrategy::Random { limit: 1000 });
+        let query = sampler.path_statistics_query("public", "events", "payload", 64);
+
+        assert!(query.contains("WITH RECURSIVE docs AS MATERIALIZED"));
+        assert!(query.contains("ORDER BY random() LIMIT 1000"));
+        assert!(query.contains("jsonb_each"));
+        assert!(query.contains("w.path || '[]'"));
+        assert!(query.contains("WHERE w.depth < 64"));
+        assert!(query.contains("WHERE NOT is_element"));
+        assert!(!query.contains("jsonb_array_elements(s.arr)"));
+
+        let sampler =
+            Sampler::with_strategy(SamplingStrategy::Full).column_type(ColumnType::JsonbArray);
+        let query = sampler.path_statistics_query("public", "events", "payloads", 8);
+        assert!(query.contains("jsonb_array_elements(s.arr)"));
+        assert!(query.contains("to_jsonb(\"payloads\")"));
+    }
+
+    #[test]
+    fn test_strategy_info_tail() {
+        let sampler = Sampler::with_strategy(SamplingStrategy::Tail {
+            limit: 1000,
+            order_by: "id".to_string(),
+        });
         assert_eq!(
-            quote_identifier("table\"; DROP TABLE users; --"),
-            "\"table\"\"; DROP TABLE users; --\""
+            sampler.strategy_info(),
+            "Tail sampling: newest 1000 rows by 'id'"
+        );
+    }
+
+    #[test]
+    fn test_build_query_with_watermark() {
+        let strategy = SamplingStrategy::Random { limit: 1000 };
+        let filter = SampleFilter {
+            since: Some(Watermark {
+                column: "id".to_string(),
+                value: "42".to_string(),
+            }),
+            ..Default::default()
+        };
+        let query = strategy.build_query(
+            "public",
+            "users",
+            "metadata",
+            &filter,
+            ColumnType::Jsonb,
+            false,
+        );
+
+        assert!(query.contains("IS NOT NULL AND \"id\" > '42'"));
+        assert!(query.contains("ORDER BY random()"));
+    }
+
+    #[test]
+    fn test_build_query_with_upper_watermark() {
+        let strategy = SamplingStrategy::Random { limit: 1000 };
+        let filter = SampleFilter {
+            since: Some(Watermark {
+                column: "id".to_string(),
+                value: "42".to_string(),
+            }),
+            until: Some(Watermark {
+                column: "id".to_string(),
+                value: "99".to_string(),
+            }),
+            ..Default::default()
+        };
+        let query = strategy.build_query(
+            "public",
+            "users",
+            "metadata",
+            &filter,
+            ColumnType::Jsonb,
+            false,
+        );
+
+        assert!(query.contains("IS NOT NULL AND \"id\" > '42' AND \"id\" <= '99'"));
+    }
+
+    #[test]
+    fn test_count_query() {
+        let sampler = Sampler::with_strategy(SamplingStrategy::Random { limit: 100 }).with_filter(
+            SampleFilter {
+                until: Some(Watermark {
+                    column: "id".to_string(),
+                    value: "99".to_string(),
+                }),
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            sampler.count_query("public", "users", "metadata"),
+            "SELECT count(*) FROM (SELECT \"metadata\" FROM \"public\".\"users\" t \
+             WHERE \"metadata\" IS NOT NULL AND \"id\" <= '99') AS s(document)"
+        );
+
+        let sampler = sampler.column_type(ColumnType::JsonbArray);
+        let query = sampler.count_query("public", "users", "events");
+        assert!(query.contains("FROM (SELECT to_jsonb(\"events\") FROM"));
+        assert!(query.ends_with("WHERE e.element <> 'null'::jsonb"));
+    }
+
+    #[test]
+    fn test_watermark_value_is_quoted() {
+        let filter = SampleFilter {
+            since: Some(Watermark {
+                column: "created_at".to_string(),
+                value: "2025-01-01'; DROP TABLE users; --".to_string(),
+            }),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            filter.to_sql("\"metadata\"", false),
+            " AND \"created_at\" > '2025-01-01''; DROP TABLE users; --'"
+        );
+    }
+
+    #[test]
+    fn test_watermark_replaces_reservoir_strategy() {
+        let sampler = Sampler::with_strategy(SamplingStrategy::ReservoirPK {
+            sample_size: 500,
+            pk: "id".to_string(),
+        })
+        .with_filter(SampleFilter {
+            since: Some(Watermark {
+                column: "id".to_string(),
+                value: "10".to_string(),
+            }),
+            ..Default::default()
+        });
+
+        assert_eq!(sampler.strategy, SamplingStrategy::Random { limit: 500 });
+    }
+
+    #[test]
+    fn test_strategy_info_with_watermark() {
+        let sampler = Sampler::with_strategy(SamplingStrategy::Full).with_filter(SampleFilter {
+            since: Some(Watermark {
+                column: "id".to_string(),
+                value: "100".to_string(),
+            }),
+            ..Default::default()
+        });
+        assert_eq!(
+            sampler.strategy_info(),
+            "Full table scan (all non-NULL rows), rows with id > 100"
+        );
+    }
+
+    #[test]
+    fn test_build_query_with_containment_filter() {
+        let strategy = SamplingStrategy::Random { limit: 100 };
+        let filter = SampleFilter {
+            contains: Some(serde_json::json!({"type": "o'rder"})),
+            ..Default::default()
+        };
+        let query = strategy.build_query(
+            "public",
+            "events",
+            "payload",
+            &filter,
+            ColumnType::Jsonb,
+            false,
+        );
+
+        assert!(query.contains("AND \"payload\" @> '{\"type\":\"o''rder\"}'::jsonb"));
+    }
+
+    #[test]
+    fn test_build_query_with_cohort() {
+        let strategy = SamplingStrategy::Random { limit: 100 };
+        let mut filter = SampleFilter {
+            cohort: Some(Cohort {
+                column: "created_at".to_string(),
+                interval: "30 days".to_string(),
+                newer: true,
+            }),
+            ..Default::default()
+        };
+        let query = strategy.build_query(
+            "public",
+            "events",
+            "payload",
+            &filter,
+            ColumnType::Jsonb,
+            false,
+        );
+        assert!(query.contains("AND \"created_at\" >= now() - '30 days'::interval"));
+
+        filter.cohort.as_mut().unwrap().newer = false;
+        let sampler = Sampler::with_strategy(strategy).with_filter(filter);
+        assert_eq!(
+            sampler.strategy_info(),
+            "Random sampling (up to 100 rows), rows with created_at before 30 days ago"
+        );
+    }
+
+    #[test]
+    fn test_build_query_casts_json_column() {
+        let strategy = SamplingStrategy::ReservoirPK {
+            sample_size: 100,
+            pk: "id".to_string(),
+        };
+        let filter = SampleFilter {
+            contains: Some(serde_json::json!({"type": "order"})),
+            ..Default::default()
+        };
+        let query =
+            strategy.build_query("public", "legacy", "doc", &filter, ColumnType::Json, false);
+
+        assert!(query.contains("SELECT t.\"doc\"::jsonb"));
+        assert!(query.contains("WHERE t.\"doc\"::jsonb IS NOT NULL"));
+        assert!(query.contains("AND \"doc\"::jsonb @> '{\"type\":\"order\"}'::jsonb"));
+    }
+
+    #[test]
+    fn test_build_query_hstore_and_array_columns() {
+        let strategy = SamplingStrategy::Random { limit: 100 };
+        let filter = SampleFilter {
+            contains: Some(serde_json::json!({"type": "order"})),
+            ..Default::default()
+        };
+
+        let query = strategy.build_query(
+            "public",
+            "legacy",
+            "attrs",
+            &filter,
+            ColumnType::Hstore,
+            false,
+        );
+        assert!(query.starts_with("SELECT hstore_to_jsonb(\"attrs\")"));
+
+        let query = strategy.build_query(
+            "public",
+            "legacy",
+            "events",
+            &filter,
+            ColumnType::JsonbArray,
+            false,
+        );
+        assert!(query.starts_with("SELECT to_jsonb(\"events\")"));
+        // Array rows match if any element contains the filter
+        assert!(query.contains("AND to_jsonb(\"events\") @> '[{\"type\":\"order\"}]'::jsonb"));
+    }
+
+    #[test]
+    fn test_array_columns_are_flattened() {
+        let sampler =
+            Sampler::with_strategy(SamplingStrategy::Full).column_type(ColumnType::JsonbArray);
+        let mut samples = Vec::new();
+
+        sampler.expand(serde_json::json!([{"a": 1}, null, {"b": 2}]), &mut samples);
+        assert_eq!(
+            samples,
+            vec![serde_json::json!({"a": 1}), serde_json::json!({"b": 2})]
+        );
+
+        // Plain jsonb arrays are documents in their own right
+        let sampler = Sampler::with_strategy(SamplingStrategy::Full);
+        let mut samples = Vec::new();
+        sampler.expand(serde_json::json!([1, 2]), &mut samples);
+        assert_eq!(samples.len(), 1);
+    }
+
+    #[test]
+    fn test_containment_filter_replaces_tablesample_strategy() {
+        let sampler = Sampler::with_strategy(SamplingStrategy::TableSample {
+            percentage: 1.0,
+            limit: 200,
+        })
+        .with_filter(SampleFilter {
+            contains: Some(serde_json::json!({"type": "order"})),
+            ..Default::default()
+        });
+
+        assert_eq!(sampler.strategy, SamplingStrategy::Random { limit: 200 });
+        assert_eq!(
+            sampler.strategy_info(),
+            "Random sampling (up to 200 rows), documents containing {\"type\":\"order\"}"
         );
     }
 
@@ -0,0 +1,244 @@
+use crate::output::AnalysisResult;
+use anyhow::{Context, Result};
+use pgdrift_core::drift::Severity;
+use pgdrift_core::report::diff as report;
+use pgdrift_core::types::JsonType;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Snapshot of a column's analysis that later runs are compared against
+///
+/// Written by `analyze --save-baseline` and read by `diff`, usually committed
+/// next to the code so CI only fails on drift that wasn't there before.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Baseline {
+    pub schema: String,
+    pub table: String,
+    pub column: String,
+    pub samples_analyzed: u64,
+    pub health_score: u8,
+    pub fields: Vec<BaselineField>,
+    pub issues: Vec<BaselineIssue>,
+}
+
+/// Shape of one path when the baseline was taken
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BaselineField {
+    pub path: String,
+    pub density: f64,
+    pub types: HashMap<JsonType, u64>,
+}
+
+/// A reported drift issue, reduced to what is needed to match it up later
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BaselineIssue {
+    /// Kind of issue (`SparseField`, `TypeInconsistency`, ...)
+    pub kind: String,
+    pub path: String,
+    pub severity: Severity,
+    pub description: String,
+}
+
+/// An issue reported at a higher severity than in the baseline
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WorsenedIssue {
+    pub issue: BaselineIssue,
+    pub previous_severity: Severity,
+}
+
+/// How the issues of a new run differ from the baseline
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct BaselineDiff {
+    /// Issues the baseline didn't have
+    pub new_issues: Vec<BaselineIssue>,
+    /// Baseline issues no longer reported
+    pub resolved_issues: Vec<BaselineIssue>,
+    /// Issues whose severity went up
+    pub worsened_issues: Vec<WorsenedIssue>,
+}
+
+impl BaselineDiff {
+    /// Whether the new run has new or worsened issues
+    pub fn is_regression(&self) -> bool {
+        !self.new_issues.is_empty() || !self.worsened_issues.is_empty()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self.is_regression() && self.resolved_issues.is_empty()
+    }
+}
+
+impl From<&BaselineIssue> for report::BaselineIssue {
+    fn from(issue: &BaselineIssue) -> Self {
+        Self {
+            kind: issue.kind.clone(),
+            path: issue.path.clone(),
+            severity: issue.severity,
+            description: issue.description.clone(),
+        }
+    }
+}
+
+impl From<&WorsenedIssue> for report::WorsenedIssue {
+    fn from(worsened: &WorsenedIssue) -> Self {
+        Self {
+            issue: (&worsened.issue).into(),
+            previous_severity: worsened.previous_severity,
+        }
+    }
+}
+
+impl Baseline {
+    /// Snapshot an analysis of `schema.table.column`
+    pub fn from_result(schema: &str, result: &AnalysisResult) -> Self {
+        Self {
+            schema: schema.to_string(),
+            table: result.table.clone(),
+            column: result.column.clone(),
+            samples_analyzed: result.samples_analyzed,
+            health_score: result.health_score,
+            fields: result
+                .field_stats
+                .iter()
+                .map(|fs| BaselineField {
+                    path: fs.path.clone(),
+                    density: fs.density,
+                    types: fs.types.clone(),
+                })
+                .collect(),
+            issues: result
+                .drift_issues
+                .iter()
+                .map(|issue| BaselineIssue {
+                    kind: issue.kind().to_string(),
+                    path: issue.path().to_string(),
+                    severity: issue.severity(),
+                    description: issue.description(),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read baseline {}", path.display()))?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid baseline file {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let contents = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, contents)
+            .with_context(|| format!("Failed to write baseline {}", path.display()))
+    }
+
+    /// Compare a new run against this baseline
+    ///
+    /// Issues are matched by kind and path, so an issue whose numbers moved
+    /// (a sparse field getting a little sparser) is neither new nor resolved.
+    pub fn diff(&self, current: &Baseline) -> BaselineDiff {
+        let key = |issue: &BaselineIssue| (issue.kind.clone(), issue.path.clone());
+        let before: HashMap<_, _> = self.issues.iter().map(|i| (key(i), i)).collect();
+        let after: HashMap<_, _> = current.issues.iter().map(|i| (key(i), i)).collect();
+
+        let mut diff = BaselineDiff::default();
+        for issue in &current.issues {
+            match before.get(&key(issue)) {
+                None => diff.new_issues.push(issue.clone()),
+                Some(previous) if issue.severity > previous.severity => {
+                    diff.worsened_issues.push(WorsenedIssue {
+                        issue: issue.clone(),
+                        previous_severity: previous.severity,
+                    })
+                }
+                Some(_) => {}
+            }
+        }
+        diff.resolved_issues = self
+            .issues
+            .iter()
+            .filter(|issue| !after.contains_key(&key(issue)))
+            .cloned()
+            .collect();
+        diff
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issue(kind: &str, path: &str, severity: Severity) -> BaselineIssue {
+        BaselineIssue {
+            kind: kind.to_string(),
+            path: path.to_string(),
+            severity,
+            description: String::new(),
+        }
+    }
+
+    fn baseline(issues: Vec<BaselineIssue>) -> Baseline {
+        Baseline {
+            schema: "public".to_string(),
+            table: "orders".to_string(),
+            column: "data".to_string(),
+            samples_analyzed: 100,
+            health_score: 90,
+            fields: vec![BaselineField {
+                path: "total".to_string(),
+                density: 1.0,
+                types: HashMap::from([(JsonType::Number, 100)]),
+            }],
+            issues,
+        }
+    }
+
+    #[test]
+    fn test_diff() {
+        let before = baseline(vec![
+            issue("SparseField", "coupon", Severity::Info),
+            issue("MissingKey", "email", Severity::Warning),
+            issue("GhostKey", "legacy_id", Severity::Info),
+        ]);
+        let after = baseline(vec![
+            issue("SparseField", "coupon", Severity::Info),
+            issue("MissingKey", "email", Severity::Critical),
+            issue("TypeInconsistency", "total", Severity::Critical),
+        ]);
+
+        let diff = before.diff(&after);
+        assert!(diff.is_regression());
+        assert_eq!(
+            diff.new_issues,
+            vec![issue("TypeInconsistency", "total", Severity::Critical)]
+        );
+        assert_eq!(
+            diff.resolved_issues,
+            vec![issue("GhostKey", "legacy_id", Severity::Info)]
+        );
+        assert_eq!(
+            diff.worsened_issues,
+            vec![WorsenedIssue {
+                issue: issue("MissingKey", "email", Severity::Critical),
+                previous_severity: Severity::Warning,
+            }]
+        );
+
+        assert!(before.diff(&before).is_empty());
+
+        let resolved = before.diff(&baseline(vec![]));
+        assert!(!resolved.is_regression());
+        assert_eq!(resolved.resolved_issues.len(), 3);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let path =
+            std::env::temp_dir().join(format!("pgdrift-baseline-{}.json", std::process::id()));
+        let saved = baseline(vec![issue("MissingKey", "email", Severity::Warning)]);
+        saved.save(&path).unwrap();
+        assert_eq!(Baseline::load(&path).unwrap(), saved);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,161 @@
+use anyhow::{Context, Result};
+use pgdrift_core::analyzer::AnalyzerState;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+
+/// Partial analysis of one column, persisted as a JSON file
+///
+/// Saved periodically during a long analysis so an interrupted run (crash,
+/// Ctrl-C) picks up where it stopped instead of starting over. The file
+/// records which column (`schema.table.column`) it belongs to.
+#[derive(Debug, Clone)]
+pub struct Checkpoint {
+    path: PathBuf,
+    column: String,
+}
+
+/// What a checkpoint holds: the stats so far and where the scan stopped
+#[derive(Debug, Clone)]
+pub struct Progress {
+    pub analyzer: AnalyzerState,
+    /// Primary key of the last row analyzed, as text
+    pub after: Option<String>,
+}
+
+impl Checkpoint {
+    pub fn new(path: &Path, column: &str) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            column: column.to_string(),
+        }
+    }
+
+    /// Load the saved progress - a missing file means a fresh start
+    ///
+    /// Fails if the checkpoint belongs to another column, rather than mixing
+    /// statistics from two columns, or if it doesn't say where to resume.
+    pub fn load(&self) -> Result<Option<Progress>> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+
+        let contents = std::fs::read_to_string(&self.path)
+            .with_context(|| format!("Failed to read checkpoint {}", self.path.display()))?;
+        let mut saved: serde_json::Value = serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid checkpoint file {}", self.path.display()))?;
+
+        let column = saved["column"].as_str().unwrap_or_default();
+        if column != self.column {
+            anyhow::bail!(
+                "Checkpoint {} belongs to {}, not {}",
+                self.path.display(),
+                column,
+                self.column
+            );
+        }
+
+        let analyzer: AnalyzerState = serde_json::from_value(saved["analyzer"].take())
+            .with_context(|| format!("Invalid checkpoint file {}", self.path.display()))?;
+        let after = saved["after"].as_str().map(String::from);
+        if after.is_none() && analyzer.total_samples() > 0 {
+            anyhow::bail!(
+                "Checkpoint {} doesn't record where to resume, delete it to start over",
+                self.path.display()
+            );
+        }
+        Ok(Some(Progress { analyzer, after }))
+    }
+
+    /// Write the analyzer state and the key of the last row it saw to disk
+    ///
+    /// Written to a temporary file first and renamed into place, so a crash
+    /// mid-write leaves the previous checkpoint intact.
+    pub fn save(&self, state: &AnalyzerState, after: Option<&str>) -> Result<()> {
+        let contents = serde_json::to_string(&json!({
+            "column": self.column,
+            "analyzer": state,
+            "after": after,
+        }))?;
+
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(".tmp");
+        let tmp = PathBuf::from(tmp);
+        std::fs::write(&tmp, contents)
+            .with_context(|| format!("Failed to write checkpoint {}", tmp.display()))?;
+        std::fs::rename(&tmp, &self.path)
+            .with_context(|| format!("Failed to write checkpoint {}", self.path.display()))
+    }
+
+    /// Delete the checkpoint once the analysis has completed
+    pub fn remove(&self) -> Result<()> {
+        match std::fs::remove_file(&self.path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e)
+                .with_context(|| format!("Failed to remove checkpoint {}", self.path.display())),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pgdrift_core::analyzer::JsonAnalyzer;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "pgdrift-checkpoint-{}-{}.json",
+            name,
+            std::process::id()
+        ))
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let path = temp_path("round-trip");
+        let checkpoint = Checkpoint::new(&path, "public.events.payload");
+        assert!(checkpoint.load().unwrap().is_none());
+
+        let mut analyzer = JsonAnalyzer::new();
+        analyzer.analyze(&json!({"type": "click"}));
+        checkpoint.save(&analyzer.state(), Some("17")).unwrap();
+
+        let progress = checkpoint.load().unwrap().expect("checkpoint should exist");
+        assert_eq!(progress.analyzer.total_samples(), 1);
+        assert_eq!(progress.after.as_deref(), Some("17"));
+
+        checkpoint.remove().unwrap();
+        assert!(!path.exists());
+        checkpoint.remove().unwrap();
+    }
+
+    #[test]
+    fn test_rejects_other_column() {
+        let path = temp_path("other-column");
+        Checkpoint::new(&path, "public.users.metadata")
+            .save(&JsonAnalyzer::new().state(), None)
+            .unwrap();
+
+        let err = Checkpoint::new(&path, "public.events.payload")
+            .load()
+            .unwrap_err();
+        assert!(err.to_string().contains("belongs to public.users.metadata"));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_rejects_checkpoint_without_position() {
+        let path = temp_path("no-position");
+        let mut analyzer = JsonAnalyzer::new();
+        analyzer.analyze(&json!({"type": "click"}));
+        let contents = json!({"column": "public.events.payload", "analyzer": analyzer.state()});
+        std::fs::write(&path, contents.to_string()).unwrap();
+
+        let err = Checkpoint::new(&path, "public.events.payload")
+            .load()
+            .unwrap_err();
+        assert!(err.to_string().contains("doesn't record where to resume"));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
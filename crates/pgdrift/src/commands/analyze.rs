@@ -1,8 +1,149 @@
-use crate::output::{AnalysisResult, OutputFormat, print_analysis};
+use crate::baseline::Baseline;
+use crate::checkpoint::Checkpoint;
+use crate::config::{Config, Date, DriftSettings};
+use crate::diagram::DiagramFormat;
+use crate::metrics::{ColumnMetrics, publish_metrics};
+use crate::output::{
+    AnalysisResult, AnalysisView, CohortResult, OutputFormat, SegmentResult, print_analysis,
+    print_analysis_template, print_cohort_analysis, print_grouped_analysis, print_schema_diagram,
+    print_segmented_analysis, show_progress, write_summary_file,
+};
+use crate::watermark::{WatermarkStore, incremental_filter, upper_bound};
+use crate::xlsx::{ColumnExport, write_workbook};
 use anyhow::{Context, Result};
-use pgdrift_core::analyzer::JsonAnalyzer;
-use pgdrift_core::drift::{DriftConfig, detect_drift};
-use pgdrift_db::{ConnectionPool, Sampler};
+use clap::ValueEnum;
+use pgdrift_core::analyzer::{AnalyzerConfig, JsonAnalyzer};
+use pgdrift_core::cohort::{CohortConfig, compare_cohorts};
+use pgdrift_core::conditional::{ConditionalConfig, explain_conditional_fields};
+use pgdrift_core::conformance::ConformanceChecker;
+use pgdrift_core::cooccurrence::{CooccurrenceConfig, analyze_cooccurrence};
+use pgdrift_core::denormalization::{DenormalizationConfig, find_denormalization};
+use pgdrift_core::drift::{DriftIssue, Severity, confirm_renames};
+use pgdrift_core::redact::Redaction;
+use pgdrift_core::remediation::{RemediationConfig, migration_file, remediate};
+use pgdrift_core::report::{RunSummary, Thresholds};
+use pgdrift_core::score::health_score;
+use pgdrift_core::segment::{group_samples, segment_samples};
+use pgdrift_core::stats::FieldStats;
+use pgdrift_core::types::JsonType;
+use pgdrift_core::usage::{UsageConfig, find_unused_fields};
+use pgdrift_core::version::{VersionConfig, compare_versions};
+use pgdrift_db::discovery::{column_type, has_extension, query_texts};
+use pgdrift_db::sampler::max_column_value;
+use pgdrift_db::{Cohort, ColumnType, ConnectionPool, SampleFilter, Sampler, SamplingStrategy};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Where documents are analyzed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Engine {
+    /// Fetch sampled documents and analyze them locally (full statistics)
+    #[default]
+    Client,
+    /// Count paths and types inside Postgres; only aggregates are transferred
+    Sql,
+}
+
+/// Optional settings for the analyze command
+#[derive(Debug, Clone, Default)]
+pub struct AnalyzeOptions {
+    /// Read replica to run sampling queries against
+    pub replica_url: Option<String>,
+    /// Column used as the watermark for incremental runs
+    pub since_column: Option<String>,
+    /// Only sample rows where `since_column` is above this value
+    pub since_value: Option<String>,
+    /// File that stores watermarks between incremental runs
+    pub watermark_file: Option<PathBuf>,
+    /// Sample the newest N rows instead of random rows
+    pub tail: Option<usize>,
+    /// Column that orders rows by recency for tail sampling (defaults to the PK)
+    pub tail_column: Option<String>,
+    /// Only sample documents containing this JSON (`column @> filter`)
+    pub filter: Option<serde_json::Value>,
+    /// Keep sampling until path densities reach this confidence level
+    ///
+    /// `sample_size` becomes an upper bound rather than a fixed count.
+    pub confidence: Option<f64>,
+    /// Margin of error for adaptive sampling (defaults to `DEFAULT_MARGIN`)
+    pub margin: Option<f64>,
+    /// Keep every key of map-like objects instead of collapsing them into `*` paths
+    pub expand_maps: bool,
+    /// Nesting depth below which documents are not walked
+    pub max_depth: Option<usize>,
+    /// Maximum number of unique paths to track
+    pub max_paths: Option<usize>,
+    /// Approximate memory budget for field statistics, in MiB
+    pub memory_budget_mb: Option<usize>,
+    /// Example values kept per path (default 10)
+    pub examples: Option<usize>,
+    /// Hide sampled values (examples, top values) in the report
+    pub redact_examples: Option<Redaction>,
+    /// Threads used to analyze samples (`Some(0)` = one per CPU core, default 1)
+    pub jobs: Option<usize>,
+    /// Dotted JSON path whose value splits samples into separately analyzed segments
+    pub segment_by: Option<String>,
+    /// Column of the table whose value splits samples into separately analyzed groups
+    pub group_by: Option<String>,
+    /// Report co-occurring, mutually exclusive and implied fields
+    pub cooccurrence: bool,
+    /// Analyze fetched documents locally or push path counting down to Postgres
+    pub engine: Engine,
+    /// File the analyzer state is saved to periodically and resumed from
+    pub checkpoint: Option<PathBuf>,
+    /// Config file to read instead of `.pgdrift.toml` in the working directory
+    pub config: Option<PathBuf>,
+    /// Run only these built-in detectors (all of them if empty)
+    pub detectors: Vec<String>,
+    /// Built-in detectors to skip
+    pub disable_detectors: Vec<String>,
+    /// Timestamp column splitting rows into an older and a newer cohort
+    pub cohort_column: Option<String>,
+    /// How long before now the cohorts are split, as a Postgres interval (see `parse_cohort_split`)
+    pub cohort_split: Option<String>,
+    /// Fail once the report is printed if the health score is below this
+    pub fail_below_score: Option<u8>,
+    /// Fail once the report is printed if any issue is at least this severe
+    pub fail_on: Option<Severity>,
+    /// File a compact JSON summary of the run is written to, pass or fail
+    pub summary_file: Option<PathBuf>,
+    /// File the report's field stats and issues are saved to for `pgdrift diff`
+    pub save_baseline: Option<PathBuf>,
+    /// Excel workbook the column, field stats, issues and index
+    /// recommendations are exported to
+    pub xlsx: Option<PathBuf>,
+    /// JSON Schema (draft 2020-12) every sampled document is validated against
+    pub json_schema: Option<PathBuf>,
+    /// Add SQL normalizing drifted values to the report
+    pub remediation: bool,
+    /// File the remediation SQL is written to as a migration
+    pub remediation_file: Option<PathBuf>,
+    /// Report JSON paths duplicating other columns of the table
+    pub denormalization: bool,
+    /// Report JSON paths no query in pg_stat_statements or schema object refers to
+    pub unused_fields: bool,
+    /// File the run's Prometheus metrics are written to
+    pub metrics_file: Option<PathBuf>,
+    /// Prometheus Pushgateway the run's metrics are pushed to
+    pub pushgateway: Option<String>,
+    /// Optional sections of the table and markdown reports
+    pub view: AnalysisView,
+    /// Print the field hierarchy as a graph instead of the report
+    pub diagram: Option<DiagramFormat>,
+    /// Tera template the report is rendered through instead of `format`
+    pub template: Option<PathBuf>,
+    /// Sample the same rows on every run while the table doesn't change
+    pub stable: bool,
+}
+
+/// Default margin of error for adaptive sampling (±2 percentage points)
+pub const DEFAULT_MARGIN: f64 = 0.02;
+
+/// Documents drawn between convergence checks in adaptive sampling
+const ADAPTIVE_BATCH_SIZE: usize = 500;
+
+/// Documents analyzed between checkpoint saves
+const CHECKPOINT_INTERVAL: usize = 10_000;
 
 /// run performs analysis of a specified jsonb column in a PostgreSQL database
 pub async fn run(
@@ -11,10 +152,230 @@ pub async fn run(
     column: &str,
     sample_size: usize,
     format: OutputFormat,
+) -> Result<()> {
+    run_with_options(
+        database_url,
+        table,
+        column,
+        sample_size,
+        format,
+        &AnalyzeOptions::default(),
+    )
+    .await
+}
+
+/// run_with_options performs analysis with the given optional settings
+pub async fn run_with_options(
+    database_url: &str,
+    table: &str,
+    column: &str,
+    sample_size: usize,
+    format: OutputFormat,
+    options: &AnalyzeOptions,
 ) -> Result<()> {
     let (schema, table) = parse_table_name(table);
+    let mut config = Config::load(options.config.as_deref())?;
+    config
+        .drift
+        .select_detectors(&options.detectors, &options.disable_detectors);
+    let today = Date::today();
+    for suppression in config.drift.expired(today) {
+        eprintln!(
+            "Warning: suppression of {} expired, its issues are reported again",
+            suppression.path
+        );
+    }
+    let margin = options.margin.unwrap_or(DEFAULT_MARGIN);
 
-    let conn = ConnectionPool::new(database_url)
+    if let Some(confidence) = options.confidence {
+        if confidence <= 0.0 || confidence >= 1.0 {
+            anyhow::bail!("--confidence must be between 0 and 1, got {}", confidence);
+        }
+        if margin <= 0.0 || margin >= 1.0 {
+            anyhow::bail!("--margin must be between 0 and 1, got {}", margin);
+        }
+        if options.segment_by.is_some() {
+            anyhow::bail!("--confidence can't be combined with --segment-by");
+        }
+        if options.denormalization {
+            anyhow::bail!("--confidence can't be combined with --denormalization");
+        }
+        // Checkpointed scans go in key order, so stopping early would only
+        // see the oldest rows
+        if options.checkpoint.is_some() {
+            anyhow::bail!("--confidence can't be combined with --checkpoint");
+        }
+    }
+    if options.engine == Engine::Sql {
+        // These need the documents themselves, which the sql engine never fetches
+        if options.confidence.is_some() {
+            anyhow::bail!("--engine sql can't be combined with --confidence");
+        }
+        if options.segment_by.is_some() {
+            anyhow::bail!("--engine sql can't be combined with --segment-by");
+        }
+        if options.view.examples.is_some() {
+            anyhow::bail!("--engine sql can't be combined with --show-examples");
+        }
+        if options.cooccurrence {
+            anyhow::bail!("--engine sql can't be combined with --cooccurrence");
+        }
+        if options.json_schema.is_some() {
+            anyhow::bail!("--engine sql can't be combined with --json-schema");
+        }
+        if options.denormalization {
+            anyhow::bail!("--engine sql can't be combined with --denormalization");
+        }
+    }
+    if options.cohort_column.is_some() != options.cohort_split.is_some() {
+        anyhow::bail!("--cohort-column and --cohort-split must be used together");
+    }
+    if options.cohort_column.is_some() {
+        // Each cohort is sampled and analyzed in one go
+        if options.engine == Engine::Sql {
+            anyhow::bail!("--cohort-column can't be combined with --engine sql");
+        }
+        if options.confidence.is_some() || options.checkpoint.is_some() {
+            anyhow::bail!("--cohort-column can't be combined with --confidence or --checkpoint");
+        }
+        if options.segment_by.is_some() || options.tail.is_some() {
+            anyhow::bail!("--cohort-column can't be combined with --segment-by or --tail");
+        }
+        if options.fail_below_score.is_some() || options.fail_on.is_some() {
+            anyhow::bail!("--cohort-column can't be combined with --fail-below-score or --fail-on");
+        }
+        if options.summary_file.is_some() {
+            anyhow::bail!("--cohort-column can't be combined with --summary-file");
+        }
+        if options.save_baseline.is_some() {
+            anyhow::bail!("--cohort-column can't be combined with --save-baseline");
+        }
+        if options.xlsx.is_some() {
+            anyhow::bail!("--cohort-column can't be combined with --xlsx");
+        }
+        if options.json_schema.is_some() {
+            anyhow::bail!("--cohort-column can't be combined with --json-schema");
+        }
+        if options.remediation || options.remediation_file.is_some() {
+            anyhow::bail!("--cohort-column can't be combined with --remediation");
+        }
+        if options.denormalization {
+            anyhow::bail!("--cohort-column can't be combined with --denormalization");
+        }
+        if options.unused_fields {
+            anyhow::bail!("--cohort-column can't be combined with --unused-fields");
+        }
+        if options.view.field_stats.is_some() || options.view.tree || options.diagram.is_some() {
+            anyhow::bail!(
+                "--cohort-column can't be combined with --field-stats, --tree or --diagram"
+            );
+        }
+        if options.view.examples.is_some() {
+            anyhow::bail!("--cohort-column can't be combined with --show-examples");
+        }
+        if options.template.is_some() {
+            anyhow::bail!("--cohort-column can't be combined with --template");
+        }
+    }
+    let metrics = options.metrics_file.is_some() || options.pushgateway.is_some();
+    if metrics && (options.cohort_column.is_some() || options.segment_by.is_some()) {
+        anyhow::bail!(
+            "--metrics-file and --pushgateway can't be combined with --cohort-column or --segment-by"
+        );
+    }
+    if options.view.examples.is_some() && options.redact_examples == Some(Redaction::Drop) {
+        anyhow::bail!("--show-examples can't be combined with --redact-examples drop");
+    }
+    if options.diagram.is_some() && (options.segment_by.is_some() || options.group_by.is_some()) {
+        anyhow::bail!("--diagram can't be combined with --segment-by or --group-by");
+    }
+    if options.template.is_some() && (options.segment_by.is_some() || options.group_by.is_some()) {
+        anyhow::bail!("--template can't be combined with --segment-by or --group-by");
+    }
+    if options.template.is_some() && options.diagram.is_some() {
+        anyhow::bail!("--template can't be combined with --diagram");
+    }
+    if options.save_baseline.is_some() && options.segment_by.is_some() {
+        anyhow::bail!("--save-baseline can't be combined with --segment-by");
+    }
+    if options.xlsx.is_some() && (options.segment_by.is_some() || options.group_by.is_some()) {
+        anyhow::bail!("--xlsx can't be combined with --segment-by or --group-by");
+    }
+    if options.checkpoint.is_some() {
+        // A resumed run only holds the merged statistics, not the documents
+        // these passes need, and a tail sample can't be continued
+        if options.segment_by.is_some() {
+            anyhow::bail!("--checkpoint can't be combined with --segment-by");
+        }
+        if options.cooccurrence {
+            anyhow::bail!("--checkpoint can't be combined with --cooccurrence");
+        }
+        if options.engine == Engine::Sql {
+            anyhow::bail!("--checkpoint can't be combined with --engine sql");
+        }
+        if options.tail.is_some() {
+            anyhow::bail!("--checkpoint can't be combined with --tail");
+        }
+        if options.json_schema.is_some() {
+            anyhow::bail!("--checkpoint can't be combined with --json-schema");
+        }
+        if options.denormalization {
+            anyhow::bail!("--checkpoint can't be combined with --denormalization");
+        }
+    }
+    if options.json_schema.is_some() && options.segment_by.is_some() {
+        anyhow::bail!("--json-schema can't be combined with --segment-by");
+    }
+    let remediation = options.remediation || options.remediation_file.is_some();
+    if remediation && options.segment_by.is_some() {
+        anyhow::bail!("--remediation can't be combined with --segment-by");
+    }
+    if options.denormalization && options.segment_by.is_some() {
+        anyhow::bail!("--denormalization can't be combined with --segment-by");
+    }
+    if options.unused_fields && options.segment_by.is_some() {
+        anyhow::bail!("--unused-fields can't be combined with --segment-by");
+    }
+    // Incremental runs keep the stats of the whole column, not of segments
+    if options.watermark_file.is_some()
+        && (options.segment_by.is_some() || options.group_by.is_some())
+    {
+        anyhow::bail!("--watermark-file can't be combined with --segment-by or --group-by");
+    }
+    if options.group_by.is_some() {
+        // Groups are analyzed like segments, from documents fetched together
+        // with the group column
+        if options.segment_by.is_some() {
+            anyhow::bail!("--group-by can't be combined with --segment-by");
+        }
+        if options.engine == Engine::Sql {
+            anyhow::bail!("--group-by can't be combined with --engine sql");
+        }
+        if options.confidence.is_some() || options.checkpoint.is_some() {
+            anyhow::bail!("--group-by can't be combined with --confidence or --checkpoint");
+        }
+        if options.cohort_column.is_some() {
+            anyhow::bail!("--group-by can't be combined with --cohort-column");
+        }
+        if options.save_baseline.is_some() || options.json_schema.is_some() {
+            anyhow::bail!("--group-by can't be combined with --save-baseline or --json-schema");
+        }
+        if remediation || options.denormalization || options.unused_fields {
+            anyhow::bail!(
+                "--group-by can't be combined with --remediation, --denormalization or --unused-fields"
+            );
+        }
+        if metrics {
+            anyhow::bail!("--group-by can't be combined with --metrics-file or --pushgateway");
+        }
+    }
+    let mut conformance = options
+        .json_schema
+        .as_deref()
+        .map(load_json_schema)
+        .transpose()?;
+
+    let conn = ConnectionPool::with_replica(database_url, options.replica_url.as_deref())
         .await
         .context("Failed to create database connection pool")?;
 
@@ -22,45 +383,745 @@ pub async fn run(
         .await
         .context("Failed to connect to the database")?;
 
-    let sampler = Sampler::new(conn.pool(), &schema, &table, None, sample_size)
-        .await
-        .context("Failed to create sampler")?
-        .show_progress(true);
+    super::warn_replica_fallback(&conn);
+
+    // Statement statistics live on the primary, so check before sampling
+    if options.unused_fields
+        && !has_extension(conn.pool(), "pg_stat_statements")
+            .await
+            .context("Failed to look up extensions")?
+    {
+        anyhow::bail!(
+            "--unused-fields needs the pg_stat_statements extension \
+             (add it to shared_preload_libraries and run CREATE EXTENSION pg_stat_statements)"
+        );
+    }
 
-    println!("\nSampling Strategy: {}", sampler.strategy_info());
+    let watermark_key = format!("{}.{}.{}", schema, table, column);
+    let mut watermarks = options
+        .watermark_file
+        .as_deref()
+        .map(WatermarkStore::load)
+        .transpose()?;
+    let mut filter = incremental_filter(
+        options.since_column.as_deref(),
+        options.since_value.as_deref(),
+        watermarks.as_ref(),
+        &watermark_key,
+    );
+    let incremental = !filter.is_empty();
+    filter.contains = options.filter.clone();
 
-    let samples = sampler
-        .sample(conn.pool(), &schema, &table, column)
+    // Read the high-water mark before sampling so rows inserted mid-run are
+    // picked up again next time rather than skipped
+    let next_watermark = match &options.since_column {
+        Some(since_column) => max_column_value(conn.sampling_pool(), &schema, &table, since_column)
+            .await
+            .context("Failed to read watermark column")?,
+        None => None,
+    };
+    // Rows inserted while sampling are above it and left to the next run
+    filter.until = upper_bound(options.since_column.as_deref(), next_watermark.as_deref());
+
+    // json, hstore and array columns are analyzed too, converted to jsonb while sampling
+    let data_type = column_type(conn.pool(), &schema, &table, column)
         .await
-        .context("Failed to sample data")?;
+        .context("Failed to look up column type")?
+        .unwrap_or(ColumnType::Jsonb);
+
+    if let (Some(cohort_column), Some(interval)) = (&options.cohort_column, &options.cohort_split) {
+        let result = analyze_cohorts(
+            &conn,
+            (&schema, &table, column),
+            sample_size,
+            filter,
+            data_type,
+            (cohort_column, interval),
+            options,
+        )
+        .await?;
+        print_cohort_analysis(&result, &format);
+        return Ok(());
+    }
+
+    let checkpoint = options
+        .checkpoint
+        .as_deref()
+        .map(|path| Checkpoint::new(path, &watermark_key));
+    let (mut analyzer, resume_after) = match checkpoint
+        .as_ref()
+        .map(Checkpoint::load)
+        .transpose()?
+        .flatten()
+    {
+        Some(saved) => {
+            progress!(
+                "Resuming from checkpoint: {} samples already analyzed",
+                saved.analyzer.total_samples()
+            );
+            (
+                JsonAnalyzer::from_state(saved.analyzer, analyzer_config(options)),
+                saved.after,
+            )
+        }
+        None => (JsonAnalyzer::with_config(analyzer_config(options)), None),
+    };
+
+    let sampler = match options.tail {
+        Some(limit) => Sampler::with_strategy(
+            SamplingStrategy::tail(
+                conn.pool(),
+                &schema,
+                &table,
+                options.tail_column.as_deref(),
+                limit,
+            )
+            .await
+            .context("Tail sampling needs a numeric primary key or --tail-column")?,
+        ),
+        None => Sampler::new(conn.pool(), &schema, &table, None, sample_size)
+            .await
+            .context("Failed to create sampler")?,
+    }
+    .with_filter(filter)
+    .column_type(data_type)
+    .show_progress(show_progress())
+    .repeatable(options.stable)
+    // Adaptive sampling stops early, which is only fair if the rows come in
+    // random order
+    .shuffled(options.confidence.is_some());
+
+    // A checkpointed scan goes in primary key order and resumes after the
+    // last row analyzed; sampled rows have no order to resume in
+    let sampler = match &checkpoint {
+        Some(_) if *sampler.strategy() != SamplingStrategy::Full => {
+            anyhow::bail!(
+                "--checkpoint only resumes full scans, pass a --sample-size of at least the row count"
+            );
+        }
+        Some(_) => sampler
+            .resume_after(conn.pool(), &schema, &table, resume_after)
+            .await
+            .context("--checkpoint needs a table with a numeric primary key")?,
+        None => sampler,
+    };
+
+    progress!("\nSampling Strategy: {}", sampler.strategy_info());
 
-    if samples.is_empty() {
+    let jobs = worker_count(options.jobs);
+    let mut segments = None;
+    // Kept after analysis only when a later pass needs the documents again
+    let mut samples = Vec::new();
+    // The other columns of each sampled row, for --denormalization
+    let mut rows = Vec::new();
+    let samples_analyzed = match (options.engine, options.confidence) {
+        (Engine::Sql, _) => {
+            progress!("Computing path statistics in the database ...");
+            let max_depth = analyzer_config(options).max_depth;
+            let statistics = sampler
+                .path_statistics(conn.sampling_pool(), &schema, &table, column, max_depth)
+                .await
+                .context("Failed to compute path statistics")?;
+
+            analyzer.add_documents(statistics.total_documents);
+            for path in &statistics.paths {
+                if let Some(json_type) = JsonType::from_jsonb_typeof(&path.json_type) {
+                    analyzer.record_type_count(&path.path, path.depth, json_type, path.count);
+                }
+            }
+            statistics.total_documents as usize
+        }
+        (Engine::Client, confidence) if confidence.is_some() || checkpoint.is_some() => {
+            let batch_size = match confidence {
+                Some(_) => ADAPTIVE_BATCH_SIZE,
+                None => CHECKPOINT_INTERVAL,
+            };
+            let mut converged = false;
+            let mut since_checkpoint = 0;
+            let mut save_error = None;
+            let total = sampler
+                .sample_batches(
+                    conn.sampling_pool(),
+                    &schema,
+                    &table,
+                    column,
+                    batch_size,
+                    |batch, last_key| {
+                        analyzer.analyze_parallel(batch, jobs);
+                        if let Some(checker) = &mut conformance {
+                            checker.check_all(batch);
+                        }
+                        if options.cooccurrence {
+                            samples.extend_from_slice(batch);
+                        }
+
+                        since_checkpoint += batch.len();
+                        if let Some(checkpoint) = &checkpoint
+                            && since_checkpoint >= CHECKPOINT_INTERVAL
+                        {
+                            since_checkpoint = 0;
+                            if let Err(e) = checkpoint.save(&analyzer.state(), last_key) {
+                                save_error = Some(e);
+                                return false;
+                            }
+                        }
+
+                        if let Some(confidence) = confidence {
+                            converged = analyzer.max_margin_of_error(confidence) <= margin;
+                        }
+                        !converged
+                    },
+                )
+                .await
+                .context("Failed to sample data")?;
+            if let Some(e) = save_error {
+                return Err(e);
+            }
+
+            if let (Some(confidence), true) = (confidence, total > 0) {
+                if converged {
+                    progress!(
+                        "Reached ±{:.1}% margin at {:.0}% confidence after {} samples",
+                        margin * 100.0,
+                        confidence * 100.0,
+                        total
+                    );
+                } else {
+                    eprintln!(
+                        "Warning: sample limit reached before ±{:.1}% margin (achieved ±{:.1}%)",
+                        margin * 100.0,
+                        analyzer.max_margin_of_error(confidence) * 100.0
+                    );
+                }
+            }
+            analyzer.total_samples() as usize
+        }
+        (Engine::Client, _) if options.group_by.is_some() => {
+            let group_by: Vec<String> = options.group_by.iter().cloned().collect();
+            let rows = sampler
+                .sample_rows(
+                    conn.sampling_pool(),
+                    &schema,
+                    &table,
+                    column,
+                    Some(&group_by),
+                )
+                .await
+                .context("Failed to sample data")?;
+
+            if !rows.is_empty() {
+                progress!("Analyzing {} samples ...", rows.len());
+            }
+            let count = rows.len();
+            // NULL group values are grouped with missing ones
+            segments = Some(group_samples(rows.into_iter().map(
+                |(document, mut row)| {
+                    let value = row.remove(&group_by[0]).filter(|value| !value.is_null());
+                    (value, document)
+                },
+            )));
+            count
+        }
+        (Engine::Client, _) if options.denormalization => {
+            (samples, rows) = sampler
+                .sample_rows(conn.sampling_pool(), &schema, &table, column, None)
+                .await
+                .context("Failed to sample data")?
+                .into_iter()
+                .unzip();
+
+            if !samples.is_empty() {
+                progress!("Analyzing {} samples ...", samples.len());
+            }
+            if let Some(checker) = &mut conformance {
+                checker.check_all(&samples);
+            }
+            analyzer.analyze_parallel(&samples, jobs);
+            samples.len()
+        }
+        (Engine::Client, _) => {
+            samples = sampler
+                .sample(conn.sampling_pool(), &schema, &table, column)
+                .await
+                .context("Failed to sample data")?;
+
+            if !samples.is_empty() {
+                progress!("Analyzing {} samples ...", samples.len());
+            }
+            if let Some(checker) = &mut conformance {
+                checker.check_all(&samples);
+            }
+            let count = samples.len();
+            match &options.segment_by {
+                Some(path) => segments = Some(segment_samples(std::mem::take(&mut samples), path)),
+                None => analyzer.analyze_parallel(&samples, jobs),
+            }
+            count
+        }
+    };
+
+    // Each run samples its rows at its own rate, so their statistics are
+    // weighted by the rows they stand for before being merged
+    if watermarks.is_some()
+        && next_watermark.is_some()
+        && *sampler.strategy() != SamplingStrategy::Full
+        && analyzer.total_samples() > 0
+    {
+        let rows = sampler
+            .count_documents(conn.sampling_pool(), &schema, &table, column)
+            .await
+            .context("Failed to count rows")?;
+        analyzer.reweight(rows as f64 / analyzer.total_samples() as f64);
+    }
+
+    // Rows analyzed by previous runs count towards the report, so fields that
+    // no longer appear in new rows aren't reported as gone
+    let previous = match (&watermarks, &options.since_value) {
+        (Some(store), None) if incremental => store.analyzer(&watermark_key).cloned(),
+        _ => None,
+    };
+    let merged = previous.is_some();
+    if let Some(state) = previous {
+        progress!(
+            "Merging with {} samples from previous runs",
+            state.total_samples()
+        );
+        analyzer.merge(JsonAnalyzer::from_state(state, analyzer_config(options)));
+    }
+
+    // Saved once the run's outputs are written, so a failed run is retried
+    // from the same watermark
+    if let (Some(store), Some(value)) = (watermarks.as_mut(), next_watermark) {
+        store.set(&watermark_key, value, analyzer.state());
+    }
+
+    if samples_analyzed == 0 && !merged {
+        if incremental {
+            progress!("No new rows since the last watermark.");
+            return save_watermarks(watermarks.as_ref());
+        }
         anyhow::bail!("No samples found. Column may be empty or NUILL.");
     }
 
-    println!("Analyzing {} samples ...", samples.len());
+    if let Some(segments) = segments {
+        let results: Vec<SegmentResult> = segments
+            .into_iter()
+            .map(|segment| {
+                let mut analyzer = JsonAnalyzer::with_config(analyzer_config(options));
+                analyzer.analyze_parallel(&segment.samples, jobs);
+                let mut result = analysis_result(
+                    &table,
+                    column,
+                    analyzer,
+                    &segment.samples,
+                    &config,
+                    today,
+                    options,
+                );
+                // The discriminator is constant within its own segment by definition
+                if let Some(path) = &options.segment_by {
+                    result.drift_issues.retain(|issue| issue.path() != path);
+                }
+                SegmentResult {
+                    value: segment.value,
+                    result,
+                }
+            })
+            .collect();
+
+        if let Some(group_by) = &options.group_by {
+            print_grouped_analysis(group_by, &results, &format, &options.view);
+        } else if let Some(path) = &options.segment_by {
+            print_segmented_analysis(path, &results, &format, &options.view);
+        }
+        if let Some(path) = &options.summary_file {
+            let summary = RunSummary::new(
+                1,
+                results.iter().map(|s| s.result.samples_analyzed).sum(),
+                results.iter().map(|s| s.result.health_score),
+                results.iter().flat_map(|s| &s.result.drift_issues),
+                results.iter().map(|s| s.result.suppressed_issues).sum(),
+                thresholds(options),
+            );
+            write_summary_file(path, &summary)?;
+        }
+        let lowest = results.iter().map(|s| s.result.health_score).min();
+        check_score(lowest, options.fail_below_score)?;
+        return check_issues(
+            results.iter().flat_map(|s| &s.result.drift_issues),
+            options.fail_on,
+        );
+    }
 
-    let mut analyzer = JsonAnalyzer::new();
-    for sample in &samples {
-        analyzer.analyze(sample)
+    let mut result = analysis_result(&table, column, analyzer, &samples, &config, today, options);
+    result.conformance = conformance.as_ref().map(ConformanceChecker::report);
+    if options.denormalization {
+        result.denormalization = Some(find_denormalization(
+            &samples,
+            &rows,
+            &result.field_stats,
+            &DenormalizationConfig::default(),
+        ));
     }
+    if options.unused_fields {
+        let queries = query_texts(conn.pool())
+            .await
+            .context("Failed to read query texts")?;
+        result.unused_fields = Some(find_unused_fields(
+            &table,
+            column,
+            &result.field_stats,
+            &queries,
+            &UsageConfig::default(),
+        ));
+    }
+    if remediation {
+        // jsonb_set only works on jsonb; other column types would need casts both ways
+        if data_type == ColumnType::Jsonb {
+            result.remediations = Some(remediate(
+                &schema,
+                &table,
+                column,
+                &result.drift_issues,
+                &RemediationConfig::default(),
+            ));
+        } else {
+            eprintln!("Warning: remediation SQL is only generated for jsonb columns");
+        }
+    }
+    match (options.diagram, &options.template) {
+        (Some(diagram), _) => print_schema_diagram(&result, diagram),
+        (None, Some(template)) => {
+            print_analysis_template(&result, template, options.view.examples)?
+        }
+        (None, None) => print_analysis(&result, &format, &options.view),
+    }
+    if let (Some(path), Some(remediations)) = (&options.remediation_file, &result.remediations) {
+        std::fs::write(path, migration_file(remediations))
+            .with_context(|| format!("Failed to write remediation SQL {}", path.display()))?;
+    }
+    if let Some(checkpoint) = &checkpoint {
+        checkpoint.remove()?;
+    }
+    if let Some(path) = &options.save_baseline {
+        Baseline::from_result(&schema, &result).save(path)?;
+    }
+    if let Some(path) = &options.xlsx {
+        write_workbook(path, &[ColumnExport::from_analysis(&schema, &result)])?;
+    }
+    if metrics {
+        let column_metrics = ColumnMetrics::new(
+            &schema,
+            &table,
+            column,
+            result.samples_analyzed,
+            &result.drift_issues,
+            result.health_score,
+        );
+        publish_metrics(
+            options.metrics_file.as_deref(),
+            options.pushgateway.as_deref(),
+            &[("schema", &schema), ("table", &table), ("column", column)],
+            &[column_metrics],
+        )
+        .await?;
+    }
+    if let Some(path) = &options.summary_file {
+        let summary = RunSummary::new(
+            1,
+            result.samples_analyzed,
+            [result.health_score],
+            &result.drift_issues,
+            result.suppressed_issues,
+            thresholds(options),
+        );
+        write_summary_file(path, &summary)?;
+    }
+    save_watermarks(watermarks.as_ref())?;
+    check_score(Some(result.health_score), options.fail_below_score)?;
+    check_issues(&result.drift_issues, options.fail_on)
+}
+
+fn save_watermarks(watermarks: Option<&WatermarkStore>) -> Result<()> {
+    match watermarks {
+        Some(store) => store.save(),
+        None => Ok(()),
+    }
+}
+
+fn thresholds(options: &AnalyzeOptions) -> Thresholds {
+    Thresholds {
+        fail_below_score: options.fail_below_score,
+        fail_on: options.fail_on,
+    }
+}
+
+/// Read and compile a JSON Schema file
+fn load_json_schema(path: &Path) -> Result<ConformanceChecker> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read JSON Schema {}", path.display()))?;
+    let schema: serde_json::Value = serde_json::from_str(&contents)
+        .with_context(|| format!("Invalid JSON in {}", path.display()))?;
+    ConformanceChecker::new(&schema)
+        .map_err(|e| anyhow::anyhow!("Invalid JSON Schema {}: {}", path.display(), e))
+}
+
+/// Fail if the health score (the lowest one, for a segmented report) is below the threshold
+fn check_score(score: Option<u8>, threshold: Option<u8>) -> Result<()> {
+    match (score, threshold) {
+        (Some(score), Some(threshold)) if score < threshold => {
+            anyhow::bail!("Health score {} is below {}", score, threshold)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Fail if any reported issue is at least as severe as `fail_on`
+fn check_issues<'a>(
+    issues: impl IntoIterator<Item = &'a DriftIssue>,
+    fail_on: Option<Severity>,
+) -> Result<()> {
+    let Some(fail_on) = fail_on else {
+        return Ok(());
+    };
+    let failing = issues
+        .into_iter()
+        .filter(|issue| issue.severity() >= fail_on)
+        .count();
+    if failing > 0 {
+        anyhow::bail!("{} issue(s) at {:?} severity or above", failing, fail_on);
+    }
+    Ok(())
+}
+
+/// Sample rows older and newer than the cohort split and compare their shape
+///
+/// Up to `sample_size` documents are drawn from each side; fields whose
+/// density or type mix differs significantly show drift that is happening now.
+async fn analyze_cohorts(
+    conn: &ConnectionPool,
+    (schema, table, column): (&str, &str, &str),
+    sample_size: usize,
+    filter: SampleFilter,
+    data_type: ColumnType,
+    (cohort_column, interval): (&str, &str),
+    options: &AnalyzeOptions,
+) -> Result<CohortResult> {
+    let jobs = worker_count(options.jobs);
+    let mut cohorts = Vec::new();
+
+    for newer in [false, true] {
+        let sampler = Sampler::new(conn.pool(), schema, table, None, sample_size)
+            .await
+            .context("Failed to create sampler")?
+            .with_filter(SampleFilter {
+                cohort: Some(Cohort {
+                    column: cohort_column.to_string(),
+                    interval: interval.to_string(),
+                    newer,
+                }),
+                ..filter.clone()
+            })
+            .column_type(data_type)
+            .show_progress(show_progress())
+            .repeatable(options.stable);
+        progress!("\nSampling Strategy: {}", sampler.strategy_info());
+
+        let samples = sampler
+            .sample(conn.sampling_pool(), schema, table, column)
+            .await
+            .context("Failed to sample data")?;
+        if samples.is_empty() {
+            anyhow::bail!(
+                "No rows with {} {} {} ago, nothing to compare",
+                cohort_column,
+                if newer { "since" } else { "before" },
+                interval
+            );
+        }
+
+        let mut analyzer = JsonAnalyzer::with_config(analyzer_config(options));
+        analyzer.analyze_parallel(&samples, jobs);
+        cohorts.push((analyzer.total_samples(), analyzer.finalize()));
+    }
+
+    let (new_samples, new_stats) = cohorts.pop().expect("two cohorts");
+    let (old_samples, old_stats) = cohorts.pop().expect("two cohorts");
+    Ok(CohortResult {
+        table: table.to_string(),
+        column: column.to_string(),
+        cohort_column: cohort_column.to_string(),
+        split: interval.to_string(),
+        old_samples,
+        new_samples,
+        shifts: compare_cohorts(
+            &old_stats,
+            &new_stats,
+            old_samples,
+            new_samples,
+            &CohortConfig::default(),
+        ),
+    })
+}
+
+/// Parse a `--cohort-split` like `30d` into a Postgres interval (`30 days`)
+///
+/// Accepts a positive whole number of hours (`h`), days (`d`) or weeks (`w`).
+pub fn parse_cohort_split(split: &str) -> Result<String, String> {
+    let invalid = || {
+        format!(
+            "invalid cohort split {:?}, expected e.g. 12h, 30d or 2w",
+            split
+        )
+    };
+    let unit = match split.chars().last() {
+        Some('h') => "hours",
+        Some('d') => "days",
+        Some('w') => "weeks",
+        _ => return Err(invalid()),
+    };
+    match split[..split.len() - 1].parse::<u32>() {
+        Ok(count) if count > 0 => Ok(format!("{} {}", count, unit)),
+        _ => Err(invalid()),
+    }
+}
+
+/// Finalize an analyzer into a report: field stats, drift issues and redaction
+///
+/// `samples` are the analyzed documents, needed for co-occurrence analysis.
+pub fn analysis_result(
+    table: &str,
+    column: &str,
+    analyzer: JsonAnalyzer,
+    samples: &[serde_json::Value],
+    config: &Config,
+    today: Date,
+    options: &AnalyzeOptions,
+) -> AnalysisResult {
+    let samples_analyzed = analyzer.total_samples();
+    let truncation = analyzer.truncation().clone();
+    if truncation.is_truncated() {
+        eprintln!("Warning: analysis truncated - {}", truncation.summary());
+    }
+
     let stats = analyzer.finalize();
     let mut field_stats: Vec<_> = stats.values().cloned().collect();
     field_stats.sort_by(|a, b| a.path.cmp(&b.path));
 
-    let config = DriftConfig::default();
-    let drift_issues = detect_drift(&stats, &config);
+    let issues = column_issues(
+        &stats,
+        samples,
+        &config.drift,
+        today,
+        options.redact_examples,
+    );
+    let mut cooccurrence = options
+        .cooccurrence
+        .then(|| analyze_cooccurrence(samples, &stats, &CooccurrenceConfig::default()));
+
+    // Redact after drift detection so constant fields are still found
+    if let Some(redaction) = options.redact_examples {
+        for fs in &mut field_stats {
+            fs.redact(redaction);
+        }
+        if let Some(cooccurrence) = &mut cooccurrence {
+            cooccurrence.redact(redaction);
+        }
+    }
 
-    let result = AnalysisResult {
+    AnalysisResult {
         table: table.to_string(),
         column: column.to_string(),
-        samples_analyzed: samples.len() as u64,
+        samples_analyzed,
         field_stats,
-        drift_issues,
+        drift_issues: issues.drift_issues,
+        suppressed_issues: issues.suppressed_issues,
+        expiring_suppressions: config.drift.expiring(today),
+        truncation,
+        cooccurrence,
+        health_score: issues.health_score,
+        conformance: None,
+        remediations: None,
+        denormalization: None,
+        unused_fields: None,
+    }
+}
+
+/// Drift issues of a column that made it through the config, and its score
+pub struct ColumnIssues {
+    pub drift_issues: Vec<DriftIssue>,
+    /// Issues left out because their path matches an `ignore` pattern
+    pub suppressed_issues: usize,
+    pub health_score: u8,
+}
+
+/// Detect the drift issues of a column the same way for every command
+///
+/// Rename candidates are confirmed against the documents, sparse fields
+/// explained by conditions and version fields compared, then the config
+/// suppresses and reclassifies issues before the column is scored. Without
+/// documents (server-side or streamed analysis) sparse fields stay as they
+/// are and versions aren't compared. Examples are redacted last, so constant
+/// fields are still found.
+pub fn column_issues(
+    stats: &HashMap<String, FieldStats>,
+    samples: &[serde_json::Value],
+    settings: &DriftSettings,
+    today: Date,
+    redaction: Option<Redaction>,
+) -> ColumnIssues {
+    let config = settings.drift_config();
+    let issues = settings.detectors().detect(stats, &config);
+    // Renames are only reported once the documents show the keys apart
+    let issues = confirm_renames(issues, samples, &config);
+    let issues = if samples.is_empty() {
+        issues
+    } else {
+        let issues =
+            explain_conditional_fields(issues, samples, stats, &ConditionalConfig::default());
+        compare_versions(issues, samples, stats, &VersionConfig::default())
     };
+    let (issues, suppressed) = settings.suppress(issues, today);
+    let mut drift_issues = settings.reclassify(issues);
+    let health_score = health_score(stats, &drift_issues);
 
-    print_analysis(&result, &format);
-    Ok(())
+    if let Some(redaction) = redaction {
+        for issue in &mut drift_issues {
+            issue.redact(redaction);
+        }
+    }
+
+    ColumnIssues {
+        drift_issues,
+        suppressed_issues: suppressed.len(),
+        health_score,
+    }
+}
+
+/// Analyzer settings from the command options, falling back to the defaults
+fn analyzer_config(options: &AnalyzeOptions) -> AnalyzerConfig {
+    let defaults = AnalyzerConfig::default();
+    AnalyzerConfig {
+        collapse_maps: !options.expand_maps,
+        max_depth: options.max_depth.unwrap_or(defaults.max_depth),
+        max_paths: options.max_paths.unwrap_or(defaults.max_paths),
+        memory_budget_bytes: options
+            .memory_budget_mb
+            .map_or(defaults.memory_budget_bytes, |mb| {
+                mb.saturating_mul(1024 * 1024)
+            }),
+        max_examples: options.examples.unwrap_or(defaults.max_examples),
+        ..defaults
+    }
+}
+
+/// Resolve `--jobs`: unset means one thread, 0 means one per CPU core
+fn worker_count(jobs: Option<usize>) -> usize {
+    match jobs {
+        None => 1,
+        Some(0) => std::thread::available_parallelism().map_or(1, |n| n.get()),
+        Some(n) => n,
+    }
 }
 
 /// Parse table name into schema and table components
@@ -74,6 +1135,119 @@ fn parse_table_name(table: &str) -> (String, String) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use pgdrift_core::drift::EvolutionPattern;
+
+    #[test]
+    fn test_analyzer_config() {
+        let config = analyzer_config(&AnalyzeOptions::default());
+        assert!(config.collapse_maps);
+        assert_eq!(config.max_depth, AnalyzerConfig::default().max_depth);
+
+        let config = analyzer_config(&AnalyzeOptions {
+            expand_maps: true,
+            max_depth: Some(8),
+            max_paths: Some(500),
+            memory_budget_mb: Some(64),
+            ..Default::default()
+        });
+        assert!(!config.collapse_maps);
+        assert_eq!(config.max_depth, 8);
+        assert_eq!(config.max_paths, 500);
+        assert_eq!(config.memory_budget_bytes, 64 * 1024 * 1024);
+    }
+
+    #[test]
+    fn test_parse_cohort_split() {
+        assert_eq!(parse_cohort_split("30d").unwrap(), "30 days");
+        assert_eq!(parse_cohort_split("12h").unwrap(), "12 hours");
+        assert_eq!(parse_cohort_split("2w").unwrap(), "2 weeks");
+        assert!(parse_cohort_split("0d").is_err());
+        assert!(parse_cohort_split("-3d").is_err());
+        assert!(parse_cohort_split("30").is_err());
+        assert!(parse_cohort_split("d").is_err());
+        assert!(parse_cohort_split("").is_err());
+    }
+
+    #[test]
+    fn test_check_score() {
+        assert!(check_score(Some(85), Some(80)).is_ok());
+        assert!(check_score(Some(80), Some(80)).is_ok());
+        assert!(check_score(Some(85), None).is_ok());
+        assert!(check_score(None, Some(80)).is_ok());
+
+        let err = check_score(Some(72), Some(80)).unwrap_err();
+        assert_eq!(err.to_string(), "Health score 72 is below 80");
+    }
+
+    #[test]
+    fn test_check_issues() {
+        let issues = vec![
+            DriftIssue::MissingKey {
+                path: "email".to_string(),
+                density: 0.85,
+                expected_occurrences: 100,
+                actual_occurrences: 85,
+                margin: 0.0,
+            },
+            DriftIssue::EmptyString {
+                path: "name".to_string(),
+                empty_count: 12,
+                null_count: 0,
+                missing_count: 0,
+                total_samples: 100,
+            },
+        ];
+
+        assert!(check_issues(&issues, None).is_ok());
+        assert!(check_issues(&[], Some(Severity::Info)).is_ok());
+        assert!(check_issues(&issues[1..], Some(Severity::Critical)).is_ok());
+
+        let err = check_issues(&issues, Some(Severity::Warning)).unwrap_err();
+        assert_eq!(err.to_string(), "2 issue(s) at Warning severity or above");
+        let err = check_issues(&issues, Some(Severity::Critical)).unwrap_err();
+        assert_eq!(err.to_string(), "1 issue(s) at Critical severity or above");
+    }
+
+    #[test]
+    fn test_column_issues_compares_versions() {
+        let samples: Vec<serde_json::Value> = (0..200)
+            .map(|i| match i % 5 {
+                0..=2 => serde_json::json!({
+                    "id": i, "meta": {"version": 2}, "first_name": "Ada", "last_name": "L"
+                }),
+                _ => serde_json::json!({"id": i, "meta": {"version": 1}, "name": "Ada L"}),
+            })
+            .collect();
+        let mut analyzer = JsonAnalyzer::new();
+        for sample in &samples {
+            analyzer.analyze(sample);
+        }
+        let stats = analyzer.finalize();
+        let settings = DriftSettings::default();
+
+        let differences = |samples: &[serde_json::Value]| {
+            column_issues(&stats, samples, &settings, Date::today(), None)
+                .drift_issues
+                .into_iter()
+                .find_map(|issue| match issue {
+                    DriftIssue::SchemaEvolution {
+                        pattern: EvolutionPattern::VersionMarker { differences, .. },
+                        ..
+                    } => Some(differences.len()),
+                    _ => None,
+                })
+        };
+        assert_eq!(differences(&samples), Some(3));
+        // Streamed or server-side analysis has no documents to compare
+        assert_eq!(differences(&[]), Some(0));
+    }
+
+    #[test]
+    fn test_worker_count() {
+        assert_eq!(worker_count(None), 1);
+        assert_eq!(worker_count(Some(6)), 6);
+        assert!(worker_count(Some(0)) >= 1);
+    }
 
     #[test]
     fn test_parse_table_name() {
@@ -0,0 +1,111 @@
+use crate::baseline::Baseline;
+use crate::commands::analyze::{AnalyzeOptions, analysis_result};
+use crate::config::{Config, Date};
+use crate::output::{DiffResult, OutputFormat, print_diff, show_progress};
+use anyhow::{Context, Result};
+use pgdrift_core::analyzer::JsonAnalyzer;
+use pgdrift_db::discovery::column_type;
+use pgdrift_db::{ColumnType, ConnectionPool, SampleFilter, Sampler};
+use std::path::{Path, PathBuf};
+
+/// Optional settings for the diff command
+#[derive(Debug, Clone, Default)]
+pub struct DiffOptions {
+    /// Read replica to run sampling queries against
+    pub replica_url: Option<String>,
+    /// Only sample documents containing this JSON (`column @> filter`)
+    pub filter: Option<serde_json::Value>,
+    /// Config file to read instead of `.pgdrift.toml` in the working directory
+    pub config: Option<PathBuf>,
+    /// Fail once the report is printed if any issue is new or worsened
+    pub fail_on_regression: bool,
+}
+
+/// Analyze the column recorded in a baseline again and report what changed
+///
+/// Only issues that are new, resolved or reported at a higher severity are
+/// shown, so a column with long-known drift doesn't fail CI on every run.
+pub async fn run(
+    database_url: &str,
+    baseline: &Path,
+    sample_size: usize,
+    format: OutputFormat,
+    options: &DiffOptions,
+) -> Result<()> {
+    let baseline = Baseline::load(baseline)?;
+    let (schema, table, column) = (&baseline.schema, &baseline.table, &baseline.column);
+    let config = Config::load(options.config.as_deref())?;
+    let today = Date::today();
+
+    let conn = ConnectionPool::with_replica(database_url, options.replica_url.as_deref())
+        .await
+        .context("Failed to create database connection pool")?;
+
+    conn.test_connection()
+        .await
+        .context("Failed to connect to the database")?;
+
+    super::warn_replica_fallback(&conn);
+
+    let data_type = column_type(conn.pool(), schema, table, column)
+        .await
+        .context("Failed to look up column type")?
+        .unwrap_or(ColumnType::Jsonb);
+
+    let sampler = Sampler::new(conn.pool(), schema, table, None, sample_size)
+        .await
+        .context("Failed to create sampler")?
+        .with_filter(SampleFilter {
+            contains: options.filter.clone(),
+            ..Default::default()
+        })
+        .column_type(data_type)
+        .show_progress(show_progress());
+
+    progress!("\nSampling Strategy: {}", sampler.strategy_info());
+
+    let samples = sampler
+        .sample(conn.sampling_pool(), schema, table, column)
+        .await
+        .context("Failed to sample data")?;
+
+    if samples.is_empty() {
+        anyhow::bail!("No samples found. Column may be empty or NULL.");
+    }
+
+    let mut analyzer = JsonAnalyzer::new();
+    for sample in &samples {
+        analyzer.analyze(sample);
+    }
+    let result = analysis_result(
+        table,
+        column,
+        analyzer,
+        &samples,
+        &config,
+        today,
+        &AnalyzeOptions::default(),
+    );
+    let current = Baseline::from_result(schema, &result);
+    let diff = baseline.diff(&current);
+
+    let regression = diff.is_regression();
+    print_diff(
+        &DiffResult {
+            schema: schema.clone(),
+            table: table.clone(),
+            column: column.clone(),
+            baseline_samples: baseline.samples_analyzed,
+            samples_analyzed: current.samples_analyzed,
+            baseline_score: baseline.health_score,
+            health_score: current.health_score,
+            diff,
+        },
+        &format,
+    );
+
+    if options.fail_on_regression && regression {
+        anyhow::bail!("New or worsened drift issues since the baseline");
+    }
+    Ok(())
+}
@@ -1,9 +1,59 @@
 use crate::output::{OutputFormat, print_columns};
 use anyhow::{Context, Result};
-use pgdrift_db::{ConnectionPool, discover_jsonb_columns};
+use clap::ValueEnum;
+use pgdrift_db::{ConnectionPool, DiscoveryOptions, JsonbColumn, discover_columns};
+use std::cmp::Reverse;
+
+/// Ordering of discovered columns
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum DiscoverSort {
+    /// Alphabetical by schema, table and column
+    #[default]
+    Table,
+    /// Largest estimated row count first
+    Rows,
+    /// Largest estimated storage footprint first (implies storage sampling)
+    Size,
+}
+
+/// Optional settings for the discover command
+#[derive(Debug, Clone, Default)]
+pub struct DiscoverOptions {
+    /// Also list legacy `json` columns
+    pub include_json: bool,
+    /// Also list `hstore` columns
+    pub include_hstore: bool,
+    /// Also list `jsonb[]` (and with `include_json`, `json[]`) array columns
+    pub include_arrays: bool,
+    /// Glob patterns (`schema.table.column`) of columns to include
+    pub include: Vec<String>,
+    /// Glob patterns (`schema.table.column`) of columns to skip
+    pub exclude: Vec<String>,
+    /// Estimate document size and TOAST usage for each column
+    pub storage: bool,
+    /// Also list columns of views and materialized views
+    pub include_views: bool,
+    /// List each partition instead of one rolled-up entry per partitioned table
+    pub expand_partitions: bool,
+    /// Hide columns of tables with fewer estimated rows than this
+    pub min_rows: Option<i64>,
+    /// Order in which columns are listed
+    pub sort: DiscoverSort,
+    /// Only list the first N columns after sorting
+    pub top: Option<usize>,
+}
 
 /// runs the discover command to find JSONB columns in the database
 pub async fn run(database_url: &str, format: OutputFormat) -> Result<()> {
+    run_with_options(database_url, format, &DiscoverOptions::default()).await
+}
+
+/// runs the discover command with the given optional settings
+pub async fn run_with_options(
+    database_url: &str,
+    format: OutputFormat,
+    options: &DiscoverOptions,
+) -> Result<()> {
     let conn = ConnectionPool::new(database_url)
         .await
         .context("Failed to connect to database")?;
@@ -12,11 +62,106 @@ pub async fn run(database_url: &str, format: OutputFormat) -> Result<()> {
         .await
         .context("Failed to test database connection")?;
 
-    let columns = discover_jsonb_columns(conn.pool())
+    let discovery = DiscoveryOptions {
+        include_json: options.include_json,
+        include_hstore: options.include_hstore,
+        include_arrays: options.include_arrays,
+        include: options.include.clone(),
+        exclude: options.exclude.clone(),
+        storage: options.storage || options.sort == DiscoverSort::Size,
+        include_views: options.include_views,
+        expand_partitions: options.expand_partitions,
+        min_rows: options.min_rows,
+    };
+    let mut columns = discover_columns(conn.pool(), &discovery)
         .await
         .context("Failed to discover JSONB columns")?;
 
+    sort_columns(&mut columns, options.sort);
+    if let Some(top) = options.top {
+        columns.truncate(top);
+    }
+
     print_columns(&columns, &format);
 
     Ok(())
 }
+
+/// Sort columns in place; ties keep discovery's alphabetical order
+fn sort_columns(columns: &mut [JsonbColumn], sort: DiscoverSort) {
+    match sort {
+        DiscoverSort::Table => {}
+        DiscoverSort::Rows => columns.sort_by_key(|c| Reverse(c.estimated_rows.unwrap_or(-1))),
+        DiscoverSort::Size => columns.sort_by_key(|c| {
+            Reverse(
+                c.storage
+                    .as_ref()
+                    .and_then(|s| s.estimated_total_bytes)
+                    .unwrap_or(-1),
+            )
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pgdrift_db::{ColumnStorage, ColumnType, RelationKind};
+
+    fn column(table: &str, rows: Option<i64>, bytes: Option<i64>) -> JsonbColumn {
+        JsonbColumn {
+            schema: "public".to_string(),
+            table: table.to_string(),
+            column: "data".to_string(),
+            data_type: ColumnType::Jsonb,
+            relation_kind: RelationKind::Table,
+            estimated_rows: rows,
+            partitions: None,
+            partition_of: None,
+            dead_tuples: None,
+            last_analyze: None,
+            last_autoanalyze: None,
+            stats_age_secs: None,
+            indexes: vec![],
+            storage: bytes.map(|b| ColumnStorage {
+                avg_bytes: 0.0,
+                estimated_total_bytes: Some(b),
+                toast_bytes: 0,
+                toasted_fraction: 0.0,
+            }),
+        }
+    }
+
+    fn tables(columns: &[JsonbColumn]) -> Vec<&str> {
+        columns.iter().map(|c| c.table.as_str()).collect()
+    }
+
+    #[test]
+    fn test_sort_by_rows() {
+        let mut columns = vec![
+            column("a", Some(10), None),
+            column("b", None, None),
+            column("c", Some(500), None),
+        ];
+        sort_columns(&mut columns, DiscoverSort::Rows);
+        assert_eq!(tables(&columns), vec!["c", "a", "b"]);
+    }
+
+    #[test]
+    fn test_sort_by_size() {
+        let mut columns = vec![
+            column("a", Some(10), Some(2048)),
+            column("b", Some(20), Some(1 << 30)),
+            column("c", Some(30), None),
+        ];
+        sort_columns(&mut columns, DiscoverSort::Size);
+        assert_eq!(tables(&columns), vec!["b", "a", "c"]);
+    }
+
+    #[test]
+    fn test_sort_by_table_keeps_discovery_order() {
+        let mut columns = vec![column("b", Some(1), None), column("a", Some(2), None)];
+        sort_columns(&mut columns, DiscoverSort::Table);
+        assert_eq!(tables(&columns), vec!["b", "a"]);
+    }
+}
@@ -1,8 +1,83 @@
-use crate::output::{IndexRecommendationResult, OutputFormat, print_index_recommendations};
+use crate::migration::{MigrationSettings, MigrationStep, migration_script};
+use crate::output::{
+    IndexBuild, IndexRecommendationResult, OutputFormat, print_index_builds,
+    print_index_recommendations, show_progress,
+};
 use anyhow::{Context, Result};
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
 use pgdrift_core::analyzer::JsonAnalyzer;
-use pgdrift_core::index::{IndexConfig, recommend_index};
-use pgdrift_db::{ConnectionPool, Sampler};
+use pgdrift_core::index::{
+    ExistingIndex, GinOperatorClass, IndexConfig, IndexRecommendation, IndexUsage, PlannerCheck,
+    brin_candidates, estimate_index_sizes, order_correlation, plan_indexes, probe_query,
+    recommend_drops,
+};
+use pgdrift_core::workload::{JsonOperator, Statement, find_column_accesses};
+use pgdrift_db::discovery::{
+    column_data_type, get_row_count, has_extension, physical_order_sample, statement_stats,
+    stats_reset, table_indexes, table_pages,
+};
+use pgdrift_db::explain::explain_analyze_index;
+use pgdrift_db::hypopg::explain_hypothetical;
+use pgdrift_db::index_build::{BuildProgress, build_index_concurrently, index_exists};
+use pgdrift_db::{ConnectionPool, SampleFilter, Sampler, TableIndex};
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Rows read to measure how a timestamp path follows the physical row order
+const ORDER_SAMPLE_ROWS: i64 = 1000;
+
+/// How long `--apply` and migrations wait for a lock before giving up on an index
+pub(crate) const DEFAULT_LOCK_TIMEOUT: &str = "5s";
+
+/// `statement_timeout` of migrations: none, so a server-wide limit doesn't
+/// cancel long builds
+pub(crate) const DEFAULT_STATEMENT_TIMEOUT: &str = "0";
+
+/// Optional settings for the index command
+#[derive(Debug, Clone, Default)]
+pub struct IndexOptions {
+    /// Read replica to run sampling queries against
+    pub replica_url: Option<String>,
+    /// Only sample documents containing this JSON (`column @> filter`)
+    pub filter: Option<serde_json::Value>,
+    /// Recommend `jsonb_path_ops` GIN indexes, for columns only queried by containment
+    pub containment_only: bool,
+    /// EXPLAIN a probe query against a hypothetical copy of each recommended index
+    pub hypothetical: bool,
+    /// Build each recommended index in a transaction that's rolled back, to
+    /// time a probe query with EXPLAIN ANALYZE before and after. Blocks
+    /// writes to the table while each index builds
+    pub explain_analyze: bool,
+    /// Prioritize recommendations by the statements recorded in pg_stat_statements
+    pub workload: bool,
+    /// Paths searched with LIKE/ILIKE, to get trigram indexes
+    pub text_search: Vec<String>,
+    /// Recommend generated columns with a plain index for the busiest extracted paths
+    pub generated_columns: bool,
+    /// Overrides `IndexConfig::high_density_threshold`
+    pub high_density_threshold: Option<f64>,
+    /// Overrides `IndexConfig::medium_density_threshold`
+    pub medium_density_threshold: Option<f64>,
+    /// Overrides `IndexConfig::min_occurences`
+    pub min_occurrences: Option<u64>,
+    /// Plain columns queries filter on along with the JSON paths, to lead
+    /// composite B-trees
+    pub with_columns: Vec<String>,
+    /// Build the recommended indexes with `CREATE INDEX CONCURRENTLY`
+    pub apply: bool,
+    /// Build without asking for confirmation
+    pub yes: bool,
+    /// `lock_timeout` for each build or migration statement, e.g. "5s"
+    /// (defaults to 5 seconds)
+    pub lock_timeout: Option<String>,
+    /// 1-based numbers of the recommendations to build, all when empty
+    pub select: Vec<usize>,
+    /// Write the recommended statements to this file as one migration script
+    pub emit_migration: Option<PathBuf>,
+    /// `statement_timeout` of the migration script (no limit by default)
+    pub statement_timeout: Option<String>,
+}
 
 /// run performs index recommendation analysis on a JSONB column
 pub async fn run(
@@ -11,10 +86,44 @@ pub async fn run(
     column: &str,
     sample_size: usize,
     format: OutputFormat,
+) -> Result<()> {
+    run_with_options(
+        database_url,
+        table,
+        column,
+        sample_size,
+        format,
+        &IndexOptions::default(),
+    )
+    .await
+}
+
+/// run_with_options performs index recommendation analysis with the given optional settings
+pub async fn run_with_options(
+    database_url: &str,
+    table: &str,
+    column: &str,
+    sample_size: usize,
+    format: OutputFormat,
+    options: &IndexOptions,
 ) -> Result<()> {
     let (schema, table) = parse_table_name(table);
 
-    let conn = ConnectionPool::new(database_url)
+    check_options(options)?;
+    if !options.apply && (options.yes || !options.select.is_empty()) {
+        anyhow::bail!("--yes and --select only apply to --apply");
+    }
+    if !options.apply
+        && !options.explain_analyze
+        && options.emit_migration.is_none()
+        && options.lock_timeout.is_some()
+    {
+        anyhow::bail!(
+            "--lock-timeout only applies to --apply, --explain-analyze and --emit-migration"
+        );
+    }
+
+    let conn = ConnectionPool::with_replica(database_url, options.replica_url.as_deref())
         .await
         .context("Failed to create database connection pool")?;
 
@@ -22,15 +131,228 @@ pub async fn run(
         .await
         .context("Failed to connect to the database")?;
 
-    let sampler = Sampler::new(conn.pool(), &schema, &table, None, sample_size)
+    super::warn_replica_fallback(&conn);
+
+    for with in &options.with_columns {
+        if with == column {
+            anyhow::bail!("--with-column {} is the column being indexed", with);
+        }
+        if column_data_type(conn.pool(), &schema, &table, with)
+            .await
+            .context("Failed to look up columns")?
+            .is_none()
+        {
+            anyhow::bail!(
+                "--with-column {}: no such column in {}.{}",
+                with,
+                schema,
+                table
+            );
+        }
+    }
+
+    let features = database_features(&conn, options).await?;
+    let result = recommend(
+        &conn,
+        &schema,
+        &table,
+        column,
+        sample_size,
+        options,
+        &features,
+    )
+    .await?;
+
+    print_index_recommendations(&result, &format);
+
+    if let Some(path) = &options.emit_migration {
+        let steps: Vec<MigrationStep> = result
+            .recommendations
+            .iter()
+            .map(|recommendation| MigrationStep {
+                schema: &schema,
+                table: &table,
+                column,
+                recommendation,
+            })
+            .collect();
+        write_migration(path, &steps, options)?;
+    }
+
+    if options.apply {
+        apply_recommendations(&conn, &schema, &result.recommendations, options).await?;
+    }
+    Ok(())
+}
+
+/// Reject option combinations that can't work, before connecting
+pub(crate) fn check_options(options: &IndexOptions) -> Result<()> {
+    if options.generated_columns && !options.workload {
+        anyhow::bail!("--generated-columns needs --workload to tell which paths are busy");
+    }
+    let defaults = IndexConfig::default();
+    if options
+        .medium_density_threshold
+        .unwrap_or(defaults.medium_density_threshold)
+        > options
+            .high_density_threshold
+            .unwrap_or(defaults.high_density_threshold)
+    {
+        anyhow::bail!(
+            "--medium-density-threshold must not be above --high-density-threshold ({} and {} by default)",
+            defaults.medium_density_threshold,
+            defaults.high_density_threshold
+        );
+    }
+    if options.emit_migration.is_none() && options.statement_timeout.is_some() {
+        anyhow::bail!("--statement-timeout only applies to --emit-migration");
+    }
+    if options.explain_analyze && options.hypothetical {
+        anyhow::bail!(
+            "--explain-analyze and --hypothetical are exclusive: --explain-analyze builds real indexes"
+        );
+    }
+    Ok(())
+}
+
+/// Write recommendations to a migration script, with the options' timeouts
+pub(crate) fn write_migration(
+    path: &Path,
+    steps: &[MigrationStep],
+    options: &IndexOptions,
+) -> Result<()> {
+    let settings = MigrationSettings {
+        lock_timeout: options
+            .lock_timeout
+            .clone()
+            .unwrap_or_else(|| DEFAULT_LOCK_TIMEOUT.to_string()),
+        statement_timeout: options
+            .statement_timeout
+            .clone()
+            .unwrap_or_else(|| DEFAULT_STATEMENT_TIMEOUT.to_string()),
+    };
+    std::fs::write(path, migration_script(steps, &settings))
+        .with_context(|| format!("Failed to write migration {}", path.display()))?;
+    progress!(
+        "Wrote {} index recommendations to {}",
+        steps.len(),
+        path.display()
+    );
+    Ok(())
+}
+
+/// Extensions and recorded statements the recommendations draw on, looked
+/// up once per run
+pub(crate) struct DatabaseFeatures {
+    /// Statements recorded by pg_stat_statements, empty without `workload`
+    pub statements: Vec<Statement>,
+    /// Whether the pg_trgm extension is installed
+    pub trigram: bool,
+}
+
+/// Check the extensions the options need and read the recorded workload
+pub(crate) async fn database_features(
+    conn: &ConnectionPool,
+    options: &IndexOptions,
+) -> Result<DatabaseFeatures> {
+    if options.hypothetical
+        && !has_extension(conn.pool(), "hypopg")
+            .await
+            .context("Failed to look up extensions")?
+    {
+        anyhow::bail!("--hypothetical needs the hypopg extension (CREATE EXTENSION hypopg)");
+    }
+
+    // Statement statistics live on the primary
+    let statements = if options.workload {
+        if !has_extension(conn.pool(), "pg_stat_statements")
+            .await
+            .context("Failed to look up extensions")?
+        {
+            anyhow::bail!(
+                "--workload needs the pg_stat_statements extension \
+                 (add it to shared_preload_libraries and run CREATE EXTENSION pg_stat_statements)"
+            );
+        }
+        statement_stats(conn.pool())
+            .await
+            .context("Failed to read pg_stat_statements")?
+            .into_iter()
+            .map(|s| Statement {
+                query: s.query,
+                calls: s.calls.max(0) as u64,
+                mean_exec_time_ms: s.mean_exec_time,
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    let trigram = has_extension(conn.pool(), "pg_trgm")
+        .await
+        .context("Failed to look up extensions")?;
+    if !trigram && !options.text_search.is_empty() {
+        anyhow::bail!("--text-search needs the pg_trgm extension (CREATE EXTENSION pg_trgm)");
+    }
+
+    Ok(DatabaseFeatures {
+        statements,
+        trigram,
+    })
+}
+
+/// Sample a column and recommend indexes for it, leaving out what the
+/// table's existing indexes already serve
+pub(crate) async fn recommend(
+    conn: &ConnectionPool,
+    schema: &str,
+    table: &str,
+    column: &str,
+    sample_size: usize,
+    options: &IndexOptions,
+    features: &DatabaseFeatures,
+) -> Result<IndexRecommendationResult> {
+    let accesses = find_column_accesses(table, column, &features.statements);
+    if !features.trigram && accesses.iter().any(|a| a.operator == JsonOperator::Pattern) {
+        eprintln!(
+            "Warning: queries use LIKE/ILIKE on {}, but trigram indexes need the pg_trgm extension (CREATE EXTENSION pg_trgm)",
+            column
+        );
+    }
+
+    // Index statistics live on the primary
+    let indexes = table_indexes(conn.pool(), schema, table)
+        .await
+        .context("Failed to list existing indexes")?;
+    let usage: Vec<IndexUsage> = indexes
+        .iter()
+        .filter(|index| index.columns.iter().any(|c| c == column))
+        .map(|index| IndexUsage {
+            name: index.name.clone(),
+            unique: index.unique,
+            scans: index.scans,
+            size_bytes: index.size_bytes,
+        })
+        .collect();
+    let existing: Vec<ExistingIndex> = indexes.into_iter().map(existing_index).collect();
+
+    let row_count = get_row_count(conn.sampling_pool(), schema, table)
+        .await
+        .context("Failed to count rows")?;
+
+    let sampler = Sampler::new(conn.pool(), schema, table, Some(row_count), sample_size)
         .await
         .context("Failed to create sampler")?
-        .show_progress(true);
+        .with_filter(SampleFilter {
+            contains: options.filter.clone(),
+            ..Default::default()
+        })
+        .show_progress(show_progress());
 
-    println!("\nSampling Strategy: {}", sampler.strategy_info());
+    progress!("\nSampling Strategy: {}", sampler.strategy_info());
 
     let samples = sampler
-        .sample(conn.pool(), &schema, &table, column)
+        .sample(conn.sampling_pool(), schema, table, column)
         .await
         .context("Failed to sample data")?;
 
@@ -38,7 +360,7 @@ pub async fn run(
         anyhow::bail!("No samples found. Column may be empty or NULL.");
     }
 
-    println!(
+    progress!(
         "Analyzing {} samples for index recommendations...",
         samples.len()
     );
@@ -48,24 +370,333 @@ pub async fn run(
     for sample in &samples {
         analyzer.analyze(sample);
     }
+    if analyzer.truncation().is_truncated() {
+        eprintln!(
+            "Warning: analysis truncated - {}",
+            analyzer.truncation().summary()
+        );
+    }
     let stats = analyzer.finalize();
     let mut field_stats: Vec<_> = stats.values().cloned().collect();
     field_stats.sort_by(|a, b| a.path.cmp(&b.path));
 
     // Generate index recommendations
-    let config = IndexConfig::default();
-    let recommendations = recommend_index(&table, column, &field_stats, &config);
+    let mut config = IndexConfig {
+        gin_operator_class: if options.containment_only {
+            GinOperatorClass::JsonbPathOps
+        } else {
+            GinOperatorClass::JsonbOps
+        },
+        trigram: features.trigram,
+        text_search_paths: options.text_search.clone(),
+        generated_columns: options.generated_columns,
+        with_columns: options.with_columns.clone(),
+        schema: Some(schema.to_string()),
+        ..Default::default()
+    };
+    if let Some(threshold) = options.high_density_threshold {
+        config.high_density_threshold = threshold;
+    }
+    if let Some(threshold) = options.medium_density_threshold {
+        config.medium_density_threshold = threshold;
+    }
+    if let Some(occurrences) = options.min_occurrences {
+        config.min_occurences = occurrences;
+    }
+    config.layout.rows = row_count;
 
-    let result = IndexRecommendationResult {
+    // BRIN indexes suit timestamps that follow the physical row order
+    let candidates = brin_candidates(&field_stats, &config);
+    if !candidates.is_empty() {
+        config.layout.pages = table_pages(conn.sampling_pool(), schema, table)
+            .await
+            .context("Failed to read the table size")?;
+    }
+    for path in candidates {
+        let keys: Vec<String> = path.split('.').map(str::to_string).collect();
+        match physical_order_sample(
+            conn.sampling_pool(),
+            schema,
+            table,
+            column,
+            &keys,
+            row_count,
+            ORDER_SAMPLE_ROWS,
+        )
+        .await
+        {
+            Ok(rows) => {
+                if let Some(correlation) = order_correlation(&rows) {
+                    config.layout.correlations.insert(path, correlation);
+                }
+            }
+            Err(e) => eprintln!(
+                "Warning: failed to measure how {} follows the row order: {}",
+                path, e
+            ),
+        }
+    }
+
+    let mut plan = plan_indexes(table, column, &field_stats, &accesses, &existing, &config);
+    estimate_index_sizes(&mut plan.recommendations, &field_stats, row_count, &config);
+
+    if options.hypothetical || options.explain_analyze {
+        if options.explain_analyze && !plan.recommendations.is_empty() {
+            eprintln!(
+                "Warning: --explain-analyze builds the recommended indexes on {}.{}; writes to the table wait until each build is rolled back",
+                schema, table
+            );
+        }
+        for recommendation in &mut plan.recommendations {
+            let Some(query) = probe_query(table, column, recommendation, &field_stats) else {
+                continue;
+            };
+            match check_with_planner(conn, schema, &recommendation.sql, query, options).await {
+                Ok(check) => {
+                    recommendation.estimated_benefit = check.benefit();
+                    recommendation.planner_check = Some(check);
+                }
+                Err(e) => eprintln!(
+                    "Warning: failed to check {} with the planner: {}",
+                    recommendation.field_path, e
+                ),
+            }
+        }
+    }
+
+    let drops = recommend_drops(schema, &plan.overlaps, &usage);
+    let stats_reset = if drops.is_empty() {
+        None
+    } else {
+        stats_reset(conn.pool())
+            .await
+            .context("Failed to read when statistics were reset")?
+    };
+
+    Ok(IndexRecommendationResult {
+        schema: schema.to_string(),
         table: table.to_string(),
         column: column.to_string(),
-        recommendations,
+        row_count,
+        recommendations: plan.recommendations,
+        covered: plan.covered,
+        overlaps: plan.overlaps,
+        drops,
+        stats_reset,
+        accesses,
+    })
+}
+
+/// EXPLAIN `query` without and with the index `sql` recommends: a real one,
+/// rolled back again, with `explain_analyze`, or else a hypothetical one
+async fn check_with_planner(
+    conn: &ConnectionPool,
+    schema: &str,
+    sql: &str,
+    query: String,
+    options: &IndexOptions,
+) -> Result<PlannerCheck, sqlx::Error> {
+    let statement = index_statement(sql);
+    if !options.explain_analyze {
+        let explained = explain_hypothetical(conn.pool(), schema, &statement, &query).await?;
+        return Ok(PlannerCheck {
+            query,
+            used: explained.uses_index,
+            cost_without: explained.cost_without,
+            cost_with: explained.cost_with,
+            ..Default::default()
+        });
+    }
+
+    let name = statement
+        .strip_prefix("CREATE INDEX ")
+        .and_then(|rest| rest.split_whitespace().next())
+        .unwrap_or_default();
+    let lock_timeout = options
+        .lock_timeout
+        .as_deref()
+        .unwrap_or(DEFAULT_LOCK_TIMEOUT);
+    let measured =
+        explain_analyze_index(conn.pool(), schema, name, &statement, &query, lock_timeout).await?;
+    Ok(PlannerCheck {
+        query,
+        used: measured.uses_index,
+        cost_without: measured.cost_without,
+        cost_with: measured.cost_with,
+        time_without_ms: Some(measured.time_without_ms),
+        time_with_ms: Some(measured.time_with_ms),
+    })
+}
+
+/// Build the selected recommendations one at a time, after confirmation
+async fn apply_recommendations(
+    conn: &ConnectionPool,
+    schema: &str,
+    recommendations: &[IndexRecommendation],
+    options: &IndexOptions,
+) -> Result<()> {
+    let selected: Vec<&IndexRecommendation> = if options.select.is_empty() {
+        recommendations.iter().collect()
+    } else {
+        let mut selected = Vec::new();
+        for &number in &options.select {
+            match number.checked_sub(1).and_then(|i| recommendations.get(i)) {
+                Some(recommendation) => selected.push(recommendation),
+                None => anyhow::bail!(
+                    "--select {}: there are {} recommendations",
+                    number,
+                    recommendations.len()
+                ),
+            }
+        }
+        selected
     };
 
-    print_index_recommendations(&result, &format);
+    let mut builds = Vec::new();
+    let mut planned = Vec::new();
+    for recommendation in selected {
+        match concurrent_statement(&recommendation.sql) {
+            Some((name, statement)) => planned.push((name, statement)),
+            // Adding a stored column rewrites the table under an exclusive lock
+            None => builds.push((
+                recommendation.field_path.clone(),
+                IndexBuild::Skipped("adds a generated column, run its SQL by hand".to_string()),
+            )),
+        }
+    }
+
+    if planned.is_empty() {
+        eprintln!("\nNo indexes to build.");
+        print_index_builds(&builds);
+        return Ok(());
+    }
+
+    eprintln!("\nIndexes to build:");
+    for (_, statement) in &planned {
+        eprintln!("  {}", statement);
+    }
+    if !options.yes && !confirm(&format!("Build {} indexes?", planned.len()))? {
+        eprintln!("Nothing built.");
+        return Ok(());
+    }
+
+    let lock_timeout = options
+        .lock_timeout
+        .as_deref()
+        .unwrap_or(DEFAULT_LOCK_TIMEOUT);
+    for (name, statement) in planned {
+        if index_exists(conn.pool(), schema, &name)
+            .await
+            .context("Failed to look up existing indexes")?
+        {
+            builds.push((name, IndexBuild::Skipped("already exists".to_string())));
+            continue;
+        }
+
+        let pb = ProgressBar::new_spinner();
+        pb.set_style(
+            ProgressStyle::default_spinner()
+                .template("{spinner} [{elapsed_precise}] {msg}")
+                .expect("Invalid progress bar template"),
+        );
+        if !show_progress() {
+            pb.set_draw_target(ProgressDrawTarget::hidden());
+        }
+        pb.set_message(name.clone());
+        pb.enable_steady_tick(Duration::from_millis(100));
+
+        let started = Instant::now();
+        let result = build_index_concurrently(
+            conn.pool(),
+            schema,
+            &name,
+            &statement,
+            lock_timeout,
+            |progress| pb.set_message(format!("{}: {}", name, progress_label(progress))),
+        )
+        .await;
+        pb.finish_and_clear();
+
+        let build = match result {
+            Ok(size_bytes) => IndexBuild::Created {
+                size_bytes,
+                seconds: started.elapsed().as_secs_f64(),
+            },
+            Err(e) => IndexBuild::Failed(e.to_string()),
+        };
+        builds.push((name, build));
+    }
+
+    print_index_builds(&builds);
+
+    let failed = builds
+        .iter()
+        .filter(|(_, build)| matches!(build, IndexBuild::Failed(_)))
+        .count();
+    if failed > 0 {
+        anyhow::bail!("{} of {} indexes failed to build", failed, builds.len());
+    }
     Ok(())
 }
 
+/// Ask a yes/no question on the terminal, defaulting to no
+fn confirm(question: &str) -> Result<bool> {
+    let stdin = std::io::stdin();
+    if !stdin.is_terminal() {
+        anyhow::bail!(
+            "--apply asks for confirmation on a terminal; pass --yes to build without it"
+        );
+    }
+    eprint!("{} [y/N] ", question);
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    stdin.lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+fn progress_label(progress: &BuildProgress) -> String {
+    if progress.blocks_total > 0 {
+        format!(
+            "{} ({}/{} blocks)",
+            progress.phase, progress.blocks_done, progress.blocks_total
+        )
+    } else {
+        progress.phase.clone()
+    }
+}
+
+/// The index name and `CREATE INDEX CONCURRENTLY` form of a recommendation,
+/// or None when it's more than a single `CREATE INDEX`
+fn concurrent_statement(sql: &str) -> Option<(String, String)> {
+    let statement = index_statement(sql);
+    let rest = statement.strip_prefix("CREATE INDEX ")?;
+    if rest.trim_end().trim_end_matches(';').contains(';') {
+        return None;
+    }
+    let name = rest.split_whitespace().next()?.to_string();
+    Some((name, format!("CREATE INDEX CONCURRENTLY {}", rest)))
+}
+
+/// The `CREATE INDEX` statement of a recommendation, without its comment
+fn index_statement(sql: &str) -> String {
+    sql.lines()
+        .filter(|line| !line.trim_start().starts_with("--"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn existing_index(index: TableIndex) -> ExistingIndex {
+    ExistingIndex {
+        name: index.name,
+        method: index.method,
+        keys: index.keys,
+        operator_classes: index.operator_classes,
+        predicate: index.predicate,
+        unique: index.unique,
+        definition: index.definition,
+    }
+}
+
 /// Parse table name into schema and table components
 fn parse_table_name(table: &str) -> (String, String) {
     match table.split_once('.') {
@@ -78,6 +709,38 @@ fn parse_table_name(table: &str) -> (String, String) {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_index_statement() {
+        assert_eq!(
+            index_statement(
+                "-- GIN index for high-density fields\nCREATE INDEX idx ON users USING GIN (metadata);"
+            ),
+            "CREATE INDEX idx ON users USING GIN (metadata);"
+        );
+    }
+
+    #[test]
+    fn test_concurrent_statement() {
+        assert_eq!(
+            concurrent_statement(
+                "-- Hash index for nearly unique lookups\n\
+                 CREATE INDEX idx_users_metadata_id_hash ON users USING HASH ((metadata #>> '{id}'));"
+            ),
+            Some((
+                "idx_users_metadata_id_hash".to_string(),
+                "CREATE INDEX CONCURRENTLY idx_users_metadata_id_hash ON users USING HASH ((metadata #>> '{id}'));"
+                    .to_string()
+            ))
+        );
+        assert_eq!(
+            concurrent_statement(
+                "ALTER TABLE users ADD COLUMN user_email TEXT GENERATED ALWAYS AS (metadata #>> '{user,email}') STORED;\n\
+                 CREATE INDEX idx_users_metadata_user_email_gen ON users (user_email);"
+            ),
+            None
+        );
+    }
+
     #[test]
     fn test_parse_table_name() {
         let (schema, table) = parse_table_name("myschema.mytable");
@@ -0,0 +1,202 @@
+use crate::commands::index::{
+    IndexOptions, check_options, database_features, recommend, write_migration,
+};
+use crate::migration::MigrationStep;
+use crate::output::{IndexAllResult, OutputFormat, print_index_all_summary};
+use anyhow::{Context, Result};
+use pgdrift_core::report::index::ColumnFailure;
+use pgdrift_db::{ConnectionPool, DiscoveryOptions, discover_columns};
+use std::path::PathBuf;
+
+/// Optional settings for the index-all command
+#[derive(Debug, Clone, Default)]
+pub struct IndexAllOptions {
+    /// Read replica to run sampling queries against
+    pub replica_url: Option<String>,
+    /// Only sample documents containing this JSON (`column @> filter`)
+    pub filter: Option<serde_json::Value>,
+    /// Recommend `jsonb_path_ops` GIN indexes, for columns only queried by containment
+    pub containment_only: bool,
+    /// EXPLAIN a probe query against a hypothetical copy of each recommended index
+    pub hypothetical: bool,
+    /// Time a probe query with EXPLAIN ANALYZE before and after building each
+    /// recommended index in a transaction that's rolled back
+    pub explain_analyze: bool,
+    /// Prioritize recommendations by the statements recorded in pg_stat_statements
+    pub workload: bool,
+    /// Recommend generated columns with a plain index for the busiest extracted paths
+    pub generated_columns: bool,
+    /// Overrides `IndexConfig::high_density_threshold`
+    pub high_density_threshold: Option<f64>,
+    /// Overrides `IndexConfig::medium_density_threshold`
+    pub medium_density_threshold: Option<f64>,
+    /// Overrides `IndexConfig::min_occurences`
+    pub min_occurrences: Option<u64>,
+    /// Glob patterns (`schema.table.column`) of columns to include
+    pub include: Vec<String>,
+    /// Glob patterns (`schema.table.column`) of columns to skip
+    pub exclude: Vec<String>,
+    /// Skip columns of tables with fewer estimated rows than this
+    pub min_rows: Option<i64>,
+    /// Write every recommended statement to this file as one migration script
+    pub emit_migration: Option<PathBuf>,
+    /// `lock_timeout` of the migration script and of each build of
+    /// `explain_analyze` (defaults to 5 seconds)
+    pub lock_timeout: Option<String>,
+    /// `statement_timeout` of the migration script (no limit by default)
+    pub statement_timeout: Option<String>,
+}
+
+impl IndexAllOptions {
+    /// Settings for each column's recommendations
+    fn index_options(&self) -> IndexOptions {
+        IndexOptions {
+            replica_url: self.replica_url.clone(),
+            filter: self.filter.clone(),
+            containment_only: self.containment_only,
+            hypothetical: self.hypothetical,
+            explain_analyze: self.explain_analyze,
+            workload: self.workload,
+            generated_columns: self.generated_columns,
+            high_density_threshold: self.high_density_threshold,
+            medium_density_threshold: self.medium_density_threshold,
+            min_occurrences: self.min_occurrences,
+            lock_timeout: self.lock_timeout.clone(),
+            emit_migration: self.emit_migration.clone(),
+            statement_timeout: self.statement_timeout.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Run index-all command to recommend indexes for all JSONB columns in the given DB
+pub async fn run(database_url: &str, sample_size: usize, format: OutputFormat) -> Result<()> {
+    run_with_options(
+        database_url,
+        sample_size,
+        format,
+        &IndexAllOptions::default(),
+    )
+    .await
+}
+
+/// Run index-all command with the given optional settings
+pub async fn run_with_options(
+    database_url: &str,
+    sample_size: usize,
+    format: OutputFormat,
+    options: &IndexAllOptions,
+) -> Result<()> {
+    let index_options = options.index_options();
+    check_options(&index_options)?;
+    if options.emit_migration.is_none()
+        && !options.explain_analyze
+        && options.lock_timeout.is_some()
+    {
+        anyhow::bail!("--lock-timeout only applies to --explain-analyze and --emit-migration");
+    }
+
+    let conn = ConnectionPool::with_replica(database_url, options.replica_url.as_deref())
+        .await
+        .context("Failed to connect to the database")?;
+
+    conn.test_connection()
+        .await
+        .context("Failed to test the database connection")?;
+
+    super::warn_replica_fallback(&conn);
+
+    // Only tables can be indexed, so views are never discovered
+    let discovery = DiscoveryOptions {
+        include: options.include.clone(),
+        exclude: options.exclude.clone(),
+        min_rows: options.min_rows,
+        ..Default::default()
+    };
+    let columns = discover_columns(conn.pool(), &discovery)
+        .await
+        .context("Failed to discover JSONB columns")?;
+
+    if columns.is_empty() {
+        progress!("No JSONB columns found in the database.");
+        return Ok(());
+    }
+
+    let features = database_features(&conn, &index_options).await?;
+
+    progress!(
+        "Discovered {} JSONB columns. Starting analysis...\n",
+        columns.len()
+    );
+
+    let mut column_results = Vec::new();
+    let mut failures = Vec::new();
+    for col in &columns {
+        progress!(
+            "Analyzing column: {}.{} (table: {})",
+            col.schema,
+            col.column,
+            col.table
+        );
+
+        match recommend(
+            &conn,
+            &col.schema,
+            &col.table,
+            &col.column,
+            sample_size,
+            &index_options,
+            &features,
+        )
+        .await
+        {
+            Ok(result) => {
+                progress!(
+                    "Analysis complete for {}.{}.{} - Recommendations: {}\n",
+                    col.schema,
+                    col.table,
+                    col.column,
+                    result.recommendations.len()
+                );
+                column_results.push(result);
+            }
+            Err(e) => {
+                eprintln!(
+                    "Error analyzing column {}.{}.{}: {:#}\n",
+                    col.schema, col.table, col.column, e
+                );
+                // Continue with next column even if there's an error
+                failures.push(ColumnFailure {
+                    schema: col.schema.clone(),
+                    table: col.table.clone(),
+                    column: col.column.clone(),
+                    error: format!("{:#}", e),
+                });
+            }
+        }
+    }
+
+    let result = IndexAllResult {
+        total_columns: columns.len(),
+        column_results,
+        failures,
+    };
+
+    print_index_all_summary(&result, &format);
+
+    if let Some(path) = &options.emit_migration {
+        let steps: Vec<MigrationStep> = result
+            .ranked()
+            .into_iter()
+            .map(|(r, recommendation)| MigrationStep {
+                schema: &r.schema,
+                table: &r.table,
+                column: &r.column,
+                recommendation,
+            })
+            .collect();
+        write_migration(path, &steps, &index_options)?;
+    }
+
+    Ok(())
+}
@@ -1,4 +1,22 @@
 pub mod analyze;
+pub mod compare;
+pub mod diff;
 pub mod discover;
 pub mod index;
+pub mod index_all;
+pub mod promote;
 pub mod scan_all;
+pub mod validate;
+
+use pgdrift_db::ConnectionPool;
+
+/// Warn that sampling runs on the primary because the read replica given
+/// couldn't be reached, and why
+pub(crate) fn warn_replica_fallback(conn: &ConnectionPool) {
+    if let Some(e) = conn.replica_error() {
+        eprintln!(
+            "Warning: read replica unreachable, sampling from primary: {}",
+            e
+        );
+    }
+}
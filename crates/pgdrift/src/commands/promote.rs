@@ -0,0 +1,109 @@
+use crate::output::{OutputFormat, PromotionResult, print_promotions, show_progress};
+use anyhow::{Context, Result};
+use pgdrift_core::analyzer::JsonAnalyzer;
+use pgdrift_core::promotion::{PromotionConfig, recommend_promotions};
+use pgdrift_db::discovery::{column_type, get_row_count};
+use pgdrift_db::{ColumnType, ConnectionPool, SampleFilter, Sampler};
+
+/// Optional settings for the promote command
+#[derive(Debug, Clone, Default)]
+pub struct PromoteOptions {
+    /// Read replica to run sampling queries against
+    pub replica_url: Option<String>,
+    /// Only sample documents containing this JSON (`column @> filter`)
+    pub filter: Option<serde_json::Value>,
+}
+
+/// Recommend moving stable scalar paths of a column into real columns
+pub async fn run(
+    database_url: &str,
+    table: &str,
+    column: &str,
+    sample_size: usize,
+    format: OutputFormat,
+    options: &PromoteOptions,
+) -> Result<()> {
+    let (schema, table) = parse_table_name(table);
+
+    let conn = ConnectionPool::with_replica(database_url, options.replica_url.as_deref())
+        .await
+        .context("Failed to create database connection pool")?;
+
+    conn.test_connection()
+        .await
+        .context("Failed to connect to the database")?;
+
+    super::warn_replica_fallback(&conn);
+
+    // The DDL extracts values with `#>>`, which json and jsonb support alike
+    let data_type = column_type(conn.pool(), &schema, &table, column)
+        .await
+        .context("Failed to look up column type")?
+        .unwrap_or(ColumnType::Jsonb);
+    if !matches!(data_type, ColumnType::Jsonb | ColumnType::Json) {
+        anyhow::bail!(
+            "Column promotion needs a jsonb or json column, {} is {}",
+            column,
+            data_type.as_str()
+        );
+    }
+
+    let row_count = get_row_count(conn.sampling_pool(), &schema, &table)
+        .await
+        .context("Failed to count rows")?;
+
+    let sampler = Sampler::new(conn.pool(), &schema, &table, Some(row_count), sample_size)
+        .await
+        .context("Failed to create sampler")?
+        .with_filter(SampleFilter {
+            contains: options.filter.clone(),
+            ..Default::default()
+        })
+        .column_type(data_type)
+        .show_progress(show_progress());
+
+    progress!("\nSampling Strategy: {}", sampler.strategy_info());
+
+    let samples = sampler
+        .sample(conn.sampling_pool(), &schema, &table, column)
+        .await
+        .context("Failed to sample data")?;
+
+    if samples.is_empty() {
+        anyhow::bail!("No samples found. Column may be empty or NULL.");
+    }
+
+    let mut analyzer = JsonAnalyzer::new();
+    for sample in &samples {
+        analyzer.analyze(sample);
+    }
+    let samples_analyzed = analyzer.total_samples();
+    let field_stats: Vec<_> = analyzer.finalize().into_values().collect();
+    let promotions = recommend_promotions(
+        &schema,
+        &table,
+        column,
+        &field_stats,
+        &PromotionConfig::default(),
+    );
+
+    print_promotions(
+        &PromotionResult {
+            table,
+            column: column.to_string(),
+            samples_analyzed,
+            row_count,
+            promotions,
+        },
+        &format,
+    );
+    Ok(())
+}
+
+/// Parse table name into schema and table components
+fn parse_table_name(table: &str) -> (String, String) {
+    match table.split_once('.') {
+        Some((schema, table)) => (schema.to_string(), table.to_string()),
+        None => ("public".to_string(), table.to_string()),
+    }
+}
@@ -1,12 +1,118 @@
-use crate::output::{ColumnScanResult, OutputFormat, ScanAllResult};
+use crate::commands::analyze::{ColumnIssues, column_issues};
+use crate::config::{Config, Date, DriftSettings};
+use crate::metrics::{ColumnMetrics, publish_metrics};
+use crate::output::{
+    ColumnScanResult, OutputFormat, ScanAllResult, ScanAllView, write_summary_file,
+};
+use crate::watermark::{WatermarkStore, incremental_filter, upper_bound};
+use crate::xlsx::{ColumnExport, index_recommendations, write_workbook};
 use anyhow::{Context, Result};
-use pgdrift_core::analyzer::JsonAnalyzer;
-use pgdrift_core::drift::{DriftConfig, DriftIssue, Severity, detect_drift};
-use pgdrift_db::{ConnectionPool, Sampler, discover_jsonb_columns};
+use pgdrift_core::analyzer::{AnalyzerState, JsonAnalyzer};
+use pgdrift_core::drift::Severity;
+use pgdrift_core::redact::Redaction;
+use pgdrift_core::report::{RunSummary, Thresholds};
+use pgdrift_core::stats::FieldStats;
+use pgdrift_db::sampler::max_column_value;
+use pgdrift_db::{
+    ConnectionPool, DiscoveryOptions, JsonbColumn, SampleFilter, Sampler, SamplingStrategy,
+    discover_columns,
+};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Optional settings for the scan-all command
+#[derive(Debug, Clone, Default)]
+pub struct ScanAllOptions {
+    /// Read replica to run sampling queries against
+    pub replica_url: Option<String>,
+    /// Column used as the watermark for incremental runs
+    pub since_column: Option<String>,
+    /// Only sample rows where `since_column` is above this value
+    pub since_value: Option<String>,
+    /// File that stores watermarks between incremental runs
+    pub watermark_file: Option<PathBuf>,
+    /// Sample the newest N rows of each column instead of random rows
+    pub tail: Option<usize>,
+    /// Column that orders rows by recency for tail sampling (defaults to the PK)
+    pub tail_column: Option<String>,
+    /// Only sample documents containing this JSON (`column @> filter`)
+    pub filter: Option<serde_json::Value>,
+    /// Also scan legacy `json` columns
+    pub include_json: bool,
+    /// Also scan `hstore` columns
+    pub include_hstore: bool,
+    /// Also scan `jsonb[]` (and with `include_json`, `json[]`) array columns
+    pub include_arrays: bool,
+    /// Glob patterns (`schema.table.column`) of columns to include
+    pub include: Vec<String>,
+    /// Glob patterns (`schema.table.column`) of columns to skip
+    pub exclude: Vec<String>,
+    /// Also scan columns of views and materialized views
+    pub include_views: bool,
+    /// Skip columns of tables with fewer estimated rows than this
+    pub min_rows: Option<i64>,
+    /// Hide sampled values in the reported issues
+    pub redact_examples: Option<Redaction>,
+    /// Config file to read instead of `.pgdrift.toml` in the working directory
+    pub config: Option<PathBuf>,
+    /// Run only these built-in detectors (all of them if empty)
+    pub detectors: Vec<String>,
+    /// Built-in detectors to skip
+    pub disable_detectors: Vec<String>,
+    /// Fail once the summary is printed if any column's health score is below this
+    pub fail_below_score: Option<u8>,
+    /// Fail once the summary is printed if any column has an issue at least this severe
+    pub fail_on: Option<Severity>,
+    /// File a compact JSON summary of the run is written to, pass or fail
+    pub summary_file: Option<PathBuf>,
+    /// Excel workbook the columns, their field stats, issues and index
+    /// recommendations are exported to
+    pub xlsx: Option<PathBuf>,
+    /// File the run's Prometheus metrics are written to
+    pub metrics_file: Option<PathBuf>,
+    /// Prometheus Pushgateway the run's metrics are pushed to
+    pub pushgateway: Option<String>,
+    /// Tera template the summary is rendered through instead of `format`
+    pub template: Option<PathBuf>,
+    /// How the columns are listed in table and markdown output
+    pub view: ScanAllView,
+    /// Sample the same rows on every run while the tables don't change
+    pub stable: bool,
+}
 
 /// Run scan-all command to analyze all JSONB columns in the given DB
 pub async fn run(database_url: &str, sample_size: usize, format: OutputFormat) -> Result<()> {
-    let conn = ConnectionPool::new(database_url)
+    run_with_options(
+        database_url,
+        sample_size,
+        format,
+        &ScanAllOptions::default(),
+    )
+    .await
+}
+
+/// Run scan-all command with the given optional settings
+pub async fn run_with_options(
+    database_url: &str,
+    sample_size: usize,
+    format: OutputFormat,
+    options: &ScanAllOptions,
+) -> Result<()> {
+    if options.view.grouped && !matches!(format, OutputFormat::Markdown | OutputFormat::Html) {
+        anyhow::bail!("--grouped only applies to the markdown and html formats");
+    }
+    let mut settings = Config::load(options.config.as_deref())?;
+    settings
+        .drift
+        .select_detectors(&options.detectors, &options.disable_detectors);
+    let today = Date::today();
+    for suppression in settings.drift.expired(today) {
+        eprintln!(
+            "Warning: suppression of {} expired, its issues are reported again",
+            suppression.path
+        );
+    }
+    let conn = ConnectionPool::with_replica(database_url, options.replica_url.as_deref())
         .await
         .context("Failed to connect to the database")?;
 
@@ -14,40 +120,121 @@ pub async fn run(database_url: &str, sample_size: usize, format: OutputFormat) -
         .await
         .context("Failed to test the database connection")?;
 
-    let columns = discover_jsonb_columns(conn.pool())
+    super::warn_replica_fallback(&conn);
+
+    let discovery = DiscoveryOptions {
+        include_json: options.include_json,
+        include_hstore: options.include_hstore,
+        include_arrays: options.include_arrays,
+        include: options.include.clone(),
+        exclude: options.exclude.clone(),
+        include_views: options.include_views,
+        min_rows: options.min_rows,
+        ..Default::default()
+    };
+    let columns = discover_columns(conn.pool(), &discovery)
         .await
         .context("Failed to discover JSONB columns")?;
 
     if columns.is_empty() {
-        println!("No JSONB columns found in the database.");
+        progress!("No JSONB columns found in the database.");
         return Ok(());
     }
 
-    println!(
+    progress!(
         "Discovered {} JSONB columns. Starting analysis...\n",
         columns.len()
     );
 
     let mut column_results = Vec::new();
-    let config = DriftConfig::default();
+    // Only kept for the workbook, field stats take up more memory than the rest
+    let mut exports = Vec::new();
+    let mut watermarks = options
+        .watermark_file
+        .as_deref()
+        .map(WatermarkStore::load)
+        .transpose()?;
 
     for col in &columns {
-        println!(
+        progress!(
             "Analyzing column: {}.{} (table: {})",
-            col.schema, col.column, col.table
+            col.schema,
+            col.column,
+            col.table
+        );
+
+        let watermark_key = col.full_name();
+        let mut filter = incremental_filter(
+            options.since_column.as_deref(),
+            options.since_value.as_deref(),
+            watermarks.as_ref(),
+            &watermark_key,
         );
+        filter.contains = options.filter.clone();
+        // Stats of the rows previous runs analyzed, merged with the new ones
+        let previous = match (&watermarks, &options.since_value) {
+            (Some(store), None) if filter.since.is_some() => {
+                store.analyzer(&watermark_key).cloned()
+            }
+            _ => None,
+        };
 
         match analyze_column(
-            conn.pool(),
-            &col.schema,
-            &col.table,
-            &col.column,
+            &conn,
+            col,
             sample_size,
-            &config,
+            filter,
+            previous,
+            options,
+            &settings.drift,
+            today,
         )
         .await
         {
-            Ok((samples_analyzed, drift_issues)) => {
+            Ok(ColumnAnalysis {
+                samples_analyzed,
+                stats,
+                issues:
+                    ColumnIssues {
+                        drift_issues,
+                        suppressed_issues,
+                        health_score,
+                    },
+                state,
+                next_watermark,
+            }) => {
+                if let (Some(store), Some(value), Some(state)) =
+                    (watermarks.as_mut(), next_watermark, state)
+                {
+                    store.set(&watermark_key, value, state);
+                }
+
+                if options.xlsx.is_some() {
+                    let mut field_stats: Vec<FieldStats> = stats.into_values().collect();
+                    field_stats.sort_by(|a, b| a.path.cmp(&b.path));
+                    if let Some(redaction) = options.redact_examples {
+                        for fs in &mut field_stats {
+                            fs.redact(redaction);
+                        }
+                    }
+                    exports.push(ColumnExport {
+                        schema: col.schema.clone(),
+                        table: col.table.clone(),
+                        column: col.column.clone(),
+                        samples_analyzed: samples_analyzed as u64,
+                        health_score: Some(health_score),
+                        suppressed_issues,
+                        recommendations: index_recommendations(
+                            &col.schema,
+                            &col.table,
+                            &col.column,
+                            &field_stats,
+                        ),
+                        field_stats,
+                        drift_issues: drift_issues.clone(),
+                    });
+                }
+
                 let critical = drift_issues
                     .iter()
                     .filter(|i| i.severity() == Severity::Critical)
@@ -61,8 +248,8 @@ pub async fn run(database_url: &str, sample_size: usize, format: OutputFormat) -
                     .filter(|i| i.severity() == Severity::Info)
                     .count();
 
-                println!(
-                    "Analysis complete for {}.{}.{} - Samples Analyzed: {}, Issues Found: {} (Critical: {}, Warning: {}, Info: {})\n",
+                progress!(
+                    "Analysis complete for {}.{}.{} - Samples Analyzed: {}, Issues Found: {} (Critical: {}, Warning: {}, Info: {}), Health Score: {}\n",
                     col.schema,
                     col.table,
                     col.column,
@@ -70,7 +257,8 @@ pub async fn run(database_url: &str, sample_size: usize, format: OutputFormat) -
                     drift_issues.len(),
                     critical,
                     warning,
-                    info
+                    info,
+                    health_score
                 );
 
                 column_results.push(ColumnScanResult {
@@ -79,20 +267,32 @@ pub async fn run(database_url: &str, sample_size: usize, format: OutputFormat) -
                     column: col.column.clone(),
                     samples_analyzed: samples_analyzed as u64,
                     drift_issues,
+                    suppressed_issues,
+                    health_score: Some(health_score),
                 });
             }
             Err(e) => {
                 eprintln!(
-                    "Error analyzing column {}.{}.{}: {}\n",
+                    "Error analyzing column {}.{}.{}: {:#}\n",
                     col.schema, col.table, col.column, e
                 );
                 // Continue with next column even if there's an error
+                if options.xlsx.is_some() {
+                    exports.push(ColumnExport {
+                        schema: col.schema.clone(),
+                        table: col.table.clone(),
+                        column: col.column.clone(),
+                        ..Default::default()
+                    });
+                }
                 column_results.push(ColumnScanResult {
                     schema: col.schema.clone(),
                     table: col.table.clone(),
                     column: col.column.clone(),
                     samples_analyzed: 0,
                     drift_issues: vec![],
+                    suppressed_issues: 0,
+                    health_score: None,
                 });
             }
         }
@@ -101,32 +301,188 @@ pub async fn run(database_url: &str, sample_size: usize, format: OutputFormat) -
     let result = ScanAllResult {
         total_columns: columns.len(),
         column_results,
+        expiring_suppressions: settings.drift.expiring(today),
     };
 
-    crate::output::print_scan_all_summary(&result, &format)?;
+    match &options.template {
+        Some(template) => crate::output::print_scan_all_template(&result, template)?,
+        None => crate::output::print_scan_all_summary(&result, &format, &options.view)?,
+    }
+
+    if options.metrics_file.is_some() || options.pushgateway.is_some() {
+        // Columns that couldn't be analyzed have nothing to report
+        let column_metrics: Vec<ColumnMetrics> = result
+            .column_results
+            .iter()
+            .filter_map(|r| {
+                Some(ColumnMetrics::new(
+                    &r.schema,
+                    &r.table,
+                    &r.column,
+                    r.samples_analyzed,
+                    &r.drift_issues,
+                    r.health_score?,
+                ))
+            })
+            .collect();
+        publish_metrics(
+            options.metrics_file.as_deref(),
+            options.pushgateway.as_deref(),
+            &[],
+            &column_metrics,
+        )
+        .await?;
+    }
+
+    if let Some(path) = &options.xlsx {
+        write_workbook(path, &exports)?;
+    }
+
+    if let Some(path) = &options.summary_file {
+        let summary = RunSummary::new(
+            result.column_results.len(),
+            result
+                .column_results
+                .iter()
+                .map(|r| r.samples_analyzed)
+                .sum(),
+            result.column_results.iter().filter_map(|r| r.health_score),
+            result.column_results.iter().flat_map(|r| &r.drift_issues),
+            result.total_suppressed(),
+            Thresholds {
+                fail_below_score: options.fail_below_score,
+                fail_on: options.fail_on,
+            },
+        );
+        write_summary_file(path, &summary)?;
+    }
+
+    // Saved once the outputs are written, so a failed run is retried from
+    // the same watermarks
+    if let Some(store) = &watermarks {
+        store.save()?;
+    }
+
+    if let Some(threshold) = options.fail_below_score {
+        let failing: Vec<String> = result
+            .column_results
+            .iter()
+            .filter_map(|r| {
+                let score = r.health_score.filter(|score| *score < threshold)?;
+                Some(format!("{}.{}.{} ({})", r.schema, r.table, r.column, score))
+            })
+            .collect();
+        if !failing.is_empty() {
+            anyhow::bail!(
+                "Health score below {} for {}",
+                threshold,
+                failing.join(", ")
+            );
+        }
+    }
+
+    if let Some(fail_on) = options.fail_on {
+        let failing: Vec<String> = result
+            .column_results
+            .iter()
+            .filter_map(|r| {
+                let count = r
+                    .drift_issues
+                    .iter()
+                    .filter(|issue| issue.severity() >= fail_on)
+                    .count();
+                (count > 0).then(|| format!("{}.{}.{} ({})", r.schema, r.table, r.column, count))
+            })
+            .collect();
+        if !failing.is_empty() {
+            anyhow::bail!(
+                "Issues at {:?} severity or above in {}",
+                fail_on,
+                failing.join(", ")
+            );
+        }
+    }
 
     Ok(())
 }
 
+/// A column analyzed by scan-all
+struct ColumnAnalysis {
+    samples_analyzed: usize,
+    stats: HashMap<String, FieldStats>,
+    issues: ColumnIssues,
+    /// Stats before finalizing, kept for the next incremental run
+    state: Option<AnalyzerState>,
+    /// Highest watermark column value when sampling started
+    next_watermark: Option<String>,
+}
+
+#[allow(clippy::too_many_arguments)]
 async fn analyze_column(
-    pool: &sqlx::PgPool,
-    schema: &str,
-    table: &str,
-    column: &str,
+    conn: &ConnectionPool,
+    col: &JsonbColumn,
     sample_size: usize,
-    config: &DriftConfig,
-) -> Result<(usize, Vec<DriftIssue>)> {
-    let sampler = Sampler::new(pool, schema, table, None, sample_size)
-        .await
-        .context("Failed to create sampler")?
-        .show_progress(false);
+    mut filter: SampleFilter,
+    previous: Option<AnalyzerState>,
+    options: &ScanAllOptions,
+    settings: &DriftSettings,
+    today: Date,
+) -> Result<ColumnAnalysis> {
+    let (schema, table, column) = (&col.schema, &col.table, &col.column);
+    let incremental = filter.since.is_some();
+
+    // Read the high-water mark before sampling so rows inserted mid-run are
+    // picked up again next time rather than skipped
+    let next_watermark = match &options.since_column {
+        Some(since_column) => max_column_value(conn.sampling_pool(), schema, table, since_column)
+            .await
+            .context("Failed to read watermark column")?,
+        None => None,
+    };
+    // Rows inserted while sampling are above it and left to the next run
+    filter.until = upper_bound(options.since_column.as_deref(), next_watermark.as_deref());
+
+    let sampler = match options.tail {
+        Some(limit) => Sampler::with_strategy(
+            SamplingStrategy::tail(
+                conn.pool(),
+                schema,
+                table,
+                options.tail_column.as_deref(),
+                limit,
+            )
+            .await
+            .context("Tail sampling needs a numeric primary key or --tail-column")?,
+        ),
+        None => Sampler::new(conn.pool(), schema, table, None, sample_size)
+            .await
+            .context("Failed to create sampler")?,
+    }
+    .with_filter(filter)
+    .column_type(col.data_type)
+    .show_progress(false)
+    .repeatable(options.stable);
 
     let samples = sampler
-        .sample(pool, schema, table, column)
+        .sample(conn.sampling_pool(), schema, table, column)
         .await
         .context("Failed to sample data")?;
 
-    if samples.is_empty() {
+    if samples.is_empty() && previous.is_none() {
+        if incremental {
+            // Nothing new since the last watermark
+            return Ok(ColumnAnalysis {
+                samples_analyzed: 0,
+                stats: HashMap::new(),
+                issues: ColumnIssues {
+                    drift_issues: vec![],
+                    suppressed_issues: 0,
+                    health_score: 100,
+                },
+                state: None,
+                next_watermark,
+            });
+        }
         anyhow::bail!("No samples found in the column");
     }
 
@@ -134,8 +490,42 @@ async fn analyze_column(
     for sample in &samples {
         analyzer.analyze(sample);
     }
+    // Each run samples its rows at its own rate, so their statistics are
+    // weighted by the rows they stand for before being merged
+    if options.watermark_file.is_some()
+        && next_watermark.is_some()
+        && *sampler.strategy() != SamplingStrategy::Full
+        && !samples.is_empty()
+    {
+        let rows = sampler
+            .count_documents(conn.sampling_pool(), schema, table, column)
+            .await
+            .context("Failed to count rows")?;
+        analyzer.reweight(rows as f64 / samples.len() as f64);
+    }
+    if let Some(previous) = previous {
+        analyzer.merge(JsonAnalyzer::from_state(previous, Default::default()));
+    }
+    if analyzer.truncation().is_truncated() {
+        eprintln!(
+            "Warning: analysis of {}.{}.{} truncated - {}",
+            schema,
+            table,
+            column,
+            analyzer.truncation().summary()
+        );
+    }
+    let samples_analyzed = analyzer.total_samples() as usize;
+    let state =
+        (options.watermark_file.is_some() && next_watermark.is_some()).then(|| analyzer.state());
     let stats = analyzer.finalize();
-    let drift_issues = detect_drift(&stats, config);
+    let issues = column_issues(&stats, &samples, settings, today, options.redact_examples);
 
-    Ok((samples.len(), drift_issues))
+    Ok(ColumnAnalysis {
+        samples_analyzed,
+        stats,
+        issues,
+        state,
+        next_watermark,
+    })
 }
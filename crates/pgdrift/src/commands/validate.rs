@@ -0,0 +1,184 @@
+use crate::output::{OutputFormat, ValidationResult, print_validation, show_progress};
+use anyhow::{Context, Result};
+use pgdrift_core::analyzer::JsonAnalyzer;
+use pgdrift_core::drift::Severity;
+use pgdrift_core::validate::{SchemaSpec, validate};
+use pgdrift_db::discovery::column_type;
+use pgdrift_db::{ColumnType, ConnectionPool, SampleFilter, Sampler};
+use std::path::Path;
+
+/// Optional settings for the validate command
+#[derive(Debug, Clone)]
+pub struct ValidateOptions {
+    /// Read replica to run sampling queries against
+    pub replica_url: Option<String>,
+    /// Only sample documents containing this JSON (`column @> filter`)
+    pub filter: Option<serde_json::Value>,
+    /// Lowest violation severity that makes the command fail
+    pub fail_on: Severity,
+}
+
+impl Default for ValidateOptions {
+    fn default() -> Self {
+        Self {
+            replica_url: None,
+            filter: None,
+            fail_on: Severity::Warning,
+        }
+    }
+}
+
+/// Sample a column and check it against an expected-schema spec
+///
+/// Fails after printing the report if any violation is at least as severe
+/// as `fail_on`, so the command can gate CI.
+pub async fn run(
+    database_url: &str,
+    table: &str,
+    column: &str,
+    spec: &Path,
+    sample_size: usize,
+    format: OutputFormat,
+    options: &ValidateOptions,
+) -> Result<()> {
+    let spec = load_spec(spec)?;
+    let (schema, table) = parse_table_name(table);
+
+    let conn = ConnectionPool::with_replica(database_url, options.replica_url.as_deref())
+        .await
+        .context("Failed to create database connection pool")?;
+
+    conn.test_connection()
+        .await
+        .context("Failed to connect to the database")?;
+
+    super::warn_replica_fallback(&conn);
+
+    let data_type = column_type(conn.pool(), &schema, &table, column)
+        .await
+        .context("Failed to look up column type")?
+        .unwrap_or(ColumnType::Jsonb);
+
+    let sampler = Sampler::new(conn.pool(), &schema, &table, None, sample_size)
+        .await
+        .context("Failed to create sampler")?
+        .with_filter(SampleFilter {
+            contains: options.filter.clone(),
+            ..Default::default()
+        })
+        .column_type(data_type)
+        .show_progress(show_progress());
+
+    progress!("\nSampling Strategy: {}", sampler.strategy_info());
+
+    let samples = sampler
+        .sample(conn.sampling_pool(), &schema, &table, column)
+        .await
+        .context("Failed to sample data")?;
+
+    if samples.is_empty() {
+        anyhow::bail!("No samples found. Column may be empty or NULL.");
+    }
+
+    let mut analyzer = JsonAnalyzer::new();
+    for sample in &samples {
+        analyzer.analyze(sample);
+    }
+    let samples_analyzed = analyzer.total_samples();
+    let violations = validate(&analyzer.finalize(), &spec);
+
+    let failing = violations
+        .iter()
+        .filter(|v| v.severity >= options.fail_on)
+        .count();
+    print_validation(
+        &ValidationResult {
+            table,
+            column: column.to_string(),
+            samples_analyzed,
+            violations,
+        },
+        &format,
+    );
+
+    if failing > 0 {
+        anyhow::bail!(
+            "{} violation(s) at {:?} severity or above",
+            failing,
+            options.fail_on
+        );
+    }
+    Ok(())
+}
+
+/// Read a spec file (TOML, see `SchemaSpec`)
+fn load_spec(path: &Path) -> Result<SchemaSpec> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read spec {}", path.display()))?;
+    toml::from_str(&contents).with_context(|| format!("Invalid spec file {}", path.display()))
+}
+
+/// Parse a `--fail-on` severity name
+pub fn parse_severity(s: &str) -> Result<Severity, String> {
+    match s.to_ascii_lowercase().as_str() {
+        "info" => Ok(Severity::Info),
+        "warning" => Ok(Severity::Warning),
+        "critical" => Ok(Severity::Critical),
+        _ => Err(format!(
+            "unknown severity {:?}, expected info, warning or critical",
+            s
+        )),
+    }
+}
+
+/// Parse table name into schema and table components
+fn parse_table_name(table: &str) -> (String, String) {
+    match table.split_once('.') {
+        Some((schema, table)) => (schema.to_string(), table.to_string()),
+        None => ("public".to_string(), table.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pgdrift_core::types::JsonType;
+
+    #[test]
+    fn test_load_spec() {
+        let path = std::env::temp_dir().join(format!("pgdrift-spec-{}.toml", std::process::id()));
+        std::fs::write(
+            &path,
+            r#"
+[[paths]]
+path = "status"
+required = true
+types = ["string"]
+values = ["pending", "paid"]
+
+[[paths]]
+path = "total"
+types = ["number"]
+max_null_percentage = 1.5
+severity = "critical"
+"#,
+        )
+        .unwrap();
+
+        let spec = load_spec(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(spec.paths.len(), 2);
+        assert!(spec.paths[0].required);
+        assert_eq!(spec.paths[0].values, vec!["pending", "paid"]);
+        assert_eq!(spec.paths[1].types, vec![JsonType::Number]);
+        assert_eq!(spec.paths[1].max_null_percentage, Some(1.5));
+        assert_eq!(spec.paths[1].severity, Some(Severity::Critical));
+    }
+
+    #[test]
+    fn test_parse_severity() {
+        assert_eq!(parse_severity("critical"), Ok(Severity::Critical));
+        assert_eq!(parse_severity("Warning"), Ok(Severity::Warning));
+        assert!(parse_severity("fatal").is_err());
+    }
+}
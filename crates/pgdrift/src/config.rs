@@ -0,0 +1,601 @@
+use anyhow::{Context, Result};
+use pgdrift_core::drift::{
+    BUILTIN_DETECTORS, DriftConfig, DriftDetectors, DriftIssue, EVOLUTION_PATTERNS, ISSUE_KINDS,
+    Severity, sort_issues,
+};
+use pgdrift_db::discovery::glob_match;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Config file read from the working directory when `--config` isn't given
+pub const DEFAULT_CONFIG_FILE: &str = ".pgdrift.toml";
+
+/// Suppressions ending within this many days are listed in the report
+pub const EXPIRY_NOTICE_DAYS: i64 = 14;
+
+/// Project settings, usually committed next to the code as `.pgdrift.toml`
+///
+/// ```toml
+/// [drift]
+/// ignore = ["metadata.debug.*"]
+/// suppress = [
+///     { path = "legacy_plan", until = "2025-09-01", reason = "migration in flight" },
+/// ]
+/// severity = [
+///     { path = "order.total", issue = "TypeInconsistency", severity = "critical" },
+/// ]
+/// disable = ["constant_field"]
+/// max_nesting_depth = 10
+///
+/// [drift.schema_evolution]
+/// disable = ["mutually_exclusive"]
+/// deprecated_suffixes = ["_old", "_bak"]
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub drift: DriftSettings,
+}
+
+/// The `[drift]` section of the config file
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DriftSettings {
+    /// Glob patterns (`*`, `?`) of paths whose drift issues are known noise
+    pub ignore: Vec<String>,
+    /// Suppressions with a reason and an optional expiry date
+    pub suppress: Vec<Suppression>,
+    /// Severities that replace the computed ones; later entries win
+    pub severity: Vec<SeverityOverride>,
+    /// Built-in detectors not to run (`sparse_field`, `ghost_key`, ...)
+    pub disable: Vec<String>,
+    /// Nesting depth beyond which paths are reported as excessively deep
+    pub max_nesting_depth: Option<usize>,
+    /// Average string size in bytes from which a path is an oversized payload
+    pub oversized_string_bytes: Option<u64>,
+    /// Confidence level of the density intervals sparse, ghost and missing keys are judged on
+    pub density_confidence: Option<f64>,
+    /// Heuristics of the `schema_evolution` detector
+    pub schema_evolution: SchemaEvolutionSettings,
+}
+
+/// The `[drift.schema_evolution]` section; unset lists keep the built-in defaults
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SchemaEvolutionSettings {
+    /// Patterns not to look for (`version_marker`, `deprecated_naming`, `mutually_exclusive`)
+    pub disable: Vec<String>,
+    /// Key names marking a schema version
+    pub version_keys: Option<Vec<String>>,
+    /// Key prefixes marking the old copy of a renamed field
+    pub deprecated_prefixes: Option<Vec<String>>,
+    /// Key suffixes marking the old copy of a renamed field
+    pub deprecated_suffixes: Option<Vec<String>>,
+    /// Key suffixes marking variants of one field, `#` standing for any number
+    pub variant_suffixes: Option<Vec<String>>,
+    /// Lowest density every field of a pattern needs
+    pub min_density: Option<f64>,
+}
+
+/// Severity to report an issue at for matching paths
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SeverityOverride {
+    /// Glob pattern of the paths it applies to
+    pub path: String,
+    /// Kind of issue (`SparseField`, `TypeInconsistency`, ...); every kind if unset
+    pub issue: Option<String>,
+    pub severity: Severity,
+}
+
+impl SeverityOverride {
+    pub fn matches(&self, issue: &DriftIssue) -> bool {
+        self.issue
+            .as_deref()
+            .is_none_or(|kind| kind == issue.kind())
+            && glob_match(&self.path, issue.path())
+    }
+}
+
+/// Suppressed path with the reason it's expected, optionally time-boxed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Suppression {
+    /// Glob pattern of the suppressed paths
+    pub path: String,
+    /// Last day the suppression applies; after it the issues are reported again
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until: Option<Date>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl Suppression {
+    pub fn is_expired(&self, today: Date) -> bool {
+        self.until.is_some_and(|until| today > until)
+    }
+}
+
+/// Calendar date (`YYYY-MM-DD`), stored as days since 1970-01-01
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Date(i64);
+
+impl Date {
+    /// Today's date in UTC
+    pub fn today() -> Self {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        Date((secs / 86_400) as i64)
+    }
+
+    /// Days from `self` until `other` (negative if `other` is earlier)
+    pub fn days_until(self, other: Date) -> i64 {
+        other.0 - self.0
+    }
+
+    fn from_civil(year: i64, month: i64, day: i64) -> Self {
+        // Howard Hinnant's days_from_civil, with years starting in March
+        let year = if month <= 2 { year - 1 } else { year };
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        Date(era * 146_097 + day_of_era - 719_468)
+    }
+
+    fn to_civil(self) -> (i64, i64, i64) {
+        let days = self.0 + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days - era * 146_097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = (shifted_month + 2) % 12 + 1;
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+        (year, month, day)
+    }
+}
+
+impl std::str::FromStr for Date {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid date '{}', expected YYYY-MM-DD", s);
+        let mut parts = s.splitn(3, '-').map(|part| part.parse::<i64>().ok());
+        let (Some(Some(year)), Some(Some(month)), Some(Some(day))) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+
+        // Out-of-range days (e.g. Feb 30) don't survive the round trip
+        let date = Date::from_civil(year, month, day);
+        if (1..=12).contains(&month) && date.to_civil() == (year, month, day) {
+            Ok(date)
+        } else {
+            Err(invalid())
+        }
+    }
+}
+
+impl std::fmt::Display for Date {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (year, month, day) = self.to_civil();
+        write!(f, "{:04}-{:02}-{:02}", year, month, day)
+    }
+}
+
+impl TryFrom<String> for Date {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Date> for String {
+    fn from(date: Date) -> Self {
+        date.to_string()
+    }
+}
+
+impl Config {
+    /// Load the config file at `path`, or `.pgdrift.toml` if it exists
+    ///
+    /// A file named explicitly must exist; without one, a missing default
+    /// file just means default settings.
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let path = match path {
+            Some(path) => path,
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => Path::new(DEFAULT_CONFIG_FILE),
+            None => return Ok(Self::default()),
+        };
+
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+        Self::parse(&contents).with_context(|| format!("Invalid config file {}", path.display()))
+    }
+
+    /// Parse the contents of a config file
+    pub fn parse(contents: &str) -> Result<Self> {
+        let config: Self = toml::from_str(contents)?;
+        for kind in config
+            .drift
+            .severity
+            .iter()
+            .filter_map(|o| o.issue.as_deref())
+        {
+            if !ISSUE_KINDS.contains(&kind) {
+                anyhow::bail!(
+                    "unknown issue kind '{}', expected one of {}",
+                    kind,
+                    ISSUE_KINDS.join(", ")
+                );
+            }
+        }
+        for name in &config.drift.disable {
+            if !BUILTIN_DETECTORS.contains(&name.as_str()) {
+                anyhow::bail!(
+                    "unknown detector '{}', expected one of {}",
+                    name,
+                    BUILTIN_DETECTORS.join(", ")
+                );
+            }
+        }
+        if let Some(confidence) = config.drift.density_confidence
+            && !(0.0..1.0).contains(&confidence)
+        {
+            anyhow::bail!(
+                "density_confidence must be at least 0 and below 1, got {}",
+                confidence
+            );
+        }
+        let evolution = &config.drift.schema_evolution;
+        for name in &evolution.disable {
+            if !EVOLUTION_PATTERNS.contains(&name.as_str()) {
+                anyhow::bail!(
+                    "unknown schema evolution pattern '{}', expected one of {}",
+                    name,
+                    EVOLUTION_PATTERNS.join(", ")
+                );
+            }
+        }
+        if let Some(density) = evolution.min_density
+            && !(0.0..=1.0).contains(&density)
+        {
+            anyhow::bail!(
+                "schema_evolution.min_density must be between 0 and 1, got {}",
+                density
+            );
+        }
+        Ok(config)
+    }
+}
+
+impl DriftSettings {
+    /// Whether drift issues at `path` are suppressed on `today`
+    pub fn is_ignored(&self, path: &str, today: Date) -> bool {
+        self.ignore.iter().any(|pattern| glob_match(pattern, path))
+            || self
+                .suppress
+                .iter()
+                .any(|s| !s.is_expired(today) && glob_match(&s.path, path))
+    }
+
+    /// Narrow the detectors down from the command line
+    ///
+    /// A non-empty `only` disables every built-in not listed; `disable` adds
+    /// to the ones the config file already disables.
+    pub fn select_detectors(&mut self, only: &[String], disable: &[String]) {
+        if !only.is_empty() {
+            self.disable.extend(
+                BUILTIN_DETECTORS
+                    .iter()
+                    .filter(|name| !only.iter().any(|o| o == *name))
+                    .map(|name| name.to_string()),
+            );
+        }
+        self.disable.extend(disable.iter().cloned());
+    }
+
+    /// Detection thresholds, with the ones set in the file replacing the defaults
+    pub fn drift_config(&self) -> DriftConfig {
+        let mut config = DriftConfig::default();
+        if let Some(depth) = self.max_nesting_depth {
+            config.max_nesting_depth = depth;
+        }
+        if let Some(bytes) = self.oversized_string_bytes {
+            config.oversized_string_bytes = bytes;
+        }
+        if let Some(confidence) = self.density_confidence {
+            config.density_confidence = confidence;
+        }
+
+        let settings = &self.schema_evolution;
+        let evolution = &mut config.schema_evolution;
+        for pattern in &settings.disable {
+            evolution.disable(pattern);
+        }
+        if let Some(keys) = &settings.version_keys {
+            evolution.version_keys = keys.clone();
+        }
+        if let Some(prefixes) = &settings.deprecated_prefixes {
+            evolution.deprecated_prefixes = prefixes.clone();
+        }
+        if let Some(suffixes) = &settings.deprecated_suffixes {
+            evolution.deprecated_suffixes = suffixes.clone();
+        }
+        if let Some(suffixes) = &settings.variant_suffixes {
+            evolution.variant_suffixes = suffixes.clone();
+        }
+        if let Some(density) = settings.min_density {
+            evolution.min_density = density;
+        }
+        config
+    }
+
+    /// The built-in detectors minus the disabled ones
+    pub fn detectors(&self) -> DriftDetectors {
+        let mut detectors = DriftDetectors::builtin();
+        for name in &self.disable {
+            detectors.disable(name);
+        }
+        detectors
+    }
+
+    /// Split issues into those to report and those suppressed on `today`
+    pub fn suppress(
+        &self,
+        issues: Vec<DriftIssue>,
+        today: Date,
+    ) -> (Vec<DriftIssue>, Vec<DriftIssue>) {
+        issues
+            .into_iter()
+            .partition(|issue| !self.is_ignored(issue.path(), today))
+    }
+
+    /// Apply the severity overrides and restore the most-severe-first order
+    pub fn reclassify(&self, issues: Vec<DriftIssue>) -> Vec<DriftIssue> {
+        if self.severity.is_empty() {
+            return issues;
+        }
+
+        let mut issues: Vec<DriftIssue> = issues
+            .into_iter()
+            .map(
+                |issue| match self.severity.iter().rev().find(|o| o.matches(&issue)) {
+                    Some(found) => issue.reclassify(found.severity),
+                    None => issue,
+                },
+            )
+            .collect();
+        sort_issues(&mut issues);
+        issues
+    }
+
+    /// Suppressions that have run out, so their issues are reported again
+    pub fn expired(&self, today: Date) -> Vec<&Suppression> {
+        self.suppress
+            .iter()
+            .filter(|s| s.is_expired(today))
+            .collect()
+    }
+
+    /// Suppressions ending within `EXPIRY_NOTICE_DAYS`, soonest first
+    pub fn expiring(&self, today: Date) -> Vec<Suppression> {
+        let mut expiring: Vec<Suppression> = self
+            .suppress
+            .iter()
+            .filter(|s| {
+                s.until.is_some_and(|until| {
+                    (0..=EXPIRY_NOTICE_DAYS).contains(&today.days_until(until))
+                })
+            })
+            .cloned()
+            .collect();
+        expiring.sort_by_key(|s| s.until);
+        expiring
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pgdrift_core::drift::SchemaEvolutionConfig;
+
+    fn sparse(path: &str) -> DriftIssue {
+        DriftIssue::SparseField {
+            path: path.to_string(),
+            density: 0.5,
+            occurrences: 50,
+            total_samples: 100,
+            margin: 0.1,
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let config = Config::parse("[drift]\nignore = [\"metadata.debug.*\"]\n").unwrap();
+        assert_eq!(config.drift.ignore, vec!["metadata.debug.*"]);
+
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+        assert!(Config::parse("[drift]\nignroe = []\n").is_err());
+    }
+
+    #[test]
+    fn test_drift_config() {
+        let config =
+            Config::parse("[drift]\nmax_nesting_depth = 12\noversized_string_bytes = 8192\n")
+                .unwrap();
+        assert_eq!(config.drift.drift_config().max_nesting_depth, 12);
+        assert_eq!(config.drift.drift_config().oversized_string_bytes, 8192);
+        assert_eq!(
+            Config::default().drift.drift_config().max_nesting_depth,
+            DriftConfig::default().max_nesting_depth
+        );
+
+        let config = Config::parse("[drift]\ndensity_confidence = 0.99\n").unwrap();
+        assert_eq!(config.drift.drift_config().density_confidence, 0.99);
+        assert!(Config::parse("[drift]\ndensity_confidence = 1.5\n").is_err());
+    }
+
+    #[test]
+    fn test_schema_evolution_settings() {
+        let config = Config::parse(
+            "[drift.schema_evolution]\n\
+             disable = [\"version_marker\"]\n\
+             deprecated_suffixes = [\"_bak\"]\n\
+             min_density = 0.05\n",
+        )
+        .unwrap();
+        let evolution = config.drift.drift_config().schema_evolution;
+        assert!(!evolution.version_markers);
+        assert!(evolution.deprecated_naming && evolution.mutually_exclusive);
+        assert_eq!(evolution.deprecated_suffixes, vec!["_bak"]);
+        assert_eq!(
+            evolution.deprecated_prefixes,
+            SchemaEvolutionConfig::default().deprecated_prefixes
+        );
+        assert_eq!(evolution.min_density, 0.05);
+
+        assert!(Config::parse("[drift.schema_evolution]\ndisable = [\"typo\"]\n").is_err());
+        assert!(Config::parse("[drift.schema_evolution]\nmin_density = 2.0\n").is_err());
+    }
+
+    #[test]
+    fn test_select_detectors() {
+        let mut config = Config::parse("[drift]\ndisable = [\"constant_field\"]\n").unwrap();
+        assert!(!config.drift.detectors().names().contains(&"constant_field"));
+        assert!(Config::parse("[drift]\ndisable = [\"typo\"]\n").is_err());
+
+        config
+            .drift
+            .select_detectors(&[], &["ghost_key".to_string()]);
+        let detectors = config.drift.detectors();
+        assert_eq!(detectors.names().len(), BUILTIN_DETECTORS.len() - 2);
+
+        config.drift.select_detectors(
+            &["sparse_field".to_string(), "constant_field".to_string()],
+            &[],
+        );
+        // constant_field stays disabled by the config file
+        assert_eq!(config.drift.detectors().names(), vec!["sparse_field"]);
+    }
+
+    fn date(s: &str) -> Date {
+        s.parse().unwrap()
+    }
+
+    fn suppression(path: &str, until: &str) -> Suppression {
+        Suppression {
+            path: path.to_string(),
+            until: Some(date(until)),
+            reason: None,
+        }
+    }
+
+    #[test]
+    fn test_date() {
+        assert_eq!(date("1970-01-01"), Date(0));
+        assert_eq!(date("2000-03-01").to_string(), "2000-03-01");
+        assert_eq!(date("2024-02-29").days_until(date("2024-03-01")), 1);
+        assert_eq!(date("2025-12-31").days_until(date("2026-01-01")), 1);
+        assert!("2025-02-29".parse::<Date>().is_err());
+        assert!("2025-13-01".parse::<Date>().is_err());
+        assert!("next week".parse::<Date>().is_err());
+    }
+
+    #[test]
+    fn test_parse_suppressions() {
+        let config = Config::parse(
+            r#"
+            [[drift.suppress]]
+            path = "legacy_plan"
+            until = "2025-09-01"
+            reason = "migration in flight"
+            "#,
+        )
+        .unwrap();
+        let suppression = &config.drift.suppress[0];
+        assert_eq!(suppression.until, Some(date("2025-09-01")));
+        assert_eq!(suppression.reason.as_deref(), Some("migration in flight"));
+
+        assert!(Config::parse("[[drift.suppress]]\npath = \"a\"\nuntil = \"soon\"\n").is_err());
+    }
+
+    #[test]
+    fn test_expired_suppressions() {
+        let settings = DriftSettings {
+            suppress: vec![
+                suppression("legacy_plan", "2025-09-01"),
+                suppression("beta.*", "2025-09-10"),
+                suppression("old_*", "2025-12-01"),
+            ],
+            ..Default::default()
+        };
+
+        let today = date("2025-09-01");
+        assert!(settings.is_ignored("legacy_plan", today));
+        assert!(!settings.is_ignored("legacy_plan", date("2025-09-02")));
+
+        let expiring = settings.expiring(today);
+        let paths: Vec<&str> = expiring.iter().map(|s| s.path.as_str()).collect();
+        assert_eq!(paths, vec!["legacy_plan", "beta.*"]);
+        assert_eq!(settings.expired(date("2025-09-05")).len(), 1);
+    }
+
+    #[test]
+    fn test_reclassify() {
+        let config = Config::parse(
+            r#"
+            [[drift.severity]]
+            path = "metadata.experiment_flags.*"
+            severity = "warning"
+
+            [[drift.severity]]
+            path = "metadata.experiment_flags.*"
+            issue = "SparseField"
+            severity = "critical"
+            "#,
+        )
+        .unwrap();
+
+        let issues = config.drift.reclassify(vec![
+            sparse("metadata.source"),
+            sparse("metadata.experiment_flags.dark_mode"),
+        ]);
+        assert_eq!(issues[0].path(), "metadata.experiment_flags.dark_mode");
+        assert_eq!(issues[0].severity(), Severity::Critical);
+        assert_eq!(issues[1].severity(), Severity::Info);
+
+        let err = Config::parse(
+            "[[drift.severity]]\npath = \"a\"\nissue = \"Sparse\"\nseverity = \"info\"\n",
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("unknown issue kind 'Sparse'"));
+    }
+
+    #[test]
+    fn test_suppress() {
+        let settings = DriftSettings {
+            ignore: vec!["metadata.debug.*".to_string(), "legacy_plan".to_string()],
+            ..Default::default()
+        };
+        let issues = vec![
+            sparse("metadata.debug.trace_id"),
+            sparse("metadata.source"),
+            sparse("legacy_plan"),
+            sparse("legacy_plan_id"),
+        ];
+
+        let (reported, suppressed) = settings.suppress(issues, Date::today());
+        let paths: Vec<&str> = reported.iter().map(|i| i.path()).collect();
+        assert_eq!(paths, vec!["metadata.source", "legacy_plan_id"]);
+        assert_eq!(suppressed.len(), 2);
+    }
+}
@@ -0,0 +1,141 @@
+use crate::output::type_mix;
+use clap::ValueEnum;
+use pgdrift_core::stats::FieldStats;
+use pgdrift_core::tree::{FieldNode, field_tree};
+
+/// Graph language of a schema diagram
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DiagramFormat {
+    /// Mermaid flowchart, rendered by GitHub and GitLab in markdown
+    Mermaid,
+    /// Graphviz DOT
+    Dot,
+}
+
+/// A box of the diagram
+struct Node {
+    id: String,
+    /// Lines of the label: the key, then its density and types
+    label: Vec<String>,
+}
+
+/// Render the field hierarchy under `root` (e.g. `users.metadata`) as a graph,
+/// one node per path labeled with its density and types
+pub fn schema_diagram(format: DiagramFormat, root: &str, field_stats: &[FieldStats]) -> String {
+    let mut nodes = vec![Node {
+        id: "n0".to_string(),
+        label: vec![root.to_string()],
+    }];
+    let mut edges = Vec::new();
+    collect(&field_tree(field_stats), 0, &mut nodes, &mut edges);
+
+    match format {
+        DiagramFormat::Mermaid => mermaid(&nodes, &edges),
+        DiagramFormat::Dot => dot(&nodes, &edges),
+    }
+}
+
+/// Add `children` of the node at `parent` and their subtrees, depth first
+fn collect(
+    children: &[FieldNode],
+    parent: usize,
+    nodes: &mut Vec<Node>,
+    edges: &mut Vec<(usize, usize)>,
+) {
+    for child in children {
+        let index = nodes.len();
+        let mut label = vec![child.key.clone()];
+        if let Some(fs) = child.stats {
+            label.push(format!("{:.1}% {}", fs.density * 100.0, type_mix(fs)));
+        }
+        nodes.push(Node {
+            id: format!("n{}", index),
+            label,
+        });
+        edges.push((parent, index));
+        collect(&child.children, index, nodes, edges);
+    }
+}
+
+fn mermaid(nodes: &[Node], edges: &[(usize, usize)]) -> String {
+    let mut diagram = String::from("flowchart LR\n");
+    for node in nodes {
+        // Quotes end a label; Mermaid reads entity codes instead
+        let label: Vec<String> = node
+            .label
+            .iter()
+            .map(|line| line.replace('"', "#quot;"))
+            .collect();
+        diagram.push_str(&format!("    {}[\"{}\"]\n", node.id, label.join("<br/>")));
+    }
+    for (from, to) in edges {
+        diagram.push_str(&format!("    {} --> {}\n", nodes[*from].id, nodes[*to].id));
+    }
+    diagram
+}
+
+fn dot(nodes: &[Node], edges: &[(usize, usize)]) -> String {
+    let mut diagram = String::from("digraph schema {\n    rankdir=LR;\n    node [shape=box];\n");
+    for node in nodes {
+        let label: Vec<String> = node
+            .label
+            .iter()
+            .map(|line| line.replace('\\', "\\\\").replace('"', "\\\""))
+            .collect();
+        diagram.push_str(&format!(
+            "    {} [label=\"{}\"];\n",
+            node.id,
+            label.join("\\n")
+        ));
+    }
+    for (from, to) in edges {
+        diagram.push_str(&format!("    {} -> {};\n", nodes[*from].id, nodes[*to].id));
+    }
+    diagram.push_str("}\n");
+    diagram
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn field_stats() -> Vec<FieldStats> {
+        let mut user = FieldStats::new("user".to_string(), 1);
+        user.record(&json!({"na\"me": "Ann"}));
+        user.finalize(1);
+        let mut name = FieldStats::new("user.na\"me".to_string(), 2);
+        name.record(&json!("Ann"));
+        name.finalize(2);
+        vec![user, name]
+    }
+
+    #[test]
+    fn test_mermaid() {
+        assert_eq!(
+            schema_diagram(DiagramFormat::Mermaid, "users.metadata", &field_stats()),
+            "flowchart LR\n\
+             \x20   n0[\"users.metadata\"]\n\
+             \x20   n1[\"user<br/>100.0% object\"]\n\
+             \x20   n2[\"na#quot;me<br/>50.0% string\"]\n\
+             \x20   n0 --> n1\n\
+             \x20   n1 --> n2\n"
+        );
+    }
+
+    #[test]
+    fn test_dot() {
+        assert_eq!(
+            schema_diagram(DiagramFormat::Dot, "users.metadata", &field_stats()),
+            "digraph schema {\n\
+             \x20   rankdir=LR;\n\
+             \x20   node [shape=box];\n\
+             \x20   n0 [label=\"users.metadata\"];\n\
+             \x20   n1 [label=\"user\\n100.0% object\"];\n\
+             \x20   n2 [label=\"na\\\"me\\n50.0% string\"];\n\
+             \x20   n0 -> n1;\n\
+             \x20   n1 -> n2;\n\
+             }\n"
+        );
+    }
+}
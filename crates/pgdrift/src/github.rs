@@ -0,0 +1,183 @@
+use crate::output::{AnalysisResult, ScanAllResult};
+use anyhow::{Context, Result};
+use pgdrift_core::drift::{DriftIssue, Severity};
+use pgdrift_core::report::IssueCounts;
+use std::io::Write;
+
+/// Environment variable GitHub Actions sets to the file of a step's job summary
+const STEP_SUMMARY: &str = "GITHUB_STEP_SUMMARY";
+
+/// Issue rows a comment lists before summing up the rest
+const COMMENT_ISSUES: usize = 50;
+
+/// Workflow commands annotating each issue of a column, as errors (critical),
+/// warnings and notices (info)
+pub fn annotations(location: &str, issues: &[DriftIssue]) -> Vec<String> {
+    issues
+        .iter()
+        .map(|issue| {
+            let command = match issue.severity() {
+                Severity::Critical => "error",
+                Severity::Warning => "warning",
+                Severity::Info => "notice",
+            };
+            format!(
+                "::{} title={}::{}: {}",
+                command,
+                escape_property(&format!("Schema drift in {}", location)),
+                escape_data(issue.path()),
+                escape_data(&issue.description())
+            )
+        })
+        .collect()
+}
+
+/// Markdown for a pull request comment on one analyzed column
+pub fn analysis_comment(result: &AnalysisResult) -> String {
+    let counts = IssueCounts::from_issues(&result.drift_issues);
+    let mut comment = format!(
+        "### Schema drift in `{}.{}`\n\n**Health score:** {}/100 | {} | {} samples\n",
+        result.table,
+        result.column,
+        result.health_score,
+        counts_label(&counts),
+        result.samples_analyzed
+    );
+    if !result.drift_issues.is_empty() {
+        comment.push_str("\n| Severity | Path | Issue |\n|----------|------|-------|\n");
+        let mut issues: Vec<&DriftIssue> = result.drift_issues.iter().collect();
+        issues.sort_by_key(|issue| std::cmp::Reverse(issue.severity()));
+        for issue in issues.iter().take(COMMENT_ISSUES) {
+            comment.push_str(&format!(
+                "| {:?} | `{}` | {} |\n",
+                issue.severity(),
+                issue.path(),
+                escape_cell(&issue.description())
+            ));
+        }
+        if issues.len() > COMMENT_ISSUES {
+            comment.push_str(&format!(
+                "\n...and {} more issues.\n",
+                issues.len() - COMMENT_ISSUES
+            ));
+        }
+    }
+    comment
+}
+
+/// Markdown for a pull request comment on a scan, listing the columns with
+/// issues, lowest health score first
+pub fn scan_all_comment(result: &ScanAllResult) -> String {
+    let counts =
+        IssueCounts::from_issues(result.column_results.iter().flat_map(|r| &r.drift_issues));
+    let mut comment = format!(
+        "### Schema drift scan\n\n**{} columns** | {}\n",
+        result.total_columns,
+        counts_label(&counts)
+    );
+    let mut drifted: Vec<_> = result
+        .column_results
+        .iter()
+        .filter(|r| !r.drift_issues.is_empty())
+        .collect();
+    drifted.sort_by_key(|r| r.health_score);
+    if !drifted.is_empty() {
+        comment.push_str(
+            "\n| Column | Score | Critical | Warning | Info |\n\
+             |--------|-------|----------|---------|------|\n",
+        );
+        for r in drifted {
+            let counts = IssueCounts::from_issues(&r.drift_issues);
+            comment.push_str(&format!(
+                "| `{}.{}.{}` | {} | {} | {} | {} |\n",
+                r.schema,
+                r.table,
+                r.column,
+                r.health_score.map_or("-".to_string(), |s| s.to_string()),
+                counts.critical,
+                counts.warning,
+                counts.info
+            ));
+        }
+    }
+    comment
+}
+
+/// Append markdown to the job summary of the running GitHub Actions step, if any
+pub fn append_step_summary(markdown: &str) -> Result<()> {
+    let Some(path) = std::env::var_os(STEP_SUMMARY) else {
+        return Ok(());
+    };
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("Failed to open the job summary {}", path.display()))?;
+    writeln!(file, "{}", markdown)
+        .with_context(|| format!("Failed to write the job summary {}", path.display()))
+}
+
+fn counts_label(counts: &IssueCounts) -> String {
+    format!(
+        "{} critical, {} warnings, {} info",
+        counts.critical, counts.warning, counts.info
+    )
+}
+
+/// Escape the message of a workflow command
+fn escape_data(text: &str) -> String {
+    text.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escape a property (e.g. `title`) of a workflow command
+fn escape_property(text: &str) -> String {
+    escape_data(text).replace(':', "%3A").replace(',', "%2C")
+}
+
+fn escape_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_annotations() {
+        let issues = vec![
+            DriftIssue::MissingKey {
+                path: "email".to_string(),
+                density: 0.85,
+                expected_occurrences: 100,
+                actual_occurrences: 85,
+                margin: 0.0,
+            },
+            DriftIssue::EmptyString {
+                path: "name".to_string(),
+                empty_count: 12,
+                null_count: 0,
+                missing_count: 0,
+                total_samples: 100,
+            },
+        ];
+
+        let lines = annotations("public.users.metadata", &issues);
+        assert_eq!(lines.len(), 2);
+        assert!(
+            lines[0].starts_with("::error title=Schema drift in public.users.metadata::email: "),
+            "{}",
+            lines[0]
+        );
+        assert!(lines[1].starts_with("::warning "), "{}", lines[1]);
+        assert!(!lines[0].contains('\n'));
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape_data("50% of\nrows"), "50%25 of%0Arows");
+        assert_eq!(escape_property("a: b, c"), "a%3A b%2C c");
+        assert_eq!(escape_cell("a | b"), "a \\| b");
+    }
+}
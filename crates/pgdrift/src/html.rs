@@ -0,0 +1,330 @@
+use crate::config::Suppression;
+use crate::output::{ScanAllResult, ScanAllView, score_label, suppression_rows};
+use pgdrift_core::drift::{DriftIssue, Severity};
+use pgdrift_core::report::IssueCounts;
+
+/// Styles of the HTML reports, inlined so a report is a single file
+const STYLE: &str = "\
+body { font-family: system-ui, sans-serif; margin: 2rem auto; max-width: 72rem; padding: 0 1rem; color: #1f2328; }
+table { border-collapse: collapse; margin: 0.5rem 0 1rem; }
+th, td { border: 1px solid #d0d7de; padding: 0.3rem 0.6rem; text-align: left; vertical-align: top; }
+th { background: #f6f8fa; }
+a { color: #0969da; }
+code { font-size: 0.9em; }
+.critical { color: #cf222e; font-weight: 600; }
+.warning { color: #9a6700; font-weight: 600; }
+.info { color: #0550ae; }
+";
+
+/// A scan as a standalone HTML page, listing the columns as the view says
+pub fn scan_all_page(result: &ScanAllResult, view: &ScanAllView) -> String {
+    let counts =
+        IssueCounts::from_issues(result.column_results.iter().flat_map(|r| &r.drift_issues));
+    let samples: u64 = result
+        .column_results
+        .iter()
+        .map(|r| r.samples_analyzed)
+        .sum();
+
+    let mut body = format!(
+        "<h1>Scan All Results</h1>\n<p><strong>Total columns scanned:</strong> {}</p>\n",
+        result.total_columns
+    );
+    body.push_str("<h2>Summary</h2>\n<ul>\n");
+    body.push_str(&format!("<li>Total samples analyzed: {}</li>\n", samples));
+    body.push_str(&format!(
+        "<li>Total issues found: {} ({} critical, {} warning, {} info)</li>\n",
+        counts.total(),
+        counts.critical,
+        counts.warning,
+        counts.info
+    ));
+    body.push_str(&format!(
+        "<li>Suppressed issues: {}</li>\n</ul>\n",
+        result.total_suppressed()
+    ));
+    if !view.summary_only {
+        if view.grouped {
+            body.push_str(&grouped_details(result));
+        } else {
+            body.push_str(&column_details(result, view));
+        }
+        body.push_str(&expiring_suppressions(&result.expiring_suppressions));
+    }
+    page("Scan All Results", &body)
+}
+
+/// One row per column, in the view's order
+fn column_details(result: &ScanAllResult, view: &ScanAllView) -> String {
+    let columns = result.sorted_columns(view);
+    let note = view.page.note(columns.len(), "columns");
+    let rows: Vec<Vec<String>> = view
+        .page
+        .apply(columns)
+        .into_iter()
+        .map(|col| {
+            let mut row = vec![
+                escape(&col.schema),
+                escape(&col.table),
+                escape(&col.column),
+                col.samples_analyzed.to_string(),
+            ];
+            row.extend(count_cells(&IssueCounts::from_issues(&col.drift_issues)));
+            row.push(col.drift_issues.len().to_string());
+            row.push(score_label(col.health_score));
+            row
+        })
+        .collect();
+
+    let mut html = String::from("<h2>Column Details</h2>\n");
+    html.push_str(&table(
+        &[
+            "Schema", "Table", "Column", "Samples", "Critical", "Warning", "Info", "Total", "Score",
+        ],
+        &rows,
+    ));
+    if let Some(note) = note {
+        html.push_str(&format!("<p><em>{}</em></p>\n", escape(&note)));
+    }
+    html
+}
+
+/// A roll-up of the tables linking to a section on each, which lists its
+/// columns and links to their issues
+fn grouped_details(result: &ScanAllResult) -> String {
+    let groups = result.table_groups();
+    let rows: Vec<Vec<String>> = groups
+        .iter()
+        .map(|group| {
+            let counts = group.counts();
+            let mut row = vec![
+                link(&group.name(), &group.anchor()),
+                group.columns.len().to_string(),
+                score_label(group.lowest_score()),
+            ];
+            row.extend(count_cells(&counts));
+            row.push(counts.total().to_string());
+            row
+        })
+        .collect();
+
+    let mut html = String::from("<h2 id=\"tables\">Tables</h2>\n");
+    html.push_str(&table(
+        &[
+            "Table",
+            "Columns",
+            "Lowest Score",
+            "Critical",
+            "Warning",
+            "Info",
+            "Total",
+        ],
+        &rows,
+    ));
+
+    for group in &groups {
+        html.push_str(&format!(
+            "<section>\n<h2 id=\"{}\">{}</h2>\n",
+            escape(&group.anchor()),
+            escape(&group.name())
+        ));
+        let rows: Vec<Vec<String>> = group
+            .columns
+            .iter()
+            .map(|col| {
+                let mut row = vec![
+                    if col.has_details() {
+                        link(&col.column, &col.anchor())
+                    } else {
+                        escape(&col.column)
+                    },
+                    col.samples_analyzed.to_string(),
+                ];
+                row.extend(count_cells(&IssueCounts::from_issues(&col.drift_issues)));
+                row.push(score_label(col.health_score));
+                row
+            })
+            .collect();
+        html.push_str(&table(
+            &["Column", "Samples", "Critical", "Warning", "Info", "Score"],
+            &rows,
+        ));
+
+        for col in group.columns.iter().filter(|c| c.has_details()) {
+            html.push_str(&format!(
+                "<h3 id=\"{}\">{}</h3>\n",
+                escape(&col.anchor()),
+                escape(&col.name())
+            ));
+            if col.health_score.is_none() {
+                html.push_str("<p><em>Not analyzed, see the errors of the run.</em></p>\n");
+            } else {
+                html.push_str(&issue_table(&col.drift_issues));
+            }
+            html.push_str(&format!(
+                "<p>{}</p>\n",
+                link(&format!("Back to {}", group.name()), &group.anchor())
+            ));
+        }
+        html.push_str("</section>\n");
+    }
+    html
+}
+
+fn issue_table(issues: &[DriftIssue]) -> String {
+    let rows: Vec<Vec<String>> = issues
+        .iter()
+        .map(|issue| {
+            vec![
+                format!("<code>{}</code>", escape(issue.path())),
+                severity_label(issue.severity()),
+                escape(&issue.description()),
+            ]
+        })
+        .collect();
+    table(&["Path", "Severity", "Issue"], &rows)
+}
+
+fn expiring_suppressions(suppressions: &[Suppression]) -> String {
+    if suppressions.is_empty() {
+        return String::new();
+    }
+    let rows: Vec<Vec<String>> = suppression_rows(suppressions)
+        .into_iter()
+        .map(|row| vec![escape(&row.path), escape(&row.until), escape(&row.reason)])
+        .collect();
+    format!(
+        "<h2>Expiring Suppressions</h2>\n{}",
+        table(&["Path", "Until", "Reason"], &rows)
+    )
+}
+
+/// Critical, warning and info counts, highlighted when not zero
+fn count_cells(counts: &IssueCounts) -> [String; 3] {
+    let cell = |count: usize, class: &str| {
+        if count == 0 {
+            "0".to_string()
+        } else {
+            format!("<span class=\"{}\">{}</span>", class, count)
+        }
+    };
+    [
+        cell(counts.critical, "critical"),
+        cell(counts.warning, "warning"),
+        cell(counts.info, "info"),
+    ]
+}
+
+fn severity_label(severity: Severity) -> String {
+    let class = match severity {
+        Severity::Critical => "critical",
+        Severity::Warning => "warning",
+        Severity::Info => "info",
+    };
+    format!("<span class=\"{}\">{:?}</span>", class, severity)
+}
+
+/// A table of cells that are HTML already
+fn table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut html = String::from("<table>\n<thead><tr>");
+    for header in headers {
+        html.push_str(&format!("<th>{}</th>", escape(header)));
+    }
+    html.push_str("</tr></thead>\n<tbody>\n");
+    for row in rows {
+        html.push_str("<tr>");
+        for cell in row {
+            html.push_str(&format!("<td>{}</td>", cell));
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</tbody>\n</table>\n");
+    html
+}
+
+fn link(text: &str, anchor: &str) -> String {
+    format!("<a href=\"#{}\">{}</a>", escape(anchor), escape(text))
+}
+
+fn page(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{}</title>\n<style>\n{}</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+        escape(title),
+        STYLE,
+        body
+    )
+}
+
+/// Escape text for HTML content and attribute values
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::ColumnScanResult;
+
+    fn column(table: &str, column: &str, drift_issues: Vec<DriftIssue>) -> ColumnScanResult {
+        ColumnScanResult {
+            schema: "public".to_string(),
+            table: table.to_string(),
+            column: column.to_string(),
+            samples_analyzed: 100,
+            drift_issues,
+            suppressed_issues: 0,
+            health_score: Some(90),
+        }
+    }
+
+    #[test]
+    fn test_grouped_page() {
+        let issue = DriftIssue::MissingKey {
+            path: "<email>".to_string(),
+            density: 0.85,
+            expected_occurrences: 100,
+            actual_occurrences: 85,
+            margin: 0.0,
+        };
+        let result = ScanAllResult {
+            total_columns: 3,
+            column_results: vec![
+                column("users", "settings", vec![]),
+                column("orders", "payload", vec![]),
+                column("users", "metadata", vec![issue]),
+            ],
+            expiring_suppressions: vec![],
+        };
+        let page = scan_all_page(
+            &result,
+            &ScanAllView {
+                grouped: true,
+                ..Default::default()
+            },
+        );
+
+        // Tables alphabetical, each linking to its section
+        let orders = page.find("<a href=\"#public-orders\">").unwrap();
+        let users = page.find("<a href=\"#public-users\">").unwrap();
+        assert!(orders < users);
+        assert!(page.contains("<h2 id=\"public-users\">public.users</h2>"));
+        // Only the column with issues gets a section
+        assert!(page.contains("<a href=\"#public-users-metadata\">metadata</a>"));
+        assert!(page.contains("<h3 id=\"public-users-metadata\">"));
+        assert!(!page.contains("id=\"public-users-settings\""));
+        assert!(page.contains("<code>&lt;email&gt;</code>"));
+        assert!(page.ends_with("</html>\n"));
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(
+            escape("<a href=\"x\">&</a>"),
+            "&lt;a href=&quot;x&quot;&gt;&amp;&lt;/a&gt;"
+        );
+    }
+}
@@ -1,4 +1,23 @@
 // Library interface for pgdrift (for testing and potential library use)
 
+/// `eprintln!` for progress and other informational messages, silenced by `--quiet`
+macro_rules! progress {
+    ($($arg:tt)*) => {
+        if !$crate::output::is_quiet() {
+            eprintln!($($arg)*);
+        }
+    };
+}
+
+pub mod baseline;
+pub mod checkpoint;
 pub mod commands;
+pub mod config;
+pub mod diagram;
+pub mod github;
+pub mod html;
+pub mod metrics;
+pub mod migration;
 pub mod output;
+pub mod watermark;
+pub mod xlsx;
@@ -1,7 +1,8 @@
+use clap::builder::PossibleValuesParser;
 use clap::{Parser, Subcommand};
-
-mod commands;
-mod output;
+use pgdrift::{commands, output};
+use pgdrift_core::drift::BUILTIN_DETECTORS;
+use pgdrift_core::redact::{Redaction, RedactionKey};
 
 #[derive(Parser)]
 #[command(
@@ -12,8 +13,22 @@ mod output;
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// Write the report to this file instead of stdout (progress and warnings stay on stderr)
+    #[arg(short, long, global = true, value_name = "PATH")]
+    output: Option<std::path::PathBuf>,
+
+    /// Don't print progress messages, only the report, warnings and errors
+    #[arg(short, long, global = true)]
+    quiet: bool,
+
+    /// Don't color the output (also set by the NO_COLOR environment variable)
+    #[arg(long, global = true)]
+    no_color: bool,
 }
 
+// Parsed once per run, so the size of the largest variant doesn't matter
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand)]
 enum Commands {
     /// List all jsonb columns in the database
@@ -25,6 +40,50 @@ enum Commands {
         /// Output format
         #[arg(short, long, value_enum, default_value = "table")]
         format: output::OutputFormat,
+
+        /// Also discover legacy `json` columns
+        #[arg(long)]
+        include_json: bool,
+
+        /// Also discover `hstore` columns (keys become top-level paths)
+        #[arg(long)]
+        include_hstore: bool,
+
+        /// Also discover `jsonb[]` array columns (each element is a document; `json[]` with --include-json)
+        #[arg(long)]
+        include_arrays: bool,
+
+        /// Only include columns matching these glob patterns, e.g. 'public.*'
+        #[arg(long, value_delimiter = ',')]
+        include: Vec<String>,
+
+        /// Skip columns matching these glob patterns, e.g. '*.audit_*'
+        #[arg(long, value_delimiter = ',')]
+        exclude: Vec<String>,
+
+        /// Sample each column to report document size and TOAST usage
+        #[arg(long)]
+        storage: bool,
+
+        /// Also list columns of views and materialized views
+        #[arg(long)]
+        include_views: bool,
+
+        /// List every partition separately instead of rolling them up into the parent
+        #[arg(long)]
+        expand_partitions: bool,
+
+        /// Skip tables with fewer estimated rows than this
+        #[arg(long, value_name = "N")]
+        min_rows: Option<i64>,
+
+        /// Sort columns by table name, estimated rows or storage size (largest first)
+        #[arg(long, value_enum, default_value = "table")]
+        sort: commands::discover::DiscoverSort,
+
+        /// Only show the first N columns after sorting
+        #[arg(long, value_name = "N")]
+        top: Option<usize>,
     },
 
     /// Analyze a jsonb column for schema drift
@@ -33,6 +92,10 @@ enum Commands {
         #[arg(short, long, env = "DATABASE_URL")]
         database_url: String,
 
+        /// Read replica URL to run sampling queries against
+        #[arg(long, env = "REPLICA_DATABASE_URL")]
+        replica_url: Option<String>,
+
         /// Table name
         // #[arg(short, long)]
         table: String,
@@ -48,6 +111,407 @@ enum Commands {
         /// Number of samples to analyze
         #[arg(short, long, default_value = "5000")]
         sample_size: usize,
+
+        /// Column used as a watermark for incremental runs (e.g. id or created_at)
+        #[arg(long)]
+        since_column: Option<String>,
+
+        /// Only sample rows where the watermark column is greater than this value
+        #[arg(long, requires = "since_column")]
+        since_value: Option<String>,
+
+        /// JSON file storing watermarks between runs (read before, updated after)
+        #[arg(long, requires = "since_column")]
+        watermark_file: Option<std::path::PathBuf>,
+
+        /// Sample the newest N rows (by primary key or --tail-column) instead of random rows
+        #[arg(long, value_name = "N")]
+        tail: Option<usize>,
+
+        /// Column ordering rows by recency for --tail (defaults to the primary key)
+        #[arg(long, requires = "tail")]
+        tail_column: Option<String>,
+
+        /// Only analyze documents containing this JSON, e.g. '{"type":"order"}'
+        #[arg(long, value_parser = parse_json_filter)]
+        filter: Option<serde_json::Value>,
+
+        /// Sample until path densities reach this confidence level (e.g. 0.95);
+        /// --sample-size becomes the upper bound
+        #[arg(long, conflicts_with = "tail")]
+        confidence: Option<f64>,
+
+        /// Margin of error for --confidence (default 0.02)
+        #[arg(long, requires = "confidence")]
+        margin: Option<f64>,
+
+        /// Report every key of map-like objects instead of collapsing them into `parent.*`
+        #[arg(long)]
+        expand_maps: bool,
+
+        /// Don't walk documents deeper than this nesting level (default 64)
+        #[arg(long, value_name = "N")]
+        max_depth: Option<usize>,
+
+        /// Stop tracking new paths after this many unique paths (default 10000)
+        #[arg(long, value_name = "N")]
+        max_paths: Option<usize>,
+
+        /// Stop tracking new paths once field statistics use about this much memory (default 512)
+        #[arg(long, value_name = "MB")]
+        memory_budget_mb: Option<usize>,
+
+        /// Example values kept per path, picked at random from the whole sample (default 10)
+        #[arg(long, value_name = "N")]
+        examples: Option<usize>,
+
+        /// Hide sampled values in the report
+        #[arg(long, value_name = "MODE", value_enum)]
+        redact_examples: Option<RedactMode>,
+
+        /// Secret key of --redact-examples hash; equal values only hash alike
+        /// under the same key
+        #[arg(
+            long,
+            value_name = "SECRET",
+            env = "PGDRIFT_REDACT_KEY",
+            hide_env_values = true
+        )]
+        redact_key: Option<String>,
+
+        /// Analyze samples on N threads (0 = one per CPU core)
+        #[arg(short, long, value_name = "N")]
+        jobs: Option<usize>,
+
+        /// Analyze each value of this JSON path separately, e.g. 'type' or 'meta.kind'
+        #[arg(long, value_name = "PATH", conflicts_with = "confidence")]
+        segment_by: Option<String>,
+
+        /// Analyze each value of this column of the table separately, e.g. 'tenant_id'
+        #[arg(
+            long,
+            value_name = "COLUMN",
+            conflicts_with_all = [
+                "segment_by",
+                "confidence",
+                "checkpoint",
+                "cohort_column",
+                "save_baseline",
+                "json_schema",
+                "remediation",
+                "remediation_file",
+                "denormalization",
+                "unused_fields"
+            ]
+        )]
+        group_by: Option<String>,
+
+        /// Report fields that always or never appear together, and implication rules
+        #[arg(long)]
+        cooccurrence: bool,
+
+        /// Where to analyze: fetch documents (client) or count paths and types inside Postgres (sql)
+        #[arg(
+            long,
+            value_enum,
+            default_value = "client",
+            conflicts_with_all = ["confidence", "segment_by", "cooccurrence", "denormalization", "group_by"]
+        )]
+        engine: commands::analyze::Engine,
+
+        /// Save progress to this file and resume from it if a previous run was interrupted
+        /// (full scans only: the sample size must cover the table)
+        #[arg(
+            long,
+            value_name = "PATH",
+            conflicts_with_all = ["segment_by", "cooccurrence", "tail", "confidence"]
+        )]
+        checkpoint: Option<std::path::PathBuf>,
+
+        /// Config file (default: .pgdrift.toml in the current directory, if present)
+        #[arg(long, value_name = "PATH")]
+        config: Option<std::path::PathBuf>,
+
+        /// Run only these built-in drift detectors (comma-separated)
+        #[arg(
+            long,
+            value_name = "NAMES",
+            value_delimiter = ',',
+            value_parser = PossibleValuesParser::new(BUILTIN_DETECTORS.iter().copied())
+        )]
+        detectors: Vec<String>,
+
+        /// Skip these built-in drift detectors (comma-separated)
+        #[arg(
+            long,
+            value_name = "NAMES",
+            value_delimiter = ',',
+            value_parser = PossibleValuesParser::new(BUILTIN_DETECTORS.iter().copied())
+        )]
+        disable_detectors: Vec<String>,
+
+        /// Timestamp column splitting rows into old and new cohorts that are compared
+        #[arg(
+            long,
+            value_name = "COLUMN",
+            requires = "cohort_split",
+            conflicts_with_all = [
+                "tail",
+                "confidence",
+                "segment_by",
+                "checkpoint",
+                "fail_below_score",
+                "fail_on",
+                "save_baseline"
+            ]
+        )]
+        cohort_column: Option<String>,
+
+        /// How long ago the cohorts are split, e.g. 12h, 30d or 2w
+        #[arg(
+            long,
+            value_name = "AGE",
+            requires = "cohort_column",
+            value_parser = commands::analyze::parse_cohort_split
+        )]
+        cohort_split: Option<String>,
+
+        /// Exit with an error if the health score (0-100) is below this, e.g. 80
+        #[arg(long, value_name = "SCORE", value_parser = clap::value_parser!(u8).range(0..=100))]
+        fail_below_score: Option<u8>,
+
+        /// Exit with an error if any issue is at least this severe (info, warning, critical)
+        #[arg(long, value_name = "SEVERITY", value_parser = commands::validate::parse_severity)]
+        fail_on: Option<pgdrift_core::drift::Severity>,
+
+        /// Write a compact JSON summary of the run (issue counts, score, thresholds, pass/fail) to this file
+        #[arg(long, value_name = "PATH", conflicts_with = "cohort_column")]
+        summary_file: Option<std::path::PathBuf>,
+
+        /// Export the column, field stats, issues and index recommendations to this Excel workbook
+        #[arg(long, value_name = "PATH", conflicts_with_all = ["segment_by", "group_by", "cohort_column"])]
+        xlsx: Option<std::path::PathBuf>,
+
+        /// Save field stats and issues to this file for `pgdrift diff`
+        #[arg(long, value_name = "PATH", conflicts_with = "segment_by")]
+        save_baseline: Option<std::path::PathBuf>,
+
+        /// Validate every sampled document against this JSON Schema (draft 2020-12)
+        #[arg(
+            long,
+            value_name = "PATH",
+            conflicts_with_all = ["segment_by", "checkpoint", "cohort_column"]
+        )]
+        json_schema: Option<std::path::PathBuf>,
+
+        /// Show SQL that normalizes type and boolean encoding drift to the dominant representation
+        #[arg(long, conflicts_with_all = ["segment_by", "cohort_column"])]
+        remediation: bool,
+
+        /// Write the remediation SQL to this file as a migration (implies --remediation)
+        #[arg(
+            long,
+            value_name = "PATH",
+            conflicts_with_all = ["segment_by", "cohort_column"]
+        )]
+        remediation_file: Option<std::path::PathBuf>,
+
+        /// Report JSON paths that duplicate other columns of the table, with mismatch rates
+        #[arg(
+            long,
+            conflicts_with_all = ["confidence", "segment_by", "checkpoint", "cohort_column"]
+        )]
+        denormalization: bool,
+
+        /// Report JSON paths no recorded query refers to (needs pg_stat_statements)
+        #[arg(long, conflicts_with_all = ["segment_by", "cohort_column"])]
+        unused_fields: bool,
+
+        /// Write Prometheus metrics of the run to this file, e.g. for node_exporter's textfile collector
+        #[arg(
+            long,
+            value_name = "PATH",
+            conflicts_with_all = ["segment_by", "group_by", "cohort_column"]
+        )]
+        metrics_file: Option<std::path::PathBuf>,
+
+        /// Push Prometheus metrics of the run to this Pushgateway, e.g. http://localhost:9091
+        #[arg(
+            long,
+            value_name = "URL",
+            conflicts_with_all = ["segment_by", "group_by", "cohort_column"]
+        )]
+        pushgateway: Option<String>,
+
+        /// List the density, types, depth and examples of each path (table and markdown formats)
+        #[arg(long, conflicts_with = "cohort_column")]
+        field_stats: bool,
+
+        /// Only list paths matching this glob pattern in the field stats, e.g. 'address.*'
+        #[arg(long, value_name = "GLOB", requires = "field_stats")]
+        paths: Option<String>,
+
+        /// Order of the paths in the field stats
+        #[arg(long, value_enum, default_value = "path", requires = "field_stats")]
+        sort: output::FieldSort,
+
+        /// List at most N paths in the field stats
+        #[arg(long, value_name = "N", requires = "field_stats")]
+        limit: Option<usize>,
+
+        /// Skip the first N paths of the field stats, e.g. to page through them with --limit
+        #[arg(long, value_name = "N", default_value = "0", requires = "field_stats")]
+        offset: usize,
+
+        /// Draw the path hierarchy as a tree with the density and types of each path (table and markdown formats)
+        #[arg(long, conflicts_with = "cohort_column")]
+        tree: bool,
+
+        /// Print the path hierarchy as a Mermaid or Graphviz DOT graph instead of the report
+        #[arg(
+            long,
+            value_enum,
+            value_name = "FORMAT",
+            conflicts_with_all = ["segment_by", "group_by", "cohort_column"]
+        )]
+        diagram: Option<pgdrift::diagram::DiagramFormat>,
+
+        /// Render the report through this Tera template instead of --format
+        #[arg(
+            long,
+            value_name = "PATH",
+            conflicts_with_all = ["segment_by", "group_by", "cohort_column", "diagram"]
+        )]
+        template: Option<std::path::PathBuf>,
+
+        /// Only print the health score and issue counts (table and markdown formats)
+        #[arg(long, conflicts_with_all = ["field_stats", "tree", "diagram", "template"])]
+        summary: bool,
+
+        /// List N example values of each path with issues (default 3), after --redact-examples
+        #[arg(
+            long,
+            value_name = "N",
+            num_args = 0..=1,
+            default_missing_value = "3",
+            conflicts_with_all = ["cohort_column", "summary"]
+        )]
+        show_examples: Option<usize>,
+
+        /// Sample the same rows on every run while the table doesn't change, so reports only differ when the data does (random sampling gets slower)
+        #[arg(long)]
+        stable: bool,
+    },
+
+    /// Compare a column against a baseline saved with `analyze --save-baseline`
+    Diff {
+        /// DB connection URL
+        #[arg(short, long, env = "DATABASE_URL")]
+        database_url: String,
+
+        /// Read replica URL to run sampling queries against
+        #[arg(long, env = "REPLICA_DATABASE_URL")]
+        replica_url: Option<String>,
+
+        /// Baseline file; the column it was taken of is analyzed again
+        baseline: std::path::PathBuf,
+
+        /// Output format
+        #[arg(short = 'f', long, value_enum, default_value = "table")]
+        format: output::OutputFormat,
+
+        /// Number of samples to analyze
+        #[arg(short, long, default_value = "5000")]
+        sample_size: usize,
+
+        /// Only analyze documents containing this JSON, e.g. '{"type":"order"}'
+        #[arg(long, value_parser = parse_json_filter)]
+        filter: Option<serde_json::Value>,
+
+        /// Config file (default: .pgdrift.toml in the current directory, if present)
+        #[arg(long, value_name = "PATH")]
+        config: Option<std::path::PathBuf>,
+
+        /// Exit with an error if any issue is new or more severe than in the baseline
+        #[arg(long)]
+        fail_on_regression: bool,
+    },
+
+    /// Compare the structure of a jsonb column in two databases (e.g. staging and prod)
+    Compare {
+        /// Connection URL of the first database
+        left_url: String,
+
+        /// Connection URL of the second database
+        right_url: String,
+
+        /// Table name
+        table: String,
+
+        /// Column name
+        column: String,
+
+        /// Output format
+        #[arg(short = 'f', long, value_enum, default_value = "table")]
+        format: output::OutputFormat,
+
+        /// Number of samples to analyze in each database
+        #[arg(short, long, default_value = "5000")]
+        sample_size: usize,
+
+        /// Name for the first database in the report (default: its host and database)
+        #[arg(long, value_name = "NAME")]
+        left_label: Option<String>,
+
+        /// Name for the second database in the report (default: its host and database)
+        #[arg(long, value_name = "NAME")]
+        right_label: Option<String>,
+
+        /// Only analyze documents containing this JSON, e.g. '{"type":"order"}'
+        #[arg(long, value_parser = parse_json_filter)]
+        filter: Option<serde_json::Value>,
+    },
+
+    /// Check a jsonb column against an expected-schema spec
+    Validate {
+        /// DB connection URL
+        #[arg(short, long, env = "DATABASE_URL")]
+        database_url: String,
+
+        /// Read replica URL to run sampling queries against
+        #[arg(long, env = "REPLICA_DATABASE_URL")]
+        replica_url: Option<String>,
+
+        /// Table name
+        table: String,
+
+        /// Column name
+        column: String,
+
+        /// Spec file (TOML) listing required paths, types, allowed values and null limits
+        #[arg(long, value_name = "PATH")]
+        spec: std::path::PathBuf,
+
+        /// Output format
+        #[arg(short = 'f', long, value_enum, default_value = "table")]
+        format: output::OutputFormat,
+
+        /// Number of samples to analyze
+        #[arg(short, long, default_value = "5000")]
+        sample_size: usize,
+
+        /// Only analyze documents containing this JSON, e.g. '{"type":"order"}'
+        #[arg(long, value_parser = parse_json_filter)]
+        filter: Option<serde_json::Value>,
+
+        /// Exit with an error if any violation is at least this severe (info, warning, critical)
+        #[arg(
+            long,
+            value_name = "SEVERITY",
+            default_value = "warning",
+            value_parser = commands::validate::parse_severity
+        )]
+        fail_on: pgdrift_core::drift::Severity,
     },
 
     /// Generate index recommendations for a jsonb column
@@ -56,6 +520,10 @@ enum Commands {
         #[arg(short, long, env = "DATABASE_URL")]
         database_url: String,
 
+        /// Read replica URL to run sampling queries against
+        #[arg(long, env = "REPLICA_DATABASE_URL")]
+        replica_url: Option<String>,
+
         /// Table name
         table: String,
 
@@ -69,6 +537,121 @@ enum Commands {
         /// Number of samples to analyze
         #[arg(short, long, default_value = "5000")]
         sample_size: usize,
+
+        /// Only analyze documents containing this JSON, e.g. '{"type":"order"}'
+        #[arg(long, value_parser = parse_json_filter)]
+        filter: Option<serde_json::Value>,
+
+        /// The column is only queried by containment (@>): recommend smaller
+        /// jsonb_path_ops GIN indexes, which can't serve key-existence (?) queries
+        #[arg(long)]
+        containment_only: bool,
+
+        /// Check each recommendation with a hypothetical index and EXPLAIN,
+        /// reporting whether the planner would use it (needs the hypopg extension)
+        #[arg(long)]
+        hypothetical: bool,
+
+        /// Build each recommended index in a transaction that is rolled back and
+        /// time a query it serves with EXPLAIN ANALYZE before and after. Writes
+        /// to the table wait while each index builds: not for production
+        #[arg(long)]
+        explain_analyze: bool,
+
+        /// Prioritize recommendations by how pg_stat_statements says the column
+        /// is queried (needs the pg_stat_statements extension)
+        #[arg(long)]
+        workload: bool,
+
+        /// Paths searched with LIKE/ILIKE, e.g. 'profile.bio': recommend trigram
+        /// GIN indexes for them (needs the pg_trgm extension)
+        #[arg(long, value_delimiter = ',')]
+        text_search: Vec<String>,
+
+        /// Recommend a stored generated column with a plain index, rather than
+        /// an expression index, for extracted paths busy queries filter on
+        /// (needs --workload)
+        #[arg(long)]
+        generated_columns: bool,
+
+        /// Density (share of documents with the path) from which a path gets a
+        /// B-tree or is covered by a GIN index (default 0.8)
+        #[arg(long, value_name = "DENSITY", value_parser = parse_fraction)]
+        high_density_threshold: Option<f64>,
+
+        /// Density up to which a path is sparse enough for a partial index
+        /// (default 0.2)
+        #[arg(long, value_name = "DENSITY", value_parser = parse_fraction)]
+        medium_density_threshold: Option<f64>,
+
+        /// Sampled documents a path must appear in to get a recommendation
+        /// (default 100)
+        #[arg(long, value_name = "N")]
+        min_occurrences: Option<u64>,
+
+        /// Plain columns queries filter on along with the JSON paths, e.g.
+        /// 'tenant_id': extracted B-trees are recommended as composite indexes
+        /// led by them
+        #[arg(long = "with-column", value_name = "COLUMN", value_delimiter = ',')]
+        with_columns: Vec<String>,
+
+        /// Build the recommended indexes with CREATE INDEX CONCURRENTLY, after
+        /// confirmation
+        #[arg(long)]
+        apply: bool,
+
+        /// Build without asking for confirmation (with --apply)
+        #[arg(short, long)]
+        yes: bool,
+
+        /// How long each build may wait for a lock before it fails, e.g. '10s'
+        /// (with --apply, --explain-analyze or --emit-migration, default 5s)
+        #[arg(long)]
+        lock_timeout: Option<String>,
+
+        /// Numbers of the recommendations to build, as listed under SQL
+        /// Commands, e.g. '1,3' (with --apply, default all)
+        #[arg(long, value_delimiter = ',')]
+        select: Vec<usize>,
+
+        /// Write the recommended statements, most urgent first, to this file
+        /// as one migration script building the indexes concurrently
+        #[arg(long, value_name = "FILE")]
+        emit_migration: Option<std::path::PathBuf>,
+
+        /// statement_timeout set by the migration script, e.g. '30min'
+        /// (with --emit-migration, default 0 for no limit)
+        #[arg(long)]
+        statement_timeout: Option<String>,
+    },
+
+    /// Recommend moving stable scalar paths of a jsonb column into real columns
+    Promote {
+        /// DB connection URL
+        #[arg(short, long, env = "DATABASE_URL")]
+        database_url: String,
+
+        /// Read replica URL to run sampling queries against
+        #[arg(long, env = "REPLICA_DATABASE_URL")]
+        replica_url: Option<String>,
+
+        /// Table name
+        table: String,
+
+        /// Column name
+        column: String,
+
+        /// Output format
+        #[arg(short = 'f', long, value_enum, default_value = "table")]
+        format: output::OutputFormat,
+
+        /// Number of samples to analyze
+        #[arg(short, long, default_value = "5000")]
+        sample_size: usize,
+
+        /// Only analyze documents containing this JSON, e.g. '{"type":"order"}'
+        #[arg(long, value_parser = parse_json_filter)]
+        filter: Option<serde_json::Value>,
     },
 
     /// Scan all jsonb columns in the database for drift
@@ -77,6 +660,174 @@ enum Commands {
         #[arg(short, long, env = "DATABASE_URL")]
         database_url: String,
 
+        /// Read replica URL to run sampling queries against
+        #[arg(long, env = "REPLICA_DATABASE_URL")]
+        replica_url: Option<String>,
+
+        /// Output format
+        #[arg(short = 'f', long, value_enum, default_value = "table")]
+        format: output::OutputFormat,
+
+        /// Number of samples to analyze per column
+        #[arg(short, long, default_value = "5000")]
+        sample_size: usize,
+
+        /// Column used as a watermark for incremental runs (e.g. id or created_at)
+        #[arg(long)]
+        since_column: Option<String>,
+
+        /// Only sample rows where the watermark column is greater than this value
+        #[arg(long, requires = "since_column")]
+        since_value: Option<String>,
+
+        /// JSON file storing watermarks between runs (read before, updated after)
+        #[arg(long, requires = "since_column")]
+        watermark_file: Option<std::path::PathBuf>,
+
+        /// Sample the newest N rows (by primary key or --tail-column) instead of random rows
+        #[arg(long, value_name = "N")]
+        tail: Option<usize>,
+
+        /// Column ordering rows by recency for --tail (defaults to the primary key)
+        #[arg(long, requires = "tail")]
+        tail_column: Option<String>,
+
+        /// Only analyze documents containing this JSON, e.g. '{"type":"order"}'
+        #[arg(long, value_parser = parse_json_filter)]
+        filter: Option<serde_json::Value>,
+
+        /// Also scan legacy `json` columns (cast to jsonb while sampling)
+        #[arg(long)]
+        include_json: bool,
+
+        /// Also scan `hstore` columns (keys become top-level paths)
+        #[arg(long)]
+        include_hstore: bool,
+
+        /// Also scan `jsonb[]` array columns (each element is a document; `json[]` with --include-json)
+        #[arg(long)]
+        include_arrays: bool,
+
+        /// Only include columns matching these glob patterns, e.g. 'public.*'
+        #[arg(long, value_delimiter = ',')]
+        include: Vec<String>,
+
+        /// Skip columns matching these glob patterns, e.g. '*.audit_*'
+        #[arg(long, value_delimiter = ',')]
+        exclude: Vec<String>,
+
+        /// Also scan columns of views and materialized views
+        #[arg(long)]
+        include_views: bool,
+
+        /// Skip tables with fewer estimated rows than this
+        #[arg(long, value_name = "N")]
+        min_rows: Option<i64>,
+
+        /// Hide sampled values in the reported issues
+        #[arg(long, value_name = "MODE", value_enum)]
+        redact_examples: Option<RedactMode>,
+
+        /// Secret key of --redact-examples hash; equal values only hash alike
+        /// under the same key
+        #[arg(
+            long,
+            value_name = "SECRET",
+            env = "PGDRIFT_REDACT_KEY",
+            hide_env_values = true
+        )]
+        redact_key: Option<String>,
+
+        /// Config file (default: .pgdrift.toml in the current directory, if present)
+        #[arg(long, value_name = "PATH")]
+        config: Option<std::path::PathBuf>,
+
+        /// Run only these built-in drift detectors (comma-separated)
+        #[arg(
+            long,
+            value_name = "NAMES",
+            value_delimiter = ',',
+            value_parser = PossibleValuesParser::new(BUILTIN_DETECTORS.iter().copied())
+        )]
+        detectors: Vec<String>,
+
+        /// Skip these built-in drift detectors (comma-separated)
+        #[arg(
+            long,
+            value_name = "NAMES",
+            value_delimiter = ',',
+            value_parser = PossibleValuesParser::new(BUILTIN_DETECTORS.iter().copied())
+        )]
+        disable_detectors: Vec<String>,
+
+        /// Exit with an error if any column's health score (0-100) is below this, e.g. 80
+        #[arg(long, value_name = "SCORE", value_parser = clap::value_parser!(u8).range(0..=100))]
+        fail_below_score: Option<u8>,
+
+        /// Exit with an error if any column has an issue at least this severe (info, warning, critical)
+        #[arg(long, value_name = "SEVERITY", value_parser = commands::validate::parse_severity)]
+        fail_on: Option<pgdrift_core::drift::Severity>,
+
+        /// Write a compact JSON summary of the run (issue counts, lowest score, thresholds, pass/fail) to this file
+        #[arg(long, value_name = "PATH")]
+        summary_file: Option<std::path::PathBuf>,
+
+        /// Export the columns, their field stats, issues and index recommendations to this Excel workbook
+        #[arg(long, value_name = "PATH")]
+        xlsx: Option<std::path::PathBuf>,
+
+        /// Write Prometheus metrics of the run to this file, e.g. for node_exporter's textfile collector
+        #[arg(long, value_name = "PATH")]
+        metrics_file: Option<std::path::PathBuf>,
+
+        /// Push Prometheus metrics of the run to this Pushgateway, e.g. http://localhost:9091
+        #[arg(long, value_name = "URL")]
+        pushgateway: Option<String>,
+
+        /// Render the summary through this Tera template instead of --format
+        #[arg(long, value_name = "PATH")]
+        template: Option<std::path::PathBuf>,
+
+        /// Only print the totals, not the details of each column (table, markdown and html formats)
+        #[arg(long, conflicts_with = "template")]
+        summary: bool,
+
+        /// Group the column details by table, with a roll-up per table and links to each column's issues (markdown and html formats)
+        #[arg(long, conflicts_with_all = ["summary", "template", "sort", "limit", "offset"])]
+        grouped: bool,
+
+        /// Order of the column details (default: the order they were scanned in)
+        #[arg(long, value_enum, conflicts_with_all = ["summary", "template"])]
+        sort: Option<output::ColumnSort>,
+
+        /// List at most N columns in the column details
+        #[arg(long, value_name = "N", conflicts_with_all = ["summary", "template"])]
+        limit: Option<usize>,
+
+        /// Skip the first N columns of the column details, e.g. to page through them with --limit
+        #[arg(
+            long,
+            value_name = "N",
+            default_value = "0",
+            conflicts_with_all = ["summary", "template"]
+        )]
+        offset: usize,
+
+        /// Sample the same rows on every run while the tables don't change, so reports only differ when the data does (random sampling gets slower)
+        #[arg(long)]
+        stable: bool,
+    },
+
+    /// Recommend indexes for all jsonb columns in the database, in one report
+    IndexAll {
+        /// DB connection URL
+        #[arg(short, long, env = "DATABASE_URL")]
+        database_url: String,
+
+        /// Read replica URL to run sampling queries against
+        #[arg(long, env = "REPLICA_DATABASE_URL")]
+        replica_url: Option<String>,
+
         /// Output format
         #[arg(short = 'f', long, value_enum, default_value = "table")]
         format: output::OutputFormat,
@@ -84,44 +835,569 @@ enum Commands {
         /// Number of samples to analyze per column
         #[arg(short, long, default_value = "5000")]
         sample_size: usize,
+
+        /// Only analyze documents containing this JSON, e.g. '{"type":"order"}'
+        #[arg(long, value_parser = parse_json_filter)]
+        filter: Option<serde_json::Value>,
+
+        /// The columns are only queried by containment (@>): recommend smaller
+        /// jsonb_path_ops GIN indexes, which can't serve key-existence (?) queries
+        #[arg(long)]
+        containment_only: bool,
+
+        /// Check each recommendation with a hypothetical index and EXPLAIN,
+        /// reporting whether the planner would use it (needs the hypopg extension)
+        #[arg(long)]
+        hypothetical: bool,
+
+        /// Build each recommended index in a transaction that is rolled back and
+        /// time a query it serves with EXPLAIN ANALYZE before and after. Writes
+        /// to the table wait while each index builds: not for production
+        #[arg(long)]
+        explain_analyze: bool,
+
+        /// Prioritize recommendations by how pg_stat_statements says each column
+        /// is queried (needs the pg_stat_statements extension)
+        #[arg(long)]
+        workload: bool,
+
+        /// Recommend a stored generated column with a plain index, rather than
+        /// an expression index, for extracted paths busy queries filter on
+        /// (needs --workload)
+        #[arg(long)]
+        generated_columns: bool,
+
+        /// Density (share of documents with the path) from which a path gets a
+        /// B-tree or is covered by a GIN index (default 0.8)
+        #[arg(long, value_name = "DENSITY", value_parser = parse_fraction)]
+        high_density_threshold: Option<f64>,
+
+        /// Density up to which a path is sparse enough for a partial index
+        /// (default 0.2)
+        #[arg(long, value_name = "DENSITY", value_parser = parse_fraction)]
+        medium_density_threshold: Option<f64>,
+
+        /// Sampled documents a path must appear in to get a recommendation
+        /// (default 100)
+        #[arg(long, value_name = "N")]
+        min_occurrences: Option<u64>,
+
+        /// Only include columns matching these glob patterns, e.g. 'public.*'
+        #[arg(long, value_delimiter = ',')]
+        include: Vec<String>,
+
+        /// Skip columns matching these glob patterns, e.g. '*.audit_*'
+        #[arg(long, value_delimiter = ',')]
+        exclude: Vec<String>,
+
+        /// Skip tables with fewer estimated rows than this
+        #[arg(long, value_name = "N")]
+        min_rows: Option<i64>,
+
+        /// Write all recommended statements, most urgent first, to this file
+        /// as one migration script building the indexes concurrently
+        #[arg(long, value_name = "FILE")]
+        emit_migration: Option<std::path::PathBuf>,
+
+        /// How long each statement of the migration script, or each build of
+        /// --explain-analyze, may wait for a lock before it fails, e.g. '10s'
+        /// (default 5s)
+        #[arg(long)]
+        lock_timeout: Option<String>,
+
+        /// statement_timeout set by the migration script, e.g. '30min'
+        /// (with --emit-migration, default 0 for no limit)
+        #[arg(long)]
+        statement_timeout: Option<String>,
     },
 }
 
+/// Parse a share between 0 and 1, e.g. a density threshold
+fn parse_fraction(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(value) if (0.0..=1.0).contains(&value) => Ok(value),
+        Ok(_) => Err("must be between 0 and 1".to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// How --redact-examples hides sampled values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum RedactMode {
+    /// Keep the shape: letters become x, digits become 9
+    Mask,
+    /// Replace strings with a keyed hash, see --redact-key
+    Hash,
+    /// Remove example values entirely
+    Drop,
+}
+
+/// Combine --redact-examples with the key hashing needs
+fn redaction(mode: Option<RedactMode>, key: Option<String>) -> anyhow::Result<Option<Redaction>> {
+    Ok(match mode {
+        None => None,
+        Some(RedactMode::Mask) => Some(Redaction::Mask),
+        Some(RedactMode::Drop) => Some(Redaction::Drop),
+        Some(RedactMode::Hash) => {
+            let Some(key) = key.filter(|key| !key.is_empty()) else {
+                anyhow::bail!(
+                    "--redact-examples hash needs a secret, pass --redact-key or set PGDRIFT_REDACT_KEY"
+                );
+            };
+            Some(Redaction::Hash(RedactionKey::new(key.as_bytes())))
+        }
+    })
+}
+
+/// Parse a --filter argument as a JSON document for `@>` containment
+fn parse_json_filter(s: &str) -> Result<serde_json::Value, String> {
+    serde_json::from_str(s).map_err(|e| format!("invalid JSON filter: {}", e))
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
 
-    match cli.command {
+    output::set_quiet(cli.quiet);
+    output::init_colors(cli.no_color);
+    if let Some(path) = &cli.output {
+        output::report_to_file(path)?;
+    }
+    // Keep the part of the report written before a failure
+    let result = run(cli.command).await;
+    output::close_report_file()?;
+    result
+}
+
+async fn run(command: Commands) -> anyhow::Result<()> {
+    if let Commands::Discover { format, .. }
+    | Commands::Analyze { format, .. }
+    | Commands::Diff { format, .. }
+    | Commands::Compare { format, .. }
+    | Commands::Validate { format, .. }
+    | Commands::Index { format, .. }
+    | Commands::Promote { format, .. }
+    | Commands::IndexAll { format, .. } = &command
+        && matches!(format, output::OutputFormat::Html)
+    {
+        anyhow::bail!("--format html is only supported by scan-all");
+    }
+    match command {
         Commands::Discover {
             database_url,
             format,
+            include_json,
+            include_hstore,
+            include_arrays,
+            include,
+            exclude,
+            storage,
+            include_views,
+            expand_partitions,
+            min_rows,
+            sort,
+            top,
         } => {
-            commands::discover::run(&database_url, format).await?;
+            let options = commands::discover::DiscoverOptions {
+                include_json,
+                include_hstore,
+                include_arrays,
+                include,
+                exclude,
+                storage,
+                include_views,
+                expand_partitions,
+                min_rows,
+                sort,
+                top,
+            };
+            commands::discover::run_with_options(&database_url, format, &options).await?;
         }
         Commands::Analyze {
             database_url,
+            replica_url,
             table,
             column,
             sample_size,
             format,
+            since_column,
+            since_value,
+            watermark_file,
+            tail,
+            tail_column,
+            filter,
+            confidence,
+            margin,
+            expand_maps,
+            max_depth,
+            max_paths,
+            memory_budget_mb,
+            examples,
+            redact_examples,
+            redact_key,
+            jobs,
+            segment_by,
+            group_by,
+            cooccurrence,
+            engine,
+            checkpoint,
+            config,
+            detectors,
+            disable_detectors,
+            cohort_column,
+            cohort_split,
+            fail_below_score,
+            fail_on,
+            summary_file,
+            save_baseline,
+            xlsx,
+            json_schema,
+            remediation,
+            remediation_file,
+            denormalization,
+            unused_fields,
+            metrics_file,
+            pushgateway,
+            field_stats,
+            paths,
+            sort,
+            limit,
+            offset,
+            tree,
+            diagram,
+            template,
+            summary,
+            show_examples,
+            stable,
         } => {
-            commands::analyze::run(&database_url, &table, &column, sample_size, format).await?;
+            let options = commands::analyze::AnalyzeOptions {
+                replica_url,
+                since_column,
+                since_value,
+                watermark_file,
+                tail,
+                tail_column,
+                filter,
+                confidence,
+                margin,
+                expand_maps,
+                max_depth,
+                max_paths,
+                memory_budget_mb,
+                examples,
+                redact_examples: redaction(redact_examples, redact_key)?,
+                jobs,
+                segment_by,
+                group_by,
+                cooccurrence,
+                engine,
+                checkpoint,
+                config,
+                detectors,
+                disable_detectors,
+                cohort_column,
+                cohort_split,
+                fail_below_score,
+                fail_on,
+                summary_file,
+                save_baseline,
+                xlsx,
+                json_schema,
+                remediation,
+                remediation_file,
+                denormalization,
+                unused_fields,
+                metrics_file,
+                pushgateway,
+                view: output::AnalysisView {
+                    field_stats: field_stats.then_some(output::FieldStatsView {
+                        paths,
+                        sort,
+                        page: output::Page { offset, limit },
+                    }),
+                    tree,
+                    summary_only: summary,
+                    examples: show_examples,
+                },
+                diagram,
+                template,
+                stable,
+            };
+            commands::analyze::run_with_options(
+                &database_url,
+                &table,
+                &column,
+                sample_size,
+                format,
+                &options,
+            )
+            .await?;
+        }
+        Commands::Diff {
+            database_url,
+            replica_url,
+            baseline,
+            format,
+            sample_size,
+            filter,
+            config,
+            fail_on_regression,
+        } => {
+            let options = commands::diff::DiffOptions {
+                replica_url,
+                filter,
+                config,
+                fail_on_regression,
+            };
+            commands::diff::run(&database_url, &baseline, sample_size, format, &options).await?;
+        }
+        Commands::Compare {
+            left_url,
+            right_url,
+            table,
+            column,
+            format,
+            sample_size,
+            left_label,
+            right_label,
+            filter,
+        } => {
+            let options = commands::compare::CompareOptions {
+                left_label,
+                right_label,
+                filter,
+            };
+            commands::compare::run(
+                &left_url,
+                &right_url,
+                &table,
+                &column,
+                sample_size,
+                format,
+                &options,
+            )
+            .await?;
+        }
+        Commands::Validate {
+            database_url,
+            replica_url,
+            table,
+            column,
+            spec,
+            format,
+            sample_size,
+            filter,
+            fail_on,
+        } => {
+            let options = commands::validate::ValidateOptions {
+                replica_url,
+                filter,
+                fail_on,
+            };
+            commands::validate::run(
+                &database_url,
+                &table,
+                &column,
+                &spec,
+                sample_size,
+                format,
+                &options,
+            )
+            .await?;
         }
         Commands::Index {
             database_url,
+            replica_url,
+            table,
+            column,
+            sample_size,
+            format,
+            filter,
+            containment_only,
+            hypothetical,
+            explain_analyze,
+            workload,
+            text_search,
+            generated_columns,
+            high_density_threshold,
+            medium_density_threshold,
+            min_occurrences,
+            with_columns,
+            apply,
+            yes,
+            lock_timeout,
+            select,
+            emit_migration,
+            statement_timeout,
+        } => {
+            let options = commands::index::IndexOptions {
+                replica_url,
+                filter,
+                containment_only,
+                hypothetical,
+                explain_analyze,
+                workload,
+                text_search,
+                generated_columns,
+                high_density_threshold,
+                medium_density_threshold,
+                min_occurrences,
+                with_columns,
+                apply,
+                yes,
+                lock_timeout,
+                select,
+                emit_migration,
+                statement_timeout,
+            };
+            commands::index::run_with_options(
+                &database_url,
+                &table,
+                &column,
+                sample_size,
+                format,
+                &options,
+            )
+            .await?;
+        }
+        Commands::Promote {
+            database_url,
+            replica_url,
             table,
             column,
             sample_size,
             format,
+            filter,
         } => {
-            commands::index::run(&database_url, &table, &column, sample_size, format).await?;
+            let options = commands::promote::PromoteOptions {
+                replica_url,
+                filter,
+            };
+            commands::promote::run(
+                &database_url,
+                &table,
+                &column,
+                sample_size,
+                format,
+                &options,
+            )
+            .await?;
         }
         Commands::ScanAll {
             database_url,
+            replica_url,
+            sample_size,
+            format,
+            since_column,
+            since_value,
+            watermark_file,
+            tail,
+            tail_column,
+            filter,
+            include_json,
+            include_hstore,
+            include_arrays,
+            include,
+            exclude,
+            include_views,
+            min_rows,
+            redact_examples,
+            redact_key,
+            config,
+            detectors,
+            disable_detectors,
+            fail_below_score,
+            fail_on,
+            summary_file,
+            xlsx,
+            metrics_file,
+            pushgateway,
+            template,
+            summary,
+            grouped,
+            sort,
+            limit,
+            offset,
+            stable,
+        } => {
+            let options = commands::scan_all::ScanAllOptions {
+                replica_url,
+                since_column,
+                since_value,
+                watermark_file,
+                tail,
+                tail_column,
+                filter,
+                include_json,
+                include_hstore,
+                include_arrays,
+                include,
+                exclude,
+                include_views,
+                min_rows,
+                redact_examples: redaction(redact_examples, redact_key)?,
+                config,
+                detectors,
+                disable_detectors,
+                fail_below_score,
+                fail_on,
+                summary_file,
+                xlsx,
+                metrics_file,
+                pushgateway,
+                template,
+                view: output::ScanAllView {
+                    summary_only: summary,
+                    grouped,
+                    sort,
+                    page: output::Page { offset, limit },
+                },
+                stable,
+            };
+            commands::scan_all::run_with_options(&database_url, sample_size, format, &options)
+                .await?;
+        }
+        Commands::IndexAll {
+            database_url,
+            replica_url,
             sample_size,
             format,
+            filter,
+            containment_only,
+            hypothetical,
+            explain_analyze,
+            workload,
+            generated_columns,
+            high_density_threshold,
+            medium_density_threshold,
+            min_occurrences,
+            include,
+            exclude,
+            min_rows,
+            emit_migration,
+            lock_timeout,
+            statement_timeout,
         } => {
-            commands::scan_all::run(&database_url, sample_size, format).await?;
+            let options = commands::index_all::IndexAllOptions {
+                replica_url,
+                filter,
+                containment_only,
+                hypothetical,
+                explain_analyze,
+                workload,
+                generated_columns,
+                high_density_threshold,
+                medium_density_threshold,
+                min_occurrences,
+                include,
+                exclude,
+                min_rows,
+                emit_migration,
+                lock_timeout,
+                statement_timeout,
+            };
+            commands::index_all::run_with_options(&database_url, sample_size, format, &options)
+                .await?;
         }
     }
     Ok(())
@@ -0,0 +1,280 @@
+use anyhow::{Context, Result};
+use pgdrift_core::drift::{DriftIssue, Severity};
+use std::path::Path;
+
+/// Content type of the Prometheus text exposition format
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// Job the metrics are pushed to the Pushgateway under
+const JOB: &str = "pgdrift";
+
+/// Drift metrics of one analyzed column
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnMetrics {
+    pub schema: String,
+    pub table: String,
+    pub column: String,
+    pub samples_analyzed: u64,
+    /// Reported issues, per severity
+    pub critical: usize,
+    pub warning: usize,
+    pub info: usize,
+    /// Drift health from 0 to 100, see `pgdrift_core::score::health_score`
+    pub health_score: u8,
+}
+
+impl ColumnMetrics {
+    /// Metrics of a column from its reported issues
+    pub fn new(
+        schema: &str,
+        table: &str,
+        column: &str,
+        samples_analyzed: u64,
+        issues: &[DriftIssue],
+        health_score: u8,
+    ) -> Self {
+        let count = |severity| issues.iter().filter(|i| i.severity() == severity).count();
+        Self {
+            schema: schema.to_string(),
+            table: table.to_string(),
+            column: column.to_string(),
+            samples_analyzed,
+            critical: count(Severity::Critical),
+            warning: count(Severity::Warning),
+            info: count(Severity::Info),
+            health_score,
+        }
+    }
+
+    fn labels(&self) -> String {
+        format!(
+            "schema=\"{}\",table=\"{}\",column=\"{}\"",
+            escape_label(&self.schema),
+            escape_label(&self.table),
+            escape_label(&self.column)
+        )
+    }
+}
+
+/// Render metrics in the Prometheus text exposition format
+///
+/// Every metric is a gauge: a run reports the issues found now, not a
+/// running count.
+pub fn exposition(columns: &[ColumnMetrics]) -> String {
+    let mut text = String::from(
+        "# HELP pgdrift_issues_total Drift issues reported by the last run\n\
+         # TYPE pgdrift_issues_total gauge\n",
+    );
+    for metrics in columns {
+        for (severity, count) in [
+            ("critical", metrics.critical),
+            ("warning", metrics.warning),
+            ("info", metrics.info),
+        ] {
+            text.push_str(&format!(
+                "pgdrift_issues_total{{{},severity=\"{}\"}} {}\n",
+                metrics.labels(),
+                severity,
+                count
+            ));
+        }
+    }
+
+    text.push_str(
+        "# HELP pgdrift_samples_analyzed Documents the last run sampled and analyzed\n\
+         # TYPE pgdrift_samples_analyzed gauge\n",
+    );
+    for metrics in columns {
+        text.push_str(&format!(
+            "pgdrift_samples_analyzed{{{}}} {}\n",
+            metrics.labels(),
+            metrics.samples_analyzed
+        ));
+    }
+
+    text.push_str(
+        "# HELP pgdrift_drift_score Drift health score, from 0 to 100 for no drift at all\n\
+         # TYPE pgdrift_drift_score gauge\n",
+    );
+    for metrics in columns {
+        text.push_str(&format!(
+            "pgdrift_drift_score{{{}}} {}\n",
+            metrics.labels(),
+            metrics.health_score
+        ));
+    }
+    text
+}
+
+/// Write metrics to `path`, e.g. for node_exporter's textfile collector
+///
+/// The file is written next to `path` first and renamed into place, so a
+/// scrape never reads half of it.
+pub fn write_metrics(path: &Path, columns: &[ColumnMetrics]) -> Result<()> {
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".tmp");
+    std::fs::write(&partial, exposition(columns))
+        .with_context(|| format!("Failed to write metrics {}", path.display()))?;
+    std::fs::rename(&partial, path)
+        .with_context(|| format!("Failed to write metrics {}", path.display()))
+}
+
+/// Push metrics to a Prometheus Pushgateway at `url`
+///
+/// They replace the metrics pushed before under the same grouping key: the
+/// `pgdrift` job and the given labels.
+pub async fn push_metrics(
+    url: &str,
+    grouping: &[(&str, &str)],
+    columns: &[ColumnMetrics],
+) -> Result<()> {
+    let endpoint = pushgateway_endpoint(url, grouping);
+    let body = exposition(columns);
+    tokio::task::spawn_blocking(move || {
+        ureq::put(&endpoint)
+            .header("Content-Type", CONTENT_TYPE)
+            .send(body)
+    })
+    .await?
+    .with_context(|| format!("Failed to push metrics to {}", url))?;
+    Ok(())
+}
+
+/// Write and push metrics, to whichever of `file` and `pushgateway` is set
+pub async fn publish_metrics(
+    file: Option<&Path>,
+    pushgateway: Option<&str>,
+    grouping: &[(&str, &str)],
+    columns: &[ColumnMetrics],
+) -> Result<()> {
+    if let Some(path) = file {
+        write_metrics(path, columns)?;
+    }
+    if let Some(url) = pushgateway {
+        push_metrics(url, grouping, columns).await?;
+    }
+    Ok(())
+}
+
+/// URL metrics are pushed to for a grouping key
+fn pushgateway_endpoint(url: &str, grouping: &[(&str, &str)]) -> String {
+    let mut endpoint = format!("{}/metrics/job/{}", url.trim_end_matches('/'), JOB);
+    for (label, value) in grouping {
+        // The Pushgateway can't take slashes in a path segment, even escaped
+        if value.is_empty() || value.contains('/') {
+            endpoint.push_str(&format!("/{}@base64/{}", label, base64_url(value)));
+        } else {
+            endpoint.push_str(&format!("/{}/{}", label, percent_encode(value)));
+        }
+    }
+    endpoint
+}
+
+/// Escape a label value for the text exposition format
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// URL-safe base64 with padding, as the Pushgateway reads `label@base64` values
+fn base64_url(value: &str) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+    if value.is_empty() {
+        // An empty value has to be spelled as a lone padding character
+        return "=".to_string();
+    }
+    let mut encoded = String::new();
+    for chunk in value.as_bytes().chunks(3) {
+        let bytes = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exposition() {
+        let metrics = ColumnMetrics {
+            schema: "public".to_string(),
+            table: "users".to_string(),
+            column: "meta\"data".to_string(),
+            samples_analyzed: 5000,
+            critical: 2,
+            warning: 1,
+            info: 0,
+            health_score: 71,
+        };
+
+        assert_eq!(
+            exposition(&[metrics]),
+            "# HELP pgdrift_issues_total Drift issues reported by the last run\n\
+             # TYPE pgdrift_issues_total gauge\n\
+             pgdrift_issues_total{schema=\"public\",table=\"users\",column=\"meta\\\"data\",severity=\"critical\"} 2\n\
+             pgdrift_issues_total{schema=\"public\",table=\"users\",column=\"meta\\\"data\",severity=\"warning\"} 1\n\
+             pgdrift_issues_total{schema=\"public\",table=\"users\",column=\"meta\\\"data\",severity=\"info\"} 0\n\
+             # HELP pgdrift_samples_analyzed Documents the last run sampled and analyzed\n\
+             # TYPE pgdrift_samples_analyzed gauge\n\
+             pgdrift_samples_analyzed{schema=\"public\",table=\"users\",column=\"meta\\\"data\"} 5000\n\
+             # HELP pgdrift_drift_score Drift health score, from 0 to 100 for no drift at all\n\
+             # TYPE pgdrift_drift_score gauge\n\
+             pgdrift_drift_score{schema=\"public\",table=\"users\",column=\"meta\\\"data\"} 71\n"
+        );
+    }
+
+    #[test]
+    fn test_pushgateway_endpoint() {
+        assert_eq!(
+            pushgateway_endpoint("http://localhost:9091/", &[]),
+            "http://localhost:9091/metrics/job/pgdrift"
+        );
+        assert_eq!(
+            pushgateway_endpoint(
+                "http://localhost:9091",
+                &[
+                    ("schema", "public"),
+                    ("table", "order items"),
+                    ("column", "a/b")
+                ]
+            ),
+            "http://localhost:9091/metrics/job/pgdrift/schema/public/table/order%20items/column@base64/YS9i"
+        );
+    }
+
+    #[test]
+    fn test_base64_url() {
+        assert_eq!(base64_url(""), "=");
+        assert_eq!(base64_url("a"), "YQ==");
+        assert_eq!(base64_url("ab"), "YWI=");
+        assert_eq!(base64_url("abc"), "YWJj");
+        assert_eq!(base64_url("/var/tmp"), "L3Zhci90bXA=");
+        assert_eq!(base64_url("~~~"), "fn5-");
+    }
+}
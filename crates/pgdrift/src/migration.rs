@@ -0,0 +1,199 @@
+use pgdrift_core::index::IndexRecommendation;
+use pgdrift_core::sql::{quote_ident, quote_literal};
+
+/// A recommended index and the column it was recommended for
+#[derive(Debug, Clone, Copy)]
+pub struct MigrationStep<'a> {
+    pub schema: &'a str,
+    pub table: &'a str,
+    pub column: &'a str,
+    pub recommendation: &'a IndexRecommendation,
+}
+
+/// Session settings a migration script starts with
+#[derive(Debug, Clone, PartialEq)]
+pub struct MigrationSettings {
+    /// How long each statement may wait for a lock, e.g. "5s"
+    pub lock_timeout: String,
+    /// How long each statement may run, "0" for no limit
+    pub statement_timeout: String,
+}
+
+/// Render recommendations as one SQL script, in the order given
+///
+/// Indexes are built with `CREATE INDEX CONCURRENTLY`, so writes carry on
+/// while they build, and each step is followed by the statements undoing
+/// it, commented out. Recommended statements name their index without a
+/// schema, so the script sets `search_path` whenever the schema changes
+/// from one step to the next: the index is created, and dropped again, in
+/// the schema of its table.
+pub fn migration_script(steps: &[MigrationStep], settings: &MigrationSettings) -> String {
+    let mut script = String::from(
+        "-- Index recommendations from pgdrift, most urgent first\n\
+         --\n\
+         -- CREATE INDEX CONCURRENTLY can't run inside a transaction block, so run\n\
+         -- the script as it is (psql -f), not in a single transaction. A build\n\
+         -- that fails leaves an invalid index behind: drop it before retrying.\n",
+    );
+    script.push_str(&format!(
+        "\nSET lock_timeout = {};\nSET statement_timeout = {};\n",
+        quote_literal(&settings.lock_timeout),
+        quote_literal(&settings.statement_timeout)
+    ));
+
+    let mut schema = None;
+    for step in steps {
+        if schema != Some(step.schema) {
+            script.push_str(&format!(
+                "\nSET search_path TO {}, public;\n",
+                quote_ident(step.schema)
+            ));
+            schema = Some(step.schema);
+        }
+        let recommendation = step.recommendation;
+        script.push_str(&format!(
+            "\n-- {}.{}.{}: {} ({}, {} priority)\n",
+            step.schema,
+            step.table,
+            step.column,
+            recommendation.field_path,
+            recommendation.index_type.to_name(),
+            recommendation.priority.to_name()
+        ));
+
+        let mut rollback = Vec::new();
+        for line in recommendation.sql.lines() {
+            let line = line.trim();
+            if let Some(rest) = line.strip_prefix("CREATE INDEX ") {
+                if let Some(name) = rest.split_whitespace().next() {
+                    rollback.push(format!("DROP INDEX CONCURRENTLY IF EXISTS {};", name));
+                }
+                script.push_str(&format!("CREATE INDEX CONCURRENTLY {}\n", rest));
+            } else {
+                if line.starts_with("ALTER TABLE ")
+                    && let Some((table, rest)) = line.split_once(" ADD COLUMN ")
+                    && let Some(column) = rest.split_whitespace().next()
+                {
+                    rollback.push(format!("{} DROP COLUMN IF EXISTS {};", table, column));
+                }
+                script.push_str(line);
+                script.push('\n');
+            }
+        }
+
+        if !rollback.is_empty() {
+            script.push_str("-- Rollback:\n");
+            // Undo in the reverse order
+            for statement in rollback.iter().rev() {
+                script.push_str(&format!("--   {}\n", statement));
+            }
+        }
+    }
+    script
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pgdrift_core::index::{IndexPriority, IndexType};
+
+    fn recommendation(
+        field_path: &str,
+        index_type: IndexType,
+        priority: IndexPriority,
+        sql: &str,
+    ) -> IndexRecommendation {
+        IndexRecommendation {
+            field_path: field_path.to_string(),
+            index_type,
+            priority,
+            reason: String::new(),
+            sql: sql.to_string(),
+            estimated_benefit: String::new(),
+            estimated_size_bytes: None,
+            planner_check: None,
+            workload: None,
+        }
+    }
+
+    #[test]
+    fn test_migration_script() {
+        let email = recommendation(
+            "user.email",
+            IndexType::GeneratedColumn,
+            IndexPriority::High,
+            "-- Generated TEXT column for user.email\n\
+             ALTER TABLE \"public\".\"users\" ADD COLUMN \"user_email\" TEXT GENERATED ALWAYS AS (\"metadata\" #>> '{user,email}') STORED;\n\
+             CREATE INDEX idx_users_metadata_user_email_gen ON \"public\".\"users\" (\"user_email\");",
+        );
+        let sku = recommendation(
+            "sku",
+            IndexType::BTreeExtracted,
+            IndexPriority::High,
+            "-- B-tree index on extracted TEXT value: 100.0% density\n\
+             CREATE INDEX idx_products_attrs_sku_btree_ext ON \"public\".\"products\" ((\"attrs\" #>> '{sku}')) WHERE (\"attrs\" #>> '{sku}') IS NOT NULL;",
+        );
+        let tenant = recommendation(
+            "tenant",
+            IndexType::Gin,
+            IndexPriority::Medium,
+            "-- GIN index for high-density fields: tenant\n\
+             CREATE INDEX idx_events_payload_gin ON \"audit\".\"events\" USING GIN (\"payload\");",
+        );
+        let step = |schema, table, column, recommendation| MigrationStep {
+            schema,
+            table,
+            column,
+            recommendation,
+        };
+        let settings = MigrationSettings {
+            lock_timeout: "5s".to_string(),
+            statement_timeout: "0".to_string(),
+        };
+
+        let script = migration_script(
+            &[
+                step("public", "users", "metadata", &email),
+                step("public", "products", "attrs", &sku),
+                step("audit", "events", "payload", &tenant),
+            ],
+            &settings,
+        );
+
+        assert_eq!(
+            script,
+            "-- Index recommendations from pgdrift, most urgent first\n\
+             --\n\
+             -- CREATE INDEX CONCURRENTLY can't run inside a transaction block, so run\n\
+             -- the script as it is (psql -f), not in a single transaction. A build\n\
+             -- that fails leaves an invalid index behind: drop it before retrying.\n\
+             \n\
+             SET lock_timeout = '5s';\n\
+             SET statement_timeout = '0';\n\
+             \n\
+             SET search_path TO \"public\", public;\n\
+             \n\
+             -- public.users.metadata: user.email (Generated column, High priority)\n\
+             -- Generated TEXT column for user.email\n\
+             ALTER TABLE \"public\".\"users\" ADD COLUMN \"user_email\" TEXT GENERATED ALWAYS AS (\"metadata\" #>> '{user,email}') STORED;\n\
+             CREATE INDEX CONCURRENTLY idx_users_metadata_user_email_gen ON \"public\".\"users\" (\"user_email\");\n\
+             -- Rollback:\n\
+             --   DROP INDEX CONCURRENTLY IF EXISTS idx_users_metadata_user_email_gen;\n\
+             --   ALTER TABLE \"public\".\"users\" DROP COLUMN IF EXISTS \"user_email\";\n\
+             \n\
+             -- public.products.attrs: sku (B-tree (extracted), High priority)\n\
+             -- B-tree index on extracted TEXT value: 100.0% density\n\
+             CREATE INDEX CONCURRENTLY idx_products_attrs_sku_btree_ext ON \"public\".\"products\" ((\"attrs\" #>> '{sku}')) WHERE (\"attrs\" #>> '{sku}') IS NOT NULL;\n\
+             -- Rollback:\n\
+             --   DROP INDEX CONCURRENTLY IF EXISTS idx_products_attrs_sku_btree_ext;\n\
+             \n\
+             SET search_path TO \"audit\", public;\n\
+             \n\
+             -- audit.events.payload: tenant (GIN, Medium priority)\n\
+             -- GIN index for high-density fields: tenant\n\
+             CREATE INDEX CONCURRENTLY idx_events_payload_gin ON \"audit\".\"events\" USING GIN (\"payload\");\n\
+             -- Rollback:\n\
+             --   DROP INDEX CONCURRENTLY IF EXISTS idx_events_payload_gin;\n"
+        );
+    }
+}
@@ -1,9 +1,57 @@
+use crate::baseline::{BaselineDiff, BaselineIssue};
+use crate::config::Suppression;
+use crate::diagram::{DiagramFormat, schema_diagram};
+use crate::github;
+use crate::html;
+use anyhow::Context;
 use clap::ValueEnum;
-use colored::Colorize;
-use pgdrift_core::drift::{DriftIssue, Severity};
+use colored::{ColoredString, Colorize};
+use pgdrift_core::analyzer::Truncation;
+use pgdrift_core::cohort::CohortShift;
+use pgdrift_core::compare::PathDifference;
+use pgdrift_core::conformance::ConformanceReport;
+use pgdrift_core::cooccurrence::Cooccurrence;
+use pgdrift_core::denormalization::Denormalization;
+use pgdrift_core::drift::{DriftIssue, EvolutionPattern, Severity};
+use pgdrift_core::format::StringFormat;
+use pgdrift_core::index::{
+    CoveredRecommendation, DropRecommendation, IndexOverlap, IndexPriority, IndexRecommendation,
+    PlannerCheck,
+};
+use pgdrift_core::promotion::Promotion;
+use pgdrift_core::remediation::Remediation;
+use pgdrift_core::report::analysis::{
+    AnalysisReport, AnalysisSummary, GroupReport, GroupsReport, SegmentReport, SegmentsReport,
+    issue_examples,
+};
+use pgdrift_core::report::cohort::CohortReport;
+use pgdrift_core::report::compare::{CompareReport, CompareSide};
+use pgdrift_core::report::diff::DiffReport;
+use pgdrift_core::report::discover::DiscoverReport;
+use pgdrift_core::report::index::{
+    ColumnDrop, ColumnFailure, ColumnRecommendation, IndexAllReport, IndexAllSummary, IndexReport,
+    IndexSummary, IndexedColumn,
+};
+use pgdrift_core::report::promote::PromoteReport;
+use pgdrift_core::report::scan_all::{ColumnIssueCounts, ColumnReport, ScanAllReport};
+use pgdrift_core::report::validate::ValidateReport;
+use pgdrift_core::report::{ExpiringSuppression, IssueCounts, RunSummary, Versioned};
 use pgdrift_core::stats::FieldStats;
-use pgdrift_db::discovery::JsonbColumn;
-use serde_json::json;
+use pgdrift_core::tree::{FieldNode, field_tree};
+use pgdrift_core::types::JsonType;
+use pgdrift_core::usage::UnusedField;
+use pgdrift_core::validate::Violation;
+use pgdrift_core::workload::ColumnAccess;
+use pgdrift_db::discovery::{ColumnStorage, JsonbColumn, RelationKind, glob_match};
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, MutexGuard};
 use tabled::{
     Table, Tabled,
     settings::{
@@ -12,11 +60,163 @@ use tabled::{
     },
 };
 
+/// Set by `--quiet`, see `set_quiet`
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Silence progress messages and spinners; warnings and errors still print
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// Turn colors on only if stdout is a terminal, `NO_COLOR` isn't set and
+/// `--no-color` wasn't given
+pub fn init_colors(no_color: bool) {
+    let no_color_env = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    colored::control::set_override(!no_color && !no_color_env && io::stdout().is_terminal());
+}
+
+pub fn colors_enabled() -> bool {
+    colored::control::SHOULD_COLORIZE.should_colorize()
+}
+
+/// Whether to draw progress bars and spinners: not with `--quiet`, nor when
+/// stderr isn't a terminal, where they would fill logs with redraws
+pub fn show_progress() -> bool {
+    !is_quiet() && io::stderr().is_terminal()
+}
+
+/// File reports are written to instead of stdout, see `report_to_file`
+static REPORT_FILE: Mutex<Option<ReportFile>> = Mutex::new(None);
+
+struct ReportFile {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    /// First write that failed, surfaced by `close_report_file`
+    error: Option<io::Error>,
+}
+
+/// Write reports to `path` instead of stdout, until `close_report_file`
+///
+/// Only the report goes to the file, without colors: progress and warnings
+/// stay on stderr.
+pub fn report_to_file(path: &Path) -> anyhow::Result<()> {
+    let file = File::create(path)
+        .with_context(|| format!("Failed to create report {}", path.display()))?;
+    *report_file() = Some(ReportFile {
+        path: path.to_path_buf(),
+        writer: BufWriter::new(file),
+        error: None,
+    });
+    Ok(())
+}
+
+/// Flush and close the file reports are written to, if any
+pub fn close_report_file() -> anyhow::Result<()> {
+    let Some(mut report) = report_file().take() else {
+        return Ok(());
+    };
+    let written = match report.error.take() {
+        Some(e) => Err(e),
+        None => report.writer.flush(),
+    };
+    written.with_context(|| format!("Failed to write report {}", report.path.display()))
+}
+
+fn report_file() -> MutexGuard<'static, Option<ReportFile>> {
+    REPORT_FILE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Print one line of a report, to stdout or the file set by `report_to_file`
+fn report_line(line: fmt::Arguments) {
+    match report_file().as_mut() {
+        Some(report) => {
+            if report.error.is_none()
+                && let Err(e) = writeln!(report.writer, "{}", strip_ansi(&line.to_string()))
+            {
+                report.error = Some(e);
+            }
+        }
+        None => println!("{}", line),
+    }
+}
+
+/// `println!` for reports
+macro_rules! report {
+    () => {
+        report_line(format_args!(""))
+    };
+    ($($arg:tt)*) => {
+        report_line(format_args!($($arg)*))
+    };
+}
+
+/// Print a JSON report, stamped with the version of the report structures
+fn print_json(report: impl Serialize) {
+    report!(
+        "{}",
+        serde_json::to_string_pretty(&Versioned::new(report)).unwrap()
+    );
+}
+
+/// Write the summary of a run to `path` as JSON, for `--summary-file`
+pub fn write_summary_file(path: &Path, summary: &RunSummary) -> anyhow::Result<()> {
+    let json = serde_json::to_string_pretty(&Versioned::new(summary))?;
+    std::fs::write(path, json + "\n")
+        .with_context(|| format!("Failed to write summary {}", path.display()))
+}
+
+/// Render a report through the Tera template at `path`, in place of the
+/// built-in formats
+///
+/// The template sees the fields of the JSON report, `schema_version` included.
+/// Nothing is escaped: the output format is whatever the template makes it.
+fn print_template(path: &Path, report: impl Serialize) -> anyhow::Result<()> {
+    let template = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read template {}", path.display()))?;
+    let rendered = render_template(&template, report)
+        .with_context(|| format!("Failed to render template {}", path.display()))?;
+    report!("{}", rendered.trim_end_matches('\n'));
+    Ok(())
+}
+
+fn render_template(template: &str, report: impl Serialize) -> anyhow::Result<String> {
+    let context = tera::Context::from_serialize(Versioned::new(report))?;
+    Ok(tera::Tera::one_off(template, &context, false)?)
+}
+
+/// Remove the ANSI escape sequences that color terminal output
+fn strip_ansi(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // CSI sequences end at their first letter, e.g. "\x1b[1;31m"
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            stripped.push(c);
+        }
+    }
+    stripped
+}
+
 #[derive(Debug, Clone, ValueEnum)]
 pub enum OutputFormat {
     Table,
     Json,
     Markdown,
+    /// GitHub Actions annotations per issue and a pull request comment
+    /// (markdown for reports without drift issues)
+    Github,
+    /// Standalone HTML page (scan-all only)
+    Html,
 }
 
 #[derive(Tabled)]
@@ -27,28 +227,148 @@ pub struct ColumnRow {
     pub table: String,
     #[tabled(rename = "Column")]
     pub column: String,
+    #[tabled(rename = "Type")]
+    pub data_type: String,
     #[tabled(rename = "Est. Rows")]
     pub row_count: String,
+    #[tabled(rename = "Indexes")]
+    pub indexes: String,
+    #[tabled(rename = "Stats Age")]
+    pub stats_age: String,
 }
 
 impl From<JsonbColumn> for ColumnRow {
     fn from(col: JsonbColumn) -> Self {
+        let indexes = col.index_summary();
+        let table = relation_label(&col);
+        let stats_age = stats_age_label(&col);
         Self {
             schema: col.schema,
-            table: col.table,
+            table,
             column: col.column,
+            data_type: col.data_type.to_string(),
             row_count: col
                 .estimated_rows
                 .map_or("N/A".to_string(), |c| c.to_string()),
+            indexes,
+            stats_age,
+        }
+    }
+}
+
+/// Table name, annotated with the relation kind for views and matviews and
+/// with the partition hierarchy for partitioned tables
+fn relation_label(col: &JsonbColumn) -> String {
+    if let Some(parent) = &col.partition_of {
+        return format!("{} (partition of {})", col.table, parent);
+    }
+    if let Some(partitions) = col.partitions {
+        return format!("{} ({} partitions)", col.table, partitions);
+    }
+
+    match col.relation_kind {
+        RelationKind::Table => col.table.clone(),
+        kind => format!("{} ({})", col.table, kind),
+    }
+}
+
+/// How long ago the table statistics were refreshed, e.g. `3d`, with dead
+/// tuples called out when they make up a notable share of the table
+fn stats_age_label(col: &JsonbColumn) -> String {
+    let age = match col.stats_age_secs {
+        Some(secs) => format_age(secs),
+        None if col.estimated_rows.is_none() => return "N/A".to_string(),
+        None => "never".to_string(),
+    };
+
+    match (col.dead_tuples, col.estimated_rows) {
+        (Some(dead), Some(live)) if dead > 0 && live + dead > 0 => {
+            let pct = dead as f64 / (live + dead) as f64 * 100.0;
+            if pct >= 1.0 {
+                format!("{} ({:.0}% dead)", age, pct)
+            } else {
+                age
+            }
+        }
+        _ => age,
+    }
+}
+
+/// Format a duration in seconds as its largest whole unit, e.g. `5m`, `3d`
+fn format_age(secs: i64) -> String {
+    match secs {
+        s if s < 60 => format!("{}s", s.max(0)),
+        s if s < 3600 => format!("{}m", s / 60),
+        s if s < 86_400 => format!("{}h", s / 3600),
+        s => format!("{}d", s / 86_400),
+    }
+}
+
+#[derive(Tabled)]
+pub struct StorageRow {
+    #[tabled(rename = "Column")]
+    pub column: String,
+    #[tabled(rename = "Avg Doc Size")]
+    pub avg_size: String,
+    #[tabled(rename = "Est. Total")]
+    pub total_size: String,
+    #[tabled(rename = "Table TOAST")]
+    pub toast_size: String,
+    #[tabled(rename = "Over TOAST Threshold")]
+    pub toasted: String,
+}
+
+impl StorageRow {
+    fn new(col: &JsonbColumn, storage: &ColumnStorage) -> Self {
+        Self {
+            column: col.full_name(),
+            avg_size: format_bytes(storage.avg_bytes as i64),
+            total_size: storage
+                .estimated_total_bytes
+                .map_or("N/A".to_string(), format_bytes),
+            toast_size: format_bytes(storage.toast_bytes),
+            toasted: format!("{:.1}%", storage.toasted_fraction * 100.0),
         }
     }
 }
 
+/// Columns with storage stats, largest estimated footprint first
+fn storage_rows(columns: &[JsonbColumn]) -> Vec<StorageRow> {
+    let mut with_storage: Vec<_> = columns
+        .iter()
+        .filter_map(|c| c.storage.as_ref().map(|s| (c, s)))
+        .collect();
+    with_storage.sort_by_key(|(_, s)| std::cmp::Reverse(s.estimated_total_bytes.unwrap_or(0)));
+
+    with_storage
+        .into_iter()
+        .map(|(c, s)| StorageRow::new(c, s))
+        .collect()
+}
+
+/// Format a byte count for humans, e.g. `1.5 MB`
+fn format_bytes(bytes: i64) -> String {
+    const UNITS: [&str; 5] = ["B", "kB", "MB", "GB", "TB"];
+
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
 pub fn print_columns(columns: &[JsonbColumn], format: &OutputFormat) {
     match format {
         OutputFormat::Table => {
             if columns.is_empty() {
-                println!("{}", "No JSONB columns found.".yellow());
+                report!("{}", "No JSONB columns found.".yellow());
                 return;
             }
 
@@ -56,32 +376,85 @@ pub fn print_columns(columns: &[JsonbColumn], format: &OutputFormat) {
             let mut table = Table::new(rows);
             table.with(Style::rounded());
 
-            println!("\n{}", "JSONB Columns:".bold().green());
-            println!("{}", table);
-            println!("\nFound {} JSONB column(s)\n", columns.len());
+            report!("\n{}", "JSONB Columns:".bold().green());
+            report!("{}", table);
+            report!("\nFound {} JSONB column(s)", columns.len());
+            let unindexed = columns.iter().filter(|c| c.indexes.is_empty()).count();
+            if unindexed > 0 {
+                report!(
+                    "{}",
+                    format!("{} column(s) have no index", unindexed).yellow()
+                );
+            }
+            let stale = columns.iter().filter(|c| c.has_stale_stats()).count();
+            if stale > 0 {
+                report!(
+                    "{}",
+                    format!(
+                        "{} column(s) have stale statistics - row estimates and TABLESAMPLE may be off (run ANALYZE)",
+                        stale
+                    )
+                    .yellow()
+                );
+            }
+
+            let storage = storage_rows(columns);
+            if !storage.is_empty() {
+                let mut table = Table::new(storage);
+                table.with(Style::rounded());
+                report!("\n{}", "Storage (largest first):".bold());
+                report!("{}", table);
+            }
+            report!();
         }
         OutputFormat::Json => {
-            let output = json!({
-                "columns": columns,
-                "count": columns.len()
+            print_json(DiscoverReport {
+                columns: columns.iter().map(Into::into).collect(),
+                count: columns.len(),
+                unindexed: columns.iter().filter(|c| c.indexes.is_empty()).count(),
+                stale_stats: columns.iter().filter(|c| c.has_stale_stats()).count(),
             });
-            println!("{}", serde_json::to_string_pretty(&output).unwrap());
         }
-        OutputFormat::Markdown => {
-            println!("# JSONB Columns\n");
-            println!("| Schema | Table | Column | Est. Rows |");
-            println!("|--------|-------|--------|-----------|");
+        OutputFormat::Markdown | OutputFormat::Github | OutputFormat::Html => {
+            report!("# JSONB Columns\n");
+            report!("| Schema | Table | Column | Type | Est. Rows | Indexes | Stats Age |");
+            report!("|--------|-------|--------|------|-----------|---------|-----------|");
             for col in columns {
-                println!(
-                    "| {} | {} | {} | {} |",
+                report!(
+                    "| {} | {} | {} | {} | {} | {} | {} |",
                     col.schema,
-                    col.table,
+                    relation_label(col),
                     col.column,
+                    col.data_type,
                     col.estimated_rows
-                        .map_or("N/A".to_string(), |c| c.to_string())
+                        .map_or("N/A".to_string(), |c| c.to_string()),
+                    col.index_summary(),
+                    stats_age_label(col)
+                );
+            }
+            report!("\nFound {} JSONB column(s)\n", columns.len());
+
+            let storage = storage_rows(columns);
+            if !storage.is_empty() {
+                report!("## Storage\n");
+                report!(
+                    "| Column | Avg Doc Size | Est. Total | Table TOAST | Over TOAST Threshold |"
+                );
+                report!(
+                    "|--------|--------------|------------|-------------|----------------------|"
                 );
+                for row in storage {
+                    report!(
+                        "| {} | {} | {} | {} | {} |",
+                        row.column,
+                        row.avg_size,
+                        row.total_size,
+                        row.toast_size,
+                        row.toasted
+                    );
+                }
+                report!();
             }
-            println!("\nFound {} JSONB column(s)\n", columns.len());
         }
     }
 }
@@ -112,151 +485,923 @@ impl From<&DriftIssue> for DriftRow {
     }
 }
 
-pub struct AnalysisResult {
-    pub table: String,
-    pub column: String,
-    pub samples_analyzed: u64,
-    pub field_stats: Vec<FieldStats>,
-    pub drift_issues: Vec<DriftIssue>,
+/// Order of the paths in the field stats table
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum FieldSort {
+    /// Alphabetical
+    #[default]
+    Path,
+    /// Sparsest first
+    Density,
+    /// Most deeply nested first
+    Depth,
+    /// Most critical issues first, then most warnings, then most info issues
+    Issues,
 }
 
-pub struct ColumnScanResult {
-    pub schema: String,
-    pub table: String,
-    pub column: String,
-    pub samples_analyzed: u64,
-    pub drift_issues: Vec<DriftIssue>,
+/// Paths listed in the field stats table, and their order
+#[derive(Debug, Clone, Default)]
+pub struct FieldStatsView {
+    /// Only list paths matching this glob pattern, e.g. 'address.*'
+    pub paths: Option<String>,
+    pub sort: FieldSort,
+    pub page: Page,
 }
 
-pub struct ScanAllResult {
-    pub total_columns: usize,
-    pub column_results: Vec<ColumnScanResult>,
+/// Window of the rows of a long table, set by `--offset` and `--limit`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Page {
+    /// Rows skipped from the start
+    pub offset: usize,
+    /// Rows listed at most, all if `None`
+    pub limit: Option<usize>,
+}
+
+impl Page {
+    pub(crate) fn apply<T>(&self, rows: Vec<T>) -> Vec<T> {
+        rows.into_iter()
+            .skip(self.offset)
+            .take(self.limit.unwrap_or(usize::MAX))
+            .collect()
+    }
+
+    /// Which of `total` rows (e.g. paths) the page holds, if not all of them
+    pub(crate) fn note(&self, total: usize, rows: &str) -> Option<String> {
+        let shown = total.saturating_sub(self.offset);
+        let shown = self.limit.map_or(shown, |limit| shown.min(limit));
+        if shown == total {
+            None
+        } else if shown == 0 {
+            Some(format!(
+                "No {} past the first {} of {}",
+                rows, self.offset, total
+            ))
+        } else {
+            Some(format!(
+                "Showing {}-{} of {} {}",
+                self.offset + 1,
+                self.offset + shown,
+                total,
+                rows
+            ))
+        }
+    }
+}
+
+/// Sort key putting the most severe issues first: descending by critical,
+/// then warning, then info issues
+fn severity_rank<'a>(
+    issues: impl IntoIterator<Item = &'a DriftIssue>,
+) -> std::cmp::Reverse<(usize, usize, usize)> {
+    let counts = IssueCounts::from_issues(issues);
+    std::cmp::Reverse((counts.critical, counts.warning, counts.info))
 }
 
 #[derive(Tabled)]
-pub struct ScanAllRow {
-    #[tabled(rename = "Schema")]
-    pub schema: String,
-    #[tabled(rename = "Table")]
-    pub table: String,
-    #[tabled(rename = "Column")]
-    pub column: String,
-    #[tabled(rename = "Samples")]
-    pub samples: String,
-    #[tabled(rename = "Critical")]
-    pub critical: String,
-    #[tabled(rename = "Warning")]
-    pub warning: String,
-    #[tabled(rename = "Info")]
-    pub info: String,
-    #[tabled(rename = "Total Issues")]
-    pub total: String,
+pub struct FieldStatsRow {
+    #[tabled(rename = "Path")]
+    pub path: String,
+    #[tabled(rename = "Density")]
+    pub density: String,
+    #[tabled(rename = "Types")]
+    pub types: String,
+    #[tabled(rename = "Depth")]
+    pub depth: usize,
+    #[tabled(rename = "Issues")]
+    pub issues: usize,
+    #[tabled(rename = "Examples")]
+    pub examples: String,
 }
 
-impl From<&ColumnScanResult> for ScanAllRow {
-    fn from(result: &ColumnScanResult) -> Self {
-        let critical = result
-            .drift_issues
-            .iter()
-            .filter(|i| i.severity() == Severity::Critical)
-            .count();
-        let warning = result
-            .drift_issues
+/// Examples listed per path in the field stats table, unless `--show-examples`
+/// says otherwise
+const LISTED_EXAMPLES: usize = 3;
+
+/// Characters an example is cut to in the field stats table
+const EXAMPLE_WIDTH: usize = 30;
+
+/// One row per path the view selects, in its order, with up to `examples`
+/// example values
+///
+/// The view's page isn't applied, so the rows can be counted first.
+fn field_stats_rows(
+    field_stats: &[FieldStats],
+    drift_issues: &[DriftIssue],
+    view: &FieldStatsView,
+    examples: usize,
+) -> Vec<FieldStatsRow> {
+    let issues_of = |fs: &FieldStats| {
+        drift_issues
             .iter()
-            .filter(|i| i.severity() == Severity::Warning)
-            .count();
-        let info = result
-            .drift_issues
+            .filter(|issue| issue.path() == fs.path)
+            .collect::<Vec<_>>()
+    };
+    let mut selected: Vec<&FieldStats> = field_stats
+        .iter()
+        .filter(|fs| {
+            view.paths
+                .as_ref()
+                .is_none_or(|pattern| glob_match(pattern, &fs.path))
+        })
+        .collect();
+    selected.sort_by(|a, b| match view.sort {
+        FieldSort::Path => a.path.cmp(&b.path),
+        FieldSort::Density => a
+            .density
+            .total_cmp(&b.density)
+            .then_with(|| a.path.cmp(&b.path)),
+        FieldSort::Depth => b.depth.cmp(&a.depth).then_with(|| a.path.cmp(&b.path)),
+        FieldSort::Issues => severity_rank(issues_of(a))
+            .cmp(&severity_rank(issues_of(b)))
+            .then_with(|| a.path.cmp(&b.path)),
+    });
+    selected
+        .into_iter()
+        .map(|fs| FieldStatsRow {
+            path: fs.path.clone(),
+            density: format!("{:.1}%", fs.density * 100.0),
+            types: type_mix(fs),
+            depth: fs.depth,
+            issues: issues_of(fs).len(),
+            examples: example_labels(&fs.examples[..examples.min(fs.examples.len())]).join(", "),
+        })
+        .collect()
+}
+
+/// Types of a path's values, with their shares if there is more than one
+pub(crate) fn type_mix(fs: &FieldStats) -> String {
+    let total: u64 = fs.types.values().sum();
+    let mut types: Vec<(&JsonType, &u64)> = fs.types.iter().collect();
+    types.sort_by(|a, b| {
+        b.1.cmp(a.1)
+            .then_with(|| a.0.to_string().cmp(&b.0.to_string()))
+    });
+    match types.as_slice() {
+        [(only, _)] => only.to_string(),
+        _ => types
             .iter()
-            .filter(|i| i.severity() == Severity::Info)
-            .count();
-        let total = result.drift_issues.len();
+            .map(|(json_type, count)| {
+                format!(
+                    "{} {:.1}%",
+                    json_type,
+                    **count as f64 * 100.0 / total as f64
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", "),
+    }
+}
 
-        Self {
-            schema: result.schema.clone(),
-            table: result.table.clone(),
-            column: result.column.clone(),
-            samples: result.samples_analyzed.to_string(),
-            critical: critical.to_string(),
-            warning: warning.to_string(),
-            info: info.to_string(),
-            total: total.to_string(),
-        }
+/// `text` cut to `max` characters, ending in an ellipsis if it was longer
+pub(crate) fn truncate_chars(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
     }
+    let mut cut: String = text.chars().take(max - 1).collect();
+    cut.push('…');
+    cut
 }
 
-pub fn print_scan_all_summary(result: &ScanAllResult, format: &OutputFormat) -> anyhow::Result<()> {
-    match format {
-        OutputFormat::Table => print_scan_all_table(result),
-        OutputFormat::Json => print_scan_all_json(result),
-        OutputFormat::Markdown => print_scan_all_markdown(result),
+/// Optional sections of the table and markdown analysis reports
+#[derive(Debug, Clone, Default)]
+pub struct AnalysisView {
+    /// List the stats of each path
+    pub field_stats: Option<FieldStatsView>,
+    /// Draw the path hierarchy as a tree
+    pub tree: bool,
+    /// Only print the score and issue counts, leaving out every other section
+    pub summary_only: bool,
+    /// Example values listed for each path with issues, and in the field stats
+    pub examples: Option<usize>,
+}
+
+/// The path hierarchy under `root`, one line per node with its density and types
+fn tree_lines(root: &str, field_stats: &[FieldStats]) -> Vec<String> {
+    let mut lines = vec![root.to_string()];
+    push_tree_lines(&field_tree(field_stats), "", &mut lines);
+    lines
+}
+
+fn push_tree_lines(nodes: &[FieldNode], indent: &str, lines: &mut Vec<String>) {
+    for (i, node) in nodes.iter().enumerate() {
+        let last = i + 1 == nodes.len();
+        let label = match node.stats {
+            Some(fs) => format!("{}  {:.1}% {}", node.key, fs.density * 100.0, type_mix(fs)),
+            None => node.key.clone(),
+        };
+        lines.push(format!(
+            "{}{}{}",
+            indent,
+            if last { "└── " } else { "├── " },
+            label
+        ));
+        let indent = format!("{}{}", indent, if last { "    " } else { "│   " });
+        push_tree_lines(&node.children, &indent, lines);
     }
-    Ok(())
 }
 
-fn print_scan_all_json(result: &ScanAllResult) {
-    let total_samples: u64 = result
-        .column_results
-        .iter()
-        .map(|r| r.samples_analyzed)
-        .sum();
-    let total_critical: usize = result
-        .column_results
-        .iter()
-        .flat_map(|r| &r.drift_issues)
-        .filter(|i| i.severity() == Severity::Critical)
-        .count();
-    let total_warning: usize = result
-        .column_results
+#[derive(Tabled)]
+pub struct NumericRow {
+    #[tabled(rename = "Path")]
+    pub path: String,
+    #[tabled(rename = "Min")]
+    pub min: String,
+    #[tabled(rename = "Max")]
+    pub max: String,
+    #[tabled(rename = "Mean")]
+    pub mean: String,
+    #[tabled(rename = "P50")]
+    pub p50: String,
+    #[tabled(rename = "P90")]
+    pub p90: String,
+    #[tabled(rename = "P99")]
+    pub p99: String,
+}
+
+/// One row per path that holds numbers, ordered by path
+fn numeric_rows(field_stats: &[FieldStats]) -> Vec<NumericRow> {
+    let mut rows: Vec<NumericRow> = field_stats
         .iter()
-        .flat_map(|r| &r.drift_issues)
-        .filter(|i| i.severity() == Severity::Warning)
-        .count();
-    let total_info: usize = result
-        .column_results
+        .filter_map(|fs| {
+            let n = fs.numeric.as_ref()?;
+            Some(NumericRow {
+                path: fs.path.clone(),
+                min: format_number(n.min),
+                max: format_number(n.max),
+                mean: format_number(n.mean),
+                p50: format_number(n.p50),
+                p90: format_number(n.p90),
+                p99: format_number(n.p99),
+            })
+        })
+        .collect();
+    rows.sort_by(|a, b| a.path.cmp(&b.path));
+    rows
+}
+
+#[derive(Tabled)]
+pub struct FormatRow {
+    #[tabled(rename = "Path")]
+    pub path: String,
+    #[tabled(rename = "Format")]
+    pub format: String,
+    #[tabled(rename = "Consistency")]
+    pub consistency: String,
+}
+
+/// One row per string path with a recognizable format, ordered by path
+fn format_rows(field_stats: &[FieldStats]) -> Vec<FormatRow> {
+    let mut rows: Vec<FormatRow> = field_stats
         .iter()
-        .flat_map(|r| &r.drift_issues)
-        .filter(|i| i.severity() == Severity::Info)
-        .count();
+        .filter_map(|fs| {
+            let f = fs.format.as_ref()?;
+            if f.format == StringFormat::Text {
+                return None;
+            }
+            Some(FormatRow {
+                path: fs.path.clone(),
+                format: f.format.to_string(),
+                consistency: format!("{:.1}%", f.consistency),
+            })
+        })
+        .collect();
+    rows.sort_by(|a, b| a.path.cmp(&b.path));
+    rows
+}
 
-    let output = json!({
-        "total_columns": result.total_columns,
-        "total_samples": total_samples,
-        "summary": {
-            "total_issues": total_critical + total_warning + total_info,
-            "critical": total_critical,
-            "warning": total_warning,
-            "info": total_info,
-        },
-        "columns": result.column_results.iter().map(|col| {
-            json!({
-                "schema": col.schema,
-                "table": col.table,
-                "column": col.column,
-                "samples_analyzed": col.samples_analyzed,
-                "drift_issues": col.drift_issues,
-                "issue_counts": {
-                    "critical": col.drift_issues.iter().filter(|i| i.severity() == Severity::Critical).count(),
-                    "warning": col.drift_issues.iter().filter(|i| i.severity() == Severity::Warning).count(),
-                    "info": col.drift_issues.iter().filter(|i| i.severity() == Severity::Info).count(),
-                }
+#[derive(Tabled)]
+pub struct ArrayRow {
+    #[tabled(rename = "Path")]
+    pub path: String,
+    #[tabled(rename = "Min")]
+    pub min: u64,
+    #[tabled(rename = "Max")]
+    pub max: u64,
+    #[tabled(rename = "Avg")]
+    pub avg: String,
+    #[tabled(rename = "P99")]
+    pub p99: u64,
+    #[tabled(rename = "Empty")]
+    pub empty: String,
+    #[tabled(rename = "Note")]
+    pub note: String,
+}
+
+/// Arrays longer than this are flagged - GIN indexes and containment queries
+/// over them get expensive
+const LARGE_ARRAY_LENGTH: u64 = 1000;
+
+/// One row per path that holds arrays, ordered by path
+fn array_rows(field_stats: &[FieldStats]) -> Vec<ArrayRow> {
+    let mut rows: Vec<ArrayRow> = field_stats
+        .iter()
+        .filter_map(|fs| {
+            let a = fs.array.as_ref()?;
+            let note = if a.is_singleton() {
+                "always 1 element - consider flattening".to_string()
+            } else if a.max_length >= LARGE_ARRAY_LENGTH {
+                "large arrays - index risk".to_string()
+            } else {
+                String::new()
+            };
+            Some(ArrayRow {
+                path: fs.path.clone(),
+                min: a.min_length,
+                max: a.max_length,
+                avg: format!("{:.1}", a.avg_length),
+                p99: a.p99_length,
+                empty: format!("{:.1}%", a.empty_ratio * 100.0),
+                note,
             })
-        }).collect::<Vec<_>>(),
-    });
-    println!("{}", serde_json::to_string_pretty(&output).unwrap());
+        })
+        .collect();
+    rows.sort_by(|a, b| a.path.cmp(&b.path));
+    rows
 }
 
-fn print_scan_all_markdown(result: &ScanAllResult) {
-    println!("# Scan All Results\n");
-    println!("**Total columns scanned:** {}\n", result.total_columns);
+#[derive(Tabled)]
+pub struct TopValuesRow {
+    #[tabled(rename = "Path")]
+    pub path: String,
+    #[tabled(rename = "Values")]
+    pub values: String,
+}
 
-    let total_samples: u64 = result
-        .column_results
+/// One row per enum-like path, ordered by path
+fn top_values_rows(field_stats: &[FieldStats]) -> Vec<TopValuesRow> {
+    let mut rows: Vec<TopValuesRow> = field_stats
         .iter()
-        .map(|r| r.samples_analyzed)
-        .sum();
-    let total_critical: usize = result
-        .column_results
+        .filter_map(|fs| {
+            let top = fs.top_values.as_ref()?;
+            let values: Vec<String> = top
+                .iter()
+                .map(|vc| format!("{} {:.1}%", value_label(&vc.value), vc.percentage))
+                .collect();
+            Some(TopValuesRow {
+                path: fs.path.clone(),
+                values: values.join(", "),
+            })
+        })
+        .collect();
+    rows.sort_by(|a, b| a.path.cmp(&b.path));
+    rows
+}
+
+/// Example values as listed in reports, each cut to `EXAMPLE_WIDTH` characters
+fn example_labels(values: &[serde_json::Value]) -> Vec<String> {
+    values
+        .iter()
+        .map(|value| truncate_chars(&value_label(value), EXAMPLE_WIDTH))
+        .collect()
+}
+
+/// Strings unquoted unless empty, everything else as JSON
+pub(crate) fn value_label(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) if !s.is_empty() => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[derive(Tabled)]
+pub struct RelationshipRow {
+    #[tabled(rename = "Relationship")]
+    pub relationship: String,
+    #[tabled(rename = "Fields")]
+    pub fields: String,
+    #[tabled(rename = "Samples")]
+    pub samples: u64,
+}
+
+/// Groups, exclusive sets, then implication rules
+fn relationship_rows(cooccurrence: &Cooccurrence) -> Vec<RelationshipRow> {
+    let groups = cooccurrence.groups.iter().map(|g| RelationshipRow {
+        relationship: "always together".to_string(),
+        fields: g.paths.join(", "),
+        samples: g.occurrences,
+    });
+    let exclusive = cooccurrence.exclusive.iter().map(|g| RelationshipRow {
+        relationship: "mutually exclusive".to_string(),
+        fields: g.paths.join(", "),
+        samples: g.occurrences,
+    });
+    let implications = cooccurrence.implications.iter().map(|i| RelationshipRow {
+        relationship: "implies".to_string(),
+        fields: i.to_string(),
+        samples: i.support,
+    });
+    groups.chain(exclusive).chain(implications).collect()
+}
+
+#[derive(Tabled)]
+pub struct VersionDifferenceRow {
+    #[tabled(rename = "Marker")]
+    pub marker: String,
+    #[tabled(rename = "Field")]
+    pub path: String,
+    #[tabled(rename = "Density by Version")]
+    pub densities: String,
+}
+
+/// Fields differing between the versions of each version marker
+fn version_difference_rows(issues: &[DriftIssue]) -> Vec<VersionDifferenceRow> {
+    let mut rows = Vec::new();
+    for issue in issues {
+        let issue = match issue {
+            DriftIssue::Reclassified { issue, .. } => issue,
+            issue => issue,
+        };
+        let DriftIssue::SchemaEvolution {
+            pattern:
+                EvolutionPattern::VersionMarker {
+                    marker_path,
+                    versions,
+                    differences,
+                },
+            ..
+        } = issue
+        else {
+            continue;
+        };
+        for difference in differences {
+            let densities: Vec<String> = versions
+                .iter()
+                .zip(&difference.densities)
+                .map(|(version, density)| {
+                    format!("{}: {:.1}%", value_label(&version.value), density * 100.0)
+                })
+                .collect();
+            rows.push(VersionDifferenceRow {
+                marker: marker_path.clone(),
+                path: difference.path.clone(),
+                densities: densities.join(", "),
+            });
+        }
+    }
+    rows
+}
+
+#[derive(Tabled)]
+pub struct ConformanceRow {
+    #[tabled(rename = "Path")]
+    pub path: String,
+    #[tabled(rename = "Keyword")]
+    pub keyword: String,
+    #[tabled(rename = "Documents")]
+    pub documents: String,
+    #[tabled(rename = "Example")]
+    pub example: String,
+}
+
+fn conformance_rows(report: &ConformanceReport) -> Vec<ConformanceRow> {
+    report
+        .violations
+        .iter()
+        .map(|v| ConformanceRow {
+            path: if v.path.is_empty() {
+                "(document)".to_string()
+            } else {
+                v.path.clone()
+            },
+            keyword: v.keyword.clone(),
+            documents: format!("{} ({:.1}%)", v.documents, v.percentage),
+            example: v.example.clone(),
+        })
+        .collect()
+}
+
+#[derive(Tabled)]
+pub struct DenormalizationRow {
+    #[tabled(rename = "Path")]
+    pub path: String,
+    #[tabled(rename = "Column")]
+    pub column: String,
+    #[tabled(rename = "Matched By")]
+    pub matched_by: String,
+    #[tabled(rename = "Mismatches")]
+    pub mismatches: String,
+}
+
+fn denormalization_rows(denormalization: &[Denormalization]) -> Vec<DenormalizationRow> {
+    denormalization
+        .iter()
+        .map(|d| DenormalizationRow {
+            path: d.path.clone(),
+            column: d.column.clone(),
+            matched_by: if d.same_name { "name" } else { "values" }.to_string(),
+            mismatches: format!(
+                "{} of {} ({:.1}%)",
+                d.mismatches, d.compared, d.mismatch_percentage
+            ),
+        })
+        .collect()
+}
+
+#[derive(Tabled)]
+pub struct UnusedFieldRow {
+    #[tabled(rename = "Path")]
+    pub path: String,
+    #[tabled(rename = "Density")]
+    pub density: String,
+    #[tabled(rename = "Storage per 1M Docs")]
+    pub storage: String,
+    #[tabled(rename = "Deprecation Candidate")]
+    pub candidate: String,
+}
+
+fn unused_field_rows(unused_fields: &[UnusedField]) -> Vec<UnusedFieldRow> {
+    unused_fields
+        .iter()
+        .map(|u| UnusedFieldRow {
+            path: u.path.clone(),
+            density: format!("{:.1}%", u.density * 100.0),
+            storage: format_bytes((u.bytes_per_document * 1_000_000.0) as i64),
+            candidate: if u.candidate { "yes" } else { "no" }.to_string(),
+        })
+        .collect()
+}
+
+fn conformance_summary(report: &ConformanceReport) -> String {
+    format!(
+        "{:.1}% of {} documents valid ({} failing)",
+        report.conformance_percentage(),
+        report.documents,
+        report.failing_documents
+    )
+}
+
+#[derive(Tabled)]
+pub struct SuppressionRow {
+    #[tabled(rename = "Path")]
+    pub path: String,
+    #[tabled(rename = "Until")]
+    pub until: String,
+    #[tabled(rename = "Reason")]
+    pub reason: String,
+}
+
+pub(crate) fn suppression_rows(suppressions: &[Suppression]) -> Vec<SuppressionRow> {
+    suppressions
+        .iter()
+        .map(|s| SuppressionRow {
+            path: s.path.clone(),
+            until: s.until.map(|d| d.to_string()).unwrap_or_default(),
+            reason: s.reason.clone().unwrap_or_default(),
+        })
+        .collect()
+}
+
+fn print_expiring_suppressions_table(suppressions: &[Suppression]) {
+    if suppressions.is_empty() {
+        return;
+    }
+    report!("\n{}", "Expiring Suppressions:".bold());
+    let mut table = Table::new(suppression_rows(suppressions));
+    table.with(Style::rounded());
+    report!("{}", table);
+}
+
+fn print_expiring_suppressions_markdown(suppressions: &[Suppression]) {
+    if suppressions.is_empty() {
+        return;
+    }
+    report!("\n## Expiring Suppressions\n");
+    report!("| Path | Until | Reason |");
+    report!("|------|-------|--------|");
+    for row in suppression_rows(suppressions) {
+        report!(
+            "| {} | {} | {} |",
+            row.path,
+            row.until,
+            markdown_cell(&row.reason)
+        );
+    }
+}
+
+/// Text escaped for a markdown table cell, where `|` ends the cell and a
+/// line break the row
+fn markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+/// Health score as shown in tables, `-` for columns that weren't scored
+pub(crate) fn score_label(score: Option<u8>) -> String {
+    score.map_or_else(|| "-".to_string(), |s| s.to_string())
+}
+
+/// Health score colored by how worrying it is
+fn colored_score(score: u8) -> ColoredString {
+    let label = format!("{}/100", score);
+    match score {
+        80.. => label.green(),
+        50..80 => label.yellow(),
+        _ => label.red(),
+    }
+}
+
+/// Whole numbers without decimals, everything else to two places
+fn format_number(n: f64) -> String {
+    if n.fract() == 0.0 && n.abs() < 1e15 {
+        format!("{}", n as i64)
+    } else {
+        format!("{:.2}", n)
+    }
+}
+
+pub struct AnalysisResult {
+    pub table: String,
+    pub column: String,
+    pub samples_analyzed: u64,
+    pub field_stats: Vec<FieldStats>,
+    pub drift_issues: Vec<DriftIssue>,
+    /// Issues left out because their path matches an `ignore` pattern
+    pub suppressed_issues: usize,
+    /// Configured suppressions about to run out
+    pub expiring_suppressions: Vec<Suppression>,
+    pub truncation: Truncation,
+    pub cooccurrence: Option<Cooccurrence>,
+    /// Drift health from 0 to 100, see `pgdrift_core::score::health_score`
+    pub health_score: u8,
+    /// Violations of the JSON Schema given with `--json-schema`
+    pub conformance: Option<ConformanceReport>,
+    /// SQL normalizing drifted values, when asked for with `--remediation`
+    pub remediations: Option<Vec<Remediation>>,
+    /// JSON paths duplicating other columns, when asked for with `--denormalization`
+    pub denormalization: Option<Vec<Denormalization>>,
+    /// JSON paths no recorded query refers to, when asked for with `--unused-fields`
+    pub unused_fields: Option<Vec<UnusedField>>,
+}
+
+pub struct ColumnScanResult {
+    pub schema: String,
+    pub table: String,
+    pub column: String,
+    pub samples_analyzed: u64,
+    pub drift_issues: Vec<DriftIssue>,
+    /// Issues left out because their path matches an `ignore` pattern
+    pub suppressed_issues: usize,
+    /// Drift health from 0 to 100, `None` if the column couldn't be analyzed
+    pub health_score: Option<u8>,
+}
+
+pub struct ScanAllResult {
+    pub total_columns: usize,
+    pub column_results: Vec<ColumnScanResult>,
+    /// Configured suppressions about to run out
+    pub expiring_suppressions: Vec<Suppression>,
+}
+
+/// Order of the columns in the details of a scan
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ColumnSort {
+    /// Alphabetical by schema, table and column
+    Path,
+    /// Most critical issues first, then most warnings, then most info issues
+    Issues,
+    /// Lowest health score first
+    Score,
+}
+
+/// How the columns of a scan are listed in table, markdown and HTML output
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScanAllView {
+    /// Only print the totals, leaving out the details of each column
+    pub summary_only: bool,
+    /// List the columns under their tables, with a section on the issues of
+    /// each column (markdown and HTML)
+    pub grouped: bool,
+    /// Order of the columns, the order they were scanned in if `None`
+    pub sort: Option<ColumnSort>,
+    pub page: Page,
+}
+
+impl ScanAllResult {
+    /// Columns in the view's order, before the view's page is applied
+    pub(crate) fn sorted_columns(&self, view: &ScanAllView) -> Vec<&ColumnScanResult> {
+        let mut columns: Vec<&ColumnScanResult> = self.column_results.iter().collect();
+        let path = |c: &ColumnScanResult| (c.schema.clone(), c.table.clone(), c.column.clone());
+        match view.sort {
+            None => {}
+            Some(ColumnSort::Path) => columns.sort_by_key(|c| path(c)),
+            Some(ColumnSort::Issues) => {
+                columns.sort_by_key(|c| (severity_rank(&c.drift_issues), path(c)))
+            }
+            // Columns that couldn't be analyzed go last
+            Some(ColumnSort::Score) => {
+                columns.sort_by_key(|c| (c.health_score.unwrap_or(u8::MAX), path(c)))
+            }
+        }
+        columns
+    }
+
+    /// Issues suppressed across all columns
+    pub fn total_suppressed(&self) -> usize {
+        self.column_results
+            .iter()
+            .map(|r| r.suppressed_issues)
+            .sum()
+    }
+
+    /// Columns grouped by table, tables ordered by schema and name and
+    /// columns by name
+    pub(crate) fn table_groups(&self) -> Vec<TableGroup<'_>> {
+        let mut groups: BTreeMap<(&str, &str), Vec<&ColumnScanResult>> = BTreeMap::new();
+        for col in &self.column_results {
+            groups
+                .entry((&col.schema, &col.table))
+                .or_default()
+                .push(col);
+        }
+        groups
+            .into_iter()
+            .map(|((schema, table), mut columns)| {
+                columns.sort_by(|a, b| a.column.cmp(&b.column));
+                TableGroup {
+                    schema,
+                    table,
+                    columns,
+                }
+            })
+            .collect()
+    }
+}
+
+impl ColumnScanResult {
+    pub(crate) fn name(&self) -> String {
+        format!("{}.{}.{}", self.schema, self.table, self.column)
+    }
+
+    /// Whether the grouped report has a section on the column: if it has
+    /// issues or couldn't be analyzed
+    pub(crate) fn has_details(&self) -> bool {
+        !self.drift_issues.is_empty() || self.health_score.is_none()
+    }
+
+    pub(crate) fn anchor(&self) -> String {
+        anchor(&[&self.schema, &self.table, &self.column])
+    }
+}
+
+/// The columns of one table of a scan, for the report grouped by table
+pub(crate) struct TableGroup<'a> {
+    pub schema: &'a str,
+    pub table: &'a str,
+    pub columns: Vec<&'a ColumnScanResult>,
+}
+
+impl TableGroup<'_> {
+    pub fn name(&self) -> String {
+        format!("{}.{}", self.schema, self.table)
+    }
+
+    /// Issues of all the table's columns, per severity
+    pub fn counts(&self) -> IssueCounts {
+        IssueCounts::from_issues(self.columns.iter().flat_map(|c| &c.drift_issues))
+    }
+
+    /// Lowest health score of the table's columns, `None` if none was scored
+    pub fn lowest_score(&self) -> Option<u8> {
+        self.columns.iter().filter_map(|c| c.health_score).min()
+    }
+
+    pub fn anchor(&self) -> String {
+        anchor(&[self.schema, self.table])
+    }
+}
+
+/// Link target of a table or column in the grouped reports: its name
+/// lowercased, with anything but letters, digits and underscores as hyphens
+pub(crate) fn anchor(parts: &[&str]) -> String {
+    parts
+        .join(".")
+        .to_lowercase()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+#[derive(Tabled)]
+pub struct ScanAllRow {
+    #[tabled(rename = "Schema")]
+    pub schema: String,
+    #[tabled(rename = "Table")]
+    pub table: String,
+    #[tabled(rename = "Column")]
+    pub column: String,
+    #[tabled(rename = "Samples")]
+    pub samples: String,
+    #[tabled(rename = "Critical")]
+    pub critical: String,
+    #[tabled(rename = "Warning")]
+    pub warning: String,
+    #[tabled(rename = "Info")]
+    pub info: String,
+    #[tabled(rename = "Total Issues")]
+    pub total: String,
+    #[tabled(rename = "Score")]
+    pub score: String,
+}
+
+impl From<&ColumnScanResult> for ScanAllRow {
+    fn from(result: &ColumnScanResult) -> Self {
+        let critical = result
+            .drift_issues
+            .iter()
+            .filter(|i| i.severity() == Severity::Critical)
+            .count();
+        let warning = result
+            .drift_issues
+            .iter()
+            .filter(|i| i.severity() == Severity::Warning)
+            .count();
+        let info = result
+            .drift_issues
+            .iter()
+            .filter(|i| i.severity() == Severity::Info)
+            .count();
+        let total = result.drift_issues.len();
+
+        Self {
+            schema: result.schema.clone(),
+            table: result.table.clone(),
+            column: result.column.clone(),
+            samples: result.samples_analyzed.to_string(),
+            critical: critical.to_string(),
+            warning: warning.to_string(),
+            info: info.to_string(),
+            total: total.to_string(),
+            score: score_label(result.health_score),
+        }
+    }
+}
+
+/// Print a scan, listing its columns as the view says (in every format but
+/// JSON, which always lists all of them)
+pub fn print_scan_all_summary(
+    result: &ScanAllResult,
+    format: &OutputFormat,
+    view: &ScanAllView,
+) -> anyhow::Result<()> {
+    match format {
+        OutputFormat::Table => print_scan_all_table(result, view),
+        OutputFormat::Json => print_scan_all_json(result),
+        OutputFormat::Markdown => print_scan_all_markdown(result, view),
+        OutputFormat::Github => print_scan_all_github(result),
+        OutputFormat::Html => report!("{}", html::scan_all_page(result, view).trim_end()),
+    }
+    Ok(())
+}
+
+fn print_scan_all_json(result: &ScanAllResult) {
+    print_json(scan_all_report(result));
+}
+
+/// Print a scan-all summary through a user-supplied Tera template
+pub fn print_scan_all_template(result: &ScanAllResult, template: &Path) -> anyhow::Result<()> {
+    print_template(template, scan_all_report(result))
+}
+
+fn scan_all_report(result: &ScanAllResult) -> ScanAllReport {
+    let columns = result
+        .column_results
+        .iter()
+        .map(|col| ColumnReport {
+            schema: col.schema.clone(),
+            table: col.table.clone(),
+            column: col.column.clone(),
+            samples_analyzed: col.samples_analyzed,
+            health_score: col.health_score,
+            drift_issues: col.drift_issues.iter().map(Into::into).collect(),
+            issue_counts: ColumnIssueCounts {
+                counts: IssueCounts::from_issues(&col.drift_issues),
+                suppressed: col.suppressed_issues,
+            },
+        })
+        .collect();
+    ScanAllReport::new(
+        result.total_columns,
+        columns,
+        expiring_suppressions(&result.expiring_suppressions),
+    )
+}
+
+fn print_scan_all_markdown(result: &ScanAllResult, view: &ScanAllView) {
+    report!("# Scan All Results\n");
+    report!("**Total columns scanned:** {}\n", result.total_columns);
+
+    let total_samples: u64 = result
+        .column_results
+        .iter()
+        .map(|r| r.samples_analyzed)
+        .sum();
+    let total_critical: usize = result
+        .column_results
         .iter()
         .flat_map(|r| &r.drift_issues)
         .filter(|i| i.severity() == Severity::Critical)
@@ -274,20 +1419,31 @@ fn print_scan_all_markdown(result: &ScanAllResult) {
         .filter(|i| i.severity() == Severity::Info)
         .count();
 
-    println!("## Summary\n");
-    println!("- Total samples analyzed: {}", total_samples);
-    println!(
-        "- Total issues found: {} ({} critical, {} warning, {} info)\n",
+    report!("## Summary\n");
+    report!("- Total samples analyzed: {}", total_samples);
+    report!(
+        "- Total issues found: {} ({} critical, {} warning, {} info)",
         total_critical + total_warning + total_info,
         total_critical,
         total_warning,
         total_info
     );
+    report!("- Suppressed issues: {}\n", result.total_suppressed());
+    if view.summary_only {
+        return;
+    }
+    if view.grouped {
+        print_scan_all_grouped_markdown(result);
+        print_expiring_suppressions_markdown(&result.expiring_suppressions);
+        return;
+    }
 
-    println!("## Column Details\n");
-    println!("| Schema | Table | Column | Samples | Critical | Warning | Info | Total |");
-    println!("|--------|-------|--------|---------|----------|---------|------|-------|");
-    for col in &result.column_results {
+    let columns = result.sorted_columns(view);
+    let note = view.page.note(columns.len(), "columns");
+    report!("## Column Details\n");
+    report!("| Schema | Table | Column | Samples | Critical | Warning | Info | Total | Score |");
+    report!("|--------|-------|--------|---------|----------|---------|------|-------|-------|");
+    for col in view.page.apply(columns) {
         let critical = col
             .drift_issues
             .iter()
@@ -303,8 +1459,8 @@ fn print_scan_all_markdown(result: &ScanAllResult) {
             .iter()
             .filter(|i| i.severity() == Severity::Info)
             .count();
-        println!(
-            "| {} | {} | {} | {} | {} | {} | {} | {} |",
+        report!(
+            "| {} | {} | {} | {} | {} | {} | {} | {} | {} |",
             col.schema,
             col.table,
             col.column,
@@ -312,13 +1468,83 @@ fn print_scan_all_markdown(result: &ScanAllResult) {
             critical,
             warning,
             info,
-            col.drift_issues.len()
+            col.drift_issues.len(),
+            score_label(col.health_score)
+        );
+    }
+    if let Some(note) = note {
+        report!("\n_{}_", note);
+    }
+    print_expiring_suppressions_markdown(&result.expiring_suppressions);
+}
+
+/// Scan details grouped by table: a roll-up of the tables linking to a
+/// section on each, which lists its columns and links to their issues
+fn print_scan_all_grouped_markdown(result: &ScanAllResult) {
+    let groups = result.table_groups();
+    report!("## <a id=\"tables\"></a>Tables\n");
+    report!("| Table | Columns | Lowest Score | Critical | Warning | Info | Total |");
+    report!("|-------|---------|--------------|----------|---------|------|-------|");
+    for group in &groups {
+        let counts = group.counts();
+        report!(
+            "| [{}](#{}) | {} | {} | {} | {} | {} | {} |",
+            markdown_cell(&group.name()),
+            group.anchor(),
+            group.columns.len(),
+            score_label(group.lowest_score()),
+            counts.critical,
+            counts.warning,
+            counts.info,
+            counts.total()
         );
     }
+
+    for group in &groups {
+        report!("\n## <a id=\"{}\"></a>{}\n", group.anchor(), group.name());
+        report!("| Column | Samples | Critical | Warning | Info | Score |");
+        report!("|--------|---------|----------|---------|------|-------|");
+        for col in &group.columns {
+            let counts = IssueCounts::from_issues(&col.drift_issues);
+            let column = if col.has_details() {
+                format!("[{}](#{})", markdown_cell(&col.column), col.anchor())
+            } else {
+                markdown_cell(&col.column)
+            };
+            report!(
+                "| {} | {} | {} | {} | {} | {} |",
+                column,
+                col.samples_analyzed,
+                counts.critical,
+                counts.warning,
+                counts.info,
+                score_label(col.health_score)
+            );
+        }
+
+        for col in group.columns.iter().filter(|c| c.has_details()) {
+            report!("\n### <a id=\"{}\"></a>{}\n", col.anchor(), col.name());
+            if col.health_score.is_none() {
+                report!("_Not analyzed, see the errors of the run._");
+            } else {
+                report!("| Path | Severity | Issue |");
+                report!("|------|----------|-------|");
+                for issue in &col.drift_issues {
+                    report!(
+                        "| {} | {:?} | {} |",
+                        markdown_cell(issue.path()),
+                        issue.severity(),
+                        markdown_cell(&issue.description())
+                    );
+                }
+            }
+            report!("\n[Back to {}](#{})", group.name(), group.anchor());
+        }
+    }
 }
 
-fn print_scan_all_table(result: &ScanAllResult) {
-    println!(
+fn print_scan_all_table(result: &ScanAllResult, view: &ScanAllView) {
+    report!(
         "\n{} - Scanned {} column(s)\n",
         "Scan All Complete".bold().green(),
         result.total_columns
@@ -350,34 +1576,48 @@ fn print_scan_all_table(result: &ScanAllResult) {
         .count();
     let total_issues = total_critical + total_warning + total_info;
 
-    println!("{}", "Overall Summary:".bold());
-    println!("  Total samples analyzed: {}", total_samples);
-    println!("  Total issues found: {}", total_issues);
+    report!("{}", "Overall Summary:".bold());
+    report!("  Total samples analyzed: {}", total_samples);
+    report!("  Total issues found: {}", total_issues);
     if total_critical > 0 {
-        println!("    Critical: {}", total_critical.to_string().red());
+        report!("    Critical: {}", total_critical.to_string().red());
     }
     if total_warning > 0 {
-        println!("    Warning: {}", total_warning.to_string().yellow());
+        report!("    Warning: {}", total_warning.to_string().yellow());
     }
     if total_info > 0 {
-        println!("    Info: {}", total_info.to_string().cyan());
+        report!("    Info: {}", total_info.to_string().cyan());
+    }
+    if result.total_suppressed() > 0 {
+        report!("  Suppressed issues: {}", result.total_suppressed());
+    }
+    if view.summary_only {
+        return;
     }
 
     if result.column_results.is_empty() {
-        println!("\n{}", "No columns analyzed.".yellow());
+        report!("\n{}", "No columns analyzed.".yellow());
         return;
     }
 
-    println!("\n{}", "Column Details:".bold());
-    let rows: Vec<ScanAllRow> = result.column_results.iter().map(|r| r.into()).collect();
-    let mut table = Table::new(rows);
-    table.with(Style::rounded());
-    println!("{}", table);
+    let columns = result.sorted_columns(view);
+    let note = view.page.note(columns.len(), "columns");
+    let columns = view.page.apply(columns);
+    report!("\n{}", "Column Details:".bold());
+    if !columns.is_empty() {
+        let rows: Vec<ScanAllRow> = columns.iter().map(|r| (*r).into()).collect();
+        let mut table = Table::new(rows);
+        table.with(Style::rounded());
+        report!("{}", table);
+    }
+    if let Some(note) = note {
+        report!("  {}", note.dimmed());
+    }
 
     // Highlight columns with critical issues
-    let critical_columns: Vec<&ColumnScanResult> = result
-        .column_results
+    let critical_columns: Vec<&ColumnScanResult> = columns
         .iter()
+        .copied()
         .filter(|r| {
             r.drift_issues
                 .iter()
@@ -386,9 +1626,9 @@ fn print_scan_all_table(result: &ScanAllResult) {
         .collect();
 
     if !critical_columns.is_empty() {
-        println!("\n{} Columns with critical issues:", "*".red().bold());
+        report!("\n{} Columns with critical issues:", "*".red().bold());
         for col in critical_columns {
-            println!(
+            report!(
                 "  • {}.{}.{}",
                 col.schema.dimmed(),
                 col.table,
@@ -398,9 +1638,9 @@ fn print_scan_all_table(result: &ScanAllResult) {
     }
 
     // Highlight columns with warnings
-    let warning_columns: Vec<&ColumnScanResult> = result
-        .column_results
+    let warning_columns: Vec<&ColumnScanResult> = columns
         .iter()
+        .copied()
         .filter(|r| {
             r.drift_issues
                 .iter()
@@ -409,9 +1649,9 @@ fn print_scan_all_table(result: &ScanAllResult) {
         .collect();
 
     if !warning_columns.is_empty() {
-        println!("\n{} Columns with warnings:", "*".yellow().bold());
+        report!("\n{} Columns with warnings:", "*".yellow().bold());
         for col in warning_columns {
-            println!(
+            report!(
                 "  • {}.{}.{}",
                 col.schema.dimmed(),
                 col.table,
@@ -420,9 +1660,9 @@ fn print_scan_all_table(result: &ScanAllResult) {
         }
     }
 
-    let info_columns: Vec<&ColumnScanResult> = result
-        .column_results
+    let info_columns: Vec<&ColumnScanResult> = columns
         .iter()
+        .copied()
         .filter(|r| {
             r.drift_issues
                 .iter()
@@ -431,9 +1671,9 @@ fn print_scan_all_table(result: &ScanAllResult) {
         .collect();
 
     if !info_columns.is_empty() {
-        println!("\n{} Columns with info issues:", "*".cyan().bold());
+        report!("\n{} Columns with info issues:", "*".cyan().bold());
         for col in info_columns {
-            println!(
+            report!(
                 "  • {}.{}.{}",
                 col.schema.dimmed(),
                 col.table,
@@ -442,38 +1682,304 @@ fn print_scan_all_table(result: &ScanAllResult) {
         }
     }
 
-    println!();
+    print_expiring_suppressions_table(&result.expiring_suppressions);
+    report!();
 }
 
-pub fn print_analysis(result: &AnalysisResult, format: &OutputFormat) {
+/// Print an analysis, with the optional sections of `view` in the table and
+/// markdown formats
+pub fn print_analysis(result: &AnalysisResult, format: &OutputFormat, view: &AnalysisView) {
     match format {
-        OutputFormat::Table => print_analysis_table(result),
-        OutputFormat::Json => print_analysis_json(result),
-        OutputFormat::Markdown => print_analysis_markdown(result),
-    }
-}
-
-fn print_analysis_json(result: &AnalysisResult) {
-    let output = json!({
-        "table": result.table,
-        "column": result.column,
-        "samples_analyzed": result.samples_analyzed,
-        "field_stats": result.field_stats,
-        "drift_issues": result.drift_issues,
-        "summary": {
-            "total_paths": result.field_stats.len(),
-            "max_depth": result.field_stats.iter().map(|fs| fs.depth).max().unwrap_or(0),
-            "critical_issues": result.drift_issues.iter().filter(|di| di.severity() == Severity::Critical).count(),
-            "warning_issues": result.drift_issues.iter().filter(|di| di.severity() == Severity::Warning).count(),
-            "info_issues": result.drift_issues.iter().filter(|di| di.severity() == Severity::Info).count(),
+        OutputFormat::Table => print_analysis_table(result, view),
+        OutputFormat::Json => print_analysis_json(result, view.examples),
+        OutputFormat::Markdown | OutputFormat::Html => print_analysis_markdown(result, view),
+        OutputFormat::Github => print_analysis_github(result),
+    }
+}
+
+fn print_analysis_github(result: &AnalysisResult) {
+    let location = format!("{}.{}", result.table, result.column);
+    for line in github::annotations(&location, &result.drift_issues) {
+        report!("{}", line);
+    }
+    print_github_comment(&github::analysis_comment(result));
+}
+
+fn print_scan_all_github(result: &ScanAllResult) {
+    for r in &result.column_results {
+        let location = format!("{}.{}.{}", r.schema, r.table, r.column);
+        for line in github::annotations(&location, &r.drift_issues) {
+            report!("{}", line);
         }
-    });
-    println!("{}", serde_json::to_string_pretty(&output).unwrap());
+    }
+    print_github_comment(&github::scan_all_comment(result));
+}
+
+/// Print a pull request comment, and add it to the job summary in GitHub Actions
+fn print_github_comment(comment: &str) {
+    report!("{}", comment.trim_end());
+    if let Err(e) = github::append_step_summary(comment) {
+        eprintln!("Warning: {:#}", e);
+    }
+}
+
+fn print_analysis_json(result: &AnalysisResult, examples: Option<usize>) {
+    print_json(analysis_report(result, examples));
+}
+
+/// Print an analysis through a user-supplied Tera template
+pub fn print_analysis_template(
+    result: &AnalysisResult,
+    template: &Path,
+    examples: Option<usize>,
+) -> anyhow::Result<()> {
+    print_template(template, analysis_report(result, examples))
+}
+
+/// Report of an analysis, with up to `examples` example values of each path
+/// with issues if set
+fn analysis_report(result: &AnalysisResult, examples: Option<usize>) -> AnalysisReport {
+    AnalysisReport {
+        table: result.table.clone(),
+        column: result.column.clone(),
+        samples_analyzed: result.samples_analyzed,
+        field_stats: result.field_stats.iter().map(Into::into).collect(),
+        drift_issues: result.drift_issues.iter().map(Into::into).collect(),
+        summary: AnalysisSummary::new(
+            &result.field_stats,
+            &result.drift_issues,
+            result.health_score,
+            result.suppressed_issues,
+        ),
+        truncation: Some(&result.truncation)
+            .filter(|t| t.is_truncated())
+            .map(Into::into),
+        cooccurrence: result.cooccurrence.as_ref().map(Into::into),
+        conformance: result.conformance.as_ref().map(Into::into),
+        remediations: result
+            .remediations
+            .as_ref()
+            .map(|r| r.iter().map(Into::into).collect()),
+        denormalization: result
+            .denormalization
+            .as_ref()
+            .map(|d| d.iter().map(Into::into).collect()),
+        unused_fields: result
+            .unused_fields
+            .as_ref()
+            .map(|u| u.iter().map(Into::into).collect()),
+        expiring_suppressions: expiring_suppressions(&result.expiring_suppressions),
+        issue_examples: examples.map(|limit| {
+            issue_examples(&result.field_stats, &result.drift_issues, limit)
+                .into_iter()
+                .map(|(path, examples)| (path.to_string(), examples.to_vec()))
+                .collect()
+        }),
+    }
+}
+
+fn expiring_suppressions(suppressions: &[Suppression]) -> Vec<ExpiringSuppression> {
+    suppressions
+        .iter()
+        .map(|s| ExpiringSuppression {
+            path: s.path.clone(),
+            until: s.until.map(|until| until.to_string()),
+            reason: s.reason.clone(),
+        })
+        .collect()
+}
+
+/// Print the field hierarchy of an analysis as a Mermaid or DOT graph, in
+/// place of the report
+pub fn print_schema_diagram(result: &AnalysisResult, format: DiagramFormat) {
+    let root = format!("{}.{}", result.table, result.column);
+    report!(
+        "{}",
+        schema_diagram(format, &root, &result.field_stats).trim_end()
+    );
+}
+
+/// Analysis of the documents sharing one value of the `--segment-by` field
+/// or `--group-by` column
+pub struct SegmentResult {
+    pub value: String,
+    pub result: AnalysisResult,
+}
+
+pub fn print_segmented_analysis(
+    segment_by: &str,
+    segments: &[SegmentResult],
+    format: &OutputFormat,
+    view: &AnalysisView,
+) {
+    match format {
+        OutputFormat::Json => {
+            print_json(SegmentsReport {
+                segment_by: segment_by.to_string(),
+                segments: segments
+                    .iter()
+                    .map(|s| SegmentReport {
+                        segment: s.value.clone(),
+                        analysis: analysis_report(&s.result, view.examples),
+                    })
+                    .collect(),
+            });
+        }
+        OutputFormat::Table => {
+            for segment in segments {
+                report!(
+                    "\n{} {} = {} ({} samples)",
+                    "Segment".bold().blue(),
+                    segment_by,
+                    segment.value.bold(),
+                    segment.result.samples_analyzed
+                );
+                print_analysis_table(&segment.result, view);
+            }
+        }
+        OutputFormat::Markdown | OutputFormat::Github | OutputFormat::Html => {
+            for segment in segments {
+                report!(
+                    "# Segment: {} = {} ({} samples)\n",
+                    segment_by,
+                    segment.value,
+                    segment.result.samples_analyzed
+                );
+                print_analysis_markdown(&segment.result, view);
+                report!();
+            }
+        }
+    }
+}
+
+#[derive(Tabled)]
+pub struct GroupRow {
+    #[tabled(rename = "Group")]
+    pub group: String,
+    #[tabled(rename = "Samples")]
+    pub samples: u64,
+    #[tabled(rename = "Score")]
+    pub score: u8,
+    #[tabled(rename = "Critical")]
+    pub critical: usize,
+    #[tabled(rename = "Warning")]
+    pub warning: usize,
+    #[tabled(rename = "Drifting Paths")]
+    pub paths: String,
+}
+
+/// Paths listed per group in the summary, the rest are counted
+const MAX_GROUP_PATHS: usize = 3;
+
+impl From<&SegmentResult> for GroupRow {
+    fn from(group: &SegmentResult) -> Self {
+        let issues = &group.result.drift_issues;
+        let count = |severity| issues.iter().filter(|i| i.severity() == severity).count();
+        let mut paths: Vec<&str> = issues
+            .iter()
+            .filter(|i| i.severity() >= Severity::Warning)
+            .map(|i| i.path())
+            .collect();
+        paths.sort();
+        paths.dedup();
+        let mut listed: Vec<String> = paths
+            .iter()
+            .take(MAX_GROUP_PATHS)
+            .map(|p| p.to_string())
+            .collect();
+        if paths.len() > MAX_GROUP_PATHS {
+            listed.push(format!("{} more", paths.len() - MAX_GROUP_PATHS));
+        }
+        Self {
+            group: group.value.clone(),
+            samples: group.result.samples_analyzed,
+            score: group.result.health_score,
+            critical: count(Severity::Critical),
+            warning: count(Severity::Warning),
+            paths: listed.join(", "),
+        }
+    }
+}
+
+/// Groups from the lowest health score up, so drifting tenants come first
+fn groups_by_score(groups: &[SegmentResult]) -> Vec<&SegmentResult> {
+    let mut sorted: Vec<&SegmentResult> = groups.iter().collect();
+    sorted.sort_by_key(|g| g.result.health_score);
+    sorted
+}
+
+/// Print the analysis of each value of a `--group-by` column, after a summary
+/// of which groups drift
+pub fn print_grouped_analysis(
+    group_by: &str,
+    groups: &[SegmentResult],
+    format: &OutputFormat,
+    view: &AnalysisView,
+) {
+    let groups = groups_by_score(groups);
+    match format {
+        OutputFormat::Json => {
+            print_json(GroupsReport {
+                group_by: group_by.to_string(),
+                groups: groups
+                    .iter()
+                    .map(|g| GroupReport {
+                        group: g.value.clone(),
+                        analysis: analysis_report(&g.result, view.examples),
+                    })
+                    .collect(),
+            });
+        }
+        OutputFormat::Table => {
+            report!("\n{}", format!("Drift by {}:", group_by).bold());
+            let mut table = Table::new(groups.iter().map(|g| GroupRow::from(*g)));
+            table.with(Style::rounded());
+            report!("{}", table);
+            for group in groups {
+                report!(
+                    "\n{} {} = {} ({} samples)",
+                    "Group".bold().blue(),
+                    group_by,
+                    group.value.bold(),
+                    group.result.samples_analyzed
+                );
+                print_analysis_table(&group.result, view);
+            }
+        }
+        OutputFormat::Markdown | OutputFormat::Github | OutputFormat::Html => {
+            report!("# Drift by {}\n", group_by);
+            report!("| Group | Samples | Score | Critical | Warning | Drifting Paths |");
+            report!("|-------|---------|-------|----------|---------|----------------|");
+            for group in &groups {
+                let row = GroupRow::from(*group);
+                report!(
+                    "| {} | {} | {} | {} | {} | {} |",
+                    row.group,
+                    row.samples,
+                    row.score,
+                    row.critical,
+                    row.warning,
+                    row.paths
+                );
+            }
+            report!();
+            for group in groups {
+                report!(
+                    "# Group: {} = {} ({} samples)\n",
+                    group_by,
+                    group.value,
+                    group.result.samples_analyzed
+                );
+                print_analysis_markdown(&group.result, view);
+                report!();
+            }
+        }
+    }
 }
 
-fn print_analysis_markdown(result: &AnalysisResult) {
-    println!("# Schema Analysis: {}.{}\n", result.table, result.column);
-    println!("**Samples analyzed:** {}\n", result.samples_analyzed);
+fn print_analysis_markdown(result: &AnalysisResult, view: &AnalysisView) {
+    report!("# Schema Analysis: {}.{}\n", result.table, result.column);
+    report!("**Samples analyzed:** {}\n", result.samples_analyzed);
 
     let max_depth = result
         .field_stats
@@ -497,33 +2003,260 @@ fn print_analysis_markdown(result: &AnalysisResult) {
         .filter(|di| di.severity() == Severity::Info)
         .count();
 
-    println!("## Summary\n");
-    println!("- Total unique paths: {}", result.field_stats.len());
-    println!("- Max nesting depth: {}", max_depth);
-    println!(
-        "- Issues found: {} critical, {} warnings, {} info\n",
-        critical_count, warning_count, info_count
+    report!("## Summary\n");
+    report!("- Health score: {}/100", result.health_score);
+    report!("- Total unique paths: {}", result.field_stats.len());
+    report!("- Max nesting depth: {}", max_depth);
+    report!(
+        "- Issues found: {} critical, {} warnings, {} info",
+        critical_count,
+        warning_count,
+        info_count
     );
+    report!("- Suppressed issues: {}\n", result.suppressed_issues);
+    if view.summary_only {
+        return;
+    }
 
     if !result.drift_issues.is_empty() {
-        println!("## Drift Issues\n");
-        println!("| Path | Severity | Issue |");
-        println!("|------|----------|-------|");
+        report!("## Drift Issues\n");
+        report!("| Path | Severity | Issue |");
+        report!("|------|----------|-------|");
         for issue in &result.drift_issues {
-            println!(
+            report!(
                 "| {} | {:?} | {} |",
                 issue.path(),
                 issue.severity(),
-                issue.description()
+                markdown_cell(&issue.description())
             );
         }
     } else {
-        println!("**No drift issues found!**\n");
+        report!("**No drift issues found!**\n");
+    }
+
+    if let Some(limit) = view.examples {
+        let examples = issue_examples(&result.field_stats, &result.drift_issues, limit);
+        if !examples.is_empty() {
+            report!("\n## Examples\n");
+            report!("| Path | Examples |");
+            report!("|------|----------|");
+            for (path, values) in examples {
+                report!(
+                    "| {} | {} |",
+                    path,
+                    markdown_cell(&example_labels(values).join(", "))
+                );
+            }
+        }
+    }
+
+    if let Some(stats_view) = &view.field_stats {
+        let rows = field_stats_rows(
+            &result.field_stats,
+            &result.drift_issues,
+            stats_view,
+            view.examples.unwrap_or(LISTED_EXAMPLES),
+        );
+        let note = stats_view.page.note(rows.len(), "paths");
+        let rows = stats_view.page.apply(rows);
+        report!("\n## Field Stats\n");
+        if rows.is_empty() && note.is_none() {
+            report!("No paths match.");
+        } else if !rows.is_empty() {
+            report!("| Path | Density | Types | Depth | Issues | Examples |");
+            report!("|------|---------|-------|-------|--------|----------|");
+            for row in &rows {
+                report!(
+                    "| {} | {} | {} | {} | {} | {} |",
+                    row.path,
+                    row.density,
+                    row.types,
+                    row.depth,
+                    row.issues,
+                    markdown_cell(&row.examples)
+                );
+            }
+        }
+        if let Some(note) = note {
+            report!("\n_{}_", note);
+        }
+    }
+
+    if view.tree {
+        report!("\n## Field Tree\n");
+        report!("```");
+        for line in tree_lines(&result.column, &result.field_stats) {
+            report!("{}", line);
+        }
+        report!("```");
+    }
+
+    let numeric = numeric_rows(&result.field_stats);
+    if !numeric.is_empty() {
+        report!("\n## Numeric Fields\n");
+        report!("| Path | Min | Max | Mean | P50 | P90 | P99 |");
+        report!("|------|-----|-----|------|-----|-----|-----|");
+        for row in &numeric {
+            report!(
+                "| {} | {} | {} | {} | {} | {} | {} |",
+                row.path,
+                row.min,
+                row.max,
+                row.mean,
+                row.p50,
+                row.p90,
+                row.p99
+            );
+        }
+    }
+
+    let formats = format_rows(&result.field_stats);
+    if !formats.is_empty() {
+        report!("\n## String Formats\n");
+        report!("| Path | Format | Consistency |");
+        report!("|------|--------|-------------|");
+        for row in &formats {
+            report!("| {} | {} | {} |", row.path, row.format, row.consistency);
+        }
+    }
+
+    let arrays = array_rows(&result.field_stats);
+    if !arrays.is_empty() {
+        report!("\n## Array Fields\n");
+        report!("| Path | Min | Max | Avg | P99 | Empty | Note |");
+        report!("|------|-----|-----|-----|-----|-------|------|");
+        for row in &arrays {
+            report!(
+                "| {} | {} | {} | {} | {} | {} | {} |",
+                row.path,
+                row.min,
+                row.max,
+                row.avg,
+                row.p99,
+                row.empty,
+                row.note
+            );
+        }
+    }
+
+    let top_values = top_values_rows(&result.field_stats);
+    if !top_values.is_empty() {
+        report!("\n## Top Values\n");
+        report!("| Path | Values |");
+        report!("|------|--------|");
+        for row in &top_values {
+            report!("| {} | {} |", row.path, markdown_cell(&row.values));
+        }
+    }
+
+    if let Some(cooccurrence) = &result.cooccurrence {
+        let relationships = relationship_rows(cooccurrence);
+        if !relationships.is_empty() {
+            report!("\n## Field Relationships\n");
+            report!("| Relationship | Fields | Samples |");
+            report!("|--------------|--------|---------|");
+            for row in &relationships {
+                report!(
+                    "| {} | {} | {} |",
+                    row.relationship,
+                    row.fields,
+                    row.samples
+                );
+            }
+        }
+    }
+
+    let version_differences = version_difference_rows(&result.drift_issues);
+    if !version_differences.is_empty() {
+        report!("\n## Version Skew\n");
+        report!("| Marker | Field | Density by Version |");
+        report!("|--------|-------|--------------------|");
+        for row in &version_differences {
+            report!("| {} | {} | {} |", row.marker, row.path, row.densities);
+        }
+    }
+
+    if let Some(conformance) = &result.conformance {
+        report!("\n## Schema Conformance\n");
+        report!("**{}**\n", conformance_summary(conformance));
+        let rows = conformance_rows(conformance);
+        if !rows.is_empty() {
+            report!("| Path | Keyword | Documents | Example |");
+            report!("|------|---------|-----------|---------|");
+            for row in &rows {
+                report!(
+                    "| {} | {} | {} | {} |",
+                    row.path,
+                    row.keyword,
+                    row.documents,
+                    markdown_cell(&row.example)
+                );
+            }
+        }
+    }
+
+    if let Some(remediations) = &result.remediations {
+        report!("\n## Remediation\n");
+        if remediations.is_empty() {
+            report!("No automatic fixes for the reported issues.");
+        }
+        for remediation in remediations {
+            report!("### {} ({})\n", remediation.path, remediation.description);
+            report!("```sql\n{}\n```\n", remediation.sql);
+        }
+    }
+
+    if let Some(denormalization) = &result.denormalization {
+        report!("\n## Denormalized Fields\n");
+        if denormalization.is_empty() {
+            report!("No JSON paths duplicate other columns of the table.");
+        } else {
+            report!("| Path | Column | Matched By | Mismatches |");
+            report!("|------|--------|------------|------------|");
+            for row in denormalization_rows(denormalization) {
+                report!(
+                    "| {} | {} | {} | {} |",
+                    row.path,
+                    row.column,
+                    row.matched_by,
+                    row.mismatches
+                );
+            }
+        }
+    }
+
+    if let Some(unused_fields) = &result.unused_fields {
+        report!("\n## Unused Fields\n");
+        if unused_fields.is_empty() {
+            report!("Every JSON path is referenced by a known query.");
+        } else {
+            report!("| Path | Density | Storage per 1M Docs | Deprecation Candidate |");
+            report!("|------|---------|---------------------|-----------------------|");
+            for row in unused_field_rows(unused_fields) {
+                report!(
+                    "| {} | {} | {} | {} |",
+                    row.path,
+                    row.density,
+                    row.storage,
+                    row.candidate
+                );
+            }
+        }
+    }
+
+    print_expiring_suppressions_markdown(&result.expiring_suppressions);
+}
+
+/// Color the severity column of an issue table, which tabled does by
+/// itself, bypassing `colored`
+fn color_severity_column(table: &mut Table, color: Color) {
+    if colors_enabled() {
+        table.with(Modify::new(Columns::new(1..=1).intersect(Rows::new(1..))).with(color));
     }
 }
 
-fn print_analysis_table(result: &AnalysisResult) {
-    println!(
+fn print_analysis_table(result: &AnalysisResult, view: &AnalysisView) {
+    report!(
         "\n{} {}.{} ({} samples)\n",
         "Analyzing".bold().green(),
         result.table,
@@ -554,20 +2287,29 @@ fn print_analysis_table(result: &AnalysisResult) {
         .filter(|i| i.severity() == Severity::Info)
         .count();
 
-    println!("{}", "Schema Summary:".bold());
-    println!("  Total unique paths: {}", result.field_stats.len());
-    println!("  Max nesting depth: {}", max_depth);
+    report!("{}", "Schema Summary:".bold());
+    report!("  Health score: {}", colored_score(result.health_score));
+    report!("  Total unique paths: {}", result.field_stats.len());
+    report!("  Max nesting depth: {}", max_depth);
+    if result.suppressed_issues > 0 {
+        report!("  Suppressed issues: {}", result.suppressed_issues);
+    }
 
     if result.drift_issues.is_empty() {
-        println!("  {}", "No drift issues found!".green().bold());
+        report!("  {}", "No drift issues found!".green().bold());
     } else {
-        println!(
+        report!(
             "  Issues found: {} critical, {} warnings, {} info",
             critical_count.to_string().red(),
             warning_count.to_string().yellow(),
             info_count.to_string().cyan()
         );
+    }
+    if view.summary_only {
+        return;
+    }
 
+    if !result.drift_issues.is_empty() {
         // Group by severity
         let critical_issues: Vec<&DriftIssue> = result
             .drift_issues
@@ -587,196 +2329,2192 @@ fn print_analysis_table(result: &AnalysisResult) {
 
         // Print critical issues first
         if !critical_issues.is_empty() {
-            println!("\n{}", "Critical Issues:".red().bold());
+            report!("\n{}", "Critical Issues:".red().bold());
             let rows: Vec<DriftRow> = critical_issues.iter().map(|i| (*i).into()).collect();
             let mut table = Table::new(rows);
             table.with(Style::rounded());
-            table.with(
-                Modify::new(Columns::new(1..=1).intersect(Rows::new(1..))).with(Color::FG_RED),
-            );
-            println!("{}", table);
+            color_severity_column(&mut table, Color::FG_RED);
+            report!("{}", table);
         }
 
         // Then warnings
         if !warning_issues.is_empty() {
-            println!("\n{}", "Warnings:".yellow().bold());
+            report!("\n{}", "Warnings:".yellow().bold());
             let rows: Vec<DriftRow> = warning_issues.iter().map(|i| (*i).into()).collect();
             let mut table = Table::new(rows);
             table.with(Style::rounded());
-            table.with(
-                Modify::new(Columns::new(1..=1).intersect(Rows::new(1..))).with(Color::FG_YELLOW),
-            );
-            println!("{}", table);
+            color_severity_column(&mut table, Color::FG_YELLOW);
+            report!("{}", table);
         }
 
         // Then info
         if !info_issues.is_empty() {
-            println!("\n{}", "Info:".cyan().bold());
+            report!("\n{}", "Info:".cyan().bold());
             let rows: Vec<DriftRow> = info_issues.iter().map(|i| (*i).into()).collect();
             let mut table = Table::new(rows);
             table.with(Style::rounded());
-            table.with(
-                Modify::new(Columns::new(1..=1).intersect(Rows::new(1..))).with(Color::FG_CYAN),
+            color_severity_column(&mut table, Color::FG_CYAN);
+            report!("{}", table);
+        }
+    }
+
+    if let Some(limit) = view.examples {
+        let examples = issue_examples(&result.field_stats, &result.drift_issues, limit);
+        if !examples.is_empty() {
+            report!("\n{}", "Examples:".bold());
+            for (path, values) in examples {
+                report!("  {}: {}", path.bold(), example_labels(values).join(", "));
+            }
+        }
+    }
+
+    if let Some(stats_view) = &view.field_stats {
+        let rows = field_stats_rows(
+            &result.field_stats,
+            &result.drift_issues,
+            stats_view,
+            view.examples.unwrap_or(LISTED_EXAMPLES),
+        );
+        let note = stats_view.page.note(rows.len(), "paths");
+        let rows = stats_view.page.apply(rows);
+        report!("\n{}", "Field Stats:".bold());
+        if rows.is_empty() && note.is_none() {
+            report!("  No paths match.");
+        } else if !rows.is_empty() {
+            let mut table = Table::new(rows);
+            table.with(Style::rounded());
+            report!("{}", table);
+        }
+        if let Some(note) = note {
+            report!("  {}", note.dimmed());
+        }
+    }
+
+    if view.tree {
+        report!("\n{}", "Field Tree:".bold());
+        for line in tree_lines(&result.column, &result.field_stats) {
+            report!("  {}", line);
+        }
+    }
+
+    let numeric = numeric_rows(&result.field_stats);
+    if !numeric.is_empty() {
+        report!("\n{}", "Numeric Fields:".bold());
+        let mut table = Table::new(numeric);
+        table.with(Style::rounded());
+        report!("{}", table);
+    }
+
+    let formats = format_rows(&result.field_stats);
+    if !formats.is_empty() {
+        report!("\n{}", "String Formats:".bold());
+        let mut table = Table::new(formats);
+        table.with(Style::rounded());
+        report!("{}", table);
+    }
+
+    let arrays = array_rows(&result.field_stats);
+    if !arrays.is_empty() {
+        report!("\n{}", "Array Fields:".bold());
+        let mut table = Table::new(arrays);
+        table.with(Style::rounded());
+        report!("{}", table);
+    }
+
+    let top_values = top_values_rows(&result.field_stats);
+    if !top_values.is_empty() {
+        report!("\n{}", "Top Values:".bold());
+        let mut table = Table::new(top_values);
+        table.with(Style::rounded());
+        report!("{}", table);
+    }
+
+    if let Some(cooccurrence) = &result.cooccurrence {
+        let relationships = relationship_rows(cooccurrence);
+        if !relationships.is_empty() {
+            report!("\n{}", "Field Relationships:".bold());
+            let mut table = Table::new(relationships);
+            table.with(Style::rounded());
+            report!("{}", table);
+        }
+    }
+
+    let version_differences = version_difference_rows(&result.drift_issues);
+    if !version_differences.is_empty() {
+        report!("\n{}", "Version Skew:".bold());
+        let mut table = Table::new(version_differences);
+        table.with(Style::rounded());
+        report!("{}", table);
+    }
+
+    if let Some(conformance) = &result.conformance {
+        let summary = conformance_summary(conformance);
+        let summary = if conformance.failing_documents == 0 {
+            summary.green()
+        } else {
+            summary.yellow()
+        };
+        report!("\n{} {}", "Schema Conformance:".bold(), summary);
+        let rows = conformance_rows(conformance);
+        if !rows.is_empty() {
+            let mut table = Table::new(rows);
+            table.with(Style::rounded());
+            report!("{}", table);
+        }
+    }
+
+    if let Some(remediations) = &result.remediations {
+        report!("\n{}", "Remediation:".bold().green());
+        if remediations.is_empty() {
+            report!("  No automatic fixes for the reported issues.");
+        }
+        for remediation in remediations {
+            report!(
+                "\n{} ({})",
+                remediation.path.bold(),
+                remediation.description
             );
-            println!("{}", table);
+            report!("{}", remediation.sql.dimmed());
+        }
+    }
+
+    if let Some(denormalization) = &result.denormalization {
+        report!("\n{}", "Denormalized Fields:".bold());
+        if denormalization.is_empty() {
+            report!("  No JSON paths duplicate other columns of the table.");
+        } else {
+            let mut table = Table::new(denormalization_rows(denormalization));
+            table.with(Style::rounded());
+            report!("{}", table);
+        }
+    }
+
+    if let Some(unused_fields) = &result.unused_fields {
+        report!("\n{}", "Unused Fields:".bold());
+        if unused_fields.is_empty() {
+            report!("  Every JSON path is referenced by a known query.");
+        } else {
+            let mut table = Table::new(unused_field_rows(unused_fields));
+            table.with(Style::rounded());
+            report!("{}", table);
         }
     }
 
-    println!();
+    print_expiring_suppressions_table(&result.expiring_suppressions);
+    report!();
 }
 
 #[derive(Tabled)]
-pub struct IndexRow {
-    #[tabled(rename = "Field Path")]
-    pub field_path: String,
-    #[tabled(rename = "Index Type")]
-    pub index_type: String,
-    #[tabled(rename = "Priority")]
-    pub priority: String,
-    #[tabled(rename = "Reason")]
-    pub reason: String,
+pub struct CohortRow {
+    #[tabled(rename = "Path")]
+    pub path: String,
+    #[tabled(rename = "Change")]
+    pub change: String,
 }
 
-impl From<&pgdrift_core::index::IndexRecommendation> for IndexRow {
-    fn from(rec: &pgdrift_core::index::IndexRecommendation) -> Self {
+impl From<&CohortShift> for CohortRow {
+    fn from(shift: &CohortShift) -> Self {
         Self {
-            field_path: rec.field_path.clone(),
-            index_type: rec.index_type.to_name().to_string(),
-            priority: rec.priority.to_name().to_string(),
-            reason: rec.reason.clone(),
+            path: shift.path().to_string(),
+            change: shift.description(),
         }
     }
 }
 
-pub struct IndexRecommendationResult {
+/// Comparison of rows older and newer than the `--cohort-split` point
+pub struct CohortResult {
     pub table: String,
     pub column: String,
-    pub recommendations: Vec<pgdrift_core::index::IndexRecommendation>,
+    /// Timestamp column the rows were split on
+    pub cohort_column: String,
+    /// How long before now the split lies, as a Postgres interval
+    pub split: String,
+    pub old_samples: u64,
+    pub new_samples: u64,
+    pub shifts: Vec<CohortShift>,
 }
 
-pub fn print_index_recommendations(result: &IndexRecommendationResult, format: &OutputFormat) {
+pub fn print_cohort_analysis(result: &CohortResult, format: &OutputFormat) {
     match format {
-        OutputFormat::Table => print_index_recommendations_table(result),
-        OutputFormat::Json => print_index_recommendations_json(result),
-        OutputFormat::Markdown => print_index_recommendations_markdown(result),
+        OutputFormat::Table => print_cohort_analysis_table(result),
+        OutputFormat::Json => print_cohort_analysis_json(result),
+        OutputFormat::Markdown | OutputFormat::Github | OutputFormat::Html => {
+            print_cohort_analysis_markdown(result)
+        }
     }
 }
 
-fn print_index_recommendations_json(result: &IndexRecommendationResult) {
-    let output = json!({
-        "table": result.table,
-        "column": result.column,
-        "recommendations": result.recommendations,
-        "summary": {
-            "total_recommendations": result.recommendations.len(),
-            "high_priority": result.recommendations.iter().filter(|r| r.priority == pgdrift_core::index::IndexPriority::High).count(),
-            "medium_priority": result.recommendations.iter().filter(|r| r.priority == pgdrift_core::index::IndexPriority::Medium).count(),
-            "low_priority": result.recommendations.iter().filter(|r| r.priority == pgdrift_core::index::IndexPriority::Low).count(),
-        }
+fn print_cohort_analysis_json(result: &CohortResult) {
+    print_json(CohortReport {
+        table: result.table.clone(),
+        column: result.column.clone(),
+        cohort_column: result.cohort_column.clone(),
+        split: result.split.clone(),
+        old_samples: result.old_samples,
+        new_samples: result.new_samples,
+        shifts: result.shifts.iter().map(Into::into).collect(),
     });
-    println!("{}", serde_json::to_string_pretty(&output).unwrap());
 }
 
-fn print_index_recommendations_markdown(result: &IndexRecommendationResult) {
-    println!(
-        "# Index Recommendations: {}.{}\n",
-        result.table, result.column
+fn print_cohort_analysis_markdown(result: &CohortResult) {
+    report!("# Cohort Drift: {}.{}\n", result.table, result.column);
+    report!(
+        "**Split:** {} {} ago ({} older samples, {} newer samples)\n",
+        result.cohort_column,
+        result.split,
+        result.old_samples,
+        result.new_samples
     );
 
-    if result.recommendations.is_empty() {
-        println!("**No index recommendations.**\n");
-        println!("This could mean:\n");
-        println!("- All fields have low occurrence counts (< 100 samples)");
-        println!("- All fields are objects or arrays (not directly indexable)");
-        println!("- Field densities are in the middle range without strong indexing needs\n");
+    if result.shifts.is_empty() {
+        report!("**No significant differences between old and new rows.**");
         return;
     }
 
-    println!("Found {} recommendation(s)\n", result.recommendations.len());
-
-    println!("| Field Path | Index Type | Priority | Reason |");
-    println!("|------------|------------|----------|--------|");
-    for rec in &result.recommendations {
-        println!(
-            "| {} | {} | {} | {} |",
-            rec.field_path,
-            rec.index_type.to_name(),
-            rec.priority.to_name(),
-            rec.reason
+    report!("| Path | Change |");
+    report!("|------|--------|");
+    for shift in &result.shifts {
+        report!(
+            "| {} | {} |",
+            shift.path(),
+            markdown_cell(&shift.description())
         );
     }
-
-    println!("\n## SQL Commands\n");
-    for (i, rec) in result.recommendations.iter().enumerate() {
-        println!("### {} - {}\n", i + 1, rec.field_path);
-        println!("```sql\n{}\n```\n", rec.sql);
-        println!("**Estimated Benefit:** {}\n", rec.estimated_benefit);
-    }
 }
 
-fn print_index_recommendations_table(result: &IndexRecommendationResult) {
-    println!(
-        "\n{} {}.{}\n",
-        "Index Recommendations for".bold().green(),
+fn print_cohort_analysis_table(result: &CohortResult) {
+    report!(
+        "\n{} {}.{} split on {} {} ago\n",
+        "Comparing cohorts of".bold().green(),
         result.table,
-        result.column
+        result.column,
+        result.cohort_column,
+        result.split
     );
+    report!("  Older samples: {}", result.old_samples);
+    report!("  Newer samples: {}", result.new_samples);
 
-    if result.recommendations.is_empty() {
-        println!("{}", "No index recommendations.".yellow());
-        println!("\n{}", "This could mean:".bold());
-        println!("  • All fields have low occurrence counts (< 100 samples)");
-        println!("  • All fields are objects or arrays (not directly indexable)");
-        println!("  • Field densities are in the middle range without strong indexing needs\n");
+    if result.shifts.is_empty() {
+        report!(
+            "  {}\n",
+            "No significant differences between old and new rows!"
+                .green()
+                .bold()
+        );
         return;
     }
 
-    println!("{}", "Summary:".bold());
-    println!("  Total recommendations: {}", result.recommendations.len());
-    let high_count = result
-        .recommendations
-        .iter()
-        .filter(|r| r.priority == pgdrift_core::index::IndexPriority::High)
-        .count();
-    let medium_count = result
-        .recommendations
-        .iter()
-        .filter(|r| r.priority == pgdrift_core::index::IndexPriority::Medium)
-        .count();
-    let low_count = result
-        .recommendations
-        .iter()
-        .filter(|r| r.priority == pgdrift_core::index::IndexPriority::Low)
-        .count();
-
-    if high_count > 0 {
-        println!("  High priority: {}", high_count.to_string().red());
+    report!("\n{}", "Changes in newer rows:".yellow().bold());
+    let rows: Vec<CohortRow> = result.shifts.iter().map(|s| s.into()).collect();
+    let mut table = Table::new(rows);
+    table.with(Style::rounded());
+    report!("{}\n", table);
+}
+
+#[derive(Tabled)]
+pub struct DiffRow {
+    #[tabled(rename = "Path")]
+    pub path: String,
+    #[tabled(rename = "Severity")]
+    pub severity: String,
+    #[tabled(rename = "Issue")]
+    pub issue: String,
+}
+
+impl DiffRow {
+    fn new(issue: &BaselineIssue, severity: String) -> Self {
+        Self {
+            path: issue.path.clone(),
+            severity,
+            issue: issue.description.clone(),
+        }
+    }
+}
+
+/// Rows of the diff report: new, worsened and resolved issues, with a section label each
+fn diff_rows(diff: &BaselineDiff) -> Vec<(&'static str, Vec<DiffRow>)> {
+    vec![
+        (
+            "New issues",
+            diff.new_issues
+                .iter()
+                .map(|i| DiffRow::new(i, format!("{:?}", i.severity)))
+                .collect(),
+        ),
+        (
+            "Worsened issues",
+            diff.worsened_issues
+                .iter()
+                .map(|w| {
+                    DiffRow::new(
+                        &w.issue,
+                        format!("{:?} → {:?}", w.previous_severity, w.issue.severity),
+                    )
+                })
+                .collect(),
+        ),
+        (
+            "Resolved issues",
+            diff.resolved_issues
+                .iter()
+                .map(|i| DiffRow::new(i, format!("{:?}", i.severity)))
+                .collect(),
+        ),
+    ]
+}
+
+/// A new run of a column compared against its baseline
+pub struct DiffResult {
+    pub schema: String,
+    pub table: String,
+    pub column: String,
+    pub baseline_samples: u64,
+    pub samples_analyzed: u64,
+    pub baseline_score: u8,
+    pub health_score: u8,
+    pub diff: BaselineDiff,
+}
+
+pub fn print_diff(result: &DiffResult, format: &OutputFormat) {
+    match format {
+        OutputFormat::Table => print_diff_table(result),
+        OutputFormat::Json => print_diff_json(result),
+        OutputFormat::Markdown | OutputFormat::Github | OutputFormat::Html => {
+            print_diff_markdown(result)
+        }
+    }
+}
+
+fn print_diff_json(result: &DiffResult) {
+    let diff = &result.diff;
+    print_json(DiffReport {
+        schema: result.schema.clone(),
+        table: result.table.clone(),
+        column: result.column.clone(),
+        baseline_samples: result.baseline_samples,
+        samples_analyzed: result.samples_analyzed,
+        baseline_health_score: result.baseline_score,
+        health_score: result.health_score,
+        new_issues: diff.new_issues.iter().map(Into::into).collect(),
+        worsened_issues: diff.worsened_issues.iter().map(Into::into).collect(),
+        resolved_issues: diff.resolved_issues.iter().map(Into::into).collect(),
+        regression: diff.is_regression(),
+    });
+}
+
+fn print_diff_markdown(result: &DiffResult) {
+    report!(
+        "# Drift Since Baseline: {}.{}.{}\n",
+        result.schema,
+        result.table,
+        result.column
+    );
+    report!(
+        "**Samples analyzed:** {} (baseline {})\n",
+        result.samples_analyzed,
+        result.baseline_samples
+    );
+    report!(
+        "**Health score:** {}/100 (baseline {}/100)\n",
+        result.health_score,
+        result.baseline_score
+    );
+
+    if result.diff.is_empty() {
+        report!("**No changes since the baseline.**");
+        return;
+    }
+
+    for (label, rows) in diff_rows(&result.diff) {
+        if rows.is_empty() {
+            continue;
+        }
+        report!("## {}\n", label);
+        report!("| Path | Severity | Issue |");
+        report!("|------|----------|-------|");
+        for row in &rows {
+            report!("| {} | {} | {} |", row.path, row.severity, row.issue);
+        }
+        report!();
+    }
+}
+
+fn print_diff_table(result: &DiffResult) {
+    report!(
+        "\n{} {}.{}.{} against baseline ({} samples, baseline {})\n",
+        "Comparing".bold().green(),
+        result.schema,
+        result.table,
+        result.column,
+        result.samples_analyzed,
+        result.baseline_samples
+    );
+    report!(
+        "  Health score: {} (baseline {})",
+        colored_score(result.health_score),
+        colored_score(result.baseline_score)
+    );
+
+    if result.diff.is_empty() {
+        report!("  {}\n", "No changes since the baseline!".green().bold());
+        return;
+    }
+
+    for (label, rows) in diff_rows(&result.diff) {
+        if rows.is_empty() {
+            continue;
+        }
+        let heading = format!("{}:", label);
+        let heading = if label == "Resolved issues" {
+            heading.green().bold()
+        } else {
+            heading.red().bold()
+        };
+        report!("\n{}", heading);
+        let mut table = Table::new(rows);
+        table.with(Style::rounded());
+        report!("{}", table);
+    }
+    report!();
+}
+
+#[derive(Tabled)]
+pub struct CompareRow {
+    #[tabled(rename = "Path")]
+    pub path: String,
+    #[tabled(rename = "Difference")]
+    pub difference: String,
+}
+
+/// The same column analyzed in two databases
+pub struct CompareResult {
+    pub table: String,
+    pub column: String,
+    /// Name of the first database in the report
+    pub left_label: String,
+    /// Name of the second database in the report
+    pub right_label: String,
+    pub left_samples: u64,
+    pub right_samples: u64,
+    pub differences: Vec<PathDifference>,
+}
+
+impl CompareResult {
+    fn rows(&self) -> Vec<CompareRow> {
+        self.differences
+            .iter()
+            .map(|d| CompareRow {
+                path: d.path().to_string(),
+                difference: d.description(&self.left_label, &self.right_label),
+            })
+            .collect()
+    }
+}
+
+pub fn print_compare(result: &CompareResult, format: &OutputFormat) {
+    match format {
+        OutputFormat::Table => print_compare_table(result),
+        OutputFormat::Json => print_compare_json(result),
+        OutputFormat::Markdown | OutputFormat::Github | OutputFormat::Html => {
+            print_compare_markdown(result)
+        }
+    }
+}
+
+fn print_compare_json(result: &CompareResult) {
+    print_json(CompareReport {
+        table: result.table.clone(),
+        column: result.column.clone(),
+        left: CompareSide {
+            label: result.left_label.clone(),
+            samples_analyzed: result.left_samples,
+        },
+        right: CompareSide {
+            label: result.right_label.clone(),
+            samples_analyzed: result.right_samples,
+        },
+        differences: result.differences.iter().map(Into::into).collect(),
+    });
+}
+
+fn print_compare_markdown(result: &CompareResult) {
+    report!("# Schema Comparison: {}.{}\n", result.table, result.column);
+    report!(
+        "**{}:** {} samples, **{}:** {} samples\n",
+        result.left_label,
+        result.left_samples,
+        result.right_label,
+        result.right_samples
+    );
+
+    if result.differences.is_empty() {
+        report!("**No structural differences found.**");
+        return;
+    }
+
+    report!("| Path | Difference |");
+    report!("|------|------------|");
+    for row in result.rows() {
+        report!("| {} | {} |", row.path, row.difference);
+    }
+}
+
+fn print_compare_table(result: &CompareResult) {
+    report!(
+        "\n{} {}.{}: {} ({} samples) vs {} ({} samples)\n",
+        "Comparing".bold().green(),
+        result.table,
+        result.column,
+        result.left_label.bold(),
+        result.left_samples,
+        result.right_label.bold(),
+        result.right_samples
+    );
+
+    if result.differences.is_empty() {
+        report!("  {}\n", "No structural differences found!".green().bold());
+        return;
+    }
+
+    report!(
+        "{} {}",
+        "Differences:".yellow().bold(),
+        result.differences.len()
+    );
+    let mut table = Table::new(result.rows());
+    table.with(Style::rounded());
+    report!("{}\n", table);
+}
+
+#[derive(Tabled)]
+pub struct ViolationRow {
+    #[tabled(rename = "Path")]
+    pub path: String,
+    #[tabled(rename = "Severity")]
+    pub severity: String,
+    #[tabled(rename = "Rule")]
+    pub rule: String,
+    #[tabled(rename = "Violation")]
+    pub message: String,
+}
+
+impl From<&Violation> for ViolationRow {
+    fn from(violation: &Violation) -> Self {
+        Self {
+            path: violation.path.clone(),
+            severity: format!("{:?}", violation.severity),
+            rule: format!("{:?}", violation.rule),
+            message: violation.message.clone(),
+        }
+    }
+}
+
+/// A sampled column checked against a schema spec
+pub struct ValidationResult {
+    pub table: String,
+    pub column: String,
+    pub samples_analyzed: u64,
+    pub violations: Vec<Violation>,
+}
+
+pub fn print_validation(result: &ValidationResult, format: &OutputFormat) {
+    match format {
+        OutputFormat::Table => print_validation_table(result),
+        OutputFormat::Json => print_validation_json(result),
+        OutputFormat::Markdown | OutputFormat::Github | OutputFormat::Html => {
+            print_validation_markdown(result)
+        }
+    }
+}
+
+fn print_validation_json(result: &ValidationResult) {
+    print_json(ValidateReport {
+        table: result.table.clone(),
+        column: result.column.clone(),
+        samples_analyzed: result.samples_analyzed,
+        violations: result.violations.iter().map(Into::into).collect(),
+    });
+}
+
+fn print_validation_markdown(result: &ValidationResult) {
+    report!("# Spec Validation: {}.{}\n", result.table, result.column);
+    report!("**Samples analyzed:** {}\n", result.samples_analyzed);
+
+    if result.violations.is_empty() {
+        report!("**Column matches the spec.**");
+        return;
+    }
+
+    report!("| Path | Severity | Rule | Violation |");
+    report!("|------|----------|------|-----------|");
+    for row in result.violations.iter().map(ViolationRow::from) {
+        report!(
+            "| {} | {} | {} | {} |",
+            row.path,
+            row.severity,
+            row.rule,
+            markdown_cell(&row.message)
+        );
+    }
+}
+
+fn print_validation_table(result: &ValidationResult) {
+    report!(
+        "\n{} {}.{} against spec ({} samples)\n",
+        "Validating".bold().green(),
+        result.table,
+        result.column,
+        result.samples_analyzed
+    );
+
+    if result.violations.is_empty() {
+        report!("  {}\n", "Column matches the spec!".green().bold());
+        return;
+    }
+
+    report!("{} {}", "Violations:".red().bold(), result.violations.len());
+    let rows: Vec<ViolationRow> = result.violations.iter().map(ViolationRow::from).collect();
+    let mut table = Table::new(rows);
+    table.with(Style::rounded());
+    report!("{}\n", table);
+}
+
+#[derive(Tabled)]
+pub struct PromotionRow {
+    #[tabled(rename = "Path")]
+    pub path: String,
+    #[tabled(rename = "Column")]
+    pub column: String,
+    #[tabled(rename = "Density")]
+    pub density: String,
+    #[tabled(rename = "Kind")]
+    pub kind: String,
+    #[tabled(rename = "Storage (JSON → column)")]
+    pub storage: String,
+}
+
+/// Column recommendations for the stable paths of a JSON column
+pub struct PromotionResult {
+    pub table: String,
+    pub column: String,
+    pub samples_analyzed: u64,
+    /// Rows in the table, used to scale the storage estimates
+    pub row_count: i64,
+    pub promotions: Vec<Promotion>,
+}
+
+impl PromotionResult {
+    /// Bytes saved across the table once the promoted keys are removed from the documents
+    fn storage_saved(&self) -> i64 {
+        let per_row: f64 = self
+            .promotions
+            .iter()
+            .map(|p| p.json_bytes_per_row - p.column_bytes_per_row)
+            .sum();
+        (per_row * self.row_count as f64) as i64
+    }
+
+    fn rows(&self) -> Vec<PromotionRow> {
+        let total =
+            |bytes_per_row: f64| format_bytes((bytes_per_row * self.row_count as f64) as i64);
+        self.promotions
+            .iter()
+            .map(|p| PromotionRow {
+                path: p.path.clone(),
+                column: format!("{} {}", p.column_name, p.sql_type),
+                density: format!("{:.1}%", p.density * 100.0),
+                kind: match (p.generated, p.indexed) {
+                    (true, true) => "generated, indexed",
+                    (true, false) => "generated",
+                    (false, true) => "backfilled, indexed",
+                    (false, false) => "backfilled",
+                }
+                .to_string(),
+                storage: format!(
+                    "{} → {}",
+                    total(p.json_bytes_per_row),
+                    total(p.column_bytes_per_row)
+                ),
+            })
+            .collect()
+    }
+}
+
+pub fn print_promotions(result: &PromotionResult, format: &OutputFormat) {
+    match format {
+        OutputFormat::Table => print_promotions_table(result),
+        OutputFormat::Json => print_promotions_json(result),
+        OutputFormat::Markdown | OutputFormat::Github | OutputFormat::Html => {
+            print_promotions_markdown(result)
+        }
+    }
+}
+
+fn print_promotions_json(result: &PromotionResult) {
+    print_json(PromoteReport {
+        table: result.table.clone(),
+        column: result.column.clone(),
+        samples_analyzed: result.samples_analyzed,
+        row_count: result.row_count,
+        promotions: result.promotions.iter().map(Into::into).collect(),
+        estimated_bytes_saved: result.storage_saved(),
+    });
+}
+
+fn print_promotions_markdown(result: &PromotionResult) {
+    report!("# Column Promotion: {}.{}\n", result.table, result.column);
+    report!(
+        "**Samples analyzed:** {} of {} rows\n",
+        result.samples_analyzed,
+        result.row_count
+    );
+
+    if result.promotions.is_empty() {
+        report!("**No paths are stable enough to promote.**");
+        return;
+    }
+
+    report!("| Path | Column | Density | Kind | Storage (JSON → column) |");
+    report!("|------|--------|---------|------|-------------------------|");
+    for row in result.rows() {
+        report!(
+            "| {} | {} | {} | {} | {} |",
+            row.path,
+            row.column,
+            row.density,
+            row.kind,
+            row.storage
+        );
+    }
+    report!(
+        "\nRemoving the promoted keys from the documents saves about {}.\n",
+        format_bytes(result.storage_saved())
+    );
+
+    report!("## DDL\n");
+    for promotion in &result.promotions {
+        report!("### {}\n", promotion.path);
+        report!("```sql\n{}\n```\n", promotion.sql);
+        report!("**Benefit:** {}\n", promotion.benefit);
+    }
+}
+
+fn print_promotions_table(result: &PromotionResult) {
+    report!(
+        "\n{} {}.{} ({} samples of {} rows)\n",
+        "Column Promotion for".bold().green(),
+        result.table,
+        result.column,
+        result.samples_analyzed,
+        result.row_count
+    );
+
+    if result.promotions.is_empty() {
+        report!("{}\n", "No paths are stable enough to promote.".yellow());
+        return;
+    }
+
+    let mut table = Table::new(result.rows());
+    table.with(Style::rounded());
+    report!("{}", table);
+    report!(
+        "Removing the promoted keys from the documents saves about {}.",
+        format_bytes(result.storage_saved()).bold()
+    );
+
+    report!("\n{}", "DDL:".bold().green());
+    for promotion in &result.promotions {
+        report!("\n{}", promotion.path.bold());
+        report!("{}", promotion.sql.dimmed());
+        report!("{} {}", "Benefit:".bold(), promotion.benefit);
+    }
+    report!();
+}
+
+#[derive(Tabled)]
+pub struct IndexRow {
+    #[tabled(rename = "Field Path")]
+    pub field_path: String,
+    #[tabled(rename = "Index Type")]
+    pub index_type: String,
+    #[tabled(rename = "Priority")]
+    pub priority: String,
+    #[tabled(rename = "Est. Size")]
+    pub size: String,
+    #[tabled(rename = "Reason")]
+    pub reason: String,
+}
+
+impl From<&pgdrift_core::index::IndexRecommendation> for IndexRow {
+    fn from(rec: &pgdrift_core::index::IndexRecommendation) -> Self {
+        Self {
+            field_path: rec.field_path.clone(),
+            index_type: rec.index_type.to_name().to_string(),
+            priority: rec.priority.to_name().to_string(),
+            size: index_size_label(rec.estimated_size_bytes),
+            reason: rec.reason.clone(),
+        }
+    }
+}
+
+fn index_size_label(bytes: Option<i64>) -> String {
+    bytes.map_or_else(|| "-".to_string(), format_bytes)
+}
+
+/// What the planner made of a hypothetical or rolled back copy of the index
+fn planner_label(check: &PlannerCheck) -> String {
+    let timing = match (check.time_without_ms, check.time_with_ms) {
+        (Some(without), Some(with)) if check.used => {
+            format!(", {:.2} ms -> {:.2} ms", without, with)
+        }
+        (Some(without), _) => format!(", {:.2} ms", without),
+        _ => String::new(),
+    };
+    if check.used {
+        format!(
+            "used (cost {:.1} -> {:.1}{})",
+            check.cost_without, check.cost_with, timing
+        )
+    } else {
+        format!("not used (cost {:.1}{})", check.cost_without, timing)
+    }
+}
+
+#[derive(Tabled)]
+pub struct CoveredIndexRow {
+    #[tabled(rename = "Field Path")]
+    pub field_path: String,
+    #[tabled(rename = "Index Type")]
+    pub index_type: String,
+    #[tabled(rename = "Existing Index")]
+    pub covered_by: String,
+}
+
+#[derive(Tabled)]
+pub struct QueryPatternRow {
+    #[tabled(rename = "Path")]
+    pub path: String,
+    #[tabled(rename = "Operator")]
+    pub operator: String,
+    #[tabled(rename = "Statements")]
+    pub statements: usize,
+    #[tabled(rename = "Calls")]
+    pub calls: u64,
+    #[tabled(rename = "Mean Time")]
+    pub mean_time: String,
+}
+
+/// Query patterns listed in table and markdown output; JSON has all of them
+const MAX_QUERY_PATTERNS: usize = 15;
+
+#[derive(Tabled)]
+pub struct IndexOverlapRow {
+    #[tabled(rename = "Index")]
+    pub index: String,
+    #[tabled(rename = "Kind")]
+    pub kind: String,
+    #[tabled(rename = "Overlaps")]
+    pub overlaps: String,
+    #[tabled(rename = "Reason")]
+    pub reason: String,
+}
+
+#[derive(Tabled)]
+pub struct DropRecommendationRow {
+    #[tabled(rename = "Index")]
+    pub index: String,
+    #[tabled(rename = "Scans")]
+    pub scans: i64,
+    #[tabled(rename = "Size")]
+    pub size: String,
+    #[tabled(rename = "Reason")]
+    pub reason: String,
+}
+
+pub struct IndexRecommendationResult {
+    pub schema: String,
+    pub table: String,
+    pub column: String,
+    /// Rows in the table, which the size estimates are based on
+    pub row_count: i64,
+    pub recommendations: Vec<pgdrift_core::index::IndexRecommendation>,
+    /// Recommendations left out because an existing index serves them
+    pub covered: Vec<CoveredRecommendation>,
+    /// Existing indexes made redundant by another existing index
+    pub overlaps: Vec<IndexOverlap>,
+    /// Existing indexes on the column that are unused or redundant
+    pub drops: Vec<DropRecommendation>,
+    /// When the statistics the scan counts start from were last reset
+    pub stats_reset: Option<String>,
+    /// How recorded statements query the column, busiest first
+    pub accesses: Vec<ColumnAccess>,
+}
+
+impl IndexRecommendationResult {
+    /// Estimated size of all recommended indexes together
+    fn total_size(&self) -> i64 {
+        self.recommendations
+            .iter()
+            .filter_map(|r| r.estimated_size_bytes)
+            .sum()
+    }
+
+    /// Recommendations checked with the planner, and those it used the index for
+    fn planner_counts(&self) -> (usize, usize) {
+        let checks: Vec<&PlannerCheck> = self
+            .recommendations
+            .iter()
+            .filter_map(|r| r.planner_check.as_ref())
+            .collect();
+        (checks.len(), checks.iter().filter(|c| c.used).count())
+    }
+
+    pub fn covered_rows(&self) -> Vec<CoveredIndexRow> {
+        self.covered
+            .iter()
+            .map(|c| CoveredIndexRow {
+                field_path: c.field_path.clone(),
+                index_type: c.index_type.to_name().to_string(),
+                covered_by: c.covered_by.clone(),
+            })
+            .collect()
+    }
+
+    pub fn query_pattern_rows(&self) -> Vec<QueryPatternRow> {
+        self.accesses
+            .iter()
+            .take(MAX_QUERY_PATTERNS)
+            .map(|a| QueryPatternRow {
+                path: a.path.clone().unwrap_or_else(|| "(parameter)".to_string()),
+                operator: a.operator.to_name().to_string(),
+                statements: a.usage.statements,
+                calls: a.usage.calls,
+                mean_time: format!("{:.1} ms", a.usage.mean_exec_time_ms),
+            })
+            .collect()
+    }
+
+    pub fn drop_rows(&self) -> Vec<DropRecommendationRow> {
+        self.drops
+            .iter()
+            .map(|d| DropRecommendationRow {
+                index: d.index.clone(),
+                scans: d.scans,
+                size: format_bytes(d.size_bytes),
+                reason: d.reason.clone(),
+            })
+            .collect()
+    }
+
+    /// Where the scan counts of the drop recommendations start from
+    fn scans_since(&self) -> String {
+        match &self.stats_reset {
+            Some(reset) => format!("Scans since the statistics were reset at {}", reset),
+            None => "Scans since the statistics were created".to_string(),
+        }
+    }
+
+    pub fn overlap_rows(&self) -> Vec<IndexOverlapRow> {
+        self.overlaps
+            .iter()
+            .map(|o| IndexOverlapRow {
+                index: o.index.clone(),
+                kind: o.kind.to_name().to_string(),
+                overlaps: o.overlaps.clone(),
+                reason: o.reason.clone(),
+            })
+            .collect()
+    }
+}
+
+pub fn print_index_recommendations(result: &IndexRecommendationResult, format: &OutputFormat) {
+    match format {
+        OutputFormat::Table => print_index_recommendations_table(result),
+        OutputFormat::Json => print_index_recommendations_json(result),
+        OutputFormat::Markdown | OutputFormat::Github | OutputFormat::Html => {
+            print_index_recommendations_markdown(result)
+        }
+    }
+}
+
+fn print_index_recommendations_json(result: &IndexRecommendationResult) {
+    print_json(index_report(result));
+}
+
+fn index_report(result: &IndexRecommendationResult) -> IndexReport {
+    let recommendations: Vec<_> = result.recommendations.iter().map(Into::into).collect();
+    let summary = IndexSummary::new(
+        &recommendations,
+        result.covered.len(),
+        result.overlaps.len(),
+        result.drops.len(),
+    );
+    IndexReport {
+        schema: result.schema.clone(),
+        table: result.table.clone(),
+        column: result.column.clone(),
+        row_count: result.row_count,
+        recommendations,
+        covered: result.covered.iter().map(Into::into).collect(),
+        redundant_indexes: result.overlaps.iter().map(Into::into).collect(),
+        drop_recommendations: result.drops.iter().map(Into::into).collect(),
+        stats_reset: result.stats_reset.clone(),
+        query_patterns: result.accesses.iter().map(Into::into).collect(),
+        summary,
+    }
+}
+
+fn print_index_recommendations_markdown(result: &IndexRecommendationResult) {
+    report!(
+        "# Index Recommendations: {}.{}\n",
+        result.table,
+        result.column
+    );
+
+    if result.recommendations.is_empty() {
+        if result.covered.is_empty() {
+            report!("**No index recommendations.**\n");
+            report!("This could mean:\n");
+            report!("- All fields have low occurrence counts (< 100 samples)");
+            report!("- All fields are objects or arrays (not directly indexable)");
+            report!("- Field densities are in the middle range without strong indexing needs\n");
+        } else {
+            report!("**Existing indexes already serve every recommendation.**\n");
+        }
+        print_query_patterns_markdown(result);
+        print_existing_indexes_markdown(result);
+        return;
+    }
+
+    report!(
+        "Found {} recommendation(s), about {} in total for {} rows\n",
+        result.recommendations.len(),
+        format_bytes(result.total_size()),
+        result.row_count
+    );
+
+    report!("| Field Path | Index Type | Priority | Est. Size | Reason |");
+    report!("|------------|------------|----------|-----------|--------|");
+    for rec in &result.recommendations {
+        report!(
+            "| {} | {} | {} | {} | {} |",
+            rec.field_path,
+            rec.index_type.to_name(),
+            rec.priority.to_name(),
+            index_size_label(rec.estimated_size_bytes),
+            markdown_cell(&rec.reason)
+        );
+    }
+
+    report!("\n## SQL Commands\n");
+    for (i, rec) in result.recommendations.iter().enumerate() {
+        report!("### {} - {}\n", i + 1, rec.field_path);
+        report!("```sql\n{}\n```\n", rec.sql);
+        report!("**Estimated Benefit:** {}\n", rec.estimated_benefit);
+        report!(
+            "**Estimated Size:** {}\n",
+            index_size_label(rec.estimated_size_bytes)
+        );
+        if let Some(check) = &rec.planner_check {
+            report!(
+                "**Planner:** {} for `{}`\n",
+                planner_label(check),
+                check.query
+            );
+        }
+    }
+
+    print_query_patterns_markdown(result);
+    print_existing_indexes_markdown(result);
+}
+
+fn print_query_patterns_markdown(result: &IndexRecommendationResult) {
+    if result.accesses.is_empty() {
+        return;
+    }
+    report!("## Query Patterns\n");
+    report!("| Path | Operator | Statements | Calls | Mean Time |");
+    report!("|------|----------|------------|-------|-----------|");
+    for row in result.query_pattern_rows() {
+        report!(
+            "| {} | `{}` | {} | {} | {} |",
+            row.path,
+            row.operator,
+            row.statements,
+            row.calls,
+            row.mean_time
+        );
+    }
+    if result.accesses.len() > MAX_QUERY_PATTERNS {
+        report!(
+            "\n...and {} more",
+            result.accesses.len() - MAX_QUERY_PATTERNS
+        );
+    }
+    report!();
+}
+
+fn print_existing_indexes_markdown(result: &IndexRecommendationResult) {
+    if !result.covered.is_empty() {
+        report!("## Already Indexed\n");
+        report!("| Field Path | Index Type | Existing Index |");
+        report!("|------------|------------|----------------|");
+        for row in result.covered_rows() {
+            report!(
+                "| {} | {} | {} |",
+                row.field_path,
+                row.index_type,
+                row.covered_by
+            );
+        }
+        report!();
+    }
+
+    if !result.overlaps.is_empty() {
+        report!("## Redundant Indexes\n");
+        report!("| Index | Kind | Overlaps | Reason |");
+        report!("|-------|------|----------|--------|");
+        for row in result.overlap_rows() {
+            report!(
+                "| {} | {} | {} | {} |",
+                row.index,
+                row.kind,
+                row.overlaps,
+                markdown_cell(&row.reason)
+            );
+        }
+        report!("\nCheck `idx_scan` in `pg_stat_user_indexes` before dropping any of them.\n");
+    }
+
+    if !result.drops.is_empty() {
+        report!("## Drop Candidates\n");
+        report!("| Index | Scans | Size | Reason |");
+        report!("|-------|-------|------|--------|");
+        for row in result.drop_rows() {
+            report!(
+                "| {} | {} | {} | {} |",
+                row.index,
+                row.scans,
+                row.size,
+                markdown_cell(&row.reason)
+            );
+        }
+        report!("\n{}, on this server only.\n", result.scans_since());
+        report!("```sql");
+        for drop in &result.drops {
+            report!("{}", drop.sql);
+        }
+        report!("```\n");
+    }
+}
+
+fn print_index_recommendations_table(result: &IndexRecommendationResult) {
+    report!(
+        "\n{} {}.{}\n",
+        "Index Recommendations for".bold().green(),
+        result.table,
+        result.column
+    );
+
+    if result.recommendations.is_empty() {
+        if result.covered.is_empty() {
+            report!("{}", "No index recommendations.".yellow());
+            report!("\n{}", "This could mean:".bold());
+            report!("  • All fields have low occurrence counts (< 100 samples)");
+            report!("  • All fields are objects or arrays (not directly indexable)");
+            report!("  • Field densities are in the middle range without strong indexing needs\n");
+        } else {
+            report!(
+                "{}",
+                "Existing indexes already serve every recommendation.".green()
+            );
+        }
+        print_query_patterns_table(result);
+        print_existing_indexes_table(result);
+        return;
+    }
+
+    report!("{}", "Summary:".bold());
+    report!("  Total recommendations: {}", result.recommendations.len());
+    report!(
+        "  Estimated size: {} ({} rows)",
+        format_bytes(result.total_size()),
+        result.row_count
+    );
+    let (checked, used) = result.planner_counts();
+    if checked > 0 {
+        report!("  Used by the planner: {} of {} checked", used, checked);
+    }
+    let high_count = result
+        .recommendations
+        .iter()
+        .filter(|r| r.priority == pgdrift_core::index::IndexPriority::High)
+        .count();
+    let medium_count = result
+        .recommendations
+        .iter()
+        .filter(|r| r.priority == pgdrift_core::index::IndexPriority::Medium)
+        .count();
+    let low_count = result
+        .recommendations
+        .iter()
+        .filter(|r| r.priority == pgdrift_core::index::IndexPriority::Low)
+        .count();
+
+    if high_count > 0 {
+        report!("  High priority: {}", high_count.to_string().red());
     }
     if medium_count > 0 {
-        println!("  Medium priority: {}", medium_count.to_string().yellow());
+        report!("  Medium priority: {}", medium_count.to_string().yellow());
     }
     if low_count > 0 {
-        println!("  Low priority: {}", low_count.to_string().cyan());
+        report!("  Low priority: {}", low_count.to_string().cyan());
     }
 
     // Print recommendations table
-    println!("\n{}", "Recommendations:".bold());
+    report!("\n{}", "Recommendations:".bold());
     let rows: Vec<IndexRow> = result.recommendations.iter().map(|r| r.into()).collect();
     let mut table = Table::new(rows);
     table.with(Style::rounded());
-    println!("{}", table);
+    report!("{}", table);
 
     // Print SQL commands
-    println!("\n{}", "SQL Commands:".bold().green());
+    report!("\n{}", "SQL Commands:".bold().green());
     for (i, rec) in result.recommendations.iter().enumerate() {
-        println!(
+        report!(
             "\n{} - {}",
             (i + 1).to_string().bold(),
             rec.field_path.bold()
         );
-        println!("{}", rec.sql.dimmed());
-        println!("{} {}", "Benefit:".bold(), rec.estimated_benefit);
+        report!("{}", rec.sql.dimmed());
+        report!("{} {}", "Benefit:".bold(), rec.estimated_benefit);
+        report!(
+            "{} {}",
+            "Estimated size:".bold(),
+            index_size_label(rec.estimated_size_bytes)
+        );
+        if let Some(check) = &rec.planner_check {
+            let label = planner_label(check);
+            report!(
+                "{} {} for {}",
+                "Planner:".bold(),
+                if check.used {
+                    label.green()
+                } else {
+                    label.yellow()
+                },
+                check.query.dimmed()
+            );
+        }
+    }
+
+    print_query_patterns_table(result);
+    print_existing_indexes_table(result);
+    report!();
+}
+
+fn print_query_patterns_table(result: &IndexRecommendationResult) {
+    if result.accesses.is_empty() {
+        return;
+    }
+    report!("\n{}", "Query Patterns:".bold());
+    let mut table = Table::new(result.query_pattern_rows());
+    table.with(Style::rounded());
+    report!("{}", table);
+    if result.accesses.len() > MAX_QUERY_PATTERNS {
+        report!("...and {} more", result.accesses.len() - MAX_QUERY_PATTERNS);
+    }
+}
+
+fn print_existing_indexes_table(result: &IndexRecommendationResult) {
+    if !result.covered.is_empty() {
+        report!("\n{}", "Already Indexed:".bold());
+        let mut table = Table::new(result.covered_rows());
+        table.with(Style::rounded());
+        report!("{}", table);
+    }
+
+    if !result.overlaps.is_empty() {
+        report!("\n{}", "Redundant Indexes:".bold().yellow());
+        let mut table = Table::new(result.overlap_rows());
+        table.with(Style::rounded());
+        report!("{}", table);
+        report!(
+            "{}",
+            "Check idx_scan in pg_stat_user_indexes before dropping any of them.".dimmed()
+        );
+    }
+
+    if !result.drops.is_empty() {
+        report!("\n{}", "Drop Candidates:".bold().yellow());
+        let mut table = Table::new(result.drop_rows());
+        table.with(Style::rounded());
+        report!("{}", table);
+        report!(
+            "{}",
+            format!("{}, on this server only.", result.scans_since()).dimmed()
+        );
+        for drop in &result.drops {
+            report!("{}", drop.sql.dimmed());
+        }
+    }
+}
+
+pub struct IndexAllResult {
+    pub total_columns: usize,
+    pub column_results: Vec<IndexRecommendationResult>,
+    pub failures: Vec<ColumnFailure>,
+}
+
+#[derive(Tabled)]
+pub struct IndexAllRow {
+    #[tabled(rename = "#")]
+    pub number: usize,
+    #[tabled(rename = "Column")]
+    pub column: String,
+    #[tabled(rename = "Field Path")]
+    pub field_path: String,
+    #[tabled(rename = "Index Type")]
+    pub index_type: String,
+    #[tabled(rename = "Priority")]
+    pub priority: String,
+    #[tabled(rename = "Est. Size")]
+    pub size: String,
+}
+
+impl IndexAllResult {
+    /// Recommendations across all columns, most urgent first
+    ///
+    /// Within a priority, recommendations for bigger tables come first, and
+    /// each column keeps its own order.
+    pub fn ranked(&self) -> Vec<(&IndexRecommendationResult, &IndexRecommendation)> {
+        let mut ranked: Vec<_> = self
+            .column_results
+            .iter()
+            .flat_map(|result| result.recommendations.iter().map(move |rec| (result, rec)))
+            .collect();
+        ranked.sort_by_key(|(result, rec)| (rec.priority.rank(), Reverse(result.row_count)));
+        ranked
+    }
+
+    pub fn rows(&self) -> Vec<IndexAllRow> {
+        self.ranked()
+            .into_iter()
+            .enumerate()
+            .map(|(i, (result, rec))| IndexAllRow {
+                number: i + 1,
+                column: column_label(result),
+                field_path: rec.field_path.clone(),
+                index_type: rec.index_type.to_name().to_string(),
+                priority: rec.priority.to_name().to_string(),
+                size: index_size_label(rec.estimated_size_bytes),
+            })
+            .collect()
+    }
+
+    /// Drop candidates across all columns, with schema-qualified index names
+    pub fn drop_rows(&self) -> Vec<DropRecommendationRow> {
+        self.column_results
+            .iter()
+            .flat_map(|result| {
+                result
+                    .drop_rows()
+                    .into_iter()
+                    .map(|row| DropRecommendationRow {
+                        index: format!("{}.{}", result.schema, row.index),
+                        ..row
+                    })
+            })
+            .collect()
+    }
+
+    fn drops(&self) -> impl Iterator<Item = &DropRecommendation> {
+        self.column_results.iter().flat_map(|r| &r.drops)
+    }
+
+    fn total_size(&self) -> i64 {
+        self.column_results.iter().map(|r| r.total_size()).sum()
+    }
+
+    fn priority_count(&self, priority: IndexPriority) -> usize {
+        self.column_results
+            .iter()
+            .flat_map(|r| &r.recommendations)
+            .filter(|r| r.priority == priority)
+            .count()
+    }
+
+    /// Where the scan counts of the drop candidates start from
+    fn scans_since(&self) -> String {
+        self.column_results
+            .iter()
+            .find(|r| r.stats_reset.is_some())
+            .or(self.column_results.first())
+            .map(|r| r.scans_since())
+            .unwrap_or_default()
+    }
+}
+
+fn column_label(result: &IndexRecommendationResult) -> String {
+    format!("{}.{}.{}", result.schema, result.table, result.column)
+}
+
+pub fn print_index_all_summary(result: &IndexAllResult, format: &OutputFormat) {
+    match format {
+        OutputFormat::Table => print_index_all_table(result),
+        OutputFormat::Json => print_index_all_json(result),
+        OutputFormat::Markdown | OutputFormat::Github | OutputFormat::Html => {
+            print_index_all_markdown(result)
+        }
+    }
+}
+
+fn print_index_all_json(result: &IndexAllResult) {
+    let reports: Vec<IndexReport> = result.column_results.iter().map(index_report).collect();
+    let summaries: Vec<IndexSummary> = reports.iter().map(|r| r.summary.clone()).collect();
+    print_json(IndexAllReport {
+        total_columns: result.total_columns,
+        summary: IndexAllSummary::new(&summaries, result.failures.len()),
+        recommendations: result
+            .ranked()
+            .into_iter()
+            .map(|(r, rec)| ColumnRecommendation {
+                schema: r.schema.clone(),
+                table: r.table.clone(),
+                column: r.column.clone(),
+                recommendation: rec.into(),
+            })
+            .collect(),
+        drop_recommendations: result
+            .column_results
+            .iter()
+            .flat_map(|r| {
+                r.drops.iter().map(move |drop| ColumnDrop {
+                    schema: r.schema.clone(),
+                    table: r.table.clone(),
+                    column: r.column.clone(),
+                    drop: drop.into(),
+                })
+            })
+            .collect(),
+        columns: reports
+            .into_iter()
+            .map(|r| IndexedColumn {
+                schema: r.schema,
+                table: r.table,
+                column: r.column,
+                row_count: r.row_count,
+                recommendations: r.recommendations.len(),
+                covered: r.covered.len(),
+                redundant_indexes: r.redundant_indexes.len(),
+            })
+            .collect(),
+        failed_columns: result.failures.clone(),
+    });
+}
+
+fn print_index_all_markdown(result: &IndexAllResult) {
+    report!("# Index Recommendations\n");
+    report!("**Total columns analyzed:** {}\n", result.total_columns);
+
+    let ranked = result.ranked();
+    if ranked.is_empty() {
+        report!("**No index recommendations.**\n");
+    } else {
+        report!(
+            "Found {} recommendation(s), about {} in total ({} high, {} medium, {} low priority)\n",
+            ranked.len(),
+            format_bytes(result.total_size()),
+            result.priority_count(IndexPriority::High),
+            result.priority_count(IndexPriority::Medium),
+            result.priority_count(IndexPriority::Low)
+        );
+
+        report!("| # | Column | Field Path | Index Type | Priority | Est. Size |");
+        report!("|---|--------|------------|------------|----------|-----------|");
+        for row in result.rows() {
+            report!(
+                "| {} | {} | {} | {} | {} | {} |",
+                row.number,
+                row.column,
+                row.field_path,
+                row.index_type,
+                row.priority,
+                row.size
+            );
+        }
+
+        report!("\n## SQL Commands\n");
+        for (i, (r, rec)) in ranked.iter().enumerate() {
+            report!("### {} - {}: {}\n", i + 1, column_label(r), rec.field_path);
+            report!("```sql\n{}\n```\n", rec.sql);
+            report!("**Estimated Benefit:** {}\n", rec.estimated_benefit);
+        }
+    }
+
+    let drops = result.drop_rows();
+    if !drops.is_empty() {
+        report!("## Drop Candidates\n");
+        report!("| Index | Scans | Size | Reason |");
+        report!("|-------|-------|------|--------|");
+        for row in drops {
+            report!(
+                "| {} | {} | {} | {} |",
+                row.index,
+                row.scans,
+                row.size,
+                markdown_cell(&row.reason)
+            );
+        }
+        report!("\n{}, on this server only.\n", result.scans_since());
+        report!("```sql");
+        for drop in result.drops() {
+            report!("{}", drop.sql);
+        }
+        report!("```\n");
+    }
+
+    if !result.failures.is_empty() {
+        report!("## Failed Columns\n");
+        for failure in &result.failures {
+            report!(
+                "- {}.{}.{}: {}",
+                failure.schema,
+                failure.table,
+                failure.column,
+                failure.error
+            );
+        }
+        report!();
+    }
+}
+
+fn print_index_all_table(result: &IndexAllResult) {
+    report!(
+        "\n{} {} JSONB columns\n",
+        "Index Recommendations for".bold().green(),
+        result.total_columns
+    );
+
+    let ranked = result.ranked();
+    if ranked.is_empty() {
+        report!("{}", "No index recommendations.".yellow());
+    } else {
+        report!("{}", "Summary:".bold());
+        report!("  Total recommendations: {}", ranked.len());
+        report!("  Estimated size: {}", format_bytes(result.total_size()));
+        let high_count = result.priority_count(IndexPriority::High);
+        let medium_count = result.priority_count(IndexPriority::Medium);
+        let low_count = result.priority_count(IndexPriority::Low);
+        if high_count > 0 {
+            report!("  High priority: {}", high_count.to_string().red());
+        }
+        if medium_count > 0 {
+            report!("  Medium priority: {}", medium_count.to_string().yellow());
+        }
+        if low_count > 0 {
+            report!("  Low priority: {}", low_count.to_string().cyan());
+        }
+
+        report!("\n{}", "Recommendations:".bold());
+        let mut table = Table::new(result.rows());
+        table.with(Style::rounded());
+        report!("{}", table);
+
+        report!("\n{}", "SQL Commands:".bold().green());
+        for (i, (r, rec)) in ranked.iter().enumerate() {
+            report!(
+                "\n{} - {}: {}",
+                (i + 1).to_string().bold(),
+                column_label(r),
+                rec.field_path.bold()
+            );
+            report!("{}", rec.sql.dimmed());
+            report!("{} {}", "Benefit:".bold(), rec.estimated_benefit);
+        }
+    }
+
+    let drops = result.drop_rows();
+    if !drops.is_empty() {
+        report!("\n{}", "Drop Candidates:".bold().yellow());
+        let mut table = Table::new(drops);
+        table.with(Style::rounded());
+        report!("{}", table);
+        report!(
+            "{}",
+            format!("{}, on this server only.", result.scans_since()).dimmed()
+        );
+        for drop in result.drops() {
+            report!("{}", drop.sql.dimmed());
+        }
+    }
+
+    if !result.failures.is_empty() {
+        report!("\n{}", "Failed Columns:".bold().red());
+        for failure in &result.failures {
+            report!(
+                "  {}.{}.{}: {}",
+                failure.schema,
+                failure.table,
+                failure.column,
+                failure.error
+            );
+        }
+    }
+    report!();
+}
+
+/// What `index --apply` did with one recommendation
+#[derive(Debug, Clone, PartialEq)]
+pub enum IndexBuild {
+    /// Built, with its size on disk and how long the build took
+    Created { size_bytes: i64, seconds: f64 },
+    /// Not built, for the given reason
+    Skipped(String),
+    /// The build failed with the given error
+    Failed(String),
+}
+
+/// Print the outcome of `index --apply`, one line per selected index
+pub fn print_index_builds(builds: &[(String, IndexBuild)]) {
+    let created = builds
+        .iter()
+        .filter(|(_, build)| matches!(build, IndexBuild::Created { .. }))
+        .count();
+    report!(
+        "\n{}",
+        format!("Created {} of {} indexes:", created, builds.len()).bold()
+    );
+    for (name, build) in builds {
+        match build {
+            IndexBuild::Created {
+                size_bytes,
+                seconds,
+            } => report!(
+                "  {} {} ({}, {:.1}s)",
+                "created".green(),
+                name,
+                format_bytes(*size_bytes),
+                seconds
+            ),
+            IndexBuild::Skipped(reason) => {
+                report!("  {} {}: {}", "skipped".yellow(), name, reason)
+            }
+            IndexBuild::Failed(error) => report!("  {} {}: {}", "failed".red(), name, error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_report_to_file() {
+        let path = std::env::temp_dir().join(format!("pgdrift-report-{}.txt", std::process::id()));
+
+        report_to_file(&path).unwrap();
+        report!("{} {}", "Drift".red().bold(), 3);
+        report!();
+        close_report_file().unwrap();
+
+        let report = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(report, "Drift 3\n\n");
+        // Closing again is a no-op
+        assert!(close_report_file().is_ok());
+    }
+
+    #[test]
+    fn test_render_template() {
+        let result = ScanAllResult {
+            total_columns: 2,
+            column_results: vec![ColumnScanResult {
+                schema: "public".to_string(),
+                table: "users".to_string(),
+                column: "metadata".to_string(),
+                samples_analyzed: 100,
+                drift_issues: vec![],
+                suppressed_issues: 0,
+                health_score: Some(96),
+            }],
+            expiring_suppressions: vec![],
+        };
+        let template = "v{{ schema_version }}: {{ total_columns }} columns\n\
+            {% for c in columns %}- {{ c.table }}.{{ c.column }} <{{ c.health_score }}>\n{% endfor %}";
+
+        assert_eq!(
+            render_template(template, scan_all_report(&result)).unwrap(),
+            "v1: 2 columns\n- users.metadata <96>\n"
+        );
+        assert!(render_template("{{ missing.field }}", scan_all_report(&result)).is_err());
+    }
+
+    #[test]
+    fn test_markdown_cell() {
+        assert_eq!(markdown_cell("a | b\nc"), "a \\| b c");
+    }
+
+    #[test]
+    fn test_strip_ansi() {
+        assert_eq!(strip_ansi("\x1b[1;31mcritical\x1b[0m: 2"), "critical: 2");
+        assert_eq!(strip_ansi("plain ✓"), "plain ✓");
+    }
+
+    fn column(table: &str) -> JsonbColumn {
+        JsonbColumn {
+            schema: "public".to_string(),
+            table: table.to_string(),
+            column: "data".to_string(),
+            data_type: pgdrift_db::ColumnType::Jsonb,
+            relation_kind: RelationKind::Table,
+            estimated_rows: None,
+            partitions: None,
+            partition_of: None,
+            dead_tuples: None,
+            last_analyze: None,
+            last_autoanalyze: None,
+            stats_age_secs: None,
+            indexes: vec![],
+            storage: None,
+        }
+    }
+
+    #[test]
+    fn test_relation_label() {
+        assert_eq!(relation_label(&column("users")), "users");
+
+        let mut matview = column("users_mv");
+        matview.relation_kind = RelationKind::MaterializedView;
+        assert_eq!(relation_label(&matview), "users_mv (matview)");
+
+        let mut parent = column("events");
+        parent.partitions = Some(12);
+        assert_eq!(relation_label(&parent), "events (12 partitions)");
+
+        let mut child = column("events_2024_01");
+        child.partition_of = Some("public.events".to_string());
+        assert_eq!(
+            relation_label(&child),
+            "events_2024_01 (partition of public.events)"
+        );
+    }
+
+    #[test]
+    fn test_stats_age_label() {
+        let mut col = column("users");
+        assert_eq!(stats_age_label(&col), "N/A");
+
+        col.estimated_rows = Some(900);
+        assert_eq!(stats_age_label(&col), "never");
+
+        col.stats_age_secs = Some(3 * 86_400 + 10);
+        col.dead_tuples = Some(100);
+        assert_eq!(stats_age_label(&col), "3d (10% dead)");
+
+        col.dead_tuples = Some(0);
+        col.stats_age_secs = Some(90);
+        assert_eq!(stats_age_label(&col), "1m");
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 kB");
+        assert_eq!(format_bytes(5 * 1024 * 1024), "5.0 MB");
+    }
+
+    #[test]
+    fn test_planner_label() {
+        let mut check = PlannerCheck {
+            query: "SELECT 1".to_string(),
+            used: true,
+            cost_without: 2041.0,
+            cost_with: 12.3,
+            ..Default::default()
+        };
+        assert_eq!(planner_label(&check), "used (cost 2041.0 -> 12.3)");
+
+        check.time_without_ms = Some(85.2);
+        check.time_with_ms = Some(0.412);
+        assert_eq!(
+            planner_label(&check),
+            "used (cost 2041.0 -> 12.3, 85.20 ms -> 0.41 ms)"
+        );
+
+        check.used = false;
+        assert_eq!(planner_label(&check), "not used (cost 2041.0, 85.20 ms)");
+    }
+
+    #[test]
+    fn test_score_label() {
+        assert_eq!(score_label(Some(87)), "87");
+        assert_eq!(score_label(None), "-");
+    }
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(format_number(42.0), "42");
+        assert_eq!(format_number(-3.0), "-3");
+        assert_eq!(format_number(19.999), "20.00");
+        assert_eq!(format_number(0.126), "0.13");
+    }
+
+    #[test]
+    fn test_numeric_rows() {
+        let mut amount = FieldStats::new("order.amount".to_string(), 2);
+        amount.record(&json!(1250));
+        amount.record(&json!(12.5));
+        amount.finalize(2);
+
+        let mut name = FieldStats::new("order.name".to_string(), 2);
+        name.record(&json!("widget"));
+        name.finalize(1);
+
+        let rows = numeric_rows(&[name, amount]);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].path, "order.amount");
+        assert_eq!(rows[0].min, "12.50");
+        assert_eq!(rows[0].max, "1250");
+    }
+
+    #[test]
+    fn test_field_stats_rows() {
+        let mut id = FieldStats::new("id".to_string(), 1);
+        id.record(&json!(7));
+        id.record(&json!("7"));
+        id.finalize(2);
+
+        let mut city = FieldStats::new("address.city".to_string(), 2);
+        city.record(&json!("a very long city name that goes on and on"));
+        city.finalize(4);
+
+        let field_stats = [id, city];
+        let rows = field_stats_rows(
+            &field_stats,
+            &[],
+            &FieldStatsView::default(),
+            LISTED_EXAMPLES,
+        );
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].path, "address.city");
+        assert_eq!(rows[0].density, "25.0%");
+        assert_eq!(rows[0].types, "string");
+        assert_eq!(rows[0].examples, "a very long city name that go…");
+        assert_eq!(rows[1].types, "number 50.0%, string 50.0%");
+
+        let by_density = FieldStatsView {
+            sort: FieldSort::Density,
+            ..Default::default()
+        };
+        let rows = field_stats_rows(&field_stats, &[], &by_density, LISTED_EXAMPLES);
+        assert_eq!(rows[0].path, "address.city");
+
+        let by_depth = FieldStatsView {
+            sort: FieldSort::Depth,
+            ..Default::default()
+        };
+        assert_eq!(
+            field_stats_rows(&field_stats, &[], &by_depth, LISTED_EXAMPLES)[0].path,
+            "address.city"
+        );
+
+        let filtered = FieldStatsView {
+            paths: Some("i*".to_string()),
+            ..Default::default()
+        };
+        let rows = field_stats_rows(&field_stats, &[], &filtered, 0);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].path, "id");
+        assert_eq!(rows[0].examples, "");
+
+        let issues = [DriftIssue::MissingKey {
+            path: "id".to_string(),
+            density: 0.5,
+            expected_occurrences: 4,
+            actual_occurrences: 2,
+            margin: 0.0,
+        }];
+        let by_issues = FieldStatsView {
+            sort: FieldSort::Issues,
+            ..Default::default()
+        };
+        let rows = field_stats_rows(&field_stats, &issues, &by_issues, LISTED_EXAMPLES);
+        assert_eq!(rows[0].path, "id");
+        assert_eq!(rows[0].issues, 1);
+        assert_eq!(rows[1].issues, 0);
+    }
+
+    #[test]
+    fn test_page() {
+        let page = Page {
+            offset: 2,
+            limit: Some(2),
+        };
+        assert_eq!(page.apply(vec![1, 2, 3, 4, 5]), [3, 4]);
+        assert_eq!(page.note(5, "paths").unwrap(), "Showing 3-4 of 5 paths");
+        assert_eq!(page.note(3, "paths").unwrap(), "Showing 3-3 of 3 paths");
+        assert_eq!(
+            page.note(1, "paths").unwrap(),
+            "No paths past the first 2 of 1"
+        );
+        assert_eq!(Page::default().note(5, "paths"), None);
+    }
+
+    #[test]
+    fn test_table_groups() {
+        let column = |schema: &str, table: &str, column: &str, health_score| ColumnScanResult {
+            schema: schema.to_string(),
+            table: table.to_string(),
+            column: column.to_string(),
+            samples_analyzed: 100,
+            drift_issues: vec![],
+            suppressed_issues: 0,
+            health_score,
+        };
+        let result = ScanAllResult {
+            total_columns: 4,
+            column_results: vec![
+                column("public", "users", "settings", Some(90)),
+                column("audit", "events", "payload", None),
+                column("public", "users", "metadata", Some(70)),
+                column("public", "orders", "items", Some(100)),
+            ],
+            expiring_suppressions: vec![],
+        };
+        let groups = result.table_groups();
+
+        let names: Vec<String> = groups.iter().map(|g| g.name()).collect();
+        assert_eq!(names, ["audit.events", "public.orders", "public.users"]);
+        let users = &groups[2];
+        assert_eq!(users.columns[0].column, "metadata");
+        assert_eq!(users.lowest_score(), Some(70));
+        assert_eq!(groups[0].lowest_score(), None);
+        assert!(groups[0].columns[0].has_details());
+        assert!(!users.columns[0].has_details());
+
+        assert_eq!(users.anchor(), "public-users");
+        assert_eq!(
+            anchor(&["Sales", "Order Items", "data"]),
+            "sales-order-items-data"
+        );
+    }
+
+    #[test]
+    fn test_sorted_columns() {
+        let column = |table: &str, health_score, drift_issues| ColumnScanResult {
+            schema: "public".to_string(),
+            table: table.to_string(),
+            column: "metadata".to_string(),
+            samples_analyzed: 100,
+            drift_issues,
+            suppressed_issues: 0,
+            health_score,
+        };
+        let critical = DriftIssue::MissingKey {
+            path: "email".to_string(),
+            density: 0.85,
+            expected_occurrences: 100,
+            actual_occurrences: 85,
+            margin: 0.0,
+        };
+        let result = ScanAllResult {
+            total_columns: 3,
+            column_results: vec![
+                column("users", Some(90), vec![]),
+                column("orders", Some(60), vec![critical]),
+                column("events", None, vec![]),
+            ],
+            expiring_suppressions: vec![],
+        };
+        let tables = |view: &ScanAllView| {
+            result
+                .sorted_columns(view)
+                .into_iter()
+                .map(|c| c.table.as_str())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            tables(&ScanAllView::default()),
+            ["users", "orders", "events"]
+        );
+        let sorted = |sort| ScanAllView {
+            sort: Some(sort),
+            ..Default::default()
+        };
+        assert_eq!(
+            tables(&sorted(ColumnSort::Path)),
+            ["events", "orders", "users"]
+        );
+        assert_eq!(
+            tables(&sorted(ColumnSort::Issues)),
+            ["orders", "events", "users"]
+        );
+        assert_eq!(
+            tables(&sorted(ColumnSort::Score)),
+            ["orders", "users", "events"]
+        );
+    }
+
+    #[test]
+    fn test_tree_lines() {
+        let mut user = FieldStats::new("user".to_string(), 1);
+        user.record(&json!({"age": 30}));
+        user.finalize(2);
+        let mut age = FieldStats::new("user.age".to_string(), 2);
+        age.record(&json!(30));
+        age.finalize(2);
+        let mut tags = FieldStats::new("tags[]".to_string(), 1);
+        tags.record(&json!("new"));
+        tags.finalize(2);
+
+        assert_eq!(
+            tree_lines("metadata", &[user, age, tags]),
+            [
+                "metadata",
+                "├── tags",
+                "│   └── []  50.0% string",
+                "└── user  50.0% object",
+                "    └── age  50.0% number",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_rows() {
+        let mut created = FieldStats::new("created".to_string(), 1);
+        created.record(&json!("2024-01-15"));
+        created.record(&json!("2024-01-16"));
+        created.record(&json!("01/17/2024"));
+        created.finalize(3);
+
+        let mut note = FieldStats::new("note".to_string(), 1);
+        note.record(&json!("call back later"));
+        note.finalize(1);
+
+        let rows = format_rows(&[note, created]);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].path, "created");
+        assert_eq!(rows[0].format, "date");
+        assert_eq!(rows[0].consistency, "66.7%");
+    }
+
+    #[test]
+    fn test_array_rows() {
+        let mut addresses = FieldStats::new("addresses".to_string(), 1);
+        addresses.record(&json!([{"city": "Paris"}]));
+        addresses.finalize(1);
+
+        let mut tags = FieldStats::new("tags".to_string(), 1);
+        tags.record(&json!([]));
+        tags.record(&json!(vec![1; 1500]));
+        tags.finalize(2);
+
+        let rows = array_rows(&[tags, addresses]);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].path, "addresses");
+        assert!(rows[0].note.contains("flattening"));
+        assert_eq!(rows[1].empty, "50.0%");
+        assert_eq!(rows[1].max, 1500);
+        assert!(rows[1].note.contains("index risk"));
+    }
+
+    #[test]
+    fn test_top_values_rows() {
+        let mut status = FieldStats::new("status".to_string(), 1);
+        for value in ["active", "active", "active", ""] {
+            status.record(&json!(value));
+        }
+        status.finalize(4);
+
+        let rows = top_values_rows(&[status]);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].values, "active 75.0%, \"\" 25.0%");
+    }
+
+    #[test]
+    fn test_relationship_rows() {
+        use pgdrift_core::cooccurrence::{Condition, Implication, PathGroup};
+
+        let cooccurrence = Cooccurrence {
+            groups: vec![PathGroup {
+                paths: vec!["card_brand".to_string(), "card_last4".to_string()],
+                occurrences: 60,
+            }],
+            exclusive: vec![],
+            implications: vec![Implication {
+                antecedent: "refund".to_string(),
+                consequent: Condition::Equals {
+                    path: "status".to_string(),
+                    value: json!("refunded"),
+                },
+                support: 20,
+            }],
+        };
+
+        let rows = relationship_rows(&cooccurrence);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].relationship, "always together");
+        assert_eq!(rows[0].fields, "card_brand, card_last4");
+        assert_eq!(rows[1].fields, "refund present ⇒ status == \"refunded\"");
+        assert_eq!(rows[1].samples, 20);
     }
+    #[test]
+    fn test_group_rows() {
+        fn group(value: &str, health_score: u8, issues: Vec<DriftIssue>) -> SegmentResult {
+            SegmentResult {
+                value: value.to_string(),
+                result: AnalysisResult {
+                    table: "accounts".to_string(),
+                    column: "settings".to_string(),
+                    samples_analyzed: 100,
+                    field_stats: vec![],
+                    drift_issues: issues,
+                    suppressed_issues: 0,
+                    expiring_suppressions: vec![],
+                    truncation: Truncation::default(),
+                    cooccurrence: None,
+                    health_score,
+                    conformance: None,
+                    remediations: None,
+                    denormalization: None,
+                    unused_fields: None,
+                },
+            }
+        }
+        let issue = |path: &str, severity| {
+            DriftIssue::ConstantField {
+                path: path.to_string(),
+                value: json!(true),
+                occurrences: 100,
+            }
+            .reclassify(severity)
+        };
 
-    println!();
+        let groups = vec![
+            group("8", 100, vec![issue("beta", Severity::Info)]),
+            group(
+                "42",
+                61,
+                vec![
+                    issue("sso", Severity::Warning),
+                    issue("billing.plan", Severity::Critical),
+                    issue("seats", Severity::Warning),
+                    issue("seats", Severity::Critical),
+                    issue("theme", Severity::Warning),
+                ],
+            ),
+        ];
+        let rows: Vec<GroupRow> = groups_by_score(&groups)
+            .into_iter()
+            .map(GroupRow::from)
+            .collect();
+        assert_eq!(rows[0].group, "42");
+        assert_eq!((rows[0].critical, rows[0].warning), (2, 3));
+        assert_eq!(rows[0].paths, "billing.plan, seats, sso, 1 more");
+        assert_eq!(rows[1].group, "8");
+        assert_eq!(rows[1].paths, "");
+    }
 }
@@ -0,0 +1,217 @@
+use anyhow::{Context, Result};
+use pgdrift_core::analyzer::AnalyzerState;
+use pgdrift_db::{SampleFilter, Watermark};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Watermarks from previous incremental runs, persisted as a JSON file
+///
+/// Keys are fully qualified column names (`schema.table.column`). Each holds
+/// the highest watermark column value seen by the last run, and the
+/// statistics of every row analyzed up to it, which the next run merges its
+/// new rows into.
+#[derive(Debug, Clone)]
+pub struct WatermarkStore {
+    path: PathBuf,
+    values: BTreeMap<String, Entry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+enum Entry {
+    Column {
+        value: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        analyzer: Option<AnalyzerState>,
+    },
+    /// Files of earlier versions only kept the watermark
+    Value(String),
+}
+
+impl WatermarkStore {
+    /// Load the store from disk - a missing file is an empty store
+    pub fn load(path: &Path) -> Result<Self> {
+        let values = if path.exists() {
+            let contents = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read watermark file {}", path.display()))?;
+            serde_json::from_str(&contents)
+                .with_context(|| format!("Invalid watermark file {}", path.display()))?
+        } else {
+            BTreeMap::new()
+        };
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            values,
+        })
+    }
+
+    /// Get the stored watermark for a column
+    pub fn get(&self, key: &str) -> Option<&str> {
+        match self.values.get(key)? {
+            Entry::Column { value, .. } | Entry::Value(value) => Some(value),
+        }
+    }
+
+    /// Get the statistics of the rows previous runs analyzed, up to the
+    /// stored watermark
+    pub fn analyzer(&self, key: &str) -> Option<&AnalyzerState> {
+        match self.values.get(key)? {
+            Entry::Column { analyzer, .. } => analyzer.as_ref(),
+            Entry::Value(_) => None,
+        }
+    }
+
+    /// Record a new watermark for a column, with the statistics of every row
+    /// analyzed up to it
+    pub fn set(&mut self, key: &str, value: String, analyzer: AnalyzerState) {
+        self.values.insert(
+            key.to_string(),
+            Entry::Column {
+                value,
+                analyzer: Some(analyzer),
+            },
+        );
+    }
+
+    /// Write the store back to disk
+    pub fn save(&self) -> Result<()> {
+        let contents = serde_json::to_string_pretty(&self.values)?;
+        std::fs::write(&self.path, contents)
+            .with_context(|| format!("Failed to write watermark file {}", self.path.display()))
+    }
+}
+
+/// Build the sampling filter for an incremental run
+///
+/// An explicit `since_value` wins over the stored watermark. Without either
+/// (e.g. the first run) the whole table is sampled.
+pub fn incremental_filter(
+    since_column: Option<&str>,
+    since_value: Option<&str>,
+    store: Option<&WatermarkStore>,
+    key: &str,
+) -> SampleFilter {
+    let since = since_column.and_then(|column| {
+        since_value
+            .or_else(|| store.and_then(|s| s.get(key)))
+            .map(|value| Watermark {
+                column: column.to_string(),
+                value: value.to_string(),
+            })
+    });
+
+    SampleFilter {
+        since,
+        ..Default::default()
+    }
+}
+
+/// Bound an incremental run at the high-water mark read when it started
+///
+/// The next run starts from the same value, so no row is sampled by both
+/// or by neither.
+pub fn upper_bound(since_column: Option<&str>, next_watermark: Option<&str>) -> Option<Watermark> {
+    Some(Watermark {
+        column: since_column?.to_string(),
+        value: next_watermark?.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pgdrift_core::analyzer::JsonAnalyzer;
+    use serde_json::json;
+
+    fn state(documents: &[serde_json::Value]) -> AnalyzerState {
+        let mut analyzer = JsonAnalyzer::new();
+        for document in documents {
+            analyzer.analyze(document);
+        }
+        analyzer.state()
+    }
+
+    #[test]
+    fn test_incremental_filter_prefers_explicit_value() {
+        let mut store = WatermarkStore::load(Path::new("/nonexistent/watermarks.json")).unwrap();
+        store.set("public.users.metadata", "10".to_string(), state(&[]));
+
+        let filter = incremental_filter(
+            Some("id"),
+            Some("20"),
+            Some(&store),
+            "public.users.metadata",
+        );
+        assert_eq!(filter.since.unwrap().value, "20");
+
+        let filter = incremental_filter(Some("id"), None, Some(&store), "public.users.metadata");
+        assert_eq!(filter.since.unwrap().value, "10");
+    }
+
+    #[test]
+    fn test_incremental_filter_first_run_samples_everything() {
+        let filter = incremental_filter(Some("id"), None, None, "public.users.metadata");
+        assert!(filter.is_empty());
+
+        let filter = incremental_filter(None, Some("20"), None, "public.users.metadata");
+        assert!(filter.is_empty());
+    }
+
+    #[test]
+    fn test_upper_bound() {
+        let bound = upper_bound(Some("id"), Some("99")).unwrap();
+        assert_eq!(bound.column, "id");
+        assert_eq!(bound.value, "99");
+
+        // An empty table has no high-water mark to stop at
+        assert!(upper_bound(Some("id"), None).is_none());
+        assert!(upper_bound(None, Some("99")).is_none());
+    }
+
+    #[test]
+    fn test_missing_file_is_empty() {
+        let store = WatermarkStore::load(Path::new("/nonexistent/pgdrift-watermarks.json"))
+            .expect("missing file should load");
+        assert_eq!(store.get("public.users.metadata"), None);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let path = std::env::temp_dir().join(format!(
+            "pgdrift-watermark-test-{}.json",
+            std::process::id()
+        ));
+
+        let mut store = WatermarkStore::load(&path).unwrap();
+        store.set(
+            "public.users.metadata",
+            "5000".to_string(),
+            state(&[json!({"plan": "pro"}), json!({"plan": "free"})]),
+        );
+        store.save().unwrap();
+
+        let reloaded = WatermarkStore::load(&path).unwrap();
+        assert_eq!(reloaded.get("public.users.metadata"), Some("5000"));
+        let analyzer = reloaded.analyzer("public.users.metadata").unwrap();
+        assert_eq!(analyzer.total_samples(), 2);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_reads_watermark_only_files() {
+        let path = std::env::temp_dir().join(format!(
+            "pgdrift-watermark-legacy-{}.json",
+            std::process::id()
+        ));
+        std::fs::write(&path, r#"{"public.users.metadata": "42"}"#).unwrap();
+
+        let store = WatermarkStore::load(&path).unwrap();
+        assert_eq!(store.get("public.users.metadata"), Some("42"));
+        assert!(store.analyzer("public.users.metadata").is_none());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
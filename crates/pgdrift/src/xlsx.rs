@@ -0,0 +1,356 @@
+use crate::output::{AnalysisResult, truncate_chars, type_mix, value_label};
+use anyhow::{Context, Result};
+use pgdrift_core::drift::DriftIssue;
+use pgdrift_core::index::{IndexConfig, IndexRecommendation, recommend_index};
+use pgdrift_core::report::IssueCounts;
+use pgdrift_core::stats::FieldStats;
+use rust_xlsxwriter::{Format, Workbook, XlsxError};
+use std::path::Path;
+
+/// Characters Excel holds in a cell; longer text is cut
+const MAX_CELL_CHARS: usize = 32_767;
+
+/// An analyzed column, as exported to a workbook
+#[derive(Debug, Clone, Default)]
+pub struct ColumnExport {
+    pub schema: String,
+    pub table: String,
+    pub column: String,
+    pub samples_analyzed: u64,
+    /// Drift health from 0 to 100, `None` if the column couldn't be analyzed
+    pub health_score: Option<u8>,
+    /// Issues left out because their path matches an `ignore` pattern
+    pub suppressed_issues: usize,
+    pub field_stats: Vec<FieldStats>,
+    pub drift_issues: Vec<DriftIssue>,
+    /// Indexes the field stats call for, see `index_recommendations`
+    pub recommendations: Vec<IndexRecommendation>,
+}
+
+impl ColumnExport {
+    /// Export of a column analyzed by the analyze command
+    pub fn from_analysis(schema: &str, result: &AnalysisResult) -> Self {
+        Self {
+            schema: schema.to_string(),
+            table: result.table.clone(),
+            column: result.column.clone(),
+            samples_analyzed: result.samples_analyzed,
+            health_score: Some(result.health_score),
+            suppressed_issues: result.suppressed_issues,
+            field_stats: result.field_stats.clone(),
+            drift_issues: result.drift_issues.clone(),
+            recommendations: index_recommendations(
+                schema,
+                &result.table,
+                &result.column,
+                &result.field_stats,
+            ),
+        }
+    }
+}
+
+/// Indexes recommended from the field stats alone, with the default settings
+///
+/// Unlike the index command, this doesn't look at existing indexes, the
+/// recorded workload or the table size.
+pub fn index_recommendations(
+    schema: &str,
+    table: &str,
+    column: &str,
+    field_stats: &[FieldStats],
+) -> Vec<IndexRecommendation> {
+    let config = IndexConfig {
+        schema: Some(schema.to_string()),
+        ..Default::default()
+    };
+    recommend_index(table, column, field_stats, &config)
+}
+
+/// A cell of a sheet
+#[derive(Debug, Clone, PartialEq)]
+enum Cell {
+    Text(String),
+    Number(f64),
+    /// Share from 0 to 1, shown as a percentage
+    Percent(f64),
+    Empty,
+}
+
+impl From<&str> for Cell {
+    fn from(text: &str) -> Self {
+        Cell::Text(text.to_string())
+    }
+}
+
+impl From<String> for Cell {
+    fn from(text: String) -> Self {
+        Cell::Text(text)
+    }
+}
+
+struct Sheet {
+    name: &'static str,
+    headers: &'static [&'static str],
+    rows: Vec<Vec<Cell>>,
+}
+
+/// Write analyzed columns to an Excel workbook at `path`, one sheet each for
+/// the columns, their field stats, their issues and index recommendations
+///
+/// Every sheet starts with the schema, table and column of its rows, so a
+/// filter on them narrows all of it down to one column.
+pub fn write_workbook(path: &Path, columns: &[ColumnExport]) -> Result<()> {
+    workbook(columns)
+        .and_then(|mut workbook| workbook.save(path))
+        .with_context(|| format!("Failed to write workbook {}", path.display()))
+}
+
+fn workbook(columns: &[ColumnExport]) -> Result<Workbook, XlsxError> {
+    let header = Format::new().set_bold();
+    let percent = Format::new().set_num_format("0.0%");
+    let mut workbook = Workbook::new();
+    for sheet in sheets(columns) {
+        let worksheet = workbook.add_worksheet();
+        worksheet.set_name(sheet.name)?;
+        for (col, title) in sheet.headers.iter().enumerate() {
+            worksheet.write_string_with_format(0, col as u16, *title, &header)?;
+        }
+        for (i, cells) in sheet.rows.iter().enumerate() {
+            let row = i as u32 + 1;
+            for (col, cell) in cells.iter().enumerate() {
+                let col = col as u16;
+                match cell {
+                    Cell::Text(text) => {
+                        worksheet.write_string(row, col, truncate_chars(text, MAX_CELL_CHARS))?
+                    }
+                    Cell::Number(n) => worksheet.write_number(row, col, *n)?,
+                    Cell::Percent(share) => {
+                        worksheet.write_number_with_format(row, col, *share, &percent)?
+                    }
+                    Cell::Empty => continue,
+                };
+            }
+        }
+        worksheet.set_freeze_panes(1, 0)?;
+        worksheet.autofilter(
+            0,
+            0,
+            sheet.rows.len() as u32,
+            sheet.headers.len() as u16 - 1,
+        )?;
+        worksheet.autofit();
+    }
+    Ok(workbook)
+}
+
+fn sheets(columns: &[ColumnExport]) -> [Sheet; 4] {
+    let location = |c: &ColumnExport| -> Vec<Cell> {
+        vec![
+            c.schema.as_str().into(),
+            c.table.as_str().into(),
+            c.column.as_str().into(),
+        ]
+    };
+
+    let columns_sheet = Sheet {
+        name: "Columns",
+        headers: &[
+            "Schema",
+            "Table",
+            "Column",
+            "Samples",
+            "Health Score",
+            "Critical",
+            "Warning",
+            "Info",
+            "Suppressed",
+        ],
+        rows: columns
+            .iter()
+            .map(|c| {
+                let counts = IssueCounts::from_issues(&c.drift_issues);
+                let mut row = location(c);
+                row.extend([
+                    Cell::Number(c.samples_analyzed as f64),
+                    c.health_score
+                        .map_or(Cell::Empty, |score| Cell::Number(score as f64)),
+                    Cell::Number(counts.critical as f64),
+                    Cell::Number(counts.warning as f64),
+                    Cell::Number(counts.info as f64),
+                    Cell::Number(c.suppressed_issues as f64),
+                ]);
+                row
+            })
+            .collect(),
+    };
+
+    let field_stats_sheet = Sheet {
+        name: "Field Stats",
+        headers: &[
+            "Schema",
+            "Table",
+            "Column",
+            "Path",
+            "Depth",
+            "Density",
+            "Occurrences",
+            "Nulls",
+            "Types",
+            "Distinct Values",
+            "Issues",
+            "Examples",
+        ],
+        rows: columns
+            .iter()
+            .flat_map(|c| {
+                c.field_stats.iter().map(move |fs| {
+                    let issues = c
+                        .drift_issues
+                        .iter()
+                        .filter(|issue| issue.path() == fs.path)
+                        .count();
+                    let examples: Vec<String> = fs.examples.iter().map(value_label).collect();
+                    let mut row = location(c);
+                    row.extend([
+                        fs.path.as_str().into(),
+                        Cell::Number(fs.depth as f64),
+                        Cell::Percent(fs.density),
+                        Cell::Number(fs.occurrences as f64),
+                        Cell::Number(fs.null_count as f64),
+                        type_mix(fs).into(),
+                        Cell::Number(fs.cardinality as f64),
+                        Cell::Number(issues as f64),
+                        examples.join(", ").into(),
+                    ]);
+                    row
+                })
+            })
+            .collect(),
+    };
+
+    let issues_sheet = Sheet {
+        name: "Issues",
+        headers: &["Schema", "Table", "Column", "Path", "Severity", "Issue"],
+        rows: columns
+            .iter()
+            .flat_map(|c| {
+                c.drift_issues.iter().map(move |issue| {
+                    let mut row = location(c);
+                    row.extend([
+                        issue.path().into(),
+                        format!("{:?}", issue.severity()).into(),
+                        issue.description().into(),
+                    ]);
+                    row
+                })
+            })
+            .collect(),
+    };
+
+    let recommendations_sheet = Sheet {
+        name: "Index Recommendations",
+        headers: &[
+            "Schema",
+            "Table",
+            "Column",
+            "Field Path",
+            "Index Type",
+            "Priority",
+            "Reason",
+            "Benefit",
+            "SQL",
+        ],
+        rows: columns
+            .iter()
+            .flat_map(|c| {
+                c.recommendations.iter().map(move |rec| {
+                    let mut row = location(c);
+                    row.extend([
+                        rec.field_path.as_str().into(),
+                        rec.index_type.to_name().into(),
+                        rec.priority.to_name().into(),
+                        rec.reason.as_str().into(),
+                        rec.estimated_benefit.as_str().into(),
+                        rec.sql.as_str().into(),
+                    ]);
+                    row
+                })
+            })
+            .collect(),
+    };
+
+    [
+        columns_sheet,
+        field_stats_sheet,
+        issues_sheet,
+        recommendations_sheet,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn column() -> ColumnExport {
+        let mut email = FieldStats::new("email".to_string(), 1);
+        for value in [json!("a@example.com"), json!(null)] {
+            email.record(&value);
+        }
+        email.finalize(4);
+        ColumnExport {
+            schema: "public".to_string(),
+            table: "users".to_string(),
+            column: "metadata".to_string(),
+            samples_analyzed: 4,
+            health_score: Some(80),
+            suppressed_issues: 1,
+            drift_issues: vec![DriftIssue::MissingKey {
+                path: "email".to_string(),
+                density: 0.5,
+                expected_occurrences: 4,
+                actual_occurrences: 2,
+                margin: 0.0,
+            }],
+            recommendations: index_recommendations("public", "users", "metadata", &[]),
+            field_stats: vec![email],
+        }
+    }
+
+    #[test]
+    fn test_sheets() {
+        let [columns, field_stats, issues, recommendations] = sheets(&[column()]);
+
+        assert_eq!(columns.rows.len(), 1);
+        assert_eq!(columns.rows[0].len(), columns.headers.len());
+        assert_eq!(columns.rows[0][4], Cell::Number(80.0));
+        assert_eq!(columns.rows[0][5], Cell::Number(1.0));
+
+        assert_eq!(field_stats.rows.len(), 1);
+        let row = &field_stats.rows[0];
+        assert_eq!(row.len(), field_stats.headers.len());
+        assert_eq!(row[3], Cell::Text("email".to_string()));
+        assert_eq!(row[5], Cell::Percent(0.5));
+        assert_eq!(row[10], Cell::Number(1.0));
+        assert_eq!(row[11], Cell::Text("a@example.com, null".to_string()));
+
+        assert_eq!(issues.rows.len(), 1);
+        assert_eq!(issues.rows[0][4], Cell::Text("Critical".to_string()));
+        assert!(recommendations.rows.is_empty());
+    }
+
+    #[test]
+    fn test_workbook() {
+        let unanalyzed = ColumnExport {
+            schema: "public".to_string(),
+            table: "orders".to_string(),
+            column: "payload".to_string(),
+            ..Default::default()
+        };
+        let buffer = workbook(&[column(), unanalyzed])
+            .and_then(|mut workbook| workbook.save_to_buffer())
+            .unwrap();
+        // An xlsx file is a zip archive
+        assert!(buffer.starts_with(b"PK"));
+    }
+}
@@ -1,7 +1,11 @@
+use pgdrift::baseline::Baseline;
+use pgdrift::checkpoint::Checkpoint;
 use pgdrift::commands::analyze;
 use pgdrift::output::OutputFormat;
+use pgdrift_core::analyzer::JsonAnalyzer;
 use pgdrift_db::fixtures;
 use pgdrift_db::test_utils::TestDb;
+use pgdrift_db::{Sampler, SamplingStrategy};
 
 /// Test end-to-end analyze command with consistent schema
 #[tokio::test]
@@ -888,3 +892,388 @@ async fn test_analyze_inconsistent_nesting_levels() {
 
     test_db.cleanup().await.expect("Failed to cleanup");
 }
+
+/// Test analyze scoped to documents matching a containment filter
+#[tokio::test]
+async fn test_analyze_with_containment_filter() {
+    let test_db = TestDb::new().await.expect("Failed to create test database");
+
+    fixtures::create_products_schema_evolution(&test_db.pool)
+        .await
+        .expect("Failed to create fixture");
+
+    let options = analyze::AnalyzeOptions {
+        filter: Some(serde_json::json!({"category": "electronics"})),
+        ..Default::default()
+    };
+
+    let result = analyze::run_with_options(
+        test_db.database_url(),
+        "products",
+        "data",
+        1000,
+        OutputFormat::Json,
+        &options,
+    )
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Analyze with filter failed: {:?}",
+        result.err()
+    );
+
+    // A filter that matches nothing should report no samples
+    let options = analyze::AnalyzeOptions {
+        filter: Some(serde_json::json!({"category": "does-not-exist"})),
+        ..Default::default()
+    };
+
+    let result = analyze::run_with_options(
+        test_db.database_url(),
+        "products",
+        "data",
+        1000,
+        OutputFormat::Json,
+        &options,
+    )
+    .await;
+
+    assert!(result.is_err(), "Expected no samples for unmatched filter");
+
+    test_db.cleanup().await.expect("Failed to cleanup");
+}
+
+/// Test analyze split into segments by a discriminator field
+#[tokio::test]
+async fn test_analyze_segment_by() {
+    let test_db = TestDb::new().await.expect("Failed to create test database");
+
+    fixtures::create_products_schema_evolution(&test_db.pool)
+        .await
+        .expect("Failed to create fixture");
+
+    let options = analyze::AnalyzeOptions {
+        segment_by: Some("category".to_string()),
+        ..Default::default()
+    };
+
+    for format in [OutputFormat::Json, OutputFormat::Table] {
+        let result = analyze::run_with_options(
+            test_db.database_url(),
+            "products",
+            "data",
+            1000,
+            format,
+            &options,
+        )
+        .await;
+
+        assert!(
+            result.is_ok(),
+            "Analyze with segments failed: {:?}",
+            result.err()
+        );
+    }
+
+    test_db.cleanup().await.expect("Failed to cleanup");
+}
+
+/// Test analyze with co-occurrence analysis
+#[tokio::test]
+async fn test_analyze_cooccurrence() {
+    let test_db = TestDb::new().await.expect("Failed to create test database");
+
+    fixtures::create_products_schema_evolution(&test_db.pool)
+        .await
+        .expect("Failed to create fixture");
+
+    let options = analyze::AnalyzeOptions {
+        cooccurrence: true,
+        ..Default::default()
+    };
+
+    let result = analyze::run_with_options(
+        test_db.database_url(),
+        "products",
+        "data",
+        1000,
+        OutputFormat::Table,
+        &options,
+    )
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Analyze with co-occurrence failed: {:?}",
+        result.err()
+    );
+
+    test_db.cleanup().await.expect("Failed to cleanup");
+}
+
+/// Test analyze with path counting pushed down to Postgres
+#[tokio::test]
+async fn test_analyze_sql_engine() {
+    let test_db = TestDb::new().await.expect("Failed to create test database");
+
+    fixtures::create_products_schema_evolution(&test_db.pool)
+        .await
+        .expect("Failed to create fixture");
+
+    let options = analyze::AnalyzeOptions {
+        engine: analyze::Engine::Sql,
+        ..Default::default()
+    };
+
+    let result = analyze::run_with_options(
+        test_db.database_url(),
+        "products",
+        "data",
+        1000,
+        OutputFormat::Json,
+        &options,
+    )
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Analyze with sql engine failed: {:?}",
+        result.err()
+    );
+
+    test_db.cleanup().await.expect("Failed to cleanup");
+}
+
+/// Test analyze with a checkpoint file, which is removed after a completed run
+#[tokio::test]
+async fn test_analyze_checkpoint() {
+    let test_db = TestDb::new().await.expect("Failed to create test database");
+
+    fixtures::create_products_schema_evolution(&test_db.pool)
+        .await
+        .expect("Failed to create fixture");
+
+    let checkpoint = std::env::temp_dir().join(format!(
+        "pgdrift-analyze-checkpoint-{}.json",
+        std::process::id()
+    ));
+    let options = analyze::AnalyzeOptions {
+        checkpoint: Some(checkpoint.clone()),
+        ..Default::default()
+    };
+
+    let result = analyze::run_with_options(
+        test_db.database_url(),
+        "products",
+        "data",
+        10000,
+        OutputFormat::Json,
+        &options,
+    )
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Analyze with checkpoint failed: {:?}",
+        result.err()
+    );
+    assert!(!checkpoint.exists());
+
+    test_db.cleanup().await.expect("Failed to cleanup");
+}
+
+/// Test that a run resumed from a checkpoint reports the same as an
+/// uninterrupted one
+#[tokio::test]
+async fn test_analyze_checkpoint_resume() {
+    let test_db = TestDb::new().await.expect("Failed to create test database");
+
+    fixtures::create_products_schema_evolution(&test_db.pool)
+        .await
+        .expect("Failed to create fixture");
+
+    let temp_file = |name: &str| {
+        std::env::temp_dir().join(format!(
+            "pgdrift-analyze-resume-{}-{}.json",
+            name,
+            std::process::id()
+        ))
+    };
+    let checkpoint = temp_file("checkpoint");
+    let resumed = temp_file("resumed");
+    let uninterrupted = temp_file("uninterrupted");
+
+    // A run interrupted after its first batch
+    let mut analyzer = JsonAnalyzer::new();
+    let mut last_key = None;
+    Sampler::with_strategy(SamplingStrategy::Full)
+        .show_progress(false)
+        .resume_after(&test_db.pool, "public", "products", None)
+        .await
+        .expect("Failed to find the primary key")
+        .sample_batches(
+            &test_db.pool,
+            "public",
+            "products",
+            "data",
+            1500,
+            |batch, key| {
+                batch.iter().for_each(|document| analyzer.analyze(document));
+                last_key = key.map(String::from);
+                false
+            },
+        )
+        .await
+        .expect("Failed to sample");
+    Checkpoint::new(&checkpoint, "public.products.data")
+        .save(&analyzer.state(), last_key.as_deref())
+        .expect("Failed to save checkpoint");
+
+    for (checkpoint, baseline) in [(Some(checkpoint.clone()), &resumed), (None, &uninterrupted)] {
+        let options = analyze::AnalyzeOptions {
+            checkpoint,
+            save_baseline: Some(baseline.clone()),
+            ..Default::default()
+        };
+        let result = analyze::run_with_options(
+            test_db.database_url(),
+            "products",
+            "data",
+            10000,
+            OutputFormat::Json,
+            &options,
+        )
+        .await;
+        assert!(result.is_ok(), "Analyze failed: {:?}", result.err());
+    }
+
+    let resumed_report = Baseline::load(&resumed).expect("Failed to load baseline");
+    let uninterrupted_report = Baseline::load(&uninterrupted).expect("Failed to load baseline");
+    assert_eq!(resumed_report.samples_analyzed, 5000);
+    assert_eq!(
+        resumed_report.samples_analyzed,
+        uninterrupted_report.samples_analyzed
+    );
+    assert_eq!(resumed_report.fields, uninterrupted_report.fields);
+    assert!(!checkpoint.exists());
+
+    std::fs::remove_file(&resumed).unwrap();
+    std::fs::remove_file(&uninterrupted).unwrap();
+    test_db.cleanup().await.expect("Failed to cleanup");
+}
+
+/// Test that sampled runs can't be checkpointed, having no order to resume in
+#[tokio::test]
+async fn test_analyze_checkpoint_rejects_sampling() {
+    let test_db = TestDb::new().await.expect("Failed to create test database");
+
+    fixtures::create_products_schema_evolution(&test_db.pool)
+        .await
+        .expect("Failed to create fixture");
+
+    let options = analyze::AnalyzeOptions {
+        checkpoint: Some(std::env::temp_dir().join(format!(
+            "pgdrift-analyze-sampled-checkpoint-{}.json",
+            std::process::id()
+        ))),
+        ..Default::default()
+    };
+    let result = analyze::run_with_options(
+        test_db.database_url(),
+        "products",
+        "data",
+        1000,
+        OutputFormat::Json,
+        &options,
+    )
+    .await;
+
+    let err = result.expect_err("Sampled run should not be checkpointed");
+    assert!(err.to_string().contains("only resumes full scans"));
+
+    test_db.cleanup().await.expect("Failed to cleanup");
+}
+
+/// Test that an incremental run reports the rows of previous runs too
+#[tokio::test]
+async fn test_analyze_incremental_merges_previous_runs() {
+    let test_db = TestDb::new().await.expect("Failed to create test database");
+
+    fixtures::create_users_consistent(&test_db.pool)
+        .await
+        .expect("Failed to create fixture");
+
+    let watermarks = std::env::temp_dir().join(format!(
+        "pgdrift-analyze-watermarks-{}.json",
+        std::process::id()
+    ));
+    let baseline = std::env::temp_dir().join(format!(
+        "pgdrift-analyze-incremental-baseline-{}.json",
+        std::process::id()
+    ));
+    let options = analyze::AnalyzeOptions {
+        since_column: Some("id".to_string()),
+        watermark_file: Some(watermarks.clone()),
+        save_baseline: Some(baseline.clone()),
+        ..Default::default()
+    };
+
+    let first = analyze::run_with_options(
+        test_db.database_url(),
+        "users",
+        "metadata",
+        1000,
+        OutputFormat::Json,
+        &options,
+    )
+    .await;
+    assert!(first.is_ok(), "First run failed: {:?}", first.err());
+
+    // New rows no longer have the preferences of the first run
+    for i in 0..10 {
+        sqlx::query("INSERT INTO users (metadata) VALUES ($1)")
+            .bind(serde_json::json!({ "email": format!("new{}@example.com", i) }))
+            .execute(&test_db.pool)
+            .await
+            .expect("Failed to insert rows");
+    }
+
+    let second = analyze::run_with_options(
+        test_db.database_url(),
+        "users",
+        "metadata",
+        1000,
+        OutputFormat::Json,
+        &options,
+    )
+    .await;
+    assert!(second.is_ok(), "Second run failed: {:?}", second.err());
+
+    let report = Baseline::load(&baseline).expect("Failed to load baseline");
+    assert!(report.samples_analyzed > 10);
+    assert!(
+        report
+            .fields
+            .iter()
+            .any(|field| field.path == "preferences.theme")
+    );
+
+    // A run without new rows still reports the column
+    std::fs::remove_file(&baseline).unwrap();
+    let third = analyze::run_with_options(
+        test_db.database_url(),
+        "users",
+        "metadata",
+        1000,
+        OutputFormat::Json,
+        &options,
+    )
+    .await;
+    assert!(third.is_ok(), "Third run failed: {:?}", third.err());
+    assert!(baseline.exists());
+
+    std::fs::remove_file(&baseline).unwrap();
+    std::fs::remove_file(&watermarks).unwrap();
+    test_db.cleanup().await.expect("Failed to cleanup");
+}
@@ -0,0 +1,103 @@
+use pgdrift::commands::index_all;
+use pgdrift::output::OutputFormat;
+use pgdrift_db::fixtures;
+use pgdrift_db::test_utils::TestDb;
+
+#[tokio::test]
+async fn test_index_all_multiple_columns() {
+    let test_db = TestDb::new().await.expect("Failed to create test database");
+
+    fixtures::create_users_consistent(&test_db.pool)
+        .await
+        .expect("Failed to create users fixture");
+
+    fixtures::create_products_schema_evolution(&test_db.pool)
+        .await
+        .expect("Failed to create products fixture");
+
+    let result = index_all::run(test_db.database_url(), 1000, OutputFormat::Json).await;
+
+    assert!(
+        result.is_ok(),
+        "Index all command failed: {:?}",
+        result.err()
+    );
+
+    test_db.cleanup().await.expect("Failed to cleanup");
+}
+
+#[tokio::test]
+async fn test_index_all_emit_migration() {
+    let test_db = TestDb::new().await.expect("Failed to create test database");
+
+    fixtures::create_users_consistent(&test_db.pool)
+        .await
+        .expect("Failed to create users fixture");
+
+    fixtures::create_products_schema_evolution(&test_db.pool)
+        .await
+        .expect("Failed to create products fixture");
+
+    let migration = std::env::temp_dir().join(format!(
+        "pgdrift-index-all-migration-{}.sql",
+        std::process::id()
+    ));
+    let options = index_all::IndexAllOptions {
+        emit_migration: Some(migration.clone()),
+        ..Default::default()
+    };
+
+    let result =
+        index_all::run_with_options(test_db.database_url(), 1000, OutputFormat::Json, &options)
+            .await;
+
+    assert!(
+        result.is_ok(),
+        "Index all with a migration failed: {:?}",
+        result.err()
+    );
+
+    let script = std::fs::read_to_string(&migration).expect("Migration not written");
+    std::fs::remove_file(&migration).ok();
+    assert!(script.contains("SET lock_timeout = '5s';"));
+    assert!(script.contains("SET search_path TO \"public\", public;"));
+    assert!(script.contains("CREATE INDEX CONCURRENTLY"), "{}", script);
+    assert!(
+        script.contains("--   DROP INDEX CONCURRENTLY IF EXISTS"),
+        "{}",
+        script
+    );
+    assert!(script.contains("ON \"public\".\"users\""), "{}", script);
+    assert!(script.contains("ON \"public\".\"products\""), "{}", script);
+
+    test_db.cleanup().await.expect("Failed to cleanup");
+}
+
+#[tokio::test]
+async fn test_index_all_excludes_columns() {
+    let test_db = TestDb::new().await.expect("Failed to create test database");
+
+    fixtures::create_users_consistent(&test_db.pool)
+        .await
+        .expect("Failed to create users fixture");
+
+    let migration = std::env::temp_dir().join(format!(
+        "pgdrift-index-all-excluded-{}.sql",
+        std::process::id()
+    ));
+    let options = index_all::IndexAllOptions {
+        exclude: vec!["public.users.*".to_string()],
+        emit_migration: Some(migration.clone()),
+        ..Default::default()
+    };
+
+    let result =
+        index_all::run_with_options(test_db.database_url(), 1000, OutputFormat::Json, &options)
+            .await;
+
+    assert!(result.is_ok(), "Index all failed: {:?}", result.err());
+    // Nothing left to analyze, so no migration either
+    assert!(!migration.exists());
+
+    test_db.cleanup().await.expect("Failed to cleanup");
+}
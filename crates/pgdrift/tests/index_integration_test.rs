@@ -482,3 +482,97 @@ async fn test_index_sql_injection_in_column_name() {
 
     test_db.cleanup().await.expect("Failed to cleanup");
 }
+
+#[tokio::test]
+async fn test_index_emit_migration() {
+    let test_db = TestDb::new().await.expect("Failed to create test database");
+
+    fixtures::create_users_consistent(&test_db.pool)
+        .await
+        .expect("Failed to create fixture");
+
+    let migration = std::env::temp_dir().join(format!(
+        "pgdrift-index-migration-{}.sql",
+        std::process::id()
+    ));
+    let options = index::IndexOptions {
+        emit_migration: Some(migration.clone()),
+        lock_timeout: Some("10s".to_string()),
+        statement_timeout: Some("30min".to_string()),
+        ..Default::default()
+    };
+
+    let result = index::run_with_options(
+        test_db.database_url(),
+        "users",
+        "metadata",
+        1000,
+        OutputFormat::Json,
+        &options,
+    )
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Index with a migration failed: {:?}",
+        result.err()
+    );
+
+    let script = std::fs::read_to_string(&migration).expect("Migration not written");
+    std::fs::remove_file(&migration).ok();
+    assert!(script.contains("SET lock_timeout = '10s';\nSET statement_timeout = '30min';"));
+    assert!(script.contains("CREATE INDEX CONCURRENTLY"), "{}", script);
+    assert!(!script.contains("CREATE INDEX idx"), "{}", script);
+
+    // Nothing is built
+    let indexes: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM pg_indexes WHERE tablename = 'users' AND indexname LIKE 'idx_%'",
+    )
+    .fetch_one(&test_db.pool)
+    .await
+    .expect("Failed to count indexes");
+    assert_eq!(indexes, 0);
+
+    test_db.cleanup().await.expect("Failed to cleanup");
+}
+
+#[tokio::test]
+async fn test_index_explain_analyze() {
+    let test_db = TestDb::new().await.expect("Failed to create test database");
+
+    fixtures::create_users_consistent(&test_db.pool)
+        .await
+        .expect("Failed to create fixture");
+
+    let options = index::IndexOptions {
+        explain_analyze: true,
+        ..Default::default()
+    };
+
+    let result = index::run_with_options(
+        test_db.database_url(),
+        "users",
+        "metadata",
+        1000,
+        OutputFormat::Json,
+        &options,
+    )
+    .await;
+
+    assert!(
+        result.is_ok(),
+        "Index with EXPLAIN ANALYZE failed: {:?}",
+        result.err()
+    );
+
+    // The indexes timed are rolled back
+    let indexes: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM pg_indexes WHERE tablename = 'users' AND indexname LIKE 'idx_%'",
+    )
+    .fetch_one(&test_db.pool)
+    .await
+    .expect("Failed to count indexes");
+    assert_eq!(indexes, 0);
+
+    test_db.cleanup().await.expect("Failed to cleanup");
+}
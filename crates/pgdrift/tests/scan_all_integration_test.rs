@@ -289,3 +289,102 @@ async fn test_scan_all_aggregates_drift_correctly() {
 
     test_db.cleanup().await.expect("Failed to cleanup");
 }
+
+#[tokio::test]
+async fn test_scan_all_metrics_file() {
+    let test_db = TestDb::new().await.expect("Failed to create test database");
+
+    fixtures::create_users_type_inconsistency(&test_db.pool)
+        .await
+        .expect("Failed to create fixture");
+
+    let metrics = std::env::temp_dir().join(format!("pgdrift-metrics-{}.prom", std::process::id()));
+    let options = scan_all::ScanAllOptions {
+        metrics_file: Some(metrics.clone()),
+        ..Default::default()
+    };
+
+    let result =
+        scan_all::run_with_options(test_db.database_url(), 1000, OutputFormat::Json, &options)
+            .await;
+
+    assert!(
+        result.is_ok(),
+        "Scan all with metrics failed: {:?}",
+        result.err()
+    );
+
+    let text = std::fs::read_to_string(&metrics).expect("Metrics not written");
+    std::fs::remove_file(&metrics).ok();
+    assert!(text.contains(
+        "pgdrift_samples_analyzed{schema=\"public\",table=\"users\",column=\"metadata\"}"
+    ));
+    assert!(
+        text.contains("pgdrift_drift_score{schema=\"public\",table=\"users\",column=\"metadata\"}")
+    );
+    assert!(text.contains("severity=\"critical\"}"), "{}", text);
+
+    test_db.cleanup().await.expect("Failed to cleanup");
+}
+
+#[tokio::test]
+async fn test_scan_all_compares_versions() {
+    let test_db = TestDb::new().await.expect("Failed to create test database");
+
+    // v1 documents hold `name`, v2 documents `first_name` and `last_name`
+    sqlx::query("CREATE TABLE people (id SERIAL PRIMARY KEY, doc JSONB)")
+        .execute(&test_db.pool)
+        .await
+        .expect("Failed to create table");
+    sqlx::query(
+        r#"INSERT INTO people (doc)
+           SELECT CASE WHEN i % 5 < 3
+               THEN jsonb_build_object('id', i, 'meta', '{"version": 2}'::jsonb,
+                                       'first_name', 'Ada', 'last_name', 'L')
+               ELSE jsonb_build_object('id', i, 'meta', '{"version": 1}'::jsonb,
+                                       'name', 'Ada L')
+           END
+           FROM generate_series(0, 199) AS i"#,
+    )
+    .execute(&test_db.pool)
+    .await
+    .expect("Failed to insert rows");
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_pgdrift"))
+        .args(["scan-all", "--format", "json"])
+        .args(["--database-url", test_db.database_url()])
+        .output()
+        .expect("Failed to run pgdrift");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    // The version marker lists the fields that differ between versions, as
+    // analyze reports them
+    let report: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("Invalid JSON report");
+    let differences = find_key(&report, "differences").expect("No version marker reported");
+    let paths: Vec<&str> = differences
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|d| d["path"].as_str())
+        .collect();
+    assert!(paths.contains(&"name"), "{}", report);
+    assert!(paths.contains(&"first_name"), "{}", report);
+
+    test_db.cleanup().await.expect("Failed to cleanup");
+}
+
+/// First value stored under `key` anywhere in the document
+fn find_key<'a>(value: &'a serde_json::Value, key: &str) -> Option<&'a serde_json::Value> {
+    match value {
+        serde_json::Value::Object(map) => map
+            .get(key)
+            .or_else(|| map.values().find_map(|v| find_key(v, key))),
+        serde_json::Value::Array(values) => values.iter().find_map(|v| find_key(v, key)),
+        _ => None,
+    }
+}